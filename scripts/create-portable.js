@@ -22,6 +22,9 @@ if (!fs.existsSync(portableDir)) {
 console.log('创建便携式版本...');
 fs.copyFileSync(sourceExe, targetExe);
 
+// 便携标记：程序检测到它后把所有数据写到 data 目录
+fs.writeFileSync(path.join(portableDir, 'portable.flag'), '', 'utf8');
+
 // 创建说明文件
 const readmeContent = `# 智狼 (MindWolf) - 便携版
 
@@ -30,8 +33,8 @@ const readmeContent = `# 智狼 (MindWolf) - 便携版
 ## 使用方法
 
 1. 双击 mindwolf.exe 启动应用
-2. 首次运行会在当前目录下创建 config 目录存储配置
-3. 如需重置配置，删除 config 目录即可
+2. 程序检测到同目录下的 portable.flag 后进入便携模式，配置、数据库、日志和语音缓存全部写在 data 目录，不会在系统目录留下文件
+3. 如需重置，删除 data 目录即可；删除 portable.flag 则改用系统目录
 
 ## 系统要求
 
@@ -41,8 +44,8 @@ const readmeContent = `# 智狼 (MindWolf) - 便携版
 ## 文件说明
 
 - mindwolf.exe: 主程序文件 (约 ${Math.round(fs.statSync(targetExe).size / 1024 / 1024 * 10) / 10} MB)
-- config/: 配置文件目录 (首次运行后自动创建)
-- logs/: 日志文件目录 (首次运行后自动创建)
+- portable.flag: 便携模式标记
+- data/: 配置、数据库、日志和缓存目录 (首次运行后自动创建，旧版的 config/ 会自动迁移)
 
 ## 版本信息
 
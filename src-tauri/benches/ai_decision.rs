@@ -0,0 +1,25 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use mindwolf_lib::benchmark::{BenchmarkScenario, BENCHMARK_SCALES};
+use tokio::runtime::Runtime;
+
+/// AI决策各环节在不同人数规模下的耗时
+fn ai_decision(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("创建tokio运行时失败");
+
+    for players in BENCHMARK_SCALES {
+        let scenario = runtime
+            .block_on(BenchmarkScenario::new(players))
+            .expect("创建基准场景失败");
+        let mut group = c.benchmark_group(format!("{}_players", players));
+
+        group.bench_function("reasoning_update", |b| b.iter(|| black_box(scenario.reasoning_update())));
+        group.bench_function("monte_carlo", |b| b.iter(|| black_box(scenario.monte_carlo())));
+        group.bench_function("prompt_construction", |b| b.iter(|| black_box(scenario.prompt_construction())));
+        group.bench_function("serialization", |b| b.iter(|| black_box(scenario.serialization())));
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, ai_decision);
+criterion_main!(benches);
@@ -1,7 +1,6 @@
 fn main() {
-    // 确保静态链接和嵌入所有资源
-    #[cfg(windows)]
-    {
+    // 确保静态链接和嵌入所有资源；按目标平台判断，交叉编译时也正确
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") {
         // 强制使用静态链接，避免外部依赖
         println!("cargo:rustc-env=RUSTFLAGS=-C target-feature=+crt-static");
         // 嵌入Windows资源文件
@@ -1,11 +1,20 @@
 use crate::types::*;
 use crate::error::{AppError, AppResult};
-use crate::ai::{reasoning::ReasoningEngine, strategy::StrategyEngine, nlp::NLPProcessor};
+use crate::ai::{reasoning::ReasoningEngine, strategy::StrategyEngine, nlp::NLPProcessor, difficulty::DifficultyProfile};
+use crate::ai::consistency::{ConsistencyChecker, Inconsistency};
+use crate::ai::drift::{DriftCause, DriftRecord, PersonalityDrift};
 use crate::llm::LLMManager;
+use crate::claims::{CheckReport, RoleClaim};
+use crate::objection::Objection;
+use crate::embeddings;
+use crate::memory::{ApproxSize, MemoryStats, RingBuffer};
 use crate::types::*;
 use std::sync::Arc;
 use log::{info, warn, debug};
 
+/// 发言记忆保留的条数，更早的发言可以从数据库中的完整记录查到
+const SPEECH_HISTORY_CAPACITY: usize = 200;
+
 /// AI代理 - 智能AI玩家的核心
 pub struct AIAgent {
     pub player_id: String,
@@ -15,16 +24,19 @@ pub struct AIAgent {
     strategy_engine: StrategyEngine,
     nlp_processor: NLPProcessor,
     memory: AIMemory,
+    drift: PersonalityDrift,
 }
 
 /// AI记忆系统
 #[derive(Debug, Clone)]
 pub struct AIMemory {
     pub known_roles: std::collections::HashMap<String, RoleType>,
+    /// 查验得知的阵营，查验只能看出阵营，看不出具体身份
+    pub known_factions: std::collections::HashMap<String, Faction>,
     pub trust_scores: std::collections::HashMap<String, f32>,
     pub suspicion_scores: std::collections::HashMap<String, f32>,
     pub voting_history: Vec<VoteRecord>,
-    pub speech_history: Vec<SpeechMemory>,
+    pub speech_history: RingBuffer<SpeechMemory>,
     pub night_action_history: Vec<crate::types::NightActionMemory>,
 }
 
@@ -38,6 +50,12 @@ pub struct SpeechMemory {
     pub my_reaction: String,
 }
 
+impl ApproxSize for SpeechMemory {
+    fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<Self>() + self.speaker.capacity() + self.content.capacity() + self.my_reaction.capacity()
+    }
+}
+
 
 
 /// AI决策结果
@@ -61,10 +79,11 @@ impl AIMemory {
     fn new() -> Self {
         Self {
             known_roles: std::collections::HashMap::new(),
+            known_factions: std::collections::HashMap::new(),
             trust_scores: std::collections::HashMap::new(),
             suspicion_scores: std::collections::HashMap::new(),
             voting_history: Vec::new(),
-            speech_history: Vec::new(),
+            speech_history: RingBuffer::new(SPEECH_HISTORY_CAPACITY),
             night_action_history: Vec::new(),
         }
     }
@@ -79,8 +98,9 @@ impl AIAgent {
         llm_manager: Option<Arc<LLMManager>>
     ) -> Self {
         let reasoning_engine = ReasoningEngine::new();
-        let strategy_engine = StrategyEngine::new(personality.clone(), &role);
+        let strategy_engine = StrategyEngine::new(player_id.clone(), personality.clone(), &role);
         let nlp_processor = NLPProcessor::new(llm_manager);
+        let drift = PersonalityDrift::new(personality.traits.clone());
         
         Self {
             player_id,
@@ -90,9 +110,32 @@ impl AIAgent {
             strategy_engine,
             nlp_processor,
             memory: AIMemory::new(),
+            drift,
         }
     }
     
+    /// 设置难度配置，同时作用于推理与策略引擎
+    pub fn set_difficulty(&mut self, profile: DifficultyProfile) {
+        self.reasoning_engine.set_profile(profile.clone());
+        self.strategy_engine.set_profile(profile);
+    }
+    
+    /// 局面变化后更新欺骗计划，并对照公开记录校验（非狼人无操作）
+    pub fn refresh_deception_plan(&mut self, game_state: &GameState) {
+        self.strategy_engine.update_strategy(game_state, &self.reasoning_engine);
+        self.strategy_engine.validate_deception(game_state);
+    }
+    
+    /// 欺骗计划给出的发言要点，写进发言提示词
+    pub fn deception_guidance(&self, game_state: &GameState) -> Vec<String> {
+        self.strategy_engine.deception_guidance(game_state)
+    }
+    
+    /// 按本座位的推理和难度在候选人中选出投票目标
+    pub fn choose_vote(&self, candidates: &[String]) -> Option<String> {
+        self.strategy_engine.choose_vote_among(candidates, &self.reasoning_engine)
+    }
+    
     /// 初始化AI代理
     pub fn initialize(&mut self, game_state: &GameState) -> AppResult<()> {
         self.reasoning_engine.initialize(game_state);
@@ -109,6 +152,70 @@ impl AIAgent {
         Ok(())
     }
     
+    /// 根据公开记录和座位的私有信息引导记忆（用于中途接管座位）
+    pub async fn bootstrap_from_record(
+        &mut self,
+        game_state: &GameState,
+        speeches: &[SpeechRecord]
+    ) -> AppResult<()> {
+        self.initialize(game_state)?;
+        
+        // 座位私有信息：狼人只知道狼队中公开给他的队友
+        let me = game_state.players.iter()
+            .chain(game_state.dead_players.iter())
+            .find(|p| p.id == self.player_id)
+            .cloned();
+        if let Some(me) = me {
+            for player in game_state.players.iter().chain(game_state.dead_players.iter()) {
+                if me.knows_teammate(player) {
+                    self.memory.known_roles.insert(player.id.clone(), player.role.role_type.clone());
+                }
+            }
+        }
+        
+        // 回放公开发言
+        for speech in speeches {
+            if speech.speaker == self.player_id {
+                continue;
+            }
+            self.process_player_speech(speech.speaker.clone(), speech.content.clone(), game_state).await?;
+        }
+        
+        // 回放死亡公示
+        for reveal in &game_state.death_reveals {
+            self.process_death_reveal(reveal);
+        }
+        
+        // 回放声明板
+        let board = &game_state.claims_board;
+        for claim in &board.claims {
+            self.process_role_claim(claim, board.is_contested(&claim.role))?;
+        }
+        for report in &board.check_reports {
+            self.process_check_report(report, board.report_credibility(&report.reporter))?;
+        }
+        let mut reporters: Vec<&str> = board.check_reports.iter().map(|r| r.reporter.as_str()).collect();
+        reporters.sort();
+        reporters.dedup();
+        for reporter in reporters {
+            for inconsistency in ConsistencyChecker::check_public_reports(reporter, game_state) {
+                self.process_claim_inconsistency(&inconsistency)?;
+            }
+        }
+        
+        for outcome in &game_state.vote_outcomes {
+            self.process_vote_outcome(outcome);
+        }
+        
+        // 回放本轮已公开的投票
+        for vote in game_state.votes.iter().filter(|v| !v.is_abstention()) {
+            self.process_vote(vote.clone())?;
+        }
+        
+        info!("AI代理 {} 已从公开记录恢复 {} 条发言", self.player_id, speeches.len());
+        Ok(())
+    }
+    
     /// 决定夜晚行动
     pub async fn decide_night_action(&mut self, game_state: &GameState) -> AppResult<Option<NightAction>> {
         debug!("AI {} 正在决定夜晚行动", self.player_id);
@@ -173,6 +280,9 @@ impl AIAgent {
         // 更新推理状态
         self.update_reasoning(game_state)?;
         
+        // 发言前确认欺骗计划与公开记录一致
+        self.strategy_engine.validate_deception(game_state);
+        
         // 生成发言策略
         let strategy = self.strategy_engine.generate_speech_strategy(
             game_state,
@@ -181,8 +291,11 @@ impl AIAgent {
         );
         
         // 使用NLP生成发言
-        let player = self.create_player_snapshot();
-        let context = self.build_speech_context(game_state);
+        let player = self.create_player_snapshot(game_state);
+        let mut context = self.build_speech_context(game_state);
+        if !strategy.key_points.is_empty() {
+            context.push_str(&format!("\n发言要点: {}", strategy.key_points.join("；")));
+        }
         
         let speech = self.nlp_processor.generate_speech(
             &player,
@@ -256,6 +369,86 @@ impl AIAgent {
         Ok(())
     }
     
+    /// 处理放逐投票结果，自己被投会带来压力
+    pub fn process_vote_outcome(&mut self, outcome: &VoteOutcome) {
+        if let Some(tally) = outcome.tally.iter().find(|t| t.target == self.player_id) {
+            self.apply_drift(DriftCause::VotedAgainst { votes: tally.count }, outcome.day);
+        }
+    }
+    
+    /// 处理死亡公示
+    pub fn process_death_reveal(&mut self, reveal: &DeathReveal) {
+        self.reasoning_engine.apply_death_reveal(reveal);
+        
+        if let Some(role) = &reveal.revealed_role {
+            self.memory.known_roles.insert(reveal.player_id.clone(), role.clone());
+        }
+        
+        if reveal.player_id == self.player_id {
+            return;
+        }
+        let faction = reveal.revealed_faction.clone()
+            .or_else(|| reveal.revealed_role.as_ref().map(|role| role.faction()));
+        match faction {
+            Some(Faction::Werewolf) if self.drift.is_accuser(&reveal.player_id) => {
+                self.apply_drift(DriftCause::Vindicated { accuser: reveal.player_id.clone() }, reveal.day);
+            }
+            Some(faction) if faction == self.role.faction => {
+                self.apply_drift(DriftCause::AllyLost { player_id: reveal.player_id.clone() }, reveal.day);
+            }
+            _ => {}
+        }
+    }
+    
+    /// 处理发言中的打断，不评估自己参与的打断
+    pub fn process_objection(&mut self, objection: &Objection) -> AppResult<()> {
+        if objection.speaker == self.player_id || objection.player_id == self.player_id {
+            return Ok(());
+        }
+        self.reasoning_engine.analyze_objection(objection.player_id.clone(), objection.speaker.clone())
+    }
+    
+    /// 记下自己的查验结果，作为确定的阵营信息交给推理
+    pub fn process_seer_check(&mut self, result: &SeerCheckResult) {
+        if result.seer != self.player_id {
+            return;
+        }
+        let faction = if result.is_werewolf { Faction::Werewolf } else { Faction::Villager };
+        if result.is_werewolf {
+            self.memory.known_roles.insert(result.target.clone(), RoleType::Werewolf);
+        }
+        self.memory.known_factions.insert(result.target.clone(), faction.clone());
+        self.reasoning_engine.apply_known_faction(&result.target, &faction);
+    }
+    
+    /// 处理声明板上的身份声明
+    pub fn process_role_claim(&mut self, claim: &RoleClaim, contested: bool) -> AppResult<()> {
+        if claim.player_id == self.player_id {
+            return Ok(());
+        }
+        self.reasoning_engine.analyze_role_claim(claim.player_id.clone(), &claim.role, contested)
+    }
+    
+    /// 处理声明板上的查验报告
+    pub fn process_check_report(&mut self, report: &CheckReport, credibility: f32) -> AppResult<()> {
+        if report.reporter == self.player_id {
+            return Ok(());
+        }
+        if report.target == self.player_id && report.is_werewolf {
+            self.apply_drift(DriftCause::Accused { by: report.reporter.clone() }, report.day);
+        }
+        self.reasoning_engine.analyze_check_report(&report.reporter, report.target.clone(), report.is_werewolf, credibility)
+    }
+    
+    /// 处理他人查验声明中的矛盾
+    pub fn process_claim_inconsistency(&mut self, inconsistency: &Inconsistency) -> AppResult<()> {
+        if inconsistency.reporter == self.player_id {
+            return Ok(());
+        }
+        debug!("AI {} 发现{}的声明矛盾: {}", self.player_id, inconsistency.reporter, inconsistency.description);
+        self.reasoning_engine.analyze_claim_inconsistency(inconsistency)
+    }
+    
     /// 处理夜晚结果
     pub fn process_night_result(&mut self, result: NightResult) -> AppResult<()> {
         debug!("AI {} 处理夜晚结果", self.player_id);
@@ -285,6 +478,16 @@ impl AIAgent {
         Ok(())
     }
     
+    /// 对某名玩家的怀疑度
+    pub fn suspicion_of(&self, player_id: &str) -> f32 {
+        self.reasoning_engine.get_suspicion_score(player_id)
+    }
+    
+    /// AI自己的推理引擎，只读
+    pub fn reasoning(&self) -> &ReasoningEngine {
+        &self.reasoning_engine
+    }
+    
     /// 获取AI的分析报告
     pub fn get_analysis_report(&self) -> AIAnalysisReport {
         let reasoning_report = self.reasoning_engine.get_analysis_report();
@@ -307,7 +510,32 @@ impl AIAgent {
         Ok(())
     }
     
-    fn create_player_snapshot(&self) -> Player {
+    /// 局内性格漂移，漂移后的性格同步给策略引擎
+    fn apply_drift(&mut self, cause: DriftCause, day: u32) {
+        let is_wolf = self.role.faction == Faction::Werewolf;
+        if self.drift.apply(&mut self.personality.traits, cause, is_wolf, day) {
+            debug!("AI {} 性格漂移: {:?}", self.player_id, self.personality.traits);
+            self.strategy_engine.update_personality(self.personality.clone(), &self.role);
+        }
+    }
+    
+    /// 当前（含局内漂移）的性格特征
+    pub fn traits(&self) -> &PersonalityTraits {
+        &self.personality.traits
+    }
+    
+    /// 取出尚未记入复盘的性格漂移
+    pub fn take_drift_records(&mut self) -> Vec<DriftRecord> {
+        self.drift.take_unreported()
+    }
+    
+    fn create_player_snapshot(&self, game_state: &GameState) -> Player {
+        let seat = game_state.players.iter()
+            .chain(game_state.dead_players.iter())
+            .find(|p| p.id == self.player_id)
+            .map(|p| p.seat)
+            .unwrap_or(0);
+        
         Player {
             id: self.player_id.clone(),
             name: format!("AI_{}", self.player_id),
@@ -316,24 +544,56 @@ impl AIAgent {
             is_alive: true,
             is_ai: true,
             personality: Some(self.personality.clone()),
+            persona: None,
+            seat,
         }
     }
     
     fn build_speech_context(&self, game_state: &GameState) -> String {
-        let recent_speeches = self.memory.speech_history.iter()
-            .rev()
-            .take(3)
-            .map(|s| format!("{}: {}", s.speaker, s.content))
-            .collect::<Vec<_>>()
-            .join("\n");
-        
         format!(
-            "当前阶段: {:?}\n最近发言:\n{}",
+            "当前阶段: {:?}\n相关发言:\n{}",
             game_state.phase,
-            recent_speeches
+            self.relevant_speeches(3).join("\n")
         )
     }
     
+    /// 最新一条发言加上与它最相关的历史发言（按发言顺序）
+    fn relevant_speeches(&self, limit: usize) -> Vec<String> {
+        let history = &self.memory.speech_history;
+        let latest = match history.last() {
+            Some(latest) => latest,
+            None => return Vec::new(),
+        };
+        
+        let query = embeddings::local_embedding(&latest.content);
+        let mut ranked: Vec<(usize, f32)> = history.iter()
+            .take(history.len() - 1)
+            .enumerate()
+            .map(|(index, s)| (index, embeddings::cosine(&query, &embeddings::local_embedding(&s.content))))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        
+        let mut picked: Vec<usize> = ranked.into_iter()
+            .take(limit.saturating_sub(1))
+            .map(|(index, _)| index)
+            .collect();
+        picked.push(history.len() - 1);
+        picked.sort();
+        
+        picked.into_iter()
+            .filter_map(|index| history.get(index))
+            .map(|s| format!("{}: {}", s.speaker, s.content))
+            .collect()
+    }
+    
+    /// 代理记忆的内存统计（发言记忆与NLP上下文）
+    pub fn memory_stats(&self) -> Vec<MemoryStats> {
+        vec![
+            self.memory.speech_history.stats("agent_speech_history"),
+            self.nlp_processor.memory_stats(),
+        ]
+    }
+    
     fn update_player_impression(&mut self, player_id: &str, analysis: &crate::ai::nlp::SpeechAnalysis) {
         // 更新信任度
         if let Some(trust) = self.memory.trust_scores.get_mut(player_id) {
@@ -0,0 +1,202 @@
+use crate::types::*;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// 声称的一次查验
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimedCheck {
+    /// 第几晚查验，未知时为None
+    pub night: Option<u32>,
+    pub target: String,
+    pub is_werewolf: bool,
+}
+
+/// 矛盾类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum InconsistencyKind {
+    /// 查验时报告者已经出局
+    ReporterDead,
+    /// 查验时目标已经出局
+    TargetDead,
+    /// 查验的是尚未到来的夜晚
+    FutureNight,
+    /// 查验自己
+    SelfCheck,
+    /// 查验结果与公开身份不符
+    RoleMismatch,
+    /// 对同一目标报出相反结果
+    ConflictingResults,
+    /// 查验次数超过已经过去的夜晚数
+    TooManyChecks,
+}
+
+/// 发现的矛盾
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inconsistency {
+    pub kind: InconsistencyKind,
+    pub reporter: String,
+    pub target: String,
+    pub description: String,
+}
+
+/// 查验声明一致性校验器：狼人发布编造的查验前自检，好人用来识破悍跳
+pub struct ConsistencyChecker;
+
+impl ConsistencyChecker {
+    /// 已经结束的夜晚数
+    pub fn completed_nights(game_state: &GameState) -> u32 {
+        match game_state.phase {
+            GamePhase::Night => game_state.day.saturating_sub(1),
+            GamePhase::Preparation => 0,
+            _ => game_state.day,
+        }
+    }
+
+    /// 校验一组查验声明与公开事实是否矛盾
+    pub fn check(reporter: &str, checks: &[ClaimedCheck], game_state: &GameState) -> Vec<Inconsistency> {
+        let nights = Self::completed_nights(game_state);
+        let death_day = |player_id: &str| game_state.death_reveals.iter()
+            .find(|r| r.player_id == player_id)
+            .map(|r| r.day);
+        let mut found = Vec::new();
+        let mut push = |kind: InconsistencyKind, target: &str, description: String| {
+            found.push(Inconsistency {
+                kind,
+                reporter: reporter.to_string(),
+                target: target.to_string(),
+                description,
+            });
+        };
+
+        let mut results: HashMap<&str, bool> = HashMap::new();
+        for check in checks {
+            if check.target == reporter {
+                push(InconsistencyKind::SelfCheck, &check.target, format!("{}声称查验了自己", reporter));
+            }
+
+            if let Some(night) = check.night {
+                if night > nights {
+                    push(InconsistencyKind::FutureNight, &check.target, format!("第{}晚还没有到来", night));
+                }
+                // 第d天白天出局的玩家无法在第d+1晚及之后行动或被查验
+                if death_day(reporter).is_some_and(|day| day < night) {
+                    push(InconsistencyKind::ReporterDead, &check.target, format!("第{}晚{}已经出局", night, reporter));
+                }
+                if death_day(&check.target).is_some_and(|day| day < night) {
+                    push(InconsistencyKind::TargetDead, &check.target, format!("第{}晚{}已经出局", night, check.target));
+                }
+            }
+
+            let revealed = game_state.death_reveals.iter()
+                .find(|r| r.player_id == check.target)
+                .and_then(|r| r.revealed_faction.as_ref());
+            if let Some(faction) = revealed {
+                if (*faction == Faction::Werewolf) != check.is_werewolf {
+                    push(InconsistencyKind::RoleMismatch, &check.target, format!("{}的公开身份与查验结果不符", check.target));
+                }
+            }
+
+            match results.get(check.target.as_str()) {
+                Some(previous) if *previous != check.is_werewolf => {
+                    push(InconsistencyKind::ConflictingResults, &check.target, format!("对{}报出了相反的查验结果", check.target));
+                }
+                _ => {
+                    results.insert(check.target.as_str(), check.is_werewolf);
+                }
+            }
+        }
+
+        if results.len() as u32 > nights {
+            push(InconsistencyKind::TooManyChecks, "", format!("{}晚里报出了{}次查验", nights, results.len()));
+        }
+
+        found
+    }
+
+    /// 校验声明板上某位玩家公开报出的全部查验
+    pub fn check_public_reports(reporter: &str, game_state: &GameState) -> Vec<Inconsistency> {
+        // 自由发言无法确定查验发生在哪一晚，只校验与夜晚无关的事实
+        let checks: Vec<ClaimedCheck> = game_state.claims_board.check_reports.iter()
+            .filter(|r| r.reporter == reporter)
+            .map(|r| ClaimedCheck {
+                night: None,
+                target: r.target.clone(),
+                is_werewolf: r.is_werewolf,
+            })
+            .collect();
+
+        Self::check(reporter, &checks, game_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::game_engine::GameEngine;
+
+    /// 第2天白天，players[3]在第1天出局，公开身份为狼人
+    fn day_two_state() -> (GameState, Vec<String>) {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        let mut state = engine.get_state().clone();
+        state.day = 2;
+        state.phase = GamePhase::DayDiscussion;
+        let ids: Vec<String> = state.players.iter().map(|p| p.id.clone()).collect();
+        state.death_reveals.push(DeathReveal {
+            player_id: ids[3].clone(),
+            day: 1,
+            revealed_role: Some(RoleType::Werewolf),
+            revealed_faction: Some(Faction::Werewolf),
+        });
+        (state, ids)
+    }
+
+    fn check(night: Option<u32>, target: &str, is_werewolf: bool) -> ClaimedCheck {
+        ClaimedCheck { night, target: target.to_string(), is_werewolf }
+    }
+
+    fn kinds(found: &[Inconsistency]) -> Vec<InconsistencyKind> {
+        found.iter().map(|i| i.kind.clone()).collect()
+    }
+
+    #[test]
+    fn test_consistent_checks_pass() {
+        let (state, ids) = day_two_state();
+        let checks = [check(Some(1), &ids[3], true), check(Some(2), &ids[4], false)];
+        assert!(ConsistencyChecker::check(&ids[0], &checks, &state).is_empty());
+    }
+
+    #[test]
+    fn test_impossible_checks_are_flagged() {
+        let (state, ids) = day_two_state();
+        let checks = [
+            check(Some(3), &ids[4], false),
+            check(Some(2), &ids[3], true),
+            check(None, &ids[0], false),
+        ];
+        let found = kinds(&ConsistencyChecker::check(&ids[0], &checks, &state));
+        assert!(found.contains(&InconsistencyKind::FutureNight));
+        assert!(found.contains(&InconsistencyKind::TargetDead));
+        assert!(found.contains(&InconsistencyKind::SelfCheck));
+        assert!(found.contains(&InconsistencyKind::TooManyChecks));
+    }
+
+    #[test]
+    fn test_results_must_match_reveals_and_each_other() {
+        let (state, ids) = day_two_state();
+        let checks = [check(None, &ids[3], false), check(None, &ids[4], true), check(None, &ids[4], false)];
+        let found = kinds(&ConsistencyChecker::check(&ids[0], &checks, &state));
+        assert_eq!(found, vec![InconsistencyKind::RoleMismatch, InconsistencyKind::ConflictingResults]);
+    }
+
+    #[test]
+    fn test_completed_nights_by_phase() {
+        let (mut state, _) = day_two_state();
+        assert_eq!(ConsistencyChecker::completed_nights(&state), 2);
+        state.phase = GamePhase::Night;
+        assert_eq!(ConsistencyChecker::completed_nights(&state), 1);
+        state.phase = GamePhase::Preparation;
+        assert_eq!(ConsistencyChecker::completed_nights(&state), 0);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::ai::reasoning::ReasoningEngine;
+use crate::ai::consistency::{ClaimedCheck, ConsistencyChecker};
+use crate::types::*;
+use serde::{Serialize, Deserialize};
+use rand::{thread_rng, Rng};
+use log::{debug, info};
+
+/// 残局判定：存活人数不超过该值时考虑弃车保帅
+const ENDGAME_ALIVE_THRESHOLD: usize = 6;
+
+/// 编造的查验记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FabricatedCheck {
+    pub night: u32,
+    pub target: String,
+    pub is_werewolf: bool,
+    /// 是否已在公开场合报出
+    pub published: bool,
+}
+
+/// 狼人的跨天欺骗计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeceptionPlan {
+    /// 被怀疑时准备跳的身份
+    pub fake_role: RoleType,
+    /// 是否已经公开跳过身份
+    pub role_claimed: bool,
+    /// 编造的查验历史（仅在伪装预言家时使用）
+    pub fabricated_checks: Vec<FabricatedCheck>,
+    /// 残局时准备舍弃的队友
+    pub sacrifice: Option<String>,
+    /// 与公开记录冲突、需要在发言中圆回来的问题
+    pub conflicts: Vec<String>,
+}
+
+impl DeceptionPlan {
+    /// 根据欺骗倾向生成初始计划：欺骗性强的狼倾向于悍跳预言家
+    pub fn new(deception: f32) -> Self {
+        let fake_role = if thread_rng().gen::<f32>() < deception {
+            RoleType::Seer
+        } else {
+            RoleType::Villager
+        };
+
+        Self {
+            fake_role,
+            role_claimed: false,
+            fabricated_checks: Vec::new(),
+            sacrifice: None,
+            conflicts: Vec::new(),
+        }
+    }
+
+    /// 每天更新计划：补齐查验历史、在残局选择弃车对象
+    pub fn update(&mut self, my_id: &str, game_state: &GameState, reasoning: &ReasoningEngine) {
+        self.role_claimed = game_state.claims_board.current_claim(my_id) == Some(&self.fake_role);
+
+        if self.fake_role == RoleType::Seer {
+            self.extend_fabricated_checks(my_id, game_state, reasoning);
+        }
+
+        self.choose_sacrifice(my_id, game_state, reasoning);
+    }
+
+    /// 按夜晚数补齐编造的查验，保证不重复查验同一人
+    fn extend_fabricated_checks(&mut self, my_id: &str, game_state: &GameState, reasoning: &ReasoningEngine) {
+        let nights = ConsistencyChecker::completed_nights(game_state);
+        for night in 1..=nights {
+            if self.fabricated_checks.iter().any(|c| c.night == night) {
+                continue;
+            }
+            let checked: Vec<&str> = self.fabricated_checks.iter().map(|c| c.target.as_str()).collect();
+
+            // 只挑存活的好人：给最有威胁的好人发查杀，否则给队友发金水
+            let villagers: Vec<String> = game_state.players.iter()
+                .filter(|p| p.is_alive && p.id != my_id && p.faction == Faction::Villager)
+                .filter(|p| !checked.contains(&p.id.as_str()))
+                .map(|p| p.id.clone())
+                .collect();
+            let teammates: Vec<String> = game_state.players.iter()
+                .filter(|p| p.is_alive && Self::knows_teammate(my_id, p, game_state))
+                .filter(|p| !checked.contains(&p.id.as_str()))
+                .map(|p| p.id.clone())
+                .collect();
+
+            let check = if night == 1 || teammates.is_empty() {
+                Self::most_trusted(&villagers, reasoning).map(|target| (target, true))
+            } else {
+                Some((teammates[0].clone(), false))
+            };
+
+            match check {
+                Some((target, is_werewolf)) => {
+                    debug!("狼人{}编造第{}晚查验: {} -> {}", my_id, night, target, is_werewolf);
+                    self.fabricated_checks.push(FabricatedCheck { night, target, is_werewolf, published: false });
+                    self.fabricated_checks.sort_by_key(|c| c.night);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// 只把自己认识的狼当作队友
+    fn knows_teammate(my_id: &str, other: &Player, game_state: &GameState) -> bool {
+        game_state.players.iter()
+            .find(|p| p.id == my_id)
+            .map(|me| me.knows_teammate(other))
+            .unwrap_or(false)
+    }
+
+    /// 好人中最受信任的玩家（最容易带队的威胁）
+    fn most_trusted(candidates: &[String], reasoning: &ReasoningEngine) -> Option<String> {
+        candidates.iter()
+            .min_by(|a, b| {
+                reasoning.get_werewolf_probability(a)
+                    .partial_cmp(&reasoning.get_werewolf_probability(b))
+                    .unwrap()
+            })
+            .cloned()
+    }
+
+    /// 残局且有多只狼存活时，舍弃最被怀疑的队友
+    fn choose_sacrifice(&mut self, my_id: &str, game_state: &GameState, reasoning: &ReasoningEngine) {
+        let alive = game_state.players.iter().filter(|p| p.is_alive).count();
+        let teammates: Vec<String> = game_state.players.iter()
+            .filter(|p| p.is_alive && Self::knows_teammate(my_id, p, game_state))
+            .map(|p| p.id.clone())
+            .collect();
+
+        if alive > ENDGAME_ALIVE_THRESHOLD || teammates.is_empty() {
+            self.sacrifice = None;
+            return;
+        }
+
+        let sacrifice = reasoning.get_most_suspicious_among(&teammates);
+        if sacrifice != self.sacrifice {
+            info!("狼人{}计划舍弃队友: {:?}", my_id, sacrifice);
+            self.sacrifice = sacrifice;
+        }
+    }
+
+    /// 发言前对照公开记录校验计划，修正尚未公开的破绽并记录已公开的矛盾
+    pub fn validate(&mut self, my_id: &str, game_state: &GameState) {
+        let board = &game_state.claims_board;
+        self.conflicts.clear();
+
+        // 已报出的查验以声明板为准
+        for check in self.fabricated_checks.iter_mut() {
+            check.published = board.check_reports.iter()
+                .any(|r| r.reporter == my_id && r.target == check.target);
+        }
+
+        // 未公开的查验若与公开事实矛盾则直接丢弃，已公开的只能记下来在发言中圆
+        let mut kept: Vec<FabricatedCheck> = Vec::new();
+        for check in self.fabricated_checks.drain(..) {
+            let mut claimed: Vec<ClaimedCheck> = kept.iter().map(Self::as_claimed).collect();
+            claimed.push(Self::as_claimed(&check));
+            let inconsistencies: Vec<_> = ConsistencyChecker::check(my_id, &claimed, game_state)
+                .into_iter()
+                .filter(|i| i.target == check.target)
+                .collect();
+
+            if inconsistencies.is_empty() {
+                kept.push(check);
+            } else if check.published {
+                self.conflicts.extend(inconsistencies.into_iter().map(|i| i.description));
+                kept.push(check);
+            } else {
+                debug!("狼人{}放弃与公开事实矛盾的编造查验: {}", my_id, check.target);
+            }
+        }
+        self.fabricated_checks = kept;
+
+        // 跳预言家前发现已有多人对跳，则退回平民身份
+        if self.fake_role == RoleType::Seer && !self.role_claimed
+            && board.claimants(&RoleType::Seer).len() >= 2 {
+            self.fake_role = RoleType::Villager;
+            self.fabricated_checks.clear();
+        }
+    }
+
+    fn as_claimed(check: &FabricatedCheck) -> ClaimedCheck {
+        ClaimedCheck {
+            night: Some(check.night),
+            target: check.target.clone(),
+            is_werewolf: check.is_werewolf,
+        }
+    }
+
+    /// 为发言生成要点
+    pub fn speech_guidance(&self, game_state: &GameState, pressured: bool) -> Vec<String> {
+        let name_of = |id: &str| game_state.players.iter()
+            .chain(game_state.dead_players.iter())
+            .find(|p| p.id == id)
+            .map(|p| p.display_name())
+            .unwrap_or_else(|| id.to_string());
+        let mut points = Vec::new();
+
+        if pressured || self.role_claimed {
+            points.push(format!("坚持声称自己是{}", crate::utils::get_role_name(&self.fake_role)));
+            for check in &self.fabricated_checks {
+                points.push(format!(
+                    "第{}晚查验{}是{}",
+                    check.night,
+                    name_of(&check.target),
+                    if check.is_werewolf { "狼人" } else { "好人" }
+                ));
+            }
+        }
+
+        if let Some(sacrifice) = &self.sacrifice {
+            points.push(format!("必要时可以跟着大家怀疑{}", name_of(sacrifice)));
+        }
+
+        for conflict in &self.conflicts {
+            points.push(format!("设法解释：{}", conflict));
+        }
+
+        points
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Serialize, Deserialize};
+
+/// 难度配置 - 控制AI在推理与决策中的信息限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyProfile {
+    pub name: String,
+    /// 每个玩家最多保留的证据条数，None表示完美记忆
+    pub evidence_capacity: Option<usize>,
+    /// 每次获得新证据时遗忘旧印象的概率
+    pub forget_chance: f32,
+    /// 投票时故意偏离最优选择的概率
+    pub suboptimal_vote_rate: f32,
+    /// 蒙特卡洛模拟的采样次数
+    pub monte_carlo_samples: u32,
+}
+
+impl DifficultyProfile {
+    /// 简单：容易遗忘旧证据，经常做出次优投票
+    pub fn easy() -> Self {
+        Self {
+            name: "easy".to_string(),
+            evidence_capacity: Some(3),
+            forget_chance: 0.25,
+            suboptimal_vote_rate: 0.4,
+            monte_carlo_samples: 8,
+        }
+    }
+
+    /// 普通：偶尔遗忘，较少失误
+    pub fn normal() -> Self {
+        Self {
+            name: "normal".to_string(),
+            evidence_capacity: Some(8),
+            forget_chance: 0.05,
+            suboptimal_vote_rate: 0.15,
+            monte_carlo_samples: 32,
+        }
+    }
+
+    /// 困难：记忆充足，几乎不失误
+    pub fn hard() -> Self {
+        Self {
+            name: "hard".to_string(),
+            evidence_capacity: Some(20),
+            forget_chance: 0.0,
+            suboptimal_vote_rate: 0.05,
+            monte_carlo_samples: 128,
+        }
+    }
+
+    /// 专家：完美记忆，更深的蒙特卡洛搜索
+    pub fn expert() -> Self {
+        Self {
+            name: "expert".to_string(),
+            evidence_capacity: None,
+            forget_chance: 0.0,
+            suboptimal_vote_rate: 0.0,
+            monte_carlo_samples: 512,
+        }
+    }
+
+    /// 是否拥有完美记忆
+    pub fn has_perfect_memory(&self) -> bool {
+        self.evidence_capacity.is_none() && self.forget_chance <= 0.0
+    }
+}
+
+/// 对局配置里选择的AI难度，开局时换算成各AI座位的难度配置
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AIDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Expert,
+}
+
+impl AIDifficulty {
+    pub fn profile(&self) -> DifficultyProfile {
+        match self {
+            AIDifficulty::Easy => DifficultyProfile::easy(),
+            AIDifficulty::Normal => DifficultyProfile::normal(),
+            AIDifficulty::Hard => DifficultyProfile::hard(),
+            AIDifficulty::Expert => DifficultyProfile::expert(),
+        }
+    }
+}
+
+impl Default for DifficultyProfile {
+    fn default() -> Self {
+        Self::normal()
+    }
+}
@@ -0,0 +1,181 @@
+use crate::types::PersonalityTraits;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 单个事件引起的特征变化幅度
+const DRIFT_STEP: f32 = 0.05;
+/// 特征相对模板基线的最大偏移
+const DRIFT_BOUND: f32 = 0.2;
+
+/// 引起性格漂移的事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DriftCause {
+    /// 被公开报为查杀
+    Accused { by: String },
+    /// 放逐投票中被投
+    VotedAgainst { votes: u32 },
+    /// 已公示的同阵营玩家出局
+    AllyLost { player_id: String },
+    /// 指控自己的人出局后被翻出是狼
+    Vindicated { accuser: String },
+}
+
+impl DriftCause {
+    pub fn describe(&self) -> String {
+        match self {
+            DriftCause::Accused { by } => format!("被{}报为查杀", by),
+            DriftCause::VotedAgainst { votes } => format!("放逐投票中被投{}票", votes),
+            DriftCause::AllyLost { player_id } => format!("同阵营的{}出局", player_id),
+            DriftCause::Vindicated { accuser } => format!("指控自己的{}被翻出是狼", accuser),
+        }
+    }
+}
+
+/// 一次性格漂移
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftRecord {
+    pub day: u32,
+    pub cause: DriftCause,
+    pub before: PersonalityTraits,
+    pub after: PersonalityTraits,
+}
+
+/// 局内性格漂移：事件让有效性格小幅偏移，但不会偏离模板基线超过DRIFT_BOUND
+#[derive(Debug, Clone)]
+pub struct PersonalityDrift {
+    baseline: PersonalityTraits,
+    accusers: HashSet<String>,
+    history: Vec<DriftRecord>,
+    /// 已交给复盘记录的条数
+    reported: usize,
+}
+
+impl PersonalityDrift {
+    pub fn new(baseline: PersonalityTraits) -> Self {
+        Self {
+            baseline,
+            accusers: HashSet::new(),
+            history: Vec::new(),
+            reported: 0,
+        }
+    }
+
+    /// 按事件调整特征，没有任何特征变化时（已到边界）返回false
+    pub fn apply(&mut self, traits: &mut PersonalityTraits, cause: DriftCause, is_wolf: bool, day: u32) -> bool {
+        let before = traits.clone();
+        let base = self.baseline.clone();
+        let shift = |value: &mut f32, base: f32, steps: f32| {
+            let low = (base - DRIFT_BOUND).max(0.0);
+            let high = (base + DRIFT_BOUND).min(1.0);
+            *value = (*value + steps * DRIFT_STEP).clamp(low, high);
+        };
+
+        match &cause {
+            DriftCause::Accused { by } => {
+                self.accusers.insert(by.clone());
+                Self::under_pressure(traits, &base, 1.0, is_wolf, shift);
+            }
+            DriftCause::VotedAgainst { votes } => {
+                Self::under_pressure(traits, &base, (*votes as f32 * 0.5).min(1.0), is_wolf, shift);
+            }
+            DriftCause::AllyLost { .. } => {
+                shift(&mut traits.patience, base.patience, -1.0);
+                shift(&mut traits.aggressiveness, base.aggressiveness, 0.5);
+            }
+            DriftCause::Vindicated { .. } => {
+                shift(&mut traits.logic, base.logic, 0.5);
+                shift(&mut traits.trustfulness, base.trustfulness, 0.5);
+            }
+        }
+
+        let changed = [
+            (before.aggressiveness, traits.aggressiveness),
+            (before.logic, traits.logic),
+            (before.deception, traits.deception),
+            (before.trustfulness, traits.trustfulness),
+            (before.patience, traits.patience),
+        ].iter().any(|(a, b)| (a - b).abs() > f32::EPSILON);
+
+        if changed {
+            self.history.push(DriftRecord { day, cause, before, after: traits.clone() });
+        }
+        changed
+    }
+
+    /// 该玩家是否曾经报过自己查杀
+    pub fn is_accuser(&self, player_id: &str) -> bool {
+        self.accusers.contains(player_id)
+    }
+
+    /// 取出尚未交给复盘记录的漂移
+    pub fn take_unreported(&mut self) -> Vec<DriftRecord> {
+        let records = self.history[self.reported..].to_vec();
+        self.reported = self.history.len();
+        records
+    }
+
+    /// 被冤枉的好人变得更激进、更不轻信；被盯上的狼则收敛锋芒、加强伪装
+    fn under_pressure(
+        traits: &mut PersonalityTraits,
+        base: &PersonalityTraits,
+        weight: f32,
+        is_wolf: bool,
+        shift: impl Fn(&mut f32, f32, f32),
+    ) {
+        if is_wolf {
+            shift(&mut traits.deception, base.deception, weight);
+            shift(&mut traits.aggressiveness, base.aggressiveness, -0.5 * weight);
+        } else {
+            shift(&mut traits.aggressiveness, base.aggressiveness, weight);
+            shift(&mut traits.trustfulness, base.trustfulness, -weight);
+            shift(&mut traits.patience, base.patience, -0.5 * weight);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neutral() -> PersonalityTraits {
+        PersonalityTraits {
+            aggressiveness: 0.5,
+            logic: 0.5,
+            deception: 0.5,
+            trustfulness: 0.5,
+            patience: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_accused_villager_and_wolf_drift_differently() {
+        let accused = || DriftCause::Accused { by: "seer".to_string() };
+
+        let mut villager = neutral();
+        let mut drift = PersonalityDrift::new(neutral());
+        assert!(drift.apply(&mut villager, accused(), false, 1));
+        assert!(villager.aggressiveness > 0.5 && villager.trustfulness < 0.5);
+        assert!(drift.is_accuser("seer"));
+
+        let mut wolf = neutral();
+        let mut drift = PersonalityDrift::new(neutral());
+        assert!(drift.apply(&mut wolf, accused(), true, 1));
+        assert!(wolf.deception > 0.5 && wolf.aggressiveness < 0.5);
+    }
+
+    #[test]
+    fn test_drift_stops_at_the_bound() {
+        let mut traits = neutral();
+        let mut drift = PersonalityDrift::new(neutral());
+        let mut applied = 0;
+        let vindicated = || DriftCause::Vindicated { accuser: "wolf".to_string() };
+        while drift.apply(&mut traits, vindicated(), false, 2) {
+            applied += 1;
+            assert!(applied < 100);
+        }
+        assert!((traits.logic - (0.5 + DRIFT_BOUND)).abs() < 1e-5);
+        assert!((traits.trustfulness - (0.5 + DRIFT_BOUND)).abs() < 1e-5);
+        assert_eq!(drift.take_unreported().len(), applied);
+        assert!(drift.take_unreported().is_empty());
+    }
+}
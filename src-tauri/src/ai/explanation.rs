@@ -0,0 +1,83 @@
+use crate::ai::reasoning::{Evidence, EvidenceType, ReasoningEngine};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+
+/// 一次解释最多引用的证据条数
+const EXPLANATION_EVIDENCE: usize = 3;
+
+/// 对某名玩家怀疑度的可读解释，前端悬停玩家时展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspicionExplanation {
+    /// 解释所站的视角，None为只看公开信息的旁观推理
+    pub observer_id: Option<String>,
+    pub target_id: String,
+    pub suspicion: f32,
+    pub text: String,
+    /// 是否经过模型润色
+    pub polished: bool,
+}
+
+/// 用模板把分量最重的几条证据拼成一句解释
+pub fn explain_suspicion(engine: &ReasoningEngine, state: &GameState, target_id: &str) -> (f32, String) {
+    let suspicion = engine.get_suspicion_score(target_id);
+    let name = display_name(state, target_id);
+
+    let mut reasons: Vec<String> = Vec::new();
+    for evidence in engine.top_evidence(target_id, EXPLANATION_EVIDENCE) {
+        let reason = reason(evidence, state);
+        if !reasons.contains(&reason) {
+            reasons.push(reason);
+        }
+    }
+    if reasons.is_empty() {
+        return (suspicion, format!("{}目前没有值得一提的可疑表现。", name));
+    }
+
+    let level = if suspicion >= 0.7 {
+        "很可疑"
+    } else if suspicion >= 0.5 {
+        "有些可疑"
+    } else {
+        "暂时不太可疑"
+    };
+    (suspicion, format!("{}{}，因为{}。", name, level, reasons.join("；")))
+}
+
+/// 让模型润色解释的提示词，只改说法不加信息
+pub fn polish_prompt(text: &str) -> String {
+    format!(
+        "把下面这段狼人杀局势分析改写成一句自然、口语化的中文，保留所有事实，不要添加任何新信息或推测，\
+         只输出改写后的句子：\n{}",
+        text
+    )
+}
+
+/// 单条证据的说法，证据描述里的玩家ID换成座位号和名字
+fn reason(evidence: &Evidence, state: &GameState) -> String {
+    let fact = humanize(&evidence.description, state);
+    match evidence.evidence_type {
+        EvidenceType::LogicalInconsistency => format!("{}，前后对不上", fact),
+        EvidenceType::DefensiveBehavior => format!("{}，显得急于自保", fact),
+        EvidenceType::AggressiveBehavior => format!("{}，有带节奏的嫌疑", fact),
+        EvidenceType::TeamworkIndicator => format!("{}，像是在和人打配合", fact),
+        EvidenceType::VotingPattern
+        | EvidenceType::SpeechAnalysis
+        | EvidenceType::NightResult
+        | EvidenceType::RoleClaimConsistency => fact,
+    }
+}
+
+fn humanize(description: &str, state: &GameState) -> String {
+    // 长ID先替换，避免一个ID是另一个的前缀时替换错位
+    let mut players: Vec<&Player> = state.players.iter().chain(state.dead_players.iter()).collect();
+    players.sort_by_key(|p| std::cmp::Reverse(p.id.len()));
+    players.iter().fold(description.to_string(), |text, player| text.replace(&player.id, &player.display_name()))
+}
+
+fn display_name(state: &GameState, player_id: &str) -> String {
+    state.players.iter()
+        .chain(state.dead_players.iter())
+        .find(|p| p.id == player_id)
+        .map(|p| p.display_name())
+        .unwrap_or_else(|| player_id.to_string())
+}
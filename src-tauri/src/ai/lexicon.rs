@@ -0,0 +1,118 @@
+use jieba_rs::Jieba;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+use log::{info, warn};
+
+/// 内置词表，用户目录下没有lexicon.json时使用
+const DEFAULT_LEXICON: &str = include_str!("../../data/lexicon.json");
+
+/// 词表文件名，放在配置目录下即可覆盖内置词表
+pub const LEXICON_FILE: &str = "lexicon.json";
+
+static JIEBA: OnceLock<Jieba> = OnceLock::new();
+static LEXICON: OnceLock<Lexicon> = OnceLock::new();
+
+/// 发言分析用的关键词表
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lexicon {
+    #[serde(default)]
+    pub suspicious: Vec<String>,
+    #[serde(default)]
+    pub defensive: Vec<String>,
+    #[serde(default)]
+    pub aggressive: Vec<String>,
+}
+
+impl Lexicon {
+    /// 优先读取配置目录下的词表，读取失败时回退到内置词表
+    pub fn load(dir: Option<&Path>) -> Self {
+        if let Some(path) = dir.map(|dir| dir.join(LEXICON_FILE)) {
+            if path.exists() {
+                match std::fs::read_to_string(&path).map(|content| serde_json::from_str::<Lexicon>(&content)) {
+                    Ok(Ok(lexicon)) => {
+                        info!("已加载自定义词表: {:?}", path);
+                        return lexicon;
+                    }
+                    Ok(Err(e)) => warn!("解析词表失败，使用内置词表: {}", e),
+                    Err(e) => warn!("读取词表失败，使用内置词表: {}", e),
+                }
+            }
+        }
+
+        serde_json::from_str(DEFAULT_LEXICON).unwrap_or_default()
+    }
+
+    /// 启动时安装全局词表，只有第一次生效
+    pub fn install(lexicon: Lexicon) {
+        if LEXICON.set(lexicon).is_err() {
+            warn!("词表已加载，忽略重复安装");
+        }
+    }
+
+    /// 全局词表，未安装时使用内置词表
+    pub fn global() -> &'static Lexicon {
+        LEXICON.get_or_init(|| Self::load(None))
+    }
+
+    /// 找出发言中命中的词条（按分词结果匹配，避免跨词误命中）
+    pub fn find<'a>(terms: &'a [String], tokens: &[String]) -> Vec<&'a str> {
+        terms.iter()
+            .filter(|term| contains_term(tokens, term))
+            .map(|term| term.as_str())
+            .collect()
+    }
+}
+
+/// 中文分词
+pub fn segment(text: &str) -> Vec<String> {
+    let jieba = JIEBA.get_or_init(Jieba::new);
+    jieba.cut(text, false)
+        .into_iter()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// 按字符计算长度（一个汉字算一个字）
+pub fn char_len(text: &str) -> usize {
+    text.trim().chars().count()
+}
+
+/// 分词后是否包含某个词（或词组）
+pub fn contains_term(tokens: &[String], term: &str) -> bool {
+    let term_tokens = segment(term);
+    !term_tokens.is_empty() && tokens.windows(term_tokens.len()).any(|window| window == term_tokens.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms_match_whole_tokens_only() {
+        let tokens = segment("我觉得他在划水，不像好人");
+        assert!(contains_term(&tokens, "划水"));
+        assert!(contains_term(&tokens, "不像好人"));
+        assert!(!contains_term(&tokens, "水"));
+        assert!(!contains_term(&tokens, ""));
+    }
+
+    #[test]
+    fn test_custom_lexicon_falls_back_to_builtin_when_unreadable() {
+        let dir = std::env::temp_dir().join(format!("mindwolf_lexicon_{}", crate::utils::generate_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let builtin = Lexicon::load(None);
+        assert!(!builtin.suspicious.is_empty());
+
+        std::fs::write(dir.join(LEXICON_FILE), r#"{"suspicious": ["划水"]}"#).unwrap();
+        let custom = Lexicon::load(Some(&dir));
+        assert_eq!(custom.suspicious, vec!["划水".to_string()]);
+        assert!(custom.defensive.is_empty());
+
+        std::fs::write(dir.join(LEXICON_FILE), "不是JSON").unwrap();
+        assert_eq!(Lexicon::load(Some(&dir)).suspicious, builtin.suspicious);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,9 +3,23 @@ pub mod strategy;
 pub mod personality;
 pub mod nlp;
 pub mod agent;
+pub mod difficulty;
+pub mod persona;
+pub mod deception;
+pub mod consistency;
+pub mod lexicon;
+pub mod sentiment;
+pub mod drift;
+pub mod explanation;
+pub mod vote_prediction;
 
 pub use reasoning::*;
 pub use strategy::*;
 pub use personality::*;
 pub use nlp::*;
-pub use agent::*;
\ No newline at end of file
+pub use agent::*;
+pub use difficulty::*;
+pub use persona::*;
+pub use consistency::*;
+pub use explanation::*;
+pub use vote_prediction::*;
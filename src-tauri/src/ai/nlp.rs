@@ -1,15 +1,21 @@
 use crate::types::*;
 use crate::error::{AppError, AppResult};
 use crate::llm::LLMManager;
+use crate::ai::lexicon;
+use crate::ai::sentiment::SentimentClassifier;
+use crate::memory::{ApproxSize, MemoryStats, RingBuffer};
 use crate::types::*;
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 use log::{info, warn};
 
+/// 上下文记忆保留的发言条数，完整发言记录已由发言管道落库
+const CONTEXT_MEMORY_CAPACITY: usize = 50;
+
 /// 自然语言处理模块
 pub struct NLPProcessor {
     llm_manager: Option<Arc<LLMManager>>,
-    context_memory: Vec<SpeechRecord>,
+    context_memory: RingBuffer<SpeechRecord>,
 }
 
 /// 发言记录
@@ -22,6 +28,12 @@ pub struct SpeechRecord {
     pub day: u32,
 }
 
+impl ApproxSize for SpeechRecord {
+    fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<Self>() + self.speaker.capacity() + self.content.capacity()
+    }
+}
+
 /// 发言分析结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpeechAnalysis {
@@ -36,7 +48,7 @@ impl NLPProcessor {
     pub fn new(llm_manager: Option<Arc<LLMManager>>) -> Self {
         Self {
             llm_manager,
-            context_memory: Vec::new(),
+            context_memory: RingBuffer::new(CONTEXT_MEMORY_CAPACITY),
         }
     }
     
@@ -52,7 +64,8 @@ impl NLPProcessor {
             
             match llm_manager.generate_with_fallback(prompt).await {
                 Ok(response) => {
-                    let speech = self.post_process_speech(response.as_str());
+                    let max_chars = game_state.game_config.speech_limits.max_chars_for(&game_state.phase);
+                    let speech = self.post_process_speech(response.as_str(), max_chars);
                     self.record_speech(player.id.clone(), speech.clone(), game_state.phase.clone(), game_state.day);
                     Ok(speech)
                 }
@@ -75,8 +88,15 @@ impl NLPProcessor {
         self.record_speech(speaker_id.clone(), content.clone(), game_state.phase.clone(), game_state.day);
         
         let intent = self.analyze_intent(&content);
-        let emotion = self.analyze_emotion(&content);
-        let credibility = self.calculate_credibility(&content);
+        // 有本地情感模型时用模型打分，否则退回关键词规则
+        let scores = SentimentClassifier::global()
+            .and_then(|classifier| classifier.classify(&content)
+                .map_err(|e| warn!("情感模型打分失败: {}", e))
+                .ok());
+        let (emotion, credibility) = match scores {
+            Some(scores) => (scores.emotion(), scores.credibility()),
+            None => (self.analyze_emotion(&content), self.calculate_credibility(&content)),
+        };
         let key_info = self.extract_key_info(&content);
         let targets = self.extract_targets(&content, game_state);
         
@@ -91,52 +111,40 @@ impl NLPProcessor {
     
     fn build_speech_prompt(&self, player: &Player, game_state: &GameState, context: &str) -> String {
         let role_desc = match player.role.role_type {
-            RoleType::Werewolf => "你是狼人，需要隐藏身份，误导好人。",
+            RoleType::Werewolf | RoleType::DreamWolf | RoleType::Gargoyle => "你是狼人，需要隐藏身份，误导好人。",
             RoleType::Seer => "你是预言家，需要分享验人信息。",
             RoleType::Villager => "你是村民，需要找出狼人。",
             _ => "你需要根据身份合理发言。",
         };
         
         format!(
-            "你是{}，{}当前是第{}天。存活玩家：{}。{}请生成50-150字的发言：",
-            player.name,
+            "你是{}，{}当前是第{}天。存活玩家：{}。{}{}{}请生成不超过{}字的发言：",
+            player.display_name(),
             role_desc,
             game_state.day,
             self.format_alive_players(game_state),
-            context
+            context,
+            game_state.game_config.speech_style.prompt_instruction(),
+            game_state.game_config.speech_style.banter_instruction(player),
+            game_state.game_config.speech_limits.max_chars_for(&game_state.phase)
         )
     }
     
     fn generate_fallback_speech(&self, player: &Player, game_state: &GameState) -> String {
-        let templates = match player.role.role_type {
-            RoleType::Werewolf => vec![
-                "我觉得某位玩家的发言有些可疑。",
-                "我们需要仔细分析投票情况。",
-                "我倾向于相信好人的判断。",
-            ],
-            RoleType::Seer => vec![
-                "我有一些信息要分享。",
-                "根据我的观察，有人可能有问题。",
-                "大家要相信我的判断。",
-            ],
-            _ => vec![
-                "我需要再观察一下。",
-                "大家的分析都很有道理。",
-                "我暂时保留意见。",
-            ],
-        };
-        
-        templates[game_state.day as usize % templates.len()].to_string()
+        game_state.game_config.speech_style.fallback_speech(&player.role.role_type, game_state.day)
     }
     
     fn analyze_intent(&self, content: &str) -> SpeechIntent {
-        let intent_type = if content.contains("投票") {
+        let tokens = lexicon::segment(content);
+        let has = |term: &str| lexicon::contains_term(&tokens, term);
+        
+        let intent_type = if has("投票") {
             SpeechType::Vote
-        } else if content.contains("怀疑") {
+        } else if has("怀疑") {
             SpeechType::Accusation
-        } else if content.contains("不是我") {
+        } else if has("不是我") {
             SpeechType::Defense
-        } else if content.contains("验了") {
+        } else if has("验了") {
             SpeechType::Information
         } else {
             SpeechType::Strategy
@@ -151,11 +159,14 @@ impl NLPProcessor {
     }
     
     fn analyze_emotion(&self, content: &str) -> String {
-        if content.contains("气死") || content.contains("愤怒") {
+        let tokens = lexicon::segment(content);
+        let has = |term: &str| lexicon::contains_term(&tokens, term);
+        
+        if has("气死") || has("愤怒") {
             "愤怒".to_string()
-        } else if content.contains("紧张") || content.contains("不是我") {
+        } else if has("紧张") || has("不是我") {
             "紧张".to_string()
-        } else if content.contains("一定") || content.contains("肯定") {
+        } else if has("一定") || has("肯定") {
             "自信".to_string()
         } else {
             "冷静".to_string()
@@ -163,15 +174,17 @@ impl NLPProcessor {
     }
     
     fn calculate_credibility(&self, content: &str) -> f32 {
+        let tokens = lexicon::segment(content);
+        let has = |term: &str| lexicon::contains_term(&tokens, term);
         let mut score: f32 = 0.7;
         
-        if content.contains("绝对") || content.contains("一定") {
+        if has("绝对") || has("一定") {
             score -= 0.1;
         }
-        if content.contains("为什么怀疑我") {
+        if has("为什么怀疑我") {
             score -= 0.2;
         }
-        if content.len() > 200 {
+        if lexicon::char_len(content) > 150 {
             score -= 0.1;
         }
         
@@ -179,15 +192,17 @@ impl NLPProcessor {
     }
     
     fn extract_key_info(&self, content: &str) -> Vec<String> {
+        let tokens = lexicon::segment(content);
+        let has = |term: &str| lexicon::contains_term(&tokens, term);
         let mut info = Vec::new();
         
-        if content.contains("我是") {
+        if has("我是") {
             info.push("角色声明".to_string());
         }
-        if content.contains("验了") {
+        if has("验了") {
             info.push("验人结果".to_string());
         }
-        if content.contains("投票") {
+        if has("投票") {
             info.push("投票意向".to_string());
         }
         
@@ -203,6 +218,18 @@ impl NLPProcessor {
             }
         }
         
+        // 按座位号解析提及（“3号”“三号”）
+        for seat in crate::utils::parse_seat_mentions(content) {
+            if let Some(player) = game_state.players.iter()
+                .chain(game_state.dead_players.iter())
+                .find(|p| p.seat == seat)
+            {
+                if !targets.contains(&player.id) {
+                    targets.push(player.id.clone());
+                }
+            }
+        }
+        
         targets
     }
     
@@ -216,20 +243,17 @@ impl NLPProcessor {
         };
         
         self.context_memory.push(record);
-        
-        if self.context_memory.len() > 50 {
-            self.context_memory.remove(0);
-        }
     }
     
-    fn post_process_speech(&self, speech: &str) -> String {
-        let mut processed = speech.trim().to_string();
-        
-        if processed.len() > 200 {
-            processed = processed.chars().take(197).collect::<String>() + "...";
-        }
+    /// 上下文记忆的内存统计
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.context_memory.stats("nlp_context_memory")
+    }
+    
+    fn post_process_speech(&self, speech: &str, max_chars: usize) -> String {
+        let mut processed = crate::utils::trim_to_sentence(speech, max_chars);
         
-        if processed.len() < 10 {
+        if lexicon::char_len(&processed) < 4 {
             processed = "我需要再思考一下。".to_string();
         }
         
@@ -239,7 +263,7 @@ impl NLPProcessor {
     fn format_alive_players(&self, game_state: &GameState) -> String {
         game_state.players.iter()
             .filter(|p| p.is_alive)
-            .map(|p| p.name.clone())
+            .map(|p| p.display_name())
             .collect::<Vec<_>>()
             .join(", ")
     }
@@ -0,0 +1,102 @@
+use crate::types::AIPersona;
+use crate::ai::personality::PersonalityTemplate;
+use rand::{thread_rng, Rng};
+use std::collections::HashSet;
+
+/// 人设生成器 - 为同一局中的AI生成不重名且与性格模板一致的人设
+#[derive(Debug, Default)]
+pub struct PersonaGenerator {
+    used_names: HashSet<String>,
+}
+
+impl PersonaGenerator {
+    pub fn new() -> Self {
+        Self {
+            used_names: HashSet::new(),
+        }
+    }
+
+    /// 登记已被占用的名字（例如人类玩家的名字）
+    pub fn reserve_name(&mut self, name: &str) {
+        self.used_names.insert(name.to_string());
+    }
+
+    /// 根据性格模板生成人设
+    pub fn generate(&mut self, template: &PersonalityTemplate) -> AIPersona {
+        let (prefixes, nouns, avatars, flavor) = Self::template_style(&template.id);
+        let mut rng = thread_rng();
+
+        // 优先在组合空间内随机取未使用的名字
+        let mut candidates: Vec<String> = prefixes.iter()
+            .flat_map(|prefix| nouns.iter().map(move |noun| format!("{}{}", prefix, noun)))
+            .filter(|name| !self.used_names.contains(name))
+            .collect();
+
+        let name = if candidates.is_empty() {
+            // 组合用尽时追加编号保证唯一
+            let base = format!("{}{}", prefixes[0], nouns[0]);
+            (2..)
+                .map(|n| format!("{}{}号", base, n))
+                .find(|name| !self.used_names.contains(name))
+                .unwrap_or(base)
+        } else {
+            candidates.swap_remove(rng.gen_range(0..candidates.len()))
+        };
+        self.used_names.insert(name.clone());
+
+        AIPersona {
+            name,
+            avatar: avatars[rng.gen_range(0..avatars.len())].to_string(),
+            bio: format!("{}。{}", template.description, flavor),
+            template_id: template.id.clone(),
+        }
+    }
+
+    /// 各性格模板对应的命名风格、头像与简介
+    fn template_style(template_id: &str) -> (&'static [&'static str], &'static [&'static str], &'static [&'static str], &'static str) {
+        match template_id {
+            "analytical" => (
+                &["冷静的", "缜密的", "理性的", "沉思的"],
+                &["棋手", "学者", "侦探", "观星者"],
+                &["🦉", "♟️", "🔍"],
+                "习惯把每个人的发言记在心里，再一条条对照",
+            ),
+            "impulsive" => (
+                &["急躁的", "火爆的", "直率的", "热血的"],
+                &["猎豹", "火狐", "野马", "闪电"],
+                &["🐆", "🔥", "⚡"],
+                "想到什么说什么，常常第一个跳出来表态",
+            ),
+            "deceptive" => (
+                &["狡黠的", "神秘的", "善变的", "微笑的"],
+                &["狐狸", "魔术师", "旅人", "面具"],
+                &["🦊", "🎭", "🃏"],
+                "说话滴水不漏，让人猜不透真实想法",
+            ),
+            "cautious" => (
+                &["谨慎的", "安静的", "沉稳的", "低调的"],
+                &["乌龟", "守望者", "灯塔", "山岩"],
+                &["🐢", "🏔️", "🕯️"],
+                "很少抢先发言，总在观察局势",
+            ),
+            "leader" => (
+                &["果断的", "威严的", "无畏的", "远见的"],
+                &["雄狮", "船长", "将军", "领航员"],
+                &["🦁", "⚓", "👑"],
+                "喜欢组织大家发言，擅长带动投票",
+            ),
+            "chaotic" => (
+                &["古怪的", "跳脱的", "随性的", "迷糊的"],
+                &["猫咪", "骰子", "风筝", "泡泡"],
+                &["🐱", "🎲", "🎈"],
+                "思路天马行空，常常让人摸不着头脑",
+            ),
+            _ => (
+                &["聪明的", "机智的", "敏锐的", "睿智的"],
+                &["狼", "鹰", "鹿", "熊"],
+                &["🐺", "🦅", "🦌"],
+                "一位普通的玩家",
+            ),
+        }
+    }
+}
@@ -271,6 +271,13 @@ impl PersonalityManager {
         ]
     }
     
+    /// 按人设记录的模板ID取发言模式，未知模板返回None
+    pub fn speech_patterns_of(template_id: &str) -> Option<SpeechPatterns> {
+        Self::get_personality_templates().into_iter()
+            .find(|template| template.id == template_id)
+            .map(|template| template.speech_patterns)
+    }
+    
     /// 根据模板创建个性化AI性格
     pub fn create_personality_from_template(
         template: &PersonalityTemplate,
@@ -284,6 +291,7 @@ impl PersonalityManager {
             logic: Self::vary_trait(template.base_traits.logic, variation_factor, &mut rng),
             deception: Self::vary_trait(template.base_traits.deception, variation_factor, &mut rng),
             trustfulness: Self::vary_trait(template.base_traits.trustfulness, variation_factor, &mut rng),
+            patience: Self::vary_trait(template.base_traits.patience, variation_factor, &mut rng),
         };
         
         AIPersonality {
@@ -303,6 +311,7 @@ impl PersonalityManager {
             logic: rng.gen_range(0.3..0.9),
             deception: rng.gen_range(0.1..0.8),
             trustfulness: rng.gen_range(0.2..0.8),
+            patience: rng.gen_range(0.2..0.9),
         };
         
         let personality_type = if traits.logic > 0.7 {
@@ -331,7 +340,9 @@ impl PersonalityManager {
         let mut optimized_traits = base_personality.traits.clone();
         
         match role.role_type {
-            crate::types::RoleType::Werewolf => {
+            crate::types::RoleType::Werewolf
+            | crate::types::RoleType::DreamWolf
+            | crate::types::RoleType::Gargoyle => {
                 // 狼人需要更强的欺骗能力
                 optimized_traits.deception = (optimized_traits.deception + 0.3).min(1.0);
                 optimized_traits.trustfulness = (optimized_traits.trustfulness - 0.2).max(0.1);
@@ -441,6 +452,8 @@ impl PersonalityManager {
             crate::types::RoleType::Witch => "女巫",
             crate::types::RoleType::Hunter => "猎人",
             crate::types::RoleType::Guard => "守卫",
+            crate::types::RoleType::DreamWolf => "梦狼",
+            crate::types::RoleType::Gargoyle => "石像鬼",
         }
     }
 }
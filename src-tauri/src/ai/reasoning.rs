@@ -1,8 +1,12 @@
 use crate::types::*;
 use crate::error::{AppError, AppResult};
 use crate::types::*;
+use crate::ai::difficulty::DifficultyProfile;
+use crate::ai::consistency::Inconsistency;
+use crate::ai::lexicon::{self, Lexicon};
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
+use rand::{thread_rng, Rng};
 use log::{info, warn, debug};
 
 /// 贝叶斯推理网络节点
@@ -44,6 +48,7 @@ pub struct ReasoningEngine {
     nodes: HashMap<String, BayesianNode>,
     game_state: Option<GameState>,
     reasoning_rules: Vec<ReasoningRule>,
+    profile: DifficultyProfile,
 }
 
 /// 推理规则
@@ -82,9 +87,15 @@ impl ReasoningEngine {
             nodes: HashMap::new(),
             game_state: None,
             reasoning_rules: Self::create_default_rules(),
+            profile: DifficultyProfile::default(),
         }
     }
     
+    /// 设置难度配置
+    pub fn set_profile(&mut self, profile: DifficultyProfile) {
+        self.profile = profile;
+    }
+    
     /// 初始化贝叶斯网络
     pub fn initialize(&mut self, game_state: &GameState) {
         self.game_state = Some(game_state.clone());
@@ -100,11 +111,11 @@ impl ReasoningEngine {
                 role_probabilities.insert(role.clone(), probability);
             }
             
-            let faction_probability = if role_probabilities.contains_key(&RoleType::Werewolf) {
-                role_probabilities[&RoleType::Werewolf]
-            } else {
-                0.0
-            };
+            // 狼人阵营包括不参与刀人的狼
+            let faction_probability: f32 = role_probabilities.iter()
+                .filter(|(role, _)| role.faction() == Faction::Werewolf)
+                .map(|(_, probability)| probability)
+                .sum();
             
             let node = BayesianNode {
                 player_id: player.id.clone(),
@@ -128,9 +139,35 @@ impl ReasoningEngine {
             self.update_probabilities(&player_id, &evidence)?;
             debug!("为玩家{}添加证据: {:?}", player_id, evidence.evidence_type);
         }
+        self.apply_memory_limits();
         Ok(())
     }
     
+    /// 根据难度配置遗忘旧证据
+    fn apply_memory_limits(&mut self) {
+        if self.profile.has_perfect_memory() {
+            return;
+        }
+        
+        let mut rng = thread_rng();
+        for (player_id, node) in self.nodes.iter_mut() {
+            if let Some(capacity) = self.profile.evidence_capacity {
+                if node.evidence.len() > capacity {
+                    let overflow = node.evidence.len() - capacity;
+                    node.evidence.drain(..overflow);
+                }
+            }
+            
+            // 偶尔淡忘对某个玩家的印象，分数向中立回归
+            if !node.evidence.is_empty() && rng.gen::<f32>() < self.profile.forget_chance {
+                node.evidence.remove(0);
+                node.suspicion_score = (node.suspicion_score + 0.5) / 2.0;
+                node.trust_score = (node.trust_score + 0.5) / 2.0;
+                debug!("淡忘了关于玩家{}的旧证据", player_id);
+            }
+        }
+    }
+    
     /// 更新概率
     fn update_probabilities(&mut self, player_id: &str, evidence: &Evidence) -> AppResult<()> {
         if let Some(node) = self.nodes.get_mut(player_id) {
@@ -189,6 +226,125 @@ impl ReasoningEngine {
         Ok(())
     }
     
+    /// 根据死亡公示更新先验，暗牌局不提供任何信息
+    pub fn apply_death_reveal(&mut self, reveal: &DeathReveal) {
+        if let Some(node) = self.nodes.get_mut(&reveal.player_id) {
+            if let Some(role) = &reveal.revealed_role {
+                node.role_probabilities.clear();
+                node.role_probabilities.insert(role.clone(), 1.0);
+            }
+        }
+        if let Some(faction) = &reveal.revealed_faction {
+            self.apply_known_faction(&reveal.player_id, faction);
+            debug!("玩家{}的阵营已公开: {:?}", reveal.player_id, faction);
+        }
+    }
+    
+    /// 确定的阵营信息（翻牌或自己的查验），直接定死狼人概率和怀疑度
+    pub fn apply_known_faction(&mut self, player_id: &str, faction: &Faction) {
+        if let Some(node) = self.nodes.get_mut(player_id) {
+            let is_wolf = *faction == Faction::Werewolf;
+            node.faction_probability = if is_wolf { 1.0 } else { 0.0 };
+            node.suspicion_score = if is_wolf { 1.0 } else { 0.0 };
+        }
+    }
+    
+    /// 分析发言中的打断：被质疑的发言者略增嫌疑，打断者本身也留下攻击性记录
+    pub fn analyze_objection(&mut self, objector: String, speaker: String) -> AppResult<()> {
+        let challenged = Evidence {
+            evidence_type: EvidenceType::SpeechAnalysis,
+            confidence: 0.75,
+            source: "objection".to_string(),
+            description: format!("{}的发言被{}当场质疑", speaker, objector),
+            weight: 0.2,
+        };
+        self.add_evidence(speaker.clone(), challenged)?;
+
+        let aggressive = Evidence {
+            evidence_type: EvidenceType::AggressiveBehavior,
+            confidence: 0.5,
+            source: "objection".to_string(),
+            description: format!("{}打断了{}的发言", objector, speaker),
+            weight: 0.1,
+        };
+        self.add_evidence(objector, aggressive)
+    }
+
+    /// 分析投票意向（非正式，权重低于正式投票）
+    pub fn analyze_vote_intent(&mut self, voter_id: String, target_id: String) -> AppResult<()> {
+        let evidence = Evidence {
+            evidence_type: EvidenceType::VotingPattern,
+            confidence: 0.5,
+            source: "vote_intent".to_string(),
+            description: format!("{}倾向投票给{}", voter_id, target_id),
+            weight: 0.1,
+        };
+        
+        self.add_evidence(voter_id, evidence)
+    }
+    
+    /// 分析身份声明，对跳的神职声明本身就是可疑信号
+    pub fn analyze_role_claim(&mut self, player_id: String, role: &RoleType, contested: bool) -> AppResult<()> {
+        if contested {
+            let evidence = Evidence {
+                evidence_type: EvidenceType::RoleClaimConsistency,
+                confidence: 0.6,
+                source: "claims_board".to_string(),
+                description: format!("{}声明{:?}但存在对跳", player_id, role),
+                weight: 0.5,
+            };
+            return self.add_evidence(player_id, evidence);
+        }
+
+        if *role != RoleType::Villager {
+            if let Some(node) = self.nodes.get_mut(&player_id) {
+                node.trust_score = (node.trust_score + 0.1).clamp(0.0, 1.0);
+            }
+        }
+        Ok(())
+    }
+
+    /// 分析公开的查验结果，报告者可信度决定影响大小
+    pub fn analyze_check_report(&mut self, reporter: &str, target: String, is_werewolf: bool, credibility: f32) -> AppResult<()> {
+        if is_werewolf {
+            let evidence = Evidence {
+                evidence_type: EvidenceType::NightResult,
+                confidence: credibility,
+                source: "claims_board".to_string(),
+                description: format!("{}报告{}为狼人", reporter, target),
+                weight: credibility,
+            };
+            return self.add_evidence(target, evidence);
+        }
+
+        if let Some(node) = self.nodes.get_mut(&target) {
+            node.trust_score = (node.trust_score + 0.2 * credibility).clamp(0.0, 1.0);
+            node.suspicion_score = (node.suspicion_score - 0.1 * credibility).clamp(0.0, 1.0);
+        }
+        Ok(())
+    }
+
+    /// 查验声明与公开事实矛盾，强烈指向悍跳的狼人
+    pub fn analyze_claim_inconsistency(&mut self, inconsistency: &Inconsistency) -> AppResult<()> {
+        let evidence = Evidence {
+            evidence_type: EvidenceType::LogicalInconsistency,
+            confidence: 0.9,
+            source: "consistency_check".to_string(),
+            description: inconsistency.description.clone(),
+            weight: 0.8,
+        };
+        
+        self.add_evidence(inconsistency.reporter.clone(), evidence)
+    }
+    
+    /// 获取指定候选人中最可疑的玩家
+    pub fn get_most_suspicious_among(&self, candidates: &[String]) -> Option<String> {
+        candidates.iter()
+            .filter_map(|id| self.nodes.get(id).map(|node| (id, node.suspicion_score)))
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(id, _)| id.clone())
+    }
+    
     /// 分析发言内容
     pub fn analyze_speech(&mut self, player_id: String, content: &str) -> AppResult<()> {
         let analysis = self.perform_speech_analysis(content);
@@ -207,57 +363,37 @@ impl ReasoningEngine {
     
     /// 执行发言分析
     fn perform_speech_analysis(&self, content: &str) -> SpeechAnalysisResult {
-        let content_lower = content.to_lowercase();
+        let tokens = lexicon::segment(&content.to_lowercase());
+        let words = Lexicon::global();
         let mut suspicion_weight: f32 = 0.0;
         let mut confidence: f32 = 0.5;
         let mut summary = String::new();
         
         // 检查可疑关键词
-        let suspicious_keywords = [
-            "一定是", "肯定是", "我觉得不是", "太明显了",
-            "这么简单", "显而易见", "不可能", "绝对"
-        ];
-        
-        let defensive_keywords = [
-            "我不是", "相信我", "为什么怀疑我", "我是好人",
-            "你们错了", "冤枉", "诬陷"
-        ];
-        
-        let aggressive_keywords = [
-            "一定是狼", "明显的狼", "狼人", "出他",
-            "投他", "他有问题"
-        ];
-        
-        // 检查可疑关键词
-        for keyword in &suspicious_keywords {
-            if content_lower.contains(keyword) {
-                suspicion_weight += 0.1;
-                summary.push_str(&format!("包含可疑词汇: {}; ", keyword));
-            }
+        for keyword in Lexicon::find(&words.suspicious, &tokens) {
+            suspicion_weight += 0.1;
+            summary.push_str(&format!("包含可疑词汇: {}; ", keyword));
         }
         
         // 检查防御性关键词
-        for keyword in &defensive_keywords {
-            if content_lower.contains(keyword) {
-                suspicion_weight += 0.2;
-                confidence += 0.1;
-                summary.push_str(&format!("防御性发言: {}; ", keyword));
-            }
+        for keyword in Lexicon::find(&words.defensive, &tokens) {
+            suspicion_weight += 0.2;
+            confidence += 0.1;
+            summary.push_str(&format!("防御性发言: {}; ", keyword));
         }
         
         // 检查攻击性关键词
-        for keyword in &aggressive_keywords {
-            if content_lower.contains(keyword) {
-                suspicion_weight += 0.05;
-                summary.push_str(&format!("攻击性发言: {}; ", keyword));
-            }
+        for keyword in Lexicon::find(&words.aggressive, &tokens) {
+            suspicion_weight += 0.05;
+            summary.push_str(&format!("攻击性发言: {}; ", keyword));
         }
         
-        // 分析发言长度
-        if content.len() > 200 {
+        // 分析发言长度（按字数）
+        let length = lexicon::char_len(content);
+        if length > 150 {
             suspicion_weight += 0.05;
             summary.push_str("发言较长，可能过度解释; ");
-        } else if content.len() < 20 {
+        } else if length < 8 {
             suspicion_weight += 0.1;
             summary.push_str("发言过短，可能隐藏信息; ");
         }
@@ -360,6 +496,30 @@ impl ReasoningEngine {
             .unwrap_or(0.5)
     }
     
+    /// 获取信任度
+    pub fn get_trust_score(&self, player_id: &str) -> f32 {
+        self.nodes.get(player_id)
+            .map(|node| node.trust_score)
+            .unwrap_or(0.5)
+    }
+
+    /// 获取怀疑度
+    pub fn get_suspicion_score(&self, player_id: &str) -> f32 {
+        self.nodes.get(player_id)
+            .map(|node| node.suspicion_score)
+            .unwrap_or(0.5)
+    }
+    
+    /// 对某名玩家分量最重的几条证据，按权重乘置信度从高到低排列
+    pub fn top_evidence(&self, player_id: &str, limit: usize) -> Vec<&Evidence> {
+        let mut evidence: Vec<&Evidence> = self.nodes.get(player_id)
+            .map(|node| node.evidence.iter().collect())
+            .unwrap_or_default();
+        evidence.sort_by(|a, b| (b.weight * b.confidence).total_cmp(&(a.weight * a.confidence)));
+        evidence.truncate(limit);
+        evidence
+    }
+    
     /// 获取推理分析报告
     pub fn get_analysis_report(&self) -> ReasoningReport {
         let mut player_analysis = Vec::new();
@@ -0,0 +1,216 @@
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use log::{info, warn};
+
+/// 模型文件所在子目录
+const MODEL_DIR: &str = "models/sentiment";
+const MODEL_FILE: &str = "model.onnx";
+const TOKENIZER_FILE: &str = "tokenizer.json";
+
+static CLASSIFIER: OnceLock<SentimentClassifier> = OnceLock::new();
+
+/// 本地情感/立场模型配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentimentConfig {
+    pub enabled: bool,
+    pub model_url: String,
+    pub tokenizer_url: String,
+    /// 模型输出的类别顺序
+    pub labels: Vec<String>,
+}
+
+impl Default for SentimentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_url: "https://huggingface.co/uer/roberta-base-finetuned-jd-binary-chinese/resolve/main/onnx/model.onnx".to_string(),
+            tokenizer_url: "https://huggingface.co/uer/roberta-base-finetuned-jd-binary-chinese/resolve/main/tokenizer.json".to_string(),
+            labels: vec!["negative".to_string(), "positive".to_string()],
+        }
+    }
+}
+
+/// 情感分类结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentimentScores {
+    /// 负面情绪概率
+    pub negative: f32,
+    /// 正面情绪概率
+    pub positive: f32,
+}
+
+impl SentimentScores {
+    /// 情绪标签，与关键词规则的取值保持一致
+    pub fn emotion(&self) -> String {
+        if self.negative > 0.8 {
+            "愤怒".to_string()
+        } else if self.negative > 0.6 {
+            "紧张".to_string()
+        } else if self.positive > 0.7 {
+            "自信".to_string()
+        } else {
+            "冷静".to_string()
+        }
+    }
+
+    /// 可信度：情绪越激动的发言越不可信
+    pub fn credibility(&self) -> f32 {
+        (0.7 - (self.negative - 0.5).max(0.0) * 0.6).clamp(0.0, 1.0)
+    }
+}
+
+/// 离线情感分类器（ONNX）
+pub struct SentimentClassifier {
+    #[cfg(feature = "local-sentiment")]
+    session: std::sync::Mutex<ort::session::Session>,
+    #[cfg(feature = "local-sentiment")]
+    tokenizer: tokenizers::Tokenizer,
+    labels: Vec<String>,
+}
+
+impl SentimentClassifier {
+    /// 模型存放目录
+    pub fn model_dir(data_dir: &Path) -> PathBuf {
+        data_dir.join(MODEL_DIR)
+    }
+
+    /// 模型文件是否已经下载
+    pub fn is_downloaded(data_dir: &Path) -> bool {
+        let dir = Self::model_dir(data_dir);
+        dir.join(MODEL_FILE).exists() && dir.join(TOKENIZER_FILE).exists()
+    }
+
+    /// 按需下载模型和分词器到数据目录
+    pub async fn download(data_dir: &Path, config: &SentimentConfig) -> AppResult<PathBuf> {
+        let dir = Self::model_dir(data_dir);
+        tokio::fs::create_dir_all(&dir).await?;
+
+        for (url, file) in [(&config.model_url, MODEL_FILE), (&config.tokenizer_url, TOKENIZER_FILE)] {
+            let path = dir.join(file);
+            if path.exists() {
+                continue;
+            }
+
+            info!("下载情感模型文件: {}", url);
+            let response = reqwest::get(url.as_str()).await?;
+            if !response.status().is_success() {
+                return Err(AppError::Network(format!("下载{}失败: {}", file, response.status())));
+            }
+            let bytes = response.bytes().await?;
+
+            // 先写临时文件再改名，避免中断后留下半个模型
+            let partial = dir.join(format!("{}.part", file));
+            tokio::fs::write(&partial, &bytes).await?;
+            tokio::fs::rename(&partial, &path).await?;
+        }
+
+        Ok(dir)
+    }
+
+    /// 从数据目录加载模型
+    #[cfg(feature = "local-sentiment")]
+    pub fn load(data_dir: &Path, config: &SentimentConfig) -> AppResult<Self> {
+        let dir = Self::model_dir(data_dir);
+        let session = ort::session::Session::builder()
+            .and_then(|builder| builder.commit_from_file(dir.join(MODEL_FILE)))
+            .map_err(|e| AppError::Config(format!("加载情感模型失败: {}", e)))?;
+        let tokenizer = tokenizers::Tokenizer::from_file(dir.join(TOKENIZER_FILE))
+            .map_err(|e| AppError::Config(format!("加载分词器失败: {}", e)))?;
+
+        Ok(Self {
+            session: std::sync::Mutex::new(session),
+            tokenizer,
+            labels: config.labels.clone(),
+        })
+    }
+
+    /// 未启用local-sentiment特性时不可用
+    #[cfg(not(feature = "local-sentiment"))]
+    pub fn load(_data_dir: &Path, _config: &SentimentConfig) -> AppResult<Self> {
+        Err(AppError::Config("未启用local-sentiment特性，无法加载本地情感模型".to_string()))
+    }
+
+    /// 启动时按配置加载（模型未下载时跳过）
+    pub fn init(data_dir: Option<&Path>, config: &SentimentConfig) {
+        let data_dir = match data_dir {
+            Some(dir) if config.enabled => dir,
+            _ => return,
+        };
+        if !Self::is_downloaded(data_dir) {
+            info!("情感模型尚未下载，使用关键词规则");
+            return;
+        }
+
+        match Self::load(data_dir, config) {
+            Ok(classifier) => Self::install(classifier),
+            Err(e) => warn!("情感模型不可用，使用关键词规则: {}", e),
+        }
+    }
+
+    /// 安装全局分类器，只有第一次生效
+    pub fn install(classifier: SentimentClassifier) {
+        if CLASSIFIER.set(classifier).is_err() {
+            warn!("情感模型已加载，忽略重复安装");
+        } else {
+            info!("本地情感模型已加载");
+        }
+    }
+
+    /// 全局分类器（未加载时为None）
+    pub fn global() -> Option<&'static SentimentClassifier> {
+        CLASSIFIER.get()
+    }
+
+    /// 对一段发言打分
+    #[cfg(feature = "local-sentiment")]
+    pub fn classify(&self, text: &str) -> AppResult<SentimentScores> {
+        let encoding = self.tokenizer.encode(text, true)
+            .map_err(|e| AppError::Unknown(format!("分词失败: {}", e)))?;
+        let ids: Vec<i64> = encoding.get_ids().iter().map(|&id| id as i64).collect();
+        let mask: Vec<i64> = encoding.get_attention_mask().iter().map(|&m| m as i64).collect();
+        let shape = [1usize, ids.len()];
+
+        let model_error = |e: ort::Error| AppError::Unknown(format!("情感模型推理失败: {}", e));
+        let input_ids = ort::value::Tensor::from_array((shape, ids)).map_err(model_error)?;
+        let attention_mask = ort::value::Tensor::from_array((shape, mask)).map_err(model_error)?;
+
+        let mut session = self.session.lock()
+            .map_err(|_| AppError::Unknown("情感模型被占用".to_string()))?;
+        let outputs = session.run(ort::inputs![
+            "input_ids" => input_ids,
+            "attention_mask" => attention_mask
+        ]).map_err(model_error)?;
+        let (_, logits) = outputs[0].try_extract_tensor::<f32>().map_err(model_error)?;
+
+        Ok(self.scores_from_logits(logits))
+    }
+
+    /// 未启用local-sentiment特性时不可用
+    #[cfg(not(feature = "local-sentiment"))]
+    pub fn classify(&self, _text: &str) -> AppResult<SentimentScores> {
+        Err(AppError::Config("未启用local-sentiment特性".to_string()))
+    }
+
+    /// softmax后按类别名取正负面概率
+    #[cfg_attr(not(feature = "local-sentiment"), allow(dead_code))]
+    fn scores_from_logits(&self, logits: &[f32]) -> SentimentScores {
+        let max = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let exp: Vec<f32> = logits.iter().map(|l| (l - max).exp()).collect();
+        let sum: f32 = exp.iter().sum::<f32>().max(f32::EPSILON);
+
+        let probability = |label: &str| {
+            self.labels.iter()
+                .position(|l| l == label)
+                .and_then(|index| exp.get(index))
+                .map(|e| e / sum)
+                .unwrap_or(0.0)
+        };
+
+        SentimentScores {
+            negative: probability("negative"),
+            positive: probability("positive"),
+        }
+    }
+}
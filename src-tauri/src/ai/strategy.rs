@@ -1,5 +1,8 @@
 use crate::error::{AppError, AppResult};
 use crate::ai::reasoning::ReasoningEngine;
+use crate::ai::difficulty::DifficultyProfile;
+use crate::ai::deception::DeceptionPlan;
+use crate::validator::ActionValidator;
 use crate::types::*;
 use serde::{Serialize, Deserialize};
 use rand::{thread_rng, Rng};
@@ -8,9 +11,12 @@ use log::{info, debug};
 /// 策略决策器
 #[derive(Debug)]
 pub struct StrategyEngine {
+    player_id: String,
     personality: AIPersonality,
     game_knowledge: GameKnowledge,
     current_strategy: Strategy,
+    profile: DifficultyProfile,
+    deception_plan: Option<DeceptionPlan>,
 }
 
 /// 游戏知识库
@@ -76,16 +82,35 @@ pub struct SpeechStrategy {
 
 impl StrategyEngine {
     /// 创建新的策略引擎
-    pub fn new(personality: AIPersonality, role: &Role) -> Self {
+    pub fn new(player_id: String, personality: AIPersonality, role: &Role) -> Self {
         let strategy = Self::generate_initial_strategy(&personality, role);
+        let deception_plan = (role.faction == Faction::Werewolf)
+            .then(|| DeceptionPlan::new(personality.traits.deception));
         
         Self {
+            player_id,
             personality,
             game_knowledge: GameKnowledge::new(),
             current_strategy: strategy,
+            profile: DifficultyProfile::default(),
+            deception_plan,
         }
     }
     
+    /// 设置难度配置
+    pub fn set_profile(&mut self, profile: DifficultyProfile) {
+        self.profile = profile;
+    }
+    
+    /// 性格漂移后重新确定策略倾向，保留已经确定的重点目标
+    pub fn update_personality(&mut self, personality: AIPersonality, role: &Role) {
+        let mut strategy = Self::generate_initial_strategy(&personality, role);
+        strategy.priority_targets = std::mem::take(&mut self.current_strategy.priority_targets);
+        strategy.avoid_targets = std::mem::take(&mut self.current_strategy.avoid_targets);
+        self.current_strategy = strategy;
+        self.personality = personality;
+    }
+    
     /// 生成初始策略
     fn generate_initial_strategy(personality: &AIPersonality, role: &Role) -> Strategy {
         let strategy_type = match role.faction {
@@ -143,7 +168,13 @@ impl StrategyEngine {
         _reasoning: &ReasoningEngine
     ) -> AppResult<Option<NightAction>> {
         match my_role.role_type {
-            RoleType::Werewolf => self.decide_werewolf_kill(game_state),
+            RoleType::Werewolf | RoleType::DreamWolf | RoleType::Gargoyle => {
+                let me = game_state.players.iter().find(|p| p.id == self.player_id);
+                match me {
+                    Some(me) if ActionValidator::can_kill(game_state, me) => self.decide_werewolf_kill(game_state),
+                    _ => Ok(None),
+                }
+            }
             RoleType::Seer => self.decide_seer_check(game_state),
             RoleType::Witch => self.decide_witch_action(game_state),
             RoleType::Guard => self.decide_guard_protect(game_state),
@@ -165,12 +196,48 @@ impl StrategyEngine {
         let target = &alive_players[rng.gen_range(0..alive_players.len())];
         
         Ok(Some(NightAction {
-            player: "werewolf".to_string(),
+            player: self.player_id.clone(),
             action: NightActionType::Kill,
             target: Some(target.id.clone()),
         }))
     }
     
+    /// 公开怀疑热度：只来自旁观者推理与发言中的投票意向，不使用狼队私有信息
+    pub fn public_suspicion_heat(game_state: &GameState, observer: &ReasoningEngine) -> std::collections::HashMap<String, f32> {
+        let intent_count = game_state.vote_intents.len().max(1) as f32;
+        
+        game_state.players.iter()
+            .filter(|p| p.is_alive)
+            .map(|p| {
+                let intents = game_state.vote_intents.values()
+                    .filter(|target| **target == p.id)
+                    .count() as f32;
+                let heat = observer.get_werewolf_probability(&p.id) * 0.7 + intents / intent_count * 0.3;
+                (p.id.clone(), heat.clamp(0.0, 1.0))
+            })
+            .collect()
+    }
+    
+    /// 击杀目标避刀：目标已被村民重点怀疑时，改刀热度最低的好人
+    pub fn veto_kill_target(
+        target: &str,
+        heat: &std::collections::HashMap<String, f32>,
+        threshold: f32,
+        game_state: &GameState
+    ) -> Option<String> {
+        let target_heat = heat.get(target).copied().unwrap_or(0.0);
+        if target_heat < threshold {
+            return None;
+        }
+        
+        game_state.players.iter()
+            .filter(|p| p.is_alive && p.faction == Faction::Villager && p.id != target)
+            .filter_map(|p| heat.get(&p.id).map(|h| (p.id.clone(), *h)))
+            .filter(|(_, h)| *h < threshold)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(id, _)| id)
+    }
+    
     /// 决定预言家查验目标
     fn decide_seer_check(&mut self, game_state: &GameState) -> AppResult<Option<NightAction>> {
         let alive_players: Vec<_> = game_state.players.iter()
@@ -185,7 +252,7 @@ impl StrategyEngine {
         let target = &alive_players[rng.gen_range(0..alive_players.len())];
         
         Ok(Some(NightAction {
-            player: "seer".to_string(),
+            player: self.player_id.clone(),
             action: NightActionType::Check,
             target: Some(target.id.clone()),
         }))
@@ -199,8 +266,13 @@ impl StrategyEngine {
     
     /// 决定守卫保护目标
     fn decide_guard_protect(&mut self, game_state: &GameState) -> AppResult<Option<NightAction>> {
+        if Self::guard_should_hold_back(game_state) {
+            return Ok(None);
+        }
+        
+        let blocked = ActionValidator::blocked_protect_target(game_state, &self.player_id);
         let alive_players: Vec<_> = game_state.players.iter()
-            .filter(|p| p.is_alive)
+            .filter(|p| p.is_alive && Some(p.id.as_str()) != blocked)
             .collect();
         
         if alive_players.is_empty() {
@@ -211,31 +283,98 @@ impl StrategyEngine {
         let target = &alive_players[rng.gen_range(0..alive_players.len())];
         
         Ok(Some(NightAction {
-            player: "guard".to_string(),
+            player: self.player_id.clone(),
             action: NightActionType::Protect,
             target: Some(target.id.clone()),
         }))
     }
     
+    /// 同守同救死时，首夜女巫大概率用解药，守卫空守以免守救叠加
+    pub fn guard_should_hold_back(game_state: &GameState) -> bool {
+        game_state.game_config.night_rules.guard_heal_conflict_kills && game_state.day <= 1
+    }
+    
+    /// 同守同救死时，无人对跳的预言家多半已被守卫守住，女巫不再去救
+    pub fn witch_should_heal(target: &str, game_state: &GameState) -> bool {
+        if !game_state.game_config.night_rules.guard_heal_conflict_kills {
+            return true;
+        }
+        
+        let board = &game_state.claims_board;
+        let likely_guarded = board.current_claim(target) == Some(&RoleType::Seer)
+            && !board.is_contested(&RoleType::Seer);
+        !likely_guarded
+    }
+    
+    /// 首夜有额外信息（金水或双查）时，预言家第一天就起跳报查验
+    pub fn seer_opening_guidance(results: &[SeerCheckResult], game_state: &GameState) -> Option<String> {
+        if game_state.day > 1 || game_state.game_config.night_rules.first_night_seer == FirstNightSeerRule::Standard {
+            return None;
+        }
+        if results.is_empty() {
+            return None;
+        }
+        
+        if results.iter().any(|r| r.is_werewolf) {
+            Some("首夜信息充足且有查杀，今天起跳预言家并报出查杀".to_string())
+        } else {
+            Some("首夜已握有多个好人信息，今天起跳预言家并报出金水，带领好人站边".to_string())
+        }
+    }
+    
     /// 更新策略
-    pub fn update_strategy(&mut self, _game_state: &GameState, _reasoning: &ReasoningEngine) {
+    pub fn update_strategy(&mut self, game_state: &GameState, reasoning: &ReasoningEngine) {
         debug!("更新AI策略");
+        
+        if let Some(plan) = &mut self.deception_plan {
+            plan.update(&self.player_id, game_state, reasoning);
+        }
+    }
+    
+    /// 发言前对照公开记录校验欺骗计划
+    pub fn validate_deception(&mut self, game_state: &GameState) {
+        if let Some(plan) = &mut self.deception_plan {
+            plan.validate(&self.player_id, game_state);
+        }
+    }
+    
+    /// 获取欺骗计划（仅狼人）
+    pub fn deception_plan(&self) -> Option<&DeceptionPlan> {
+        self.deception_plan.as_ref()
+    }
+    
+    /// 欺骗计划给出的发言要点，非狼人为空
+    pub fn deception_guidance(&self, game_state: &GameState) -> Vec<String> {
+        match &self.deception_plan {
+            Some(plan) => plan.speech_guidance(game_state, self.is_under_pressure(game_state)),
+            None => Vec::new(),
+        }
+    }
+    
+    /// 是否正被场上针对（有人意向投我或报我查杀）
+    fn is_under_pressure(&self, game_state: &GameState) -> bool {
+        game_state.vote_intents.values().any(|target| *target == self.player_id)
+            || game_state.votes.iter().any(|v| v.target == self.player_id)
+            || game_state.claims_board.check_reports.iter()
+                .any(|r| r.target == self.player_id && r.is_werewolf)
     }
     
     /// 生成发言策略
     pub fn generate_speech_strategy(
         &self,
-        _game_state: &GameState,
+        game_state: &GameState,
         _reasoning: &ReasoningEngine,
         speech_type: SpeechType
     ) -> SpeechStrategy {
-        let key_points = match speech_type {
+        let mut key_points = match speech_type {
             SpeechType::Accusation => vec!["指控某人".to_string()],
             SpeechType::Defense => vec!["为自己辩护".to_string()],
             SpeechType::Information => vec!["分享信息".to_string()],
             _ => vec!["一般发言".to_string()],
         };
         
+        key_points.extend(self.deception_guidance(game_state));
+        
         SpeechStrategy {
             strategy_type: self.current_strategy.strategy_type.clone(),
             target_players: vec![],
@@ -247,18 +386,47 @@ impl StrategyEngine {
     pub async fn decide_vote_target(
         &self,
         game_state: &GameState,
-        _reasoning: &ReasoningEngine
+        reasoning: &ReasoningEngine
     ) -> AppResult<Option<String>> {
         let alive_others: Vec<_> = game_state.players.iter()
             .filter(|p| p.is_alive && !p.is_ai)
             .collect();
             
-        if !alive_others.is_empty() {
-            let mut rng = thread_rng();
-            let target = &alive_others[rng.gen_range(0..alive_others.len())];
-            Ok(Some(target.id.clone()))
-        } else {
-            Ok(None)
+        if alive_others.is_empty() {
+            return Ok(None);
+        }
+        
+        let candidates: Vec<String> = alive_others.iter().map(|p| p.id.clone()).collect();
+        Ok(self.choose_vote_among(&candidates, reasoning))
+    }
+    
+    /// 在候选人中按难度选出投票目标：按次优投票概率随机投，否则做蒙特卡洛模拟
+    pub fn choose_vote_among(&self, candidates: &[String], reasoning: &ReasoningEngine) -> Option<String> {
+        let mut rng = thread_rng();
+        if !candidates.is_empty() && rng.gen::<f32>() < self.profile.suboptimal_vote_rate {
+            let target = &candidates[rng.gen_range(0..candidates.len())];
+            debug!("按难度配置做出随机投票: {}", target);
+            return Some(target.clone());
         }
+        self.monte_carlo_vote(candidates, reasoning)
+    }
+    
+    /// 蒙特卡洛模拟：按狼人概率采样身份分布，选择被判定为狼人次数最多的玩家
+    pub(crate) fn monte_carlo_vote(&self, candidates: &[String], reasoning: &ReasoningEngine) -> Option<String> {
+        let mut rng = thread_rng();
+        let mut wolf_counts = vec![0u32; candidates.len()];
+        
+        for _ in 0..self.profile.monte_carlo_samples.max(1) {
+            for (index, candidate) in candidates.iter().enumerate() {
+                if rng.gen::<f32>() < reasoning.get_werewolf_probability(candidate) {
+                    wolf_counts[index] += 1;
+                }
+            }
+        }
+        
+        wolf_counts.iter()
+            .enumerate()
+            .max_by_key(|(_, count)| **count)
+            .map(|(index, _)| candidates[index].clone())
     }
 }
\ No newline at end of file
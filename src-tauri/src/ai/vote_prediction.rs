@@ -0,0 +1,200 @@
+use crate::ai::reasoning::{Evidence, EvidenceType, ReasoningEngine};
+use crate::error::AppResult;
+use crate::types::*;
+use crate::validator::ActionValidator;
+use serde::{Deserialize, Serialize};
+
+/// 狼人概率换算成预测倾向的系数
+const SUSPICION_SCALE: f32 = 4.0;
+/// 讨论中公开表态过的投票意向带来的额外倾向
+const INTENT_BONUS: f32 = 2.0;
+/// 弃票的基础倾向
+const ABSTAIN_LOGIT: f32 = 0.5;
+/// 惊讶度超过该值的投票才作为证据
+pub const SURPRISE_THRESHOLD: f32 = 0.75;
+
+/// 投票揭晓前对一名玩家选票的预测
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VotePrediction {
+    pub voter: String,
+    /// 每个可能的投票对象及其概率，None表示弃票，按概率从高到低排列
+    pub distribution: Vec<(Option<String>, f32)>,
+}
+
+impl VotePrediction {
+    /// 最可能的投票对象
+    pub fn most_likely(&self) -> Option<&Option<String>> {
+        self.distribution.first().map(|(target, _)| target)
+    }
+
+    /// 预测中投给某人（None为弃票）的概率
+    pub fn probability_of(&self, target: Option<&str>) -> f32 {
+        self.distribution.iter()
+            .find(|(candidate, _)| candidate.as_deref() == target)
+            .map(|(_, probability)| *probability)
+            .unwrap_or(0.0)
+    }
+}
+
+/// 一张选票相对预测的意外程度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteSurprise {
+    pub day: u32,
+    pub voter: String,
+    /// 实际投票对象，None表示弃票
+    pub target: Option<String>,
+    /// 揭晓前认为最可能的投票对象
+    pub predicted: Option<String>,
+    /// 预测中实际这一票的概率
+    pub probability: f32,
+    /// 惊讶度即预测误差：1减去实际这一票的预测概率
+    pub surprise: f32,
+}
+
+/// 只凭公开推理预测每名有投票权玩家的选票：越像狼、越被公开表态针对的人越可能被投
+pub fn predict_votes(engine: &ReasoningEngine, state: &GameState) -> Vec<VotePrediction> {
+    let allow_abstain = !state.game_config.vote_rules.mandatory;
+    state.players.iter()
+        .filter(|p| p.is_alive && ActionValidator::can_vote(state, p))
+        .map(|voter| {
+            let intent = state.vote_intents.get(&voter.id);
+            let mut logits: Vec<(Option<String>, f32)> = state.players.iter()
+                .filter(|p| p.is_alive && p.id != voter.id)
+                .filter(|p| state.pk_candidates.is_empty() || state.pk_candidates.contains(&p.id))
+                .map(|target| {
+                    let bonus = if intent == Some(&target.id) { INTENT_BONUS } else { 0.0 };
+                    let logit = engine.get_werewolf_probability(&target.id) * SUSPICION_SCALE + bonus;
+                    (Some(target.id.clone()), logit)
+                })
+                .collect();
+            if allow_abstain {
+                logits.push((None, ABSTAIN_LOGIT));
+            }
+            VotePrediction { voter: voter.id.clone(), distribution: softmax(logits) }
+        })
+        .collect()
+}
+
+/// 投票揭晓后逐张计算惊讶度，没有投票的玩家不计
+pub fn score_surprises(predictions: &[VotePrediction], outcome: &VoteOutcome) -> Vec<VoteSurprise> {
+    predictions.iter()
+        .filter_map(|prediction| {
+            let target = if outcome.abstentions.contains(&prediction.voter) {
+                None
+            } else {
+                let tally = outcome.tally.iter().find(|t| t.voters.contains(&prediction.voter))?;
+                Some(tally.target.clone())
+            };
+            let probability = prediction.probability_of(target.as_deref());
+            Some(VoteSurprise {
+                day: outcome.day,
+                voter: prediction.voter.clone(),
+                predicted: prediction.most_likely().cloned().flatten(),
+                target,
+                probability,
+                surprise: 1.0 - probability,
+            })
+        })
+        .collect()
+}
+
+/// 出人意料的投票记为投票者的证据：偏离公开局势的票往往出自场上看不到的信息
+pub fn analyze_vote_surprise(engine: &mut ReasoningEngine, surprise: &VoteSurprise) -> AppResult<()> {
+    if surprise.surprise < SURPRISE_THRESHOLD {
+        return Ok(());
+    }
+    let description = match &surprise.target {
+        Some(target) => format!("{}出人意料地投给了{}", surprise.voter, target),
+        None => format!("{}出人意料地弃票", surprise.voter),
+    };
+    let evidence = Evidence {
+        evidence_type: EvidenceType::VotingPattern,
+        confidence: surprise.surprise,
+        source: "vote_surprise".to_string(),
+        description,
+        weight: 0.2 * surprise.surprise,
+    };
+    engine.add_evidence(surprise.voter.clone(), evidence)
+}
+
+fn softmax(logits: Vec<(Option<String>, f32)>) -> Vec<(Option<String>, f32)> {
+    let max = logits.iter().map(|(_, logit)| *logit).fold(f32::MIN, f32::max);
+    let mut weighted: Vec<(Option<String>, f32)> = logits.into_iter()
+        .map(|(target, logit)| (target, (logit - max).exp()))
+        .collect();
+    let total: f32 = weighted.iter().map(|(_, weight)| weight).sum();
+    for (_, weight) in &mut weighted {
+        *weight /= total;
+    }
+    weighted.sort_by(|a, b| b.1.total_cmp(&a.1));
+    weighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::game_engine::GameEngine;
+
+    fn day_state() -> GameState {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        let mut state = engine.get_state().clone();
+        state.day = 1;
+        state.phase = GamePhase::Voting;
+        state
+    }
+
+    fn observer(state: &GameState) -> ReasoningEngine {
+        let mut engine = ReasoningEngine::new();
+        engine.initialize(state);
+        engine
+    }
+
+    #[test]
+    fn test_declared_intent_becomes_the_most_likely_vote() {
+        let mut state = day_state();
+        let ids: Vec<String> = state.players.iter().map(|p| p.id.clone()).collect();
+        state.vote_intents.insert(ids[0].clone(), ids[5].clone());
+
+        let predictions = predict_votes(&observer(&state), &state);
+        assert_eq!(predictions.len(), ids.len());
+        let first = predictions.iter().find(|p| p.voter == ids[0]).unwrap();
+        assert_eq!(first.most_likely(), Some(&Some(ids[5].clone())));
+        assert_eq!(first.probability_of(Some(&ids[0])), 0.0);
+        let total: f32 = first.distribution.iter().map(|(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_mandatory_votes_leave_out_abstention() {
+        let mut state = day_state();
+        state.game_config.vote_rules.mandatory = true;
+        let predictions = predict_votes(&observer(&state), &state);
+        assert!(predictions.iter().all(|p| p.probability_of(None) == 0.0));
+    }
+
+    #[test]
+    fn test_surprise_is_one_minus_the_predicted_probability() {
+        let prediction = |voter: &str| VotePrediction {
+            voter: voter.to_string(),
+            distribution: vec![(Some("x".to_string()), 0.9), (None, 0.1)],
+        };
+        let outcome = VoteOutcome {
+            day: 2,
+            tally: vec![VoteTally { target: "x".to_string(), count: 1, weight: 1.0, voters: vec!["a".to_string()] }],
+            eliminated: Some("x".to_string()),
+            tied: Vec::new(),
+            abstentions: vec!["b".to_string()],
+            pk: false,
+            revote: false,
+        };
+
+        let surprises = score_surprises(&[prediction("a"), prediction("b"), prediction("c")], &outcome);
+        assert_eq!(surprises.len(), 2);
+        assert!((surprises[0].surprise - 0.1).abs() < 1e-6);
+        assert_eq!(surprises[1].target, None);
+        assert!((surprises[1].surprise - 0.9).abs() < 1e-6);
+        assert_eq!(surprises[1].predicted.as_deref(), Some("x"));
+    }
+}
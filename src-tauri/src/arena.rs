@@ -0,0 +1,476 @@
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+use crate::events::GameEvent;
+use crate::game_engine::GameEngine;
+use crate::llm::LLMManager;
+use crate::night::NightRound;
+use crate::plugins::{PluginHost, SeatView};
+use crate::types::*;
+use crate::utils;
+use crate::validator::ActionValidator;
+use chrono::Utc;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+use log::{info, warn};
+
+/// 单次对战最多的局数，模型策略每局要调用几十次模型
+pub const MAX_ARENA_GAMES: u32 = 200;
+/// 竞技场对局的人数
+const ARENA_PLAYERS: u8 = 9;
+/// 提示词中附带的最近发言条数
+const PROMPT_SPEECHES: usize = 12;
+/// 模型发言的字数上限
+const SPEECH_MAX_CHARS: usize = 80;
+/// 95%置信区间
+const Z_95: f64 = 1.96;
+
+/// 参与对战的一方策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArenaPolicy {
+    /// 报告中显示的名称
+    pub label: String,
+    #[serde(flatten)]
+    pub kind: PolicyKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PolicyKind {
+    /// 随机合法行动，作为基线
+    Random,
+    /// 语言模型决策；未指定模型时使用当前配置，strategy_prompt附加在每个提示词前
+    Model {
+        #[serde(default)]
+        llm: Option<Box<LLMConfig>>,
+        #[serde(default)]
+        strategy_prompt: Option<String>,
+    },
+    /// 插件目录中的WASM策略插件
+    Plugin { name: String },
+}
+
+/// 一方策略的战绩
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyRecord {
+    pub label: String,
+    pub wins: u32,
+    pub games: u32,
+    pub win_rate: f64,
+    /// 胜率的95% Wilson置信区间
+    pub ci_low: f64,
+    pub ci_high: f64,
+    pub wolf_wins: u32,
+    pub wolf_games: u32,
+    pub villager_wins: u32,
+    pub villager_games: u32,
+    /// 模型或插件没有给出合法决策、改用随机行动的次数
+    pub fallback_decisions: u32,
+}
+
+impl PolicyRecord {
+    fn record(&mut self, faction: &Faction, won: bool) {
+        self.games += 1;
+        match faction {
+            Faction::Werewolf => {
+                self.wolf_games += 1;
+                self.wolf_wins += won as u32;
+            }
+            Faction::Villager => {
+                self.villager_games += 1;
+                self.villager_wins += won as u32;
+            }
+        }
+        self.wins += won as u32;
+        self.win_rate = self.wins as f64 / self.games as f64;
+        (self.ci_low, self.ci_high) = wilson_interval(self.wins, self.games);
+    }
+}
+
+/// 对战报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArenaReport {
+    pub games: u32,
+    pub policy_a: PolicyRecord,
+    pub policy_b: PolicyRecord,
+    /// 超过天数上限仍未分出胜负的局数，不计入胜率
+    pub unfinished: u32,
+    pub total_millis: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    A,
+    B,
+}
+
+struct Contender {
+    policy: ArenaPolicy,
+    llm: Option<LLMManager>,
+    record: PolicyRecord,
+}
+
+/// 两种策略对战：每两局共用同一个开局（身份、座位都相同），第二局双方交换阵营，
+/// 抵消座位和身份分配带来的偏差
+pub struct Arena {
+    a: Contender,
+    b: Contender,
+    plugin_dir: Option<PathBuf>,
+}
+
+impl Arena {
+    pub fn new(policy_a: ArenaPolicy, policy_b: ArenaPolicy, default_llm: LLMConfig, plugin_dir: Option<PathBuf>) -> Self {
+        let contender = |policy: ArenaPolicy| {
+            let llm = match &policy.kind {
+                PolicyKind::Model { llm, .. } => Some(LLMManager::new(llm.as_deref().unwrap_or(&default_llm).clone(), vec![])),
+                _ => None,
+            };
+            let record = PolicyRecord { label: policy.label.clone(), ..Default::default() };
+            Contender { policy, llm, record }
+        };
+        Self { a: contender(policy_a), b: contender(policy_b), plugin_dir }
+    }
+
+    pub async fn run(mut self, games: u32) -> AppResult<ArenaReport> {
+        if games == 0 || games > MAX_ARENA_GAMES {
+            return Err(AppError::Config(format!("对局数需在1到{}之间", MAX_ARENA_GAMES)));
+        }
+        let started = Instant::now();
+        let mut unfinished = 0;
+        let mut opening = deal_opening()?;
+
+        for game in 0..games {
+            if game > 0 && game % 2 == 0 {
+                opening = deal_opening()?;
+            }
+            // 偶数局A执狼，奇数局交换；同一开局的两局随机兜底也用同一种子
+            let wolves = if game % 2 == 0 { Side::A } else { Side::B };
+            let rng = StdRng::seed_from_u64((game / 2) as u64);
+            match self.play_game(opening.fork()?, rng, wolves).await? {
+                Some(winner) => {
+                    let (wolf_side, village_side) = match wolves {
+                        Side::A => (&mut self.a, &mut self.b),
+                        Side::B => (&mut self.b, &mut self.a),
+                    };
+                    wolf_side.record.record(&Faction::Werewolf, winner == Faction::Werewolf);
+                    village_side.record.record(&Faction::Villager, winner == Faction::Villager);
+                }
+                None => unfinished += 1,
+            }
+        }
+
+        info!(
+            "竞技场结束: {} {:.1}% vs {} {:.1}%",
+            self.a.record.label, self.a.record.win_rate * 100.0, self.b.record.label, self.b.record.win_rate * 100.0
+        );
+        Ok(ArenaReport {
+            games,
+            policy_a: self.a.record,
+            policy_b: self.b.record,
+            unfinished,
+            total_millis: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// 从开局打到结束，返回获胜阵营；超过天数上限返回None
+    async fn play_game(&mut self, mut engine: GameEngine, mut rng: StdRng, wolves: Side) -> AppResult<Option<Faction>> {
+        let side_of = |player: &Player| if (player.role.faction == Faction::Werewolf) == (wolves == Side::A) { Side::A } else { Side::B };
+        let mut plugins = PluginHost::new();
+        if let Some(plugin_dir) = &self.plugin_dir {
+            plugins.set_plugin_dir(plugin_dir.clone());
+        }
+        let assignments: HashMap<u8, String> = engine.get_state().players.iter()
+            .filter_map(|p| match &self.contender(side_of(p)).policy.kind {
+                PolicyKind::Plugin { name } => Some((p.seat, name.clone())),
+                _ => None,
+            })
+            .collect();
+        plugins.load_seats(&assignments, &engine.get_state().players);
+
+        let max_days = ARENA_PLAYERS as u32 * 3;
+        while engine.get_state().phase != GamePhase::GameOver {
+            if engine.get_state().day > max_days {
+                warn!("竞技场对局{}天仍未结束", max_days);
+                return Ok(None);
+            }
+            match engine.get_state().phase {
+                GamePhase::Night => self.play_night(&mut engine, &mut plugins, &mut rng, &side_of).await,
+                GamePhase::DayDiscussion => self.play_discussion(&mut engine, &mut plugins, &side_of).await,
+                GamePhase::Voting => self.play_votes(&mut engine, &mut plugins, &mut rng, &side_of).await,
+                _ => {}
+            }
+            engine.next_phase()?;
+            let state = engine.get_state();
+            plugins.observe(&GameEvent::PhaseChanged { day: state.day, phase: state.phase.clone() });
+        }
+        Ok(engine.get_state().winner.clone())
+    }
+
+    fn contender(&self, side: Side) -> &Contender {
+        match side {
+            Side::A => &self.a,
+            Side::B => &self.b,
+        }
+    }
+
+    fn contender_mut(&mut self, side: Side) -> &mut Contender {
+        match side {
+            Side::A => &mut self.a,
+            Side::B => &mut self.b,
+        }
+    }
+
+    async fn play_night(&mut self, engine: &mut GameEngine, plugins: &mut PluginHost, rng: &mut StdRng, side_of: &impl Fn(&Player) -> Side) {
+        let mut round = NightRound::new(engine.get_state());
+        while let Some((_, player)) = round.next_step(engine.get_state()) {
+            let player = player.clone();
+            round.advance();
+
+            let side = side_of(&player);
+            let decided = match &self.contender(side).policy.kind {
+                PolicyKind::Random => None,
+                PolicyKind::Plugin { .. } => plugins.decide_night_action(SeatView::new(engine, &player))
+                    .and_then(|value| parse_night_action(&player, &value)),
+                PolicyKind::Model { .. } => {
+                    let task = format!(
+                        "请决定今晚的行动，只输出JSON：{{\"action\": \"{}\", \"target\": \"玩家ID\"}}，不行动时target为null",
+                        night_action_names(&player.role.role_type).join("或")
+                    );
+                    self.ask_model(side, engine, &player, &task).await
+                        .and_then(|response| extract_json(&response))
+                        .and_then(|value| parse_night_action(&player, &value))
+                }
+            };
+            let action = match decided {
+                Some(action) => Some(action),
+                None => {
+                    self.count_fallback(side);
+                    random_night_action(engine, &player, rng)
+                }
+            };
+            if let Some(action) = action {
+                if let Err(e) = engine.execute_night_action(action) {
+                    warn!("竞技场夜晚行动被拒绝: {}", e);
+                }
+            }
+        }
+    }
+
+    async fn play_discussion(&mut self, engine: &mut GameEngine, plugins: &mut PluginHost, side_of: &impl Fn(&Player) -> Side) {
+        let mut speakers: Vec<Player> = engine.get_state().players.iter().filter(|p| p.is_alive).cloned().collect();
+        speakers.sort_by_key(|p| p.seat);
+
+        for player in speakers {
+            let side = side_of(&player);
+            let content = match &self.contender(side).policy.kind {
+                PolicyKind::Random => None,
+                PolicyKind::Plugin { .. } => plugins.decide_speech(SeatView::new(engine, &player)).and_then(|intent| intent.text),
+                PolicyKind::Model { .. } => {
+                    let task = format!("轮到你白天发言，直接输出不超过{}字的发言内容", SPEECH_MAX_CHARS);
+                    self.ask_model(side, engine, &player, &task).await
+                }
+            };
+            let content = match content.map(|c| utils::trim_to_sentence(c.trim(), SPEECH_MAX_CHARS)).filter(|c| !c.is_empty()) {
+                Some(content) => content,
+                None => continue,
+            };
+
+            engine.set_current_speaker(Some(player.id.clone()));
+            let message = ChatMessage {
+                id: utils::generate_id(),
+                sender: player.id.clone(),
+                content: content.clone(),
+                timestamp: Utc::now(),
+                message_type: MessageType::AI,
+            };
+            if engine.add_chat_message(message).is_ok() {
+                let sequence = engine.get_speech_history().len() as u64;
+                plugins.observe(&GameEvent::SpeechDelivered { sequence, speaker: player.id.clone(), content });
+            }
+        }
+        engine.set_current_speaker(None);
+    }
+
+    async fn play_votes(&mut self, engine: &mut GameEngine, plugins: &mut PluginHost, rng: &mut StdRng, side_of: &impl Fn(&Player) -> Side) {
+        let state = engine.get_state();
+        let voters: Vec<Player> = state.players.iter().filter(|p| p.is_alive && ActionValidator::can_vote(state, p)).cloned().collect();
+
+        for voter in voters {
+            let side = side_of(&voter);
+            let decided = match &self.contender(side).policy.kind {
+                PolicyKind::Random => None,
+                PolicyKind::Plugin { .. } => plugins.decide_vote(SeatView::new(engine, &voter)).flatten(),
+                PolicyKind::Model { .. } => {
+                    let task = "请决定放逐投票，只输出JSON：{\"target\": \"玩家ID\"}";
+                    self.ask_model(side, engine, &voter, task).await
+                        .and_then(|response| extract_json(&response))
+                        .and_then(|value| value.get("target").and_then(|t| t.as_str()).map(str::to_string))
+                }
+            };
+            let target = match decided.filter(|target| is_vote_candidate(engine.get_state(), target)) {
+                Some(target) => Some(target),
+                None => {
+                    self.count_fallback(side);
+                    random_target(engine.get_state(), &voter, rng)
+                }
+            };
+            if let Some(target) = target {
+                if let Err(e) = engine.vote(voter.id.clone(), Some(target)) {
+                    warn!("竞技场投票被拒绝: {}", e);
+                }
+            }
+        }
+    }
+
+    fn count_fallback(&mut self, side: Side) {
+        if !matches!(self.contender(side).policy.kind, PolicyKind::Random) {
+            self.contender_mut(side).record.fallback_decisions += 1;
+        }
+    }
+
+    /// 以该座位的视角向模型提问，失败时返回None
+    async fn ask_model(&self, side: Side, engine: &GameEngine, player: &Player, task: &str) -> Option<String> {
+        let contender = self.contender(side);
+        let (llm, strategy_prompt) = match (&contender.llm, &contender.policy.kind) {
+            (Some(llm), PolicyKind::Model { strategy_prompt, .. }) => (llm, strategy_prompt.as_deref().unwrap_or_default()),
+            _ => return None,
+        };
+
+        let view = serde_json::to_string(&SeatView::new(engine, player)).ok()?;
+        let history = engine.get_speech_history();
+        let speeches: Vec<String> = history.iter()
+            .skip(history.len().saturating_sub(PROMPT_SPEECHES))
+            .map(|s| format!("{}: {}", engine.find_player(&s.speaker).map(Player::display_name).unwrap_or_default(), s.content))
+            .collect();
+        let prompt = format!(
+            "{}\n你正在玩狼人杀，你是{}号，身份是{}。\n局面（JSON）：{}\n最近发言：\n{}\n\n{}",
+            strategy_prompt, player.seat, utils::get_role_name(&player.role.role_type), view, speeches.join("\n"), task
+        );
+
+        match llm.generate_with_fallback(prompt).await {
+            Ok(response) => Some(response),
+            Err(e) => {
+                warn!("竞技场模型调用失败（{}）: {}", contender.policy.label, e);
+                None
+            }
+        }
+    }
+}
+
+/// 发一副新牌并开局，所有座位都交给AI
+fn deal_opening() -> AppResult<GameEngine> {
+    let mut config = AppConfig::default().game;
+    config.total_players = ARENA_PLAYERS;
+    config.night_timing = NightTimingConfig { min_duration: 0, max_padding: 0 };
+
+    let mut engine = GameEngine::new(config)?;
+    engine.initialize_game()?;
+    let humans: Vec<String> = engine.get_state().players.iter().filter(|p| !p.is_ai).map(|p| p.id.clone()).collect();
+    for player_id in humans {
+        let personality = engine.generate_ai_personality();
+        engine.replace_human_with_ai(&player_id, personality)?;
+    }
+    engine.start_game()?;
+    Ok(engine)
+}
+
+/// 该身份可用的夜晚行动
+fn night_action_names(role: &RoleType) -> &'static [&'static str] {
+    match role {
+        RoleType::Werewolf | RoleType::DreamWolf | RoleType::Gargoyle => &["kill"],
+        RoleType::Seer => &["check"],
+        RoleType::Guard => &["protect"],
+        RoleType::Witch => &["heal", "poison"],
+        _ => &[],
+    }
+}
+
+fn parse_night_action(player: &Player, value: &serde_json::Value) -> Option<NightAction> {
+    let action = match value.get("action")?.as_str()? {
+        "kill" => NightActionType::Kill,
+        "check" => NightActionType::Check,
+        "heal" => NightActionType::Heal,
+        "protect" => NightActionType::Protect,
+        "poison" => NightActionType::Poison,
+        _ => return None,
+    };
+    Some(NightAction {
+        player: player.id.clone(),
+        action,
+        target: value.get("target").and_then(|t| t.as_str()).map(str::to_string),
+    })
+}
+
+/// 取回复中第一个JSON对象，容忍模型包在代码块或说明文字里
+fn extract_json(response: &str) -> Option<serde_json::Value> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    serde_json::from_str(response.get(start..=end)?).ok()
+}
+
+fn random_target(state: &GameState, player: &Player, rng: &mut StdRng) -> Option<String> {
+    let candidates: Vec<&Player> = state.players.iter()
+        .filter(|p| p.id != player.id && !player.knows_teammate(p) && is_vote_candidate(state, &p.id))
+        .collect();
+    candidates.choose(rng).map(|p| p.id.clone())
+}
+
+/// 存活且可以被投票的玩家，PK后的重新投票只能投平票的玩家
+fn is_vote_candidate(state: &GameState, player_id: &str) -> bool {
+    state.players.iter().any(|p| p.id == player_id && p.is_alive)
+        && (state.pk_candidates.is_empty() || state.pk_candidates.iter().any(|id| id == player_id))
+}
+
+fn random_night_action(engine: &GameEngine, player: &Player, rng: &mut StdRng) -> Option<NightAction> {
+    let (action, target) = match player.role.role_type {
+        RoleType::Werewolf | RoleType::DreamWolf | RoleType::Gargoyle => (NightActionType::Kill, random_target(engine.get_state(), player, rng)),
+        RoleType::Seer => (NightActionType::Check, random_target(engine.get_state(), player, rng)),
+        RoleType::Guard => {
+            let blocked = ActionValidator::blocked_protect_target(engine.get_state(), &player.id);
+            (NightActionType::Protect, random_target(engine.get_state(), player, rng).filter(|t| Some(t.as_str()) != blocked))
+        }
+        RoleType::Witch if engine.witch_potions(&player.id).antidote => (NightActionType::Heal, engine.pending_kill_targets().first().cloned()),
+        _ => return None,
+    };
+    Some(NightAction { player: player.id.clone(), action, target: Some(target?) })
+}
+
+/// 胜率的Wilson置信区间，样本少时比正态近似稳定
+pub fn wilson_interval(wins: u32, games: u32) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = games as f64;
+    let p = wins as f64 / n;
+    let z2 = Z_95 * Z_95;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wilson_interval() {
+        let (low, high) = wilson_interval(50, 100);
+        assert!((low - 0.4038).abs() < 1e-3 && (high - 0.5962).abs() < 1e-3);
+        let (low, high) = wilson_interval(0, 10);
+        assert_eq!(low, 0.0);
+        assert!(high > 0.0 && high < 0.35);
+    }
+
+    #[tokio::test]
+    async fn test_random_policies_balance_factions() {
+        let random = |label: &str| ArenaPolicy { label: label.to_string(), kind: PolicyKind::Random };
+        let report = Arena::new(random("a"), random("b"), AppConfig::default().llm, None).run(4).await.unwrap();
+        assert_eq!(report.policy_a.wolf_games, report.policy_a.villager_games);
+        assert_eq!(report.policy_a.games + report.unfinished, 4);
+        assert_eq!(report.policy_a.wins + report.policy_b.wins, report.policy_a.games);
+    }
+}
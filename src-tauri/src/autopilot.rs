@@ -0,0 +1,145 @@
+use crate::speech_style::SpeechLanguage;
+use crate::types::*;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// 真人挂机检测与保守托管：连续两个阶段切换时都超过设定时长没有操作，
+/// 座位交给只做最少动作的托管，直到玩家主动收回
+#[derive(Debug, Clone)]
+pub struct Autopilot {
+    /// 开局或托管结束的时刻，玩家还没有任何操作时以此为准
+    since: Instant,
+    last_input: HashMap<String, Instant>,
+    /// 玩家ID -> 连续空闲的阶段数
+    idle_phases: HashMap<String, u32>,
+    active: HashSet<String>,
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Autopilot {
+    /// 判定挂机需要连续空闲的阶段数
+    pub const IDLE_PHASES: u32 = 2;
+
+    pub fn new() -> Self {
+        Self {
+            since: Instant::now(),
+            last_input: HashMap::new(),
+            idle_phases: HashMap::new(),
+            active: HashSet::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// 记录一次真人操作
+    pub fn touch(&mut self, player_id: &str) {
+        self.last_input.insert(player_id.to_string(), Instant::now());
+        self.idle_phases.remove(player_id);
+    }
+
+    pub fn is_active(&self, player_id: &str) -> bool {
+        self.active.contains(player_id)
+    }
+
+    /// 阶段切换时调用，返回本次新进入托管的玩家
+    pub fn on_phase_change(&mut self, config: &AutopilotConfig, humans: &[String]) -> Vec<String> {
+        if !config.enabled {
+            return Vec::new();
+        }
+        let threshold = Duration::from_secs(config.idle_seconds as u64);
+        let mut engaged = Vec::new();
+        for player_id in humans {
+            if self.active.contains(player_id) {
+                continue;
+            }
+            let last = self.last_input.get(player_id).copied().unwrap_or(self.since);
+            if last.elapsed() < threshold {
+                self.idle_phases.remove(player_id);
+                continue;
+            }
+            let idle = self.idle_phases.entry(player_id.clone()).or_insert(0);
+            *idle += 1;
+            if *idle >= Self::IDLE_PHASES {
+                self.idle_phases.remove(player_id);
+                self.active.insert(player_id.clone());
+                engaged.push(player_id.clone());
+            }
+        }
+        engaged
+    }
+
+    /// 玩家收回控制，返回该座位此前是否处于托管中
+    pub fn release(&mut self, player_id: &str) -> bool {
+        self.touch(player_id);
+        self.active.remove(player_id)
+    }
+}
+
+/// 托管的投票：通常弃票；自己被推上台或规则不允许弃票时，跟票投给得票最多的其他玩家
+pub fn vote_target(state: &GameState, voter: &str) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for vote in state.votes.iter().filter(|v| !v.is_abstention()) {
+        *counts.entry(vote.target.as_str()).or_insert(0) += 1;
+    }
+    let top = counts.values().copied().max().unwrap_or(0);
+    let under_pressure = top > 0 && counts.get(voter) == Some(&top);
+    if !under_pressure && !state.game_config.vote_rules.mandatory {
+        return None;
+    }
+
+    // 票数相同时按ID取，结果可复现
+    let mut candidates: Vec<(&str, usize)> = counts.into_iter()
+        .filter(|(target, _)| *target != voter)
+        .collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    candidates.first()
+        .map(|(target, _)| target.to_string())
+        .or_else(|| {
+            state.players.iter()
+                .filter(|p| p.is_alive && p.id != voter)
+                .filter(|p| state.pk_candidates.is_empty() || state.pk_candidates.contains(&p.id))
+                .map(|p| p.id.clone())
+                .min()
+        })
+}
+
+/// 托管的发言：只表明自己暂时离开，不透露任何信息
+pub fn minimal_speech(language: SpeechLanguage) -> &'static str {
+    if language == SpeechLanguage::EnUs {
+        "I'm away for now. Pass."
+    } else {
+        "暂时离开，过。"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engages_after_two_idle_phases() {
+        let config = AutopilotConfig { enabled: true, idle_seconds: 0 };
+        let humans = vec!["p1".to_string(), "p2".to_string()];
+        let mut autopilot = Autopilot::new();
+
+        assert!(autopilot.on_phase_change(&config, &humans).is_empty());
+        assert_eq!(autopilot.on_phase_change(&config, &humans), humans);
+        assert!(autopilot.is_active("p1"));
+
+        assert!(autopilot.release("p1"));
+        assert!(!autopilot.is_active("p1"));
+        assert!(!autopilot.release("p1"));
+
+        let disabled = AutopilotConfig { enabled: false, idle_seconds: 0 };
+        let mut autopilot = Autopilot::new();
+        autopilot.on_phase_change(&disabled, &humans);
+        assert!(autopilot.on_phase_change(&disabled, &humans).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::ai::{DifficultyProfile, ReasoningEngine, StrategyEngine};
+use crate::config::AppConfig;
+use crate::game_manager::GameManager;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// 基准测试覆盖的人数规模
+pub const BENCHMARK_SCALES: [u8; 4] = [6, 9, 12, 18];
+
+/// 场景中每名玩家的模拟发言，包含身份声明和查验，让推理走完整的分析路径
+const SAMPLE_SPEECHES: [&str; 4] = [
+    "我是预言家，昨晚查验{seat}号是狼，大家跟我投",
+    "我觉得{seat}号发言前后矛盾，有点像狼",
+    "我是好人，{seat}号的逻辑我认可",
+    "过，我再听听后面的发言",
+];
+
+/// 单项基准的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub name: String,
+    pub players: u8,
+    pub iterations: u32,
+    pub mean_micros: f64,
+    pub p95_micros: f64,
+}
+
+/// 一次完整基准测试的报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub results: Vec<BenchmarkResult>,
+    pub total_millis: u64,
+}
+
+/// 某个人数规模下的基准场景：已经开局，并带有一轮发言和投票
+///
+/// criterion基准（benches/ai_decision.rs）与run_benchmark命令共用同一组场景
+pub struct BenchmarkScenario {
+    players: u8,
+    manager: GameManager,
+    state: GameState,
+    speeches: Vec<(String, String)>,
+    votes: Vec<(String, String)>,
+    reasoning: ReasoningEngine,
+    strategy: StrategyEngine,
+}
+
+impl BenchmarkScenario {
+    pub async fn new(players: u8) -> Result<Self, String> {
+        let mut config = AppConfig::default().game;
+        config.total_players = players;
+
+        let mut manager = GameManager::new();
+        let state = manager.create_game(config).await.map_err(|e| e.to_string())?;
+
+        let seats: Vec<u8> = state.players.iter().map(|p| p.seat).collect();
+        let speeches: Vec<(String, String)> = state.players.iter()
+            .enumerate()
+            .map(|(index, player)| {
+                let target = seats[(index + 1) % seats.len()];
+                let template = SAMPLE_SPEECHES[index % SAMPLE_SPEECHES.len()];
+                (player.id.clone(), template.replace("{seat}", &target.to_string()))
+            })
+            .collect();
+        let votes: Vec<(String, String)> = state.players.iter()
+            .enumerate()
+            .map(|(index, player)| (player.id.clone(), state.players[(index + 1) % state.players.len()].id.clone()))
+            .collect();
+
+        let observer = state.players.iter()
+            .find(|p| p.is_ai)
+            .ok_or_else(|| "场景中没有AI玩家".to_string())?;
+        let personality = observer.personality.clone()
+            .ok_or_else(|| "AI玩家缺少性格".to_string())?;
+        let mut strategy = StrategyEngine::new(observer.id.clone(), personality, &observer.role);
+        strategy.set_profile(DifficultyProfile::expert());
+
+        let mut scenario = Self {
+            players,
+            manager,
+            state,
+            speeches,
+            votes,
+            reasoning: ReasoningEngine::new(),
+            strategy,
+        };
+        scenario.reasoning = scenario.analyze_round();
+        Ok(scenario)
+    }
+
+    pub fn players(&self) -> u8 {
+        self.players
+    }
+
+    /// 推理更新：从头分析一轮发言和投票，返回最可疑的玩家
+    pub fn reasoning_update(&self) -> Option<String> {
+        self.analyze_round().get_most_suspicious_player()
+    }
+
+    fn analyze_round(&self) -> ReasoningEngine {
+        let mut reasoning = ReasoningEngine::new();
+        reasoning.set_profile(DifficultyProfile::expert());
+        reasoning.initialize(&self.state);
+        for (speaker, content) in &self.speeches {
+            let _ = reasoning.analyze_speech(speaker.clone(), content);
+        }
+        for (voter, target) in &self.votes {
+            let _ = reasoning.analyze_vote(voter.clone(), target.clone());
+        }
+        reasoning
+    }
+
+    /// 蒙特卡洛投票模拟，按专家难度的采样次数
+    pub fn monte_carlo(&self) -> Option<String> {
+        let candidates: Vec<String> = self.state.players.iter().map(|p| p.id.clone()).collect();
+        self.strategy.monte_carlo_vote(&candidates, &self.reasoning)
+    }
+
+    /// 为每名AI构建发言提示词，返回提示词总长度
+    pub fn prompt_construction(&self) -> usize {
+        self.state.players.iter()
+            .filter(|p| p.is_ai)
+            .filter_map(|p| self.manager.build_speech_prompt(p, &self.state).ok())
+            .map(|prompt| prompt.len())
+            .sum()
+    }
+
+    /// 游戏状态的序列化与反序列化往返
+    pub fn serialization(&self) -> usize {
+        let json = serde_json::to_string(&self.state).unwrap_or_default();
+        let restored: Option<GameState> = serde_json::from_str(&json).ok();
+        json.len() + restored.map(|s| s.players.len()).unwrap_or(0)
+    }
+}
+
+/// 依次在各个人数规模下测量全部基准项
+pub async fn run_suite(iterations: u32) -> Result<BenchmarkReport, String> {
+    let started = Instant::now();
+    let iterations = iterations.max(1);
+    let mut results = Vec::new();
+
+    for players in BENCHMARK_SCALES {
+        let scenario = BenchmarkScenario::new(players).await?;
+        results.push(measure("reasoning_update", &scenario, iterations, |s| { s.reasoning_update(); }));
+        results.push(measure("monte_carlo", &scenario, iterations, |s| { s.monte_carlo(); }));
+        results.push(measure("prompt_construction", &scenario, iterations, |s| { s.prompt_construction(); }));
+        results.push(measure("serialization", &scenario, iterations, |s| { s.serialization(); }));
+    }
+
+    Ok(BenchmarkReport {
+        results,
+        total_millis: started.elapsed().as_millis() as u64,
+    })
+}
+
+fn measure(name: &str, scenario: &BenchmarkScenario, iterations: u32, run: impl Fn(&BenchmarkScenario)) -> BenchmarkResult {
+    let mut samples: Vec<f64> = (0..iterations)
+        .map(|_| {
+            let started = Instant::now();
+            run(scenario);
+            started.elapsed().as_secs_f64() * 1_000_000.0
+        })
+        .collect();
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let p95_index = ((samples.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+    BenchmarkResult {
+        name: name.to_string(),
+        players: scenario.players(),
+        iterations,
+        mean_micros: samples.iter().sum::<f64>() / samples.len() as f64,
+        p95_micros: samples[p95_index.min(samples.len() - 1)],
+    }
+}
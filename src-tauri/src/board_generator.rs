@@ -0,0 +1,209 @@
+use crate::types::{Faction, RoleType};
+use crate::utils;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 战绩局数达到该值的板子才算有实测胜率
+pub const MIN_MEASURED_GAMES: u32 = 10;
+/// 胜率偏离五成时权重衰减的速度：偏10%权重约剩三成，偏25%约剩二十分之一
+const BALANCE_SHARPNESS: f32 = 12.0;
+/// 没有实测战绩的板子相对实测均衡板子的权重
+const UNMEASURED_WEIGHT: f32 = 0.2;
+
+/// 板子难度，决定可用的神职和特殊狼
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BoardDifficulty {
+    /// 只有预言家、女巫、猎人
+    Beginner,
+    /// 加入守卫
+    #[default]
+    Standard,
+    /// 再加入不参与刀人的梦狼、石像鬼
+    Advanced,
+}
+
+impl BoardDifficulty {
+    /// 预言家之外可选的神职
+    fn optional_gods(&self) -> &'static [RoleType] {
+        match self {
+            BoardDifficulty::Beginner => &[RoleType::Witch, RoleType::Hunter],
+            BoardDifficulty::Standard | BoardDifficulty::Advanced => &[RoleType::Witch, RoleType::Hunter, RoleType::Guard],
+        }
+    }
+
+    fn passive_wolves(&self) -> &'static [RoleType] {
+        match self {
+            BoardDifficulty::Advanced => &[RoleType::DreamWolf, RoleType::Gargoyle],
+            _ => &[],
+        }
+    }
+}
+
+/// 一个板子在往局里的战绩
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BoardStats {
+    pub games: u32,
+    pub villager_wins: u32,
+    pub werewolf_wins: u32,
+}
+
+impl BoardStats {
+    pub fn villager_win_rate(&self) -> Option<f32> {
+        (self.games > 0).then(|| self.villager_wins as f32 / self.games as f32)
+    }
+
+    pub fn is_measured(&self) -> bool {
+        self.games >= MIN_MEASURED_GAMES
+    }
+}
+
+/// 生成的板子及其实测平衡数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposedBoard {
+    pub total_players: u8,
+    pub difficulty: BoardDifficulty,
+    pub role_distribution: HashMap<RoleType, u8>,
+    pub stats: BoardStats,
+    /// 好人阵营的实测胜率，局数不足时为None
+    pub villager_win_rate: Option<f32>,
+    /// 参与抽样的候选板子数，以及其中有实测战绩的个数
+    pub candidates: usize,
+    pub measured_candidates: usize,
+}
+
+/// 板子的规范写法，身份按名称排序，用来对齐不同对局里的同一种板子
+pub fn board_key(distribution: &HashMap<RoleType, u8>) -> String {
+    let sorted: BTreeMap<String, u8> = distribution.iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(role, count)| (format!("{:?}", role), *count))
+        .collect();
+    sorted.iter()
+        .map(|(role, count)| format!("{}:{}", role, count))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 按板子汇总往局战绩
+pub fn aggregate(results: &[(HashMap<RoleType, u8>, Faction)]) -> HashMap<String, BoardStats> {
+    let mut stats: HashMap<String, BoardStats> = HashMap::new();
+    for (distribution, winner) in results {
+        let entry = stats.entry(board_key(distribution)).or_default();
+        entry.games += 1;
+        match winner {
+            Faction::Villager => entry.villager_wins += 1,
+            Faction::Werewolf => entry.werewolf_wins += 1,
+        }
+    }
+    stats
+}
+
+/// 该人数和难度下所有能开局的候选板子：狼人约三分之一（上下浮动一只），
+/// 预言家必选，其余神职任选，剩下的是村民，且至少保留一名村民
+pub fn candidate_boards(total_players: u8, difficulty: BoardDifficulty) -> Vec<HashMap<RoleType, u8>> {
+    let total_players = total_players.clamp(utils::MIN_PLAYERS, utils::MAX_PLAYERS);
+    let base_wolves = (total_players + 1) / 3;
+    let optional = difficulty.optional_gods();
+    let mut boards = Vec::new();
+
+    for wolves in base_wolves.saturating_sub(1).max(1)..=base_wolves + 1 {
+        for mask in 0..(1u32 << optional.len()) {
+            let mut gods = vec![RoleType::Seer];
+            gods.extend(optional.iter().enumerate().filter(|(i, _)| mask & (1 << i) != 0).map(|(_, role)| role.clone()));
+            let Some(villagers) = total_players.checked_sub(wolves + gods.len() as u8).filter(|v| *v > 0) else {
+                continue;
+            };
+
+            let mut board = HashMap::from([(RoleType::Werewolf, wolves), (RoleType::Villager, villagers)]);
+            for god in &gods {
+                board.insert(god.clone(), 1);
+            }
+            if utils::is_playable_distribution(total_players, &board) {
+                // 特殊狼替换一只普通狼，至少保留一只能刀人的狼
+                for passive in difficulty.passive_wolves().iter().filter(|_| wolves > 1) {
+                    let mut variant = board.clone();
+                    variant.insert(RoleType::Werewolf, wolves - 1);
+                    variant.insert(passive.clone(), 1);
+                    boards.push(variant);
+                }
+                boards.push(board);
+            }
+        }
+    }
+    boards
+}
+
+/// 抽样权重：有实测战绩的按胜率离五成的距离衰减；没有的按狼人比例离三分之一的距离给一个较低的先验
+pub fn board_weight(board: &HashMap<RoleType, u8>, stats: Option<&BoardStats>) -> f32 {
+    match stats.filter(|s| s.is_measured()).and_then(BoardStats::villager_win_rate) {
+        Some(rate) => (-(rate - 0.5).abs() * BALANCE_SHARPNESS).exp(),
+        None => {
+            let total: u8 = board.values().sum();
+            let wolves: u8 = board.iter()
+                .filter(|(role, _)| role.faction() == Faction::Werewolf)
+                .map(|(_, count)| *count)
+                .sum();
+            let ratio = wolves as f32 / total.max(1) as f32;
+            UNMEASURED_WEIGHT * (-(ratio - 1.0 / 3.0).abs() * BALANCE_SHARPNESS).exp()
+        }
+    }
+}
+
+/// 在候选板子里按权重抽一个，越接近五五开的越容易被抽中
+pub fn generate<R: Rng>(
+    total_players: u8,
+    difficulty: BoardDifficulty,
+    history: &HashMap<String, BoardStats>,
+    rng: &mut R
+) -> ProposedBoard {
+    let candidates = candidate_boards(total_players, difficulty);
+    let weights: Vec<f32> = candidates.iter()
+        .map(|board| board_weight(board, history.get(&board_key(board))))
+        .collect();
+    let measured_candidates = candidates.iter()
+        .filter(|board| history.get(&board_key(board)).is_some_and(BoardStats::is_measured))
+        .count();
+
+    let role_distribution = match WeightedIndex::new(&weights) {
+        Ok(index) => candidates[index.sample(rng)].clone(),
+        Err(_) => utils::generate_role_distribution(total_players),
+    };
+    let stats = history.get(&board_key(&role_distribution)).cloned().unwrap_or_default();
+    ProposedBoard {
+        total_players: total_players.clamp(utils::MIN_PLAYERS, utils::MAX_PLAYERS),
+        difficulty,
+        villager_win_rate: stats.villager_win_rate().filter(|_| stats.is_measured()),
+        role_distribution,
+        stats,
+        candidates: candidates.len(),
+        measured_candidates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefers_measured_balanced_boards() {
+        let boards = candidate_boards(9, BoardDifficulty::Advanced);
+        assert!(boards.iter().all(|board| utils::is_playable_distribution(9, board)));
+        assert!(boards.iter().any(|board| board.contains_key(&RoleType::DreamWolf)));
+
+        let balanced = &boards[0];
+        let lopsided = &boards[1];
+        let mut results = Vec::new();
+        for i in 0..20 {
+            results.push((balanced.clone(), if i % 2 == 0 { Faction::Villager } else { Faction::Werewolf }));
+            results.push((lopsided.clone(), if i < 18 { Faction::Werewolf } else { Faction::Villager }));
+        }
+        let history = aggregate(&results);
+        let balanced_stats = &history[&board_key(balanced)];
+        assert_eq!(balanced_stats.villager_win_rate(), Some(0.5));
+
+        let balanced_weight = board_weight(balanced, Some(balanced_stats));
+        assert!(balanced_weight > board_weight(lopsided, history.get(&board_key(lopsided))));
+        assert!(balanced_weight > board_weight(&boards[2], None));
+    }
+}
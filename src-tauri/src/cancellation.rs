@@ -0,0 +1,75 @@
+use crate::error::{AppError, AppResult};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+use log::info;
+
+/// 进行中AI任务（模型请求、语音合成、思考延迟）的取消控制
+///
+/// 任务开始时取当前令牌；结束对局、切换阶段或关闭程序时取消该令牌并换上新令牌，
+/// 之前的任务立刻放弃，之后开始的任务不受影响
+#[derive(Debug, Clone, Default)]
+pub struct TaskCanceller {
+    current: Arc<Mutex<CancellationToken>>,
+}
+
+impl TaskCanceller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前这一批任务的令牌
+    pub fn token(&self) -> CancellationToken {
+        self.current.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 取消所有已经开始的任务
+    pub fn cancel_all(&self, reason: &str) {
+        let previous = {
+            let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::take(&mut *current)
+        };
+        previous.cancel();
+        info!("已取消进行中的AI任务: {}", reason);
+    }
+}
+
+/// 令牌取消时丢弃任务并返回取消错误；丢弃会中止其中的HTTP请求、关闭WebSocket并结束子进程
+pub async fn run_cancellable<T>(token: &CancellationToken, task: impl Future<Output = AppResult<T>>) -> AppResult<T> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(AppError::Cancelled("任务已被取消".to_string())),
+        result = task => result,
+    }
+}
+
+/// 可取消的等待，被取消时返回false
+pub async fn sleep_cancellable(token: &CancellationToken, duration: std::time::Duration) -> bool {
+    tokio::select! {
+        _ = token.cancelled() => false,
+        _ = tokio::time::sleep(duration) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_aborts_only_started_tasks() {
+        let canceller = TaskCanceller::new();
+        let stale = canceller.token();
+        canceller.cancel_all("测试");
+
+        let result = run_cancellable(&stale, async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        }).await;
+        assert!(matches!(result, Err(AppError::Cancelled(_))));
+
+        let fresh = canceller.token();
+        assert!(!fresh.is_cancelled());
+        assert_eq!(run_cancellable(&fresh, async { Ok(1) }).await.unwrap(), 1);
+    }
+}
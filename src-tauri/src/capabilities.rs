@@ -0,0 +1,134 @@
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use log::warn;
+
+/// 命令所需的权限
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// 普通玩家操作
+    Player,
+    /// 房主操作：开局、配置、托管座位等
+    Host,
+    /// 调试操作：上帝视角、推理报告、直接调用模型等
+    Debug,
+}
+
+impl Capability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::Player => "player",
+            Capability::Host => "host",
+            Capability::Debug => "debug",
+        }
+    }
+
+    /// 是否为需要留痕的特权
+    pub fn is_privileged(&self) -> bool {
+        *self != Capability::Player
+    }
+}
+
+/// 每个命令所需的权限，未登记的命令按调试命令处理
+pub fn required_capability(command: &str) -> Capability {
+    match command {
+        "get_app_config"
+        | "get_app_version"
+        | "check_for_updates"
+        | "preview_telemetry"
+        | "list_strategy_plugins"
+        | "get_game_state"
+        | "player_vote"
+        | "signal_vote_intent"
+        | "claim_role"
+        | "report_check"
+        | "get_claims_board"
+        | "player_speech"
+        | "get_current_speaker"
+        | "pass_turn"
+        | "raise_objection"
+        | "resume_control"
+        | "submit_night_action"
+        | "get_private_messages"
+        | "get_private_info"
+        | "take_playback_queue"
+        | "get_sound_effect"
+        | "ask_narrator"
+        | "polish_my_speech"
+        | "send_chat_message"
+        | "werewolf_chat"
+        | "get_chat_messages"
+        | "save_note"
+        | "get_notes"
+        | "get_suspicion_history"
+        | "explain_suspicion"
+        | "get_game_history"
+        | "get_coach_reports"
+        | "get_role_info"
+        | "find_similar_statements"
+        | "get_fact_sheet"
+        | "get_session_stats"
+        | "verify_replay_integrity" => Capability::Player,
+
+        "update_llm_config"
+        | "test_llm_connection"
+        | "list_models"
+        | "list_local_models"
+        | "run_diagnostics"
+        | "create_support_bundle"
+        | "update_game_config"
+        | "apply_rule_preset"
+        | "generate_board"
+        | "set_pacing_profile"
+        | "download_sentiment_model"
+        | "start_new_game"
+        | "launch_game"
+        | "generate_ai_speech"
+        | "cancel_stream"
+        | "precompute_ai_turns"
+        | "run_discussion_round"
+        | "run_night_phase"
+        | "undo_last_action"
+        | "replace_player_with_ai"
+        | "restore_human_player"
+        | "set_sheriff"
+        | "revoke_vote"
+        | "end_game"
+        | "export_config"
+        | "import_config"
+        | "transcode_audio_files" => Capability::Host,
+
+        _ => Capability::Debug,
+    }
+}
+
+/// 权限校验器，所有命令入口统一经过这里
+#[derive(Debug, Clone)]
+pub struct CapabilityGuard {
+    granted: HashSet<Capability>,
+}
+
+impl CapabilityGuard {
+    /// 按编译配置授予权限：调试命令只在调试构建或启用debug-commands特性时开放
+    pub fn from_build() -> Self {
+        let mut granted = HashSet::from([Capability::Player, Capability::Host]);
+        if cfg!(any(debug_assertions, feature = "debug-commands")) {
+            granted.insert(Capability::Debug);
+        }
+        Self { granted }
+    }
+
+    pub fn has(&self, capability: Capability) -> bool {
+        self.granted.contains(&capability)
+    }
+
+    /// 校验命令权限，返回该命令所需的权限
+    pub fn authorize(&self, command: &str) -> AppResult<Capability> {
+        let required = required_capability(command);
+        if !self.has(required) {
+            warn!("拒绝调用命令 {}：缺少{}权限", command, required.as_str());
+            return Err(AppError::PermissionDenied(format!("命令{}需要{}权限", command, required.as_str())));
+        }
+        Ok(required)
+    }
+}
@@ -0,0 +1,171 @@
+use crate::error::{AppError, AppResult};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// 聊天频道
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ChatChannel {
+    /// 公共大厅，所有人可见
+    Public,
+    /// 狼人夜间频道，仅狼人阵营可见
+    Werewolf,
+    /// 亡者频道，仅死亡玩家可见
+    Dead,
+}
+
+impl ChatChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatChannel::Public => "public",
+            ChatChannel::Werewolf => "werewolf",
+            ChatChannel::Dead => "dead",
+        }
+    }
+}
+
+/// 频道消息（与正式发言分开存储）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelMessage {
+    pub id: String,
+    pub channel: ChatChannel,
+    pub sender: String,
+    pub content: String,
+    pub day: u32,
+    pub phase: GamePhase,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 聊天服务，负责频道的发送与可见性规则
+#[derive(Debug, Clone, Default)]
+pub struct ChatService {
+    messages: Vec<ChannelMessage>,
+}
+
+impl ChatService {
+    pub fn new() -> Self {
+        Self { messages: Vec::new() }
+    }
+
+    /// 检查玩家能否在频道中发言
+    pub fn check_can_post(channel: &ChatChannel, sender: &Player, phase: &GamePhase) -> AppResult<()> {
+        match channel {
+            ChatChannel::Public => {
+                if !sender.is_alive {
+                    return Err(AppError::GameLogic("死亡玩家不能在公共频道发言".to_string()));
+                }
+                if *phase == GamePhase::Night {
+                    return Err(AppError::GameLogic("夜晚不能在公共频道发言".to_string()));
+                }
+            }
+            ChatChannel::Werewolf => {
+                if !sender.role.role_type.sees_wolf_team() || !sender.is_alive {
+                    return Err(AppError::GameLogic("只有存活的狼人可以在狼人频道发言".to_string()));
+                }
+                if *phase != GamePhase::Night {
+                    return Err(AppError::GameLogic("狼人频道仅在夜晚开放".to_string()));
+                }
+            }
+            ChatChannel::Dead => {
+                if sender.is_alive {
+                    return Err(AppError::GameLogic("只有死亡玩家可以在亡者频道发言".to_string()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查玩家能否查看频道
+    pub fn can_view(channel: &ChatChannel, viewer: &Player, phase: &GamePhase) -> bool {
+        if *phase == GamePhase::GameOver {
+            return true;
+        }
+
+        match channel {
+            ChatChannel::Public => true,
+            ChatChannel::Werewolf => viewer.role.role_type.sees_wolf_team(),
+            ChatChannel::Dead => !viewer.is_alive,
+        }
+    }
+
+    /// 校验并生成一条消息，由调用方决定何时写入频道
+    pub fn compose(channel: ChatChannel, sender: &Player, content: String, day: u32, phase: &GamePhase) -> AppResult<ChannelMessage> {
+        Self::check_can_post(&channel, sender, phase)?;
+
+        let content = content.trim().to_string();
+        if content.is_empty() {
+            return Err(AppError::GameLogic("消息内容不能为空".to_string()));
+        }
+
+        let message = ChannelMessage {
+            id: crate::utils::generate_id(),
+            channel,
+            sender: sender.id.clone(),
+            content,
+            day,
+            phase: phase.clone(),
+            timestamp: Utc::now(),
+        };
+
+        Ok(message)
+    }
+
+    /// 写入一条已生成的消息
+    pub fn append(&mut self, message: ChannelMessage) {
+        self.messages.push(message);
+    }
+
+    /// 获取玩家可见的频道消息
+    pub fn visible_messages(&self, channel: &ChatChannel, viewer: &Player, phase: &GamePhase) -> AppResult<Vec<ChannelMessage>> {
+        if !Self::can_view(channel, viewer, phase) {
+            return Err(AppError::GameLogic("无权查看该频道".to_string()));
+        }
+
+        Ok(self.channel_messages(channel).cloned().collect())
+    }
+
+    /// 获取频道中的全部消息（服务端内部使用，不做可见性检查）
+    pub fn channel_messages<'a>(&'a self, channel: &'a ChatChannel) -> impl Iterator<Item = &'a ChannelMessage> + 'a {
+        self.messages.iter().filter(move |m| &m.channel == channel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::game_engine::GameEngine;
+
+    #[test]
+    fn test_channel_visibility_and_posting() {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        let human = engine.local_player().unwrap();
+        assert!(!human.is_ai);
+        assert_eq!(Some(human.seat), engine.get_state().game_config.human_seat);
+
+        let players = &engine.get_state().players;
+        let wolf = players.iter().find(|p| p.role.role_type == RoleType::Werewolf).unwrap();
+        let villager = players.iter().find(|p| p.faction == Faction::Villager).unwrap();
+        let mut dead = villager.clone();
+        dead.is_alive = false;
+
+        let day = GamePhase::DayDiscussion;
+        assert!(ChatService::can_view(&ChatChannel::Werewolf, wolf, &day));
+        assert!(!ChatService::can_view(&ChatChannel::Werewolf, villager, &day));
+        assert!(!ChatService::can_view(&ChatChannel::Dead, villager, &day));
+        assert!(ChatService::can_view(&ChatChannel::Dead, &dead, &day));
+        assert!(ChatService::can_view(&ChatChannel::Werewolf, villager, &GamePhase::GameOver));
+
+        assert!(ChatService::check_can_post(&ChatChannel::Werewolf, wolf, &GamePhase::Night).is_ok());
+        assert!(ChatService::check_can_post(&ChatChannel::Werewolf, wolf, &day).is_err());
+        assert!(ChatService::check_can_post(&ChatChannel::Werewolf, villager, &GamePhase::Night).is_err());
+        assert!(ChatService::check_can_post(&ChatChannel::Public, villager, &GamePhase::Night).is_err());
+        assert!(ChatService::check_can_post(&ChatChannel::Dead, villager, &day).is_err());
+
+        let mut chat = ChatService::new();
+        chat.append(ChatService::compose(ChatChannel::Werewolf, wolf, "刀3号".to_string(), 1, &GamePhase::Night).unwrap());
+        assert_eq!(chat.visible_messages(&ChatChannel::Werewolf, wolf, &day).unwrap().len(), 1);
+        assert!(chat.visible_messages(&ChatChannel::Werewolf, villager, &day).is_err());
+    }
+}
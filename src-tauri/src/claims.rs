@@ -0,0 +1,223 @@
+use crate::types::RoleType;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// 声明来源
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ClaimSource {
+    /// 从自由发言中解析
+    Speech,
+    /// 玩家通过结构化操作正式声明
+    Explicit,
+}
+
+/// 身份声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleClaim {
+    pub player_id: String,
+    pub role: RoleType,
+    pub day: u32,
+    pub source: ClaimSource,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 查验报告（“昨晚验了5号是狼”）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckReport {
+    pub reporter: String,
+    pub target: String,
+    pub is_werewolf: bool,
+    pub day: u32,
+    pub source: ClaimSource,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 公开声明板，由引擎统一维护
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClaimsBoard {
+    pub claims: Vec<RoleClaim>,
+    pub check_reports: Vec<CheckReport>,
+}
+
+impl ClaimsBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录身份声明，同一玩家重复声明同一身份时忽略
+    pub fn add_claim(&mut self, claim: RoleClaim) -> bool {
+        if self.current_claim(&claim.player_id) == Some(&claim.role) {
+            return false;
+        }
+        self.claims.push(claim);
+        true
+    }
+
+    /// 记录查验报告，同一报告者同一天对同一目标只保留一条
+    pub fn add_check_report(&mut self, report: CheckReport) -> bool {
+        let duplicate = self.check_reports.iter().any(|r| {
+            r.reporter == report.reporter && r.target == report.target && r.day == report.day
+        });
+        if duplicate {
+            return false;
+        }
+        self.check_reports.push(report);
+        true
+    }
+
+    /// 玩家当前声明的身份（以最后一次声明为准）
+    pub fn current_claim(&self, player_id: &str) -> Option<&RoleType> {
+        self.claims.iter()
+            .rev()
+            .find(|c| c.player_id == player_id)
+            .map(|c| &c.role)
+    }
+
+    /// 当前声明某身份的所有玩家
+    pub fn claimants(&self, role: &RoleType) -> Vec<String> {
+        let mut players: Vec<String> = Vec::new();
+        for claim in &self.claims {
+            if !players.contains(&claim.player_id) && self.current_claim(&claim.player_id) == Some(role) {
+                players.push(claim.player_id.clone());
+            }
+        }
+        players
+    }
+
+    /// 该身份是否存在对跳（多人声明同一个神职）
+    pub fn is_contested(&self, role: &RoleType) -> bool {
+        *role != RoleType::Villager && self.claimants(role).len() > 1
+    }
+
+    /// 查验报告的可信度：独跳预言家最高，对跳减半，未跳预言家的报告参考价值很低
+    pub fn report_credibility(&self, reporter: &str) -> f32 {
+        match self.current_claim(reporter) {
+            Some(RoleType::Seer) if self.is_contested(&RoleType::Seer) => 0.4,
+            Some(RoleType::Seer) => 0.8,
+            _ => 0.2,
+        }
+    }
+}
+
+/// 从发言中解析的声明
+#[derive(Debug, Clone, Default)]
+pub struct ParsedClaims {
+    pub role: Option<RoleType>,
+    /// (座位号, 是否为狼)
+    pub checks: Vec<(u8, bool)>,
+}
+
+/// 从自由发言中解析身份声明与查验报告
+pub fn parse_claims(content: &str) -> ParsedClaims {
+    ParsedClaims {
+        role: parse_role_claim(content),
+        checks: parse_check_reports(content),
+    }
+}
+
+fn parse_role_claim(content: &str) -> Option<RoleType> {
+    const ROLE_WORDS: [(&str, RoleType); 7] = [
+        ("预言家", RoleType::Seer),
+        ("女巫", RoleType::Witch),
+        ("猎人", RoleType::Hunter),
+        ("守卫", RoleType::Guard),
+        ("村民", RoleType::Villager),
+        ("平民", RoleType::Villager),
+        ("狼人", RoleType::Werewolf),
+    ];
+
+    let mut search_from = 0;
+    while let Some(offset) = content[search_from..].find("我是") {
+        let start = search_from + offset + "我是".len();
+        // 允许“我是3号预言家”“我是真预言家”这类写法
+        let rest = content[start..]
+            .trim_start_matches(|c: char| c.is_ascii_digit() || "一二三四五六七八九十".contains(c))
+            .trim_start_matches('号')
+            .trim_start_matches("真的")
+            .trim_start_matches('真');
+        for (word, role) in ROLE_WORDS.iter() {
+            if rest.starts_with(word) {
+                return Some(role.clone());
+            }
+        }
+        search_from = start;
+    }
+    None
+}
+
+fn parse_check_reports(content: &str) -> Vec<(u8, bool)> {
+    let mut checks = Vec::new();
+
+    for clause in content.split(['，', '。', '；', '！', ',', '.', ';', '!']) {
+        let is_check_clause = clause.contains('验') || clause.contains("查杀") || clause.contains("金水");
+        if !is_check_clause {
+            continue;
+        }
+
+        let is_werewolf = if clause.contains("查杀") || clause.contains("是狼") || clause.contains("狼人") {
+            true
+        } else if clause.contains("金水") || clause.contains("好人") {
+            false
+        } else {
+            continue;
+        };
+
+        for seat in utils::parse_seat_mentions(clause) {
+            if !checks.iter().any(|(s, _)| *s == seat) {
+                checks.push((seat, is_werewolf));
+            }
+        }
+    }
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(player_id: &str, role: RoleType) -> RoleClaim {
+        RoleClaim {
+            player_id: player_id.to_string(),
+            role,
+            day: 1,
+            source: ClaimSource::Speech,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_role_claim_variants() {
+        assert_eq!(parse_claims("我是预言家，昨晚验了3号").role, Some(RoleType::Seer));
+        assert_eq!(parse_claims("我是3号真预言家").role, Some(RoleType::Seer));
+        assert_eq!(parse_claims("大家好，我是平民").role, Some(RoleType::Villager));
+        assert_eq!(parse_claims("我是觉得5号有问题，我是女巫").role, Some(RoleType::Witch));
+        assert_eq!(parse_claims("我觉得他是预言家").role, None);
+    }
+
+    #[test]
+    fn test_parse_check_reports_per_clause() {
+        let parsed = parse_claims("昨晚验了3号是狼，5号金水；7号我没验");
+        assert_eq!(parsed.checks, vec![(3, true), (5, false)]);
+        assert!(parse_claims("3号发言像狼").checks.is_empty());
+    }
+
+    #[test]
+    fn test_contested_claims_lower_report_credibility() {
+        let mut board = ClaimsBoard::new();
+        assert!(board.add_claim(claim("a", RoleType::Seer)));
+        assert!(!board.add_claim(claim("a", RoleType::Seer)));
+        assert_eq!(board.report_credibility("a"), 0.8);
+        assert_eq!(board.report_credibility("b"), 0.2);
+
+        board.add_claim(claim("b", RoleType::Seer));
+        assert!(board.is_contested(&RoleType::Seer));
+        assert_eq!(board.report_credibility("a"), 0.4);
+
+        // 改跳后以最后一次声明为准
+        board.add_claim(claim("b", RoleType::Villager));
+        assert_eq!(board.claimants(&RoleType::Seer), vec!["a".to_string()]);
+        assert!(!board.is_contested(&RoleType::Seer));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::types::*;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+
+/// 真人玩家某一天的正式投票
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HumanVote {
+    pub day: u32,
+    pub target: String,
+}
+
+/// 一条确定性的复盘结论
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoachFinding {
+    pub day: u32,
+    pub message: String,
+    /// 做得好的地方为true，需要改进的为false
+    pub positive: bool,
+}
+
+/// 教练反馈（每个白天结束或游戏结束时生成）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoachReport {
+    pub day: u32,
+    pub findings: Vec<CoachFinding>,
+    /// LLM对结论的解读，生成失败时为None
+    pub explanation: Option<String>,
+    /// 游戏结束时的总复盘，使用全部真实身份
+    pub final_report: bool,
+}
+
+/// 教练：对照已揭晓的真相评估真人玩家的投票与声明
+pub struct Coach;
+
+impl Coach {
+    /// 生成确定性结论。白天结束时只依据已公示的死亡身份，游戏结束后才使用全部真实身份，避免提前泄露
+    pub fn evaluate(human_id: &str, votes: &[HumanVote], day: Option<u32>, state: &GameState) -> Vec<CoachFinding> {
+        let human = match Self::find_player(state, human_id) {
+            Some(player) => player,
+            None => return Vec::new(),
+        };
+        let full_truth = state.phase == GamePhase::GameOver;
+        let in_scope = |d: u32| day.is_none() || day == Some(d);
+        let mut findings = Vec::new();
+
+        for vote in votes.iter().filter(|v| in_scope(v.day)) {
+            if let Some(finding) = Self::judge_vote(human, vote, state, full_truth) {
+                findings.push(finding);
+            }
+            if let Some(finding) = Self::judge_follow_seer(human, vote, state, full_truth) {
+                findings.push(finding);
+            }
+        }
+
+        // 玩家知道自己的身份，声明的评估不受公示规则限制
+        for claim in state.claims_board.claims.iter().filter(|c| c.player_id == human_id && in_scope(c.day)) {
+            if human.faction == Faction::Villager && claim.role != human.role.role_type {
+                findings.push(CoachFinding {
+                    day: claim.day,
+                    message: format!(
+                        "你第{}天声称自己是{}，但你实际是{}，好人悍跳会干扰真神职的信息",
+                        claim.day,
+                        utils::get_role_name(&claim.role),
+                        utils::get_role_name(&human.role.role_type),
+                    ),
+                    positive: false,
+                });
+            }
+        }
+
+        for report in state.claims_board.check_reports.iter().filter(|r| r.reporter == human_id && in_scope(r.day)) {
+            if human.role.role_type != RoleType::Seer {
+                continue;
+            }
+            let target = match Self::find_player(state, &report.target) {
+                Some(target) => target,
+                None => continue,
+            };
+            if report.is_werewolf != (target.faction == Faction::Werewolf) {
+                findings.push(CoachFinding {
+                    day: report.day,
+                    message: format!("你第{}天报出的{}查验结果与真实结果不符", report.day, target.display_name()),
+                    positive: false,
+                });
+            }
+        }
+
+        findings.sort_by_key(|f| f.day);
+        findings
+    }
+
+    /// 让LLM解读结论的提示词
+    pub fn explanation_prompt(findings: &[CoachFinding], final_report: bool) -> String {
+        let lines = findings.iter()
+            .map(|f| format!("- [{}] {}", if f.positive { "好" } else { "待改进" }, f.message))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let scope = if final_report { "整局" } else { "今天" };
+
+        format!(
+            "你是一名狼人杀教练，请根据以下{}的复盘结论，用不超过80字给新手玩家一段简洁、友善的建议，说明原因和下次可以怎么做：\n{}",
+            scope, lines
+        )
+    }
+
+    fn judge_vote(human: &Player, vote: &HumanVote, state: &GameState, full_truth: bool) -> Option<CoachFinding> {
+        let target = Self::find_player(state, &vote.target)?;
+        let (role, faction) = Self::known_identity(state, target, full_truth)?;
+        let name = target.display_name();
+        let is_good = human.faction == Faction::Villager;
+
+        let (message, positive) = match (faction, role) {
+            (Faction::Werewolf, _) if is_good => (format!("你第{}天投票给了狼人{}，投得准", vote.day, name), true),
+            (Faction::Werewolf, _) => (format!("你第{}天把票投给了狼队友{}", vote.day, name), false),
+            (_, Some(RoleType::Seer)) if is_good => (format!("你第{}天投票给了真预言家{}", vote.day, name), false),
+            (_, Some(role)) if is_good && role != RoleType::Villager => {
+                (format!("你第{}天投票给了{}{}", vote.day, utils::get_role_name(&role), name), false)
+            }
+            (_, _) if is_good => (format!("你第{}天投票给了好人{}", vote.day, name), false),
+            (_, _) => return None,
+        };
+
+        Some(CoachFinding { day: vote.day, message, positive })
+    }
+
+    /// 真预言家当天或之前报了查杀而玩家没有跟票
+    fn judge_follow_seer(human: &Player, vote: &HumanVote, state: &GameState, full_truth: bool) -> Option<CoachFinding> {
+        if human.faction != Faction::Villager {
+            return None;
+        }
+
+        state.claims_board.check_reports.iter()
+            .filter(|r| r.is_werewolf && r.day <= vote.day && r.reporter != human.id && r.target != vote.target)
+            .find_map(|report| {
+                let reporter = Self::find_player(state, &report.reporter)?;
+                let target = Self::find_player(state, &report.target)?;
+                let (role, _) = Self::known_identity(state, reporter, full_truth)?;
+                // 查杀对象当天仍然存活才有跟票的机会
+                let alive_that_day = target.is_alive || state.death_reveals.iter()
+                    .any(|r| r.player_id == target.id && r.day >= vote.day);
+                (role == Some(RoleType::Seer) && alive_that_day).then(|| CoachFinding {
+                    day: vote.day,
+                    message: format!(
+                        "真预言家{}报了{}查杀，你第{}天却没有跟票",
+                        reporter.display_name(), target.display_name(), vote.day
+                    ),
+                    positive: false,
+                })
+            })
+    }
+
+    /// 玩家已知的身份：游戏结束后为真实身份，之前只看公示信息
+    fn known_identity(state: &GameState, player: &Player, full_truth: bool) -> Option<(Option<RoleType>, Faction)> {
+        if full_truth {
+            return Some((Some(player.role.role_type.clone()), player.faction.clone()));
+        }
+
+        state.death_reveals.iter()
+            .find(|r| r.player_id == player.id)
+            .and_then(|r| {
+                let faction = r.revealed_faction.clone()
+                    .or_else(|| r.revealed_role.as_ref().map(|role| role.faction()))?;
+                Some((r.revealed_role.clone(), faction))
+            })
+    }
+
+    fn find_player<'a>(state: &'a GameState, player_id: &str) -> Option<&'a Player> {
+        state.players.iter()
+            .chain(state.dead_players.iter())
+            .find(|p| p.id == player_id)
+    }
+}
@@ -1,36 +1,138 @@
 use crate::config::ConfigManager;
 use crate::error::{AppError, AppResult};
-use crate::llm::{LLMManager, LLMClient};
+use crate::llm::{LLMManager, GenerationParams, TokenMeter};
+use crate::llm_stream::{StreamEvent, StreamRegistry};
+use crate::local_llm;
+use crate::model_catalog::{ModelCatalog, ModelInfo};
 use crate::game_manager::GameManager;
-use crate::types::{LLMConfig, GameConfig, GameState};
+use crate::chat::{ChatChannel, ChannelMessage};
+use crate::claims::ClaimsBoard;
+use crate::capabilities::CapabilityGuard;
+use crate::cancellation::{self, TaskCanceller};
+use crate::ai::lexicon::Lexicon;
+use crate::ai::sentiment::SentimentClassifier;
+use crate::ai::SuspicionExplanation;
+use crate::replay::{GameReplay, IntegrityReport};
+use crate::reasoning_regression;
+use crate::embeddings::SimilarStatement;
+use crate::coach::CoachReport;
+use crate::roles::{self, RoleInfo};
+use crate::memory::MemoryStats;
+use crate::session_stats::SessionStats;
+use crate::board_generator::{BoardDifficulty, ProposedBoard};
+use crate::benchmark::{self, BenchmarkReport};
+use crate::arena::{self, Arena, ArenaPolicy, ArenaReport};
+use crate::discussion::{CurrentSpeaker, DiscussionStatus};
+use crate::night::NightStatus;
+use crate::events::{PrivateInfo, PrivateMessage};
+use crate::speech_assist::{PolishMode, PolishedSpeech, SpeechAssistant};
+use crate::speech_service::PlaybackItem;
+use crate::objection::Objection;
+use crate::sfx::{SfxCue, SfxManager};
+use crate::diagnostics::{self, DiagnosticsReport};
+use crate::support_bundle;
+use crate::fact_sheet::FactSheet;
+use crate::plugins::{self, PluginInfo};
+use crate::updater::{self, UpdateStatus};
+use crate::telemetry::{Telemetry, TelemetryReport};
+use crate::voice::{self, TranscodeReport};
+use crate::database::{DatabaseManager, GameRepository};
+use crate::types::{LLMConfig, LLMProvider, GameConfig, GameState, GameView, Player, PlayerNote, ClaimNote, RoleType, RulePreset, PacingProfile, NightAction, NightActionType, UndoneAction};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use log::{info, error};
+use log::{info, warn};
 
 /// 应用状态
 pub struct AppState {
     pub config_manager: Arc<RwLock<ConfigManager>>,
     pub llm_manager: Arc<RwLock<Option<LLMManager>>>,
     pub game_manager: Arc<RwLock<GameManager>>,
+    pub database: Option<Arc<DatabaseManager>>,
+    pub capabilities: CapabilityGuard,
+    pub sfx: SfxManager,
+    pub model_catalog: Arc<RwLock<ModelCatalog>>,
+    /// 与游戏管理器共用，不需要拿锁就能取消持锁等待模型的任务
+    pub ai_tasks: TaskCanceller,
+    /// 同样不经过游戏管理器的锁，生成中的AI发言也能单独取消
+    pub streams: StreamRegistry,
+    /// 模型用量计数，重建LLM管理器时接上
+    pub token_meter: TokenMeter,
+    pub telemetry: Arc<RwLock<Telemetry>>,
 }
 
 impl AppState {
     pub fn new() -> AppResult<Self> {
         let config_manager = ConfigManager::new()?;
+        Lexicon::install(Lexicon::load(config_manager.config_dir()));
+        SentimentClassifier::init(config_manager.config_dir(), &config_manager.get_config().sentiment);
+        
+        // 数据库不可用时游戏仍可进行，只是不做持久化
+        let database = match tauri::async_runtime::block_on(DatabaseManager::new()) {
+            Ok(database) => Some(Arc::new(database)),
+            Err(e) => {
+                warn!("数据库初始化失败，游戏记录将不会保存: {}", e);
+                None
+            }
+        };
+        
+        let mut game_manager = GameManager::new();
+        if let Some(config_dir) = config_manager.config_dir() {
+            game_manager.set_plugin_dir(config_dir.join(plugins::PLUGIN_DIR));
+        }
+        if let Some(database) = &database {
+            let repository = GameRepository::new(database.get_pool().clone());
+            // 启动时没有进行中的对局，库里未结束的记录都来自上次中断的运行
+            match tauri::async_runtime::block_on(repository.finalize_abandoned_games()) {
+                Ok(abandoned) if !abandoned.is_empty() => info!("已收尾{}局中断的对局", abandoned.len()),
+                Ok(_) => {}
+                Err(e) => warn!("收尾中断对局失败: {}", e),
+            }
+            game_manager.set_repository(Arc::new(repository));
+        }
+        
+        let sfx = SfxManager::new(config_manager.config_dir());
+        let ai_tasks = game_manager.task_canceller();
+        let streams = game_manager.stream_registry();
+        let token_meter = game_manager.token_meter();
+        let mut telemetry = Telemetry::new(config_manager.config_dir());
+        telemetry.start_session(&config_manager.get_config().telemetry);
         
         Ok(Self {
             config_manager: Arc::new(RwLock::new(config_manager)),
             llm_manager: Arc::new(RwLock::new(None)),
-            game_manager: Arc::new(RwLock::new(GameManager::new())),
+            game_manager: Arc::new(RwLock::new(game_manager)),
+            database,
+            capabilities: CapabilityGuard::from_build(),
+            sfx,
+            model_catalog: Arc::new(RwLock::new(ModelCatalog::new())),
+            ai_tasks,
+            streams,
+            token_meter,
+            telemetry: Arc::new(RwLock::new(telemetry)),
         })
     }
 }
 
+/// 校验命令权限，特权调用作为完整性记录写入复盘
+async fn authorize(state: &AppState, command: &str) -> Result<(), String> {
+    let capability = state.capabilities.authorize(command)
+        .map_err(|e| e.to_string())?;
+    
+    if capability.is_privileged() {
+        let mut game_manager = state.game_manager.write().await;
+        game_manager.record_integrity_note(command, capability);
+    }
+    
+    Ok(())
+}
+
 /// 获取应用配置
 #[tauri::command]
 pub async fn get_app_config(
     state: tauri::State<'_, AppState>
 ) -> Result<crate::config::AppConfig, String> {
+    authorize(&state, "get_app_config").await?;
     let config_manager = state.config_manager.read().await;
     Ok(config_manager.get_config().clone())
 }
@@ -41,13 +143,18 @@ pub async fn update_llm_config(
     state: tauri::State<'_, AppState>,
     config: LLMConfig
 ) -> Result<(), String> {
+    authorize(&state, "update_llm_config").await?;
     let mut config_manager = state.config_manager.write().await;
     
     config_manager.update_llm_config(config.clone()).await
         .map_err(|e| e.to_string())?;
     
     // 重新创建LLM管理器
-    let llm_manager = Arc::new(LLMManager::new(config, vec![]));
+    let llm_manager = Arc::new(
+        LLMManager::new(config, vec![])
+            .with_canceller(state.ai_tasks.clone())
+            .with_token_meter(state.token_meter.clone())
+    );
     let mut llm_state = state.llm_manager.write().await;
     *llm_state = Some((*llm_manager).clone());
     
@@ -64,6 +171,12 @@ pub async fn update_llm_config(
 pub async fn test_llm_connection(
     state: tauri::State<'_, AppState>
 ) -> Result<bool, String> {
+    authorize(&state, "test_llm_connection").await?;
+    // 先核对模型名称，避免模型不存在时每次调用都静默失败
+    let config = state.config_manager.read().await.get_config().llm.clone();
+    state.model_catalog.write().await.validate(&config).await
+        .map_err(|e| e.to_string())?;
+    
     let llm_manager_guard = state.llm_manager.read().await;
     
     if let Some(llm_manager) = llm_manager_guard.as_ref() {
@@ -77,12 +190,79 @@ pub async fn test_llm_connection(
     }
 }
 
+/// 查询提供商的模型列表及能力，使用当前配置的接口地址和密钥
+#[tauri::command]
+pub async fn list_models(
+    state: tauri::State<'_, AppState>,
+    provider: LLMProvider,
+    refresh: Option<bool>
+) -> Result<Vec<ModelInfo>, String> {
+    authorize(&state, "list_models").await?;
+    let mut config = state.config_manager.read().await.get_config().llm.clone();
+    config.provider = provider;
+    state.model_catalog.write().await.models(&config, refresh.unwrap_or(false)).await
+        .map_err(|e| e.to_string())
+}
+
+/// 查询本地模型服务（Ollama或llama.cpp server）已安装的模型，未指定地址时用当前配置的本地地址或默认地址
+#[tauri::command]
+pub async fn list_local_models(
+    state: tauri::State<'_, AppState>,
+    base_url: Option<String>,
+    refresh: Option<bool>
+) -> Result<Vec<ModelInfo>, String> {
+    authorize(&state, "list_local_models").await?;
+    let mut config = state.config_manager.read().await.get_config().llm.clone();
+    config.base_url = match base_url {
+        Some(base_url) => base_url.trim_end_matches('/').to_string(),
+        None if config.provider == LLMProvider::LocalOllama => config.base_url,
+        None => local_llm::DEFAULT_LOCAL_URL.to_string(),
+    };
+    config.provider = LLMProvider::LocalOllama;
+    state.model_catalog.write().await.models(&config, refresh.unwrap_or(false)).await
+        .map_err(|e| e.to_string())
+}
+
+/// 运行环境自检，返回逐项结果供设置页展示
+#[tauri::command]
+pub async fn run_diagnostics(
+    state: tauri::State<'_, AppState>
+) -> Result<DiagnosticsReport, String> {
+    authorize(&state, "run_diagnostics").await?;
+    Ok(diagnostics::run(&state).await)
+}
+
+/// 列出插件目录中可分配给AI座位的策略插件
+#[tauri::command]
+pub async fn list_strategy_plugins(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<PluginInfo>, String> {
+    authorize(&state, "list_strategy_plugins").await?;
+    let config_manager = state.config_manager.read().await;
+    Ok(config_manager.config_dir()
+        .map(|dir| plugins::list_plugins(&dir.join(plugins::PLUGIN_DIR)))
+        .unwrap_or_default())
+}
+
+/// 生成支持包（日志、脱敏配置、自检结果，可选附带最近一局复盘），返回zip文件路径
+#[tauri::command]
+pub async fn create_support_bundle(
+    state: tauri::State<'_, AppState>,
+    include_replay: Option<bool>
+) -> Result<String, String> {
+    authorize(&state, "create_support_bundle").await?;
+    let path = support_bundle::create(&state, include_replay.unwrap_or(false)).await
+        .map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 /// 生成AI响应
 #[tauri::command]
 pub async fn generate_ai_response(
     state: tauri::State<'_, AppState>,
     prompt: String
 ) -> Result<String, String> {
+    authorize(&state, "generate_ai_response").await?;
     let llm_manager_guard = state.llm_manager.read().await;
     
     if let Some(llm_manager) = llm_manager_guard.as_ref() {
@@ -93,12 +273,77 @@ pub async fn generate_ai_response(
     }
 }
 
+/// 流式生成，文本增量通过通道逐段推给前端；立即返回流ID，可用cancel_stream中止
+#[tauri::command]
+pub async fn stream_ai_response(
+    state: tauri::State<'_, AppState>,
+    prompt: String,
+    on_event: tauri::ipc::Channel<StreamEvent>
+) -> Result<String, String> {
+    authorize(&state, "stream_ai_response").await?;
+    let llm_manager = state.llm_manager.read().await.clone()
+        .ok_or_else(|| "LLM管理器未初始化".to_string())?;
+    let streams = state.streams.clone();
+    let (stream_id, token) = streams.register(&state.ai_tasks.token());
+    
+    let id = stream_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut on_delta = |text: &str| {
+            if let Err(e) = on_event.send(StreamEvent::Delta { stream_id: id.clone(), text: text.to_string() }) {
+                warn!("推送流式文本失败: {}", e);
+            }
+        };
+        let result = llm_manager.generate_streaming(prompt, GenerationParams::default(), &token, &mut on_delta).await;
+        streams.finish(&id);
+        
+        let event = match result {
+            Ok((content, served_by)) => StreamEvent::Done { stream_id: id, content, served_by },
+            Err(AppError::Cancelled(_)) => StreamEvent::Cancelled { stream_id: id },
+            Err(e) => StreamEvent::Failed { stream_id: id, message: e.to_string() },
+        };
+        if let Err(e) = on_event.send(event) {
+            warn!("推送流式结果失败: {}", e);
+        }
+    });
+    
+    Ok(stream_id)
+}
+
+/// 取消一次流式生成（包括正在生成的AI发言），流已结束时返回false
+#[tauri::command]
+pub async fn cancel_stream(
+    state: tauri::State<'_, AppState>,
+    stream_id: String
+) -> Result<bool, String> {
+    authorize(&state, "cancel_stream").await?;
+    Ok(state.streams.cancel(&stream_id))
+}
+
+/// 润色玩家的发言草稿（不改变其中的声明），返回差异预览
+#[tauri::command]
+pub async fn polish_my_speech(
+    state: tauri::State<'_, AppState>,
+    draft: String,
+    mode: Option<PolishMode>
+) -> Result<PolishedSpeech, String> {
+    authorize(&state, "polish_my_speech").await?;
+    let llm_manager_guard = state.llm_manager.read().await;
+    
+    if let Some(llm_manager) = llm_manager_guard.as_ref() {
+        SpeechAssistant::polish(llm_manager, &draft, mode.unwrap_or_default()).await
+            .map_err(|e| e.to_string())
+    } else {
+        Err("LLM管理器未初始化".to_string())
+    }
+}
+
 /// 更新游戏配置
 #[tauri::command]
 pub async fn update_game_config(
     state: tauri::State<'_, AppState>,
     config: GameConfig
 ) -> Result<(), String> {
+    authorize(&state, "update_game_config").await?;
     let mut config_manager = state.config_manager.write().await;
     
     config_manager.update_game_config(config).await
@@ -108,13 +353,96 @@ pub async fn update_game_config(
     Ok(())
 }
 
+/// 套用规则预设
+#[tauri::command]
+pub async fn apply_rule_preset(
+    state: tauri::State<'_, AppState>,
+    preset: RulePreset
+) -> Result<GameConfig, String> {
+    authorize(&state, "apply_rule_preset").await?;
+    let mut config_manager = state.config_manager.write().await;
+    
+    let config = config_manager.apply_rule_preset(preset).await
+        .map_err(|e| e.to_string())?;
+    
+    info!("已套用规则预设: {:?}", preset);
+    Ok(config)
+}
+
+/// 切换节奏档位：保存为之后对局的默认节奏，对局进行中时立即生效
+#[tauri::command]
+pub async fn set_pacing_profile(
+    state: tauri::State<'_, AppState>,
+    profile: PacingProfile
+) -> Result<GameConfig, String> {
+    authorize(&state, "set_pacing_profile").await?;
+    let config = state.config_manager.write().await
+        .apply_pacing_profile(profile).await
+        .map_err(|e| e.to_string())?;
+    
+    state.game_manager.write().await.set_pacing_profile(profile);
+    
+    info!("已切换节奏档位: {:?}", profile);
+    Ok(config)
+}
+
+/// 下载并加载本地情感模型
+#[tauri::command]
+pub async fn download_sentiment_model(
+    state: tauri::State<'_, AppState>
+) -> Result<bool, String> {
+    authorize(&state, "download_sentiment_model").await?;
+    let (data_dir, config) = {
+        let config_manager = state.config_manager.read().await;
+        let data_dir = config_manager.config_dir()
+            .map(|dir| dir.to_path_buf())
+            .ok_or_else(|| "无法获取数据目录".to_string())?;
+        (data_dir, config_manager.get_config().sentiment.clone())
+    };
+    
+    SentimentClassifier::download(&data_dir, &config).await
+        .map_err(|e| e.to_string())?;
+    
+    if SentimentClassifier::global().is_none() {
+        let classifier = SentimentClassifier::load(&data_dir, &config)
+            .map_err(|e| e.to_string())?;
+        SentimentClassifier::install(classifier);
+    }
+    
+    info!("本地情感模型已就绪");
+    Ok(true)
+}
+
+/// 查找玩家说过的相似发言
+#[tauri::command]
+pub async fn find_similar_statements(
+    state: tauri::State<'_, AppState>,
+    player_id: String,
+    query: Option<String>,
+    limit: Option<usize>
+) -> Result<Vec<SimilarStatement>, String> {
+    authorize(&state, "find_similar_statements").await?;
+    let game_manager = state.game_manager.read().await;
+    
+    game_manager.find_similar_statements(&player_id, query.as_deref(), limit.unwrap_or(5)).await
+        .map_err(|e| e.to_string())
+}
+
 /// 开始新游戏
 #[tauri::command]
 pub async fn start_new_game(
     state: tauri::State<'_, AppState>,
     config: GameConfig
 ) -> Result<GameState, String> {
+    authorize(&state, "start_new_game").await?;
     info!("开始新游戏: {:?}", config);
+    // 先取消上一局持锁等待模型的任务，否则要等它们跑完才能拿到锁
+    state.ai_tasks.cancel_all("开始新对局");
+    {
+        let config_manager = state.config_manager.read().await;
+        let app_config = config_manager.get_config();
+        state.telemetry.write().await.record_game(&app_config.telemetry, config.total_players, &app_config.llm.provider);
+    }
     
     let mut game_manager = state.game_manager.write().await;
     let game_state = game_manager.create_game(config).await
@@ -128,29 +456,109 @@ pub async fn start_new_game(
 pub async fn launch_game(
     state: tauri::State<'_, AppState>
 ) -> Result<(), String> {
+    authorize(&state, "launch_game").await?;
     let mut game_manager = state.game_manager.write().await;
     game_manager.start_game().await
         .map_err(|e| e.to_string())
 }
 
-/// 获取当前游戏状态
+/// 获取当前游戏状态，只含本机真人座位能看到的信息
 #[tauri::command]
 pub async fn get_game_state(
     state: tauri::State<'_, AppState>
+) -> Result<Option<GameView>, String> {
+    authorize(&state, "get_game_state").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_game_view()
+        .map_err(|e| e.to_string())
+}
+
+/// 获取完整游戏状态（上帝视角，调试用）
+#[tauri::command]
+pub async fn get_full_game_state(
+    state: tauri::State<'_, AppState>
 ) -> Result<Option<GameState>, String> {
+    authorize(&state, "get_full_game_state").await?;
     let game_manager = state.game_manager.read().await;
     Ok(game_manager.get_game_state())
 }
 
-/// 玩家投票
+/// 玩家投票，target_id为空表示弃票
 #[tauri::command]
 pub async fn player_vote(
     state: tauri::State<'_, AppState>,
     voter_id: String,
-    target_id: String
+    target_id: Option<String>,
+    idempotency_key: Option<String>
+) -> Result<(), String> {
+    authorize(&state, "player_vote").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.player_vote(voter_id, target_id, idempotency_key).await
+        .map_err(|e| e.to_string())
+}
+
+/// 表达投票意向（非正式投票）
+#[tauri::command]
+pub async fn signal_vote_intent(
+    state: tauri::State<'_, AppState>,
+    voter_id: String,
+    target_id: String,
+    idempotency_key: Option<String>
+) -> Result<(), String> {
+    authorize(&state, "signal_vote_intent").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.signal_vote_intent(voter_id, target_id, idempotency_key)
+        .map_err(|e| e.to_string())
+}
+
+/// 娱乐局撤销本阶段最近一次投票
+#[tauri::command]
+pub async fn undo_last_action(
+    state: tauri::State<'_, AppState>
+) -> Result<UndoneAction, String> {
+    authorize(&state, "undo_last_action").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.undo_last_action()
+        .map_err(|e| e.to_string())
+}
+
+/// 正式声明身份
+#[tauri::command]
+pub async fn claim_role(
+    state: tauri::State<'_, AppState>,
+    player_id: String,
+    role: RoleType,
+    idempotency_key: Option<String>
+) -> Result<(), String> {
+    authorize(&state, "claim_role").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.claim_role(player_id, role, idempotency_key)
+        .map_err(|e| e.to_string())
+}
+
+/// 公布查验结果
+#[tauri::command]
+pub async fn report_check(
+    state: tauri::State<'_, AppState>,
+    reporter_id: String,
+    target_id: String,
+    is_werewolf: bool,
+    idempotency_key: Option<String>
 ) -> Result<(), String> {
+    authorize(&state, "report_check").await?;
     let mut game_manager = state.game_manager.write().await;
-    game_manager.player_vote(voter_id, target_id).await
+    game_manager.report_check(reporter_id, target_id, is_werewolf, idempotency_key)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取公开声明板
+#[tauri::command]
+pub async fn get_claims_board(
+    state: tauri::State<'_, AppState>
+) -> Result<ClaimsBoard, String> {
+    authorize(&state, "get_claims_board").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_claims_board()
         .map_err(|e| e.to_string())
 }
 
@@ -159,10 +567,87 @@ pub async fn player_vote(
 pub async fn player_speech(
     state: tauri::State<'_, AppState>,
     player_id: String,
-    content: String
+    content: String,
+    idempotency_key: Option<String>
+) -> Result<(), String> {
+    authorize(&state, "player_speech").await?;
+    let resume = {
+        let mut game_manager = state.game_manager.write().await;
+        if game_manager.handle_narrator_request(&player_id, &content).await {
+            return Ok(());
+        }
+        game_manager.handle_player_speech(player_id.clone(), content, idempotency_key).await
+            .map_err(|e| e.to_string())?;
+        game_manager.finish_human_turn(&player_id)
+    };
+    
+    // 讨论轮在等这位玩家，发言后在后台继续推进
+    if resume {
+        let game_manager = state.game_manager.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = GameManager::run_discussion_round(&game_manager).await {
+                warn!("继续讨论轮失败: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// 当前发言者及真人发言的剩余时间
+#[tauri::command]
+pub async fn get_current_speaker(state: tauri::State<'_, AppState>) -> Result<Option<CurrentSpeaker>, String> {
+    authorize(&state, "get_current_speaker").await?;
+    let game_manager = state.game_manager.read().await;
+    Ok(game_manager.current_speaker())
+}
+
+/// 轮到自己时放弃发言，讨论轮在后台继续推进
+#[tauri::command]
+pub async fn pass_turn(
+    state: tauri::State<'_, AppState>,
+    player_id: String,
+    idempotency_key: Option<String>
+) -> Result<(), String> {
+    authorize(&state, "pass_turn").await?;
+    let resume = {
+        let mut game_manager = state.game_manager.write().await;
+        game_manager.pass_turn(&player_id, idempotency_key).map_err(|e| e.to_string())?
+    };
+    
+    if resume {
+        let game_manager = state.game_manager.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = GameManager::run_discussion_round(&game_manager).await {
+                warn!("继续讨论轮失败: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// 打断当前发言者提出异议，每天限一次
+#[tauri::command]
+pub async fn raise_objection(
+    state: tauri::State<'_, AppState>,
+    player_id: String,
+    content: String,
+    idempotency_key: Option<String>
+) -> Result<Objection, String> {
+    authorize(&state, "raise_objection").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.raise_objection(player_id, content, idempotency_key)
+        .map_err(|e| e.to_string())
+}
+
+/// 挂机托管后收回座位控制
+#[tauri::command]
+pub async fn resume_control(
+    state: tauri::State<'_, AppState>,
+    player_id: String
 ) -> Result<(), String> {
+    authorize(&state, "resume_control").await?;
     let mut game_manager = state.game_manager.write().await;
-    game_manager.handle_player_speech(player_id, content).await
+    game_manager.resume_control(&player_id)
         .map_err(|e| e.to_string())
 }
 
@@ -172,8 +657,495 @@ pub async fn generate_ai_speech(
     state: tauri::State<'_, AppState>,
     player_id: String
 ) -> Result<String, String> {
+    authorize(&state, "generate_ai_speech").await?;
+    let (pending, token) = {
+        let mut game_manager = state.game_manager.write().await;
+        let token = game_manager.task_token();
+        let pending = game_manager.generate_ai_speech(player_id).await
+            .map_err(|e| e.to_string())?;
+        (pending, token)
+    };
+    
+    // 模拟思考与打字延迟，等待期间不持有锁
+    if !pending.delay.is_zero() {
+        cancellation::sleep_cancellable(&token, pending.delay).await;
+    }
+    
     let mut game_manager = state.game_manager.write().await;
-    game_manager.generate_ai_speech(player_id).await
+    if token.is_cancelled() {
+        game_manager.discard_ai_speech(pending);
+        return Err(AppError::Cancelled("对局或阶段已经变化".to_string()).to_string());
+    }
+    game_manager.deliver_ai_speech(pending).await
+        .map_err(|e| e.to_string())
+}
+
+/// 真人发言或思考期间为接下来的AI预生成发言
+#[tauri::command]
+pub async fn precompute_ai_turns(
+    state: tauri::State<'_, AppState>
+) -> Result<usize, String> {
+    authorize(&state, "precompute_ai_turns").await?;
+    GameManager::precompute_ai_turns(&state.game_manager).await
+        .map_err(|e| e.to_string())
+}
+
+/// 按发言顺序推进本轮讨论，轮到真人时返回，真人发言后自动继续
+#[tauri::command]
+pub async fn run_discussion_round(
+    state: tauri::State<'_, AppState>
+) -> Result<DiscussionStatus, String> {
+    authorize(&state, "run_discussion_round").await?;
+    let status = GameManager::run_discussion_round(&state.game_manager).await
+        .map_err(|e| e.to_string())?;
+    
+    // 真人发言期间为后面的AI预生成发言
+    if matches!(status, DiscussionStatus::AwaitingHuman { .. }) {
+        let game_manager = state.game_manager.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = GameManager::precompute_ai_turns(&game_manager).await {
+                warn!("预生成AI发言失败: {}", e);
+            }
+        });
+    }
+    Ok(status)
+}
+
+/// 按角色顺序推进夜晚，轮到真人时返回，全部行动完成后结算并返回天亮公告
+#[tauri::command]
+pub async fn run_night_phase(
+    state: tauri::State<'_, AppState>
+) -> Result<NightStatus, String> {
+    authorize(&state, "run_night_phase").await?;
+    GameManager::run_night_phase(&state.game_manager).await
+        .map_err(|e| e.to_string())
+}
+
+/// 真人提交夜晚行动，预言家的查验结果通过私信频道送达
+#[tauri::command]
+pub async fn submit_night_action(
+    state: tauri::State<'_, AppState>,
+    player_id: String,
+    action: NightActionType,
+    target: Option<String>,
+    idempotency_key: Option<String>
+) -> Result<(), String> {
+    authorize(&state, "submit_night_action").await?;
+    let resume = {
+        let mut game_manager = state.game_manager.write().await;
+        game_manager.submit_night_action(NightAction { player: player_id, action, target }, idempotency_key).await
+            .map_err(|e| e.to_string())?
+    };
+    
+    // 夜晚流程在等这位玩家，提交后在后台继续推进
+    if resume {
+        let game_manager = state.game_manager.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = GameManager::run_night_phase(&game_manager).await {
+                warn!("继续夜晚流程失败: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// 获取玩家本局收到的私信，用于断线或错过推送后补齐
+#[tauri::command]
+pub async fn get_private_messages(
+    state: tauri::State<'_, AppState>,
+    player_id: String
+) -> Result<Vec<PrivateMessage>, String> {
+    authorize(&state, "get_private_messages").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_private_messages(&player_id).await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取真人玩家自己掌握的私密信息
+#[tauri::command]
+pub async fn get_private_info(
+    state: tauri::State<'_, AppState>,
+    player_id: String
+) -> Result<PrivateInfo, String> {
+    authorize(&state, "get_private_info").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_private_info(&player_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 向主持人提问规则或公开的对局记录，不会透露隐藏信息
+#[tauri::command]
+pub async fn ask_narrator(
+    state: tauri::State<'_, AppState>,
+    player_id: String,
+    question: String
+) -> Result<String, String> {
+    authorize(&state, "ask_narrator").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.ask_narrator(&player_id, &question).await
+        .map_err(|e| e.to_string())
+}
+
+/// 取出待朗读的内容（主持人播报在前），前端依次合成播放
+#[tauri::command]
+pub async fn take_playback_queue(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<PlaybackItem>, String> {
+    authorize(&state, "take_playback_queue").await?;
+    let mut game_manager = state.game_manager.write().await;
+    Ok(game_manager.take_tts_queue())
+}
+
+/// 获取音效音频，数据目录下的同名文件优先于内置音效
+///
+/// 前端收到音效事件后用独立的播放通道播放，与朗读队列混音
+#[tauri::command]
+pub async fn get_sound_effect(
+    state: tauri::State<'_, AppState>,
+    cue: SfxCue
+) -> Result<Vec<u8>, String> {
+    authorize(&state, "get_sound_effect").await?;
+    Ok(state.sfx.load(cue))
+}
+
+/// 把数据目录下已有的WAV录音和TTS缓存转为配置的编码，可临时指定码率
+#[tauri::command]
+pub async fn transcode_audio_files(
+    state: tauri::State<'_, AppState>,
+    bitrate_kbps: Option<u32>
+) -> Result<TranscodeReport, String> {
+    authorize(&state, "transcode_audio_files").await?;
+    let (data_dir, mut encoding) = {
+        let config_manager = state.config_manager.read().await;
+        let data_dir = config_manager.config_dir()
+            .map(|dir| dir.to_path_buf())
+            .ok_or_else(|| "无法获取数据目录".to_string())?;
+        (data_dir, config_manager.get_config().voice.encoding.clone())
+    };
+    if let Some(bitrate_kbps) = bitrate_kbps {
+        encoding.bitrate_kbps = bitrate_kbps;
+    }
+    voice::transcode_data_dir(&data_dir, &encoding).await
+        .map_err(|e| e.to_string())
+}
+
+/// 以本机真人座位的身份发送频道聊天消息
+#[tauri::command]
+pub async fn send_chat_message(
+    state: tauri::State<'_, AppState>,
+    channel: ChatChannel,
+    content: String,
+    idempotency_key: Option<String>
+) -> Result<ChannelMessage, String> {
+    authorize(&state, "send_chat_message").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.send_chat_message(channel, content, idempotency_key).await
+        .map_err(|e| e.to_string())
+}
+
+/// 真人狼在狼人频道发言，可附带想刀的目标；身份取本机真人座位
+#[tauri::command]
+pub async fn werewolf_chat(
+    state: tauri::State<'_, AppState>,
+    content: String,
+    target: Option<String>,
+    idempotency_key: Option<String>
+) -> Result<ChannelMessage, String> {
+    authorize(&state, "werewolf_chat").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.werewolf_chat(content, target, idempotency_key).await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取本机真人座位可见的频道聊天消息
+#[tauri::command]
+pub async fn get_chat_messages(
+    state: tauri::State<'_, AppState>,
+    channel: ChatChannel
+) -> Result<Vec<ChannelMessage>, String> {
+    authorize(&state, "get_chat_messages").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_chat_messages(&channel)
+        .map_err(|e| e.to_string())
+}
+
+/// 由AI接管缺席的人类座位（房主操作）
+#[tauri::command]
+pub async fn replace_player_with_ai(
+    state: tauri::State<'_, AppState>,
+    player_id: String
+) -> Result<Player, String> {
+    authorize(&state, "replace_player_with_ai").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.replace_human_with_ai(player_id).await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置警长或移交警徽，player_id为空表示撕毁警徽（房主操作）
+#[tauri::command]
+pub async fn set_sheriff(
+    state: tauri::State<'_, AppState>,
+    player_id: Option<String>
+) -> Result<(), String> {
+    authorize(&state, "set_sheriff").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.set_sheriff(player_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 剥夺玩家的投票权，如翻牌后的白痴（房主操作）
+#[tauri::command]
+pub async fn revoke_vote(
+    state: tauri::State<'_, AppState>,
+    player_id: String
+) -> Result<(), String> {
+    authorize(&state, "revoke_vote").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.revoke_vote(player_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 将AI托管的座位交还给人类玩家（房主操作）
+#[tauri::command]
+pub async fn restore_human_player(
+    state: tauri::State<'_, AppState>,
+    player_id: String
+) -> Result<Player, String> {
+    authorize(&state, "restore_human_player").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.restore_human_player(player_id).await
+        .map_err(|e| e.to_string())
+}
+
+/// 保存私人笔记
+#[tauri::command]
+pub async fn save_note(
+    state: tauri::State<'_, AppState>,
+    player_id: String,
+    day: Option<u32>,
+    content: String,
+    suspected_roles: Option<HashMap<String, RoleType>>,
+    claims_heard: Option<Vec<ClaimNote>>
+) -> Result<PlayerNote, String> {
+    authorize(&state, "save_note").await?;
+    let mut game_manager = state.game_manager.write().await;
+    game_manager.save_note(
+        player_id,
+        day,
+        content,
+        suspected_roles.unwrap_or_default(),
+        claims_heard.unwrap_or_default()
+    ).await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取游戏笔记
+#[tauri::command]
+pub async fn get_notes(
+    state: tauri::State<'_, AppState>,
+    game_id: String
+) -> Result<Vec<PlayerNote>, String> {
+    authorize(&state, "get_notes").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_notes(&game_id).await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取怀疑度变化时间序列
+#[tauri::command]
+pub async fn get_suspicion_history(
+    state: tauri::State<'_, AppState>,
+    game_id: String
+) -> Result<Vec<crate::database::SuspicionSnapshotRecord>, String> {
+    authorize(&state, "get_suspicion_history").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_suspicion_history(&game_id).await
+        .map_err(|e| e.to_string())
+}
+
+/// 用自然语言解释某个视角对某名玩家的怀疑，前端悬停玩家时调用
+#[tauri::command]
+pub async fn explain_suspicion(
+    state: tauri::State<'_, AppState>,
+    observer_id: String,
+    target_id: String,
+    polish: Option<bool>
+) -> Result<SuspicionExplanation, String> {
+    authorize(&state, "explain_suspicion").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.explain_suspicion(&observer_id, &target_id, polish.unwrap_or(false)).await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取最近的对局记录，中断的对局带有abandoned标记
+#[tauri::command]
+pub async fn get_game_history(
+    state: tauri::State<'_, AppState>,
+    limit: Option<u32>
+) -> Result<Vec<crate::database::GameRecord>, String> {
+    authorize(&state, "get_game_history").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_game_history(limit.unwrap_or(50)).await
+        .map_err(|e| e.to_string())
+}
+
+/// 按人数和难度生成平衡板子，优先抽往局实测胜率接近五五开的身份配置，返回板子及其实测战绩
+#[tauri::command]
+pub async fn generate_board(
+    state: tauri::State<'_, AppState>,
+    total_players: u8,
+    difficulty: Option<BoardDifficulty>
+) -> Result<ProposedBoard, String> {
+    authorize(&state, "generate_board").await?;
+    let game_manager = state.game_manager.read().await;
+    Ok(game_manager.generate_board(total_players, difficulty.unwrap_or_default()).await)
+}
+
+/// 查询角色知识库
+#[tauri::command]
+pub async fn get_role_info(
+    state: tauri::State<'_, AppState>,
+    role_type: RoleType
+) -> Result<RoleInfo, String> {
+    authorize(&state, "get_role_info").await?;
+    Ok(roles::role_info(&role_type))
+}
+
+/// 获取教练模式的复盘反馈
+#[tauri::command]
+pub async fn get_coach_reports(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<CoachReport>, String> {
+    authorize(&state, "get_coach_reports").await?;
+    let game_manager = state.game_manager.read().await;
+    Ok(game_manager.get_coach_reports().to_vec())
+}
+
+/// 获取AI决策审计记录
+#[tauri::command]
+pub async fn get_ai_decisions(
+    state: tauri::State<'_, AppState>,
+    game_id: String,
+    player_id: Option<String>
+) -> Result<Vec<crate::database::AIDecisionRecord>, String> {
+    authorize(&state, "get_ai_decisions").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_ai_decisions(&game_id, player_id.as_deref()).await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取各组件的内存占用统计（调试用）
+#[tauri::command]
+pub async fn get_memory_stats(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<MemoryStats>, String> {
+    authorize(&state, "get_memory_stats").await?;
+    let game_manager = state.game_manager.read().await;
+    Ok(game_manager.get_memory_stats())
+}
+
+/// 本局的实时统计（用时、发言数、AI平均耗时、模型用量、已完成阶段），供状态栏轮询
+#[tauri::command]
+pub async fn get_session_stats(
+    state: tauri::State<'_, AppState>
+) -> Result<SessionStats, String> {
+    authorize(&state, "get_session_stats").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_session_stats()
+        .map_err(|e| e.to_string())
+}
+
+/// 运行AI子系统的性能基准（调试用），默认每项50次
+#[tauri::command]
+pub async fn run_benchmark(
+    state: tauri::State<'_, AppState>,
+    iterations: Option<u32>
+) -> Result<BenchmarkReport, String> {
+    authorize(&state, "run_benchmark").await?;
+    benchmark::run_suite(iterations.unwrap_or(50)).await
+}
+
+/// 两种AI策略（模型、提示词或WASM插件）对战若干局（调试用），默认20局，双方轮流执狼
+#[tauri::command]
+pub async fn run_arena(
+    state: tauri::State<'_, AppState>,
+    policy_a: ArenaPolicy,
+    policy_b: ArenaPolicy,
+    games: Option<u32>
+) -> Result<ArenaReport, String> {
+    authorize(&state, "run_arena").await?;
+    let (default_llm, plugin_dir) = {
+        let config_manager = state.config_manager.read().await;
+        (config_manager.get_config().llm.clone(), config_manager.config_dir().map(|dir| dir.join(plugins::PLUGIN_DIR)))
+    };
+    Arena::new(policy_a, policy_b, default_llm, plugin_dir)
+        .run(games.unwrap_or(20).min(arena::MAX_ARENA_GAMES))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 把一局精选复盘录制为推理回归用例（调试用），返回用例文件路径。
+/// 不传复盘JSON时取指定或最近的对局，tolerance为期望上下的余量，默认0.05
+#[tauri::command]
+pub async fn record_reasoning_case(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    game_id: Option<String>,
+    replay_json: Option<String>,
+    tolerance: Option<f32>
+) -> Result<String, String> {
+    authorize(&state, "record_reasoning_case").await?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("用例名只能包含字母、数字、-和_".to_string());
+    }
+    
+    let replay: GameReplay = match replay_json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string())?,
+        None => {
+            let game_manager = state.game_manager.read().await;
+            let replay = match game_id {
+                Some(game_id) => game_manager.full_replay(&game_id).await,
+                None => game_manager.latest_replay().await,
+            };
+            replay.map_err(|e| e.to_string())?.ok_or("没有可录制的复盘")?
+        }
+    };
+    let case = reasoning_regression::record_case(&name, replay, tolerance.unwrap_or(0.05));
+    
+    let dir = {
+        let config_manager = state.config_manager.read().await;
+        config_manager.config_dir().ok_or("配置目录不可用")?.join(reasoning_regression::RECORD_DIR)
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.json", name));
+    let json = serde_json::to_string_pretty(&case).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    info!("已录制推理回归用例{}，共{}条期望", name, case.expectations.len());
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 获取赛后事实表（真实身份、查验、出局顺序、投票矩阵、带真假标注的声明），对局结束后才有
+#[tauri::command]
+pub async fn get_fact_sheet(
+    state: tauri::State<'_, AppState>,
+    game_id: Option<String>
+) -> Result<Option<FactSheet>, String> {
+    authorize(&state, "get_fact_sheet").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.get_fact_sheet(game_id.as_deref()).await
+        .map_err(|e| e.to_string())
+}
+
+/// 校验复盘完整性（可传入导出的复盘JSON）
+#[tauri::command]
+pub async fn verify_replay_integrity(
+    state: tauri::State<'_, AppState>,
+    game_id: Option<String>,
+    replay_json: Option<String>
+) -> Result<IntegrityReport, String> {
+    authorize(&state, "verify_replay_integrity").await?;
+    let game_manager = state.game_manager.read().await;
+    game_manager.verify_replay_integrity(game_id.as_deref(), replay_json.as_deref()).await
         .map_err(|e| e.to_string())
 }
 
@@ -182,6 +1154,8 @@ pub async fn generate_ai_speech(
 pub async fn end_game(
     state: tauri::State<'_, AppState>
 ) -> Result<(), String> {
+    authorize(&state, "end_game").await?;
+    state.ai_tasks.cancel_all("对局结束");
     let mut game_manager = state.game_manager.write().await;
     game_manager.end_game().await
         .map_err(|e| e.to_string())
@@ -192,6 +1166,7 @@ pub async fn end_game(
 pub async fn export_config(
     state: tauri::State<'_, AppState>
 ) -> Result<String, String> {
+    authorize(&state, "export_config").await?;
     let config_manager = state.config_manager.read().await;
     config_manager.export_config().map_err(|e| e.to_string())
 }
@@ -202,6 +1177,7 @@ pub async fn import_config(
     state: tauri::State<'_, AppState>,
     config_json: String
 ) -> Result<(), String> {
+    authorize(&state, "import_config").await?;
     let mut config_manager = state.config_manager.write().await;
     config_manager.import_config(&config_json).await
         .map_err(|e| e.to_string())
@@ -211,4 +1187,24 @@ pub async fn import_config(
 #[tauri::command]
 pub fn get_app_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
-}
\ No newline at end of file
+}
+
+/// 检查是否有新版本，设置中关闭后不联网
+#[tauri::command]
+pub async fn check_for_updates(
+    state: tauri::State<'_, AppState>
+) -> Result<UpdateStatus, String> {
+    authorize(&state, "check_for_updates").await?;
+    let config = state.config_manager.read().await.get_config().update.clone();
+    updater::check(&config, &get_app_version()).await
+        .map_err(|e| e.to_string())
+}
+
+/// 预览匿名使用统计将要上报的全部内容
+#[tauri::command]
+pub async fn preview_telemetry(
+    state: tauri::State<'_, AppState>
+) -> Result<TelemetryReport, String> {
+    authorize(&state, "preview_telemetry").await?;
+    Ok(state.telemetry.read().await.preview())
+}
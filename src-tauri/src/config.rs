@@ -1,7 +1,14 @@
 use crate::error::{AppError, AppResult};
-use crate::types::{LLMConfig, GameConfig, LLMProvider};
+use crate::types::{LLMConfig, GameConfig, LLMProvider, PacingProfile, RulePreset};
+use crate::ai::sentiment::SentimentConfig;
+use crate::sfx::SfxConfig;
+use crate::updater::UpdateConfig;
+use crate::telemetry::TelemetryConfig;
+use crate::voice::EncodingConfig;
+use crate::paths;
+use crate::house_rules;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use log::{info, warn};
 
@@ -12,6 +19,14 @@ pub struct AppConfig {
     pub game: GameConfig,
     pub voice: VoiceConfig,
     pub app: GeneralConfig,
+    #[serde(default)]
+    pub sentiment: SentimentConfig,
+    #[serde(default)]
+    pub sfx: SfxConfig,
+    #[serde(default)]
+    pub update: UpdateConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
 }
 
 /// 语音配置
@@ -21,6 +36,9 @@ pub struct VoiceConfig {
     pub enable_tts: bool,
     pub speech_rate: f32,
     pub volume: u8,
+    /// 录音与TTS缓存的保存编码
+    #[serde(default)]
+    pub encoding: EncodingConfig,
 }
 
 /// 通用配置
@@ -62,12 +80,46 @@ impl Default for AppConfig {
                 discussion_time: 300,
                 voting_time: 60,
                 enable_voice: false,
+                speech_timing: crate::types::SpeechTimingConfig::default(),
+                role_reveal: crate::types::RoleRevealPolicy::default(),
+                night_timing: crate::types::NightTimingConfig::default(),
+                human_seat: None,
+                reactions: crate::types::ReactionConfig::default(),
+                objections: crate::types::ObjectionConfig::default(),
+                autopilot: crate::types::AutopilotConfig::default(),
+                kill_heat_veto: crate::types::KillHeatVetoConfig::default(),
+                ai_difficulty: crate::ai::AIDifficulty::default(),
+                night_vote: crate::wolf_council::NightVoteMode::default(),
+                night_rules: crate::types::NightRules::default(),
+                passive_wolf: None,
+                speech_limits: crate::types::SpeechLimitConfig::default(),
+                embeddings: crate::embeddings::EmbeddingConfig::default(),
+                exemplars: crate::exemplars::ExemplarConfig::default(),
+                content_filter: crate::content_filter::ContentFilterConfig::default(),
+                context: crate::context::ContextConfig::default(),
+                speech_style: crate::speech_style::SpeechStyle::default(),
+                output_control: crate::output_control::OutputControlConfig::default(),
+                narrator_voice: crate::narrator::NarratorVoiceConfig::default(),
+                wake_word: crate::narrator_qa::WakeWordConfig::default(),
+                coach_mode: false,
+                beginner_tips: false,
+                auto_vote: crate::types::AutoVotePolicy::default(),
+                vote_rules: crate::types::VoteRules::default(),
+                spectator: crate::types::SpectatorConfig::default(),
+                rule_preset: None,
+                auto_advance: false,
+                auto_drive: false,
+                pacing: None,
+                plugin_seats: std::collections::HashMap::new(),
+                seat_generation: std::collections::HashMap::new(),
+                house_rules: Vec::new(),
             },
             voice: VoiceConfig {
                 enable_asr: false,
                 enable_tts: true,
                 speech_rate: 1.0,
                 volume: 80,
+                encoding: EncodingConfig::default(),
             },
             app: GeneralConfig {
                 auto_save_replay: true,
@@ -75,6 +127,10 @@ impl Default for AppConfig {
                 theme: "auto".to_string(),
                 language: "zh-CN".to_string(),
             },
+            sentiment: SentimentConfig::default(),
+            sfx: SfxConfig::default(),
+            update: UpdateConfig::default(),
+            telemetry: TelemetryConfig::default(),
         }
     }
 }
@@ -97,47 +153,32 @@ impl ConfigManager {
         })
     }
     
-    /// 获取配置文件路径
+    /// 获取配置文件路径，便携模式下位于可执行文件旁的data目录
     fn get_config_path() -> AppResult<PathBuf> {
-        // 尝试便携式模式：优先使用可执行文件目录
-        let portable_path = Self::get_portable_config_path();
-        if let Ok(path) = portable_path {
-            return Ok(path);
-        }
-        
-        // 回退到系统配置目录
-        let mut path = dirs::config_dir()
-            .ok_or_else(|| AppError::Config("无法获取配置目录".to_string()))?;
-        
-        path.push("MindWolf");
+        let paths = paths::data_paths();
+        let path = paths.config_file();
         
         // 确保目录存在
+        std::fs::create_dir_all(paths.config_dir())
+            .map_err(|e| AppError::Config(format!("创建配置目录失败: {}", e)))?;
+        
         if !path.exists() {
-            std::fs::create_dir_all(&path)
-                .map_err(|e| AppError::Config(format!("创建配置目录失败: {}", e)))?;
+            Self::migrate_legacy_config(&path);
         }
-        
-        path.push("config.json");
         Ok(path)
     }
     
-    /// 获取便携式配置路径
-    fn get_portable_config_path() -> AppResult<PathBuf> {
-        let exe_path = std::env::current_exe()
-            .map_err(|e| AppError::Config(format!("无法获取可执行文件路径: {}", e)))?;
-        
-        let exe_dir = exe_path.parent()
-            .ok_or_else(|| AppError::Config("无法获取可执行文件目录".to_string()))?;
-        
-        let config_dir = exe_dir.join("config");
-        
-        // 确保配置目录存在
-        if !config_dir.exists() {
-            std::fs::create_dir_all(&config_dir)
-                .map_err(|e| AppError::Config(format!("创建便携式配置目录失败: {}", e)))?;
+    /// 旧版本总是把配置写在可执行文件旁的config目录，首次启动时搬到新位置
+    fn migrate_legacy_config(path: &Path) {
+        let legacy = std::env::current_exe().ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join("config").join("config.json")))
+            .filter(|legacy| legacy.exists());
+        if let Some(legacy) = legacy {
+            match std::fs::copy(&legacy, path) {
+                Ok(_) => info!("已迁移旧配置文件: {:?} -> {:?}", legacy, path),
+                Err(e) => warn!("迁移旧配置文件失败: {}", e),
+            }
         }
-        
-        Ok(config_dir.join("config.json"))
     }
     
     /// 加载或创建配置
@@ -165,6 +206,11 @@ impl ConfigManager {
         }
     }
     
+    /// 配置文件所在目录（自定义词表等数据文件也放在这里）
+    pub fn config_dir(&self) -> Option<&Path> {
+        self.config_path.parent()
+    }
+    
     /// 获取当前配置
     pub fn get_config(&self) -> &AppConfig {
         &self.config
@@ -182,6 +228,26 @@ impl ConfigManager {
         self.save_config().await
     }
     
+    /// 套用规则预设
+    pub async fn apply_rule_preset(&mut self, preset: RulePreset) -> AppResult<GameConfig> {
+        // 脚本校验失败时不改动当前配置
+        let house_rules = match self.config_dir() {
+            Some(config_dir) => house_rules::load_preset_scripts(config_dir, preset)?,
+            None => Vec::new(),
+        };
+        preset.apply(&mut self.config.game);
+        self.config.game.house_rules = house_rules;
+        self.save_config().await?;
+        Ok(self.config.game.clone())
+    }
+    
+    /// 套用节奏档位，作为下一局的默认节奏
+    pub async fn apply_pacing_profile(&mut self, profile: PacingProfile) -> AppResult<GameConfig> {
+        profile.apply(&mut self.config.game);
+        self.save_config().await?;
+        Ok(self.config.game.clone())
+    }
+    
     /// 更新语音配置
     pub async fn update_voice_config(&mut self, voice_config: VoiceConfig) -> AppResult<()> {
         self.config.voice = voice_config;
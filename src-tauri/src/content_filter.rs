@@ -0,0 +1,162 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use log::warn;
+
+/// 命中过滤规则时的处理方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FilterMode {
+    /// 拒绝整条内容，提示玩家修改
+    Block,
+    /// 命中的部分替换成星号后放行
+    #[default]
+    Mask,
+    /// 原样放行，只提醒玩家
+    Warn,
+}
+
+/// 命中的规则类别
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FilterCategory {
+    Profanity,
+    PersonalInfo,
+}
+
+/// 真人输入过滤配置，在发言广播、保存和发给模型之前生效，直播或录制对局时开启
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentFilterConfig {
+    pub enabled: bool,
+    pub mode: FilterMode,
+    /// 不雅词，按子串匹配，不区分大小写
+    pub profanity: Vec<String>,
+    /// 个人信息的正则，默认覆盖手机号、身份证号和邮箱
+    pub personal_info_patterns: Vec<String>,
+}
+
+impl Default for ContentFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mode: FilterMode::default(),
+            profanity: ["傻逼", "操你", "他妈的", "fuck", "shit"]
+                .iter()
+                .map(|w| w.to_string())
+                .collect(),
+            personal_info_patterns: vec![
+                r"1[3-9]\d{9}".to_string(),
+                r"\d{17}[\dXx]".to_string(),
+                r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)+".to_string(),
+            ],
+        }
+    }
+}
+
+/// 过滤结果：content是按模式处理后的文本，categories是命中的类别
+#[derive(Debug, Clone)]
+pub struct FilterResult {
+    pub content: String,
+    pub categories: Vec<FilterCategory>,
+}
+
+impl FilterResult {
+    pub fn flagged(&self) -> bool {
+        !self.categories.is_empty()
+    }
+}
+
+/// 编译好的过滤规则，开局时按配置构建
+#[derive(Debug, Clone)]
+pub struct ContentFilter {
+    enabled: bool,
+    mode: FilterMode,
+    rules: Vec<(FilterCategory, Regex)>,
+}
+
+impl ContentFilter {
+    pub fn new(config: &ContentFilterConfig) -> Self {
+        let profanity = config.profanity.iter()
+            .map(|word| word.trim())
+            .filter(|word| !word.is_empty())
+            .map(|word| (FilterCategory::Profanity, format!("(?i){}", regex::escape(word))));
+        let personal_info = config.personal_info_patterns.iter()
+            .map(|pattern| (FilterCategory::PersonalInfo, pattern.clone()));
+        let rules = profanity.chain(personal_info)
+            .filter_map(|(category, pattern)| match Regex::new(&pattern) {
+                Ok(regex) => Some((category, regex)),
+                Err(e) => {
+                    warn!("忽略无效的过滤规则 {}: {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self { enabled: config.enabled, mode: config.mode, rules }
+    }
+
+    pub fn mode(&self) -> FilterMode {
+        self.mode
+    }
+
+    /// 检查一段真人输入；只有遮挡模式会改写文本
+    pub fn check(&self, text: &str) -> FilterResult {
+        let mut categories = Vec::new();
+        let mut ranges = Vec::new();
+        if self.enabled {
+            for (category, regex) in &self.rules {
+                for found in regex.find_iter(text) {
+                    ranges.push(found.range());
+                    if !categories.contains(category) {
+                        categories.push(*category);
+                    }
+                }
+            }
+        }
+
+        let content = if self.mode == FilterMode::Mask && !ranges.is_empty() {
+            text.char_indices()
+                .map(|(index, c)| if ranges.iter().any(|r| r.contains(&index)) { '*' } else { c })
+                .collect()
+        } else {
+            text.to_string()
+        };
+        FilterResult { content, categories }
+    }
+}
+
+impl Default for ContentFilter {
+    fn default() -> Self {
+        Self::new(&ContentFilterConfig::default())
+    }
+}
+
+/// 提示玩家时用的类别名称
+pub fn describe(categories: &[FilterCategory]) -> String {
+    categories.iter()
+        .map(|category| match category {
+            FilterCategory::Profanity => "不雅用语",
+            FilterCategory::PersonalInfo => "个人信息",
+        })
+        .collect::<Vec<_>>()
+        .join("、")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_profanity_and_personal_info() {
+        let filter = ContentFilter::default();
+        let result = filter.check("我是预言家，有事打13812345678，FUCK");
+        assert_eq!(result.content, "我是预言家，有事打***********，****");
+        assert_eq!(result.categories, vec![FilterCategory::Profanity, FilterCategory::PersonalInfo]);
+
+        let clean = filter.check("3号发言有问题，我出3号");
+        assert!(!clean.flagged());
+        assert_eq!(clean.content, "3号发言有问题，我出3号");
+
+        let warn_only = ContentFilter::new(&ContentFilterConfig { mode: FilterMode::Warn, ..Default::default() });
+        let result = warn_only.check("联系我 wolf@example.com");
+        assert!(result.flagged());
+        assert_eq!(result.content, "联系我 wolf@example.com");
+    }
+}
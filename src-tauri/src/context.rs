@@ -0,0 +1,185 @@
+use crate::embeddings::{self, EmbeddedSpeech};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 提示词上下文的选择配置
+///
+/// 大板子发言很多，不再按条数直接塞进提示词，而是给每条发言打分，
+/// 在固定的字数预算内从高分往低分挑选，提示词长度与人数无关
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextConfig {
+    /// 相关发言的总字数预算
+    pub char_budget: usize,
+    /// 与最新发言的语义相似度权重
+    pub similarity_weight: f32,
+    /// 发言提到了当前玩家
+    pub mention_weight: f32,
+    /// 发言者有身份声明或查验报告
+    pub claim_weight: f32,
+    /// 发言者或发言内容涉及当前玩家最怀疑的人
+    pub suspect_weight: f32,
+    /// 越新的发言得分越高
+    pub recency_weight: f32,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            char_budget: 600,
+            similarity_weight: 1.0,
+            mention_weight: 1.5,
+            claim_weight: 1.0,
+            suspect_weight: 0.8,
+            recency_weight: 0.5,
+        }
+    }
+}
+
+/// 为某位玩家挑选上下文时关注的对象
+pub struct ContextFocus {
+    pub seat: u8,
+    pub name: String,
+    /// 当前有身份声明或查验报告的玩家
+    pub claimants: HashSet<String>,
+    /// 该玩家最怀疑的几名玩家的座位号（按玩家ID）
+    pub suspects: Vec<(String, u8)>,
+}
+
+impl ContextFocus {
+    pub fn new(player: &Player, state: &GameState, suspects: Vec<String>) -> Self {
+        let board = &state.claims_board;
+        let claimants = board.claims.iter()
+            .map(|c| c.player_id.clone())
+            .chain(board.check_reports.iter().map(|r| r.reporter.clone()))
+            .collect();
+        let suspects = suspects.into_iter()
+            .filter_map(|id| {
+                state.players.iter()
+                    .chain(state.dead_players.iter())
+                    .find(|p| p.id == id)
+                    .map(|p| (id.clone(), p.seat))
+            })
+            .collect();
+
+        Self {
+            seat: player.seat,
+            name: player.name.clone(),
+            claimants,
+            suspects,
+        }
+    }
+}
+
+/// 单条发言的相关性得分
+///
+/// recency为发言在本局中的新旧程度（0最旧，1最新）
+pub fn score(config: &ContextConfig, focus: &ContextFocus, speech: &EmbeddedSpeech, query: &[f32], recency: f32) -> f32 {
+    let mut score = config.similarity_weight * embeddings::cosine(&speech.vector, query).max(0.0)
+        + config.recency_weight * recency;
+
+    if mentions_seat(&speech.content, focus.seat) || (!focus.name.is_empty() && speech.content.contains(&focus.name)) {
+        score += config.mention_weight;
+    }
+    if focus.claimants.contains(&speech.speaker) {
+        score += config.claim_weight;
+    }
+    let about_suspect = focus.suspects.iter()
+        .any(|(id, seat)| &speech.speaker == id || mentions_seat(&speech.content, *seat));
+    if about_suspect {
+        score += config.suspect_weight;
+    }
+    score
+}
+
+/// 在字数预算和条数上限内挑选最相关的发言，按发言顺序返回
+pub fn select<'a>(config: &ContextConfig, focus: &ContextFocus, speeches: &'a [EmbeddedSpeech], limit: usize) -> Vec<&'a EmbeddedSpeech> {
+    let latest = match speeches.last() {
+        Some(latest) => latest,
+        None => return Vec::new(),
+    };
+    let span = speeches.len().saturating_sub(1).max(1) as f32;
+
+    let mut ranked: Vec<(f32, &EmbeddedSpeech)> = speeches.iter()
+        .enumerate()
+        .map(|(index, speech)| (score(config, focus, speech, &latest.vector, index as f32 / span), speech))
+        .collect();
+    ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut used = 0;
+    let mut picked: Vec<&EmbeddedSpeech> = Vec::new();
+    for (_, speech) in ranked {
+        if picked.len() >= limit {
+            break;
+        }
+        let chars = speech.content.chars().count();
+        if used + chars > config.char_budget {
+            continue;
+        }
+        used += chars;
+        picked.push(speech);
+    }
+    picked.sort_by_key(|s| s.sequence);
+    picked
+}
+
+/// 内容是否提到了某个座位号（“3号”不会匹配到“13号”）
+fn mentions_seat(content: &str, seat: u8) -> bool {
+    let needle = format!("{}号", seat);
+    content.match_indices(&needle).any(|(index, _)| {
+        !content[..index].chars().next_back().is_some_and(|c| c.is_ascii_digit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speech(sequence: u64, speaker: &str, content: &str) -> EmbeddedSpeech {
+        EmbeddedSpeech {
+            sequence,
+            speaker: speaker.to_string(),
+            day: 1,
+            content: content.to_string(),
+            vector: embeddings::local_embedding(content),
+        }
+    }
+
+    fn focus() -> ContextFocus {
+        ContextFocus {
+            seat: 3,
+            name: "冷静的棋手".to_string(),
+            claimants: HashSet::from(["ai_1".to_string()]),
+            suspects: vec![("ai_7".to_string(), 7)],
+        }
+    }
+
+    #[test]
+    fn test_mentions_seat_ignores_longer_numbers() {
+        assert!(mentions_seat("我怀疑3号", 3));
+        assert!(!mentions_seat("我怀疑13号", 3));
+        assert!(mentions_seat("13号和3号都可疑", 3));
+    }
+
+    #[test]
+    fn test_select_prefers_relevant_speeches_within_budget() {
+        let mut speeches: Vec<EmbeddedSpeech> = (0..40)
+            .map(|i| speech(i, &format!("ai_{}", 10 + i % 8), "过，我没什么信息"))
+            .collect();
+        speeches.insert(5, speech(100, "ai_1", "我是预言家，昨晚查验了5号"));
+        speeches.insert(10, speech(101, "ai_12", "3号发言很奇怪"));
+        speeches.insert(15, speech(102, "ai_9", "7号一直在划水"));
+        speeches.push(speech(103, "ai_2", "同意前面的分析"));
+
+        let config = ContextConfig { char_budget: 60, ..ContextConfig::default() };
+        let picked = select(&config, &focus(), &speeches, 4);
+        let sequences: Vec<u64> = picked.iter().map(|s| s.sequence).collect();
+
+        assert!(picked.iter().map(|s| s.content.chars().count()).sum::<usize>() <= 60);
+        assert!(picked.len() <= 4);
+        assert!(sequences.contains(&100));
+        assert!(sequences.contains(&101));
+        assert!(sequences.contains(&102));
+        assert!(sequences.contains(&103));
+    }
+}
@@ -6,7 +6,7 @@ use sqlx::SqlitePool;
 use log::{info, warn};
 
 /// 数据库版本
-const CURRENT_VERSION: i32 = 1;
+pub const CURRENT_VERSION: i32 = 8;
 
 /// 运行数据库迁移
 pub async fn run_migrations(pool: &SqlitePool) -> AppResult<()> {
@@ -42,7 +42,7 @@ pub async fn run_migrations(pool: &SqlitePool) -> AppResult<()> {
 }
 
 /// 获取当前数据库版本
-async fn get_current_version(pool: &SqlitePool) -> AppResult<i32> {
+pub async fn get_current_version(pool: &SqlitePool) -> AppResult<i32> {
     let version = sqlx::query_scalar::<_, Option<i32>>(
         "SELECT MAX(version) FROM schema_migrations"
     )
@@ -59,6 +59,13 @@ async fn apply_migration(pool: &SqlitePool, version: i32) -> AppResult<()> {
     
     match version {
         1 => apply_migration_v1(pool).await?,
+        2 => apply_migration_v2(pool).await?,
+        3 => apply_migration_v3(pool).await?,
+        4 => apply_migration_v4(pool).await?,
+        5 => apply_migration_v5(pool).await?,
+        6 => apply_migration_v6(pool).await?,
+        7 => apply_migration_v7(pool).await?,
+        8 => apply_migration_v8(pool).await?,
         _ => {
             warn!("未知的迁移版本: {}", version);
             return Err(AppError::Database(format!("未知的迁移版本: {}", version)));
@@ -85,6 +92,160 @@ async fn apply_migration_v1(pool: &SqlitePool) -> AppResult<()> {
     Ok(())
 }
 
+/// 迁移版本2：玩家记录增加座位号
+async fn apply_migration_v2(pool: &SqlitePool) -> AppResult<()> {
+    info!("应用迁移v2：player_records增加seat列");
+    
+    sqlx::query("ALTER TABLE player_records ADD COLUMN seat INTEGER")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("添加seat列失败: {}", e)))?;
+    
+    Ok(())
+}
+
+/// 迁移版本3：标记行动者或目标不是真实玩家的历史夜晚行动
+async fn apply_migration_v3(pool: &SqlitePool) -> AppResult<()> {
+    info!("应用迁移v3：night_action_records增加malformed列");
+    
+    sqlx::query("ALTER TABLE night_action_records ADD COLUMN malformed BOOLEAN NOT NULL DEFAULT 0")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("添加malformed列失败: {}", e)))?;
+    
+    // 早期版本会把"self"、"werewolf"等占位符当作行动者写入
+    let result = sqlx::query(
+        r#"
+        UPDATE night_action_records SET malformed = 1
+        WHERE player_id NOT IN (
+            SELECT id FROM player_records WHERE player_records.game_id = night_action_records.game_id
+        )
+        OR (target_id IS NOT NULL AND target_id NOT IN (
+            SELECT id FROM player_records WHERE player_records.game_id = night_action_records.game_id
+        ))
+        "#
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("标记异常夜晚行动失败: {}", e)))?;
+    
+    if result.rows_affected() > 0 {
+        warn!("发现{}条行动者或目标无效的历史夜晚行动，已标记为malformed", result.rows_affected());
+    }
+    
+    Ok(())
+}
+
+/// 迁移版本4：发言记录增加管道序号
+async fn apply_migration_v4(pool: &SqlitePool) -> AppResult<()> {
+    info!("应用迁移v4：speech_records增加sequence列");
+    
+    sqlx::query("ALTER TABLE speech_records ADD COLUMN sequence INTEGER")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("添加sequence列失败: {}", e)))?;
+    
+    Ok(())
+}
+
+/// 迁移版本5：speech_records增加发言向量
+async fn apply_migration_v5(pool: &SqlitePool) -> AppResult<()> {
+    info!("应用迁移v5：speech_records增加embedding列");
+    
+    for statement in [
+        "ALTER TABLE speech_records ADD COLUMN embedding TEXT",
+        "ALTER TABLE speech_records ADD COLUMN embedding_model TEXT",
+    ] {
+        sqlx::query(statement)
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("添加embedding列失败: {}", e)))?;
+    }
+    
+    Ok(())
+}
+
+/// 迁移版本6：game_records增加结算结果
+async fn apply_migration_v6(pool: &SqlitePool) -> AppResult<()> {
+    info!("应用迁移v6：game_records增加结算结果列");
+    
+    for statement in [
+        "ALTER TABLE game_records ADD COLUMN total_votes INTEGER",
+        "ALTER TABLE game_records ADD COLUMN players_killed TEXT",
+        "ALTER TABLE game_records ADD COLUMN survivors TEXT",
+    ] {
+        sqlx::query(statement)
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("添加结算结果列失败: {}", e)))?;
+    }
+    
+    Ok(())
+}
+
+/// 迁移版本7：game_records增加中断对局的标记
+async fn apply_migration_v7(pool: &SqlitePool) -> AppResult<()> {
+    info!("应用迁移v7：game_records增加abandoned列");
+    
+    for statement in [
+        "ALTER TABLE game_records ADD COLUMN abandoned BOOLEAN NOT NULL DEFAULT 0",
+        "ALTER TABLE game_records ADD COLUMN salvaged_events INTEGER",
+    ] {
+        sqlx::query(statement)
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("添加abandoned列失败: {}", e)))?;
+    }
+    
+    Ok(())
+}
+
+/// 迁移版本8：player_records改为按(game_id, player_id)区分
+///
+/// 玩家ID每局都一样，原来以玩家ID为主键，第二局起写入玩家记录就会主键冲突
+async fn apply_migration_v8(pool: &SqlitePool) -> AppResult<()> {
+    info!("应用迁移v8：player_records增加player_id列");
+    
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::Database(format!("开启事务失败: {}", e)))?;
+    for statement in [
+        r#"
+        CREATE TABLE player_records_v8 (
+            id TEXT PRIMARY KEY,
+            game_id TEXT NOT NULL,
+            player_id TEXT NOT NULL,
+            player_name TEXT NOT NULL,
+            role_type TEXT NOT NULL,
+            faction TEXT NOT NULL,
+            is_ai BOOLEAN NOT NULL,
+            is_winner BOOLEAN NOT NULL,
+            elimination_day INTEGER,
+            final_votes INTEGER DEFAULT 0,
+            seat INTEGER,
+            UNIQUE (game_id, player_id),
+            FOREIGN KEY (game_id) REFERENCES game_records (id)
+        )
+        "#,
+        r#"
+        INSERT INTO player_records_v8
+            (id, game_id, player_id, player_name, role_type, faction, is_ai, is_winner, elimination_day, final_votes, seat)
+        SELECT game_id || ':' || id, game_id, id, player_name, role_type, faction, is_ai, is_winner, elimination_day, final_votes, seat
+        FROM player_records
+        "#,
+        "DROP TABLE player_records",
+        "ALTER TABLE player_records_v8 RENAME TO player_records",
+    ] {
+        sqlx::query(statement)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("重建player_records失败: {}", e)))?;
+    }
+    tx.commit().await
+        .map_err(|e| AppError::Database(format!("提交player_records迁移失败: {}", e)))?;
+    
+    Ok(())
+}
+
 /// 回滚迁移（紧急情况使用）
 pub async fn rollback_migration(pool: &SqlitePool, target_version: i32) -> AppResult<()> {
     let current_version = get_current_version(pool).await?;
@@ -96,7 +257,8 @@ pub async fn rollback_migration(pool: &SqlitePool, target_version: i32) -> AppRe
     
     warn!("回滚数据库从版本 {} 到 {}", current_version, target_version);
     
-    for version in (target_version + 1)..=current_version {
+    // 从新到旧依次回滚
+    for version in ((target_version + 1)..=current_version).rev() {
         rollback_migration_version(pool, version).await?;
     }
     
@@ -109,6 +271,13 @@ async fn rollback_migration_version(pool: &SqlitePool, version: i32) -> AppResul
     
     match version {
         1 => rollback_migration_v1(pool).await?,
+        2 => rollback_migration_v2(pool).await?,
+        3 => rollback_migration_v3(pool).await?,
+        4 => rollback_migration_v4(pool).await?,
+        5 => rollback_migration_v5(pool).await?,
+        6 => rollback_migration_v6(pool).await?,
+        7 => rollback_migration_v7(pool).await?,
+        8 => rollback_migration_v8(pool).await?,
         _ => {
             warn!("未知的回滚版本: {}", version);
         }
@@ -124,15 +293,151 @@ async fn rollback_migration_version(pool: &SqlitePool, version: i32) -> AppResul
     Ok(())
 }
 
+/// 回滚版本8：玩家ID重新作为主键，同一玩家ID只保留最早一局的记录
+async fn rollback_migration_v8(pool: &SqlitePool) -> AppResult<()> {
+    warn!("回滚v8：删除player_records.player_id列");
+    
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::Database(format!("开启事务失败: {}", e)))?;
+    for statement in [
+        r#"
+        CREATE TABLE player_records_v7 (
+            id TEXT PRIMARY KEY,
+            game_id TEXT NOT NULL,
+            player_name TEXT NOT NULL,
+            role_type TEXT NOT NULL,
+            faction TEXT NOT NULL,
+            is_ai BOOLEAN NOT NULL,
+            is_winner BOOLEAN NOT NULL,
+            elimination_day INTEGER,
+            final_votes INTEGER DEFAULT 0,
+            seat INTEGER,
+            FOREIGN KEY (game_id) REFERENCES game_records (id)
+        )
+        "#,
+        r#"
+        INSERT OR IGNORE INTO player_records_v7
+            (id, game_id, player_name, role_type, faction, is_ai, is_winner, elimination_day, final_votes, seat)
+        SELECT p.player_id, p.game_id, p.player_name, p.role_type, p.faction, p.is_ai, p.is_winner, p.elimination_day, p.final_votes, p.seat
+        FROM player_records p JOIN game_records g ON g.id = p.game_id
+        ORDER BY g.start_time
+        "#,
+        "DROP TABLE player_records",
+        "ALTER TABLE player_records_v7 RENAME TO player_records",
+    ] {
+        sqlx::query(statement)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Database(format!("回滚player_records失败: {}", e)))?;
+    }
+    tx.commit().await
+        .map_err(|e| AppError::Database(format!("提交player_records回滚失败: {}", e)))?;
+    
+    Ok(())
+}
+
+/// 回滚版本7
+async fn rollback_migration_v7(pool: &SqlitePool) -> AppResult<()> {
+    warn!("回滚v7：删除game_records.abandoned列");
+    
+    for statement in [
+        "ALTER TABLE game_records DROP COLUMN salvaged_events",
+        "ALTER TABLE game_records DROP COLUMN abandoned",
+    ] {
+        sqlx::query(statement)
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("删除abandoned列失败: {}", e)))?;
+    }
+    
+    Ok(())
+}
+
+/// 回滚版本6
+async fn rollback_migration_v6(pool: &SqlitePool) -> AppResult<()> {
+    warn!("回滚v6：删除game_records结算结果列");
+    
+    for statement in [
+        "ALTER TABLE game_records DROP COLUMN survivors",
+        "ALTER TABLE game_records DROP COLUMN players_killed",
+        "ALTER TABLE game_records DROP COLUMN total_votes",
+    ] {
+        sqlx::query(statement)
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("删除结算结果列失败: {}", e)))?;
+    }
+    
+    Ok(())
+}
+
+/// 回滚版本5
+async fn rollback_migration_v5(pool: &SqlitePool) -> AppResult<()> {
+    warn!("回滚v5：删除speech_records.embedding列");
+    
+    for statement in [
+        "ALTER TABLE speech_records DROP COLUMN embedding_model",
+        "ALTER TABLE speech_records DROP COLUMN embedding",
+    ] {
+        sqlx::query(statement)
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("删除embedding列失败: {}", e)))?;
+    }
+    
+    Ok(())
+}
+
+/// 回滚版本4
+async fn rollback_migration_v4(pool: &SqlitePool) -> AppResult<()> {
+    warn!("回滚v4：删除speech_records.sequence列");
+    
+    sqlx::query("ALTER TABLE speech_records DROP COLUMN sequence")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("删除sequence列失败: {}", e)))?;
+    
+    Ok(())
+}
+
+/// 回滚版本3
+async fn rollback_migration_v3(pool: &SqlitePool) -> AppResult<()> {
+    warn!("回滚v3：删除night_action_records.malformed列");
+    
+    sqlx::query("ALTER TABLE night_action_records DROP COLUMN malformed")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("删除malformed列失败: {}", e)))?;
+    
+    Ok(())
+}
+
+/// 回滚版本2
+async fn rollback_migration_v2(pool: &SqlitePool) -> AppResult<()> {
+    warn!("回滚v2：删除player_records.seat列");
+    
+    sqlx::query("ALTER TABLE player_records DROP COLUMN seat")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("删除seat列失败: {}", e)))?;
+    
+    Ok(())
+}
+
 /// 回滚版本1
 async fn rollback_migration_v1(pool: &SqlitePool) -> AppResult<()> {
     warn!("回滚v1：删除所有表");
     
     let tables = [
+        "player_notes",
+        "suspicion_snapshots",
+        "ai_decision_records",
+        "chat_messages",
         "ai_analysis_records",
         "night_action_records", 
         "vote_records",
         "speech_records",
+        "player_personas",
         "player_records",
         "game_records"
     ];
@@ -6,6 +6,7 @@ pub use models::*;
 pub use repository::*;
 
 use crate::error::{AppError, AppResult};
+use crate::paths;
 use sqlx::{SqlitePool, Row};
 use std::path::PathBuf;
 use log::{info, error};
@@ -40,15 +41,9 @@ impl DatabaseManager {
         Ok(manager)
     }
     
-    /// 获取数据库路径
+    /// 获取数据库路径，便携模式下位于可执行文件旁的data目录
     fn get_database_path() -> AppResult<PathBuf> {
-        let mut path = dirs::data_dir()
-            .ok_or_else(|| AppError::Database("无法获取数据目录".to_string()))?;
-        
-        path.push("MindWolf");
-        path.push("mindwolf.db");
-        
-        Ok(path)
+        Ok(paths::data_paths().database_file())
     }
     
     /// 运行数据库迁移
@@ -95,6 +90,25 @@ impl DatabaseManager {
         .await
         .map_err(|e| AppError::Database(format!("创建player_records表失败: {}", e)))?;
         
+        // 创建AI人设表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS player_personas (
+                game_id TEXT NOT NULL,
+                player_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                avatar TEXT NOT NULL,
+                bio TEXT NOT NULL,
+                template_id TEXT NOT NULL,
+                PRIMARY KEY (game_id, player_id),
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建player_personas表失败: {}", e)))?;
+        
         // 创建发言记录表
         sqlx::query(
             r#"
@@ -173,6 +187,166 @@ impl DatabaseManager {
         .await
         .map_err(|e| AppError::Database(format!("创建ai_analysis_records表失败: {}", e)))?;
         
+        // 创建频道聊天记录表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS chat_messages (
+                id TEXT PRIMARY KEY,
+                game_id TEXT NOT NULL,
+                channel TEXT NOT NULL,
+                sender_id TEXT NOT NULL,
+                content TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                phase TEXT NOT NULL,
+                timestamp DATETIME NOT NULL,
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建chat_messages表失败: {}", e)))?;
+        
+        // 创建AI决策审计表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS ai_decision_records (
+                id TEXT PRIMARY KEY,
+                game_id TEXT NOT NULL,
+                player_id TEXT NOT NULL,
+                decision_type TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                phase TEXT NOT NULL,
+                context TEXT NOT NULL,
+                reasoning TEXT NOT NULL,
+                confidence REAL NOT NULL,
+                alternatives TEXT NOT NULL,
+                execution_time_ms INTEGER NOT NULL,
+                served_by TEXT,
+                timestamp DATETIME NOT NULL,
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建ai_decision_records表失败: {}", e)))?;
+        
+        // 创建怀疑度快照表（每天结束时记录）
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS suspicion_snapshots (
+                id TEXT PRIMARY KEY,
+                game_id TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                player_id TEXT NOT NULL,
+                werewolf_probability REAL NOT NULL,
+                suspicion_score REAL NOT NULL,
+                trust_score REAL NOT NULL,
+                timestamp DATETIME NOT NULL,
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建suspicion_snapshots表失败: {}", e)))?;
+        
+        // 创建玩家笔记表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS player_notes (
+                id TEXT PRIMARY KEY,
+                game_id TEXT NOT NULL,
+                player_id TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                suspected_roles TEXT NOT NULL,
+                claims_heard TEXT NOT NULL,
+                timestamp DATETIME NOT NULL,
+                UNIQUE (game_id, player_id, day),
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建player_notes表失败: {}", e)))?;
+        
+        // 创建复盘事件转存表（内存中淘汰的早期事件）
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS replay_events (
+                game_id TEXT NOT NULL,
+                event_index INTEGER NOT NULL,
+                event_type TEXT NOT NULL,
+                event_data TEXT NOT NULL,
+                chain_hash TEXT NOT NULL,
+                PRIMARY KEY (game_id, event_index),
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建replay_events表失败: {}", e)))?;
+        
+        // 创建放逐投票结果表（含每名候选人的得票明细）
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS vote_outcomes (
+                id TEXT PRIMARY KEY,
+                game_id TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                tally TEXT NOT NULL,
+                eliminated TEXT,
+                tied TEXT NOT NULL,
+                timestamp DATETIME NOT NULL,
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建vote_outcomes表失败: {}", e)))?;
+        
+        // 创建私信表（预言家查验、女巫刀口等只给单个玩家的信息）
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS private_messages (
+                id TEXT PRIMARY KEY,
+                game_id TEXT NOT NULL,
+                player_id TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                timestamp DATETIME NOT NULL,
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建private_messages表失败: {}", e)))?;
+        
+        // 创建赛后事实表（每局一份，JSON）
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS fact_sheets (
+                game_id TEXT PRIMARY KEY,
+                version INTEGER NOT NULL,
+                data TEXT NOT NULL,
+                created_at DATETIME NOT NULL,
+                FOREIGN KEY (game_id) REFERENCES game_records (id)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("创建fact_sheets表失败: {}", e)))?;
+        
+        // 建表之后的结构变更按版本号执行
+        migrations::run_migrations(&self.pool).await?;
+        
         info!("数据库迁移完成");
         Ok(())
     }
@@ -12,6 +12,15 @@ pub struct GameRecord {
     pub player_count: i32,
     pub duration_seconds: Option<i32>,
     pub created_at: DateTime<Utc>,
+    pub total_votes: Option<i32>,
+    /// JSON格式的出局玩家列表
+    pub players_killed: Option<String>,
+    /// JSON格式的存活玩家列表
+    pub survivors: Option<String>,
+    /// 上次运行中断、启动时被收尾的对局
+    pub abandoned: bool,
+    /// 收尾时保留下来的复盘事件数
+    pub salvaged_events: Option<i32>,
 }
 
 /// 玩家记录模型
@@ -19,6 +28,7 @@ pub struct GameRecord {
 pub struct PlayerRecord {
     pub id: String,
     pub game_id: String,
+    pub player_id: String,
     pub player_name: String,
     pub role_type: String,
     pub faction: String,
@@ -26,6 +36,7 @@ pub struct PlayerRecord {
     pub is_winner: bool,
     pub elimination_day: Option<i32>,
     pub final_votes: i32,
+    pub seat: Option<i32>,
 }
 
 /// 发言记录模型
@@ -39,6 +50,11 @@ pub struct SpeechRecord {
     pub phase: String,
     pub timestamp: DateTime<Utc>,
     pub analysis_result: Option<String>, // JSON格式的分析结果
+    /// 发言管道分配的序号（旧数据为空）
+    pub sequence: Option<i64>,
+    /// 发言向量（JSON数组）
+    pub embedding: Option<String>,
+    pub embedding_model: Option<String>,
 }
 
 /// 投票记录模型
@@ -64,6 +80,8 @@ pub struct NightActionRecord {
     pub night: i32,
     pub result: Option<String>,
     pub timestamp: DateTime<Utc>,
+    /// 历史数据中行动者或目标不是真实玩家
+    pub malformed: bool,
 }
 
 /// AI分析记录模型
@@ -78,15 +96,115 @@ pub struct AIAnalysisRecord {
     pub timestamp: DateTime<Utc>,
 }
 
+/// AI决策审计记录模型
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AIDecisionRecord {
+    pub id: String,
+    pub game_id: String,
+    pub player_id: String,
+    pub decision_type: String,
+    pub day: i32,
+    pub phase: String,
+    pub context: String, // JSON格式的决策上下文快照
+    pub reasoning: String,
+    pub confidence: f32,
+    pub alternatives: String, // JSON格式的备选决策
+    pub execution_time_ms: i64,
+    pub served_by: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 转存的复盘事件记录模型
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ReplayEventRecord {
+    pub game_id: String,
+    pub event_index: i64,
+    pub event_type: String,
+    pub event_data: String,
+    pub chain_hash: String,
+}
+
+/// 放逐投票结果记录模型，tally与tied为JSON
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct VoteOutcomeRecord {
+    pub id: String,
+    pub game_id: String,
+    pub day: i32,
+    pub tally: String,
+    pub eliminated: Option<String>,
+    pub tied: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 赛后事实表记录模型，data为JSON
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FactSheetRecord {
+    pub game_id: String,
+    pub version: i32,
+    pub data: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 私信记录模型，content为JSON
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PrivateMessageRecord {
+    pub id: String,
+    pub game_id: String,
+    pub player_id: String,
+    pub day: i32,
+    pub content: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 怀疑度快照记录模型
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SuspicionSnapshotRecord {
+    pub id: String,
+    pub game_id: String,
+    pub day: i32,
+    pub player_id: String,
+    pub werewolf_probability: f32,
+    pub suspicion_score: f32,
+    pub trust_score: f32,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 玩家笔记记录模型
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PlayerNoteRecord {
+    pub id: String,
+    pub game_id: String,
+    pub player_id: String,
+    pub day: i32,
+    pub content: String,
+    pub suspected_roles: String, // JSON格式的身份怀疑
+    pub claims_heard: String,    // JSON格式的身份声明
+    pub timestamp: DateTime<Utc>,
+}
+
+/// AI人设记录模型
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PlayerPersonaRecord {
+    pub game_id: String,
+    pub player_id: String,
+    pub name: String,
+    pub avatar: String,
+    pub bio: String,
+    pub template_id: String,
+}
+
 /// 游戏详情（包含所有相关记录）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameDetails {
     pub game: GameRecord,
     pub players: Vec<PlayerRecord>,
+    pub personas: Vec<PlayerPersonaRecord>,
     pub speeches: Vec<SpeechRecord>,
     pub votes: Vec<VoteRecord>,
+    pub vote_outcomes: Vec<VoteOutcomeRecord>,
     pub night_actions: Vec<NightActionRecord>,
     pub ai_analyses: Vec<AIAnalysisRecord>,
+    pub notes: Vec<PlayerNoteRecord>,
 }
 
 /// 游戏统计
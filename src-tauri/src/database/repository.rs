@@ -1,9 +1,15 @@
-use crate::database::models::{GameRecord, PlayerRecord, GameDetails, SpeechRecord as ModelsSpeechRecord, VoteRecord as ModelsVoteRecord, NightActionRecord, AIAnalysisRecord};
+use crate::database::models::{GameRecord, PlayerRecord, GameDetails, SpeechRecord as ModelsSpeechRecord, VoteRecord as ModelsVoteRecord, NightActionRecord, AIAnalysisRecord, AIDecisionRecord, SuspicionSnapshotRecord, PlayerNoteRecord, PlayerPersonaRecord, ReplayEventRecord, VoteOutcomeRecord, PrivateMessageRecord, FactSheetRecord};
+use crate::replay::{self, AIDecision, GameEvent as ReplayEvent};
+use crate::ai::PlayerAnalysis;
+use crate::fact_sheet::FactSheet;
+use crate::chat::ChannelMessage;
+use crate::events::PrivateMessage;
 use crate::error::{AppError, AppResult};
-use crate::types::{GameState, Faction, ChatMessage, GamePhase, VoteRecord as TypesVoteRecord, NightAction, Player, RoleType, NightActionType};
-use sqlx::SqlitePool;
+use crate::types::{GameState, Faction, PlayerNote, ChatMessage, GamePhase, VoteRecord as TypesVoteRecord, VoteOutcome, GameResult, NightAction, Player, RoleType, NightActionType};
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::HashMap;
 use chrono::Utc;
-use log::{info, debug};
+use log::{info, debug, warn};
 use uuid::Uuid;
 
 /// 游戏记录仓库
@@ -22,6 +28,10 @@ impl GameRepository {
         let config_json = serde_json::to_string(&game_state.game_config)
             .map_err(|e| AppError::Serialization(e.to_string()))?;
         
+        // 对局和玩家一起写入，任何一步失败都不留下没有玩家的对局
+        let mut tx = self.pool.begin().await
+            .map_err(|e| AppError::Database(format!("开启事务失败: {}", e)))?;
+        
         sqlx::query(
             r#"
             INSERT INTO game_records (id, config, start_time, player_count)
@@ -32,50 +42,81 @@ impl GameRepository {
         .bind(&config_json)
         .bind(Utc::now())
         .bind(game_state.players.len() as i32)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| AppError::Database(format!("创建游戏记录失败: {}", e)))?;
         
         // 创建玩家记录
         for player in &game_state.players {
-            self.create_player_record(&game_id, player).await?;
+            self.create_player_record(&mut tx, &game_id, player).await?;
+            
+            if let Some(persona) = &player.persona {
+                sqlx::query(
+                    r#"
+                    INSERT INTO player_personas (game_id, player_id, name, avatar, bio, template_id)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    "#
+                )
+                .bind(&game_id)
+                .bind(&player.id)
+                .bind(&persona.name)
+                .bind(&persona.avatar)
+                .bind(&persona.bio)
+                .bind(&persona.template_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::Database(format!("保存AI人设失败: {}", e)))?;
+            }
         }
         
+        tx.commit().await
+            .map_err(|e| AppError::Database(format!("提交游戏记录失败: {}", e)))?;
         info!("创建游戏记录: {}", game_id);
         Ok(game_id)
     }
     
-    /// 更新游戏结束信息
-    pub async fn finish_game(&self, game_id: &str, winner: Option<&Faction>, duration_seconds: i32) -> AppResult<()> {
-        let winner_str = winner.map(|f| match f {
-            Faction::Werewolf => "werewolf",
-            Faction::Villager => "villager",
-        });
+    /// 更新游戏结束信息，并标记获胜阵营的玩家
+    pub async fn finish_game(&self, game_id: &str, result: &GameResult) -> AppResult<()> {
+        let winner = self.faction_to_string(&result.winner);
         
         sqlx::query(
-            "UPDATE game_records SET end_time = ?, winner = ?, duration_seconds = ? WHERE id = ?"
+            r#"
+            UPDATE game_records
+            SET end_time = ?, winner = ?, duration_seconds = ?, total_votes = ?, players_killed = ?, survivors = ?
+            WHERE id = ?
+            "#
         )
         .bind(Utc::now())
-        .bind(winner_str)
-        .bind(duration_seconds)
+        .bind(&winner)
+        .bind(result.game_duration as i32)
+        .bind(result.total_votes as i32)
+        .bind(serde_json::to_string(&result.players_killed)?)
+        .bind(serde_json::to_string(&result.survivors)?)
         .bind(game_id)
         .execute(&self.pool)
         .await
         .map_err(|e| AppError::Database(format!("更新游戏结束信息失败: {}", e)))?;
         
-        info!("游戏 {} 已结束，获胜方: {:?}", game_id, winner);
+        sqlx::query("UPDATE player_records SET is_winner = (faction = ?) WHERE game_id = ?")
+            .bind(&winner)
+            .bind(game_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("更新获胜玩家失败: {}", e)))?;
+        
+        info!("游戏 {} 已结束，获胜方: {:?}", game_id, result.winner);
         Ok(())
     }
     
     /// 记录发言
-    pub async fn record_speech(&self, game_id: &str, speech: &ChatMessage, day: u32, phase: &GamePhase) -> AppResult<()> {
+    pub async fn record_speech(&self, game_id: &str, speech: &ChatMessage, day: u32, phase: &GamePhase, sequence: u64) -> AppResult<()> {
         let speech_id = Uuid::new_v4().to_string();
         let phase_str = self.phase_to_string(phase);
         
         sqlx::query(
             r#"
-            INSERT INTO speech_records (id, game_id, player_id, content, day, phase, timestamp)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO speech_records (id, game_id, player_id, content, day, phase, timestamp, sequence)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#
         )
         .bind(&speech_id)
@@ -85,6 +126,7 @@ impl GameRepository {
         .bind(day as i32)
         .bind(&phase_str)
         .bind(speech.timestamp)
+        .bind(sequence as i64)
         .execute(&self.pool)
         .await
         .map_err(|e| AppError::Database(format!("记录发言失败: {}", e)))?;
@@ -93,6 +135,20 @@ impl GameRepository {
         Ok(())
     }
     
+    /// 为已落库的发言写入向量
+    pub async fn record_speech_embedding(&self, game_id: &str, sequence: u64, model: &str, vector: &[f32]) -> AppResult<()> {
+        sqlx::query("UPDATE speech_records SET embedding = ?, embedding_model = ? WHERE game_id = ? AND sequence = ?")
+            .bind(crate::embeddings::encode_vector(vector)?)
+            .bind(model)
+            .bind(game_id)
+            .bind(sequence as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("记录发言向量失败: {}", e)))?;
+        
+        Ok(())
+    }
+    
     /// 记录投票
     pub async fn record_vote(&self, game_id: &str, vote: &TypesVoteRecord, day: u32, round: u32) -> AppResult<()> {
         let vote_id = Uuid::new_v4().to_string();
@@ -118,6 +174,63 @@ impl GameRepository {
         Ok(())
     }
     
+    /// 记录一轮放逐投票的计票结果
+    pub async fn record_vote_outcome(&self, game_id: &str, outcome: &VoteOutcome) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO vote_outcomes (id, game_id, day, tally, eliminated, tied, timestamp)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(game_id)
+        .bind(outcome.day as i32)
+        .bind(serde_json::to_string(&outcome.tally)?)
+        .bind(&outcome.eliminated)
+        .bind(serde_json::to_string(&outcome.tied)?)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("记录投票结果失败: {}", e)))?;
+        
+        debug!("记录第{}天投票结果: {:?}", outcome.day, outcome.eliminated);
+        Ok(())
+    }
+    
+    /// 保存赛后事实表，重复保存时覆盖
+    pub async fn save_fact_sheet(&self, sheet: &FactSheet) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO fact_sheets (game_id, version, data, created_at)
+            VALUES (?, ?, ?, ?)
+            "#
+        )
+        .bind(&sheet.game_id)
+        .bind(sheet.version as i32)
+        .bind(serde_json::to_string(sheet)?)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("保存事实表失败: {}", e)))?;
+        
+        debug!("保存游戏{}的事实表", sheet.game_id);
+        Ok(())
+    }
+    
+    /// 获取赛后事实表，对局未结束或早于此功能的对局为None
+    pub async fn get_fact_sheet(&self, game_id: &str) -> AppResult<Option<FactSheet>> {
+        let record = sqlx::query_as::<_, FactSheetRecord>("SELECT * FROM fact_sheets WHERE game_id = ?")
+            .bind(game_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("获取事实表失败: {}", e)))?;
+        
+        match record {
+            Some(record) => Ok(Some(serde_json::from_str(&record.data)?)),
+            None => Ok(None),
+        }
+    }
+    
     /// 记录夜晚行动
     pub async fn record_night_action(&self, game_id: &str, action: &NightAction, night: u32, result: Option<&str>) -> AppResult<()> {
         let action_id = Uuid::new_v4().to_string();
@@ -170,6 +283,250 @@ impl GameRepository {
         Ok(())
     }
     
+    /// 记录频道聊天消息
+    pub async fn record_chat_message(&self, game_id: &str, message: &ChannelMessage) -> AppResult<()> {
+        let phase_str = self.phase_to_string(&message.phase);
+        
+        sqlx::query(
+            r#"
+            INSERT INTO chat_messages (id, game_id, channel, sender_id, content, day, phase, timestamp)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&message.id)
+        .bind(game_id)
+        .bind(message.channel.as_str())
+        .bind(&message.sender)
+        .bind(&message.content)
+        .bind(message.day as i32)
+        .bind(&phase_str)
+        .bind(message.timestamp)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("记录聊天消息失败: {}", e)))?;
+        
+        debug!("记录聊天消息: [{}] {} - {}", message.channel.as_str(), message.sender, message.content);
+        Ok(())
+    }
+    
+    /// 记录私信
+    pub async fn record_private_message(&self, game_id: &str, message: &PrivateMessage) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO private_messages (id, game_id, player_id, day, content, timestamp)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&message.id)
+        .bind(game_id)
+        .bind(&message.player_id)
+        .bind(message.day as i32)
+        .bind(serde_json::to_string(&message.content)?)
+        .bind(message.timestamp)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("记录私信失败: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// 获取某位玩家收到的私信
+    pub async fn get_private_messages(&self, game_id: &str, player_id: &str) -> AppResult<Vec<PrivateMessageRecord>> {
+        let messages = sqlx::query_as::<_, PrivateMessageRecord>(
+            "SELECT * FROM private_messages WHERE game_id = ? AND player_id = ? ORDER BY timestamp"
+        )
+        .bind(game_id)
+        .bind(player_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("获取私信失败: {}", e)))?;
+        
+        Ok(messages)
+    }
+    
+    /// 记录AI决策（审计日志）
+    pub async fn record_ai_decision(&self, game_id: &str, decision: &AIDecision) -> AppResult<()> {
+        let context_json = serde_json::to_string(&decision.context)
+            .map_err(|e| AppError::Serialization(e.to_string()))?;
+        let alternatives_json = serde_json::to_string(&decision.alternatives)
+            .map_err(|e| AppError::Serialization(e.to_string()))?;
+        let decision_type = serde_json::to_string(&decision.decision_type)
+            .map_err(|e| AppError::Serialization(e.to_string()))?;
+        
+        sqlx::query(
+            r#"
+            INSERT INTO ai_decision_records (id, game_id, player_id, decision_type, day, phase, context, reasoning, confidence, alternatives, execution_time_ms, served_by, timestamp)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&decision.id)
+        .bind(game_id)
+        .bind(&decision.player_id)
+        .bind(decision_type.trim_matches('"'))
+        .bind(decision.context.round as i32)
+        .bind(self.phase_to_string(&decision.context.phase))
+        .bind(&context_json)
+        .bind(&decision.reasoning)
+        .bind(decision.confidence)
+        .bind(&alternatives_json)
+        .bind(decision.execution_time_ms as i64)
+        .bind(&decision.served_by)
+        .bind(decision.timestamp)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("记录AI决策失败: {}", e)))?;
+        
+        debug!("记录AI决策: {} - {:?}", decision.player_id, decision.decision_type);
+        Ok(())
+    }
+    
+    /// 获取AI决策审计记录，可按玩家过滤
+    pub async fn get_ai_decisions(&self, game_id: &str, player_id: Option<&str>) -> AppResult<Vec<AIDecisionRecord>> {
+        let decisions = match player_id {
+            Some(player_id) => {
+                sqlx::query_as::<_, AIDecisionRecord>(
+                    "SELECT * FROM ai_decision_records WHERE game_id = ? AND player_id = ? ORDER BY timestamp"
+                )
+                .bind(game_id)
+                .bind(player_id)
+                .fetch_all(&self.pool)
+                .await
+            }
+            None => {
+                sqlx::query_as::<_, AIDecisionRecord>(
+                    "SELECT * FROM ai_decision_records WHERE game_id = ? ORDER BY timestamp"
+                )
+                .bind(game_id)
+                .fetch_all(&self.pool)
+                .await
+            }
+        }
+        .map_err(|e| AppError::Database(format!("获取AI决策记录失败: {}", e)))?;
+        
+        Ok(decisions)
+    }
+    
+    /// 记录某一天结束时所有玩家的怀疑度
+    pub async fn record_suspicion_snapshot(&self, game_id: &str, day: u32, analyses: &[PlayerAnalysis]) -> AppResult<()> {
+        let timestamp = Utc::now();
+        
+        for analysis in analyses {
+            sqlx::query(
+                r#"
+                INSERT INTO suspicion_snapshots (id, game_id, day, player_id, werewolf_probability, suspicion_score, trust_score, timestamp)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(game_id)
+            .bind(day as i32)
+            .bind(&analysis.player_id)
+            .bind(analysis.werewolf_probability)
+            .bind(analysis.suspicion_score)
+            .bind(analysis.trust_score)
+            .bind(timestamp)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("记录怀疑度快照失败: {}", e)))?;
+        }
+        
+        debug!("记录第{}天怀疑度快照，共{}名玩家", day, analyses.len());
+        Ok(())
+    }
+    
+    /// 获取怀疑度时间序列
+    pub async fn get_suspicion_history(&self, game_id: &str) -> AppResult<Vec<SuspicionSnapshotRecord>> {
+        let snapshots = sqlx::query_as::<_, SuspicionSnapshotRecord>(
+            "SELECT * FROM suspicion_snapshots WHERE game_id = ? ORDER BY day, player_id"
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("获取怀疑度记录失败: {}", e)))?;
+        
+        Ok(snapshots)
+    }
+    
+    /// 转存内存中淘汰的复盘事件，连同其哈希链节点
+    pub async fn record_replay_event(&self, game_id: &str, index: usize, event: &ReplayEvent, chain_hash: &str) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO replay_events (game_id, event_index, event_type, event_data, chain_hash)
+            VALUES (?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(game_id)
+        .bind(index as i64)
+        .bind(format!("{:?}", event.event_type))
+        .bind(serde_json::to_string(event)?)
+        .bind(chain_hash)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("转存复盘事件失败: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// 获取转存的复盘事件（按事件顺序）
+    pub async fn get_replay_events(&self, game_id: &str) -> AppResult<Vec<ReplayEventRecord>> {
+        let events = sqlx::query_as::<_, ReplayEventRecord>(
+            "SELECT * FROM replay_events WHERE game_id = ? ORDER BY event_index"
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("获取复盘事件失败: {}", e)))?;
+        
+        Ok(events)
+    }
+    
+    /// 保存玩家笔记（同一玩家同一天覆盖旧笔记）
+    pub async fn save_note(&self, game_id: &str, note: &PlayerNote) -> AppResult<()> {
+        let suspected_roles_json = serde_json::to_string(&note.suspected_roles)
+            .map_err(|e| AppError::Serialization(e.to_string()))?;
+        let claims_heard_json = serde_json::to_string(&note.claims_heard)
+            .map_err(|e| AppError::Serialization(e.to_string()))?;
+        
+        sqlx::query(
+            r#"
+            INSERT INTO player_notes (id, game_id, player_id, day, content, suspected_roles, claims_heard, timestamp)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (game_id, player_id, day) DO UPDATE SET
+                content = excluded.content,
+                suspected_roles = excluded.suspected_roles,
+                claims_heard = excluded.claims_heard,
+                timestamp = excluded.timestamp
+            "#
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(game_id)
+        .bind(&note.player_id)
+        .bind(note.day as i32)
+        .bind(&note.content)
+        .bind(&suspected_roles_json)
+        .bind(&claims_heard_json)
+        .bind(note.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("保存笔记失败: {}", e)))?;
+        
+        debug!("保存笔记: {} 第{}天", note.player_id, note.day);
+        Ok(())
+    }
+    
+    /// 获取游戏中的全部笔记
+    pub async fn get_notes(&self, game_id: &str) -> AppResult<Vec<PlayerNoteRecord>> {
+        let notes = sqlx::query_as::<_, PlayerNoteRecord>(
+            "SELECT * FROM player_notes WHERE game_id = ? ORDER BY day"
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("获取笔记失败: {}", e)))?;
+        
+        Ok(notes)
+    }
+    
     /// 获取游戏详情
     pub async fn get_game_details(&self, game_id: &str) -> AppResult<GameDetails> {
         // 获取游戏基本信息
@@ -183,16 +540,25 @@ impl GameRepository {
         
         // 获取玩家记录
         let players = sqlx::query_as::<_, PlayerRecord>(
-            "SELECT * FROM player_records WHERE game_id = ? ORDER BY id"
+            "SELECT * FROM player_records WHERE game_id = ? ORDER BY seat"
         )
         .bind(game_id)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| AppError::Database(format!("获取玩家记录失败: {}", e)))?;
         
+        // 获取AI人设
+        let personas = sqlx::query_as::<_, PlayerPersonaRecord>(
+            "SELECT * FROM player_personas WHERE game_id = ? ORDER BY player_id"
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("获取AI人设失败: {}", e)))?;
+        
         // 获取发言记录
         let speeches = sqlx::query_as::<_, ModelsSpeechRecord>(
-            "SELECT * FROM speech_records WHERE game_id = ? ORDER BY timestamp"
+            "SELECT * FROM speech_records WHERE game_id = ? ORDER BY timestamp, sequence"
         )
         .bind(game_id)
         .fetch_all(&self.pool)
@@ -208,6 +574,15 @@ impl GameRepository {
         .await
         .map_err(|e| AppError::Database(format!("获取投票记录失败: {}", e)))?;
         
+        // 获取投票结果
+        let vote_outcomes = sqlx::query_as::<_, VoteOutcomeRecord>(
+            "SELECT * FROM vote_outcomes WHERE game_id = ? ORDER BY day, timestamp"
+        )
+        .bind(game_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("获取投票结果失败: {}", e)))?;
+        
         // 获取夜晚行动记录
         let night_actions = sqlx::query_as::<_, NightActionRecord>(
             "SELECT * FROM night_action_records WHERE game_id = ? ORDER BY timestamp"
@@ -226,13 +601,19 @@ impl GameRepository {
         .await
         .map_err(|e| AppError::Database(format!("获取AI分析记录失败: {}", e)))?;
         
+        // 获取玩家笔记
+        let notes = self.get_notes(game_id).await?;
+        
         Ok(GameDetails {
             game,
             players,
+            personas,
             speeches,
             votes,
+            vote_outcomes,
             night_actions,
             ai_analyses,
+            notes,
         })
     }
     
@@ -249,6 +630,99 @@ impl GameRepository {
         Ok(games)
     }
     
+    /// 往局范例发言：正常打完、没有中断的对局里，该身份获胜一方在该阶段带向量的发言，
+    /// 真人的发言优先，其次是较新的对局
+    pub async fn get_exemplar_speeches(&self, role: &RoleType, phase: &GamePhase, min_chars: usize, limit: u32) -> AppResult<Vec<ModelsSpeechRecord>> {
+        let speeches = sqlx::query_as::<_, ModelsSpeechRecord>(
+            r#"
+            SELECT s.* FROM speech_records s
+            JOIN player_records p ON p.player_id = s.player_id AND p.game_id = s.game_id
+            JOIN game_records g ON g.id = s.game_id
+            WHERE p.role_type = ? AND s.phase = ? AND p.is_winner = 1
+              AND g.end_time IS NOT NULL AND g.winner IS NOT NULL AND g.abandoned = 0
+              AND s.embedding IS NOT NULL AND length(s.content) >= ?
+            ORDER BY p.is_ai ASC, g.start_time DESC, s.sequence ASC
+            LIMIT ?
+            "#
+        )
+        .bind(self.role_type_to_string(role))
+        .bind(self.phase_to_string(phase))
+        .bind(min_chars as i64)
+        .bind(limit as i32)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("获取范例发言失败: {}", e)))?;
+        
+        Ok(speeches)
+    }
+    
+    /// 某个人数下正常打完的对局的身份配置和获胜阵营，供生成平衡板子时统计胜率
+    ///
+    /// 配置解析不了（早期版本的记录）或没有身份分布的对局跳过
+    pub async fn get_board_results(&self, player_count: u8) -> AppResult<Vec<(HashMap<RoleType, u8>, Faction)>> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT config, winner FROM game_records
+            WHERE player_count = ? AND end_time IS NOT NULL AND winner IS NOT NULL AND abandoned = 0
+            "#
+        )
+        .bind(player_count as i32)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("获取板子战绩失败: {}", e)))?;
+        
+        let results = rows.into_iter()
+            .filter_map(|(config, winner)| {
+                let config: serde_json::Value = serde_json::from_str(&config).ok()?;
+                let distribution: HashMap<RoleType, u8> = serde_json::from_value(config.get("role_distribution")?.clone()).ok()?;
+                let winner = match winner.as_str() {
+                    "werewolf" => Faction::Werewolf,
+                    "villager" => Faction::Villager,
+                    _ => return None,
+                };
+                (!distribution.is_empty()).then_some((distribution, winner))
+            })
+            .collect();
+        
+        Ok(results)
+    }
+    
+    /// 收尾上次运行中断的对局：没有结束时间的记录都无法再恢复，
+    /// 标记为中断并保留哈希链完好的复盘事件，返回被收尾的对局ID
+    pub async fn finalize_abandoned_games(&self) -> AppResult<Vec<String>> {
+        let game_ids = sqlx::query_scalar::<_, String>(
+            "SELECT id FROM game_records WHERE end_time IS NULL AND abandoned = 0"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(format!("查询中断对局失败: {}", e)))?;
+        
+        for game_id in &game_ids {
+            let events = self.get_replay_events(game_id).await?;
+            let salvaged = replay::salvageable_prefix(&events);
+            if salvaged < events.len() {
+                warn!("中断对局 {} 的复盘事件从#{}起无法校验，丢弃{}条", game_id, salvaged, events.len() - salvaged);
+                sqlx::query("DELETE FROM replay_events WHERE game_id = ? AND event_index >= ?")
+                    .bind(game_id)
+                    .bind(salvaged as i64)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(|e| AppError::Database(format!("清理复盘事件失败: {}", e)))?;
+            }
+            
+            sqlx::query("UPDATE game_records SET end_time = ?, abandoned = 1, salvaged_events = ? WHERE id = ?")
+                .bind(Utc::now())
+                .bind(salvaged as i32)
+                .bind(game_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| AppError::Database(format!("标记中断对局失败: {}", e)))?;
+            info!("中断对局 {} 已收尾，保留{}条复盘事件", game_id, salvaged);
+        }
+        
+        Ok(game_ids)
+    }
+    
     /// 删除游戏记录
     pub async fn delete_game(&self, game_id: &str) -> AppResult<()> {
         // 由于外键约束，删除游戏记录会自动删除相关的其他记录
@@ -268,24 +742,27 @@ impl GameRepository {
     
     // 私有辅助方法
     
-    async fn create_player_record(&self, game_id: &str, player: &Player) -> AppResult<()> {
+    /// 玩家ID（human_player、ai_N）每局都一样，记录ID另外生成，按(game_id, player_id)区分
+    async fn create_player_record(&self, conn: &mut SqliteConnection, game_id: &str, player: &Player) -> AppResult<()> {
         let role_type = self.role_type_to_string(&player.role.role_type);
         let faction = self.faction_to_string(&player.role.faction);
         
         sqlx::query(
             r#"
-            INSERT INTO player_records (id, game_id, player_name, role_type, faction, is_ai, is_winner)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO player_records (id, game_id, player_id, player_name, role_type, faction, is_ai, is_winner, seat)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         )
-        .bind(&player.id)
+        .bind(Uuid::new_v4().to_string())
         .bind(game_id)
+        .bind(&player.id)
         .bind(&player.name)
         .bind(&role_type)
         .bind(&faction)
         .bind(player.is_ai)
         .bind(false) // 初始时都不是获胜者
-        .execute(&self.pool)
+        .bind(player.seat as i32)
+        .execute(conn)
         .await
         .map_err(|e| AppError::Database(format!("创建玩家记录失败: {}", e)))?;
         
@@ -300,6 +777,8 @@ impl GameRepository {
             RoleType::Witch => "witch",
             RoleType::Hunter => "hunter",
             RoleType::Guard => "guard",
+            RoleType::DreamWolf => "dream_wolf",
+            RoleType::Gargoyle => "gargoyle",
         }.to_string()
     }
     
@@ -316,6 +795,7 @@ impl GameRepository {
             GamePhase::Night => "night",
             GamePhase::DayDiscussion => "day_discussion",
             GamePhase::Voting => "voting",
+            GamePhase::Pk => "pk",
             GamePhase::LastWords => "last_words",
             GamePhase::GameOver => "game_over",
         }.to_string()
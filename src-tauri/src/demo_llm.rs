@@ -0,0 +1,144 @@
+use crate::utils;
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// 带座位号的中文发言模板，{}处填入座位号
+const SEAT_LINES_ZH: [&str; 5] = [
+    "我先听了一圈，{}号的发言有点飘，这轮我先盯着他。",
+    "目前信息不多，我是好人，{}号能再解释一下你的站边吗？",
+    "我觉得{}号的逻辑没什么问题，暂时不踩，先看票型。",
+    "{}号前后说法不太一致，如果没有新信息我倾向于投他。",
+    "{}号发言挺像好人的，我这轮先不考虑他。",
+];
+const PLAIN_LINES_ZH: [&str; 3] = [
+    "信息太少了，我先过，听后面的人怎么说。",
+    "我是好人，这轮没有特别的怀疑对象，先跟着票型走。",
+    "大家别急着下结论，先把身份信息理清楚。",
+];
+const SEAT_LINES_EN: [&str; 4] = [
+    "Seat {} sounds a bit shaky to me, I'll keep an eye on them.",
+    "Not much info yet. Seat {}, can you explain your read?",
+    "Seat {}'s logic seems fine, I won't push on them this round.",
+    "Seat {} keeps changing their story, I'm leaning toward voting them.",
+];
+const PLAIN_LINES_EN: [&str; 2] = [
+    "Too little info so far, I'll pass and listen to the rest.",
+    "I'm village, no strong read yet, let's see how the votes go.",
+];
+const COACH_LINE: &str = "这一天的判断整体不错。下次投票前先对照一下身份声明和查验结果，避免跟票投错人。";
+const NARRATOR_LINE: &str = "公开记录里没有这条信息。";
+
+/// 演示/离线模型：不联网，按提示词类型返回预置或程序生成的回复
+///
+/// 同一提示词总是得到同一回复，方便演示、测试和复现
+pub struct DemoResponder;
+
+impl DemoResponder {
+    pub fn respond(prompt: &str) -> String {
+        let seed = Self::seed(prompt);
+
+        if let Some(action) = Self::requested_action(prompt) {
+            return Self::night_action(prompt, action, seed);
+        }
+        if let Some((_, draft)) = prompt.rsplit_once("原文：") {
+            // 润色请求原样返回，保证声明不变
+            return draft.trim().to_string();
+        }
+        if prompt.contains("【公开记录】") {
+            return NARRATOR_LINE.to_string();
+        }
+        if prompt.contains("狼人杀教练") {
+            return COACH_LINE.to_string();
+        }
+        Self::speech(prompt, seed)
+    }
+
+    /// 夜晚行动提示词要求的行动，取自其中的JSON示例
+    fn requested_action(prompt: &str) -> Option<&'static str> {
+        ["kill", "check", "protect", "heal/poison"].into_iter()
+            .find(|action| prompt.contains(&format!("\"action\":\"{}\"", action)))
+    }
+
+    fn night_action(prompt: &str, action: &str, seed: u64) -> String {
+        if action == "heal/poison" {
+            // 女巫救当晚第一个刀口，没有刀口时不行动
+            let target = Self::section(prompt, "今晚被刀的是：")
+                .and_then(|targets| targets.split(", ").next())
+                .filter(|target| !target.is_empty() && *target != "无人");
+            return match target {
+                Some(target) => json!({ "action": "heal", "target": target }).to_string(),
+                None => json!({ "action": "none" }).to_string(),
+            };
+        }
+
+        let candidates = Self::alive_candidates(prompt);
+        match candidates.get(seed as usize % candidates.len().max(1)) {
+            Some(target) => json!({ "action": action, "target": target }).to_string(),
+            None => json!({ "action": "none" }).to_string(),
+        }
+    }
+
+    /// 存活列表“3号张三(ai_3), ...”中除自己以外的玩家ID
+    fn alive_candidates(prompt: &str) -> Vec<String> {
+        let me = Self::section(prompt, "你是").unwrap_or_default();
+        let list = match Self::section(prompt, "存活的玩家有：") {
+            Some(list) => list,
+            None => return Vec::new(),
+        };
+        list.split(", ")
+            .filter_map(|entry| {
+                let (display, rest) = entry.split_once('(')?;
+                let id = rest.strip_suffix(')')?;
+                (!me.contains(display)).then(|| id.to_string())
+            })
+            .collect()
+    }
+
+    /// 标记之后到下一个句号或逗号之前的内容
+    fn section<'a>(prompt: &'a str, marker: &str) -> Option<&'a str> {
+        let (_, rest) = prompt.split_once(marker)?;
+        let end = rest.find(['。', '，']).unwrap_or(rest.len());
+        Some(&rest[..end])
+    }
+
+    /// 发言：从提示词提到的座位里选一个来点评，英文要求时用英文模板
+    fn speech(prompt: &str, seed: u64) -> String {
+        let english = prompt.contains("Speak in English");
+        let seats = utils::parse_seat_mentions(prompt);
+        let pick = |lines: &[&str]| lines[seed as usize % lines.len()].to_string();
+
+        match seats.get(seed as usize % seats.len().max(1)) {
+            Some(seat) if english => pick(&SEAT_LINES_EN[..]).replace("{}", &seat.to_string()),
+            Some(seat) => pick(&SEAT_LINES_ZH[..]).replace("{}", &seat.to_string()),
+            None if english => pick(&PLAIN_LINES_EN[..]),
+            None => pick(&PLAIN_LINES_ZH[..]),
+        }
+    }
+
+    fn seed(prompt: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        prompt.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_night_action_targets_someone_else() {
+        let prompt = "你是预言家2号小明，现在是第1夜。存活的玩家有：1号小红(ai_1), 2号小明(human), 3号小刚(ai_3)。请选择一个目标查验，不要重复查验已知身份的玩家。返回JSON格式：{\"action\":\"check\",\"target\":\"player_id\"}";
+        let response: serde_json::Value = serde_json::from_str(&DemoResponder::respond(prompt)).unwrap();
+        assert_eq!(response["action"], "check");
+        assert!(["ai_1", "ai_3"].contains(&response["target"].as_str().unwrap()));
+        assert_eq!(DemoResponder::respond(prompt), DemoResponder::respond(prompt));
+    }
+
+    #[test]
+    fn test_polish_keeps_draft() {
+        let prompt = "把下面的狼人杀发言改得更简洁有力。只输出修改后的发言。\n\n原文：我是预言家，3号查杀";
+        assert_eq!(DemoResponder::respond(prompt), "我是预言家，3号查杀");
+    }
+}
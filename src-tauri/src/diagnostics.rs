@@ -0,0 +1,253 @@
+use crate::commands::AppState;
+use crate::config::AppConfig;
+use crate::database::migrations;
+use crate::types::LLMProvider;
+use crate::utils;
+use crate::voice::{ASREngine, AudioManager, TTSEngine, VoiceConfig};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 可用磁盘空间低于此值时判为失败
+const DISK_FAIL_BYTES: u64 = 100 * 1024 * 1024;
+/// 可用磁盘空间低于此值时给出警告
+const DISK_WARN_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// 单项检查结果
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// 一项自检
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    /// 检查项标识，如"database"
+    pub id: String,
+    pub label: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl DiagnosticCheck {
+    fn new(id: &str, label: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            label: label.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// 自检报告，设置页按项展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub checks: Vec<DiagnosticCheck>,
+    /// 所有检查项中最严重的结果
+    pub overall: CheckStatus,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// 运行全部自检：数据库、配置、模型连接、语音、音频设备和磁盘空间
+pub async fn run(state: &AppState) -> DiagnosticsReport {
+    let (config, data_dir) = {
+        let config_manager = state.config_manager.read().await;
+        (config_manager.get_config().clone(), config_manager.config_dir().map(Path::to_path_buf))
+    };
+
+    let checks = vec![
+        check_database(state).await,
+        check_config(&config),
+        check_llm(state).await,
+        check_tts(&config),
+        check_asr(&config),
+        check_audio_devices().await,
+        check_disk_space(data_dir.as_deref()),
+    ];
+    let overall = checks.iter().map(|c| c.status).max().unwrap_or(CheckStatus::Pass);
+
+    DiagnosticsReport {
+        checks,
+        overall,
+        generated_at: Utc::now(),
+    }
+}
+
+async fn check_database(state: &AppState) -> DiagnosticCheck {
+    const ID: &str = "database";
+    const LABEL: &str = "数据库";
+
+    let database = match &state.database {
+        Some(database) => database,
+        None => return DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, "数据库无法打开，游戏记录不会保存"),
+    };
+    match migrations::get_current_version(database.get_pool()).await {
+        Ok(version) if version == migrations::CURRENT_VERSION => {
+            DiagnosticCheck::new(ID, LABEL, CheckStatus::Pass, format!("结构版本{}", version))
+        }
+        Ok(version) if version < migrations::CURRENT_VERSION => DiagnosticCheck::new(
+            ID, LABEL, CheckStatus::Warn,
+            format!("结构版本{}低于程序要求的{}，部分记录可能无法保存", version, migrations::CURRENT_VERSION),
+        ),
+        Ok(version) => DiagnosticCheck::new(
+            ID, LABEL, CheckStatus::Fail,
+            format!("结构版本{}高于程序支持的{}，请升级程序", version, migrations::CURRENT_VERSION),
+        ),
+        Err(e) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, e.to_string()),
+    }
+}
+
+fn check_config(config: &AppConfig) -> DiagnosticCheck {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let llm = &config.llm;
+
+    if llm.provider != LLMProvider::Demo {
+        if !llm.base_url.starts_with("http://") && !llm.base_url.starts_with("https://") {
+            errors.push(format!("接口地址无效: {}", llm.base_url));
+        }
+        if llm.api_key.trim().is_empty() && llm.provider != LLMProvider::LocalOllama {
+            warnings.push("未填写API密钥".to_string());
+        }
+    }
+    if llm.model.trim().is_empty() {
+        errors.push("未填写模型名称".to_string());
+    }
+    if !(0.0..=2.0).contains(&llm.temperature) {
+        warnings.push(format!("温度{}超出常用范围0~2", llm.temperature));
+    }
+    if llm.max_tokens == 0 {
+        errors.push("最大输出长度为0".to_string());
+    }
+    let players = config.game.total_players;
+    if !(utils::MIN_PLAYERS..=utils::MAX_PLAYERS).contains(&players) {
+        errors.push(format!("玩家人数{}不在{}~{}之间", players, utils::MIN_PLAYERS, utils::MAX_PLAYERS));
+    }
+    if config.voice.volume > 100 {
+        warnings.push(format!("音量{}超过100", config.voice.volume));
+    }
+
+    let (status, detail) = if !errors.is_empty() {
+        (CheckStatus::Fail, errors.into_iter().chain(warnings).collect::<Vec<_>>().join("；"))
+    } else if !warnings.is_empty() {
+        (CheckStatus::Warn, warnings.join("；"))
+    } else {
+        (CheckStatus::Pass, "配置有效".to_string())
+    };
+    DiagnosticCheck::new("config", "配置", status, detail)
+}
+
+async fn check_llm(state: &AppState) -> DiagnosticCheck {
+    const ID: &str = "llm";
+    const LABEL: &str = "模型连接";
+
+    let llm_manager = state.llm_manager.read().await;
+    let llm_manager = match llm_manager.as_ref() {
+        Some(llm_manager) => llm_manager,
+        None => return DiagnosticCheck::new(ID, LABEL, CheckStatus::Warn, "尚未配置模型，AI将使用规则逻辑"),
+    };
+    match llm_manager.test_all_connections().await {
+        Ok(results) if results.first() == Some(&true) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Pass, "主接口可用"),
+        Ok(results) if results.iter().any(|ok| *ok) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Warn, "主接口不可用，正在使用备用接口"),
+        Ok(_) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, "所有接口都无法连接"),
+        Err(e) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, e.to_string()),
+    }
+}
+
+fn check_tts(config: &AppConfig) -> DiagnosticCheck {
+    const ID: &str = "tts";
+    const LABEL: &str = "语音合成";
+
+    if !config.voice.enable_tts {
+        return DiagnosticCheck::new(ID, LABEL, CheckStatus::Pass, "未启用");
+    }
+    match TTSEngine::new(&VoiceConfig::default()) {
+        Ok(engine) if engine.is_available() => {
+            DiagnosticCheck::new(ID, LABEL, CheckStatus::Pass, format!("使用{}", engine.backend().name()))
+        }
+        Ok(_) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, "未找到可用的合成程序，请安装edge-tts或系统语音（say/espeak-ng）"),
+        Err(e) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, e.to_string()),
+    }
+}
+
+fn check_asr(config: &AppConfig) -> DiagnosticCheck {
+    const ID: &str = "asr";
+    const LABEL: &str = "语音识别";
+
+    if !config.voice.enable_asr {
+        return DiagnosticCheck::new(ID, LABEL, CheckStatus::Pass, "未启用");
+    }
+    match ASREngine::new(&VoiceConfig::default()) {
+        Ok(engine) if engine.is_available() => {
+            DiagnosticCheck::new(ID, LABEL, CheckStatus::Pass, format!("使用{}", engine.backend().name()))
+        }
+        Ok(_) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, "没有可用的识别模型或在线服务"),
+        Err(e) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, e.to_string()),
+    }
+}
+
+async fn check_audio_devices() -> DiagnosticCheck {
+    const ID: &str = "audio_devices";
+    const LABEL: &str = "音频设备";
+
+    let audio = AudioManager::new();
+    let devices = match audio.scan_devices().await {
+        Ok(()) => audio.get_devices().await.unwrap_or_default(),
+        Err(e) => return DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, e.to_string()),
+    };
+    let inputs = devices.iter().filter(|d| d.is_input).count();
+    let outputs = devices.len() - inputs;
+    match (inputs, outputs) {
+        (_, 0) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Fail, "没有可用的输出设备"),
+        (0, _) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Warn, "没有麦克风，无法语音发言"),
+        _ => DiagnosticCheck::new(ID, LABEL, CheckStatus::Pass, format!("{}个输入设备，{}个输出设备", inputs, outputs)),
+    }
+}
+
+fn check_disk_space(data_dir: Option<&Path>) -> DiagnosticCheck {
+    const ID: &str = "disk_space";
+    const LABEL: &str = "磁盘空间";
+
+    let data_dir = match data_dir {
+        Some(dir) => dir,
+        None => return DiagnosticCheck::new(ID, LABEL, CheckStatus::Warn, "无法确定数据目录"),
+    };
+    match fs2::available_space(data_dir) {
+        Ok(bytes) => {
+            let detail = format!("数据目录剩余{:.1}GB", bytes as f64 / 1024f64.powi(3));
+            let status = if bytes < DISK_FAIL_BYTES {
+                CheckStatus::Fail
+            } else if bytes < DISK_WARN_BYTES {
+                CheckStatus::Warn
+            } else {
+                CheckStatus::Pass
+            };
+            DiagnosticCheck::new(ID, LABEL, status, detail)
+        }
+        Err(e) => DiagnosticCheck::new(ID, LABEL, CheckStatus::Warn, format!("无法读取剩余空间: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_check_reports_invalid_values() {
+        let mut config = AppConfig::default();
+        config.llm.api_key = "sk-test".to_string();
+        assert_eq!(check_config(&config).status, CheckStatus::Pass);
+
+        config.llm.temperature = 3.0;
+        assert_eq!(check_config(&config).status, CheckStatus::Warn);
+
+        config.game.total_players = 2;
+        let check = check_config(&config);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.contains("玩家人数") && check.detail.contains("温度"));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::types::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// 讨论轮的推进结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum DiscussionStatus {
+    /// 轮到真人发言，真人发言后自动继续
+    AwaitingHuman { player_id: String },
+    /// 所有该发言的玩家都已发言
+    Finished,
+    /// 阶段已经结束，讨论被打断
+    Interrupted,
+    /// 已经有一个讨论轮在推进中
+    InProgress,
+}
+
+/// 当前发言者，供前端显示发言顺序和倒计时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentSpeaker {
+    pub player_id: String,
+    /// 第几位发言（从1开始）
+    pub index: usize,
+    pub total: usize,
+    pub awaiting_human: bool,
+    /// 真人发言剩余秒数，AI发言时为None
+    pub seconds_remaining: Option<u32>,
+}
+
+/// 讨论轮的下一步
+pub enum DiscussionTurn {
+    /// 轮到该AI发言
+    Ai(String),
+    /// 停止推进
+    Stop(DiscussionStatus),
+}
+
+/// 一轮白天讨论或PK发言：按座位号顺时针依次发言
+#[derive(Debug, Clone)]
+pub struct DiscussionRound {
+    pub day: u32,
+    pub phase: GamePhase,
+    order: Vec<String>,
+    cursor: usize,
+    /// 是否有任务正在推进本轮
+    pub running: bool,
+    /// 正在等待发言的真人玩家
+    pub awaiting: Option<String>,
+    /// 轮到真人发言的时间，用于发言限时
+    pub awaiting_since: Option<Instant>,
+}
+
+impl DiscussionRound {
+    /// 以当前存活玩家按座位号排出发言顺序，PK阶段只有平票的玩家发言。
+    /// 警长在发言者之中时从警长的下一位开始、警长最后归票，否则随机选一位起始
+    pub fn new(state: &GameState) -> Self {
+        let mut alive: Vec<&Player> = state.players.iter()
+            .filter(|p| p.is_alive)
+            .filter(|p| state.phase != GamePhase::Pk || state.pk_candidates.contains(&p.id))
+            .collect();
+        alive.sort_by_key(|p| p.seat);
+        let mut order: Vec<String> = alive.into_iter().map(|p| p.id.clone()).collect();
+
+        if !order.is_empty() {
+            let start = match state.sheriff.as_ref().and_then(|sheriff| order.iter().position(|id| id == sheriff)) {
+                Some(index) => index + 1,
+                None => rand::thread_rng().gen_range(0..order.len()),
+            };
+            let len = order.len();
+            order.rotate_left(start % len);
+        }
+
+        Self {
+            day: state.day,
+            phase: state.phase.clone(),
+            order,
+            cursor: 0,
+            running: false,
+            awaiting: None,
+            awaiting_since: None,
+        }
+    }
+
+    /// 下一位仍然存活的发言者，跳过讨论中途出局的玩家
+    pub fn next_speaker<'a>(&mut self, state: &'a GameState) -> Option<&'a Player> {
+        while let Some(player_id) = self.order.get(self.cursor) {
+            match state.players.iter().find(|p| &p.id == player_id && p.is_alive) {
+                Some(player) => return Some(player),
+                None => self.cursor += 1,
+            }
+        }
+        None
+    }
+
+    /// 当前发言者说完，轮到下一位
+    pub fn advance(&mut self) {
+        self.cursor += 1;
+    }
+
+    /// 所有该发言的玩家都已发言
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.order.len()
+    }
+
+    /// 当前是第几位发言（从1开始）与总人数
+    pub fn position(&self) -> (usize, usize) {
+        (self.cursor + 1, self.order.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::game_engine::GameEngine;
+
+    fn engine_at_day() -> GameEngine {
+        let mut config = AppConfig::default().game;
+        config.night_timing = NightTimingConfig { min_duration: 0, max_padding: 0 };
+        let mut engine = GameEngine::new(config).unwrap();
+        engine.initialize_game().unwrap();
+        engine.start_game().unwrap();
+        engine.next_phase().unwrap();
+        engine
+    }
+
+    fn ids_by_seat(state: &GameState) -> Vec<String> {
+        let mut players: Vec<&Player> = state.players.iter().collect();
+        players.sort_by_key(|p| p.seat);
+        players.into_iter().map(|p| p.id.clone()).collect()
+    }
+
+    fn speaking_order(round: &mut DiscussionRound, state: &GameState) -> Vec<String> {
+        let mut order = Vec::new();
+        while let Some(player) = round.next_speaker(state) {
+            order.push(player.id.clone());
+            round.advance();
+        }
+        order
+    }
+
+    #[test]
+    fn test_order_runs_clockwise_from_after_the_sheriff() {
+        let mut engine = engine_at_day();
+        let ids = ids_by_seat(engine.get_state());
+        engine.set_sheriff(Some(ids[2].clone())).unwrap();
+
+        let state = engine.get_state();
+        let mut round = DiscussionRound::new(state);
+        let expected: Vec<String> = ids[3..].iter().chain(ids[..3].iter()).cloned().collect();
+        assert_eq!(round.position(), (1, ids.len()));
+        assert_eq!(speaking_order(&mut round, state), expected);
+        assert!(round.is_finished());
+    }
+
+    #[test]
+    fn test_order_without_sheriff_covers_every_seat_clockwise() {
+        let engine = engine_at_day();
+        let state = engine.get_state();
+        let ids = ids_by_seat(state);
+
+        let order = speaking_order(&mut DiscussionRound::new(state), state);
+        let start = ids.iter().position(|id| id == &order[0]).unwrap();
+        let mut expected = ids.clone();
+        expected.rotate_left(start);
+        assert_eq!(order, expected);
+    }
+
+    #[test]
+    fn test_pk_round_and_dead_speakers() {
+        let engine = engine_at_day();
+        let mut state = engine.get_state().clone();
+        let ids = ids_by_seat(&state);
+        state.phase = GamePhase::Pk;
+        state.pk_candidates = vec![ids[4].clone(), ids[1].clone()];
+        state.sheriff = Some(ids[1].clone());
+
+        // PK阶段只有平票的玩家发言，警长在其中时仍然最后发言
+        let mut round = DiscussionRound::new(&state);
+        assert_eq!(speaking_order(&mut round, &state), vec![ids[4].clone(), ids[1].clone()]);
+
+        // 讨论中途出局的玩家被跳过
+        let mut round = DiscussionRound::new(&state);
+        if let Some(player) = state.players.iter_mut().find(|p| p.id == ids[4]) {
+            player.is_alive = false;
+        }
+        assert_eq!(speaking_order(&mut round, &state), vec![ids[1].clone()]);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::ai::lexicon;
+use crate::error::AppResult;
+use crate::llm::LLMManager;
+use crate::memory::{ApproxSize, MemoryStats};
+use serde::{Deserialize, Serialize};
+use log::warn;
+
+/// 本地向量维度
+const LOCAL_DIMENSIONS: usize = 256;
+/// 本地向量的模型标识
+pub const LOCAL_MODEL: &str = "local-fnv-256";
+
+/// 向量来源
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EmbeddingProvider {
+    /// 本地哈希向量（分词 + 字二元组），离线可用
+    Local,
+    /// 调用模型服务的embeddings接口，失败时回退到本地
+    Api,
+}
+
+/// 发言向量配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingConfig {
+    pub provider: EmbeddingProvider,
+    pub api_model: String,
+    /// 同一玩家两次发言相似度超过该值视为重复
+    pub duplicate_threshold: f32,
+    /// 构建提示词时最多选取的相关发言条数
+    pub retrieval_limit: usize,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            provider: EmbeddingProvider::Local,
+            api_model: "text-embedding-3-small".to_string(),
+            duplicate_threshold: 0.95,
+            retrieval_limit: 4,
+        }
+    }
+}
+
+/// 带向量的发言
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedSpeech {
+    pub sequence: u64,
+    pub speaker: String,
+    pub day: u32,
+    pub content: String,
+    pub vector: Vec<f32>,
+}
+
+impl ApproxSize for EmbeddedSpeech {
+    fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.speaker.capacity()
+            + self.content.capacity()
+            + self.vector.capacity() * std::mem::size_of::<f32>()
+    }
+}
+
+/// 相似发言
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarStatement {
+    pub sequence: u64,
+    pub speaker: String,
+    pub day: u32,
+    pub content: String,
+    pub similarity: f32,
+}
+
+/// 计算发言向量，返回向量和模型标识
+pub async fn embed(config: &EmbeddingConfig, llm_manager: Option<&LLMManager>, text: &str) -> (Vec<f32>, String) {
+    if config.provider == EmbeddingProvider::Api {
+        if let Some(llm_manager) = llm_manager {
+            match llm_manager.embed_with_fallback(text, &config.api_model).await {
+                Ok(vector) => return (normalize(vector), config.api_model.clone()),
+                Err(e) => warn!("向量接口调用失败，改用本地向量: {}", e),
+            }
+        }
+    }
+    (local_embedding(text), LOCAL_MODEL.to_string())
+}
+
+/// 本地哈希向量：分词结果与字二元组散列到固定维度
+pub fn local_embedding(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0f32; LOCAL_DIMENSIONS];
+    let mut add = |feature: &str, weight: f32| {
+        let hash = fnv1a(feature.as_bytes());
+        // 用一位哈希决定符号，减少碰撞带来的偏差
+        let sign = if hash & 1 == 0 { 1.0 } else { -1.0 };
+        vector[(hash >> 1) as usize % LOCAL_DIMENSIONS] += sign * weight;
+    };
+
+    for token in lexicon::segment(text) {
+        add(&token, 1.0);
+    }
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    for pair in chars.windows(2) {
+        add(&pair.iter().collect::<String>(), 0.5);
+    }
+
+    normalize(vector)
+}
+
+/// FNV-1a哈希，结果不随Rust版本变化，存库的向量跨版本仍可比较
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME))
+}
+
+/// 余弦相似度（向量已归一化时即点积）
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > f32::EPSILON {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+/// 本局发言的向量库
+#[derive(Debug, Default)]
+pub struct EmbeddingStore {
+    entries: Vec<EmbeddedSpeech>,
+}
+
+impl EmbeddingStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn add(&mut self, entry: EmbeddedSpeech) {
+        self.entries.push(entry);
+    }
+
+    /// 同一玩家此前是否说过几乎相同的话，返回那条发言的序号
+    pub fn find_duplicate(&self, speaker: &str, vector: &[f32], threshold: f32) -> Option<u64> {
+        self.entries.iter()
+            .filter(|e| e.speaker == speaker)
+            .find(|e| cosine(&e.vector, vector) >= threshold)
+            .map(|e| e.sequence)
+    }
+
+    /// 某位玩家与查询最相似的历史发言
+    pub fn similar_by_player(&self, speaker: &str, query: &[f32], limit: usize) -> Vec<SimilarStatement> {
+        self.rank(query, limit, |e| e.speaker == speaker)
+    }
+
+    /// 本局全部发言（按发言顺序），提示词上下文从中挑选
+    pub fn entries(&self) -> &[EmbeddedSpeech] {
+        &self.entries
+    }
+
+    /// 最新一条发言
+    pub fn latest(&self) -> Option<&EmbeddedSpeech> {
+        self.entries.last()
+    }
+
+    /// 某位玩家最近一次发言
+    pub fn latest_by_player(&self, speaker: &str) -> Option<&EmbeddedSpeech> {
+        self.entries.iter().rev().find(|e| e.speaker == speaker)
+    }
+
+    /// 向量库的内存统计（随本局发言增长，新游戏时清空）
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            component: "embedding_store".to_string(),
+            entries: self.entries.len(),
+            capacity: None,
+            evicted: 0,
+            approx_bytes: self.entries.iter().map(ApproxSize::approx_bytes).sum(),
+        }
+    }
+
+    fn rank(&self, query: &[f32], limit: usize, filter: impl Fn(&EmbeddedSpeech) -> bool) -> Vec<SimilarStatement> {
+        let mut ranked: Vec<SimilarStatement> = self.entries.iter()
+            .filter(|e| filter(e))
+            .map(|e| SimilarStatement {
+                sequence: e.sequence,
+                speaker: e.speaker.clone(),
+                day: e.day,
+                content: e.content.clone(),
+                similarity: cosine(&e.vector, query),
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
+/// 向量的存储格式
+pub fn encode_vector(vector: &[f32]) -> AppResult<String> {
+    Ok(serde_json::to_string(vector)?)
+}
+
+/// 从存储格式还原向量
+pub fn decode_vector(encoded: &str) -> AppResult<Vec<f32>> {
+    Ok(serde_json::from_str(encoded)?)
+}
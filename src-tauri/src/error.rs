@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use crate::validator::ActionErrorCode;
 
 /// 应用程序错误类型
 #[derive(Error, Debug, Serialize, Deserialize)]
@@ -30,6 +31,18 @@ pub enum AppError {
     
     #[error("未找到资源: {0}")]
     NotFound(String),
+    
+    #[error("权限不足: {0}")]
+    PermissionDenied(String),
+    
+    #[error("已取消: {0}")]
+    Cancelled(String),
+    
+    #[error("插件错误: {0}")]
+    Plugin(String),
+    
+    #[error("非法操作[{}]: {1}", .0.as_str())]
+    InvalidAction(ActionErrorCode, String),
 }
 
 impl From<std::io::Error> for AppError {
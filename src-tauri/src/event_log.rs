@@ -0,0 +1,87 @@
+use crate::chat::ChannelMessage;
+use crate::claims::ClaimSource;
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 引擎事件：改变游戏状态的唯一途径
+///
+/// 随机结果（角色与座位分配、开局金水、夜晚的随机时长）在产生事件时就已确定，
+/// 按顺序折叠同一份事件日志总能得到同样的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload")]
+pub enum EngineEvent {
+    /// 创建游戏，日志的第一条
+    GameCreated { config: Box<GameConfig> },
+    /// 角色与座位分配完成
+    PlayersSeated { players: Vec<Player>, role_distribution: HashMap<RoleType, u8>, human_seat: u8 },
+    /// 游戏开始，附带开局告知预言家的金水和第一夜追加的随机秒数
+    GameStarted { gold_water: Vec<SeerCheckResult>, night_padding: u32 },
+    /// 结算当前阶段并进入下一阶段，进入夜晚时追加night_padding秒
+    PhaseAdvanced { night_padding: u32 },
+    VoteCast { voter: String, target: String },
+    VoteIntentSet { voter: String, target: String },
+    SpeechAdded { message: ChatMessage },
+    RoleClaimed { player_id: String, role: RoleType, source: ClaimSource },
+    CheckReported { reporter: String, target: String, is_werewolf: bool, source: ClaimSource },
+    NightActionTaken { action: NightAction },
+    SpeakerChanged { speaker: Option<String> },
+    SeatReplaced { player_id: String, personality: AIPersonality },
+    SeatRestored { player_id: String },
+    /// AI座位的性格在局内发生漂移
+    PersonalityDrifted { player_id: String, traits: PersonalityTraits },
+    ChannelMessagePosted { message: ChannelMessage },
+    /// 重新投票仍然平票，按规则随机抽中的放逐玩家，在随后的PhaseAdvanced中生效
+    TieBroken { player_id: String },
+    /// 主持人在对局中切换节奏档位
+    PacingChanged { profile: PacingProfile },
+    /// 警长当选或移交警徽，None表示撕毁警徽
+    SheriffChanged { player_id: Option<String> },
+    /// 玩家失去投票权，如翻牌后的白痴
+    VoteRevoked { player_id: String },
+    /// 撤销序号为sequence的事件，重建状态时跳过该事件
+    ActionUndone { sequence: u64 },
+}
+
+impl EngineEvent {
+    /// 阶段边界，撤销不能越过
+    pub fn is_phase_boundary(&self) -> bool {
+        matches!(self, EngineEvent::GameStarted { .. } | EngineEvent::PhaseAdvanced { .. })
+    }
+
+    /// 只有投票可以撤销。发言一送出就进了AI的记忆、朗读队列和数据库，收不回来；
+    /// 投票在计票前只是广播给界面，撤销时一并通知即可
+    pub fn undoable(&self) -> bool {
+        matches!(self, EngineEvent::VoteCast { .. })
+    }
+
+    /// 转换为返回给前端的撤销结果
+    pub fn to_undone(&self) -> Option<UndoneAction> {
+        match self {
+            EngineEvent::VoteCast { voter, target } => Some(UndoneAction::Vote {
+                voter: voter.clone(),
+                target: target.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// 事件日志中的一条记录，状态中的时间戳都取自这里而不是折叠时的当前时间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    pub event: EngineEvent,
+}
+
+/// 日志中已被撤销的事件序号
+pub fn undone_sequences(log: &[LoggedEvent]) -> HashSet<u64> {
+    log.iter()
+        .filter_map(|entry| match entry.event {
+            EngineEvent::ActionUndone { sequence } => Some(sequence),
+            _ => None,
+        })
+        .collect()
+}
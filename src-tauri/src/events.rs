@@ -0,0 +1,247 @@
+use crate::chat::ChannelMessage;
+use crate::coach::CoachReport;
+use crate::content_filter::{FilterCategory, FilterMode};
+use crate::night::DawnAnnouncement;
+use crate::narrator::BeginnerTip;
+use crate::objection::Objection;
+use crate::types::{DeathReveal, GameResult, GamePhase, PacingProfile, RoleType, SeerCheckResult, UndoneAction, VoteOutcome, WitchPotions};
+use crate::voice::WordTiming;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// 事件通道容量
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// 推送给前端的游戏事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload")]
+pub enum GameEvent {
+    /// AI开始思考，前端可显示“思考中…”
+    AIThinking { player_id: String },
+    /// AI思考结束
+    AIThinkingFinished { player_id: String },
+    /// 玩家投票意向变化
+    VoteIntentChanged { voter: String, target: String },
+    /// 玩家正式投票或改票，target为None表示弃票
+    VoteCast { voter: String, target: Option<String> },
+    /// 一条公开发言已进入发言管道
+    SpeechDelivered { sequence: u64, speaker: String, content: String },
+    /// AI发言朗读时的逐词时间轴（按1倍语速估算），前端朗读时做卡拉OK式高亮
+    SpeechTimeline { sequence: u64, speaker: String, words: Vec<WordTiming> },
+    /// AI发言生成中的文本增量，前端可逐字显示；投递时以SpeechDelivered的内容为准
+    SpeechStreaming { stream_id: String, speaker: String, delta: String },
+    /// AI对戏剧性时刻的即时反应（不占发言轮次）
+    AIReaction { player_id: String, content: String },
+    /// 有人打断当前发言者，前端暂停正在朗读的发言；reply为None时提示真人发言者回应
+    ObjectionRaised { objection: Objection },
+    /// 真人输入命中过滤规则，按模式已被拦截、遮挡或仅作提醒
+    ContentFiltered { player_id: String, categories: Vec<FilterCategory>, mode: FilterMode },
+    /// 教练模式给真人玩家的复盘反馈
+    CoachFeedback { report: CoachReport },
+    /// 进入新阶段
+    PhaseChanged { day: u32, phase: GamePhase },
+    /// 玩家出局（只含按规则公开的信息）
+    PlayerDied { reveal: DeathReveal },
+    /// 玩家正式或在发言中声明身份
+    RoleClaimed { player_id: String, role: RoleType, contested: bool },
+    /// 玩家公布查验结果
+    CheckReported { reporter: String, target: String, is_werewolf: bool },
+    /// 主持人给新手的规则提示
+    BeginnerTip { tip: BeginnerTip },
+    /// 投票即将截止，尚未投票的真人玩家会被自动处理
+    VoteDeadlineWarning { player_ids: Vec<String>, seconds_left: u32 },
+    /// 真人玩家超时后的自动投票，target为None表示弃票
+    AutoVoteApplied { player_id: String, target: Option<String> },
+    /// 放逐投票结算，附带完整的计票明细
+    VoteResolved { outcome: VoteOutcome },
+    /// 警长当选或移交警徽，None表示警徽被撕毁
+    SheriffChanged { player_id: Option<String> },
+    /// 玩家失去投票权
+    VoteRevoked { player_id: String },
+    /// 游戏结束，附带结算结果
+    GameEnded { result: GameResult },
+    /// 讨论轮轮到某位玩家发言，index从1开始；轮到真人时附带发言限时（秒）
+    DiscussionTurn { day: u32, player_id: String, index: usize, total: usize, awaiting_human: bool, time_limit: Option<u32> },
+    /// 本轮讨论所有玩家都已发言
+    DiscussionFinished { day: u32 },
+    /// 轮到真人玩家夜晚行动，私密信息通过私信频道单独发送
+    NightActionPrompt { player_id: String, role: RoleType },
+    /// 天亮公告
+    DawnAnnounced { announcement: DawnAnnouncement },
+    /// 娱乐局中主持人撤销了一次投票或发言
+    ActionUndone { action: UndoneAction },
+    /// 真人座位进入或退出挂机托管，托管中前端应突出显示“收回控制”
+    AutopilotChanged { player_id: String, active: bool },
+    /// 主持人切换了节奏档位，附带调整后的本阶段剩余秒数
+    PacingChanged { profile: PacingProfile, time_remaining: Option<u32> },
+    /// 阶段计时每秒的剩余时间，前端据此刷新倒计时而不必轮询状态
+    TimerTick { phase: GamePhase, time_remaining: u32 },
+}
+
+impl GameEvent {
+    /// 是否带来了新的公开信息（用于让推测性草稿失效）
+    pub fn is_information(&self) -> bool {
+        matches!(
+            self,
+            GameEvent::VoteIntentChanged { .. }
+                | GameEvent::VoteCast { .. }
+                | GameEvent::SpeechDelivered { .. }
+                | GameEvent::AIReaction { .. }
+                | GameEvent::ObjectionRaised { .. }
+                | GameEvent::PhaseChanged { .. }
+                | GameEvent::PlayerDied { .. }
+                | GameEvent::RoleClaimed { .. }
+                | GameEvent::CheckReported { .. }
+                | GameEvent::AutoVoteApplied { .. }
+                | GameEvent::VoteResolved { .. }
+                | GameEvent::SheriffChanged { .. }
+                | GameEvent::VoteRevoked { .. }
+                | GameEvent::GameEnded { .. }
+                | GameEvent::DawnAnnounced { .. }
+                | GameEvent::ActionUndone { .. }
+        )
+    }
+
+    /// 前端监听的事件名
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            GameEvent::AIThinking { .. } => "game://ai_thinking",
+            GameEvent::AIThinkingFinished { .. } => "game://ai_thinking_finished",
+            GameEvent::VoteIntentChanged { .. } => "game://vote_intent",
+            GameEvent::VoteCast { .. } => "game://vote",
+            GameEvent::SpeechDelivered { .. } => "game://speech",
+            GameEvent::SpeechTimeline { .. } => "game://speech_timeline",
+            GameEvent::SpeechStreaming { .. } => "game://speech_stream",
+            GameEvent::AIReaction { .. } => "game://ai_reaction",
+            GameEvent::ObjectionRaised { .. } => "game://objection",
+            GameEvent::ContentFiltered { .. } => "game://content_filtered",
+            GameEvent::CoachFeedback { .. } => "game://coach_feedback",
+            GameEvent::PhaseChanged { .. } => "game://phase_changed",
+            GameEvent::PlayerDied { .. } => "game://player_died",
+            GameEvent::RoleClaimed { .. } => "game://role_claimed",
+            GameEvent::CheckReported { .. } => "game://check_reported",
+            GameEvent::BeginnerTip { .. } => "game://beginner_tip",
+            GameEvent::VoteDeadlineWarning { .. } => "game://vote_deadline_warning",
+            GameEvent::AutoVoteApplied { .. } => "game://auto_vote",
+            GameEvent::VoteResolved { .. } => "game://vote_resolved",
+            GameEvent::SheriffChanged { .. } => "game://sheriff_changed",
+            GameEvent::VoteRevoked { .. } => "game://vote_revoked",
+            GameEvent::GameEnded { .. } => "game://game_ended",
+            GameEvent::DiscussionTurn { .. } => "game://discussion_turn",
+            GameEvent::DiscussionFinished { .. } => "game://discussion_finished",
+            GameEvent::NightActionPrompt { .. } => "game://night_action_prompt",
+            GameEvent::DawnAnnounced { .. } => "game://dawn",
+            GameEvent::ActionUndone { .. } => "game://action_undone",
+            GameEvent::AutopilotChanged { .. } => "game://autopilot",
+            GameEvent::PacingChanged { .. } => "game://pacing_changed",
+            GameEvent::TimerTick { .. } => "game://timer_tick",
+        }
+    }
+}
+
+/// 只发给某位玩家的私密信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivateMessage {
+    pub id: String,
+    pub player_id: String,
+    pub day: u32,
+    pub content: PrivateContent,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 私密信息的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload")]
+pub enum PrivateContent {
+    /// 预言家的查验结果，reference为开局告知的金水
+    SeerCheck { target: String, is_werewolf: bool, reference: bool },
+    /// 女巫得知的当晚刀口
+    NightKillTargets { targets: Vec<String> },
+    /// 主持人对玩家语音提问的回答，只含公开信息
+    NarratorAnswer { question: String, answer: String },
+    /// 狼队友在狼人频道的发言，target为其提议的刀口
+    WolfChat { sender: String, content: String, target: Option<String> },
+    /// 亡者频道的新消息，只发给已出局的真人玩家
+    SpectatorMessage { message: ChannelMessage },
+}
+
+/// 玩家自己掌握的私密信息汇总，真人玩家随时可以查看
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivateInfo {
+    pub player_id: String,
+    pub role: RoleType,
+    /// 认识的狼队友
+    pub teammates: Vec<String>,
+    /// 预言家的全部查验结果，含开局告知的金水
+    pub checks: Vec<SeerCheckResult>,
+    /// 女巫剩余的药
+    pub potions: Option<WitchPotions>,
+    /// 女巫能看到的当晚刀口
+    pub pending_kills: Vec<String>,
+}
+
+impl PrivateMessage {
+    /// 按玩家区分的前端事件名，只有该玩家的界面会监听
+    pub fn event_name(&self) -> String {
+        format!("game://private/{}", self.player_id)
+    }
+}
+
+/// 游戏事件总线
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<GameEvent>,
+    /// 私信频道，与公开事件分开，避免秘密混进通用事件流
+    private: broadcast::Sender<PrivateMessage>,
+    /// 已发布的信息类事件数量
+    sequence: Arc<AtomicU64>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (private, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            sender,
+            private,
+            sequence: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 发布事件，没有订阅者时直接丢弃
+    pub fn publish(&self, event: GameEvent) {
+        if event.is_information() {
+            self.sequence.fetch_add(1, Ordering::SeqCst);
+        }
+        let _ = self.sender.send(event);
+    }
+
+    /// 当前信息序号，每有一条新的公开信息加一
+    pub fn sequence(&self) -> u64 {
+        self.sequence.load(Ordering::SeqCst)
+    }
+
+    /// 订阅事件
+    pub fn subscribe(&self) -> broadcast::Receiver<GameEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 发送私信，没有订阅者时直接丢弃（私信已落库，可以重新拉取）
+    pub fn publish_private(&self, message: PrivateMessage) {
+        let _ = self.private.send(message);
+    }
+
+    /// 订阅私信
+    pub fn subscribe_private(&self) -> broadcast::Receiver<PrivateMessage> {
+        self.private.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
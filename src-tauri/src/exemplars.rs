@@ -0,0 +1,136 @@
+use crate::embeddings;
+use crate::memory::{ApproxSize, MemoryStats};
+use crate::types::{GamePhase, RoleType};
+use serde::{Deserialize, Serialize};
+
+/// 会挑选范例的发言阶段
+pub const EXEMPLAR_PHASES: [GamePhase; 3] = [GamePhase::DayDiscussion, GamePhase::Pk, GamePhase::LastWords];
+
+/// 往局范例发言配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExemplarConfig {
+    /// 是否在AI发言提示词里加入往局范例
+    pub enabled: bool,
+    /// 每次发言最多加入的范例条数
+    pub max_examples: usize,
+    /// 开局时每个身份、每个阶段从历史库载入的候选条数
+    pub pool_size: u32,
+    /// 太短的发言没有参考价值
+    pub min_chars: usize,
+}
+
+impl Default for ExemplarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_examples: 2,
+            pool_size: 30,
+            min_chars: 20,
+        }
+    }
+}
+
+/// 一条往局范例发言
+#[derive(Debug, Clone)]
+pub struct Exemplar {
+    pub role: RoleType,
+    pub phase: GamePhase,
+    pub day: u32,
+    pub content: String,
+    pub vector: Vec<f32>,
+}
+
+impl ApproxSize for Exemplar {
+    fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.content.capacity()
+            + self.vector.capacity() * std::mem::size_of::<f32>()
+    }
+}
+
+/// 本局用到的范例库，开局时按场上身份从历史库载入
+#[derive(Debug, Default)]
+pub struct ExemplarLibrary {
+    entries: Vec<Exemplar>,
+}
+
+impl ExemplarLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn add(&mut self, exemplar: Exemplar) {
+        self.entries.push(exemplar);
+    }
+
+    /// 同身份、同阶段的范例，按与当前局面（最新发言）的相似度挑选；
+    /// 没有可比的发言时保持载入时的质量顺序
+    pub fn select(&self, role: &RoleType, phase: &GamePhase, query: Option<&[f32]>, limit: usize) -> Vec<&Exemplar> {
+        let mut candidates: Vec<(&Exemplar, f32)> = self.entries.iter()
+            .filter(|e| &e.role == role && &e.phase == phase)
+            .map(|e| (e, query.map(|query| embeddings::cosine(&e.vector, query)).unwrap_or(0.0)))
+            .collect();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        candidates.into_iter().take(limit).map(|(e, _)| e).collect()
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            component: "exemplar_library".to_string(),
+            entries: self.entries.len(),
+            capacity: None,
+            evicted: 0,
+            approx_bytes: self.entries.iter().map(ApproxSize::approx_bytes).sum(),
+        }
+    }
+}
+
+/// 范例在提示词里的写法：只借鉴思路和说法，不能把往局的信息当成本局事实
+pub fn format_exemplars(exemplars: &[&Exemplar]) -> String {
+    if exemplars.is_empty() {
+        return String::new();
+    }
+    let lines: Vec<String> = exemplars.iter()
+        .enumerate()
+        .map(|(index, e)| format!("范例{}（第{}天）：{}", index + 1, e.day, e.content))
+        .collect();
+    format!(
+        "以下是往局同身份获胜玩家在类似局面下的发言，只参考思路和说话方式，其中的座位号和信息与本局无关，不要照搬：\n{}\n",
+        lines.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exemplar(role: RoleType, content: &str) -> Exemplar {
+        Exemplar {
+            role,
+            phase: GamePhase::DayDiscussion,
+            day: 1,
+            content: content.to_string(),
+            vector: embeddings::local_embedding(content),
+        }
+    }
+
+    #[test]
+    fn test_select_prefers_relevant_exemplar_of_same_role() {
+        let mut library = ExemplarLibrary::new();
+        library.add(exemplar(RoleType::Seer, "我是预言家，昨晚查验3号是查杀，今天大家跟我出3号"));
+        library.add(exemplar(RoleType::Seer, "我先听一听后置位的发言，目前没有特别的想法"));
+        library.add(exemplar(RoleType::Werewolf, "3号是查杀？我不信，这个预言家是假的"));
+
+        let query = embeddings::local_embedding("5号跳预言家说3号是查杀");
+        let selected = library.select(&RoleType::Seer, &GamePhase::DayDiscussion, Some(&query), 1);
+        assert_eq!(selected.len(), 1);
+        assert!(selected[0].content.contains("查杀"));
+        assert_eq!(selected[0].role, RoleType::Seer);
+
+        assert!(library.select(&RoleType::Witch, &GamePhase::DayDiscussion, Some(&query), 2).is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::game_engine::GameEngine;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 事实表格式版本，字段有不兼容变化时递增
+pub const FACT_SHEET_VERSION: u32 = 1;
+
+/// 赛后事实表：紧凑的机器可读摘要，供教练、成就和外部分析使用。
+/// 只在对局结束后生成，包含全部真实身份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactSheet {
+    pub version: u32,
+    pub game_id: String,
+    pub winner: Option<Faction>,
+    pub days: u32,
+    pub players: Vec<PlayerFact>,
+    pub checks: Vec<CheckFact>,
+    /// 按出局顺序排列
+    pub deaths: Vec<DeathFact>,
+    pub votes: Vec<VoteRoundFact>,
+    pub claims: Vec<ClaimFact>,
+    pub check_reports: Vec<CheckReportFact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerFact {
+    pub id: String,
+    pub seat: u8,
+    pub role: RoleType,
+    pub faction: Faction,
+    pub is_ai: bool,
+    pub survived: bool,
+}
+
+/// 预言家的真实查验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckFact {
+    pub night: u32,
+    pub seer: String,
+    pub target: String,
+    pub is_werewolf: bool,
+    /// 开局告知的金水
+    pub reference: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathCause {
+    /// 放逐投票出局
+    Vote,
+    /// 夜晚死亡，房规脚本连带的出局也算在这里
+    Night,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeathFact {
+    pub player_id: String,
+    pub day: u32,
+    pub cause: DeathCause,
+}
+
+/// 一轮放逐投票：ballots为投票者到目标，未投票的人不出现
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteRoundFact {
+    pub day: u32,
+    pub ballots: BTreeMap<String, String>,
+    pub eliminated: Option<String>,
+}
+
+/// 身份声明与真实身份的对照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimFact {
+    pub player_id: String,
+    pub day: u32,
+    pub claimed_role: RoleType,
+    pub true_role: RoleType,
+    pub truthful: bool,
+}
+
+/// 公开的查验报告与真实阵营的对照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckReportFact {
+    pub reporter: String,
+    pub target: String,
+    pub day: u32,
+    pub reported_werewolf: bool,
+    pub actually_werewolf: bool,
+    /// 报告者确实是预言家且当晚查验的结果与报告一致
+    pub truthful: bool,
+}
+
+impl FactSheet {
+    pub fn build(game_id: &str, engine: &GameEngine) -> Self {
+        let state = engine.get_state();
+        let everyone: Vec<&Player> = state.players.iter().chain(state.dead_players.iter()).collect();
+        let roles: HashMap<&str, &Role> = everyone.iter().map(|p| (p.id.as_str(), &p.role)).collect();
+        let is_wolf = |id: &str| roles.get(id).is_some_and(|role| role.faction == Faction::Werewolf);
+
+        let mut players: Vec<PlayerFact> = everyone.iter()
+            .map(|p| PlayerFact {
+                id: p.id.clone(),
+                seat: p.seat,
+                role: p.role.role_type.clone(),
+                faction: p.role.faction.clone(),
+                is_ai: p.is_ai,
+                survived: p.is_alive,
+            })
+            .collect();
+        players.sort_by_key(|p| p.seat);
+
+        let checks: Vec<CheckFact> = engine.all_seer_results().iter()
+            .map(|r| CheckFact {
+                night: r.night,
+                seer: r.seer.clone(),
+                target: r.target.clone(),
+                is_werewolf: r.is_werewolf,
+                reference: r.reference,
+            })
+            .collect();
+
+        let deaths = state.death_reveals.iter()
+            .map(|reveal| {
+                let voted_out = state.vote_outcomes.iter()
+                    .any(|o| o.day == reveal.day && o.eliminated.as_deref() == Some(reveal.player_id.as_str()));
+                DeathFact {
+                    player_id: reveal.player_id.clone(),
+                    day: reveal.day,
+                    cause: if voted_out { DeathCause::Vote } else { DeathCause::Night },
+                }
+            })
+            .collect();
+
+        let votes = state.vote_outcomes.iter()
+            .map(|outcome| VoteRoundFact {
+                day: outcome.day,
+                ballots: outcome.tally.iter()
+                    .flat_map(|t| t.voters.iter().map(move |voter| (voter.clone(), t.target.clone())))
+                    .collect(),
+                eliminated: outcome.eliminated.clone(),
+            })
+            .collect();
+
+        let claims = state.claims_board.claims.iter()
+            .filter_map(|claim| {
+                let true_role = roles.get(claim.player_id.as_str())?.role_type.clone();
+                Some(ClaimFact {
+                    player_id: claim.player_id.clone(),
+                    day: claim.day,
+                    truthful: claim.role == true_role,
+                    claimed_role: claim.role.clone(),
+                    true_role,
+                })
+            })
+            .collect();
+
+        let check_reports = state.claims_board.check_reports.iter()
+            .map(|report| CheckReportFact {
+                reporter: report.reporter.clone(),
+                target: report.target.clone(),
+                day: report.day,
+                reported_werewolf: report.is_werewolf,
+                actually_werewolf: is_wolf(&report.target),
+                truthful: checks.iter().any(|c| {
+                    c.seer == report.reporter && c.target == report.target && c.is_werewolf == report.is_werewolf
+                }),
+            })
+            .collect();
+
+        Self {
+            version: FACT_SHEET_VERSION,
+            game_id: game_id.to_string(),
+            winner: state.winner.clone(),
+            days: state.day,
+            players,
+            checks,
+            deaths,
+            votes,
+            claims,
+            check_reports,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    #[test]
+    fn test_claims_are_labelled_with_truth() {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        let players = engine.get_state().players.clone();
+        let villager = players.iter().find(|p| p.role.role_type == RoleType::Villager).unwrap();
+        engine.get_state_mut().claims_board.add_claim(crate::claims::RoleClaim {
+            player_id: villager.id.clone(),
+            role: RoleType::Seer,
+            day: 1,
+            source: crate::claims::ClaimSource::Explicit,
+            timestamp: chrono::Utc::now(),
+        });
+
+        let sheet = FactSheet::build("game", &engine);
+        assert_eq!(sheet.players.len(), players.len());
+        assert_eq!(sheet.claims.len(), 1);
+        assert!(!sheet.claims[0].truthful);
+        assert_eq!(sheet.claims[0].true_role, RoleType::Villager);
+    }
+}
@@ -1,21 +1,102 @@
 use crate::types::*;
+use crate::chat::{ChatChannel, ChatService, ChannelMessage};
+use crate::validator::{ActionValidator, ActionErrorCode, PlayerAction};
+use crate::narrator::Narrator;
+use crate::night_resolution::{NightResolution, NightResult};
+use crate::roles;
+use crate::claims::{self, CheckReport, ClaimSource, ClaimsBoard, RoleClaim};
+use crate::event_log::{self, EngineEvent, LoggedEvent};
+use crate::ai::{PersonaGenerator, PersonalityManager};
+use crate::house_rules::{DeathEffect, HouseRules};
 use crate::utils;
 use crate::error::{AppError, AppResult};
-use std::collections::HashMap;
-use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Utc};
 use log::{info, warn, error};
 use rand::{thread_rng, Rng};
 
+/// 讨论时长配置所对应的发言人数
+const DISCUSSION_BASELINE_SPEAKERS: u32 = 12;
+
 /// 游戏引擎
+///
+/// 除阶段计时器外的全部状态都由追加写入的事件日志折叠而来：公开方法先校验操作、
+/// 确定随机结果，再把事件写入日志并折叠进状态。存档、撤销、复盘回放都基于同一份日志
 pub struct GameEngine {
     state: GameState,
     players_map: HashMap<String, usize>, // player_id -> players index
     timer: Option<tokio::time::Instant>,
+    timer_duration: u32,
+    timer_paused_at: Option<tokio::time::Instant>,
+    chat: ChatService,
+    speech_log: Vec<SpeechRecord>,
+    replaced_humans: HashSet<String>,
+    night_actors: HashSet<String>,
+    /// 本夜收集的行动，天亮时统一结算
+    night: NightResolution,
+    night_results: Vec<NightResult>,
+    /// 重新投票仍平票、按规则随机放逐时抽中的玩家
+    tie_break: Option<String>,
+    /// 本夜在最短时长之外追加的随机秒数
+    night_padding: u32,
+    seer_results: Vec<SeerCheckResult>,
+    started_at: Option<DateTime<Utc>>,
+    log: Vec<LoggedEvent>,
+    /// 正在折叠的事件的时间
+    clock: DateTime<Utc>,
+    /// 正在折叠的事件的序号，房规脚本的随机数以此为种子
+    sequence: u64,
+    house_rules: HouseRules,
 }
 
 impl GameEngine {
     /// 创建新游戏
     pub fn new(config: GameConfig) -> AppResult<Self> {
+        if !(utils::MIN_PLAYERS..=utils::MAX_PLAYERS).contains(&config.total_players) {
+            return Err(AppError::Config(format!(
+                "玩家人数需在{}到{}之间，当前为{}",
+                utils::MIN_PLAYERS, utils::MAX_PLAYERS, config.total_players
+            )));
+        }
+        // 脚本有错时在开局前报出，而不是等到结算时静默失效
+        HouseRules::compile(&config.house_rules)?;
+        let mut engine = Self::blank(config.clone());
+        engine.record(EngineEvent::GameCreated { config: Box::new(config) });
+        Ok(engine)
+    }
+    
+    /// 从事件日志重建引擎，被撤销的事件不参与折叠
+    pub fn from_log(log: Vec<LoggedEvent>) -> AppResult<Self> {
+        let config = match log.first().map(|entry| &entry.event) {
+            Some(EngineEvent::GameCreated { config }) => config.as_ref().clone(),
+            _ => return Err(AppError::GameLogic("事件日志缺少游戏配置".to_string())),
+        };
+        let undone = event_log::undone_sequences(&log);
+        
+        let mut engine = Self::blank(config);
+        for entry in log.iter().filter(|entry| !undone.contains(&entry.sequence)) {
+            engine.fold(entry);
+        }
+        engine.log = log;
+        Ok(engine)
+    }
+
+    /// 复制当前对局，从同一局面走出互不影响的分支
+    pub fn fork(&self) -> AppResult<Self> {
+        Self::from_log(self.log.clone())
+    }
+
+    /// 完整的事件日志
+    #[cfg(any(test, feature = "rules-testing"))]
+    pub fn event_log(&self) -> &[LoggedEvent] {
+        &self.log
+    }
+    
+    fn blank(config: GameConfig) -> Self {
+        let house_rules = HouseRules::compile(&config.house_rules).unwrap_or_else(|e| {
+            warn!("房规脚本无效，按标准规则进行: {}", e);
+            HouseRules::compile(&[]).expect("空脚本列表")
+        });
         let state = GameState {
             phase: GamePhase::Preparation,
             day: 0,
@@ -26,27 +107,169 @@ impl GameEngine {
             winner: None,
             current_speaker: None,
             time_remaining: None,
+            vote_changes: Vec::new(),
+            vote_intents: HashMap::new(),
+            death_reveals: Vec::new(),
+            claims_board: ClaimsBoard::new(),
+            vote_outcomes: Vec::new(),
+            result: None,
+            witch_potions: HashMap::new(),
+            guard_protections: HashMap::new(),
+            sheriff: None,
+            revoked_votes: Vec::new(),
+            pk_candidates: Vec::new(),
         };
         
-        Ok(Self {
+        Self {
             state,
             players_map: HashMap::new(),
             timer: None,
-        })
+            timer_duration: 0,
+            timer_paused_at: None,
+            chat: ChatService::new(),
+            speech_log: Vec::new(),
+            replaced_humans: HashSet::new(),
+            night_actors: HashSet::new(),
+            night: NightResolution::default(),
+            night_results: Vec::new(),
+            tie_break: None,
+            night_padding: 0,
+            seer_results: Vec::new(),
+            started_at: None,
+            log: Vec::new(),
+            clock: Utc::now(),
+            sequence: 0,
+            house_rules,
+        }
+    }
+    
+    /// 追加事件并折叠进状态，返回事件是否改变了状态
+    fn record(&mut self, event: EngineEvent) -> bool {
+        let entry = LoggedEvent {
+            sequence: self.log.len() as u64,
+            timestamp: Utc::now(),
+            event,
+        };
+        let changed = self.fold(&entry);
+        self.log.push(entry);
+        changed
+    }
+    
+    /// 把一条事件折叠进状态。这里不做校验也不取随机数，这些在产生事件时已经完成
+    fn fold(&mut self, entry: &LoggedEvent) -> bool {
+        self.clock = entry.timestamp;
+        self.sequence = entry.sequence;
+        
+        match entry.event.clone() {
+            EngineEvent::GameCreated { .. } | EngineEvent::ActionUndone { .. } => {}
+            EngineEvent::PlayersSeated { players, role_distribution, human_seat } => {
+                self.state.game_config.role_distribution = role_distribution;
+                self.state.game_config.human_seat = Some(human_seat);
+                self.players_map = players.iter()
+                    .enumerate()
+                    .map(|(index, player)| (player.id.clone(), index))
+                    .collect();
+                self.state.witch_potions = players.iter()
+                    .filter(|p| p.role.role_type == RoleType::Witch)
+                    .map(|p| (p.id.clone(), WitchPotions::default()))
+                    .collect();
+                self.state.players = players;
+                info!("游戏初始化完成，共 {} 名玩家", self.state.players.len());
+            }
+            EngineEvent::GameStarted { gold_water, night_padding } => {
+                self.night_padding = night_padding;
+                self.state.phase = GamePhase::Night;
+                self.state.day = 1;
+                self.night_actors.clear();
+                self.started_at = Some(self.clock);
+                self.seer_results.extend(gold_water);
+                info!("游戏开始！第1夜");
+                self.start_phase_timer();
+            }
+            EngineEvent::PhaseAdvanced { night_padding } => {
+                self.night_padding = night_padding;
+                self.advance_phase();
+            }
+            EngineEvent::VoteCast { voter, target } => self.apply_vote(voter, target),
+            EngineEvent::VoteIntentSet { voter, target } => {
+                let previous = self.state.vote_intents.insert(voter, target.clone());
+                return previous.as_deref() != Some(target.as_str());
+            }
+            EngineEvent::SpeechAdded { message } => self.apply_speech(message),
+            EngineEvent::RoleClaimed { player_id, role, source } => {
+                return self.apply_claim(&player_id, role, source);
+            }
+            EngineEvent::CheckReported { reporter, target, is_werewolf, source } => {
+                return self.apply_check_report(&reporter, &target, is_werewolf, source);
+            }
+            EngineEvent::NightActionTaken { action } => self.apply_night_action(action),
+            EngineEvent::SpeakerChanged { speaker } => self.state.current_speaker = speaker,
+            EngineEvent::SeatReplaced { player_id, personality } => {
+                if let Some(player) = self.find_player_mut(&player_id) {
+                    player.is_ai = true;
+                    player.personality = Some(personality);
+                    info!("玩家 {} 的座位已由AI接管", player.name);
+                }
+                self.replaced_humans.insert(player_id);
+            }
+            EngineEvent::SeatRestored { player_id } => {
+                if let Some(player) = self.find_player_mut(&player_id) {
+                    player.is_ai = false;
+                    player.personality = None;
+                    info!("玩家 {} 已恢复控制座位", player.name);
+                }
+                self.replaced_humans.remove(&player_id);
+            }
+            EngineEvent::PersonalityDrifted { player_id, traits } => {
+                if let Some(personality) = self.find_player_mut(&player_id).and_then(|p| p.personality.as_mut()) {
+                    personality.traits = traits;
+                }
+            }
+            EngineEvent::ChannelMessagePosted { message } => self.chat.append(message),
+            EngineEvent::PacingChanged { profile } => profile.apply(&mut self.state.game_config),
+            EngineEvent::TieBroken { player_id } => self.tie_break = Some(player_id),
+            EngineEvent::SheriffChanged { player_id } => {
+                if self.state.sheriff == player_id {
+                    return false;
+                }
+                info!("警长变为: {:?}", player_id);
+                self.state.sheriff = player_id;
+            }
+            EngineEvent::VoteRevoked { player_id } => {
+                if self.state.revoked_votes.contains(&player_id) {
+                    return false;
+                }
+                info!("玩家 {} 失去投票权", player_id);
+                self.state.revoked_votes.push(player_id);
+            }
+        }
+        true
     }
     
     /// 初始化游戏
     pub fn initialize_game(&mut self) -> AppResult<()> {
         info!("初始化游戏，玩家数: {}", self.state.game_config.total_players);
         
-        // 生成角色分配
-        let role_distribution = utils::generate_role_distribution(self.state.game_config.total_players);
-        self.state.game_config.role_distribution = role_distribution.clone();
-        
+        // 配置里指定了能开局的板子（如平衡板子生成的结果）时照用，否则按人数生成
+        let config = &self.state.game_config;
+        let role_distribution = if utils::is_playable_distribution(config.total_players, &config.role_distribution) {
+            config.role_distribution.clone()
+        } else {
+            let mut role_distribution = utils::generate_role_distribution(config.total_players);
+            if let Some(passive) = config.passive_wolf.clone() {
+                // 至少保留一只能刀人的狼
+                let wolves = role_distribution.get(&RoleType::Werewolf).copied().unwrap_or(0);
+                if passive.faction() == Faction::Werewolf && !passive.joins_wolf_kill() && wolves > 1 {
+                    role_distribution.insert(RoleType::Werewolf, wolves - 1);
+                    *role_distribution.entry(passive).or_insert(0) += 1;
+                }
+            }
+            role_distribution
+        };
         // 创建角色列表
         let mut roles = Vec::new();
-        for (role_type, count) in role_distribution {
-            for _ in 0..count {
+        for (role_type, count) in &role_distribution {
+            for _ in 0..*count {
                 roles.push(self.create_role(role_type.clone()));
             }
         }
@@ -67,20 +290,36 @@ impl GameEngine {
                 is_alive: true,
                 is_ai: false,
                 personality: None,
+                persona: None,
+                seat: 0, // 座位在下方统一分配
             };
             players.push(human_player);
         }
         
-        // 添加AI玩家
+        // 添加AI玩家，性格模板轮流分配，人设与模板一致且不重名
+        let mut templates = PersonalityManager::get_personality_templates();
+        utils::shuffle(&mut templates);
+        let mut personas = PersonaGenerator::new();
+        for player in &players {
+            personas.reserve_name(&player.name);
+        }
+        
         for (i, role) in roles.into_iter().enumerate() {
+            let template = &templates[i % templates.len()];
+            let persona = personas.generate(template);
+            let mut personality = PersonalityManager::create_personality_from_template(template, 0.15);
+            personality.name = template.name.clone();
+            
             let ai_player = Player {
                 id: format!("ai_{}", i + 1),
-                name: utils::generate_ai_name(),
+                name: persona.name.clone(),
                 role: role.clone(),
                 faction: role.faction.clone(),
                 is_alive: true,
                 is_ai: true,
-                personality: Some(self.generate_ai_personality()),
+                personality: Some(personality),
+                persona: Some(persona),
+                seat: 0,
             };
             players.push(ai_player);
         }
@@ -90,150 +329,453 @@ impl GameEngine {
             human_player.faction = human_player.role.faction.clone();
         }
         
-        // 建立玩家映射
-        for (index, player) in players.iter().enumerate() {
-            self.players_map.insert(player.id.clone(), index);
+        // 分配座位：人类玩家坐在选定（或随机）的座位，AI依次填满其余座位
+        let seat_count = players.len() as u8;
+        let human_seat = self.state.game_config.human_seat
+            .filter(|seat| (1..=seat_count).contains(seat))
+            .unwrap_or_else(|| thread_rng().gen_range(1..=seat_count.max(1)));
+        let mut ai_seats = (1..=seat_count).filter(|seat| *seat != human_seat);
+        for player in players.iter_mut() {
+            player.seat = if player.is_ai {
+                ai_seats.next().unwrap_or(0)
+            } else {
+                human_seat
+            };
         }
+        players.sort_by_key(|p| p.seat);
         
-        self.state.players = players;
-        
-        info!("游戏初始化完成，共 {} 名玩家", self.state.players.len());
+        self.record(EngineEvent::PlayersSeated { players, role_distribution, human_seat });
         Ok(())
     }
     
     /// 创建角色
     fn create_role(&self, role_type: RoleType) -> Role {
-        let faction = match role_type {
-            RoleType::Werewolf => Faction::Werewolf,
-            _ => Faction::Villager,
-        };
-        
-        let (can_vote, has_night_action) = match role_type {
-            RoleType::Werewolf => (true, true),
-            RoleType::Villager => (true, false),
-            RoleType::Seer => (true, true),
-            RoleType::Witch => (true, true),
-            RoleType::Hunter => (true, false),
-            RoleType::Guard => (true, true),
-        };
+        // 梦狼和石像鬼只在狼队没有其他能刀人的狼时才会夜间行动
+        let info = roles::role_info(&role_type);
         
         Role {
-            role_type: role_type.clone(),
-            faction,
-            description: utils::get_role_description(&role_type),
-            can_vote,
-            has_night_action,
+            role_type,
+            faction: info.faction.clone(),
+            description: info.description(),
+            can_vote: info.can_vote,
+            has_night_action: info.has_night_action(),
         }
     }
     
-    /// 生成AI性格
-    fn generate_ai_personality(&self) -> AIPersonality {
-        let mut rng = thread_rng();
+    /// 生成AI性格（基于随机的性格模板）
+    pub fn generate_ai_personality(&self) -> AIPersonality {
+        let templates = PersonalityManager::get_personality_templates();
+        let template = &templates[thread_rng().gen_range(0..templates.len())];
         
-        AIPersonality {
-            id: utils::generate_id(),
-            name: "标准AI".to_string(),
-            description: "平衡型AI，具备适中的各项能力".to_string(),
-            traits: PersonalityTraits {
-                aggressiveness: rng.gen_range(0.3..0.8),
-                logic: rng.gen_range(0.5..0.9),
-                deception: rng.gen_range(0.4..0.7),
-                trustfulness: rng.gen_range(0.3..0.7),
-            },
-        }
+        let mut personality = PersonalityManager::create_personality_from_template(template, 0.15);
+        personality.name = template.name.clone();
+        personality
     }
     
     /// 开始游戏
     pub fn start_game(&mut self) -> AppResult<()> {
+        self.validate_action(&PlayerAction::StartGame)?;
+        
         if self.state.players.is_empty() {
             return Err(AppError::GameLogic("没有玩家，无法开始游戏".to_string()));
         }
         
-        self.state.phase = GamePhase::Night;
-        self.state.day = 1;
+        let gold_water = if self.state.game_config.night_rules.first_night_seer == FirstNightSeerRule::GoldWater {
+            self.draw_gold_water()
+        } else {
+            Vec::new()
+        };
+        let night_padding = self.draw_night_padding();
+        self.record(EngineEvent::GameStarted { gold_water, night_padding });
+        Ok(())
+    }
+    
+    /// 开局随机告知每位预言家一名好人
+    fn draw_gold_water(&self) -> Vec<SeerCheckResult> {
+        let mut rng = thread_rng();
+        let mut results = Vec::new();
         
-        info!("游戏开始！第1夜");
-        self.start_phase_timer()
+        for seer in self.state.players.iter().filter(|p| p.role.role_type == RoleType::Seer) {
+            let candidates: Vec<&Player> = self.state.players.iter()
+                .filter(|p| p.id != seer.id && p.faction == Faction::Villager)
+                .collect();
+            if candidates.is_empty() {
+                continue;
+            }
+            results.push(SeerCheckResult {
+                night: 1,
+                seer: seer.id.clone(),
+                target: candidates[rng.gen_range(0..candidates.len())].id.clone(),
+                is_werewolf: false,
+                reference: true,
+            });
+        }
+        results
     }
     
     /// 进入下一阶段
     pub fn next_phase(&mut self) -> AppResult<()> {
         match self.state.phase {
-            GamePhase::Preparation => {
-                self.start_game()?;
+            GamePhase::Preparation => return self.start_game(),
+            // 夜晚必须走完统一时长，不论是否有人需要行动
+            GamePhase::Night if !self.phase_time_elapsed() => {
+                return Err(AppError::InvalidAction(ActionErrorCode::WrongPhase, "夜晚尚未结束".to_string()));
+            }
+            GamePhase::GameOver => {
+                info!("游戏已结束");
+                return Ok(());
             }
+            _ => {}
+        }
+        
+        // 随机放逐在产生事件时抽好，重建状态时不再取随机数
+        if let Some(player_id) = self.draw_tie_break() {
+            self.record(EngineEvent::TieBroken { player_id });
+        }
+        let night_padding = self.draw_night_padding();
+        self.record(EngineEvent::PhaseAdvanced { night_padding });
+        Ok(())
+    }
+    
+    /// 夜晚时长的随机部分，在产生事件时抽好，重建状态时不再取随机数
+    fn draw_night_padding(&self) -> u32 {
+        thread_rng().gen_range(0..=self.state.game_config.night_timing.max_padding)
+    }
+    
+    /// 重新投票仍然平票且规则为随机放逐时，从平票的玩家中抽一人
+    fn draw_tie_break(&self) -> Option<String> {
+        use rand::seq::SliceRandom;
+        
+        if self.state.phase != GamePhase::Voting
+            || self.state.pk_candidates.is_empty()
+            || self.state.game_config.vote_rules.revote_tie != RevoteTiePolicy::Random {
+            return None;
+        }
+        self.tally_votes().tied.choose(&mut thread_rng()).cloned()
+    }
+    
+    /// 结算当前阶段并进入下一阶段
+    fn advance_phase(&mut self) {
+        match self.state.phase {
             GamePhase::Night => {
+                // 夜晚结果在天亮时同时公布
+                self.resolve_night();
                 self.state.phase = GamePhase::DayDiscussion;
                 info!("进入白天讨论阶段");
-                self.start_phase_timer()?;
+                self.start_phase_timer();
             }
             GamePhase::DayDiscussion => {
+                self.state.vote_intents.clear();
+                self.state.current_speaker = None;
                 self.state.phase = GamePhase::Voting;
                 info!("进入投票阶段");
-                self.start_phase_timer()?;
+                self.start_phase_timer();
             }
             GamePhase::Voting => {
-                self.process_votes()?;
-                if self.check_game_end()? {
+                if self.process_votes() {
+                    self.state.phase = GamePhase::Pk;
+                    info!("平票，进入PK发言: {:?}", self.state.pk_candidates);
+                    self.start_phase_timer();
+                    return;
+                }
+                if self.check_game_end() {
                     self.state.phase = GamePhase::GameOver;
                 } else {
                     self.state.phase = GamePhase::Night;
                     self.state.day += 1;
+                    self.night_actors.clear();
                     info!("进入第{}夜", self.state.day);
+                    self.start_phase_timer();
                 }
             }
+            GamePhase::Pk => {
+                self.state.current_speaker = None;
+                self.state.phase = GamePhase::Voting;
+                info!("PK发言结束，重新投票");
+                self.start_phase_timer();
+            }
             GamePhase::LastWords => {
                 self.state.phase = GamePhase::Night;
                 self.state.day += 1;
+                self.night_actors.clear();
                 info!("进入第{}夜", self.state.day);
+                self.start_phase_timer();
             }
-            GamePhase::GameOver => {
-                info!("游戏已结束");
-                return Ok(());
-            }
+            GamePhase::Preparation | GamePhase::GameOver => {}
         }
+    }
+    
+    /// 撤销本阶段最近一次投票：在日志中追加撤销记录后重新折叠整份日志。
+    /// 发言送出即生效不能撤销，计票后的投票同样不能跨阶段撤销
+    pub fn undo_last_action(&mut self) -> AppResult<UndoneAction> {
+        self.validate_action(&PlayerAction::Undo)?;
         
-        Ok(())
+        let undone = event_log::undone_sequences(&self.log);
+        let target = self.log.iter()
+            .rev()
+            .take_while(|entry| !entry.event.is_phase_boundary())
+            .find(|entry| entry.event.undoable() && !undone.contains(&entry.sequence))
+            .ok_or_else(|| AppError::InvalidAction(ActionErrorCode::NothingToUndo, "本阶段没有可撤销的投票".to_string()))?;
+        let sequence = target.sequence;
+        let action = target.event.to_undone()
+            .ok_or_else(|| AppError::GameLogic("该事件不能撤销".to_string()))?;
+        
+        let mut log = self.log.clone();
+        log.push(LoggedEvent {
+            sequence: log.len() as u64,
+            timestamp: Utc::now(),
+            event: EngineEvent::ActionUndone { sequence },
+        });
+        let mut rebuilt = Self::from_log(log)?;
+        
+        // 计时器不属于事件状态，沿用当前的计时，撤销不会延长阶段
+        rebuilt.timer = self.timer;
+        rebuilt.timer_duration = self.timer_duration;
+        rebuilt.timer_paused_at = self.timer_paused_at;
+        rebuilt.state.time_remaining = self.state.time_remaining;
+        *self = rebuilt;
+        
+        info!("已撤销操作: {:?}", action);
+        Ok(action)
     }
     
     /// 开始阶段计时器
-    fn start_phase_timer(&mut self) -> AppResult<()> {
-        let duration = match self.state.phase {
-            GamePhase::DayDiscussion => self.state.game_config.discussion_time,
-            GamePhase::Voting => self.state.game_config.voting_time,
-            _ => 0,
-        };
-        
+    fn start_phase_timer(&mut self) {
+        let duration = self.phase_duration();
         if duration > 0 {
             self.state.time_remaining = Some(duration);
             self.timer = Some(tokio::time::Instant::now());
+            self.timer_duration = duration;
+            self.timer_paused_at = None;
+        } else {
+            // 不计时的阶段不能沿用上一阶段的计时
+            self.state.time_remaining = None;
+            self.timer = None;
+            self.timer_duration = 0;
+            self.timer_paused_at = None;
+        }
+    }
+    
+    /// 按当前配置计算本阶段的总时长
+    fn phase_duration(&self) -> u32 {
+        match self.state.phase {
+            GamePhase::DayDiscussion => self.discussion_duration(),
+            GamePhase::Voting => self.state.game_config.voting_time,
+            // 每名PK玩家一次完整发言的时长
+            GamePhase::Pk => {
+                self.state.game_config.speech_limits.discussion_max_seconds * self.state.pk_candidates.len() as u32
+            }
+            GamePhase::Night => {
+                let timing = &self.state.game_config.night_timing;
+                // 中途换成随机时长更短的节奏档位时，按新的上限截断
+                timing.min_duration + self.night_padding.min(timing.max_padding)
+            }
+            _ => 0,
+        }
+    }
+    
+    /// 对局中切换节奏档位。已用掉的时间照算，新时长比已用时间还短时阶段在下一次计时时结束
+    pub fn set_pacing(&mut self, profile: PacingProfile) {
+        self.record(EngineEvent::PacingChanged { profile });
+        if self.timer.is_some() {
+            self.timer_duration = self.phase_duration();
+            let left = self.phase_time_left().as_secs() as u32;
+            self.state.time_remaining = Some(left);
+        }
+        info!("节奏档位切换为: {:?}", profile);
+    }
+    
+    /// 白天讨论时长：配置时长按12人发言设计，存活人数更多时按比例延长，保证每人的发言时间不被压缩
+    fn discussion_duration(&self) -> u32 {
+        let base = self.state.game_config.discussion_time;
+        let speakers = self.state.players.iter().filter(|p| p.is_alive).count() as u32;
+        if speakers > DISCUSSION_BASELINE_SPEAKERS {
+            base * speakers / DISCUSSION_BASELINE_SPEAKERS
+        } else {
+            base
+        }
+    }
+    
+    /// 当前阶段剩余的计时（计时暂停期间按暂停时刻计算）
+    pub fn phase_time_left(&self) -> std::time::Duration {
+        match self.timer {
+            Some(timer) => {
+                let elapsed = match self.timer_paused_at {
+                    Some(paused_at) => paused_at - timer,
+                    None => timer.elapsed(),
+                };
+                std::time::Duration::from_secs(self.timer_duration as u64).saturating_sub(elapsed)
+            }
+            None => std::time::Duration::ZERO,
+        }
+    }
+    
+    /// 当前阶段的计时是否已走完（没有计时视为已走完）
+    fn phase_time_elapsed(&self) -> bool {
+        match self.timer {
+            Some(timer) => self.timer_paused_at.is_none() && timer.elapsed().as_secs() as u32 >= self.timer_duration,
+            None => true,
+        }
+    }
+    
+    /// 按固定顺序结算本夜行动，房规可以改写最终的出局名单
+    fn resolve_night(&mut self) {
+        let mut result = self.night.resolve(self.state.day, &self.state.game_config.night_rules);
+        let mut pending = result.deaths.clone();
+        if let Some(deaths) = self.house_rules.on_night_resolve(&self.state, self.sequence, &pending) {
+            pending = deaths.into_iter().filter(|id| self.is_player_alive(id)).collect();
+            pending.sort();
+            pending.dedup();
+        }
+        result.deaths = pending.clone();
+        self.night_results.push(result);
+        for player_id in pending {
+            self.eliminate_player(player_id);
+        }
+    }
+    
+    /// 暂停阶段计时器（AI思考期间不消耗发言时间）
+    pub fn pause_timer(&mut self) {
+        if self.timer.is_some() && self.timer_paused_at.is_none() {
+            self.timer_paused_at = Some(tokio::time::Instant::now());
+        }
+    }
+    
+    /// 恢复阶段计时器，暂停的时长不计入阶段用时
+    pub fn resume_timer(&mut self) {
+        if let (Some(timer), Some(paused_at)) = (self.timer, self.timer_paused_at.take()) {
+            self.timer = Some(timer + paused_at.elapsed());
+        }
+    }
+    
+    /// 处理投票，计票结果记录到游戏状态中；返回是否因平票进入PK
+    fn process_votes(&mut self) -> bool {
+        let revote = !self.state.pk_candidates.is_empty();
+        let mut outcome = self.tally_votes();
+        outcome.revote = revote;
+        if let Some(chosen) = self.tie_break.take() {
+            info!("重新投票仍然平票，随机放逐{}", chosen);
+            outcome.eliminated = Some(chosen);
+        }
+        if let Some(eliminated) = self.house_rules.on_vote_tally(&self.state, self.sequence, &outcome) {
+            match eliminated {
+                Some(id) if !self.is_player_alive(&id) => warn!("房规脚本指定的出局玩家{}不在场，保持原计票结果", id),
+                eliminated => {
+                    info!("房规脚本改变了放逐结果: {:?} -> {:?}", outcome.eliminated, eliminated);
+                    outcome.eliminated = eliminated;
+                }
+            }
         }
         
-        Ok(())
+        outcome.pk = !revote
+            && outcome.eliminated.is_none()
+            && outcome.tied.len() > 1
+            && self.state.game_config.vote_rules.pk_on_tie;
+        if let Some(eliminated_player_id) = &outcome.eliminated {
+            self.eliminate_player(eliminated_player_id.clone());
+        } else if !outcome.tied.is_empty() && !outcome.pk {
+            info!("第{}天投票平票，无人出局", outcome.day);
+        }
+        
+        // 清空投票记录，进入PK时记下平票的玩家
+        self.state.pk_candidates = if outcome.pk { outcome.tied.clone() } else { Vec::new() };
+        self.state.votes.clear();
+        let pk = outcome.pk;
+        self.state.vote_outcomes.push(outcome);
+        pk
     }
     
-    /// 处理投票
-    fn process_votes(&mut self) -> AppResult<()> {
-        let mut vote_counts: HashMap<String, u32> = HashMap::new();
+    /// 统计票数。每票按VoteRules计权，结果按加权票数降序、座位号升序排列，与投票提交顺序无关；最高票并列时无人出局。
+    /// 只有失去票权的玩家投给的目标仍列在计票里，加权票数为0，不会被放逐
+    fn tally_votes(&self) -> VoteOutcome {
+        let seat_of = |player_id: &str| {
+            self.state.players.iter()
+                .chain(self.state.dead_players.iter())
+                .find(|p| p.id == player_id)
+                .map(|p| p.seat)
+                .unwrap_or(u8::MAX)
+        };
         
-        // 统计票数
+        let mut ballots: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut abstentions: Vec<&str> = Vec::new();
         for vote in &self.state.votes {
-            *vote_counts.entry(vote.target.clone()).or_insert(0) += 1;
+            if vote.is_abstention() {
+                abstentions.push(vote.voter.as_str());
+            } else {
+                ballots.entry(vote.target.as_str()).or_default().push(vote.voter.as_str());
+            }
         }
+        abstentions.sort_by_key(|voter| (seat_of(voter), *voter));
         
-        // 找出得票最多的玩家
-        if let Some((eliminated_player_id, _)) = vote_counts.iter().max_by_key(|(_, &count)| count) {
-            self.eliminate_player(eliminated_player_id.clone())?;
-        }
+        let rules = &self.state.game_config.vote_rules;
+        let mut tally: Vec<VoteTally> = ballots.into_iter()
+            .map(|(target, mut voters)| {
+                voters.sort_by_key(|voter| (seat_of(voter), *voter));
+                VoteTally {
+                    target: target.to_string(),
+                    count: voters.len() as u32,
+                    weight: voters.iter().map(|voter| rules.weight(&self.state, voter)).sum(),
+                    voters: voters.into_iter().map(str::to_string).collect(),
+                }
+            })
+            .collect();
+        tally.sort_by(|a, b| {
+            b.weight.total_cmp(&a.weight)
+                .then_with(|| seat_of(&a.target).cmp(&seat_of(&b.target)))
+                .then_with(|| a.target.cmp(&b.target))
+        });
         
-        // 清空投票记录
-        self.state.votes.clear();
+        let top = tally.first().map(|t| t.weight).unwrap_or(0.0);
+        let leaders: Vec<String> = tally.iter()
+            .take_while(|t| t.weight > 0.0 && (t.weight - top).abs() < 1e-4)
+            .map(|t| t.target.clone())
+            .collect();
+        let (eliminated, tied) = match leaders.len() {
+            0 => (None, Vec::new()),
+            1 => (leaders.into_iter().next(), Vec::new()),
+            _ => (None, leaders),
+        };
         
-        Ok(())
+        VoteOutcome {
+            day: self.state.day,
+            tally,
+            eliminated,
+            tied,
+            abstentions: abstentions.into_iter().map(str::to_string).collect(),
+            pk: false,
+            revote: false,
+        }
+    }
+    
+    /// 淘汰玩家并触发房规的on_death钩子
+    fn eliminate_player(&mut self, player_id: String) {
+        let player = match self.remove_player(player_id) {
+            Some(player) => player,
+            None => return,
+        };
+        
+        for effect in self.house_rules.on_death(&self.state, self.sequence, &player) {
+            match effect {
+                DeathEffect::SetRole { player: target, role } => {
+                    let role = self.create_role(role);
+                    match self.find_player_mut(&target).filter(|p| p.is_alive) {
+                        Some(target) => {
+                            info!("房规: {} 的身份变为{:?}", target.name, role.role_type);
+                            target.role = role;
+                        }
+                        None => warn!("房规脚本要改身份的玩家{}不在场", target),
+                    }
+                }
+                // 连带出局不再触发钩子，避免脚本互相触发
+                DeathEffect::Kill { player: target } => {
+                    self.remove_player(target);
+                }
+            }
+        }
     }
     
-    /// 淘汰玩家
-    fn eliminate_player(&mut self, player_id: String) -> AppResult<()> {
+    /// 把玩家移出存活列表并公示死亡信息，返回出局的玩家
+    fn remove_player(&mut self, player_id: String) -> Option<Player> {
         if let Some(&index) = self.players_map.get(&player_id) {
             if index < self.state.players.len() {
                 let mut player = self.state.players.remove(index);
@@ -250,19 +792,24 @@ impl GameEngine {
                 
                 info!("玩家 {} 被淘汰", player.name);
                 
+                // 按身份公开规则公示死亡信息
+                let reveal = self.state.game_config.role_reveal.reveal(&player, self.state.day);
+                self.announce(Narrator::death_announcement(&player.display_name(), &reveal));
+                self.state.death_reveals.push(reveal);
+                
                 // 检查猎人技能
                 if player.role.role_type == RoleType::Hunter {
                     // TODO: 实现猎人技能
                     info!("猎人 {} 可以开枪带走一名玩家", player.name);
                 }
+                return Some(player);
             }
         }
-        
-        Ok(())
+        None
     }
     
     /// 检查游戏是否结束
-    fn check_game_end(&mut self) -> AppResult<bool> {
+    fn check_game_end(&mut self) -> bool {
         let alive_werewolves = self.state.players.iter()
             .filter(|p| p.is_alive && p.role.faction == Faction::Werewolf)
             .count();
@@ -274,28 +821,62 @@ impl GameEngine {
         if let Some(winner) = utils::check_win_condition(alive_werewolves, alive_villagers) {
             self.state.winner = Some(winner.clone());
             self.state.phase = GamePhase::GameOver;
+            self.state.result = Some(self.build_result(winner.clone()));
             
             info!("游戏结束！获胜方: {:?}", winner);
-            return Ok(true);
+            return true;
         }
         
-        Ok(false)
+        false
     }
     
-    /// 投票
-    pub fn vote(&mut self, voter_id: String, target_id: String) -> AppResult<()> {
-        if self.state.phase != GamePhase::Voting {
-            return Err(AppError::GameLogic("当前不是投票阶段".to_string()));
-        }
+    /// 汇总游戏结算结果
+    fn build_result(&self, winner: Faction) -> GameResult {
+        let game_duration = self.started_at
+            .map(|started| (self.clock - started).num_seconds().max(0) as u32)
+            .unwrap_or(0);
+        let mut survivors: Vec<&Player> = self.state.players.iter().filter(|p| p.is_alive).collect();
+        survivors.sort_by_key(|p| p.seat);
         
-        // 检查投票者是否存在且存活
-        if !self.is_player_alive(&voter_id) {
-            return Err(AppError::GameLogic("投票者不存在或已死亡".to_string()));
+        GameResult {
+            winner,
+            game_duration,
+            total_votes: self.state.vote_outcomes.iter()
+                .flat_map(|outcome| outcome.tally.iter())
+                .map(|t| t.count)
+                .sum(),
+            players_killed: self.state.dead_players.iter().map(|p| p.id.clone()).collect(),
+            survivors: survivors.into_iter().map(|p| p.id.clone()).collect(),
         }
+    }
+    
+    /// 投票，target_id为None表示弃票
+    pub fn vote(&mut self, voter_id: String, target_id: Option<String>) -> AppResult<()> {
+        self.validate_action(&PlayerAction::Vote {
+            voter: voter_id.clone(),
+            target: target_id.clone(),
+        })?;
         
-        // 检查目标是否存在且存活
-        if !self.is_player_alive(&target_id) {
-            return Err(AppError::GameLogic("投票目标不存在或已死亡".to_string()));
+        let target_id = target_id.unwrap_or_else(|| VoteRecord::ABSTAIN.to_string());
+        if self.state.votes.iter().any(|v| v.voter == voter_id && v.target == target_id) {
+            return Ok(());
+        }
+        self.record(EngineEvent::VoteCast { voter: voter_id, target: target_id });
+        Ok(())
+    }
+    
+    fn apply_vote(&mut self, voter_id: String, target_id: String) {
+        // 重复提交视为改票，并留下可审计的改票记录
+        if let Some(previous) = self.state.votes.iter().find(|v| v.voter == voter_id) {
+            let change = VoteChange {
+                voter: voter_id.clone(),
+                previous_target: previous.target.clone(),
+                new_target: target_id.clone(),
+                day: self.state.day,
+                timestamp: self.clock,
+            };
+            info!("玩家 {} 改票: {} -> {}", change.voter, change.previous_target, change.new_target);
+            self.state.vote_changes.push(change);
         }
         
         // 移除之前的投票（如果有）
@@ -305,12 +886,37 @@ impl GameEngine {
         let vote = VoteRecord {
             voter: voter_id,
             target: target_id,
-            timestamp: Utc::now(),
+            timestamp: self.clock,
         };
         
         self.state.votes.push(vote);
+    }
+    
+    /// 警长当选或移交警徽，None表示撕毁警徽，返回警长是否发生变化
+    pub fn set_sheriff(&mut self, player_id: Option<String>) -> AppResult<bool> {
+        self.validate_action(&PlayerAction::SetSheriff { player: player_id.clone() })?;
+        Ok(self.record(EngineEvent::SheriffChanged { player_id }))
+    }
+    
+    /// 剥夺玩家的投票权（如白痴翻牌），返回票权是否发生变化
+    pub fn revoke_vote(&mut self, player_id: &str) -> AppResult<bool> {
+        self.validate_action(&PlayerAction::RevokeVote { player: player_id.to_string() })?;
+        Ok(self.record(EngineEvent::VoteRevoked { player_id: player_id.to_string() }))
+    }
+    
+    /// 表达投票意向（不计入正式投票），返回意向是否发生变化
+    pub fn set_vote_intent(&mut self, voter_id: String, target_id: String) -> AppResult<bool> {
+        self.validate_action(&PlayerAction::VoteIntent {
+            voter: voter_id.clone(),
+            target: target_id.clone(),
+        })?;
         
-        Ok(())
+        Ok(self.record(EngineEvent::VoteIntentSet { voter: voter_id, target: target_id }))
+    }
+    
+    /// 校验玩家操作
+    pub fn validate_action(&self, action: &PlayerAction) -> AppResult<()> {
+        ActionValidator::validate(&self.state, &self.night_actors, action)
     }
     
     /// 检查玩家是否存活
@@ -323,23 +929,28 @@ impl GameEngine {
         &self.state
     }
     
-    /// 获取可变游戏状态
+    /// 获取可变游戏状态，只供测试直接摆出局面
+    #[cfg(test)]
     pub fn get_state_mut(&mut self) -> &mut GameState {
         &mut self.state
     }
     
     /// 更新计时器
     pub fn update_timer(&mut self) -> AppResult<bool> {
-        if let (Some(timer), Some(time_remaining)) = (self.timer, self.state.time_remaining) {
+        if self.timer_paused_at.is_some() {
+            return Ok(false);
+        }
+        
+        if let Some(timer) = self.timer {
             let elapsed = timer.elapsed().as_secs() as u32;
             
-            if elapsed >= time_remaining {
+            if elapsed >= self.timer_duration {
                 self.state.time_remaining = None;
                 self.timer = None;
                 info!("阶段时间已到");
                 return Ok(true); // 时间到了
             } else {
-                self.state.time_remaining = Some(time_remaining - elapsed);
+                self.state.time_remaining = Some(self.timer_duration - elapsed);
             }
         }
         
@@ -348,39 +959,811 @@ impl GameEngine {
     
     /// 添加聊天消息
     pub fn add_chat_message(&mut self, message: ChatMessage) -> AppResult<()> {
-        // TODO: 存储聊天消息到某个地方
         info!("聊天消息: {} - {}", message.sender, message.content);
+        self.record(EngineEvent::SpeechAdded { message });
         Ok(())
     }
     
+    fn apply_speech(&mut self, message: ChatMessage) {
+        if message.sender != Narrator::SPEAKER {
+            self.record_speech_claims(&message.sender, &message.content);
+        }
+        
+        self.speech_log.push(SpeechRecord {
+            speaker: message.sender,
+            content: message.content,
+            timestamp: message.timestamp,
+            phase: self.state.phase.clone(),
+            day: self.state.day,
+        });
+    }
+    
+    /// 从发言中解析身份声明与查验报告并记入声明板
+    fn record_speech_claims(&mut self, speaker: &str, content: &str) {
+        let parsed = claims::parse_claims(content);
+        
+        if let Some(role) = parsed.role {
+            self.record_claim(speaker, role, ClaimSource::Speech);
+        }
+        
+        for (seat, is_werewolf) in parsed.checks {
+            let target = self.state.players.iter()
+                .chain(self.state.dead_players.iter())
+                .find(|p| p.seat == seat && p.id != speaker)
+                .map(|p| p.id.clone());
+            if let Some(target) = target {
+                self.record_check_report(speaker, &target, is_werewolf, ClaimSource::Speech);
+            }
+        }
+    }
+    
+    fn record_claim(&mut self, player_id: &str, role: RoleType, source: ClaimSource) -> bool {
+        let claim = RoleClaim {
+            player_id: player_id.to_string(),
+            role,
+            day: self.state.day,
+            source,
+            timestamp: self.clock,
+        };
+        let added = self.state.claims_board.add_claim(claim);
+        if added {
+            info!("玩家 {} 声明身份", player_id);
+        }
+        added
+    }
+    
+    fn record_check_report(&mut self, reporter: &str, target: &str, is_werewolf: bool, source: ClaimSource) -> bool {
+        let report = CheckReport {
+            reporter: reporter.to_string(),
+            target: target.to_string(),
+            is_werewolf,
+            day: self.state.day,
+            source,
+            timestamp: self.clock,
+        };
+        self.state.claims_board.add_check_report(report)
+    }
+    
+    /// 声明身份，返回声明板是否发生变化
+    pub fn claim_role(&mut self, player_id: &str, role: RoleType, source: ClaimSource) -> AppResult<bool> {
+        // 声明视同发言，遵循相同的阶段与发言顺序限制
+        self.validate_action(&PlayerAction::Speech { speaker: player_id.to_string() })?;
+        Ok(self.record(EngineEvent::RoleClaimed { player_id: player_id.to_string(), role, source }))
+    }
+    
+    fn apply_claim(&mut self, player_id: &str, role: RoleType, source: ClaimSource) -> bool {
+        let explicit = source == ClaimSource::Explicit;
+        let added = self.record_claim(player_id, role.clone(), source);
+        
+        // 正式声明由主持人公示，所有人都能看到
+        if added && explicit {
+            let name = self.display_name_of(player_id);
+            self.announce(Narrator::role_claim(&name, &role));
+        }
+        added
+    }
+    
+    /// 公布查验结果，返回声明板是否发生变化
+    pub fn report_check(&mut self, reporter: &str, target: &str, is_werewolf: bool, source: ClaimSource) -> AppResult<bool> {
+        self.validate_action(&PlayerAction::Speech { speaker: reporter.to_string() })?;
+        if reporter == target || self.find_player(target).is_none() {
+            return Err(AppError::InvalidAction(ActionErrorCode::TargetNotFound, format!("无效的查验目标: {}", target)));
+        }
+        Ok(self.record(EngineEvent::CheckReported {
+            reporter: reporter.to_string(),
+            target: target.to_string(),
+            is_werewolf,
+            source,
+        }))
+    }
+    
+    fn apply_check_report(&mut self, reporter: &str, target: &str, is_werewolf: bool, source: ClaimSource) -> bool {
+        let explicit = source == ClaimSource::Explicit;
+        let added = self.record_check_report(reporter, target, is_werewolf, source);
+        
+        if added && explicit {
+            let reporter_name = self.display_name_of(reporter);
+            let target_name = self.display_name_of(target);
+            self.announce(Narrator::check_report(&reporter_name, &target_name, is_werewolf));
+        }
+        added
+    }
+    
+    fn display_name_of(&self, player_id: &str) -> String {
+        self.find_player(player_id)
+            .map(|p| p.display_name())
+            .unwrap_or_else(|| player_id.to_string())
+    }
+    
+    /// 获取公开声明板
+    pub fn get_claims_board(&self) -> &ClaimsBoard {
+        &self.state.claims_board
+    }
+    
+    /// 发布主持人公告，记入公开发言记录
+    fn announce(&mut self, content: String) {
+        info!("主持人公告: {}", content);
+        
+        self.speech_log.push(SpeechRecord {
+            speaker: Narrator::SPEAKER.to_string(),
+            content,
+            timestamp: self.clock,
+            phase: self.state.phase.clone(),
+            day: self.state.day,
+        });
+    }
+    
+    /// 获取公开发言记录
+    pub fn get_speech_history(&self) -> &[SpeechRecord] {
+        &self.speech_log
+    }
+    
+    /// 查找玩家的可变引用（包括已死亡玩家）
+    fn find_player_mut(&mut self, player_id: &str) -> Option<&mut Player> {
+        self.state.players.iter_mut()
+            .chain(self.state.dead_players.iter_mut())
+            .find(|p| p.id == player_id)
+    }
+    
+    /// 将人类玩家的座位交给AI托管
+    pub fn replace_human_with_ai(&mut self, player_id: &str, personality: AIPersonality) -> AppResult<Player> {
+        let player = self.find_player(player_id)
+            .ok_or_else(|| AppError::NotFound(format!("玩家不存在: {}", player_id)))?;
+        
+        if player.is_ai {
+            return Err(AppError::GameLogic("该座位已由AI控制".to_string()));
+        }
+        
+        self.record(EngineEvent::SeatReplaced { player_id: player_id.to_string(), personality });
+        self.find_player(player_id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("玩家不存在: {}", player_id)))
+    }
+    
+    /// 同步AI座位漂移后的性格，座位上的发言节奏、狼队话语权等随之变化
+    pub fn update_personality_traits(&mut self, player_id: &str, traits: PersonalityTraits) {
+        self.record(EngineEvent::PersonalityDrifted { player_id: player_id.to_string(), traits });
+    }
+    
+    /// 将AI托管的座位交还给人类玩家
+    pub fn restore_human(&mut self, player_id: &str) -> AppResult<Player> {
+        if !self.replaced_humans.contains(player_id) {
+            return Err(AppError::GameLogic("该座位不是由AI托管的人类座位".to_string()));
+        }
+        
+        self.find_player(player_id)
+            .ok_or_else(|| AppError::NotFound(format!("玩家不存在: {}", player_id)))?;
+        
+        self.record(EngineEvent::SeatRestored { player_id: player_id.to_string() });
+        self.find_player(player_id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("玩家不存在: {}", player_id)))
+    }
+    
+    /// 设置当前发言者，None表示不限制发言顺序
+    pub fn set_current_speaker(&mut self, speaker: Option<String>) {
+        self.record(EngineEvent::SpeakerChanged { speaker });
+    }
+    
+    /// 查找玩家（包括已死亡玩家）
+    pub fn find_player(&self, player_id: &str) -> Option<&Player> {
+        self.state.players.iter()
+            .chain(self.state.dead_players.iter())
+            .find(|p| p.id == player_id)
+    }
+    
+    /// 在聊天频道发送消息
+    pub fn post_channel_message(&mut self, sender_id: &str, channel: ChatChannel, content: String) -> AppResult<ChannelMessage> {
+        let sender = self.find_player(sender_id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("玩家不存在: {}", sender_id)))?;
+        
+        let message = ChatService::compose(channel, &sender, content, self.state.day, &self.state.phase)?;
+        self.record(EngineEvent::ChannelMessagePosted { message: message.clone() });
+        Ok(message)
+    }
+    
+    /// 获取玩家可见的频道消息
+    pub fn get_channel_messages(&self, viewer_id: &str, channel: &ChatChannel) -> AppResult<Vec<ChannelMessage>> {
+        let viewer = self.find_player(viewer_id)
+            .ok_or_else(|| AppError::NotFound(format!("玩家不存在: {}", viewer_id)))?;
+        
+        self.chat.visible_messages(channel, viewer, &self.state.phase)
+    }
+    
+    /// 本机真人玩家所在的座位（含已出局、暂由AI托管的情况）
+    pub fn local_player(&self) -> Option<&Player> {
+        let seat = self.state.game_config.human_seat?;
+        self.state.players.iter()
+            .chain(self.state.dead_players.iter())
+            .find(|p| p.seat == seat)
+    }
+    
+    /// 预言家本人的查验记录
+    pub fn seer_results(&self, seer_id: &str) -> Vec<SeerCheckResult> {
+        self.seer_results.iter()
+            .filter(|r| r.seer == seer_id)
+            .cloned()
+            .collect()
+    }
+    
+    /// 全部查验记录（含开局金水），只用于赛后统计
+    pub fn all_seer_results(&self) -> &[SeerCheckResult] {
+        &self.seer_results
+    }
+    
+    /// 女巫剩余的药剂，中途成为女巫的玩家两瓶药都在
+    pub fn witch_potions(&self, player_id: &str) -> WitchPotions {
+        self.state.witch_potions.get(player_id).copied().unwrap_or_default()
+    }
+    
+    /// 已结算的各夜结果，包含全部夜间信息
+    pub fn night_results(&self) -> &[NightResult] {
+        &self.night_results
+    }
+
+    /// 今晚狼人的击杀目标（女巫夜里可见）
+    pub fn pending_kill_targets(&self) -> &[String] {
+        self.night.kill_targets()
+    }
+    
+    /// 获取频道中当天的消息（供AI协作使用）
+    pub fn get_channel_messages_for_day(&self, channel: &ChatChannel, day: u32) -> Vec<ChannelMessage> {
+        self.chat.channel_messages(channel)
+            .filter(|m| m.day == day)
+            .cloned()
+            .collect()
+    }
+    
     /// 执行夜晚行动
     pub fn execute_night_action(&mut self, action: NightAction) -> AppResult<()> {
-        match action.action {
-            NightActionType::Kill => {
-                if let Some(target_id) = action.target {
-                    self.eliminate_player(target_id)?;
-                }
+        self.validate_action(&PlayerAction::NightAction(action.clone()))?;
+        if let (NightActionType::Heal, Some(target)) = (&action.action, &action.target) {
+            if !self.night.kill_targets().contains(target) {
+                return Err(AppError::GameLogic("解药只能救今晚被刀的玩家".to_string()));
             }
+        }
+        self.record(EngineEvent::NightActionTaken { action });
+        Ok(())
+    }
+    
+    fn apply_night_action(&mut self, action: NightAction) {
+        self.night_actors.insert(action.player.clone());
+        // 生死在天亮时统一结算，夜间不暴露任何结果；药水、守护记录和查验属于行动者本人，立即生效
+        self.night.collect(action.clone());
+        
+        match action.action {
+            NightActionType::Kill => {}
             NightActionType::Check => {
-                // TODO: 实现预言家查验
                 info!("预言家查验: {:?}", action.target);
+                if let Some(target_id) = action.target {
+                    let is_werewolf = self.find_player(&target_id)
+                        .map(|p| p.faction == Faction::Werewolf)
+                        .unwrap_or(false);
+                    self.seer_results.push(SeerCheckResult {
+                        night: self.state.day,
+                        seer: action.player.clone(),
+                        target: target_id,
+                        is_werewolf,
+                        reference: false,
+                    });
+                }
+                
+                // 首夜双查：第一次查验后仍可再查一次
+                let double_check = self.state.day == 1
+                    && self.state.game_config.night_rules.first_night_seer == FirstNightSeerRule::DoubleCheck;
+                let checks_tonight = self.seer_results.iter()
+                    .filter(|r| r.seer == action.player && r.night == self.state.day && !r.reference)
+                    .count();
+                if double_check && checks_tonight < 2 {
+                    self.night_actors.remove(&action.player);
+                }
             }
             NightActionType::Heal => {
-                // TODO: 实现女巫救人
                 info!("女巫救人: {:?}", action.target);
+                if action.target.is_some() {
+                    self.state.witch_potions.entry(action.player.clone()).or_default().antidote = false;
+                }
             }
             NightActionType::Protect => {
-                // TODO: 实现守卫保护
                 info!("守卫保护: {:?}", action.target);
+                match &action.target {
+                    Some(target) => {
+                        let protection = GuardProtection { night: self.state.day, target: target.clone() };
+                        self.state.guard_protections.insert(action.player.clone(), protection);
+                    }
+                    None => {
+                        self.state.guard_protections.remove(&action.player);
+                    }
+                }
             }
             NightActionType::Poison => {
-                // TODO: 实现女巫毒人
                 info!("女巫毒人: {:?}", action.target);
+                if action.target.is_some() {
+                    self.state.witch_potions.entry(action.player.clone()).or_default().poison = false;
+                }
             }
         }
-        
-        Ok(())
     }
     
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    fn state_json(engine: &GameEngine) -> serde_json::Value {
+        serde_json::to_value(engine.get_state()).unwrap()
+    }
+
+    /// 夜晚不计时的娱乐局配置
+    fn test_config() -> GameConfig {
+        let mut config = AppConfig::default().game;
+        RulePreset::Casual.apply(&mut config);
+        config.night_timing = NightTimingConfig { min_duration: 0, max_padding: 0 };
+        config
+    }
+
+    /// 开局后第一夜无人行动，直接进入第一天讨论
+    fn engine_at_day(config: GameConfig) -> GameEngine {
+        let mut engine = GameEngine::new(config).unwrap();
+        engine.initialize_game().unwrap();
+        engine.start_game().unwrap();
+        engine.next_phase().unwrap();
+        assert_eq!(engine.get_state().phase, GamePhase::DayDiscussion);
+        engine
+    }
+
+    /// 按座位号排列的玩家ID
+    fn ids_by_seat(engine: &GameEngine) -> Vec<String> {
+        let mut players: Vec<&Player> = engine.get_state().players.iter().collect();
+        players.sort_by_key(|p| p.seat);
+        players.into_iter().map(|p| p.id.clone()).collect()
+    }
+
+    fn last_outcome(engine: &GameEngine) -> &VoteOutcome {
+        engine.get_state().vote_outcomes.last().unwrap()
+    }
+
+    fn speech(sender: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            id: utils::generate_id(),
+            sender: sender.to_string(),
+            content: content.to_string(),
+            timestamp: Utc::now(),
+            message_type: MessageType::Human,
+        }
+    }
+
+    #[test]
+    fn test_night_padding_is_replayed_from_the_log() {
+        let mut config = AppConfig::default().game;
+        config.night_timing = NightTimingConfig { min_duration: 10, max_padding: 1000 };
+        let mut engine = GameEngine::new(config).unwrap();
+        engine.initialize_game().unwrap();
+        engine.start_game().unwrap();
+
+        let rebuilt = GameEngine::from_log(engine.event_log().to_vec()).unwrap();
+        assert!(engine.get_state().time_remaining.is_some());
+        assert_eq!(rebuilt.get_state().time_remaining, engine.get_state().time_remaining);
+        assert_eq!(state_json(&rebuilt), state_json(&engine));
+    }
+
+    #[test]
+    fn test_replayed_log_reaches_the_same_state() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        engine.add_chat_message(speech(&ids[0], "我是预言家，昨晚查验了2号")).unwrap();
+        engine.set_vote_intent(ids[0].clone(), ids[1].clone()).unwrap();
+        engine.next_phase().unwrap();
+        engine.vote(ids[0].clone(), Some(ids[1].clone())).unwrap();
+        engine.vote(ids[2].clone(), None).unwrap();
+
+        let rebuilt = GameEngine::from_log(engine.event_log().to_vec()).unwrap();
+        assert_eq!(state_json(&rebuilt), state_json(&engine));
+        assert_eq!(rebuilt.get_claims_board().claims.len(), 1);
+        assert_eq!(state_json(&engine.fork().unwrap()), state_json(&engine));
+    }
+
+    #[test]
+    fn test_undone_event_is_skipped_on_rebuild() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        engine.add_chat_message(speech(&ids[0], "过")).unwrap();
+        engine.next_phase().unwrap();
+        engine.vote(ids[0].clone(), Some(ids[1].clone())).unwrap();
+        engine.vote(ids[2].clone(), Some(ids[1].clone())).unwrap();
+
+        let undone = engine.undo_last_action().unwrap();
+        assert!(matches!(undone, UndoneAction::Vote { ref voter, .. } if voter == &ids[2]));
+        assert_eq!(engine.get_state().votes.len(), 1);
+        assert!(matches!(engine.event_log().last().map(|e| &e.event), Some(EngineEvent::ActionUndone { .. })));
+        // 撤销记录留在日志里，重建时同样跳过被撤销的投票
+        let rebuilt = GameEngine::from_log(engine.event_log().to_vec()).unwrap();
+        assert_eq!(state_json(&rebuilt), state_json(&engine));
+
+        engine.undo_last_action().unwrap();
+        assert!(engine.get_state().votes.is_empty());
+        // 讨论阶段的发言已经随阶段结算生效，不能跨阶段撤销
+        assert!(matches!(
+            engine.undo_last_action(),
+            Err(AppError::InvalidAction(ActionErrorCode::NothingToUndo, _))
+        ));
+    }
+
+    #[test]
+    fn test_speech_cannot_be_undone() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        engine.add_chat_message(speech(&ids[0], "我是预言家")).unwrap();
+
+        assert!(matches!(
+            engine.undo_last_action(),
+            Err(AppError::InvalidAction(ActionErrorCode::NothingToUndo, _))
+        ));
+        assert_eq!(engine.get_speech_history().iter().filter(|s| s.speaker == ids[0]).count(), 1);
+    }
+
+    /// 进入重新投票：0号和1号平票PK
+    fn engine_at_revote(revote_tie: RevoteTiePolicy) -> (GameEngine, Vec<String>) {
+        let mut config = test_config();
+        config.vote_rules.pk_on_tie = true;
+        config.vote_rules.revote_tie = revote_tie;
+        let mut engine = engine_at_day(config);
+        let ids = ids_by_seat(&engine);
+        engine.next_phase().unwrap();
+        engine.vote(ids[0].clone(), Some(ids[1].clone())).unwrap();
+        engine.vote(ids[1].clone(), Some(ids[0].clone())).unwrap();
+        engine.next_phase().unwrap();
+        engine.next_phase().unwrap();
+        assert_eq!(engine.get_state().phase, GamePhase::Voting);
+        (engine, ids)
+    }
+
+    #[test]
+    fn test_tie_goes_to_pk_and_candidates_sit_out_the_revote() {
+        let mut config = test_config();
+        config.vote_rules.pk_on_tie = true;
+        let mut engine = engine_at_day(config);
+        let ids = ids_by_seat(&engine);
+        engine.next_phase().unwrap();
+        engine.vote(ids[0].clone(), Some(ids[1].clone())).unwrap();
+        engine.vote(ids[1].clone(), Some(ids[0].clone())).unwrap();
+        engine.next_phase().unwrap();
+
+        assert_eq!(engine.get_state().phase, GamePhase::Pk);
+        assert_eq!(engine.get_state().pk_candidates, vec![ids[0].clone(), ids[1].clone()]);
+        let outcome = last_outcome(&engine);
+        assert!(outcome.pk);
+        assert_eq!(outcome.eliminated, None);
+        // PK阶段只有平票的玩家发言
+        assert!(engine.validate_action(&PlayerAction::Speech { speaker: ids[0].clone() }).is_ok());
+        assert!(engine.validate_action(&PlayerAction::Speech { speaker: ids[2].clone() }).is_err());
+
+        engine.next_phase().unwrap();
+        assert_eq!(engine.get_state().phase, GamePhase::Voting);
+        // PK的玩家不参与重新投票，其他人只能投给PK的玩家
+        assert!(matches!(
+            engine.vote(ids[0].clone(), Some(ids[1].clone())),
+            Err(AppError::InvalidAction(ActionErrorCode::RoleNotAllowed, _))
+        ));
+        assert!(matches!(
+            engine.vote(ids[2].clone(), Some(ids[3].clone())),
+            Err(AppError::InvalidAction(ActionErrorCode::NotPkCandidate, _))
+        ));
+        engine.vote(ids[2].clone(), Some(ids[1].clone())).unwrap();
+    }
+
+    #[test]
+    fn test_revote_tie_without_elimination() {
+        let (mut engine, ids) = engine_at_revote(RevoteTiePolicy::NoElimination);
+        engine.vote(ids[2].clone(), Some(ids[0].clone())).unwrap();
+        engine.vote(ids[3].clone(), Some(ids[1].clone())).unwrap();
+        engine.next_phase().unwrap();
+
+        let outcome = last_outcome(&engine);
+        assert!(outcome.revote);
+        assert!(!outcome.pk);
+        assert_eq!(outcome.eliminated, None);
+        assert_eq!(outcome.tied.len(), 2);
+        assert_eq!(engine.get_state().phase, GamePhase::Night);
+        assert!(engine.get_state().pk_candidates.is_empty());
+        assert!(engine.get_state().dead_players.is_empty());
+    }
+
+    #[test]
+    fn test_revote_tie_eliminates_one_tied_player_at_random() {
+        let (mut engine, ids) = engine_at_revote(RevoteTiePolicy::Random);
+        engine.vote(ids[2].clone(), Some(ids[0].clone())).unwrap();
+        engine.vote(ids[3].clone(), Some(ids[1].clone())).unwrap();
+        engine.next_phase().unwrap();
+
+        let outcome = last_outcome(&engine);
+        assert!(outcome.revote);
+        let eliminated = outcome.eliminated.clone().unwrap();
+        assert!(eliminated == ids[0] || eliminated == ids[1]);
+        assert!(!engine.is_player_alive(&eliminated));
+        // 抽中的玩家记在日志里，重建后放逐的是同一人
+        let rebuilt = GameEngine::from_log(engine.event_log().to_vec()).unwrap();
+        assert_eq!(last_outcome(&rebuilt).eliminated, Some(eliminated));
+    }
+
+    #[test]
+    fn test_revote_winner_is_eliminated() {
+        let (mut engine, ids) = engine_at_revote(RevoteTiePolicy::NoElimination);
+        engine.vote(ids[2].clone(), Some(ids[1].clone())).unwrap();
+        engine.vote(ids[3].clone(), Some(ids[1].clone())).unwrap();
+        engine.vote(ids[4].clone(), Some(ids[0].clone())).unwrap();
+        engine.next_phase().unwrap();
+
+        assert_eq!(last_outcome(&engine).eliminated, Some(ids[1].clone()));
+        assert!(!engine.is_player_alive(&ids[1]));
+    }
+
+    #[test]
+    fn test_tally_orders_ties_by_seat_regardless_of_submission_order() {
+        let mut config = test_config();
+        config.vote_rules.pk_on_tie = false;
+        let mut engine = engine_at_day(config);
+        let ids = ids_by_seat(&engine);
+        engine.next_phase().unwrap();
+        let mut reversed = engine.fork().unwrap();
+
+        let ballots = [(0, 5), (1, 5), (2, 3), (4, 3), (6, 7)];
+        for (voter, target) in ballots {
+            engine.vote(ids[voter].clone(), Some(ids[target].clone())).unwrap();
+        }
+        for (voter, target) in ballots.iter().rev() {
+            reversed.vote(ids[*voter].clone(), Some(ids[*target].clone())).unwrap();
+        }
+        engine.next_phase().unwrap();
+        reversed.next_phase().unwrap();
+
+        let outcome = last_outcome(&engine);
+        let targets: Vec<&str> = outcome.tally.iter().map(|t| t.target.as_str()).collect();
+        assert_eq!(targets, vec![ids[3].as_str(), ids[5].as_str(), ids[7].as_str()]);
+        assert_eq!(outcome.tally[0].voters, vec![ids[2].clone(), ids[4].clone()]);
+        assert_eq!(outcome.tied, vec![ids[3].clone(), ids[5].clone()]);
+        assert_eq!(outcome.eliminated, None);
+        assert_eq!(
+            serde_json::to_value(outcome).unwrap(),
+            serde_json::to_value(last_outcome(&reversed)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_abstentions_are_recorded_but_not_counted() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        engine.next_phase().unwrap();
+        engine.vote(ids[1].clone(), None).unwrap();
+        engine.vote(ids[0].clone(), None).unwrap();
+        engine.vote(ids[2].clone(), Some(ids[3].clone())).unwrap();
+
+        let abstained: Vec<&VoteRecord> = engine.get_state().votes.iter().filter(|v| v.is_abstention()).collect();
+        assert_eq!(abstained.len(), 2);
+        assert!(abstained.iter().all(|v| v.target == VoteRecord::ABSTAIN));
+
+        engine.next_phase().unwrap();
+        let outcome = last_outcome(&engine);
+        assert_eq!(outcome.tally.len(), 1);
+        assert_eq!(outcome.tally[0].target, ids[3]);
+        assert_eq!(outcome.tally[0].count, 1);
+        assert_eq!(outcome.abstentions, vec![ids[0].clone(), ids[1].clone()]);
+        assert_eq!(outcome.eliminated, Some(ids[3].clone()));
+    }
+
+    #[test]
+    fn test_sheriff_vote_carries_extra_weight() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        assert!(engine.set_sheriff(Some(ids[0].clone())).unwrap());
+        assert!(!engine.set_sheriff(Some(ids[0].clone())).unwrap());
+        engine.next_phase().unwrap();
+        engine.vote(ids[0].clone(), Some(ids[5].clone())).unwrap();
+        engine.vote(ids[1].clone(), Some(ids[3].clone())).unwrap();
+        engine.next_phase().unwrap();
+
+        let outcome = last_outcome(&engine);
+        assert_eq!(outcome.tally[0].target, ids[5]);
+        assert!((outcome.tally[0].weight - 1.5).abs() < 1e-4);
+        assert_eq!(outcome.eliminated, Some(ids[5].clone()));
+        // 警长记在日志里，重建后仍然生效
+        let rebuilt = GameEngine::from_log(engine.event_log().to_vec()).unwrap();
+        assert_eq!(rebuilt.get_state().sheriff, Some(ids[0].clone()));
+    }
+
+    #[test]
+    fn test_sheriff_badge_can_be_passed_or_torn_up() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        engine.set_sheriff(Some(ids[0].clone())).unwrap();
+        engine.set_sheriff(Some(ids[1].clone())).unwrap();
+        assert_eq!(engine.get_state().sheriff, Some(ids[1].clone()));
+        engine.set_sheriff(None).unwrap();
+        assert_eq!(engine.get_state().sheriff, None);
+        assert!(matches!(
+            engine.set_sheriff(Some("nobody".to_string())),
+            Err(AppError::InvalidAction(ActionErrorCode::TargetNotFound, _))
+        ));
+    }
+
+    #[test]
+    fn test_revoked_voter_cannot_vote() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        assert!(engine.revoke_vote(&ids[0]).unwrap());
+        assert!(!engine.revoke_vote(&ids[0]).unwrap());
+        engine.next_phase().unwrap();
+        assert!(matches!(
+            engine.vote(ids[0].clone(), Some(ids[1].clone())),
+            Err(AppError::InvalidAction(ActionErrorCode::RoleNotAllowed, _))
+        ));
+    }
+
+    #[test]
+    fn test_vote_revoked_after_casting_is_listed_without_weight() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        engine.next_phase().unwrap();
+        engine.vote(ids[0].clone(), Some(ids[5].clone())).unwrap();
+        engine.vote(ids[1].clone(), Some(ids[3].clone())).unwrap();
+        engine.vote(ids[2].clone(), Some(ids[3].clone())).unwrap();
+        engine.revoke_vote(&ids[0]).unwrap();
+        engine.revoke_vote(&ids[1]).unwrap();
+        engine.next_phase().unwrap();
+
+        let outcome = last_outcome(&engine);
+        let revoked = outcome.tally.iter().find(|t| t.target == ids[5]).unwrap();
+        assert_eq!(revoked.count, 1);
+        assert_eq!(revoked.weight, 0.0);
+        assert_eq!(revoked.voters, vec![ids[0].clone()]);
+        assert_eq!(outcome.eliminated, Some(ids[3].clone()));
+    }
+
+    #[test]
+    fn test_only_revoked_votes_eliminate_nobody() {
+        let mut engine = engine_at_day(test_config());
+        let ids = ids_by_seat(&engine);
+        engine.next_phase().unwrap();
+        engine.vote(ids[0].clone(), Some(ids[5].clone())).unwrap();
+        engine.revoke_vote(&ids[0]).unwrap();
+        engine.next_phase().unwrap();
+
+        let outcome = last_outcome(&engine);
+        assert_eq!(outcome.tally.len(), 1);
+        assert_eq!(outcome.eliminated, None);
+        assert!(outcome.tied.is_empty());
+    }
+
+    #[test]
+    fn test_vote_rules_default_missing_sheriff_weight() {
+        let rules: VoteRules = serde_json::from_str("{}").unwrap();
+        assert_eq!(rules.sheriff_weight, 1.5);
+        assert!(rules.pk_on_tie);
+    }
+
+    #[test]
+    fn test_mandatory_vote_rejects_abstention() {
+        let mut config = test_config();
+        config.vote_rules.mandatory = true;
+        let mut engine = engine_at_day(config);
+        let ids = ids_by_seat(&engine);
+        engine.next_phase().unwrap();
+        assert!(matches!(
+            engine.vote(ids[0].clone(), None),
+            Err(AppError::InvalidAction(ActionErrorCode::AbstainNotAllowed, _))
+        ));
+    }
+
+    /// 12人局开局，停在第一夜
+    fn engine_at_night() -> GameEngine {
+        engine_at_night_with(test_config())
+    }
+
+    fn engine_at_night_with(mut config: GameConfig) -> GameEngine {
+        config.total_players = 12;
+        let mut engine = GameEngine::new(config).unwrap();
+        engine.initialize_game().unwrap();
+        engine.start_game().unwrap();
+        engine
+    }
+
+    fn players_with(engine: &GameEngine, role: RoleType) -> Vec<String> {
+        let mut players: Vec<&Player> = engine.get_state().players.iter()
+            .filter(|p| p.role.role_type == role)
+            .collect();
+        players.sort_by_key(|p| p.seat);
+        players.into_iter().map(|p| p.id.clone()).collect()
+    }
+
+    /// 白天无人投票，直接进入下一夜
+    fn to_next_night(engine: &mut GameEngine) {
+        for _ in 0..3 {
+            engine.next_phase().unwrap();
+        }
+        assert_eq!(engine.get_state().phase, GamePhase::Night);
+    }
+
+    fn night_action(player: &str, action: NightActionType, target: &str) -> NightAction {
+        NightAction { player: player.to_string(), action, target: Some(target.to_string()) }
+    }
+
+    #[test]
+    fn test_used_potion_is_rejected() {
+        let mut engine = engine_at_night();
+        let witch = players_with(&engine, RoleType::Witch).remove(0);
+        let wolf = players_with(&engine, RoleType::Werewolf).remove(0);
+        let villagers = players_with(&engine, RoleType::Villager);
+
+        engine.execute_night_action(night_action(&wolf, NightActionType::Kill, &villagers[0])).unwrap();
+        engine.execute_night_action(night_action(&witch, NightActionType::Heal, &villagers[0])).unwrap();
+        assert!(!engine.witch_potions(&witch).antidote);
+        to_next_night(&mut engine);
+        assert!(engine.is_player_alive(&villagers[0]));
+
+        engine.execute_night_action(night_action(&wolf, NightActionType::Kill, &villagers[1])).unwrap();
+        assert!(matches!(
+            engine.execute_night_action(night_action(&witch, NightActionType::Heal, &villagers[1])),
+            Err(AppError::InvalidAction(ActionErrorCode::PotionUsed, _))
+        ));
+        engine.execute_night_action(night_action(&witch, NightActionType::Poison, &villagers[2])).unwrap();
+        assert_eq!(engine.witch_potions(&witch), WitchPotions { antidote: false, poison: false });
+        to_next_night(&mut engine);
+        assert!(!engine.is_player_alive(&villagers[1]));
+        assert!(!engine.is_player_alive(&villagers[2]));
+
+        assert!(matches!(
+            engine.execute_night_action(night_action(&witch, NightActionType::Poison, &villagers[3])),
+            Err(AppError::InvalidAction(ActionErrorCode::PotionUsed, _))
+        ));
+        // 不选目标视为不用药，药用完了也可以提交
+        engine.execute_night_action(NightAction { player: witch, action: NightActionType::Poison, target: None }).unwrap();
+    }
+
+    #[test]
+    fn test_guard_cannot_protect_the_same_player_two_nights_running() {
+        let mut engine = engine_at_night();
+        let guard = players_with(&engine, RoleType::Guard).remove(0);
+        let wolf = players_with(&engine, RoleType::Werewolf).remove(0);
+        let villagers = players_with(&engine, RoleType::Villager);
+
+        engine.execute_night_action(night_action(&guard, NightActionType::Protect, &villagers[0])).unwrap();
+        engine.execute_night_action(night_action(&wolf, NightActionType::Kill, &villagers[0])).unwrap();
+        to_next_night(&mut engine);
+        // 被守护的玩家挡住了狼刀
+        assert!(engine.is_player_alive(&villagers[0]));
+
+        assert!(matches!(
+            engine.execute_night_action(night_action(&guard, NightActionType::Protect, &villagers[0])),
+            Err(AppError::InvalidAction(ActionErrorCode::ConsecutiveProtect, _))
+        ));
+        engine.execute_night_action(night_action(&guard, NightActionType::Protect, &villagers[1])).unwrap();
+        to_next_night(&mut engine);
+
+        // 隔一晚可以再守同一人
+        engine.execute_night_action(night_action(&guard, NightActionType::Protect, &villagers[0])).unwrap();
+    }
+
+    #[test]
+    fn test_guard_and_antidote_on_the_same_target() {
+        for conflict_kills in [true, false] {
+            let mut config = test_config();
+            config.night_rules.guard_heal_conflict_kills = conflict_kills;
+            let mut engine = engine_at_night_with(config);
+            let guard = players_with(&engine, RoleType::Guard).remove(0);
+            let witch = players_with(&engine, RoleType::Witch).remove(0);
+            let wolf = players_with(&engine, RoleType::Werewolf).remove(0);
+            let target = players_with(&engine, RoleType::Villager).remove(0);
+
+            engine.execute_night_action(night_action(&guard, NightActionType::Protect, &target)).unwrap();
+            engine.execute_night_action(night_action(&wolf, NightActionType::Kill, &target)).unwrap();
+            engine.execute_night_action(night_action(&witch, NightActionType::Heal, &target)).unwrap();
+            engine.next_phase().unwrap();
+            // 同守同救：开启时反而出局
+            assert_eq!(engine.is_player_alive(&target), !conflict_kills);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::discussion::DiscussionStatus;
+use crate::error::AppResult;
+use crate::game_manager::GameManager;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use log::warn;
+
+/// 后台循环的节拍
+const TICK: Duration = Duration::from_secs(1);
+
+/// 后台循环在本阶段要做的事
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriveStep {
+    /// 没有需要推进的事，或者已有任务在推进
+    Idle,
+    /// 启动本夜的行动流程
+    Night,
+    /// 启动或继续讨论轮
+    Discussion,
+    /// AI投出正式选票
+    Vote,
+    /// 本阶段该做的都做完了，进入下一阶段
+    Advance,
+}
+
+/// 每秒推进阶段计时；对局开启自动驱动时，按阶段启动夜晚流程、讨论轮和AI投票，
+/// 不需要前端逐步调用命令也能把一局打完。真人的行动仍由对应命令提交后继续
+pub async fn run(game_manager: Arc<RwLock<GameManager>>) {
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&game_manager).await {
+            warn!("推进对局失败: {}", e);
+        }
+    }
+}
+
+async fn tick(game_manager: &Arc<RwLock<GameManager>>) -> AppResult<()> {
+    // 投票和推进阶段在同一次持锁内判断并执行，避免与前端的操作重复推进
+    let (step, expired) = {
+        let mut manager = game_manager.write().await;
+        manager.update_timer().await?;
+        // 真人发言超时替其过麦，不论是否开启自动驱动
+        let expired = manager.expire_speech_turn();
+        let step = manager.drive_step();
+        match step {
+            DriveStep::Vote => manager.cast_ai_votes().await?,
+            DriveStep::Advance => manager.proceed_to_next_phase().await?,
+            _ => {}
+        }
+        (step, expired)
+    };
+
+    // 夜晚和讨论轮会在等待期间释放锁，放到单独的任务里跑，不耽误计时；
+    // 已有流程在推进时它们直接返回
+    // 未开启自动驱动的对局也要在超时后让后面的AI接着发言
+    let step = if expired && step == DriveStep::Idle { DriveStep::Discussion } else { step };
+    match step {
+        DriveStep::Night => {
+            let game_manager = game_manager.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = GameManager::run_night_phase(&game_manager).await {
+                    warn!("自动推进夜晚失败: {}", e);
+                }
+            });
+        }
+        DriveStep::Discussion => {
+            let game_manager = game_manager.clone();
+            tauri::async_runtime::spawn(async move {
+                match GameManager::run_discussion_round(&game_manager).await {
+                    // 真人发言期间为后面的AI预生成发言
+                    Ok(DiscussionStatus::AwaitingHuman { .. }) => {
+                        if let Err(e) = GameManager::precompute_ai_turns(&game_manager).await {
+                            warn!("预生成AI发言失败: {}", e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("自动推进讨论失败: {}", e),
+                }
+            });
+        }
+        DriveStep::Idle | DriveStep::Vote | DriveStep::Advance => {}
+    }
+    Ok(())
+}
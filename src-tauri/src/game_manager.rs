@@ -1,34 +1,219 @@
 use crate::error::{AppError, AppResult};
 use crate::types::*;
 use crate::game_engine::GameEngine;
-use crate::llm::LLMManager;
+use crate::chat::{ChatChannel, ChannelMessage};
+use crate::validator::{ActionErrorCode, ActionValidator, PlayerAction};
+use crate::idempotency::IdempotencyGuard;
+use serde::{de::DeserializeOwned, Serialize};
+use crate::cancellation::{self, TaskCanceller};
+use crate::llm_stream::StreamRegistry;
+use crate::session_stats::{SessionStats, SessionTracker};
+use crate::board_generator::{self, BoardDifficulty, ProposedBoard};
+use crate::events::{EventBus, GameEvent, PrivateContent, PrivateInfo, PrivateMessage};
+use crate::narrator::Narrator;
+use crate::narrator_qa::{self, NarratorQuery};
+use crate::output_control;
+use crate::roles;
+use crate::claims::{ClaimSource, ClaimsBoard};
+use crate::capabilities::Capability;
+use crate::speech_service::{Narration, PlaybackItem, SpeechService};
+use crate::reactions::{DramaticMoment, ReactionGenerator};
+use crate::objection::{self, Objection, ObjectionTracker};
+use crate::autopilot::{self, Autopilot};
+use crate::embeddings::{self, EmbeddedSpeech, EmbeddingStore, SimilarStatement};
+use crate::exemplars::{self, Exemplar, ExemplarLibrary, EXEMPLAR_PHASES};
+use crate::content_filter::{self, ContentFilter, FilterMode};
+use crate::context::{self, ContextFocus};
+use crate::coach::{Coach, CoachReport, HumanVote};
+use crate::speculation::{DraftCache, DraftJob};
+use crate::memory::MemoryStats;
+use crate::spectator::Spectator;
+use crate::plugins::{PluginHost, SeatView};
+use crate::fact_sheet::FactSheet;
+use crate::discussion::{CurrentSpeaker, DiscussionRound, DiscussionStatus, DiscussionTurn};
+use crate::game_loop::DriveStep;
+use crate::night::{DawnAnnouncement, NightRound, NightStatus, NightTurn};
+use crate::night_resolution::NightResult;
+use crate::wolf_council::{self, NightVoteResult, WolfCouncil, WolfProposal};
+use crate::replay::{ReplaySystem, AIDecision, DecisionContext, DecisionType, GameEvent as ReplayEvent, GameEventType, GameReplay, IntegrityReport, verify_integrity};
+use crate::database::GameRepository;
+use crate::llm::{LLMManager, TokenMeter};
+use crate::ai::{self, AIAgent, ReasoningEngine, DifficultyProfile, ConsistencyChecker, Inconsistency, StrategyEngine, SuspicionExplanation, VotePrediction};
 use crate::utils;
+use crate::voice;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use log::{info, warn};
 
+/// 投票截止前提醒真人玩家的秒数
+const VOTE_WARNING_SECONDS: u32 = 10;
+/// 真人发言期间最多为几名AI预生成发言
+const SPECULATION_LIMIT: usize = 3;
+/// 提示词中最多列出的查验报告条数（取最近的），大板子后期报告很多，避免挤占上下文
+const PROMPT_CHECK_REPORT_LIMIT: usize = 6;
+/// 挑选提示词上下文时关注的最可疑玩家人数
+const CONTEXT_SUSPECT_COUNT: usize = 3;
+
 /// 游戏管理器
 pub struct GameManager {
     engine: Option<GameEngine>,
     llm_manager: Option<Arc<LLMManager>>,
+    repository: Option<Arc<GameRepository>>,
+    game_id: Option<String>,
+    agents: HashMap<String, AIAgent>,
+    idempotency: IdempotencyGuard,
+    replay: ReplaySystem,
+    events: EventBus,
+    thinking: HashSet<String>,
+    observer: ReasoningEngine,
+    synced_reveals: usize,
+    synced_claims: usize,
+    synced_checks: usize,
+    synced_vote_outcomes: usize,
+    /// 本轮投票揭晓前的预测，揭晓后用来计算每张票的惊讶度
+    vote_predictions: Vec<VotePrediction>,
+    flagged_inconsistencies: HashSet<String>,
+    speech: SpeechService,
+    embedding_store: EmbeddingStore,
+    exemplars: ExemplarLibrary,
+    /// 真人输入过滤，开局时按本局配置编译规则
+    content_filter: ContentFilter,
+    human_votes: Vec<HumanVote>,
+    coach_reports: Vec<CoachReport>,
+    explained_rules: HashSet<String>,
+    objections: ObjectionTracker,
+    /// 挂机检测与托管中的真人座位
+    autopilot: Autopilot,
+    vote_deadline_warned: Option<u32>,
+    /// 后台循环已替AI投过票的轮次：(天数, 此前的计票轮数)
+    ai_votes_cast: Option<(u32, usize)>,
+    drafts: DraftCache,
+    discussion: Option<DiscussionRound>,
+    night: Option<NightRound>,
+    /// 本夜狼队在狼人频道的商量
+    wolf_council: Option<WolfCouncil>,
+    /// 最近一次天亮公告，供主持人答问时重复
+    last_dawn: Option<DawnAnnouncement>,
+    private_messages: Vec<PrivateMessage>,
+    /// 最近一局的赛后事实表，没有数据库时从这里读取
+    last_fact_sheet: Option<FactSheet>,
+    /// 由第三方WASM插件决策的AI座位
+    plugins: PluginHost,
+    /// 进行中AI任务的取消控制，与LLM管理器和应用状态共用
+    tasks: TaskCanceller,
+    /// 流式生成中的AI发言，可凭流ID单独取消
+    streams: StreamRegistry,
+    /// 模型用量计数，与LLM管理器共用
+    tokens: TokenMeter,
+    session: SessionTracker,
     is_running: bool,
 }
 
+/// 等待投递的AI发言，调用方在延迟结束后通过deliver_ai_speech投递
+pub struct PendingSpeech {
+    pub player_id: String,
+    pub content: String,
+    pub delay: Duration,
+    message: Option<ChatMessage>,
+}
+
 impl GameManager {
     /// 创建新的游戏管理器
     pub fn new() -> Self {
         Self {
             engine: None,
             llm_manager: None,
+            repository: None,
+            game_id: None,
+            agents: HashMap::new(),
+            idempotency: IdempotencyGuard::new(),
+            replay: ReplaySystem::new(),
+            events: EventBus::new(),
+            thinking: HashSet::new(),
+            observer: Self::new_observer(),
+            synced_reveals: 0,
+            synced_claims: 0,
+            synced_checks: 0,
+            synced_vote_outcomes: 0,
+            vote_predictions: Vec::new(),
+            flagged_inconsistencies: HashSet::new(),
+            speech: SpeechService::new(),
+            embedding_store: EmbeddingStore::new(),
+            exemplars: ExemplarLibrary::new(),
+            content_filter: ContentFilter::default(),
+            human_votes: Vec::new(),
+            coach_reports: Vec::new(),
+            explained_rules: HashSet::new(),
+            objections: ObjectionTracker::new(),
+            autopilot: Autopilot::new(),
+            vote_deadline_warned: None,
+            ai_votes_cast: None,
+            drafts: DraftCache::new(),
+            discussion: None,
+            night: None,
+            wolf_council: None,
+            last_dawn: None,
+            private_messages: Vec::new(),
+            last_fact_sheet: None,
+            plugins: PluginHost::new(),
+            tasks: TaskCanceller::new(),
+            streams: StreamRegistry::new(),
+            tokens: TokenMeter::new(),
+            session: SessionTracker::new(),
             is_running: false,
         }
     }
     
+    /// AI任务的取消控制，应用状态用它在拿到锁之前取消持锁等待模型的任务
+    pub fn task_canceller(&self) -> TaskCanceller {
+        self.tasks.clone()
+    }
+    
+    /// 模型用量计数，应用状态在重建LLM管理器时接上，更换配置不影响本局统计
+    pub fn token_meter(&self) -> TokenMeter {
+        self.tokens.clone()
+    }
+    
+    /// 流登记表，应用状态用它在拿到锁之前取消某一次流式生成
+    pub fn stream_registry(&self) -> StreamRegistry {
+        self.streams.clone()
+    }
+    
     /// 设置LLM管理器
     pub fn set_llm_manager(&mut self, llm_manager: Arc<LLMManager>) {
         self.llm_manager = Some(llm_manager);
     }
     
+    /// 基于公开信息的旁观推理引擎，用于记录怀疑度变化
+    fn new_observer() -> ReasoningEngine {
+        let mut observer = ReasoningEngine::new();
+        observer.set_profile(DifficultyProfile::expert());
+        observer
+    }
+    
+    /// 订阅游戏事件
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<GameEvent> {
+        self.events.subscribe()
+    }
+    
+    /// 订阅私信
+    pub fn subscribe_private_messages(&self) -> tokio::sync::broadcast::Receiver<PrivateMessage> {
+        self.events.subscribe_private()
+    }
+    
+    /// 设置游戏记录仓库
+    pub fn set_repository(&mut self, repository: Arc<GameRepository>) {
+        self.repository = Some(repository);
+    }
+    
+    /// 设置策略插件目录
+    pub fn set_plugin_dir(&mut self, plugin_dir: std::path::PathBuf) {
+        self.plugins.set_plugin_dir(plugin_dir);
+    }
+    
     /// 创建新游戏
     pub async fn create_game(&mut self, config: GameConfig) -> AppResult<GameState> {
         info!("创建新游戏");
@@ -37,17 +222,138 @@ impl GameManager {
         engine.initialize_game()?;
         
         let state = engine.get_state().clone();
+        // 先落库再替换当前对局：落库失败时不开局，免得之后的记录都挂在数据库里不存在的对局上
+        let game_id = match &self.repository {
+            Some(repository) => repository.create_game(&state).await?,
+            None => utils::generate_id(),
+        };
         self.engine = Some(engine);
+        self.idempotency.clear();
+        self.observer = Self::new_observer();
+        self.observer.initialize(&state);
+        self.synced_reveals = 0;
+        self.synced_claims = 0;
+        self.synced_checks = 0;
+        self.synced_vote_outcomes = 0;
+        self.vote_predictions.clear();
+        self.flagged_inconsistencies.clear();
+        self.speech.reset();
+        self.embedding_store.reset();
+        self.content_filter = ContentFilter::new(&state.game_config.content_filter);
+        self.session.reset(self.tokens.usage());
+        self.human_votes.clear();
+        self.coach_reports.clear();
+        self.explained_rules.clear();
+        self.objections.reset();
+        self.autopilot.reset();
+        self.vote_deadline_warned = None;
+        self.ai_votes_cast = None;
+        self.drafts.clear();
+        self.discussion = None;
+        self.night = None;
+        self.wolf_council = None;
+        self.last_dawn = None;
+        self.private_messages.clear();
+        self.plugins.load_seats(&state.game_config.plugin_seats, &state.players);
+        self.spawn_agents(&state);
         self.is_running = false;
         
+        self.replay.start_recording(game_id.clone(), state.game_config.clone(), state.players.clone())?;
+        self.game_id = Some(game_id);
+        self.load_exemplars(&state).await;
+        
         Ok(state)
     }
     
+    /// 为每个AI座位创建自己的推理和策略状态，难度按本局配置
+    fn spawn_agents(&mut self, state: &GameState) {
+        self.agents.clear();
+        let profile = state.game_config.ai_difficulty.profile();
+        for player in state.players.iter().filter(|p| p.is_ai) {
+            let personality = match &player.personality {
+                Some(personality) => personality.clone(),
+                None => continue,
+            };
+            let mut agent = AIAgent::new(player.id.clone(), personality, player.role.clone(), self.llm_manager.clone());
+            agent.set_difficulty(profile.clone());
+            if let Err(e) = agent.initialize(state) {
+                warn!("初始化AI代理 {} 失败: {}", player.id, e);
+                continue;
+            }
+            self.agents.insert(player.id.clone(), agent);
+        }
+    }
+    
+    /// 按本局出现的身份从历史库载入往局范例发言，数据库不可用时不加范例
+    async fn load_exemplars(&mut self, state: &GameState) {
+        self.exemplars.reset();
+        let config = &state.game_config.exemplars;
+        let repository = match &self.repository {
+            Some(repository) if config.enabled => repository.clone(),
+            _ => return,
+        };
+        
+        let mut roles: Vec<RoleType> = Vec::new();
+        for player in &state.players {
+            if !roles.contains(&player.role.role_type) {
+                roles.push(player.role.role_type.clone());
+            }
+        }
+        for role in roles {
+            for phase in &EXEMPLAR_PHASES {
+                let speeches = match repository.get_exemplar_speeches(&role, phase, config.min_chars, config.pool_size).await {
+                    Ok(speeches) => speeches,
+                    Err(e) => {
+                        warn!("载入范例发言失败: {}", e);
+                        return;
+                    }
+                };
+                for speech in speeches {
+                    let vector = match speech.embedding.as_deref().map(embeddings::decode_vector) {
+                        Some(Ok(vector)) => vector,
+                        _ => continue,
+                    };
+                    self.exemplars.add(Exemplar {
+                        role: role.clone(),
+                        phase: phase.clone(),
+                        day: speech.day.max(0) as u32,
+                        content: speech.content,
+                        vector,
+                    });
+                }
+            }
+        }
+        info!("载入往局范例发言{}条", self.exemplars.memory_stats().entries);
+    }
+    
     /// 开始游戏
     pub async fn start_game(&mut self) -> AppResult<()> {
         if let Some(engine) = &mut self.engine {
             engine.start_game()?;
+            let state = engine.get_state();
+            let phase_event = GameEvent::PhaseChanged { day: state.day, phase: state.phase.clone() };
+            // 开局金水只告知真人预言家本人
+            let gold_water: Vec<SeerCheckResult> = state.players.iter()
+                .filter(|p| !p.is_ai && p.role.role_type == RoleType::Seer)
+                .flat_map(|p| engine.seer_results(&p.id))
+                .filter(|r| r.reference)
+                .collect();
+            // 代理在建局时创建，开局金水要在这里补交给AI预言家，后续查验在夜晚行动时交付
+            for agent in self.agents.values_mut() {
+                for result in engine.seer_results(&agent.player_id) {
+                    agent.process_seer_check(&result);
+                }
+            }
+            self.publish_event(phase_event);
+            for result in gold_water {
+                self.send_private(&result.seer, PrivateContent::SeerCheck {
+                    target: result.target,
+                    is_werewolf: result.is_werewolf,
+                    reference: true,
+                }).await;
+            }
             self.is_running = true;
+            self.session.start();
             info!("游戏已开始");
             Ok(())
         } else {
@@ -57,21 +363,59 @@ impl GameManager {
     
     /// 结束游戏
     pub async fn end_game(&mut self) -> AppResult<()> {
+        self.speech.clear_playback();
         self.engine = None;
+        self.game_id = None;
+        self.agents.clear();
+        self.plugins.clear();
+        self.thinking.clear();
         self.is_running = false;
         info!("游戏已结束");
         Ok(())
     }
     
-    /// 获取游戏状态
+    /// 获取完整游戏状态（上帝视角，仅供调试）
     pub fn get_game_state(&self) -> Option<GameState> {
         self.engine.as_ref().map(|e| e.get_state().clone())
     }
     
-    /// 玩家投票
-    pub async fn player_vote(&mut self, voter_id: String, target_id: String) -> AppResult<()> {
+    /// 本机真人座位视角的游戏状态，他人身份按公开信息裁剪
+    pub fn get_game_view(&self) -> AppResult<Option<GameView>> {
+        let Some(engine) = self.engine.as_ref() else {
+            return Ok(None);
+        };
+        let viewer = engine.local_player()
+            .ok_or_else(|| AppError::GameLogic("本局没有真人座位".to_string()))?;
+        Ok(Some(engine.get_state().view_for(viewer)))
+    }
+    
+    /// 检查幂等键，重复提交返回首次处理时的结果
+    fn replayed_submission<T: DeserializeOwned>(&mut self, idempotency_key: Option<&str>) -> Option<T> {
+        let key = idempotency_key?;
+        let response = self.idempotency.replay(key);
+        if response.is_some() {
+            info!("忽略重复提交: {}", key);
+        }
+        response
+    }
+    
+    fn record_submission<T: Serialize>(&mut self, idempotency_key: Option<&str>, response: &T) {
+        if let Some(key) = idempotency_key {
+            self.idempotency.record(key, response);
+        }
+    }
+    
+    /// 玩家投票，target_id为None表示弃票
+    pub async fn player_vote(&mut self, voter_id: String, target_id: Option<String>, idempotency_key: Option<String>) -> AppResult<()> {
+        if let Some(response) = self.replayed_submission(idempotency_key.as_deref()) {
+            return Ok(response);
+        }
+        
         if let Some(engine) = &mut self.engine {
-            engine.vote(voter_id, target_id)?;
+            engine.vote(voter_id.clone(), target_id.clone())?;
+            self.record_submission(idempotency_key.as_deref(), &());
+            self.autopilot.touch(&voter_id);
+            self.broadcast(GameEvent::VoteCast { voter: voter_id.clone(), target: target_id.clone() });
             
             // 检查是否所有存活玩家都已投票
             if self.all_players_voted() {
@@ -84,12 +428,263 @@ impl GameManager {
         }
     }
     
-    /// 检查所有玩家是否都已投票
+    /// 娱乐局撤销本阶段最近一次投票，撤销记入复盘。
+    ///
+    /// 选票要到计票时才交给推理、写入复盘和数据库，计票前撤销只需回滚引擎状态并通知界面
+    pub fn undo_last_action(&mut self) -> AppResult<UndoneAction> {
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        let action = engine.undo_last_action()?;
+        let state = engine.get_state();
+        let (round, phase) = (state.day, state.phase.clone());
+        
+        if let Some(game_id) = &self.game_id {
+            let (player_id, target_id, content) = match &action {
+                UndoneAction::Vote { voter, target } if target == VoteRecord::ABSTAIN => (voter.clone(), None, "撤销弃票".to_string()),
+                UndoneAction::Vote { voter, target } => (voter.clone(), Some(target.clone()), "撤销投票".to_string()),
+            };
+            let mut metadata = HashMap::new();
+            metadata.insert("action".to_string(), serde_json::to_value(&action)?);
+            
+            let event = ReplayEvent {
+                id: utils::generate_id(),
+                event_type: GameEventType::Undo,
+                timestamp: chrono::Utc::now(),
+                round,
+                phase,
+                player_id: Some(player_id),
+                target_id,
+                content,
+                metadata,
+            };
+            if let Err(e) = self.replay.record_event(game_id, event) {
+                warn!("记录撤销事件失败: {}", e);
+            }
+        }
+        
+        self.publish_event(GameEvent::ActionUndone { action: action.clone() });
+        Ok(action)
+    }
+    
+    /// 对局中切换节奏档位，未开局时什么也不做。计时与朗读队列在同一把锁内一起调整，
+    /// 计时任务不会看到只改了一半的配置
+    pub fn set_pacing_profile(&mut self, profile: PacingProfile) {
+        let engine = match &mut self.engine {
+            Some(engine) => engine,
+            None => return,
+        };
+        engine.set_pacing(profile);
+        let state = engine.get_state();
+        let (voice, narration, time_remaining) = (
+            state.game_config.enable_voice,
+            state.game_config.narrator_voice.enabled,
+            state.time_remaining,
+        );
+        
+        // 关闭语音后不再朗读已经排队的内容
+        if !voice && !narration {
+            self.speech.clear_playback();
+        }
+        self.publish_event(GameEvent::PacingChanged { profile, time_remaining });
+    }
+    
+    /// 主持人设置警长（竞选结果或警徽移交），None表示撕毁警徽
+    pub fn set_sheriff(&mut self, player_id: Option<String>) -> AppResult<()> {
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        if engine.set_sheriff(player_id.clone())? {
+            self.broadcast(GameEvent::SheriffChanged { player_id });
+        }
+        Ok(())
+    }
+    
+    /// 主持人剥夺玩家的投票权，如白痴被放逐时翻牌留场
+    pub fn revoke_vote(&mut self, player_id: String) -> AppResult<()> {
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        if engine.revoke_vote(&player_id)? {
+            self.broadcast(GameEvent::VoteRevoked { player_id });
+        }
+        Ok(())
+    }
+    
+    /// 表达讨论阶段的投票意向
+    pub fn signal_vote_intent(&mut self, voter_id: String, target_id: String, idempotency_key: Option<String>) -> AppResult<()> {
+        if let Some(response) = self.replayed_submission(idempotency_key.as_deref()) {
+            return Ok(response);
+        }
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        if engine.set_vote_intent(voter_id.clone(), target_id.clone())? {
+            self.observer.analyze_vote_intent(voter_id.clone(), target_id.clone())?;
+            self.broadcast(GameEvent::VoteIntentChanged { voter: voter_id, target: target_id });
+        }
+        
+        self.record_submission(idempotency_key.as_deref(), &());
+        Ok(())
+    }
+    
+    /// 玩家正式声明身份
+    pub fn claim_role(&mut self, player_id: String, role: RoleType, idempotency_key: Option<String>) -> AppResult<()> {
+        if let Some(response) = self.replayed_submission(idempotency_key.as_deref()) {
+            return Ok(response);
+        }
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        if engine.claim_role(&player_id, role, ClaimSource::Explicit)? {
+            self.sync_claims()?;
+            self.refresh_ai_vote_intents()?;
+        }
+        
+        self.record_submission(idempotency_key.as_deref(), &());
+        Ok(())
+    }
+    
+    /// 玩家公布查验结果
+    pub fn report_check(&mut self, reporter_id: String, target_id: String, is_werewolf: bool, idempotency_key: Option<String>) -> AppResult<()> {
+        if let Some(response) = self.replayed_submission(idempotency_key.as_deref()) {
+            return Ok(response);
+        }
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        if engine.report_check(&reporter_id, &target_id, is_werewolf, ClaimSource::Explicit)? {
+            self.sync_claims()?;
+            self.refresh_ai_vote_intents()?;
+        }
+        
+        self.record_submission(idempotency_key.as_deref(), &());
+        Ok(())
+    }
+    
+    /// 根据最新推理更新AI的投票意向
+    fn refresh_ai_vote_intents(&mut self) -> AppResult<()> {
+        let plugins = &mut self.plugins;
+        let intents: Vec<(String, String)> = match &self.engine {
+            Some(engine) if engine.get_state().phase == GamePhase::DayDiscussion => {
+                let state = engine.get_state();
+                state.players.iter()
+                    .filter(|p| p.is_alive && p.is_ai && ActionValidator::can_vote(state, p))
+                    .filter_map(|ai| {
+                        Self::ai_vote_choice(plugins, &self.agents, &self.observer, engine, ai)
+                            .map(|target| (ai.id.clone(), target))
+                    })
+                    .collect()
+            }
+            _ => return Ok(()),
+        };
+        
+        for (voter, target) in intents {
+            self.signal_vote_intent(voter, target, None)?;
+        }
+        Ok(())
+    }
+    
+    /// AI想投的人，None表示弃票。插件座位由插件决定，插件失效时交回内置推理；
+    /// 有代理的座位按自己的推理和难度选，否则用旁观推理
+    fn ai_vote_choice(plugins: &mut PluginHost, agents: &HashMap<String, AIAgent>, observer: &ReasoningEngine, engine: &GameEngine, ai: &Player) -> Option<String> {
+        if plugins.controls(&ai.id) {
+            if let Some(target) = plugins.decide_vote(SeatView::new(engine, ai)) {
+                return target;
+            }
+        }
+        // 狼人不会投给认识的队友，重新投票时只能投PK的玩家
+        let state = engine.get_state();
+        let candidates: Vec<String> = state.players.iter()
+            .filter(|p| p.is_alive && p.id != ai.id)
+            .filter(|p| !ai.knows_teammate(p))
+            .filter(|p| state.pk_candidates.is_empty() || state.pk_candidates.contains(&p.id))
+            .map(|p| p.id.clone())
+            .collect();
+        match agents.get(&ai.id) {
+            Some(agent) => agent.choose_vote(&candidates),
+            None => observer.get_most_suspicious_among(&candidates),
+        }
+    }
+    
+    /// 后台循环驱动时AI投出正式选票，规则不允许弃票时随机投；所有人投完后进入下一阶段
+    pub async fn cast_ai_votes(&mut self) -> AppResult<()> {
+        let plugins = &mut self.plugins;
+        let ballots: Vec<(String, Option<String>)> = match &self.engine {
+            Some(engine) if engine.get_state().phase == GamePhase::Voting => {
+                let state = engine.get_state();
+                self.ai_votes_cast = Some((state.day, state.vote_outcomes.len()));
+                state.players.iter()
+                    .filter(|p| p.is_alive && p.is_ai && ActionValidator::can_vote(state, p))
+                    .filter(|p| !state.votes.iter().any(|v| v.voter == p.id))
+                    .map(|ai| {
+                        let target = Self::ai_vote_choice(plugins, &self.agents, &self.observer, engine, ai)
+                            .or_else(|| {
+                                let mandatory = state.game_config.vote_rules.mandatory;
+                                mandatory.then(|| Self::auto_vote_target(state, &ai.id, AutoVotePolicy::Random)).flatten()
+                            });
+                        (ai.id.clone(), target)
+                    })
+                    .collect()
+            }
+            _ => return Ok(()),
+        };
+        
+        for (voter, target) in ballots {
+            let engine = match &mut self.engine {
+                Some(engine) => engine,
+                None => break,
+            };
+            if let Err(e) = engine.vote(voter.clone(), target.clone()) {
+                warn!("AI {} 的投票被拒绝: {}", voter, e);
+                continue;
+            }
+            self.broadcast(GameEvent::VoteCast { voter, target });
+        }
+        
+        if self.all_players_voted() {
+            self.proceed_to_next_phase().await?;
+        }
+        Ok(())
+    }
+    
+    /// 后台循环在当前阶段要做的事，对局没有开启自动驱动时什么都不做
+    pub fn drive_step(&self) -> DriveStep {
+        let state = match &self.engine {
+            Some(engine) if engine.get_state().game_config.auto_drive => engine.get_state(),
+            _ => return DriveStep::Idle,
+        };
+        
+        match state.phase {
+            GamePhase::Night => match &self.night {
+                Some(round) if round.day == state.day && (round.running || round.awaiting.is_some()) => DriveStep::Idle,
+                _ => DriveStep::Night,
+            },
+            GamePhase::DayDiscussion | GamePhase::Pk => match &self.discussion {
+                Some(round) if round.day == state.day && round.phase == state.phase => {
+                    if round.running || round.awaiting.is_some() {
+                        DriveStep::Idle
+                    } else if round.is_finished() {
+                        DriveStep::Advance
+                    } else {
+                        DriveStep::Discussion
+                    }
+                }
+                _ => DriveStep::Discussion,
+            },
+            // 每轮投票（含PK后的重新投票）AI只投一次，被拒绝的票不反复重试
+            GamePhase::Voting if self.ai_votes_cast != Some((state.day, state.vote_outcomes.len())) => DriveStep::Vote,
+            _ => DriveStep::Idle,
+        }
+    }
+    
+    /// 检查所有有投票权的玩家是否都已投票（PK的玩家不参与重新投票）
     fn all_players_voted(&self) -> bool {
         if let Some(engine) = &self.engine {
             let state = engine.get_state();
-            let alive_players = state.players.iter().filter(|p| p.is_alive).count();
-            state.votes.len() >= alive_players
+            let voters = state.players.iter()
+                .filter(|p| p.is_alive && ActionValidator::can_vote(state, p))
+                .count();
+            state.votes.len() >= voters
         } else {
             false
         }
@@ -97,273 +692,3090 @@ impl GameManager {
     
     /// 进入下一阶段
     pub async fn proceed_to_next_phase(&mut self) -> AppResult<()> {
+        // 上一阶段还没完成的预生成、思考延迟等结果已经过时
+        self.tasks.cancel_all("阶段切换");
+        // 投票结算后记录会被清空，先留存真人玩家的票供教练复盘
+        self.capture_human_votes();
+        
         if let Some(engine) = &mut self.engine {
+            let day_before = engine.get_state().day;
             engine.next_phase()?;
+            let state = engine.get_state();
+            let day_ended = day_before > 0 && (state.day > day_before || state.phase == GamePhase::GameOver);
+            let game_over = state.phase == GamePhase::GameOver;
+            let phase_event = GameEvent::PhaseChanged { day: state.day, phase: state.phase.clone() };
+            self.publish_event(phase_event);
+            self.session.phase_completed(game_over);
+            if !game_over {
+                self.check_absence();
+            }
             
-            // 如果进入新的夜晚，执行AI夜晚行动
-            if engine.get_state().phase == GamePhase::Night {
-                self.execute_night_actions().await?;
+            if day_ended {
+                self.record_suspicion_snapshot(day_before).await;
             }
             
+            self.sync_vote_outcomes().await;
+            self.predict_votes();
+            let revealed_before = self.synced_reveals;
+            self.sync_death_reveals();
+            if self.synced_reveals > revealed_before && !game_over {
+                self.spectator_comments().await;
+            }
+            // 新公开的身份可能让之前的查验声明露出破绽
+            self.check_claim_consistency()?;
+            
+            if day_ended {
+                self.record_coach_feedback(Some(day_before)).await;
+            }
+            if game_over {
+                self.record_coach_feedback(None).await;
+                self.finalize_game().await;
+            }
+            self.record_personality_drift();
+            self.replay.flush_spilled(self.repository.as_deref()).await;
             Ok(())
         } else {
             Err(AppError::GameLogic("游戏未开始".to_string()))
         }
     }
     
-    /// 执行夜晚行动
-    async fn execute_night_actions(&mut self) -> AppResult<()> {
-        // 获取所有存活的AI玩家
-        let ai_players: Vec<Player> = if let Some(engine) = &self.engine {
-            let state = engine.get_state();
-            state.players.iter()
-                .filter(|p| p.is_alive && p.is_ai && p.role.has_night_action)
-                .cloned()
-                .collect()
-        } else {
-            return Ok(());
+    /// 按角色顺序推进夜晚：AI依次行动，轮到真人时暂停并提示，真人提交后由调用方再次推进。
+    /// 所有行动收集完后等夜晚计时走完再统一结算，返回天亮公告
+    pub async fn run_night_phase(game_manager: &RwLock<GameManager>) -> AppResult<NightStatus> {
+        let token = {
+            let mut manager = game_manager.write().await;
+            if let Some(status) = manager.begin_night()? {
+                return Ok(status);
+            }
+            manager.tasks.token()
         };
         
-        // 为每个AI生成夜晚行动
-        let mut actions = Vec::new();
-        for player in ai_players {
-            if let Some(action) = self.generate_ai_night_action(&player).await? {
-                actions.push(action);
+        loop {
+            let mut manager = game_manager.write().await;
+            if token.is_cancelled() {
+                manager.stop_night();
+                return Ok(NightStatus::Interrupted);
+            }
+            match manager.next_night_turn().await {
+                NightTurn::Ai(player) => {
+                    if let Err(e) = manager.run_ai_night_action(&player).await {
+                        manager.stop_night();
+                        return Err(e);
+                    }
+                    if let Some(round) = &mut manager.night {
+                        round.advance();
+                    }
+                }
+                NightTurn::Done => break,
+                NightTurn::Stop(status) => return Ok(status),
             }
         }
         
-        // 执行所有夜晚行动
-        if let Some(engine) = &mut self.engine {
-            for action in actions {
-                engine.execute_night_action(action)?;
-            }
+        // 夜晚必须走完统一时长，避免通过夜晚长短推断身份；等待期间不持有锁
+        let wait = game_manager.read().await.engine.as_ref()
+            .map(|engine| engine.phase_time_left())
+            .unwrap_or(Duration::ZERO);
+        if !wait.is_zero() {
+            cancellation::sleep_cancellable(&token, wait).await;
         }
         
-        Ok(())
+        let mut manager = game_manager.write().await;
+        if token.is_cancelled() {
+            manager.stop_night();
+            return Ok(NightStatus::Interrupted);
+        }
+        let result = manager.announce_dawn().await;
+        manager.stop_night();
+        result.map(|announcement| NightStatus::Dawn { announcement })
     }
     
-    /// 生成AI夜晚行动
-    async fn generate_ai_night_action(&self, player: &Player) -> AppResult<Option<NightAction>> {
-        if let Some(llm_manager) = &self.llm_manager {
-            let prompt = self.build_night_action_prompt(player)?;
-            
-            match llm_manager.generate_with_fallback(prompt).await {
-                Ok(response) => {
-                    // TODO: 解析LLM响应生成夜晚行动
-                    // 这里简化处理，实际应该解析JSON响应
-                    self.parse_night_action_response(player, response.as_str())
-                }
-                Err(e) => {
-                    warn!("AI夜晚行动生成失败: {}", e);
-                    Ok(None)
-                }
-            }
-        } else {
-            // 如果没有LLM，使用简单的随机逻辑
-            Ok(self.generate_simple_night_action(player))
+    /// 开始或继续本夜流程，不需要推进时直接返回当前状态
+    fn begin_night(&mut self) -> AppResult<Option<NightStatus>> {
+        let state = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?
+            .get_state();
+        if state.phase != GamePhase::Night {
+            return Err(AppError::InvalidAction(ActionErrorCode::WrongPhase, "当前不是夜晚".to_string()));
+        }
+        
+        if !matches!(&self.night, Some(round) if round.day == state.day) {
+            self.night = None;
         }
+        let round = self.night.get_or_insert_with(|| NightRound::new(state));
+        if round.running {
+            return Ok(Some(NightStatus::InProgress));
+        }
+        if let Some(player_id) = &round.awaiting {
+            return Ok(Some(NightStatus::AwaitingHuman { player_id: player_id.clone() }));
+        }
+        round.running = true;
+        Ok(None)
     }
     
-    /// 构建夜晚行动提示词
-    fn build_night_action_prompt(&self, player: &Player) -> AppResult<String> {
-        if let Some(engine) = &self.engine {
-            let state = engine.get_state();
-            
-            let prompt = match player.role.role_type {
-                RoleType::Werewolf => {
-                    format!(
-                        "你是狼人{}，现在是第{}夜。存活的玩家有：{}。请选择一个目标杀死。返回JSON格式：{{\"action\":\"kill\",\"target\":\"player_id\"}}",
-                        player.name,
-                        state.day,
-                        self.format_alive_players(state)
-                    )
-                }
-                RoleType::Seer => {
-                    format!(
-                        "你是预言家{}，现在是第{}夜。存活的玩家有：{}。请选择一个目标查验。返回JSON格式：{{\"action\":\"check\",\"target\":\"player_id\"}}",
-                        player.name,
-                        state.day,
-                        self.format_alive_players(state)
-                    )
-                }
-                RoleType::Witch => {
-                    format!(
-                        "你是女巫{}，现在是第{}夜。你可以选择救人或毒人。返回JSON格式：{{\"action\":\"heal/poison\",\"target\":\"player_id\"}}",
-                        player.name,
-                        state.day
-                    )
-                }
-                RoleType::Guard => {
-                    format!(
-                        "你是守卫{}，现在是第{}夜。存活的玩家有：{}。请选择一个目标保护。返回JSON格式：{{\"action\":\"protect\",\"target\":\"player_id\"}}",
-                        player.name,
-                        state.day,
-                        self.format_alive_players(state)
-                    )
-                }
-                _ => return Err(AppError::GameLogic("无效的夜晚行动角色".to_string())),
+    /// 找出下一位行动者；轮到真人时推送行动提示并停止推进
+    async fn next_night_turn(&mut self) -> NightTurn {
+        let (day, role, player) = {
+            let (engine, round) = match (&self.engine, &mut self.night) {
+                (Some(engine), Some(round)) => (engine, round),
+                _ => return NightTurn::Done,
             };
+            let state = engine.get_state();
+            if state.phase != GamePhase::Night || state.day != round.day {
+                round.running = false;
+                return NightTurn::Done;
+            }
             
-            Ok(prompt)
+            let day = round.day;
+            match round.next_step(state) {
+                Some((step, player)) => (day, step.role_type.clone(), player.clone()),
+                None => return NightTurn::Done,
+            }
+        };
+        
+        // 狼队出刀前先在狼人频道商量，真人狼能看到AI队友的意见再决定
+        if player.role.faction == Faction::Werewolf {
+            self.hold_wolf_council(day).await;
+        }
+        if player.is_ai || self.autopilot.is_active(&player.id) {
+            return NightTurn::Ai(Box::new(player));
+        }
+        
+        let (engine, round) = match (&self.engine, &mut self.night) {
+            (Some(engine), Some(round)) => (engine, round),
+            _ => return NightTurn::Done,
+        };
+        round.running = false;
+        round.awaiting = Some(player.id.clone());
+        let pending_kills = if role == RoleType::Witch {
+            Some(engine.pending_kill_targets().to_vec())
         } else {
-            Err(AppError::GameLogic("游戏引擎未初始化".to_string()))
+            None
+        };
+        self.events.publish(GameEvent::NightActionPrompt {
+            player_id: player.id.clone(),
+            role,
+        });
+        if let Some(targets) = pending_kills {
+            self.send_private(&player.id, PrivateContent::NightKillTargets { targets }).await;
         }
+        NightTurn::Stop(NightStatus::AwaitingHuman { player_id: player.id })
     }
     
-    /// 格式化存活玩家列表
-    fn format_alive_players(&self, state: &GameState) -> String {
-        state.players.iter()
-            .filter(|p| p.is_alive)
-            .map(|p| format!("{}({})", p.name, p.id))
-            .collect::<Vec<_>>()
-            .join(", ")
+    /// AI决定并执行夜晚行动，非法行动会被校验器拒绝并跳过
+    async fn run_ai_night_action(&mut self, player: &Player) -> AppResult<()> {
+        // 托管不替真人使用技能，免得误伤队友或浪费药
+        if self.autopilot.is_active(&player.id) {
+            self.record_autopilot_event(&player.id, "托管跳过夜晚行动".to_string(), None);
+            return Ok(());
+        }
+        let (action, decision) = self.generate_ai_night_action(player).await?;
+        self.record_ai_decision(decision).await;
+        
+        let mut actions: Vec<NightAction> = action.map(|action| self.apply_wolf_vote(action)).into_iter().collect();
+        self.apply_kill_heat_veto(&mut actions);
+        for action in actions {
+            if let Err(e) = self.execute_night_action(action).await {
+                warn!("AI夜晚行动被拒绝: {}", e);
+            }
+        }
+        Ok(())
     }
     
-    /// 解析夜晚行动响应
-    fn parse_night_action_response(&self, player: &Player, response: &str) -> AppResult<Option<NightAction>> {
-        // 简化的JSON解析
-        if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(response) {
-            if let (Some(action_str), target) = (
-                json_value.get("action").and_then(|v| v.as_str()),
-                json_value.get("target").and_then(|v| v.as_str())
-            ) {
-                let action_type = match action_str {
-                    "kill" => NightActionType::Kill,
-                    "check" => NightActionType::Check,
-                    "heal" => NightActionType::Heal,
-                    "protect" => NightActionType::Protect,
-                    "poison" => NightActionType::Poison,
-                    _ => return Ok(None),
-                };
-                
-                return Ok(Some(NightAction {
-                    player: player.id.clone(),
-                    action: action_type,
-                    target: target.map(|s| s.to_string()),
-                }));
+    /// 本夜的狼队商量，换了一夜就重新开始
+    fn wolf_council_for(&mut self, day: u32) -> &mut WolfCouncil {
+        if !matches!(&self.wolf_council, Some(council) if council.day == day) {
+            self.wolf_council = None;
+        }
+        self.wolf_council.get_or_insert_with(|| WolfCouncil::new(day))
+    }
+    
+    /// 存活的AI狼依次在狼人频道表态并提名刀口，每夜只商量一次；只剩一名狼时不用投票
+    async fn hold_wolf_council(&mut self, day: u32) {
+        let council = self.wolf_council_for(day);
+        if council.held {
+            return;
+        }
+        council.held = true;
+        
+        let wolves: Vec<Player> = match &self.engine {
+            Some(engine) => {
+                let state = engine.get_state();
+                let mut wolves: Vec<Player> = state.players.iter()
+                    .filter(|p| p.is_alive && p.role.role_type.sees_wolf_team())
+                    .cloned()
+                    .collect();
+                wolves.sort_by_key(|p| p.seat);
+                wolves
             }
+            None => return,
+        };
+        if wolves.len() < 2 {
+            return;
         }
         
-        Ok(None)
+        for wolf in wolves.iter().filter(|p| p.is_ai) {
+            let proposal = match self.generate_wolf_proposal(wolf).await {
+                Some(proposal) => proposal,
+                None => continue,
+            };
+            match self.post_chat_message(&wolf.id, ChatChannel::Werewolf, proposal.message).await {
+                Ok(message) => {
+                    self.wolf_council_for(day).propose(&wolf.id, &proposal.target, proposal.confidence);
+                    self.record_wolf_nomination(&wolf.id, &proposal.target, proposal.confidence);
+                    self.relay_wolf_chat(&message, Some(proposal.target)).await;
+                }
+                Err(e) => warn!("AI狼 {} 在狼人频道发言失败: {}", wolf.id, e),
+            }
+        }
     }
     
-    /// 生成简单的夜晚行动（备用逻辑）
-    fn generate_simple_night_action(&self, player: &Player) -> Option<NightAction> {
-        if let Some(engine) = &self.engine {
-            let state = engine.get_state();
-            let alive_players: Vec<_> = state.players.iter()
-                .filter(|p| p.is_alive && p.id != player.id)
-                .collect();
+    /// AI狼的提议；模型不可用或给出无效目标时跟随已有的多数意见，没有就提公开信任度最高的人
+    async fn generate_wolf_proposal(&self, wolf: &Player) -> Option<WolfProposal> {
+        let engine = self.engine.as_ref()?;
+        let state = engine.get_state();
+        let is_candidate = |target: &str| state.players.iter()
+            .any(|p| p.id == target && p.is_alive && p.id != wolf.id && !wolf.knows_teammate(p));
+        
+        if let Some(llm_manager) = &self.llm_manager {
+            let chat = engine.get_channel_messages_for_day(&ChatChannel::Werewolf, state.day);
+            let prompt = wolf_council::council_prompt(wolf, state, &chat);
+            match llm_manager.generate_with_params(prompt, Self::seat_generation(state, wolf).params()).await {
+                Ok((response, _)) => {
+                    if let Some(proposal) = wolf_council::parse_proposal(&response).filter(|p| is_candidate(&p.target)) {
+                        return Some(proposal);
+                    }
+                    warn!("AI狼 {} 的刀口提议无效: {}", wolf.id, response);
+                }
+                Err(e) => warn!("生成狼人频道发言失败: {}", e),
+            }
+        }
+        
+        let target = self.wolf_council.as_ref()
+            .and_then(|council| council.resolve(state, state.game_config.night_vote))
+            .map(|result| result.target)
+            .filter(|target| is_candidate(target))
+            .or_else(|| state.players.iter()
+                .filter(|p| is_candidate(&p.id))
+                .max_by(|a, b| self.observer.get_trust_score(&a.id).total_cmp(&self.observer.get_trust_score(&b.id)))
+                .map(|p| p.id.clone()))?;
+        let seat = state.players.iter().find(|p| p.id == target)?.seat;
+        Some(WolfProposal {
+            target,
+            message: format!("我想刀{}号。", seat),
+            confidence: wolf_council::DEFAULT_NOMINATION_CONFIDENCE,
+        })
+    }
+    
+    /// 代表狼队出刀的AI按投票结果出刀，结果记入复盘；真人狼代表狼队时由真人决定
+    fn apply_wolf_vote(&mut self, mut action: NightAction) -> NightAction {
+        if !matches!(action.action, NightActionType::Kill) {
+            return action;
+        }
+        let result = match (&self.engine, &self.wolf_council) {
+            (Some(engine), Some(council)) if council.day == engine.get_state().day => {
+                let state = engine.get_state();
+                council.resolve(state, state.game_config.night_vote)
+            }
+            _ => None,
+        };
+        if let Some(result) = result {
+            if action.target.as_ref() != Some(&result.target) {
+                info!("狼队投票的刀口为{}，{}改刀", result.target, action.player);
+            }
+            action.target = Some(result.target.clone());
+            self.record_night_vote_result(result);
+        }
+        action
+    }
+    
+    /// 把一名狼人的提名记入复盘
+    fn record_wolf_nomination(&mut self, wolf_id: &str, target: &str, confidence: f32) {
+        let content = match &self.engine {
+            Some(engine) => {
+                let name_of = |id: &str| engine.find_player(id).map(|p| p.display_name()).unwrap_or_else(|| id.to_string());
+                format!("{}提名刀{}（信心{:.2}）", name_of(wolf_id), name_of(target), confidence)
+            }
+            None => return,
+        };
+        let mut metadata = HashMap::new();
+        metadata.insert("confidence".to_string(), serde_json::json!(confidence));
+        self.record_night_vote_event(Some(wolf_id.to_string()), Some(target.to_string()), content, metadata);
+    }
+    
+    /// 把本夜的计票结果记入复盘，每夜只记一次
+    fn record_night_vote_result(&mut self, result: NightVoteResult) {
+        match &mut self.wolf_council {
+            Some(council) if !council.recorded => council.recorded = true,
+            _ => return,
+        }
+        let leader_name = result.leader.as_ref()
+            .and_then(|leader| self.engine.as_ref()?.find_player(leader))
+            .map(|p| p.display_name());
+        let content = match leader_name {
+            Some(leader) => format!("第{}夜狼队投票平票，由狼王{}拍板", result.day, leader),
+            None => format!("第{}夜狼队投票结果", result.day),
+        };
+        let mut metadata = HashMap::new();
+        metadata.insert("night_vote".to_string(), serde_json::json!(result));
+        self.record_night_vote_event(result.leader.clone(), Some(result.target.clone()), content, metadata);
+    }
+    
+    fn record_night_vote_event(&mut self, player_id: Option<String>, target_id: Option<String>, content: String, metadata: HashMap<String, serde_json::Value>) {
+        let (game_id, round) = match (&self.game_id, &self.engine) {
+            (Some(game_id), Some(engine)) => (game_id.clone(), engine.get_state().day),
+            _ => return,
+        };
+        let event = ReplayEvent {
+            id: utils::generate_id(),
+            event_type: GameEventType::NightVote,
+            timestamp: chrono::Utc::now(),
+            round,
+            phase: GamePhase::Night,
+            player_id,
+            target_id,
+            content,
+            metadata,
+        };
+        if let Err(e) = self.replay.record_event(&game_id, event) {
+            warn!("记录狼人投票失败: {}", e);
+        }
+    }
+    
+    /// 真人狼在狼人频道发言，可以同时提名今晚的刀口；真人的提名按满信心计
+    pub async fn werewolf_chat(&mut self, content: String, target: Option<String>, idempotency_key: Option<String>) -> AppResult<ChannelMessage> {
+        if let Some(message) = self.replayed_submission(idempotency_key.as_deref()) {
+            return Ok(message);
+        }
+        let player_id = &self.local_player_id()?;
+        let day = {
+            let state = self.engine.as_ref()
+                .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?
+                .get_state();
+            if let Some(target) = &target {
+                match state.players.iter().find(|p| &p.id == target) {
+                    Some(p) if p.is_alive => {}
+                    Some(_) => return Err(AppError::InvalidAction(ActionErrorCode::TargetDead, "目标已经出局".to_string())),
+                    None => return Err(AppError::InvalidAction(ActionErrorCode::TargetNotFound, "目标不存在".to_string())),
+                }
+            }
+            state.day
+        };
+        
+        let content = self.filter_human_input(player_id, content)?;
+        let message = self.post_chat_message(player_id, ChatChannel::Werewolf, content).await?;
+        self.autopilot.touch(player_id);
+        if let Some(target) = &target {
+            self.wolf_council_for(day).propose(player_id, target, 1.0);
+            self.record_wolf_nomination(player_id, target, 1.0);
+        }
+        self.relay_wolf_chat(&message, target).await;
+        self.record_submission(idempotency_key.as_deref(), &message);
+        Ok(message)
+    }
+    
+    /// 狼人频道的消息通过私信推给其他存活的真人狼，别的玩家收不到
+    async fn relay_wolf_chat(&mut self, message: &ChannelMessage, target: Option<String>) {
+        let recipients: Vec<String> = match &self.engine {
+            Some(engine) => engine.get_state().players.iter()
+                .filter(|p| p.is_alive && !p.is_ai && p.id != message.sender && p.role.role_type.sees_wolf_team())
+                .map(|p| p.id.clone())
+                .collect(),
+            None => return,
+        };
+        for player_id in recipients {
+            self.send_private(&player_id, PrivateContent::WolfChat {
+                sender: message.sender.clone(),
+                content: message.content.clone(),
+                target: target.clone(),
+            }).await;
+        }
+    }
+    
+    /// 真人提交夜晚行动，查验结果通过私信发给预言家本人，返回夜晚流程是否应该继续推进。
+    /// 重复提交不再推进，避免后台同时跑两个夜晚流程
+    pub async fn submit_night_action(&mut self, action: NightAction, idempotency_key: Option<String>) -> AppResult<bool> {
+        if self.replayed_submission::<()>(idempotency_key.as_deref()).is_some() {
+            return Ok(false);
+        }
+        // 夜晚流程推进中只接受正在等待的真人的行动，不能抢在行动顺序之前
+        let day = self.engine.as_ref().map(|e| e.get_state().day);
+        if let Some(round) = self.night.as_ref().filter(|round| Some(round.day) == day) {
+            if round.awaiting.as_deref() != Some(action.player.as_str()) {
+                return Err(AppError::InvalidAction(ActionErrorCode::NotYourTurn, "还没有轮到该玩家行动".to_string()));
+            }
+        }
+        let player_id = action.player.clone();
+        let is_check = matches!(action.action, NightActionType::Check);
+        self.execute_night_action(action).await?;
+        self.record_submission(idempotency_key.as_deref(), &());
+        self.autopilot.touch(&player_id);
+        
+        let check_result = match (&self.engine, is_check) {
+            (Some(engine), true) => engine.seer_results(&player_id).pop(),
+            _ => None,
+        };
+        if let Some(result) = check_result {
+            self.send_private(&player_id, PrivateContent::SeerCheck {
+                target: result.target,
+                is_werewolf: result.is_werewolf,
+                reference: false,
+            }).await;
+        }
+        let resume = match &mut self.night {
+            Some(round) if round.awaiting.as_deref() == Some(player_id.as_str()) => {
+                round.awaiting = None;
+                round.advance();
+                true
+            }
+            _ => false,
+        };
+        Ok(resume)
+    }
+    
+    /// 发送私信：先落库再推送，前端错过推送时可以重新拉取
+    async fn send_private(&mut self, player_id: &str, content: PrivateContent) {
+        let day = self.engine.as_ref().map(|e| e.get_state().day).unwrap_or(0);
+        let message = PrivateMessage {
+            id: utils::generate_id(),
+            player_id: player_id.to_string(),
+            day,
+            content,
+            timestamp: chrono::Utc::now(),
+        };
+        
+        if let (Some(repository), Some(game_id)) = (&self.repository, &self.game_id) {
+            if let Err(e) = repository.record_private_message(game_id, &message).await {
+                warn!("保存私信失败: {}", e);
+            }
+        }
+        self.private_messages.push(message.clone());
+        self.events.publish_private(message);
+    }
+    
+    /// 真人玩家自己掌握的私密信息：身份、狼队友、查验结果、女巫的药
+    pub fn get_private_info(&self, player_id: &str) -> AppResult<PrivateInfo> {
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        let player = engine.find_player(player_id)
+            .ok_or_else(|| AppError::NotFound(format!("玩家{}不存在", player_id)))?;
+        if player.is_ai {
+            return Err(AppError::PermissionDenied("AI座位的私密信息不对外提供".to_string()));
+        }
+        
+        let state = engine.get_state();
+        let is_witch = player.role.role_type == RoleType::Witch;
+        Ok(PrivateInfo {
+            player_id: player.id.clone(),
+            role: player.role.role_type.clone(),
+            teammates: state.players.iter()
+                .chain(state.dead_players.iter())
+                .filter(|p| player.knows_teammate(p))
+                .map(|p| p.id.clone())
+                .collect(),
+            checks: engine.seer_results(&player.id),
+            potions: is_witch.then(|| engine.witch_potions(&player.id)),
+            pending_kills: if is_witch && state.phase == GamePhase::Night {
+                engine.pending_kill_targets().to_vec()
+            } else {
+                Vec::new()
+            },
+        })
+    }
+    
+    /// 获取某位玩家本局收到的私信，优先读取数据库
+    pub async fn get_private_messages(&self, player_id: &str) -> AppResult<Vec<PrivateMessage>> {
+        if let (Some(repository), Some(game_id)) = (&self.repository, &self.game_id) {
+            let records = repository.get_private_messages(game_id, player_id).await?;
+            return records.into_iter()
+                .map(|record| -> AppResult<PrivateMessage> {
+                    Ok(PrivateMessage {
+                        id: record.id,
+                        player_id: record.player_id,
+                        day: record.day as u32,
+                        content: serde_json::from_str(&record.content)?,
+                        timestamp: record.timestamp,
+                    })
+                })
+                .collect();
+        }
+        
+        Ok(self.private_messages.iter()
+            .filter(|m| m.player_id == player_id)
+            .cloned()
+            .collect())
+    }
+    
+    /// 执行一条夜晚行动并持久化，只有通过校验的行动才会落库
+    async fn execute_night_action(&mut self, action: NightAction) -> AppResult<()> {
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        engine.execute_night_action(action.clone())?;
+        let night = engine.get_state().day;
+        
+        // 查验结果只交给预言家本人的AI代理，每个AI座位建局时都有代理
+        if matches!(action.action, NightActionType::Check) {
+            if let (Some(result), Some(agent)) = (engine.seer_results(&action.player).pop(), self.agents.get_mut(&action.player)) {
+                agent.process_seer_check(&result);
+            }
+        }
+        
+        if let (Some(repository), Some(game_id)) = (&self.repository, &self.game_id) {
+            if let Err(e) = repository.record_night_action(game_id, &action, night, None).await {
+                warn!("保存夜晚行动失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+    
+    /// 结算夜晚并进入白天，汇总本次天亮公示的死亡
+    async fn announce_dawn(&mut self) -> AppResult<DawnAnnouncement> {
+        let revealed_before = self.engine.as_ref()
+            .map(|engine| engine.get_state().death_reveals.len())
+            .unwrap_or(0);
+        self.proceed_to_next_phase().await?;
+        
+        let state = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?
+            .get_state();
+        let deaths = state.death_reveals[revealed_before.min(state.death_reveals.len())..].to_vec();
+        let announcement = DawnAnnouncement {
+            day: state.day,
+            peaceful: deaths.is_empty(),
+            deaths,
+        };
+        if let Some(result) = self.engine.as_ref().and_then(|engine| engine.night_results().last().cloned()) {
+            info!("第{}夜结算: 出局{:?}，救下{:?}", result.day, result.deaths, result.saved);
+            self.record_night_result(result);
+        }
+        self.last_dawn = Some(announcement.clone());
+        self.publish_event(GameEvent::DawnAnnounced { announcement: announcement.clone() });
+        Ok(announcement)
+    }
+    
+    /// 把完整的夜晚结算记入复盘，赛后可以看到每晚的守护、刀口和用药
+    fn record_night_result(&mut self, result: NightResult) {
+        let game_id = match &self.game_id {
+            Some(game_id) => game_id.clone(),
+            None => return,
+        };
+        let mut metadata = HashMap::new();
+        metadata.insert("night_result".to_string(), serde_json::json!(result));
+        let event = ReplayEvent {
+            id: utils::generate_id(),
+            event_type: GameEventType::SkillUse,
+            timestamp: chrono::Utc::now(),
+            round: result.day,
+            phase: GamePhase::Night,
+            player_id: None,
+            target_id: None,
+            content: format!("第{}夜结算", result.day),
+            metadata,
+        };
+        if let Err(e) = self.replay.record_event(&game_id, event) {
+            warn!("记录夜晚结算失败: {}", e);
+        }
+    }
+    
+    /// 出错或结束时停止推进
+    fn stop_night(&mut self) {
+        if let Some(round) = &mut self.night {
+            round.running = false;
+        }
+    }
+    
+    /// 狼人避刀：开启后，被村民重点怀疑的目标留给白天放逐
+    fn apply_kill_heat_veto(&self, actions: &mut [NightAction]) {
+        let engine = match &self.engine {
+            Some(engine) => engine,
+            None => return,
+        };
+        let state = engine.get_state();
+        let config = &state.game_config.kill_heat_veto;
+        if !config.enabled {
+            return;
+        }
+        
+        let heat = StrategyEngine::public_suspicion_heat(state, &self.observer);
+        for action in actions.iter_mut().filter(|a| matches!(a.action, NightActionType::Kill)) {
+            let target = match &action.target {
+                Some(target) => target.clone(),
+                None => continue,
+            };
+            if let Some(replacement) = StrategyEngine::veto_kill_target(&target, &heat, config.threshold, state) {
+                info!("狼人避刀: {} 已被重点怀疑，改刀 {}", target, replacement);
+                action.target = Some(replacement);
+            }
+        }
+    }
+    
+    /// 生成AI夜晚行动，同时返回用于审计的决策记录
+    async fn generate_ai_night_action(&mut self, player: &Player) -> AppResult<(Option<NightAction>, Option<AIDecision>)> {
+        let started = Instant::now();
+        
+        if let Some(engine) = self.engine.as_ref().filter(|_| self.plugins.controls(&player.id)) {
+            if let Some(response) = self.plugins.decide_night_action(SeatView::new(engine, player)) {
+                let action = self.parse_night_action_response(player, &response.to_string())?;
+                let served_by = self.plugins.plugin_name(&player.id).map(|name| format!("plugin:{}", name));
+                let decision = self.build_ai_decision(player, DecisionType::SkillTarget, response.to_string(), 1.0, started, served_by);
+                return Ok((action, decision));
+            }
+        }
+        
+        if let Some(llm_manager) = &self.llm_manager {
+            let prompt = self.build_night_action_prompt(player)?;
+            let params = self.engine.as_ref()
+                .map(|engine| Self::seat_generation(engine.get_state(), player).params())
+                .unwrap_or_default();
+            
+            match llm_manager.generate_with_params(prompt, params).await {
+                Ok((response, served_by)) => {
+                    let action = self.parse_night_action_response(player, response.as_str())?;
+                    let decision = self.build_ai_decision(player, DecisionType::SkillTarget, response, 0.7, started, Some(served_by));
+                    Ok((action, decision))
+                }
+                Err(e) => {
+                    warn!("AI夜晚行动生成失败: {}", e);
+                    let decision = self.build_ai_decision(player, DecisionType::SkillTarget, format!("LLM调用失败，放弃行动: {}", e), 0.0, started, None);
+                    Ok((None, decision))
+                }
+            }
+        } else {
+            // 如果没有LLM，使用简单的随机逻辑
+            let action = self.generate_simple_night_action(player);
+            let reasoning = match &action {
+                Some(action) => format!("规则引擎随机选择目标: {:?}", action.target),
+                None => "没有可选目标".to_string(),
+            };
+            let decision = self.build_ai_decision(player, DecisionType::SkillTarget, reasoning, 0.3, started, None);
+            Ok((action, decision))
+        }
+    }
+    
+    /// 构建AI决策记录，附带当前公开局面的上下文快照
+    fn build_ai_decision(
+        &self,
+        player: &Player,
+        decision_type: DecisionType,
+        reasoning: String,
+        confidence: f32,
+        started: Instant,
+        served_by: Option<String>
+    ) -> Option<AIDecision> {
+        let engine = self.engine.as_ref()?;
+        let state = engine.get_state();
+        
+        let alive_players: Vec<String> = state.players.iter()
+            .filter(|p| p.is_alive)
+            .map(|p| p.id.clone())
+            .collect();
+        
+        // 决策者只知道自己的身份
+        let mut known_roles = HashMap::new();
+        known_roles.insert(player.id.clone(), player.role.clone());
+        
+        let context = DecisionContext {
+            round: state.day,
+            phase: state.phase.clone(),
+            alive_players: alive_players.clone(),
+            known_roles,
+            voting_history: state.votes.clone(),
+            speech_history: engine.get_speech_history().to_vec(),
+            game_state: GameStateSnapshot {
+                day: state.day,
+                phase: state.phase.clone(),
+                alive_players,
+                votes: state.votes.clone(),
+                timestamp: chrono::Utc::now(),
+            },
+        };
+        
+        Some(AIDecision {
+            id: utils::generate_id(),
+            timestamp: chrono::Utc::now(),
+            player_id: player.id.clone(),
+            decision_type,
+            context,
+            reasoning,
+            confidence,
+            execution_time_ms: started.elapsed().as_millis() as u64,
+            alternatives: Vec::new(),
+            served_by,
+        })
+    }
+    
+    /// 将AI决策写入复盘与数据库审计日志，经过模型的决策计入平均耗时
+    async fn record_ai_decision(&mut self, decision: Option<AIDecision>) {
+        if let (Some(decision), Some(game_id)) = (decision, self.game_id.clone()) {
+            if decision.served_by.as_deref().is_some_and(|source| !source.starts_with("plugin:")) {
+                self.session.record_ai_latency(decision.execution_time_ms);
+            }
+            if let Some(repository) = &self.repository {
+                if let Err(e) = repository.record_ai_decision(&game_id, &decision).await {
+                    warn!("保存AI决策失败: {}", e);
+                }
+            }
+            
+            if let Err(e) = self.replay.record_ai_decision(&game_id, decision) {
+                warn!("记录AI决策到复盘失败: {}", e);
+            }
+        }
+    }
+    
+    /// 保存人类玩家的私人笔记，未指定天数时记在当天
+    pub async fn save_note(
+        &mut self,
+        player_id: String,
+        day: Option<u32>,
+        content: String,
+        suspected_roles: HashMap<String, RoleType>,
+        claims_heard: Vec<ClaimNote>
+    ) -> AppResult<PlayerNote> {
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        let player = engine.find_player(&player_id)
+            .ok_or_else(|| AppError::GameLogic("玩家不存在".to_string()))?;
+        if player.is_ai {
+            return Err(AppError::GameLogic("只有人类玩家可以记笔记".to_string()));
+        }
+        
+        let note = PlayerNote {
+            player_id,
+            day: day.unwrap_or(engine.get_state().day),
+            content,
+            suspected_roles,
+            claims_heard,
+            updated_at: chrono::Utc::now(),
+        };
+        
+        if let Some(game_id) = &self.game_id {
+            if let Some(repository) = &self.repository {
+                repository.save_note(game_id, &note).await?;
+            }
+            self.replay.record_note(game_id, note.clone())?;
+        }
+        
+        Ok(note)
+    }
+    
+    /// 获取游戏中的笔记，优先读取数据库，无数据库时读取本局复盘
+    pub async fn get_notes(&self, game_id: &str) -> AppResult<Vec<PlayerNote>> {
+        if let Some(repository) = &self.repository {
+            let records = repository.get_notes(game_id).await?;
+            return records.into_iter()
+                .map(|record| -> AppResult<PlayerNote> {
+                    Ok(PlayerNote {
+                        player_id: record.player_id,
+                        day: record.day as u32,
+                        content: record.content,
+                        suspected_roles: serde_json::from_str(&record.suspected_roles)?,
+                        claims_heard: serde_json::from_str(&record.claims_heard)?,
+                        updated_at: record.timestamp,
+                    })
+                })
+                .collect();
+        }
+        
+        Ok(self.replay.get_replay(game_id)
+            .map(|replay| replay.notes.clone())
+            .unwrap_or_default())
+    }
+    
+    /// 将结算结果同步给复盘、数据库与前端，保证各处看到的结果一致
+    async fn finalize_game(&mut self) {
+        let result = match self.engine.as_ref().and_then(|e| e.get_state().result.clone()) {
+            Some(result) => result,
+            None => return,
+        };
+        
+        if let Some(game_id) = self.game_id.clone() {
+            if let Err(e) = self.replay.finish_recording(&game_id, result.clone()).await {
+                warn!("完成复盘记录失败: {}", e);
+            }
+            let fact_sheet = self.engine.as_ref().map(|engine| FactSheet::build(&game_id, engine));
+            if let Some(repository) = &self.repository {
+                if let Err(e) = repository.finish_game(&game_id, &result).await {
+                    warn!("保存游戏结果失败: {}", e);
+                }
+                if let Some(sheet) = &fact_sheet {
+                    if let Err(e) = repository.save_fact_sheet(sheet).await {
+                        warn!("保存事实表失败: {}", e);
+                    }
+                }
+            }
+            self.last_fact_sheet = fact_sheet;
+        }
+        
+        self.publish_event(GameEvent::GameEnded { result });
+        self.is_running = false;
+    }
+    
+    /// 把AI新产生的性格漂移记入复盘，供赛后分析展示压力对行为的影响，
+    /// 并把漂移后的性格同步到座位上
+    fn record_personality_drift(&mut self) {
+        let game_id = match &self.game_id {
+            Some(game_id) => game_id.clone(),
+            None => return,
+        };
+        let phase = match &self.engine {
+            Some(engine) => engine.get_state().phase.clone(),
+            None => return,
+        };
+        
+        let mut player_ids: Vec<String> = self.agents.keys().cloned().collect();
+        player_ids.sort();
+        for player_id in player_ids {
+            let (records, traits) = match self.agents.get_mut(&player_id) {
+                Some(agent) => (agent.take_drift_records(), agent.traits().clone()),
+                None => continue,
+            };
+            if records.is_empty() {
+                continue;
+            }
+            if let Some(engine) = &mut self.engine {
+                engine.update_personality_traits(&player_id, traits);
+            }
+            for record in records {
+                let mut metadata = HashMap::new();
+                metadata.insert("cause".to_string(), serde_json::json!(record.cause));
+                metadata.insert("before".to_string(), serde_json::json!(record.before));
+                metadata.insert("after".to_string(), serde_json::json!(record.after));
+                
+                let event = ReplayEvent {
+                    id: utils::generate_id(),
+                    event_type: GameEventType::PersonalityDrift,
+                    timestamp: chrono::Utc::now(),
+                    round: record.day,
+                    phase: phase.clone(),
+                    player_id: Some(player_id.clone()),
+                    target_id: None,
+                    content: record.cause.describe(),
+                    metadata,
+                };
+                if let Err(e) = self.replay.record_event(&game_id, event) {
+                    warn!("记录性格漂移失败: {}", e);
+                }
+            }
+        }
+    }
+    
+    /// 推送并持久化新的放逐投票结果
+    async fn sync_vote_outcomes(&mut self) {
+        let outcomes = match &self.engine {
+            Some(engine) => engine.get_state().vote_outcomes[self.synced_vote_outcomes..].to_vec(),
+            None => return,
+        };
+        self.synced_vote_outcomes += outcomes.len();
+        
+        for outcome in outcomes {
+            for agent in self.agents.values_mut() {
+                agent.process_vote_outcome(&outcome);
+            }
+            // 计票后的选票才算数，撤销过的票不会进入推理
+            for tally in &outcome.tally {
+                for voter in &tally.voters {
+                    if let Err(e) = self.observer.analyze_vote(voter.clone(), tally.target.clone()) {
+                        warn!("分析投票失败: {}", e);
+                    }
+                }
+            }
+            self.publish_event(GameEvent::VoteResolved { outcome: outcome.clone() });
+            
+            // 和预测出入大的票本身就是信息
+            let surprises = ai::score_surprises(&std::mem::take(&mut self.vote_predictions), &outcome);
+            for surprise in &surprises {
+                if let Err(e) = ai::analyze_vote_surprise(&mut self.observer, surprise) {
+                    warn!("分析意外投票失败: {}", e);
+                }
+            }
+            
+            let game_id = match &self.game_id {
+                Some(game_id) => game_id.clone(),
+                None => continue,
+            };
+            let content = match (&outcome.eliminated, outcome.tied.is_empty()) {
+                (Some(player_id), false) => format!("{}平票，随机放逐{}", outcome.tied.join("、"), player_id),
+                (Some(player_id), true) => format!("{}被投票出局", player_id),
+                (None, false) if outcome.pk => format!("{}平票，进入PK", outcome.tied.join("、")),
+                (None, false) => format!("{}平票，无人出局", outcome.tied.join("、")),
+                (None, true) => "无人投票，无人出局".to_string(),
+            };
+            let mut metadata = HashMap::new();
+            metadata.insert("tally".to_string(), serde_json::json!(outcome.tally));
+            metadata.insert("tied".to_string(), serde_json::json!(outcome.tied));
+            metadata.insert("abstentions".to_string(), serde_json::json!(outcome.abstentions));
+            metadata.insert("revote".to_string(), serde_json::json!(outcome.revote));
+            metadata.insert("surprises".to_string(), serde_json::json!(surprises));
+            
+            let event = ReplayEvent {
+                id: utils::generate_id(),
+                event_type: GameEventType::Vote,
+                timestamp: chrono::Utc::now(),
+                round: outcome.day,
+                phase: GamePhase::Voting,
+                player_id: None,
+                target_id: outcome.eliminated.clone(),
+                content,
+                metadata,
+            };
+            if let Err(e) = self.replay.record_event(&game_id, event) {
+                warn!("记录投票结果失败: {}", e);
+            }
+            
+            if let Some(repository) = &self.repository {
+                if let Err(e) = repository.record_vote_outcome(&game_id, &outcome).await {
+                    warn!("保存投票结果失败: {}", e);
+                }
+            }
+        }
+    }
+    
+    /// 进入投票阶段（含PK后的重新投票）时，在任何选票揭晓前预测每个人的票
+    fn predict_votes(&mut self) {
+        self.vote_predictions = match &self.engine {
+            Some(engine) if engine.get_state().phase == GamePhase::Voting => {
+                ai::predict_votes(&self.observer, engine.get_state())
+            }
+            _ => Vec::new(),
+        };
+    }
+    
+    /// 将新的死亡公示同步给推理引擎、AI代理与复盘
+    fn sync_death_reveals(&mut self) {
+        let (reveals, phase) = match &self.engine {
+            Some(engine) => {
+                let state = engine.get_state();
+                (state.death_reveals[self.synced_reveals..].to_vec(), state.phase.clone())
+            }
+            None => return,
+        };
+        self.synced_reveals += reveals.len();
+        
+        for reveal in reveals {
+            // 先按公示前的印象判断这次出局是否出人意料
+            if let Some(moment) = self.death_moment(&reveal) {
+                self.emit_reactions(moment);
+            }
+            
+            self.observer.apply_death_reveal(&reveal);
+            for agent in self.agents.values_mut() {
+                agent.process_death_reveal(&reveal);
+            }
+            self.publish_event(GameEvent::PlayerDied { reveal: reveal.clone() });
+            
+            // 复盘中只记录按规则公开的信息
+            if let Some(game_id) = &self.game_id {
+                let mut metadata = HashMap::new();
+                if let Some(role) = &reveal.revealed_role {
+                    metadata.insert("revealed_role".to_string(), serde_json::json!(role));
+                }
+                if let Some(faction) = &reveal.revealed_faction {
+                    metadata.insert("revealed_faction".to_string(), serde_json::json!(faction));
+                }
+                
+                let event = ReplayEvent {
+                    id: utils::generate_id(),
+                    event_type: GameEventType::PlayerDeath,
+                    timestamp: chrono::Utc::now(),
+                    round: reveal.day,
+                    phase: phase.clone(),
+                    player_id: Some(reveal.player_id.clone()),
+                    target_id: None,
+                    content: format!("{}出局", reveal.player_id),
+                    metadata,
+                };
+                if let Err(e) = self.replay.record_event(game_id, event) {
+                    warn!("记录死亡事件失败: {}", e);
+                }
+            }
+        }
+    }
+    
+    /// 将声明板上的新条目同步给推理引擎与AI代理
+    fn sync_claims(&mut self) -> AppResult<()> {
+        let board = match &self.engine {
+            Some(engine) => engine.get_claims_board().clone(),
+            None => return Ok(()),
+        };
+        
+        for claim in &board.claims[self.synced_claims..] {
+            let contested = board.is_contested(&claim.role);
+            self.observer.analyze_role_claim(claim.player_id.clone(), &claim.role, contested)?;
+            for agent in self.agents.values_mut() {
+                agent.process_role_claim(claim, contested)?;
+            }
+            self.publish_event(GameEvent::RoleClaimed {
+                player_id: claim.player_id.clone(),
+                role: claim.role.clone(),
+                contested,
+            });
+        }
+        self.synced_claims = board.claims.len();
+        
+        for report in &board.check_reports[self.synced_checks..] {
+            let credibility = board.report_credibility(&report.reporter);
+            if report.is_werewolf && credibility >= 0.8 {
+                self.emit_reactions(DramaticMoment::WolfExposed {
+                    reporter: report.reporter.clone(),
+                    target: report.target.clone(),
+                });
+            }
+            self.observer.analyze_check_report(&report.reporter, report.target.clone(), report.is_werewolf, credibility)?;
+            for agent in self.agents.values_mut() {
+                agent.process_check_report(report, credibility)?;
+            }
+            self.publish_event(GameEvent::CheckReported {
+                reporter: report.reporter.clone(),
+                target: report.target.clone(),
+                is_werewolf: report.is_werewolf,
+            });
+        }
+        self.synced_checks = board.check_reports.len();
+        
+        self.record_personality_drift();
+        self.check_claim_consistency()
+    }
+    
+    /// 对照公开事实校验所有查验声明，新发现的矛盾计入推理
+    fn check_claim_consistency(&mut self) -> AppResult<()> {
+        let inconsistencies: Vec<Inconsistency> = match &self.engine {
+            Some(engine) => {
+                let state = engine.get_state();
+                let mut reporters: Vec<&str> = state.claims_board.check_reports.iter()
+                    .map(|r| r.reporter.as_str())
+                    .collect();
+                reporters.sort();
+                reporters.dedup();
+                reporters.into_iter()
+                    .flat_map(|reporter| ConsistencyChecker::check_public_reports(reporter, state))
+                    .collect()
+            }
+            None => return Ok(()),
+        };
+        
+        for inconsistency in inconsistencies {
+            let key = format!("{}:{:?}:{}", inconsistency.reporter, inconsistency.kind, inconsistency.target);
+            if !self.flagged_inconsistencies.insert(key) {
+                continue;
+            }
+            
+            info!("发现查验声明矛盾: {}", inconsistency.description);
+            self.observer.analyze_claim_inconsistency(&inconsistency)?;
+            for agent in self.agents.values_mut() {
+                agent.process_claim_inconsistency(&inconsistency)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 获取公开声明板
+    pub fn get_claims_board(&self) -> AppResult<ClaimsBoard> {
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        Ok(engine.get_claims_board().clone())
+    }
+    
+    /// 当前对局的完整复盘，没有进行中的对局时取最近开始的一局
+    pub async fn latest_replay(&self) -> AppResult<Option<GameReplay>> {
+        let game_id = match self.game_id.clone().or_else(|| {
+            self.replay.get_replay_list().into_iter()
+                .max_by_key(|replay| replay.start_time)
+                .map(|replay| replay.game_id.clone())
+        }) {
+            Some(game_id) => game_id,
+            None => return Ok(None),
+        };
+        self.full_replay(&game_id).await
+    }
+    
+    /// 指定对局的完整复盘，已转存的早期事件从数据库补全
+    pub async fn full_replay(&self, game_id: &str) -> AppResult<Option<GameReplay>> {
+        let spilled = match &self.repository {
+            Some(repository) => repository.get_replay_events(game_id).await?,
+            None => Vec::new(),
+        };
+        self.replay.full_replay(game_id, spilled)
+    }
+    
+    /// 赛后事实表，不指定对局时取本次运行中最近结束的一局
+    pub async fn get_fact_sheet(&self, game_id: Option<&str>) -> AppResult<Option<FactSheet>> {
+        let game_id = match game_id.or(self.last_fact_sheet.as_ref().map(|sheet| sheet.game_id.as_str())) {
+            Some(game_id) => game_id,
+            None => return Ok(None),
+        };
+        if let Some(sheet) = self.last_fact_sheet.as_ref().filter(|sheet| sheet.game_id == game_id) {
+            return Ok(Some(sheet.clone()));
+        }
+        match &self.repository {
+            Some(repository) => repository.get_fact_sheet(game_id).await,
+            None => Ok(None),
+        }
+    }
+    
+    /// 校验复盘完整性：传入导出的复盘JSON时校验该文件，否则校验本地记录
+    pub async fn verify_replay_integrity(&self, game_id: Option<&str>, replay_json: Option<&str>) -> AppResult<IntegrityReport> {
+        if let Some(json) = replay_json {
+            let replay: GameReplay = serde_json::from_str(json)?;
+            return verify_integrity(&replay);
+        }
+        
+        let game_id = game_id.or(self.game_id.as_deref())
+            .ok_or_else(|| AppError::GameLogic("未指定要校验的复盘".to_string()))?;
+        
+        // 早期事件已转存时，从数据库补全后校验整条哈希链
+        if let Some(repository) = &self.repository {
+            let spilled = repository.get_replay_events(game_id).await?;
+            if let Some(replay) = self.replay.full_replay(game_id, spilled)? {
+                return verify_integrity(&replay);
+            }
+        }
+        self.replay.verify_replay(game_id)
+    }
+    
+    /// 各组件的内存占用统计
+    /// 本局的实时统计：用时、发言数、AI平均耗时、模型用量和已完成的阶段数
+    pub fn get_session_stats(&self) -> AppResult<SessionStats> {
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        Ok(self.session.stats(engine.get_state(), engine.get_speech_history(), self.tokens.usage()))
+    }
+    
+    pub fn get_memory_stats(&self) -> Vec<MemoryStats> {
+        let mut stats = vec![self.replay.memory_stats()];
+        
+        let mut agent_speech = Vec::new();
+        let mut nlp_context = Vec::new();
+        for agent in self.agents.values() {
+            let mut agent_stats = agent.memory_stats().into_iter();
+            agent_speech.extend(agent_stats.next());
+            nlp_context.extend(agent_stats.next());
+        }
+        stats.push(MemoryStats::merge("agent_speech_history", agent_speech));
+        stats.push(MemoryStats::merge("nlp_context_memory", nlp_context));
+        stats.push(self.embedding_store.memory_stats());
+        stats.push(self.exemplars.memory_stats());
+        stats
+    }
+    
+    /// 判断一次出局是否值得AI即时反应
+    fn death_moment(&self, reveal: &DeathReveal) -> Option<DramaticMoment> {
+        let state = self.engine.as_ref()?.get_state();
+        let player_id = reveal.player_id.clone();
+        
+        let claimed_seer = state.claims_board.current_claim(&player_id) == Some(&RoleType::Seer);
+        if claimed_seer && reveal.revealed_faction == Some(Faction::Werewolf) {
+            return Some(DramaticMoment::ClaimedSeerWasWolf { player_id });
+        }
+        
+        let revealed_god = matches!(
+            reveal.revealed_role,
+            Some(RoleType::Seer) | Some(RoleType::Witch) | Some(RoleType::Guard) | Some(RoleType::Hunter)
+        );
+        if revealed_god || self.observer.get_trust_score(&player_id) > 0.7 {
+            return Some(DramaticMoment::TrustedPlayerDied { player_id });
+        }
+        
+        None
+    }
+    
+    /// 发布事件，开启新手提示时附带主持人的规则解释，需要播报的事件同时加入主持人语音队列
+    ///
+    /// 提示只走事件总线，不进入发言记录和AI提示词，避免向AI泄露元信息
+    fn publish_event(&mut self, event: GameEvent) {
+        let tips = match &self.engine {
+            Some(engine) if engine.get_state().game_config.beginner_tips => {
+                Narrator::beginner_tips(&event, engine.get_state())
+            }
+            _ => Vec::new(),
+        };
+        if let Some(engine) = &self.engine {
+            let voice = &engine.get_state().game_config.narrator_voice;
+            if voice.enabled {
+                if let Some(content) = Narrator::narration(&event, engine.get_state(), voice.verbosity) {
+                    self.speech.narrate(Narration {
+                        content,
+                        voice_name: voice.voice_name.clone(),
+                        speed: voice.speed,
+                    });
+                }
+            }
+        }
+        self.broadcast(event);
+        
+        for tip in tips {
+            // 同一条规则每局只解释一次
+            if self.explained_rules.insert(tip.rule.clone()) {
+                self.events.publish(GameEvent::BeginnerTip { tip });
+            }
+        }
+    }
+    
+    /// 推送事件，公开事件同时转发给策略插件
+    fn broadcast(&mut self, event: GameEvent) {
+        self.plugins.observe(&event);
+        self.events.publish(event);
+    }
+    
+    /// 让座位相近的AI做出即时反应，走低优先级通道，不进入正式发言记录
+    fn emit_reactions(&mut self, moment: DramaticMoment) {
+        let (reactions, round, phase) = match &self.engine {
+            Some(engine) => {
+                let state = engine.get_state();
+                (
+                    ReactionGenerator::react(&moment, state, &state.game_config.reactions),
+                    state.day,
+                    state.phase.clone(),
+                )
+            }
+            None => return,
+        };
+        
+        for reaction in reactions {
+            if let Some(game_id) = &self.game_id {
+                let event = ReplayEvent {
+                    id: utils::generate_id(),
+                    event_type: GameEventType::Reaction,
+                    timestamp: chrono::Utc::now(),
+                    round,
+                    phase: phase.clone(),
+                    player_id: Some(reaction.player_id.clone()),
+                    target_id: Some(moment.subject().to_string()),
+                    content: reaction.content.clone(),
+                    metadata: HashMap::new(),
+                };
+                if let Err(e) = self.replay.record_event(game_id, event) {
+                    warn!("记录AI反应失败: {}", e);
+                }
+            }
+            
+            self.events.publish(GameEvent::AIReaction {
+                player_id: reaction.player_id,
+                content: reaction.content,
+            });
+        }
+    }
+    
+    /// 真人玩家打断当前发言者，每天限一次
+    pub fn raise_objection(&mut self, player_id: String, content: String, idempotency_key: Option<String>) -> AppResult<Objection> {
+        if let Some(objection) = self.replayed_submission(idempotency_key.as_deref()) {
+            return Ok(objection);
+        }
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        engine.validate_action(&PlayerAction::Objection { player: player_id.clone() })?;
+        let state = engine.get_state();
+        let config = &state.game_config.objections;
+        if !config.enabled {
+            return Err(AppError::GameLogic("本局没有开启打断".to_string()));
+        }
+        if !self.objections.can_object(&player_id, state.day) {
+            return Err(AppError::InvalidAction(ActionErrorCode::DuplicateAction, "今天已经打断过一次".to_string()));
+        }
+        let content = objection::normalize(&content, config.max_chars)
+            .ok_or_else(|| AppError::GameLogic("异议内容不能为空".to_string()))?;
+        let speaker = state.current_speaker.clone().unwrap_or_default();
+        let content = self.filter_human_input(&player_id, content)?;
+        
+        self.autopilot.touch(&player_id);
+        let objection = self.apply_objection(player_id, speaker, content)?;
+        self.record_submission(idempotency_key.as_deref(), &objection);
+        Ok(objection)
+    }
+    
+    /// 发言刚结束、还在朗读时，按性格的打断倾向和对发言者的怀疑度决定是否有AI打断
+    fn maybe_ai_objection(&mut self, speaker_id: &str) {
+        use rand::seq::SliceRandom;
+        use rand::thread_rng;
+        
+        let (objector, content) = match &self.engine {
+            Some(engine) => {
+                let state = engine.get_state();
+                if state.phase != GamePhase::DayDiscussion || state.current_speaker.as_deref() != Some(speaker_id) {
+                    return;
+                }
+                let speaker = match engine.find_player(speaker_id) {
+                    Some(speaker) => speaker,
+                    None => return,
+                };
+                
+                let mut rng = thread_rng();
+                let mut candidates: Vec<&Player> = state.players.iter()
+                    .filter(|p| p.is_alive && p.is_ai && p.id != speaker_id)
+                    .filter(|p| !p.knows_teammate(speaker) && !self.plugins.controls(&p.id))
+                    .filter(|p| self.objections.can_object(&p.id, state.day))
+                    .collect();
+                candidates.shuffle(&mut rng);
+                let objector = candidates.into_iter().find(|p| {
+                    let suspicion = self.agents.get(&p.id).map_or(0.0, |agent| agent.suspicion_of(speaker_id));
+                    objection::ai_should_object(&state.game_config.objections, objection::interruption_tendency(p), suspicion, &mut rng)
+                });
+                match objector {
+                    Some(objector) => (
+                        objector.id.clone(),
+                        objection::challenge_line(state.game_config.speech_style.language, &mut rng),
+                    ),
+                    None => return,
+                }
+            }
+            None => return,
+        };
+        
+        if let Err(e) = self.apply_objection(objector, speaker_id.to_string(), content) {
+            warn!("AI打断失败: {}", e);
+        }
+    }
+    
+    /// 打断的统一处理：AI发言者当场回应，推理和AI代理记下证据，打断插到朗读队列最前
+    fn apply_objection(&mut self, player_id: String, speaker: String, content: String) -> AppResult<Objection> {
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        let state = engine.get_state();
+        let reply = match engine.find_player(&speaker) {
+            Some(p) if p.is_ai => Some(objection::reply_line(state.game_config.speech_style.language, &mut rand::thread_rng())),
+            _ => None,
+        };
+        let objection = Objection { day: state.day, player_id, speaker, content, reply };
+        let phase = state.phase.clone();
+        info!("{}打断了{}: {}", objection.player_id, objection.speaker, objection.content);
+        
+        self.objections.mark(&objection.player_id, objection.day);
+        self.observer.analyze_objection(objection.player_id.clone(), objection.speaker.clone())?;
+        for agent in self.agents.values_mut() {
+            if let Err(e) = agent.process_objection(&objection) {
+                warn!("AI代理 {} 处理打断失败: {}", agent.player_id, e);
+            }
+        }
+        
+        if let Some(game_id) = &self.game_id {
+            let mut metadata = HashMap::new();
+            if let Some(reply) = &objection.reply {
+                metadata.insert("reply".to_string(), serde_json::json!(reply));
+            }
+            let event = ReplayEvent {
+                id: utils::generate_id(),
+                event_type: GameEventType::Objection,
+                timestamp: chrono::Utc::now(),
+                round: objection.day,
+                phase,
+                player_id: Some(objection.player_id.clone()),
+                target_id: Some(objection.speaker.clone()),
+                content: objection.content.clone(),
+                metadata,
+            };
+            if let Err(e) = self.replay.record_event(game_id, event) {
+                warn!("记录打断失败: {}", e);
+            }
+        }
+        
+        self.speech.interrupt(objection.clone());
+        self.broadcast(GameEvent::ObjectionRaised { objection: objection.clone() });
+        Ok(objection)
+    }
+    
+    /// 将特权命令调用记入复盘，便于事后核查对局完整性
+    pub fn record_integrity_note(&mut self, command: &str, capability: Capability) {
+        info!("特权命令调用: {} ({})", command, capability.as_str());
+        
+        let game_id = match &self.game_id {
+            Some(game_id) => game_id.clone(),
+            None => return,
+        };
+        let (round, phase) = match &self.engine {
+            Some(engine) => (engine.get_state().day, engine.get_state().phase.clone()),
+            None => return,
+        };
+        
+        let mut metadata = HashMap::new();
+        metadata.insert("command".to_string(), serde_json::json!(command));
+        metadata.insert("capability".to_string(), serde_json::json!(capability));
+        
+        let event = ReplayEvent {
+            id: utils::generate_id(),
+            event_type: GameEventType::IntegrityNote,
+            timestamp: chrono::Utc::now(),
+            round,
+            phase,
+            player_id: None,
+            target_id: None,
+            content: format!("调用特权命令{}", command),
+            metadata,
+        };
+        if let Err(e) = self.replay.record_event(&game_id, event) {
+            warn!("记录完整性事件失败: {}", e);
+        }
+    }
+    
+    /// 留存真人玩家本轮的正式投票
+    fn capture_human_votes(&mut self) {
+        let engine = match &self.engine {
+            Some(engine) if engine.get_state().phase == GamePhase::Voting => engine,
+            _ => return,
+        };
+        let state = engine.get_state();
+        
+        for vote in state.votes.iter().filter(|v| !v.is_abstention()) {
+            let is_human = state.players.iter().any(|p| p.id == vote.voter && !p.is_ai);
+            if is_human {
+                self.human_votes.push(HumanVote { day: state.day, target: vote.target.clone() });
+            }
+        }
+    }
+    
+    /// 教练模式下生成复盘反馈：day为None时是游戏结束后的整局复盘
+    async fn record_coach_feedback(&mut self, day: Option<u32>) {
+        let (human_id, findings, round, phase) = match &self.engine {
+            Some(engine) if engine.get_state().game_config.coach_mode => {
+                let state = engine.get_state();
+                let human = match state.players.iter().find(|p| !p.is_ai) {
+                    Some(human) => human,
+                    None => return,
+                };
+                (
+                    human.id.clone(),
+                    Coach::evaluate(&human.id, &self.human_votes, day, state),
+                    day.unwrap_or(state.day),
+                    state.phase.clone(),
+                )
+            }
+            _ => return,
+        };
+        if findings.is_empty() {
+            return;
+        }
+        
+        let final_report = day.is_none();
+        let explanation = match &self.llm_manager {
+            Some(llm_manager) => match llm_manager.generate_with_fallback(Coach::explanation_prompt(&findings, final_report)).await {
+                Ok(text) => Some(text.trim().to_string()),
+                Err(e) => {
+                    warn!("生成教练点评失败: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        
+        let report = CoachReport { day: round, findings, explanation, final_report };
+        
+        if let Some(game_id) = &self.game_id {
+            let mut metadata = HashMap::new();
+            metadata.insert("findings".to_string(), serde_json::json!(report.findings));
+            metadata.insert("final_report".to_string(), serde_json::json!(final_report));
+            
+            let content = report.explanation.clone().unwrap_or_else(|| {
+                report.findings.iter().map(|f| f.message.as_str()).collect::<Vec<_>>().join("；")
+            });
+            let event = ReplayEvent {
+                id: utils::generate_id(),
+                event_type: GameEventType::CoachFeedback,
+                timestamp: chrono::Utc::now(),
+                round,
+                phase,
+                player_id: Some(human_id),
+                target_id: None,
+                content,
+                metadata,
+            };
+            if let Err(e) = self.replay.record_event(game_id, event) {
+                warn!("记录教练反馈失败: {}", e);
+            }
+        }
+        
+        self.events.publish(GameEvent::CoachFeedback { report: report.clone() });
+        self.coach_reports.push(report);
+    }
+    
+    /// 本局的教练反馈
+    pub fn get_coach_reports(&self) -> &[CoachReport] {
+        &self.coach_reports
+    }
+    
+    /// 记录当天结束时的怀疑度快照
+    async fn record_suspicion_snapshot(&self, day: u32) {
+        if let (Some(repository), Some(game_id)) = (&self.repository, &self.game_id) {
+            let report = self.observer.get_analysis_report();
+            if let Err(e) = repository.record_suspicion_snapshot(game_id, day, &report.player_analysis).await {
+                warn!("保存怀疑度快照失败: {}", e);
+            }
+        }
+    }
+    
+    /// 获取怀疑度时间序列
+    pub async fn get_suspicion_history(&self, game_id: &str) -> AppResult<Vec<crate::database::SuspicionSnapshotRecord>> {
+        let repository = self.repository.as_ref()
+            .ok_or_else(|| AppError::Database("数据库不可用".to_string()))?;
+        
+        repository.get_suspicion_history(game_id).await
+    }
+    
+    /// 获取最近的对局记录，包括启动时收尾的中断对局
+    pub async fn get_game_history(&self, limit: u32) -> AppResult<Vec<crate::database::GameRecord>> {
+        let repository = self.repository.as_ref()
+            .ok_or_else(|| AppError::Database("数据库不可用".to_string()))?;
+        
+        repository.get_recent_games(limit).await
+    }
+    
+    /// 生成平衡板子：按往局实测胜率抽样，数据库不可用或读取失败时只按经验比例抽
+    pub async fn generate_board(&self, total_players: u8, difficulty: BoardDifficulty) -> ProposedBoard {
+        let total_players = total_players.clamp(utils::MIN_PLAYERS, utils::MAX_PLAYERS);
+        let history = match &self.repository {
+            Some(repository) => match repository.get_board_results(total_players).await {
+                Ok(results) => board_generator::aggregate(&results),
+                Err(e) => {
+                    warn!("读取板子战绩失败: {}", e);
+                    HashMap::new()
+                }
+            },
+            None => HashMap::new(),
+        };
+        
+        let board = board_generator::generate(total_players, difficulty, &history, &mut rand::thread_rng());
+        info!("生成{}人{:?}板子，候选{}个，其中{}个有实测战绩", total_players, difficulty, board.candidates, board.measured_candidates);
+        board
+    }
+    
+    /// 获取AI决策审计记录
+    pub async fn get_ai_decisions(&self, game_id: &str, player_id: Option<&str>) -> AppResult<Vec<crate::database::AIDecisionRecord>> {
+        let repository = self.repository.as_ref()
+            .ok_or_else(|| AppError::Database("数据库不可用".to_string()))?;
+        
+        repository.get_ai_decisions(game_id, player_id).await
+    }
+    
+    /// 构建夜晚行动提示词
+    fn build_night_action_prompt(&self, player: &Player) -> AppResult<String> {
+        if let Some(engine) = &self.engine {
+            let state = engine.get_state();
+            
+            let prompt = match player.role.role_type {
+                RoleType::Werewolf | RoleType::DreamWolf | RoleType::Gargoyle => {
+                    // 看不到狼队的角色也看不到狼人频道
+                    let wolf_chat = if player.role.role_type.sees_wolf_team() {
+                        engine.get_channel_messages_for_day(&ChatChannel::Werewolf, state.day)
+                    } else {
+                        Vec::new()
+                    };
+                    let wolf_chat = wolf_chat
+                        .iter()
+                        .map(|m| format!("{}: {}", m.sender, m.content))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    
+                    // 开启避刀时告诉狼人村民正在怀疑谁
+                    let heat_hint = if state.game_config.kill_heat_veto.enabled {
+                        let heat = StrategyEngine::public_suspicion_heat(state, &self.observer);
+                        let mut hot: Vec<_> = heat.into_iter()
+                            .filter(|(_, h)| *h >= state.game_config.kill_heat_veto.threshold)
+                            .map(|(id, _)| id)
+                            .collect();
+                        hot.sort();
+                        if hot.is_empty() {
+                            String::new()
+                        } else {
+                            format!("村民正在重点怀疑：{}，留着他们让好人白天浪费放逐票。", hot.join(", "))
+                        }
+                    } else {
+                        String::new()
+                    };
+                    
+                    format!(
+                        "你是狼人{}，现在是第{}夜。存活的玩家有：{}。狼队频道讨论：\n{}\n{}请选择一个目标杀死。返回JSON格式：{{\"action\":\"kill\",\"target\":\"player_id\"}}",
+                        player.display_name(),
+                        state.day,
+                        self.format_alive_players(state),
+                        if wolf_chat.is_empty() { "（暂无）".to_string() } else { wolf_chat },
+                        heat_hint
+                    )
+                }
+                RoleType::Seer => {
+                    format!(
+                        "你是预言家{}，现在是第{}夜。存活的玩家有：{}。{}请选择一个目标查验，不要重复查验已知身份的玩家。返回JSON格式：{{\"action\":\"check\",\"target\":\"player_id\"}}",
+                        player.display_name(),
+                        state.day,
+                        self.format_alive_players(state),
+                        self.format_seer_results(engine, player)
+                    )
+                }
+                RoleType::Witch => {
+                    let kill_targets = engine.pending_kill_targets();
+                    let rule_hint = if state.game_config.night_rules.guard_heal_conflict_kills {
+                        "本局规则同守同救死：被守卫守护的人再被你救会死亡，无人对跳的预言家很可能已被守护。"
+                    } else {
+                        ""
+                    };
+                    let potions = engine.witch_potions(&player.id);
+                    let available = |left: bool| if left { "还在" } else { "已用完" };
+                    format!(
+                        "你是女巫{}，现在是第{}夜。今晚被刀的是：{}。你的解药{}，毒药{}，每瓶只能用一次，解药只能救今晚被刀的人。{}你可以选择救人或毒人，也可以不用药。返回JSON格式：{{\"action\":\"heal/poison\",\"target\":\"player_id\"}}",
+                        player.display_name(),
+                        state.day,
+                        if kill_targets.is_empty() { "无人".to_string() } else { kill_targets.join(", ") },
+                        available(potions.antidote),
+                        available(potions.poison),
+                        rule_hint
+                    )
+                }
+                RoleType::Guard => {
+                    let rule_hint = if state.game_config.night_rules.guard_heal_conflict_kills {
+                        "本局规则同守同救死：你守护的人如果再被女巫救会死亡，首夜女巫多半会用解药，可以选择空守（target为null）。"
+                    } else {
+                        ""
+                    };
+                    let blocked_hint = match ActionValidator::blocked_protect_target(state, &player.id) {
+                        Some(target) => format!("你昨晚守护了{}，今晚不能再守他。", target),
+                        None => String::new(),
+                    };
+                    format!(
+                        "你是守卫{}，现在是第{}夜。存活的玩家有：{}。{}{}请选择一个目标保护。返回JSON格式：{{\"action\":\"protect\",\"target\":\"player_id\"}}",
+                        player.display_name(),
+                        state.day,
+                        self.format_alive_players(state),
+                        rule_hint,
+                        blocked_hint
+                    )
+                }
+                _ => return Err(AppError::GameLogic("无效的夜晚行动角色".to_string())),
+            };
+            
+            Ok(prompt)
+        } else {
+            Err(AppError::GameLogic("游戏引擎未初始化".to_string()))
+        }
+    }
+    
+    /// 格式化预言家本人的查验记录
+    fn format_seer_results(&self, engine: &GameEngine, player: &Player) -> String {
+        let results = engine.seer_results(&player.id);
+        if results.is_empty() {
+            return String::new();
+        }
+        
+        let lines: Vec<String> = results.iter()
+            .map(|r| {
+                let name = engine.find_player(&r.target)
+                    .map(|p| p.display_name())
+                    .unwrap_or_else(|| r.target.clone());
+                if r.reference {
+                    format!("开局金水{}是好人", name)
+                } else {
+                    format!("第{}夜查验{}是{}", r.night, name, if r.is_werewolf { "狼人" } else { "好人" })
+                }
+            })
+            .collect();
+        format!("你的查验记录：{}。", lines.join("；"))
+    }
+    
+    /// 格式化存活玩家列表
+    fn format_alive_players(&self, state: &GameState) -> String {
+        state.players.iter()
+            .filter(|p| p.is_alive)
+            .map(|p| format!("{}({})", p.display_name(), p.id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+    
+    /// 解析夜晚行动响应
+    fn parse_night_action_response(&self, player: &Player, response: &str) -> AppResult<Option<NightAction>> {
+        // 简化的JSON解析
+        if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(response) {
+            if let (Some(action_str), target) = (
+                json_value.get("action").and_then(|v| v.as_str()),
+                json_value.get("target").and_then(|v| v.as_str())
+            ) {
+                let action_type = match action_str {
+                    "kill" => NightActionType::Kill,
+                    "check" => NightActionType::Check,
+                    "heal" => NightActionType::Heal,
+                    "protect" => NightActionType::Protect,
+                    "poison" => NightActionType::Poison,
+                    _ => return Ok(None),
+                };
+                
+                return Ok(Some(NightAction {
+                    player: player.id.clone(),
+                    action: action_type,
+                    target: target.map(|s| s.to_string()),
+                }));
+            }
+        }
+        
+        Ok(None)
+    }
+    
+    /// 生成简单的夜晚行动（备用逻辑）
+    fn generate_simple_night_action(&self, player: &Player) -> Option<NightAction> {
+        if let Some(engine) = &self.engine {
+            let state = engine.get_state();
+            
+            // 女巫只救当晚的刀口，守卫按规则决定是否空守
+            match player.role.role_type {
+                RoleType::Witch => {
+                    let target = engine.pending_kill_targets().first()?;
+                    if !engine.witch_potions(&player.id).antidote || !StrategyEngine::witch_should_heal(target, state) {
+                        return None;
+                    }
+                    return Some(NightAction {
+                        player: player.id.clone(),
+                        action: NightActionType::Heal,
+                        target: Some(target.clone()),
+                    });
+                }
+                RoleType::Guard if StrategyEngine::guard_should_hold_back(state) => return None,
+                _ => {}
+            }
+            
+            // 守卫不能连守同一人
+            let blocked = ActionValidator::blocked_protect_target(state, &player.id)
+                .filter(|_| player.role.role_type == RoleType::Guard);
+            let alive_players: Vec<_> = state.players.iter()
+                .filter(|p| p.is_alive && p.id != player.id && Some(p.id.as_str()) != blocked)
+                .collect();
             
             if !alive_players.is_empty() {
                 use rand::{thread_rng, Rng};
                 let mut rng = thread_rng();
                 let target = &alive_players[rng.gen_range(0..alive_players.len())];
                 
-                let action_type = match player.role.role_type {
-                    RoleType::Werewolf => NightActionType::Kill,
-                    RoleType::Seer => NightActionType::Check,
-                    RoleType::Guard => NightActionType::Protect,
-                    _ => return None,
+                let action_type = match player.role.role_type {
+                    RoleType::Werewolf | RoleType::DreamWolf | RoleType::Gargoyle => NightActionType::Kill,
+                    RoleType::Seer => NightActionType::Check,
+                    RoleType::Guard => NightActionType::Protect,
+                    _ => return None,
+                };
+                
+                return Some(NightAction {
+                    player: player.id.clone(),
+                    action: action_type,
+                    target: Some(target.id.clone()),
+                });
+            }
+        }
+        
+        None
+    }
+    
+    /// 处理玩家发言
+    pub async fn handle_player_speech(&mut self, player_id: String, content: String, idempotency_key: Option<String>) -> AppResult<()> {
+        if let Some(response) = self.replayed_submission(idempotency_key.as_deref()) {
+            return Ok(response);
+        }
+        
+        if let Some(engine) = &self.engine {
+            engine.validate_action(&PlayerAction::Speech { speaker: player_id.clone() })?;
+        }
+        let content = self.filter_human_input(&player_id, content)?;
+        
+        if let Some(engine) = &mut self.engine {
+            
+            let state = engine.get_state();
+            let limits = &state.game_config.speech_limits;
+            let max_chars = limits.max_chars_for(&state.phase);
+            let content = if content.trim().chars().count() <= max_chars {
+                content.trim().to_string()
+            } else {
+                match limits.over_limit {
+                    OverLimitPolicy::Truncate => utils::trim_to_sentence(&content, max_chars),
+                    OverLimitPolicy::Reject => {
+                        return Err(AppError::InvalidAction(
+                            ActionErrorCode::SpeechTooLong,
+                            format!("发言不能超过{}字，当前{}字", max_chars, content.trim().chars().count())
+                        ));
+                    }
+                }
+            };
+            
+            let message = ChatMessage {
+                id: crate::utils::generate_id(),
+                sender: player_id.clone(),
+                content: content.clone(),
+                timestamp: chrono::Utc::now(),
+                message_type: MessageType::Human,
+            };
+            
+            self.ingest_speech(message).await?;
+            self.record_submission(idempotency_key.as_deref(), &());
+            self.autopilot.touch(&player_id);
+            self.maybe_ai_objection(&player_id);
+            Ok(())
+        } else {
+            Err(AppError::GameLogic("游戏未开始".to_string()))
+        }
+    }
+    
+    /// 真人输入在广播、保存和发给模型之前先过滤：拦截模式直接拒绝，遮挡模式替换命中部分，提醒模式原样放行
+    ///
+    /// 只记录命中的类别，不把原文写进日志
+    fn filter_human_input(&mut self, player_id: &str, content: String) -> AppResult<String> {
+        let result = self.content_filter.check(&content);
+        if !result.flagged() {
+            return Ok(content);
+        }
+        let mode = self.content_filter.mode();
+        let categories = content_filter::describe(&result.categories);
+        info!("玩家{}的输入包含{}，处理方式{:?}", player_id, categories, mode);
+        self.events.publish(GameEvent::ContentFiltered {
+            player_id: player_id.to_string(),
+            categories: result.categories,
+            mode,
+        });
+        
+        match mode {
+            FilterMode::Block => Err(AppError::InvalidAction(
+                ActionErrorCode::ContentBlocked,
+                format!("内容包含{}，请修改后再发送", categories)
+            )),
+            FilterMode::Mask | FilterMode::Warn => Ok(result.content),
+        }
+    }
+    
+    /// 以唤醒词开头的真人发言交给主持人回答，返回是否已按提问处理
+    ///
+    /// 回答只通过私信和主持人语音给到提问者，不进入发言记录，也不占用发言轮次
+    pub async fn handle_narrator_request(&mut self, player_id: &str, content: &str) -> bool {
+        let (question, voice) = match &self.engine {
+            Some(engine) => {
+                let config = &engine.get_state().game_config;
+                match config.wake_word.strip(content) {
+                    Some(question) => (question.to_string(), config.narrator_voice.clone()),
+                    None => return false,
+                }
+            }
+            None => return false,
+        };
+        let question = match self.filter_human_input(player_id, question) {
+            Ok(question) => question,
+            Err(_) => {
+                let answer = "问题包含不能发送的内容，请修改后再问。".to_string();
+                self.send_private(player_id, PrivateContent::NarratorAnswer { question: String::new(), answer }).await;
+                return true;
+            }
+        };
+        
+        let answer = match self.ask_narrator(player_id, &question).await {
+            Ok(answer) => answer,
+            Err(e) => {
+                warn!("主持人答问失败: {}", e);
+                "暂时无法回答这个问题。".to_string()
+            }
+        };
+        if voice.enabled {
+            self.speech.narrate(Narration {
+                content: answer.clone(),
+                voice_name: voice.voice_name,
+                speed: voice.speed,
+            });
+        }
+        self.send_private(player_id, PrivateContent::NarratorAnswer { question, answer }).await;
+        true
+    }
+    
+    /// 主持人答问：先查公开记录，查不到再让模型只依据公开记录回答
+    pub async fn ask_narrator(&self, player_id: &str, question: &str) -> AppResult<String> {
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        let state = engine.get_state();
+        
+        let query = NarratorQuery::parse(question);
+        if let Some(answer) = narrator_qa::answer(&query, state, player_id, self.last_dawn.as_ref()) {
+            return Ok(answer);
+        }
+        
+        let llm_manager = self.llm_manager.as_ref()
+            .ok_or_else(|| AppError::Config("LLM管理器未初始化".to_string()))?;
+        let record = narrator_qa::public_record(state, self.last_dawn.as_ref(), engine.get_speech_history());
+        let answer = llm_manager.generate_with_fallback(narrator_qa::llm_prompt(question, &record)).await?;
+        Ok(answer.trim().to_string())
+    }
+    
+    /// 用自然语言解释某个视角对某名玩家的怀疑，先套模板，需要时再让模型润色。
+    ///
+    /// 真人的视角只用公开信息的旁观推理；AI的推理里有私密信息，对局结束后才能查看
+    pub async fn explain_suspicion(&self, observer_id: &str, target_id: &str, polish: bool) -> AppResult<SuspicionExplanation> {
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        let state = engine.get_state();
+        if !state.players.iter().chain(state.dead_players.iter()).any(|p| p.id == target_id) {
+            return Err(AppError::InvalidAction(ActionErrorCode::TargetNotFound, format!("玩家{}不存在", target_id)));
+        }
+        
+        let (perspective, reasoning) = match self.agents.get(observer_id) {
+            Some(_) if state.phase != GamePhase::GameOver => {
+                return Err(AppError::GameLogic("对局结束后才能查看AI的推理".to_string()));
+            }
+            Some(agent) => (Some(observer_id.to_string()), agent.reasoning()),
+            None => (None, &self.observer),
+        };
+        let (suspicion, template) = ai::explain_suspicion(reasoning, state, target_id);
+        
+        let polished = match (&self.llm_manager, polish) {
+            (Some(llm_manager), true) => match llm_manager.generate_with_fallback(ai::polish_prompt(&template)).await {
+                Ok(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+                Ok(_) => None,
+                Err(e) => {
+                    warn!("润色怀疑解释失败，使用模板: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        
+        Ok(SuspicionExplanation {
+            observer_id: perspective,
+            target_id: target_id.to_string(),
+            suspicion,
+            polished: polished.is_some(),
+            text: polished.unwrap_or(template),
+        })
+    }
+    
+    /// 本机真人玩家的ID。前端不传身份，聊天等操作一律以真人座位为准
+    fn local_player_id(&self) -> AppResult<String> {
+        self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?
+            .local_player()
+            .map(|p| p.id.clone())
+            .ok_or_else(|| AppError::GameLogic("本局没有真人座位".to_string()))
+    }
+    
+    /// 真人玩家在聊天频道发送消息
+    pub async fn send_chat_message(&mut self, channel: ChatChannel, content: String, idempotency_key: Option<String>) -> AppResult<ChannelMessage> {
+        if let Some(message) = self.replayed_submission(idempotency_key.as_deref()) {
+            return Ok(message);
+        }
+        let sender_id = self.local_player_id()?;
+        let content = self.filter_human_input(&sender_id, content)?;
+        let message = self.post_chat_message(&sender_id, channel, content).await?;
+        self.record_submission(idempotency_key.as_deref(), &message);
+        self.autopilot.touch(&sender_id);
+        
+        // 真人在亡者频道说话时，死亡的AI可能接话
+        if message.channel == ChatChannel::Dead {
+            self.spectator_comments().await;
+        }
+        if message.channel == ChatChannel::Werewolf {
+            self.relay_wolf_chat(&message, None).await;
+        }
+        
+        Ok(message)
+    }
+    
+    /// 发送并保存频道消息，亡者频道的消息通过私信只推送给已出局的真人玩家
+    async fn post_chat_message(&mut self, sender_id: &str, channel: ChatChannel, content: String) -> AppResult<ChannelMessage> {
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        let message = engine.post_channel_message(sender_id, channel, content)?;
+        
+        if let (Some(repository), Some(game_id)) = (&self.repository, &self.game_id) {
+            if let Err(e) = repository.record_chat_message(game_id, &message).await {
+                warn!("保存聊天消息失败: {}", e);
+            }
+        }
+        if message.channel == ChatChannel::Dead {
+            // 亡者频道里可能谈到全部身份，不能进公共事件流让存活的真人看到
+            let recipients: Vec<String> = self.engine.as_ref()
+                .map(|engine| {
+                    let state = engine.get_state();
+                    state.players.iter()
+                        .chain(state.dead_players.iter())
+                        .filter(|p| !p.is_alive && !p.is_ai)
+                        .map(|p| p.id.clone())
+                        .collect()
+                })
+                .unwrap_or_default();
+            for player_id in recipients {
+                self.send_private(&player_id, PrivateContent::SpectatorMessage { message: message.clone() }).await;
+            }
+        }
+        
+        Ok(message)
+    }
+    
+    /// 死亡的AI在亡者频道点评局势。它们已知全部身份，所以这些内容只留在亡者频道，不进入存活AI的上下文
+    async fn spectator_comments(&mut self) {
+        let jobs: Vec<(String, String)> = match &self.engine {
+            Some(engine) => {
+                let state = engine.get_state();
+                let recent = engine.get_channel_messages_for_day(&ChatChannel::Dead, state.day);
+                Spectator::pick_commentators(state, &state.game_config.spectator)
+                    .iter()
+                    .map(|player| (player.id.clone(), Spectator::comment_prompt(player, state, &recent)))
+                    .collect()
+            }
+            None => return,
+        };
+        let llm_manager = match &self.llm_manager {
+            Some(llm_manager) => llm_manager.clone(),
+            None => return,
+        };
+        
+        for (player_id, prompt) in jobs {
+            let content = match llm_manager.generate_with_fallback(prompt).await {
+                Ok(text) => text.trim().to_string(),
+                Err(e) => {
+                    warn!("生成亡者频道点评失败: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = self.post_chat_message(&player_id, ChatChannel::Dead, content).await {
+                warn!("发送亡者频道点评失败: {}", e);
+            }
+        }
+    }
+    
+    /// 获取真人玩家可见的频道消息
+    pub fn get_chat_messages(&self, channel: &ChatChannel) -> AppResult<Vec<ChannelMessage>> {
+        let viewer_id = self.local_player_id()?;
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        engine.get_channel_messages(&viewer_id, channel)
+    }
+    
+    /// 由AI接管缺席的人类座位
+    pub async fn replace_human_with_ai(&mut self, player_id: String) -> AppResult<Player> {
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        let personality = engine.generate_ai_personality();
+        let player = engine.replace_human_with_ai(&player_id, personality.clone())?;
+        let state = engine.get_state().clone();
+        let speeches = engine.get_speech_history().to_vec();
+        
+        let mut agent = AIAgent::new(
+            player.id.clone(),
+            personality,
+            player.role.clone(),
+            self.llm_manager.clone()
+        );
+        agent.set_difficulty(state.game_config.ai_difficulty.profile());
+        agent.bootstrap_from_record(&state, &speeches).await?;
+        for result in engine.seer_results(&player.id) {
+            agent.process_seer_check(&result);
+        }
+        self.agents.insert(player.id.clone(), agent);
+        
+        info!("座位 {} 已由AI接管", player_id);
+        Ok(player)
+    }
+    
+    /// 将座位交还给回归的人类玩家
+    pub async fn restore_human_player(&mut self, player_id: String) -> AppResult<Player> {
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        let player = engine.restore_human(&player_id)?;
+        self.agents.remove(&player_id);
+        
+        info!("座位 {} 已交还给人类玩家", player_id);
+        Ok(player)
+    }
+    
+    /// 阶段切换时检查真人是否挂机，连续两个阶段没有操作的座位转入托管
+    fn check_absence(&mut self) {
+        let (config, humans) = match &self.engine {
+            Some(engine) => {
+                let state = engine.get_state();
+                let humans: Vec<String> = state.players.iter()
+                    .filter(|p| p.is_alive && !p.is_ai)
+                    .map(|p| p.id.clone())
+                    .collect();
+                (state.game_config.autopilot.clone(), humans)
+            }
+            None => return,
+        };
+        
+        for player_id in self.autopilot.on_phase_change(&config, &humans) {
+            info!("玩家 {} 连续{}个阶段没有操作，座位转入托管", player_id, Autopilot::IDLE_PHASES);
+            self.record_autopilot_event(&player_id, "长时间无操作，座位转入托管".to_string(), Some(true));
+            self.broadcast(GameEvent::AutopilotChanged { player_id, active: true });
+        }
+    }
+    
+    /// 真人收回被托管的座位
+    pub fn resume_control(&mut self, player_id: &str) -> AppResult<()> {
+        if self.engine.is_none() {
+            return Err(AppError::GameLogic("游戏未开始".to_string()));
+        }
+        if !self.autopilot.release(player_id) {
+            return Err(AppError::GameLogic("该座位没有处于托管中".to_string()));
+        }
+        
+        info!("玩家 {} 收回了座位控制", player_id);
+        self.record_autopilot_event(player_id, "玩家收回座位控制".to_string(), Some(false));
+        self.broadcast(GameEvent::AutopilotChanged { player_id: player_id.to_string(), active: false });
+        Ok(())
+    }
+    
+    /// 把托管的开始、结束和代做的决定记入复盘。active为None表示托管期间的一次决定
+    fn record_autopilot_event(&mut self, player_id: &str, content: String, active: Option<bool>) {
+        let game_id = match &self.game_id {
+            Some(game_id) => game_id.clone(),
+            None => return,
+        };
+        let (round, phase) = match &self.engine {
+            Some(engine) => (engine.get_state().day, engine.get_state().phase.clone()),
+            None => return,
+        };
+        
+        let mut metadata = HashMap::new();
+        metadata.insert("autopilot".to_string(), serde_json::json!(true));
+        if let Some(active) = active {
+            metadata.insert("active".to_string(), serde_json::json!(active));
+        }
+        let event = ReplayEvent {
+            id: utils::generate_id(),
+            event_type: GameEventType::Autopilot,
+            timestamp: chrono::Utc::now(),
+            round,
+            phase,
+            player_id: Some(player_id.to_string()),
+            target_id: None,
+            content,
+            metadata,
+        };
+        if let Err(e) = self.replay.record_event(&game_id, event) {
+            warn!("记录托管事件失败: {}", e);
+        }
+    }
+    
+    /// 生成AI发言，返回待投递的发言及剩余的思考/打字延迟
+    pub async fn generate_ai_speech(&mut self, player_id: String) -> AppResult<PendingSpeech> {
+        if self.autopilot.is_active(&player_id) {
+            return self.autopilot_speech(player_id);
+        }
+        
+        // 插件座位先问插件：直接给出发言时不经过模型，只给出意图时交给模型展开
+        let intent = match self.engine.as_ref() {
+            Some(engine) if self.plugins.controls(&player_id) => engine.get_state().players.iter()
+                .find(|p| p.id == player_id)
+                .and_then(|player| self.plugins.decide_speech(SeatView::new(engine, player))),
+            _ => None,
+        };
+        if let Some(text) = intent.as_ref().and_then(|intent| intent.text.clone()).filter(|text| !text.trim().is_empty()) {
+            return self.plugin_speech(player_id, text).await;
+        }
+        
+        let llm_manager = match &self.llm_manager {
+            Some(llm_manager) => llm_manager.clone(),
+            None => return Ok(PendingSpeech {
+                player_id,
+                content: "AI系统未配置".to_string(),
+                delay: Duration::ZERO,
+                message: None,
+            }),
+        };
+        
+        if let (Some(engine), Some(agent)) = (&self.engine, self.agents.get_mut(&player_id)) {
+            agent.refresh_deception_plan(engine.get_state());
+        }
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        let state = engine.get_state();
+        let player = state.players.iter()
+            .find(|p| p.id == player_id)
+            .cloned()
+            .ok_or_else(|| AppError::GameLogic("玩家不存在".to_string()))?;
+        let mut prompt = self.build_speech_prompt(&player, state)?;
+        if let Some(intent) = &intent {
+            let target = intent.target.as_ref()
+                .and_then(|target| state.players.iter().find(|p| &p.id == target))
+                .map(|target| format!("，针对{}", target.display_name()))
+                .unwrap_or_default();
+            prompt.push_str(&format!("\n\n本轮发言意图: {}{}，请围绕这个意图发言。", intent.intent, target));
+        }
+        let timing = state.game_config.speech_timing.clone();
+        let max_chars = state.game_config.speech_limits.max_chars_for(&state.phase);
+        let max_duration = state.game_config.speech_limits.max_duration_for(&state.phase);
+        let style = state.game_config.speech_style;
+        let output_control = state.game_config.output_control.clone();
+        let params = Self::seat_generation(state, &player).params();
+        
+        self.begin_thinking(&player_id);
+        
+        let started = Instant::now();
+        // 真人发言期间预生成的草稿仍然有效时直接使用
+        let generated = match self.drafts.take(&player_id, self.events.sequence()) {
+            Some(draft) => {
+                info!("使用{}的预生成发言，节省{}ms", player_id, draft.latency.as_millis());
+                Ok((draft.content, draft.served_by))
+            }
+            None => {
+                let (stream_id, token) = self.streams.register(&self.tasks.token());
+                let events = self.events.clone();
+                let mut on_delta = |delta: &str| events.publish(GameEvent::SpeechStreaming {
+                    stream_id: stream_id.clone(),
+                    speaker: player_id.clone(),
+                    delta: delta.to_string(),
+                });
+                let generated = llm_manager.generate_streaming(prompt.clone(), params, &token, &mut on_delta).await;
+                self.streams.finish(&stream_id);
+                generated
+            }
+        };
+        match generated {
+            Ok((response, served_by)) => {
+                let response = output_control::enforce(&llm_manager, &prompt, response, &style, max_chars, &output_control).await;
+                let decision = self.build_ai_decision(&player, DecisionType::Speech, response.clone(), 0.7, started, Some(served_by));
+                self.record_ai_decision(decision).await;
+                
+                let message = ChatMessage {
+                    id: crate::utils::generate_id(),
+                    sender: player_id.clone(),
+                    content: response.clone(),
+                    timestamp: chrono::Utc::now(),
+                    message_type: MessageType::AI,
                 };
                 
-                return Some(NightAction {
-                    player: player.id.clone(),
-                    action: action_type,
-                    target: Some(target.id.clone()),
-                });
+                // LLM调用本身已消耗的时间计入延迟
+                let patience = player.personality.as_ref()
+                    .map(|p| p.traits.patience)
+                    .unwrap_or(0.5);
+                let delay = timing.delay_for(&response, patience)
+                    .min(max_duration)
+                    .saturating_sub(started.elapsed());
+                
+                Ok(PendingSpeech {
+                    player_id,
+                    content: response,
+                    delay,
+                    message: Some(message),
+                })
+            }
+            Err(e) => {
+                warn!("AI发言生成失败: {}", e);
+                Ok(PendingSpeech {
+                    player_id,
+                    content: style.thinking_line().to_string(),
+                    delay: Duration::ZERO,
+                    message: None,
+                })
             }
         }
+    }
+    
+    /// 插件直接给出的发言，不经过模型改写，超出字数上限时截断
+    async fn plugin_speech(&mut self, player_id: String, text: String) -> AppResult<PendingSpeech> {
+        let started = Instant::now();
+        let state = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?
+            .get_state();
+        let player = state.players.iter()
+            .find(|p| p.id == player_id)
+            .cloned()
+            .ok_or_else(|| AppError::GameLogic("玩家不存在".to_string()))?;
+        let max_chars = state.game_config.speech_limits.max_chars_for(&state.phase);
+        let max_duration = state.game_config.speech_limits.max_duration_for(&state.phase);
+        let content: String = text.trim().chars().take(max_chars).collect();
+        let delay = state.game_config.speech_timing.delay_for(&content, 0.5).min(max_duration);
         
-        None
+        self.begin_thinking(&player_id);
+        let served_by = self.plugins.plugin_name(&player_id).map(|name| format!("plugin:{}", name));
+        let decision = self.build_ai_decision(&player, DecisionType::Speech, content.clone(), 1.0, started, served_by);
+        self.record_ai_decision(decision).await;
+        
+        let message = ChatMessage {
+            id: crate::utils::generate_id(),
+            sender: player_id.clone(),
+            content: content.clone(),
+            timestamp: chrono::Utc::now(),
+            message_type: MessageType::AI,
+        };
+        Ok(PendingSpeech {
+            player_id,
+            content,
+            delay,
+            message: Some(message),
+        })
     }
     
-    /// 处理玩家发言
-    pub async fn handle_player_speech(&mut self, player_id: String, content: String) -> AppResult<()> {
-        if let Some(engine) = &mut self.engine {
-            let message = ChatMessage {
-                id: crate::utils::generate_id(),
-                sender: player_id,
-                content,
-                timestamp: chrono::Utc::now(),
-                message_type: MessageType::Human,
+    /// 托管座位的最简发言，不经过模型，也不模拟思考延迟
+    fn autopilot_speech(&mut self, player_id: String) -> AppResult<PendingSpeech> {
+        let state = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?
+            .get_state();
+        let content = autopilot::minimal_speech(state.game_config.speech_style.language).to_string();
+        
+        self.begin_thinking(&player_id);
+        let message = ChatMessage {
+            id: crate::utils::generate_id(),
+            sender: player_id.clone(),
+            content: content.clone(),
+            timestamp: chrono::Utc::now(),
+            message_type: MessageType::AI,
+        };
+        Ok(PendingSpeech {
+            player_id,
+            content,
+            delay: Duration::ZERO,
+            message: Some(message),
+        })
+    }
+    
+    /// 为接下来要发言的AI准备推测性预生成任务（真人发言或思考期间调用）
+    pub fn speculative_jobs(&self) -> Vec<DraftJob> {
+        let engine = match &self.engine {
+            Some(engine) if self.llm_manager.is_some() => engine,
+            _ => return Vec::new(),
+        };
+        let state = engine.get_state();
+        if state.phase != GamePhase::DayDiscussion {
+            return Vec::new();
+        }
+        
+        // 从真人座位往后依次排列，越早发言的AI越先预生成
+        let human_seat = state.players.iter()
+            .find(|p| !p.is_ai && p.is_alive)
+            .map(|p| p.seat)
+            .unwrap_or(0);
+        let seat_count = state.players.len().max(1) as u8;
+        let mut upcoming: Vec<&Player> = state.players.iter()
+            // 插件座位的发言由插件决定，不预生成
+            .filter(|p| p.is_alive && p.is_ai && !self.thinking.contains(&p.id) && !self.plugins.controls(&p.id))
+            .collect();
+        upcoming.sort_by_key(|p| (p.seat + seat_count - human_seat) % seat_count);
+        
+        let sequence = self.events.sequence();
+        upcoming.into_iter()
+            .filter(|p| !self.drafts.is_fresh(&p.id, sequence))
+            .take(SPECULATION_LIMIT)
+            .filter_map(|player| {
+                let prompt = self.build_speech_prompt(player, state).ok()?;
+                let params = Self::seat_generation(state, player).params();
+                Some(DraftJob { player_id: player.id.clone(), prompt, params, sequence })
+            })
+            .collect()
+    }
+    
+    /// 保存预生成结果，生成期间出现了新信息时丢弃
+    pub fn store_speculative_draft(&mut self, job: &DraftJob, content: String, served_by: String, latency: Duration) -> bool {
+        let sequence = self.events.sequence();
+        self.drafts.store(job, content, served_by, latency, sequence)
+    }
+    
+    /// 在真人发言期间为AI预生成发言，LLM调用时不持有锁，返回保存的草稿数量
+    pub async fn precompute_ai_turns(game_manager: &RwLock<GameManager>) -> AppResult<usize> {
+        let (llm_manager, jobs, token) = {
+            let manager = game_manager.read().await;
+            (manager.llm_manager.clone(), manager.speculative_jobs(), manager.tasks.token())
+        };
+        let llm_manager = match llm_manager {
+            Some(llm_manager) => llm_manager,
+            None => return Ok(0),
+        };
+        
+        let mut stored = 0;
+        for job in jobs {
+            let started = Instant::now();
+            let (content, served_by) = match llm_manager.generate_with_params(job.prompt.clone(), job.params).await {
+                Ok(result) => result,
+                Err(AppError::Cancelled(_)) => break,
+                Err(e) => {
+                    warn!("预生成{}的发言失败: {}", job.player_id, e);
+                    continue;
+                }
             };
             
-            engine.add_chat_message(message)?;
+            let mut manager = game_manager.write().await;
+            if token.is_cancelled() {
+                break;
+            }
+            if manager.store_speculative_draft(&job, content, served_by, started.elapsed()) {
+                stored += 1;
+            } else {
+                // 信息已经变化，剩下的任务也基于旧局面
+                break;
+            }
         }
         
-        Ok(())
+        Ok(stored)
     }
     
-    /// 生成AI发言
-    pub async fn generate_ai_speech(&mut self, player_id: String) -> AppResult<String> {
-        if let Some(llm_manager) = &self.llm_manager {
-            if let Some(engine) = &self.engine {
+    /// 按发言顺序推进本轮讨论：AI依次发言（有预生成草稿时直接使用），轮到真人时暂停，
+    /// 真人发言后由调用方再次推进。思考与打字延迟期间不持有锁
+    pub async fn run_discussion_round(game_manager: &RwLock<GameManager>) -> AppResult<DiscussionStatus> {
+        let token = {
+            let mut manager = game_manager.write().await;
+            if let Some(status) = manager.begin_discussion()? {
+                return Ok(status);
+            }
+            manager.tasks.token()
+        };
+        
+        loop {
+            let player_id = match game_manager.write().await.next_discussion_turn() {
+                DiscussionTurn::Ai(player_id) => player_id,
+                DiscussionTurn::Stop(status) => return Ok(status),
+            };
+            
+            let pending = {
+                let mut manager = game_manager.write().await;
+                match manager.generate_ai_speech(player_id.clone()).await {
+                    Ok(pending) => pending,
+                    Err(e) => {
+                        manager.stop_discussion();
+                        return Err(e);
+                    }
+                }
+            };
+            if !pending.delay.is_zero() {
+                cancellation::sleep_cancellable(&token, pending.delay).await;
+            }
+            
+            let mut manager = game_manager.write().await;
+            if token.is_cancelled() {
+                manager.discard_ai_speech(pending);
+                manager.stop_discussion();
+                return Ok(DiscussionStatus::Interrupted);
+            }
+            match manager.deliver_ai_speech(pending).await {
+                Ok(_) => manager.maybe_ai_objection(&player_id),
+                Err(e) => warn!("投递讨论发言失败: {}", e),
+            }
+            if let Some(round) = &mut manager.discussion {
+                round.advance();
+            }
+        }
+    }
+    
+    /// 开始或继续本轮讨论，不需要推进时直接返回当前状态
+    fn begin_discussion(&mut self) -> AppResult<Option<DiscussionStatus>> {
+        let state = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?
+            .get_state();
+        if !matches!(state.phase, GamePhase::DayDiscussion | GamePhase::Pk) {
+            return Err(AppError::InvalidAction(ActionErrorCode::WrongPhase, "当前不是讨论阶段".to_string()));
+        }
+        
+        if !matches!(&self.discussion, Some(round) if round.day == state.day && round.phase == state.phase) {
+            self.discussion = None;
+        }
+        let round = self.discussion.get_or_insert_with(|| DiscussionRound::new(state));
+        if round.running {
+            return Ok(Some(DiscussionStatus::InProgress));
+        }
+        if let Some(player_id) = &round.awaiting {
+            return Ok(Some(DiscussionStatus::AwaitingHuman { player_id: player_id.clone() }));
+        }
+        round.running = true;
+        Ok(None)
+    }
+    
+    /// 找出下一位发言者并通知前端；轮到真人或讨论结束时停止推进
+    fn next_discussion_turn(&mut self) -> DiscussionTurn {
+        let (engine, round) = match (&mut self.engine, &mut self.discussion) {
+            (Some(engine), Some(round)) => (engine, round),
+            _ => return DiscussionTurn::Stop(DiscussionStatus::Interrupted),
+        };
+        let state = engine.get_state();
+        if state.phase != round.phase || state.day != round.day {
+            round.running = false;
+            return DiscussionTurn::Stop(DiscussionStatus::Interrupted);
+        }
+        
+        // 托管中的真人座位和AI一样自动发言
+        let (player_id, is_ai) = match round.next_speaker(state) {
+            Some(player) => (player.id.clone(), player.is_ai || self.autopilot.is_active(&player.id)),
+            None => {
+                let day = round.day;
+                round.running = false;
+                engine.set_current_speaker(None);
+                self.events.publish(GameEvent::DiscussionFinished { day });
+                return DiscussionTurn::Stop(DiscussionStatus::Finished);
+            }
+        };
+        
+        let (index, total) = round.position();
+        let day = round.day;
+        let time_limit = state.game_config.speech_limits.max_duration_for(&state.phase).as_secs() as u32;
+        if !is_ai {
+            round.running = false;
+            round.awaiting = Some(player_id.clone());
+            round.awaiting_since = Some(Instant::now());
+        }
+        engine.set_current_speaker(Some(player_id.clone()));
+        self.broadcast(GameEvent::DiscussionTurn {
+            day,
+            player_id: player_id.clone(),
+            index,
+            total,
+            awaiting_human: !is_ai,
+            time_limit: (!is_ai).then_some(time_limit),
+        });
+        
+        if is_ai {
+            DiscussionTurn::Ai(player_id)
+        } else {
+            DiscussionTurn::Stop(DiscussionStatus::AwaitingHuman { player_id })
+        }
+    }
+    
+    /// 真人发言结束，返回讨论轮是否应该继续推进
+    pub fn finish_human_turn(&mut self, player_id: &str) -> bool {
+        match &mut self.discussion {
+            Some(round) if round.awaiting.as_deref() == Some(player_id) => {
+                round.awaiting = None;
+                round.awaiting_since = None;
+                round.advance();
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    /// 当前发言者，本阶段的讨论轮还没开始或已经结束时返回None
+    pub fn current_speaker(&self) -> Option<CurrentSpeaker> {
+        let state = self.engine.as_ref()?.get_state();
+        let round = self.discussion.as_ref()
+            .filter(|round| round.day == state.day && round.phase == state.phase)?;
+        let player_id = state.current_speaker.clone()?;
+        let (index, total) = round.position();
+        let limit = state.game_config.speech_limits.max_duration_for(&state.phase);
+        
+        Some(CurrentSpeaker {
+            player_id,
+            index,
+            total,
+            awaiting_human: round.awaiting.is_some(),
+            seconds_remaining: round.awaiting_since
+                .map(|since| limit.saturating_sub(since.elapsed()).as_secs() as u32),
+        })
+    }
+    
+    /// 真人放弃本次发言（“过”），返回讨论轮是否应该继续推进；重复提交不再推进
+    pub fn pass_turn(&mut self, player_id: &str, idempotency_key: Option<String>) -> AppResult<bool> {
+        if self.replayed_submission::<()>(idempotency_key.as_deref()).is_some() {
+            return Ok(false);
+        }
+        let awaiting = self.discussion.as_ref().and_then(|round| round.awaiting.as_deref());
+        if awaiting != Some(player_id) {
+            return Err(AppError::InvalidAction(ActionErrorCode::NotYourTurn, "还没有轮到该玩家发言".to_string()));
+        }
+        
+        self.autopilot.touch(player_id);
+        self.record_turn_pass(player_id, "过".to_string(), false);
+        self.record_submission(idempotency_key.as_deref(), &());
+        Ok(self.finish_human_turn(player_id))
+    }
+    
+    /// 真人发言超过本阶段的时长上限时替其结束发言，返回讨论轮是否应该继续推进
+    pub fn expire_speech_turn(&mut self) -> bool {
+        let (player_id, limit) = match (&self.engine, &self.discussion) {
+            (Some(engine), Some(round)) => {
                 let state = engine.get_state();
-                
-                if let Some(player) = state.players.iter().find(|p| p.id == player_id) {
-                    let prompt = self.build_speech_prompt(player, state)?;
-                    
-                    match llm_manager.generate_with_fallback(prompt).await {
-                        Ok(response) => {
-                            // 记录AI发言
-                            let message = ChatMessage {
-                                id: crate::utils::generate_id(),
-                                sender: player_id,
-                                content: response.clone(),
-                                timestamp: chrono::Utc::now(),
-                                message_type: MessageType::AI,
-                            };
-                            
-                            if let Some(engine) = &mut self.engine {
-                                engine.add_chat_message(message)?;
-                            }
-                            
-                            Ok(response)
-                        }
-                        Err(e) => {
-                            warn!("AI发言生成失败: {}", e);
-                            Ok("我需要思考一下...".to_string())
+                if round.day != state.day || round.phase != state.phase {
+                    return false;
+                }
+                match (&round.awaiting, round.awaiting_since) {
+                    (Some(player_id), Some(since)) => {
+                        let limit = state.game_config.speech_limits.max_duration_for(&state.phase);
+                        if since.elapsed() < limit {
+                            return false;
                         }
+                        (player_id.clone(), limit)
                     }
-                } else {
-                    Err(AppError::GameLogic("玩家不存在".to_string()))
+                    _ => return false,
                 }
-            } else {
-                Err(AppError::GameLogic("游戏未开始".to_string()))
             }
-        } else {
-            Ok("AI系统未配置".to_string())
+            _ => return false,
+        };
+        
+        info!("{}发言超时，自动结束发言", player_id);
+        self.record_turn_pass(&player_id, format!("发言超时（{}秒），自动过", limit.as_secs()), true);
+        self.finish_human_turn(&player_id)
+    }
+    
+    /// 记录真人没有发言就结束的回合，复盘时能看出是主动过还是超时
+    fn record_turn_pass(&mut self, player_id: &str, content: String, timed_out: bool) {
+        let game_id = match &self.game_id {
+            Some(game_id) => game_id.clone(),
+            None => return,
+        };
+        let (round, phase) = match &self.engine {
+            Some(engine) => (engine.get_state().day, engine.get_state().phase.clone()),
+            None => return,
+        };
+        
+        let mut metadata = HashMap::new();
+        metadata.insert("passed".to_string(), serde_json::json!(true));
+        metadata.insert("timed_out".to_string(), serde_json::json!(timed_out));
+        // 不记为发言，免得复盘分析把“过”当成发言内容
+        let event = ReplayEvent {
+            id: utils::generate_id(),
+            event_type: GameEventType::SystemAnnouncement,
+            timestamp: chrono::Utc::now(),
+            round,
+            phase,
+            player_id: Some(player_id.to_string()),
+            target_id: None,
+            content,
+            metadata,
+        };
+        if let Err(e) = self.replay.record_event(&game_id, event) {
+            warn!("记录过麦失败: {}", e);
+        }
+    }
+    
+    /// 出错时停止推进，保留进度以便重试
+    fn stop_discussion(&mut self) {
+        if let Some(round) = &mut self.discussion {
+            round.running = false;
+        }
+    }
+    
+    /// 丢弃被取消的AI发言，只结束思考状态
+    pub fn discard_ai_speech(&mut self, pending: PendingSpeech) {
+        info!("{}的发言生成后任务已取消，不再投递", pending.player_id);
+        self.finish_thinking(&pending.player_id);
+    }
+    
+    /// 当前AI任务的取消令牌，调用方在不持锁等待后据此判断结果是否过时
+    pub fn task_token(&self) -> CancellationToken {
+        self.tasks.token()
+    }
+    
+    /// 投递AI发言并结束思考状态
+    pub async fn deliver_ai_speech(&mut self, pending: PendingSpeech) -> AppResult<String> {
+        self.finish_thinking(&pending.player_id);
+        
+        if let Some(message) = pending.message {
+            self.ingest_speech(message).await?;
+        }
+        
+        Ok(pending.content)
+    }
+    
+    /// 统一发言管道：所有公开发言都从这里进入，按固定顺序分发给
+    /// 引擎、推理、AI代理、复盘、前端事件，最后按序落库
+    async fn ingest_speech(&mut self, message: ChatMessage) -> AppResult<()> {
+        let engine = self.engine.as_mut()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        
+        engine.add_chat_message(message.clone())?;
+        let state = engine.get_state().clone();
+        let entry = self.speech.ingest(message, state.day, state.phase.clone());
+        let speaker = entry.message.sender.clone();
+        let content = entry.message.content.clone();
+        
+        // 计算发言向量，顺带检查是否重复了自己说过的话
+        let embedding_config = &state.game_config.embeddings;
+        let (vector, model) = embeddings::embed(embedding_config, self.llm_manager.as_deref(), &content).await;
+        let duplicate_of = self.embedding_store.find_duplicate(&speaker, &vector, embedding_config.duplicate_threshold);
+        if let Some(previous) = duplicate_of {
+            info!("发言#{}与{}此前的发言#{}几乎相同", entry.sequence, speaker, previous);
+        }
+        self.embedding_store.add(EmbeddedSpeech {
+            sequence: entry.sequence,
+            speaker: speaker.clone(),
+            day: entry.day,
+            content: content.clone(),
+            vector: vector.clone(),
+        });
+        self.speech.attach_embedding(entry.sequence, model, vector);
+        
+        self.observer.analyze_speech(speaker.clone(), &content)?;
+        for agent in self.agents.values_mut() {
+            if agent.player_id != speaker {
+                if let Err(e) = agent.process_player_speech(speaker.clone(), content.clone(), &state).await {
+                    warn!("AI代理 {} 处理发言#{}失败: {}", agent.player_id, entry.sequence, e);
+                }
+            }
+            // 新发言可能带来跳身份和查杀，狼人的欺骗计划随之更新，预生成的发言也能用上
+            agent.refresh_deception_plan(&state);
+        }
+        
+        if let Some(game_id) = &self.game_id {
+            let mut metadata = HashMap::new();
+            metadata.insert("sequence".to_string(), serde_json::json!(entry.sequence));
+            if self.autopilot.is_active(&speaker) {
+                metadata.insert("autopilot".to_string(), serde_json::json!(true));
+            }
+            if let Some(previous) = duplicate_of {
+                metadata.insert("duplicate_of".to_string(), serde_json::json!(previous));
+            }
+            
+            let event = ReplayEvent {
+                id: entry.message.id.clone(),
+                event_type: GameEventType::Speech,
+                timestamp: entry.message.timestamp,
+                round: entry.day,
+                phase: entry.phase.clone(),
+                player_id: Some(speaker.clone()),
+                target_id: None,
+                content: content.clone(),
+                metadata,
+            };
+            if let Err(e) = self.replay.record_event(game_id, event) {
+                warn!("记录发言事件失败: {}", e);
+            }
+        }
+        
+        // 真人的发言不会被朗读
+        if !matches!(entry.message.message_type, MessageType::Human) {
+            self.events.publish(GameEvent::SpeechTimeline {
+                sequence: entry.sequence,
+                speaker: speaker.clone(),
+                words: voice::estimate_word_timings(&content, 1.0),
+            });
+        }
+        self.broadcast(GameEvent::SpeechDelivered {
+            sequence: entry.sequence,
+            speaker,
+            content,
+        });
+        
+        self.sync_claims()?;
+        self.refresh_ai_vote_intents()?;
+        
+        self.speech.flush(self.repository.as_deref(), self.game_id.as_deref()).await;
+        self.replay.flush_spilled(self.repository.as_deref()).await;
+        Ok(())
+    }
+    
+    /// 查找某位玩家与给定内容最相似的历史发言；不给内容时以他最近一次发言为准
+    pub async fn find_similar_statements(&self, player_id: &str, query: Option<&str>, limit: usize) -> AppResult<Vec<SimilarStatement>> {
+        let engine = self.engine.as_ref()
+            .ok_or_else(|| AppError::GameLogic("游戏未开始".to_string()))?;
+        let config = &engine.get_state().game_config.embeddings;
+        
+        let (vector, exclude) = match query {
+            Some(query) => (embeddings::embed(config, self.llm_manager.as_deref(), query).await.0, None),
+            None => {
+                let latest = self.embedding_store.latest_by_player(player_id)
+                    .ok_or_else(|| AppError::NotFound(format!("玩家{}还没有发言", player_id)))?;
+                (latest.vector.clone(), Some(latest.sequence))
+            }
+        };
+        
+        let mut similar = self.embedding_store.similar_by_player(player_id, &vector, limit + 1);
+        similar.retain(|s| Some(s.sequence) != exclude);
+        similar.truncate(limit);
+        Ok(similar)
+    }
+    
+    /// 按相关性在字数预算内挑选的历史发言，作为提示词上下文
+    fn format_relevant_speeches(&self, player: &Player, state: &GameState) -> String {
+        let speeches = self.embedding_store.entries();
+        if speeches.is_empty() {
+            return String::new();
+        }
+        
+        let suspects = self.agents.get(&player.id)
+            .map(|agent| agent.get_analysis_report().suspicion_rankings)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|id| id != &player.id)
+            .take(CONTEXT_SUSPECT_COUNT)
+            .collect();
+        let focus = ContextFocus::new(player, state, suspects);
+        let limit = state.game_config.embeddings.retrieval_limit;
+        let relevant = context::select(&state.game_config.context, &focus, speeches, limit);
+        
+        let lines: Vec<String> = relevant.iter()
+            .map(|s| {
+                let name = state.players.iter()
+                    .chain(state.dead_players.iter())
+                    .find(|p| p.id == s.speaker)
+                    .map(|p| p.display_name())
+                    .unwrap_or_else(|| s.speaker.clone());
+                format!("第{}天 {}：{}", s.day, name, s.content)
+            })
+            .collect();
+        format!("相关发言：\n{}\n", lines.join("\n"))
+    }
+    
+    /// 同身份同阶段的往局范例发言，以本局最新一条发言代表当前局面挑选
+    fn format_exemplars(&self, player: &Player, state: &GameState) -> String {
+        let config = &state.game_config.exemplars;
+        if !config.enabled {
+            return String::new();
+        }
+        let query = self.embedding_store.latest().map(|speech| speech.vector.as_slice());
+        let selected = self.exemplars.select(&player.role.role_type, &state.phase, query, config.max_examples);
+        exemplars::format_exemplars(&selected)
+    }
+    
+    /// 取出待朗读的内容，供语音播报按序消费，主持人播报优先
+    pub fn take_tts_queue(&mut self) -> Vec<PlaybackItem> {
+        self.speech.take_tts_queue()
+    }
+    
+    /// 标记AI开始思考，暂停阶段计时
+    fn begin_thinking(&mut self, player_id: &str) {
+        if self.thinking.insert(player_id.to_string()) {
+            if let Some(engine) = &mut self.engine {
+                engine.pause_timer();
+            }
+            self.events.publish(GameEvent::AIThinking { player_id: player_id.to_string() });
+        }
+    }
+    
+    /// 标记AI思考结束，所有AI都结束思考后恢复计时
+    fn finish_thinking(&mut self, player_id: &str) {
+        if self.thinking.remove(player_id) {
+            if self.thinking.is_empty() {
+                if let Some(engine) = &mut self.engine {
+                    engine.resume_timer();
+                }
+            }
+            self.events.publish(GameEvent::AIThinkingFinished { player_id: player_id.to_string() });
         }
     }
     
     /// 构建发言提示词
-    fn build_speech_prompt(&self, player: &Player, state: &GameState) -> AppResult<String> {
+    pub(crate) fn build_speech_prompt(&self, player: &Player, state: &GameState) -> AppResult<String> {
         let phase_desc = match state.phase {
             GamePhase::DayDiscussion => "白天讨论",
             GamePhase::Voting => "投票阶段",
+            GamePhase::Pk => "平票PK",
             _ => "其他阶段",
         };
         
+        // 预言家能看到自己的查验记录
+        let mut private_info = String::new();
+        if player.role.role_type == RoleType::Seer {
+            if let Some(engine) = &self.engine {
+                private_info = self.format_seer_results(engine, player);
+                let results = engine.seer_results(&player.id);
+                if let Some(guidance) = StrategyEngine::seer_opening_guidance(&results, state) {
+                    private_info.push_str(&format!("发言要点：{}。", guidance));
+                }
+            }
+        }
+        // 狼人按跨天的伪装计划发言，保持跳的身份和报过的查验前后一致
+        if let Some(agent) = self.agents.get(&player.id) {
+            let guidance = agent.deception_guidance(state);
+            if !guidance.is_empty() {
+                private_info.push_str(&format!("你的伪装计划：{}。", guidance.join("；")));
+            }
+        }
+        
+        let max_chars = state.game_config.speech_limits.max_chars_for(&state.phase);
+        
         let prompt = format!(
-            "你是{}，身份是{}，属于{}阵营。现在是第{}天的{}阶段。场上存活玩家：{}。请生成一段符合你身份和性格的发言，长度在50-200字之间。",
-            player.name,
-            utils::get_role_description(&player.role.role_type),
+            "你是{}，身份是{}，属于{}阵营。现在是第{}天的{}阶段。场上存活玩家：{}。{}{}{}{}请生成一段符合你身份和性格的发言，用座位号称呼其他玩家（如“3号”），不超过{}字。{}{}{}",
+            player.display_name(),
+            roles::role_info(&player.role.role_type).description(),
             utils::get_faction_description(&player.faction),
             state.day,
             phase_desc,
-            self.format_alive_players(state)
+            self.format_alive_players(state),
+            self.format_claims_board(state),
+            private_info,
+            self.format_relevant_speeches(player, state),
+            self.format_exemplars(player, state),
+            max_chars,
+            state.game_config.speech_style.prompt_instruction(),
+            state.game_config.speech_style.banter_instruction(player),
+            Self::seat_generation(state, player).persona_instruction()
         );
+        let prompt = if state.phase == GamePhase::Pk {
+            format!("{}\n\n你和其他玩家平票，这是PK发言，接下来只在PK的玩家之间重新投票，请为自己辩护。", prompt)
+        } else {
+            prompt
+        };
         
         Ok(prompt)
     }
     
-    /// 更新游戏计时器
-    pub async fn update_timer(&mut self) -> AppResult<bool> {
-        if let Some(engine) = &mut self.engine {
-            engine.update_timer()
+    /// 座位的生成参数覆盖，没有配置时为空覆盖
+    fn seat_generation(state: &GameState, player: &Player) -> SeatGeneration {
+        state.game_config.seat_generation.get(&player.seat).cloned().unwrap_or_default()
+    }
+    
+    /// 汇总声明板，供提示词使用
+    fn format_claims_board(&self, state: &GameState) -> String {
+        let board = &state.claims_board;
+        let name_of = |id: &str| state.players.iter()
+            .chain(state.dead_players.iter())
+            .find(|p| p.id == id)
+            .map(|p| p.display_name())
+            .unwrap_or_else(|| id.to_string());
+        
+        let mut claimed: Vec<String> = Vec::new();
+        let mut lines = Vec::new();
+        for claim in &board.claims {
+            if claimed.contains(&claim.player_id) {
+                continue;
+            }
+            claimed.push(claim.player_id.clone());
+            if let Some(role) = board.current_claim(&claim.player_id) {
+                lines.push(format!("{}声明自己是{}", name_of(&claim.player_id), utils::get_role_name(role)));
+            }
+        }
+        let skipped = board.check_reports.len().saturating_sub(PROMPT_CHECK_REPORT_LIMIT);
+        for report in board.check_reports.iter().skip(skipped) {
+            lines.push(format!(
+                "{}报告第{}天查验{}为{}",
+                name_of(&report.reporter),
+                report.day,
+                name_of(&report.target),
+                if report.is_werewolf { "狼人" } else { "好人" }
+            ));
+        }
+        
+        if lines.is_empty() {
+            String::new()
         } else {
-            Ok(false)
+            format!("公开声明：{}。", lines.join("；"))
+        }
+    }
+    
+    /// 更新游戏计时器，投票阶段负责截止前提醒和超时自动投票，开启自动推进时讨论或PK超时直接进入投票
+    pub async fn update_timer(&mut self) -> AppResult<bool> {
+        let (expired, day, time_remaining) = match &mut self.engine {
+            Some(engine) => {
+                let expired = engine.update_timer()?;
+                let state = engine.get_state();
+                if let Some(time_remaining) = state.time_remaining {
+                    self.events.publish(GameEvent::TimerTick { phase: state.phase.clone(), time_remaining });
+                }
+                if expired && matches!(state.phase, GamePhase::DayDiscussion | GamePhase::Pk) && state.game_config.auto_advance {
+                    self.stop_discussion();
+                    self.proceed_to_next_phase().await?;
+                    return Ok(expired);
+                }
+                if state.phase != GamePhase::Voting {
+                    return Ok(expired);
+                }
+                (expired, state.day, state.time_remaining)
+            }
+            None => return Ok(false),
+        };
+        
+        let idle = self.idle_human_voters();
+        if expired {
+            // 不让挂机的真人卡住整局游戏
+            self.apply_auto_votes(idle)?;
+            self.proceed_to_next_phase().await?;
+        } else if !idle.is_empty()
+            && self.vote_deadline_warned != Some(day)
+            && matches!(time_remaining, Some(seconds) if seconds <= VOTE_WARNING_SECONDS) {
+            self.vote_deadline_warned = Some(day);
+            self.events.publish(GameEvent::VoteDeadlineWarning {
+                player_ids: idle,
+                seconds_left: time_remaining.unwrap_or(0),
+            });
+        }
+        
+        Ok(expired)
+    }
+    
+    /// 尚未投票的存活真人玩家
+    fn idle_human_voters(&self) -> Vec<String> {
+        match &self.engine {
+            Some(engine) => {
+                let state = engine.get_state();
+                state.players.iter()
+                    .filter(|p| p.is_alive && !p.is_ai && ActionValidator::can_vote(state, p))
+                    .filter(|p| !state.votes.iter().any(|v| v.voter == p.id))
+                    .map(|p| p.id.clone())
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+    
+    /// 按配置的策略替超时的真人投票，并在复盘中标注为自动投票
+    fn apply_auto_votes(&mut self, voters: Vec<String>) -> AppResult<()> {
+        for voter in voters {
+            let piloted = self.autopilot.is_active(&voter);
+            let (target, policy, round, mandatory) = match &self.engine {
+                Some(engine) => {
+                    let state = engine.get_state();
+                    // 强制投票的规则下不允许弃票
+                    let policy = match state.game_config.auto_vote {
+                        AutoVotePolicy::Abstain if state.game_config.vote_rules.mandatory => AutoVotePolicy::Random,
+                        policy => policy,
+                    };
+                    let target = if piloted {
+                        autopilot::vote_target(state, &voter)
+                    } else {
+                        Self::auto_vote_target(state, &voter, policy)
+                    };
+                    (target, policy, state.day, state.game_config.vote_rules.mandatory)
+                }
+                None => return Ok(()),
+            };
+            
+            // 弃票也记入投票，强制投票时找不到可投的人则只留下超时记录
+            if let Some(engine) = &mut self.engine {
+                if target.is_some() || !mandatory {
+                    engine.vote(voter.clone(), target.clone())?;
+                    self.broadcast(GameEvent::VoteCast { voter: voter.clone(), target: target.clone() });
+                }
+            }
+            info!("玩家 {} 投票超时，按{:?}策略处理: {:?}", voter, policy, target);
+            
+            if let Some(game_id) = &self.game_id {
+                let mut metadata = HashMap::new();
+                metadata.insert("automatic".to_string(), serde_json::json!(true));
+                if piloted {
+                    metadata.insert("autopilot".to_string(), serde_json::json!(true));
+                } else {
+                    metadata.insert("policy".to_string(), serde_json::json!(policy));
+                }
+                
+                let content = match &target {
+                    Some(target) => format!("{}超时未投票，自动投给{}", voter, target),
+                    None => format!("{}超时未投票，视为弃票", voter),
+                };
+                let event = ReplayEvent {
+                    id: utils::generate_id(),
+                    event_type: GameEventType::Vote,
+                    timestamp: chrono::Utc::now(),
+                    round,
+                    phase: GamePhase::Voting,
+                    player_id: Some(voter.clone()),
+                    target_id: target.clone(),
+                    content,
+                    metadata,
+                };
+                if let Err(e) = self.replay.record_event(game_id, event) {
+                    warn!("记录自动投票失败: {}", e);
+                }
+            }
+            
+            self.broadcast(GameEvent::AutoVoteApplied { player_id: voter, target });
+        }
+        
+        Ok(())
+    }
+    
+    /// 自动投票的目标，弃票时为None
+    fn auto_vote_target(state: &GameState, voter: &str, policy: AutoVotePolicy) -> Option<String> {
+        use rand::seq::SliceRandom;
+        use rand::thread_rng;
+        
+        // 重新投票时只能投给PK的玩家
+        let candidates: Vec<String> = state.players.iter()
+            .filter(|p| p.is_alive && p.id != voter)
+            .filter(|p| state.pk_candidates.is_empty() || state.pk_candidates.contains(&p.id))
+            .map(|p| p.id.clone())
+            .collect();
+        let random = || candidates.choose(&mut thread_rng()).cloned();
+        
+        match policy {
+            AutoVotePolicy::Abstain => None,
+            AutoVotePolicy::Random => random(),
+            AutoVotePolicy::FollowMajority => {
+                let mut counts: HashMap<&str, usize> = HashMap::new();
+                for vote in state.votes.iter().filter(|v| candidates.contains(&v.target)) {
+                    *counts.entry(vote.target.as_str()).or_insert(0) += 1;
+                }
+                // 与计票一致，票数相同时取座位号小的玩家
+                let seat_of = |player_id: &str| state.players.iter()
+                    .find(|p| p.id == player_id)
+                    .map(|p| p.seat)
+                    .unwrap_or(u8::MAX);
+                counts.into_iter()
+                    .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count)
+                        .then_with(|| seat_of(b).cmp(&seat_of(a))))
+                    .map(|(target, _)| target.to_string())
+                    .or_else(random)
+            }
         }
     }
     
@@ -373,3 +3785,120 @@ impl GameManager {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    /// 真人坐1号、警长坐最后一个座位，第一天讨论从真人开始
+    async fn manager_awaiting_human(max_seconds: u32) -> (RwLock<GameManager>, String) {
+        let mut config = AppConfig::default().game;
+        config.human_seat = Some(1);
+        config.night_timing = NightTimingConfig { min_duration: 0, max_padding: 0 };
+        config.speech_limits.discussion_max_seconds = max_seconds;
+        let mut manager = GameManager::new();
+        let state = manager.create_game(config).await.unwrap();
+        manager.start_game().await.unwrap();
+        manager.proceed_to_next_phase().await.unwrap();
+        let sheriff = state.players.iter().max_by_key(|p| p.seat).unwrap().id.clone();
+        manager.set_sheriff(Some(sheriff)).unwrap();
+        let human = manager.local_player_id().unwrap();
+
+        let manager = RwLock::new(manager);
+        let status = GameManager::run_discussion_round(&manager).await.unwrap();
+        assert!(matches!(status, DiscussionStatus::AwaitingHuman { ref player_id } if player_id == &human));
+        (manager, human)
+    }
+
+    #[tokio::test]
+    async fn test_pass_turn_moves_the_round_on() {
+        let (manager, human) = manager_awaiting_human(90).await;
+        let mut manager = manager.write().await;
+        let speaker = manager.current_speaker().unwrap();
+        assert_eq!(speaker.player_id, human);
+        assert_eq!(speaker.index, 1);
+        assert!(speaker.awaiting_human);
+        assert!(speaker.seconds_remaining.unwrap() <= 90);
+
+        assert!(matches!(
+            manager.pass_turn("someone-else", None),
+            Err(AppError::InvalidAction(ActionErrorCode::NotYourTurn, _))
+        ));
+        // 还没到时限，不替真人结束发言
+        assert!(!manager.expire_speech_turn());
+
+        assert!(manager.pass_turn(&human, None).unwrap());
+        let round = manager.discussion.as_ref().unwrap();
+        assert_eq!(round.awaiting, None);
+        assert_eq!(round.position().0, 2);
+        assert!(manager.pass_turn(&human, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_human_turn_expires_at_the_time_limit() {
+        let (manager, human) = manager_awaiting_human(0).await;
+        let mut manager = manager.write().await;
+        assert!(manager.expire_speech_turn());
+        let round = manager.discussion.as_ref().unwrap();
+        assert_eq!(round.awaiting, None);
+        assert_eq!(round.position().0, 2);
+        // 超时后再提交“过”已经不算数
+        assert!(matches!(
+            manager.pass_turn(&human, None),
+            Err(AppError::InvalidAction(ActionErrorCode::NotYourTurn, _))
+        ));
+        assert!(!manager.expire_speech_turn());
+    }
+
+    #[tokio::test]
+    async fn test_undone_vote_never_reaches_the_observer() {
+        let mut config = AppConfig::default().game;
+        RulePreset::Casual.apply(&mut config);
+        config.human_seat = Some(1);
+        config.night_timing = NightTimingConfig { min_duration: 0, max_padding: 0 };
+        let mut manager = GameManager::new();
+        let state = manager.create_game(config).await.unwrap();
+        manager.start_game().await.unwrap();
+        manager.proceed_to_next_phase().await.unwrap();
+        manager.proceed_to_next_phase().await.unwrap();
+        let human = manager.local_player_id().unwrap();
+        let others: Vec<String> = state.players.iter().filter(|p| p.id != human).map(|p| p.id.clone()).collect();
+        let (first, second) = (&others[0], &others[1]);
+        let voted_for = |manager: &GameManager, target: &str| {
+            let description = format!("{}投票给{}", human, target);
+            manager.observer.top_evidence(&human, usize::MAX).iter().any(|e| e.description == description)
+        };
+
+        manager.player_vote(human.clone(), Some(first.clone()), None).await.unwrap();
+        // 计票前选票还没交给推理
+        assert!(!voted_for(&manager, first));
+        let undone = manager.undo_last_action().unwrap();
+        assert!(matches!(undone, UndoneAction::Vote { ref voter, ref target } if voter == &human && target == first));
+
+        manager.player_vote(human.clone(), Some(second.clone()), None).await.unwrap();
+        manager.cast_ai_votes().await.unwrap();
+        assert!(voted_for(&manager, second));
+        assert!(!voted_for(&manager, first));
+    }
+
+    #[tokio::test]
+    async fn test_night_action_out_of_turn_is_rejected() {
+        let mut config = AppConfig::default().game;
+        config.human_seat = Some(1);
+        let mut manager = GameManager::new();
+        let state = manager.create_game(config).await.unwrap();
+        manager.start_game().await.unwrap();
+        let human = manager.local_player_id().unwrap();
+        let target = state.players.iter().find(|p| p.id != human).unwrap().id.clone();
+
+        // 夜晚流程还没轮到真人
+        manager.night = manager.engine.as_ref().map(|engine| NightRound::new(engine.get_state()));
+        let action = NightAction { player: human, action: NightActionType::Kill, target: Some(target) };
+        assert!(matches!(
+            manager.submit_night_action(action, None).await,
+            Err(AppError::InvalidAction(ActionErrorCode::NotYourTurn, _))
+        ));
+        assert!(manager.engine.as_ref().unwrap().pending_kill_targets().is_empty());
+    }
+}
@@ -0,0 +1,272 @@
+use crate::error::{AppError, AppResult};
+use crate::types::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use log::{info, warn};
+
+/// 房规脚本目录，位于配置目录下，按预设分子目录，如house_rules/casual/*.rhai
+pub const HOUSE_RULES_DIR: &str = "house_rules";
+/// 单次钩子调用的操作数上限，防止脚本死循环卡住结算
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// 支持的钩子
+pub const HOOKS: [&str; 3] = ["on_night_resolve", "on_vote_tally", "on_death"];
+
+/// 一段房规脚本，随游戏配置写入事件日志，回放时按原样重新执行
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HouseRuleScript {
+    pub name: String,
+    pub source: String,
+}
+
+/// 脚本中玩家的视图
+#[derive(Debug, Clone, Serialize)]
+struct ScriptPlayer {
+    id: String,
+    seat: u8,
+    name: String,
+    role: RoleType,
+    faction: Faction,
+    alive: bool,
+}
+
+impl From<&Player> for ScriptPlayer {
+    fn from(player: &Player) -> Self {
+        Self {
+            id: player.id.clone(),
+            seat: player.seat,
+            name: player.name.clone(),
+            role: player.role.role_type.clone(),
+            faction: player.role.faction.clone(),
+            alive: player.is_alive,
+        }
+    }
+}
+
+/// on_death返回的效果
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeathEffect {
+    /// 把某名存活玩家改为指定身份，如技能传承
+    SetRole { player: String, role: RoleType },
+    /// 连带出局，不再触发on_death
+    Kill { player: String },
+}
+
+struct CompiledScript {
+    name: String,
+    ast: AST,
+    hooks: HashSet<String>,
+}
+
+/// 编译好的房规脚本
+///
+/// 钩子在事件折叠时执行，脚本里的rand_int以当前事件序号为种子，
+/// 从事件日志重建时得到完全相同的结果
+pub struct HouseRules {
+    engine: Engine,
+    scripts: Vec<CompiledScript>,
+    rng: Arc<Mutex<StdRng>>,
+}
+
+impl HouseRules {
+    /// 编译并校验脚本：语法错误或定义了不支持的钩子时返回带脚本名和行号的错误
+    pub fn compile(scripts: &[HouseRuleScript]) -> AppResult<Self> {
+        let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(0)));
+        let engine = Self::sandboxed_engine(rng.clone());
+
+        let mut compiled = Vec::with_capacity(scripts.len());
+        for script in scripts {
+            let ast = engine.compile(&script.source)
+                .map_err(|e| AppError::Config(format!("房规脚本{}: {}", script.name, e)))?;
+            let hooks: HashSet<String> = ast.iter_functions()
+                .filter(|f| f.name.starts_with("on_"))
+                .map(|f| {
+                    if !HOOKS.contains(&f.name) {
+                        return Err(AppError::Config(format!(
+                            "房规脚本{}: 不支持的钩子{}，可用的钩子有{}", script.name, f.name, HOOKS.join("、")
+                        )));
+                    }
+                    if f.params.len() != 1 {
+                        return Err(AppError::Config(format!("房规脚本{}: 钩子{}应当只有一个参数ctx", script.name, f.name)));
+                    }
+                    Ok(f.name.to_string())
+                })
+                .collect::<AppResult<_>>()?;
+            if hooks.is_empty() {
+                return Err(AppError::Config(format!("房规脚本{}没有定义任何钩子", script.name)));
+            }
+            compiled.push(CompiledScript { name: script.name.clone(), ast, hooks });
+        }
+
+        Ok(Self { engine, scripts: compiled, rng })
+    }
+
+    /// 不能访问文件和网络，限制操作数、调用深度和数据大小
+    fn sandboxed_engine(rng: Arc<Mutex<StdRng>>) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_string_size(10_000);
+        engine.set_max_array_size(1_000);
+        engine.set_max_map_size(1_000);
+        engine.on_print(|text| info!("房规脚本: {}", text));
+        // 0..max的随机整数
+        engine.register_fn("rand_int", move |max: i64| -> i64 {
+            if max <= 0 {
+                return 0;
+            }
+            rng.lock().unwrap_or_else(|e| e.into_inner()).gen_range(0..max)
+        });
+        engine
+    }
+
+    fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// 依次调用各脚本定义的钩子，返回每个脚本的结果；后一个脚本的ctx[key]是前一个脚本的返回值
+    fn call<T: serde::de::DeserializeOwned>(&self, hook: &str, seed: u64, mut ctx: rhai::Map, key: &str) -> Vec<T> {
+        *self.rng.lock().unwrap_or_else(|e| e.into_inner()) = StdRng::seed_from_u64(seed);
+
+        let mut results = Vec::new();
+        for script in self.scripts.iter().filter(|s| s.hooks.contains(hook)) {
+            let value = self.engine.call_fn::<Dynamic>(&mut Scope::new(), &script.ast, hook, (ctx.clone(),));
+            match value {
+                Ok(value) if value.is_unit() => {}
+                Ok(value) => match rhai::serde::from_dynamic::<T>(&value) {
+                    Ok(parsed) => {
+                        ctx.insert(key.into(), value);
+                        results.push(parsed);
+                    }
+                    Err(e) => warn!("房规脚本{}的{}返回值无效，已忽略: {}", script.name, hook, e),
+                },
+                Err(e) => warn!("房规脚本{}执行{}失败，已忽略: {}", script.name, hook, e),
+            }
+        }
+        results
+    }
+
+    fn context(state: &GameState) -> rhai::Map {
+        let players: Vec<ScriptPlayer> = state.players.iter()
+            .chain(state.dead_players.iter())
+            .map(ScriptPlayer::from)
+            .collect();
+        let mut ctx = rhai::Map::new();
+        ctx.insert("day".into(), Dynamic::from(state.day as i64));
+        ctx.insert("players".into(), rhai::serde::to_dynamic(players).unwrap_or(Dynamic::UNIT));
+        ctx
+    }
+
+    /// 夜晚结算：ctx.deaths为守护与解药生效后的死亡名单，返回新的名单，返回()保持不变
+    pub fn on_night_resolve(&self, state: &GameState, seed: u64, deaths: &[String]) -> Option<Vec<String>> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut ctx = Self::context(state);
+        ctx.insert("deaths".into(), rhai::serde::to_dynamic(deaths).ok()?);
+        self.call("on_night_resolve", seed, ctx, "deaths").pop()
+    }
+
+    /// 计票结束：返回出局玩家ID改变结果，返回""表示无人出局，返回()保持不变
+    pub fn on_vote_tally(&self, state: &GameState, seed: u64, outcome: &VoteOutcome) -> Option<Option<String>> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut ctx = Self::context(state);
+        ctx.insert("tally".into(), rhai::serde::to_dynamic(&outcome.tally).ok()?);
+        ctx.insert("tied".into(), rhai::serde::to_dynamic(&outcome.tied).ok()?);
+        ctx.insert("eliminated".into(), outcome.eliminated.clone().map(Dynamic::from).unwrap_or(Dynamic::UNIT));
+        self.call::<String>("on_vote_tally", seed, ctx, "eliminated")
+            .pop()
+            .map(|eliminated| Some(eliminated).filter(|id| !id.is_empty()))
+    }
+
+    /// 玩家出局后：ctx.player为出局者，返回效果数组
+    pub fn on_death(&self, state: &GameState, seed: u64, player: &Player) -> Vec<DeathEffect> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let mut ctx = Self::context(state);
+        ctx.insert("player".into(), rhai::serde::to_dynamic(ScriptPlayer::from(player)).unwrap_or(Dynamic::UNIT));
+        self.call::<Vec<DeathEffect>>("on_death", seed, ctx, "effects").into_iter().flatten().collect()
+    }
+}
+
+/// 读取某个预设目录下的全部脚本，按文件名排序并逐个校验
+pub fn load_preset_scripts(config_dir: &Path, preset: RulePreset) -> AppResult<Vec<HouseRuleScript>> {
+    let dir = config_dir.join(HOUSE_RULES_DIR).join(preset.dir_name());
+    let mut paths: Vec<_> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect(),
+        Err(_) => return Ok(Vec::new()),
+    };
+    paths.sort();
+
+    let mut scripts = Vec::with_capacity(paths.len());
+    for path in paths {
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| AppError::Io(format!("读取房规脚本{:?}失败: {}", path, e)))?;
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        scripts.push(HouseRuleScript { name, source });
+    }
+    HouseRules::compile(&scripts)?;
+    info!("预设{:?}加载了{}个房规脚本", preset, scripts.len());
+    Ok(scripts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(source: &str) -> HouseRuleScript {
+        HouseRuleScript { name: "test.rhai".to_string(), source: source.to_string() }
+    }
+
+    #[test]
+    fn test_compile_reports_clear_errors() {
+        let err = HouseRules::compile(&[script("fn on_deth(ctx) { () }")]).err().unwrap();
+        assert!(err.to_string().contains("on_deth"));
+        let err = HouseRules::compile(&[script("fn on_death(ctx) { let x = ; }")]).err().unwrap();
+        assert!(err.to_string().contains("test.rhai"));
+        assert!(HouseRules::compile(&[script("fn on_death(ctx) { [] }")]).is_ok());
+    }
+
+    #[test]
+    fn test_rand_int_is_deterministic_per_seed() {
+        let rules = HouseRules::compile(&[script("fn on_night_resolve(ctx) { [ctx.deaths[rand_int(ctx.deaths.len())]] }")]).unwrap();
+        let state = GameState {
+            phase: GamePhase::Night,
+            day: 1,
+            players: Vec::new(),
+            dead_players: Vec::new(),
+            votes: Vec::new(),
+            game_config: crate::config::AppConfig::default().game,
+            winner: None,
+            current_speaker: None,
+            time_remaining: None,
+            vote_changes: Vec::new(),
+            vote_intents: Default::default(),
+            death_reveals: Vec::new(),
+            claims_board: crate::claims::ClaimsBoard::new(),
+            vote_outcomes: Vec::new(),
+            result: None,
+            witch_potions: Default::default(),
+            guard_protections: Default::default(),
+            sheriff: None,
+            revoked_votes: Default::default(),
+            pk_candidates: Default::default(),
+        };
+        let deaths: Vec<String> = (0..10).map(|i| format!("p{}", i)).collect();
+        let first = rules.on_night_resolve(&state, 42, &deaths).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(rules.on_night_resolve(&state, 42, &deaths).unwrap(), first);
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// 默认去重窗口
+const DEFAULT_WINDOW_SECS: u64 = 30;
+
+/// 幂等键守卫，用于过滤重复点击或界面重试造成的重复提交。
+/// 同时记下首次处理的结果，重试时原样返回，界面拿到的结果与第一次一致
+#[derive(Debug)]
+pub struct IdempotencyGuard {
+    seen: HashMap<String, (Instant, serde_json::Value)>,
+    window: Duration,
+}
+
+impl IdempotencyGuard {
+    pub fn new() -> Self {
+        Self::with_window(Duration::from_secs(DEFAULT_WINDOW_SECS))
+    }
+
+    pub fn with_window(window: Duration) -> Self {
+        Self {
+            seen: HashMap::new(),
+            window,
+        }
+    }
+
+    /// 窗口内已经成功处理过该键时，返回当时的结果
+    pub fn replay<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        let now = Instant::now();
+        let window = self.window;
+        self.seen.retain(|_, (seen_at, _)| now.duration_since(*seen_at) < window);
+        self.seen.get(key)
+            .and_then(|(_, response)| serde_json::from_value(response.clone()).ok())
+    }
+
+    /// 操作成功后登记幂等键和结果；被拒绝的提交不登记，界面用同一个键重试时照常处理
+    pub fn record<T: Serialize>(&mut self, key: &str, response: &T) {
+        let response = serde_json::to_value(response).unwrap_or(serde_json::Value::Null);
+        self.seen.insert(key.to_string(), (Instant::now(), response));
+    }
+
+    /// 清空所有记录（新游戏开始时调用）
+    pub fn clear(&mut self) {
+        self.seen.clear();
+    }
+}
+
+impl Default for IdempotencyGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::game_manager::GameManager;
+    use crate::types::{NightTimingConfig, RoleType};
+
+    #[test]
+    fn test_replay_returns_recorded_response_within_window() {
+        let mut guard = IdempotencyGuard::new();
+        assert_eq!(guard.replay::<u32>("a"), None);
+        guard.record("a", &7u32);
+        assert_eq!(guard.replay::<u32>("a"), Some(7));
+        assert_eq!(guard.replay::<u32>("b"), None);
+
+        let mut expired = IdempotencyGuard::with_window(Duration::ZERO);
+        expired.record("a", &());
+        assert_eq!(expired.replay::<()>("a"), None);
+    }
+
+    #[tokio::test]
+    async fn test_replayed_key_is_a_no_op() {
+        let mut config = AppConfig::default().game;
+        config.night_timing = NightTimingConfig { min_duration: 0, max_padding: 0 };
+        let mut manager = GameManager::new();
+        let state = manager.create_game(config).await.unwrap();
+        manager.start_game().await.unwrap();
+        // 第一夜没人行动，天亮后进入讨论，声明才合法
+        manager.proceed_to_next_phase().await.unwrap();
+        let claimant = state.players[0].id.clone();
+        let key = Some("claim-1".to_string());
+
+        manager.claim_role(claimant.clone(), RoleType::Seer, key.clone()).unwrap();
+        manager.claim_role(claimant, RoleType::Hunter, key).unwrap();
+        let claims = manager.get_claims_board().unwrap().claims;
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].role, RoleType::Seer);
+
+        // 不带键的提交照常处理
+        manager.claim_role(state.players[1].id.clone(), RoleType::Hunter, None).unwrap();
+        assert_eq!(manager.get_claims_board().unwrap().claims.len(), 2);
+    }
+}
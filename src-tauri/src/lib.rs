@@ -2,6 +2,9 @@ mod error;
 mod types;
 mod config;
 mod llm;
+mod llm_stream;
+mod local_llm;
+mod model_catalog;
 mod commands;
 mod utils;
 mod game_engine;
@@ -10,10 +13,64 @@ mod ai;
 mod database;
 mod voice;
 mod replay;
+mod chat;
+mod validator;
+mod idempotency;
+mod cancellation;
+mod events;
+mod narrator;
+mod narrator_qa;
+mod claims;
+mod speech_assist;
+mod demo_llm;
+mod speech_style;
+mod output_control;
+mod capabilities;
+mod speech_service;
+mod reactions;
+mod objection;
+mod autopilot;
+mod embeddings;
+mod exemplars;
+mod content_filter;
+mod context;
+mod coach;
+mod roles;
+mod speculation;
+mod memory;
+mod session_stats;
+mod spectator;
+mod discussion;
+mod night;
+mod night_resolution;
+mod wolf_council;
+mod game_loop;
+mod event_log;
+mod sfx;
+mod platform;
+mod updater;
+mod telemetry;
+mod diagnostics;
+mod support_bundle;
+mod plugins;
+mod house_rules;
+mod board_generator;
+mod arena;
+mod reasoning_regression;
+mod fact_sheet;
+pub mod paths;
+pub mod benchmark;
+#[cfg(any(test, feature = "rules-testing"))]
+pub mod rules_harness;
 
 use commands::*;
 use std::sync::Arc;
-use log::info;
+use tauri::{Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use log::{info, warn};
+
+/// 启动失败对话框的标题
+const ERROR_TITLE: &str = "智狼 (MindWolf) - 错误";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -27,6 +84,10 @@ pub fn run() {
         info!("智狼 (MindWolf) 启动中...");
     }
     
+    if let Err(e) = paths::data_paths().ensure_dirs() {
+        warn!("创建数据目录失败: {}", e);
+    }
+    
     // 创建应用状态
     let app_state = match commands::AppState::new() {
         Ok(state) => {
@@ -34,28 +95,7 @@ pub fn run() {
             state
         },
         Err(e) => {
-            let error_msg = format!("初始化应用状态失败: {}", e);
-            eprintln!("{}", error_msg);
-            
-            // 在 Windows 上显示消息框
-            #[cfg(windows)]
-            {
-                use std::ffi::CString;
-                use std::ptr;
-                
-                unsafe {
-                    let title = CString::new("智狼 (MindWolf) - 错误").unwrap_or_default();
-                    let message = CString::new(error_msg).unwrap_or_default();
-                    
-                    winapi::um::winuser::MessageBoxA(
-                        ptr::null_mut(),
-                        message.as_ptr(),
-                        title.as_ptr(),
-                        winapi::um::winuser::MB_OK | winapi::um::winuser::MB_ICONERROR,
-                    );
-                }
-            }
-            
+            platform::show_error_dialog(ERROR_TITLE, &format!("初始化应用状态失败: {}", e));
             return;
         }
     };
@@ -64,49 +104,170 @@ pub fn run() {
     match tauri::Builder::default()
         .plugin(tauri_plugin_opener::init::<tauri::Wry>())
         .manage(app_state)
+        .setup(|app| {
+            // 将游戏事件转发给前端
+            let handle = app.handle().clone();
+            let mut receiver = {
+                let state = app.state::<commands::AppState>();
+                let game_manager = state.game_manager.blocking_read();
+                game_manager.subscribe_events()
+            };
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => {
+                            if let Err(e) = handle.emit(event.event_name(), &event) {
+                                warn!("推送游戏事件失败: {}", e);
+                            }
+                            let playback = {
+                                let state = handle.state::<commands::AppState>();
+                                let config_manager = state.config_manager.read().await;
+                                sfx::SfxManager::playback_for(&event, &config_manager.get_config().sfx)
+                            };
+                            if let Some(playback) = playback {
+                                if let Err(e) = handle.emit(sfx::SfxPlayback::EVENT_NAME, &playback) {
+                                    warn!("推送音效失败: {}", e);
+                                }
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => warn!("游戏事件积压，丢弃{}条", skipped),
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+            
+            // 私信按玩家单独的事件名推送
+            let handle = app.handle().clone();
+            let mut private_receiver = {
+                let state = app.state::<commands::AppState>();
+                let game_manager = state.game_manager.blocking_read();
+                game_manager.subscribe_private_messages()
+            };
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    match private_receiver.recv().await {
+                        Ok(message) => {
+                            if let Err(e) = handle.emit(&message.event_name(), &message) {
+                                warn!("推送私信失败: {}", e);
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => warn!("私信积压，丢弃{}条", skipped),
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+            
+            // 用户开启了使用统计时，上报之前累计的计数
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = handle.state::<commands::AppState>();
+                let config = state.config_manager.read().await.get_config().telemetry.clone();
+                let report = state.telemetry.read().await.pending_report(&config);
+                if let Some(report) = report {
+                    match telemetry::send_report(&config.endpoint, &report).await {
+                        Ok(()) => {
+                            let mut telemetry = state.telemetry.write().await;
+                            telemetry.mark_reported(&report);
+                        }
+                        Err(e) => warn!("上报使用统计失败: {}", e),
+                    }
+                }
+            });
+            
+            // 每秒推进阶段计时，投票超时由游戏管理器自动处理；开启自动驱动的对局由后台循环推进
+            let game_manager = app.state::<commands::AppState>().game_manager.clone();
+            tauri::async_runtime::spawn(game_loop::run(game_manager));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_app_config,
             update_llm_config,
             test_llm_connection,
+            list_models,
+            list_local_models,
+            run_diagnostics,
             generate_ai_response,
+            stream_ai_response,
+            cancel_stream,
+            polish_my_speech,
             update_game_config,
+            apply_rule_preset,
+            generate_board,
+            set_pacing_profile,
+            download_sentiment_model,
             start_new_game,
             launch_game,
             get_game_state,
+            get_full_game_state,
             player_vote,
+            signal_vote_intent,
+            undo_last_action,
+            set_sheriff,
+            revoke_vote,
+            claim_role,
+            report_check,
+            get_claims_board,
+            find_similar_statements,
             player_speech,
+            get_current_speaker,
+            pass_turn,
+            raise_objection,
+            resume_control,
             generate_ai_speech,
+            precompute_ai_turns,
+            run_discussion_round,
+            run_night_phase,
+            submit_night_action,
+            get_private_messages,
+            get_private_info,
+            take_playback_queue,
+            get_sound_effect,
+            ask_narrator,
+            transcode_audio_files,
+            send_chat_message,
+            werewolf_chat,
+            get_chat_messages,
+            replace_player_with_ai,
+            restore_human_player,
+            get_ai_decisions,
+            verify_replay_integrity,
+            get_memory_stats,
+            get_session_stats,
+            run_benchmark,
+            get_suspicion_history,
+            explain_suspicion,
+            get_game_history,
+            get_coach_reports,
+            get_role_info,
+            save_note,
+            get_notes,
             end_game,
             export_config,
             import_config,
-            get_app_version
+            get_app_version,
+            check_for_updates,
+            preview_telemetry,
+            create_support_bundle,
+            list_strategy_plugins,
+            run_arena,
+            record_reasoning_case,
+            get_fact_sheet
         ])
-        .run(tauri::generate_context!()) {
-        Ok(_) => {
+        .build(tauri::generate_context!()) {
+        Ok(app) => {
+            app.run(|handle, event| {
+                // 关闭程序时中止还在进行的模型请求和语音合成
+                if let tauri::RunEvent::ExitRequested { .. } = event {
+                    let state = handle.state::<commands::AppState>();
+                    state.ai_tasks.cancel_all("程序退出");
+                    let config = state.config_manager.blocking_read().get_config().telemetry.clone();
+                    state.telemetry.blocking_write().end_session(&config);
+                }
+            });
             info!("应用正常退出");
         },
         Err(e) => {
-            let error_msg = format!("启动 Tauri 应用失败: {}", e);
-            eprintln!("{}", error_msg);
-            
-            // 在 Windows 上显示消息框
-            #[cfg(windows)]
-            {
-                use std::ffi::CString;
-                use std::ptr;
-                
-                unsafe {
-                    let title = CString::new("智狼 (MindWolf) - 错误").unwrap_or_default();
-                    let message = CString::new(error_msg).unwrap_or_default();
-                    
-                    winapi::um::winuser::MessageBoxA(
-                        ptr::null_mut(),
-                        message.as_ptr(),
-                        title.as_ptr(),
-                        winapi::um::winuser::MB_OK | winapi::um::winuser::MB_ICONERROR,
-                    );
-                }
-            }
+            platform::show_error_dialog(ERROR_TITLE, &format!("启动 Tauri 应用失败: {}", e));
         }
     }
 }
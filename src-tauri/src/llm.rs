@@ -1,9 +1,19 @@
-use crate::types::LLMConfig;
+use crate::types::{LLMConfig, LLMProvider};
 use crate::error::{AppResult, AppError};
+use crate::demo_llm::DemoResponder;
+use crate::embeddings;
+use crate::model_catalog::ModelInfo;
+use crate::cancellation::{self, TaskCanceller};
+use crate::llm_stream::SseDecoder;
+use crate::local_llm::{self, LocalProtocol, NdjsonDecoder};
 use reqwest::Client;
 use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::OnceCell;
 use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 use log::{info, warn, error};
 use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
@@ -13,6 +23,9 @@ use tokio_tungstenite::{connect_async, tungstenite::Message};
 pub struct LLMClient {
     client: Client,
     config: LLMConfig,
+    meter: TokenMeter,
+    /// 本地服务的协议，第一次请求时探测
+    local_protocol: Arc<OnceCell<LocalProtocol>>,
 }
 
 impl LLMClient {
@@ -23,28 +36,210 @@ impl LLMClient {
             .build()
             .expect("Failed to create HTTP client");
         
-        Self { client, config }
+        Self { client, config, meter: TokenMeter::new(), local_protocol: Arc::new(OnceCell::new()) }
     }
     
     /// 发送聊天补全请求（传统API）
     pub async fn chat_completion(&self, messages: Vec<ChatMessage>) -> AppResult<String> {
+        self.chat_completion_with(messages, &GenerationParams::default()).await
+    }
+    
+    /// 按本次调用的生成参数发送聊天补全请求，未覆盖的参数使用客户端配置
+    pub async fn chat_completion_with(&self, messages: Vec<ChatMessage>, params: &GenerationParams) -> AppResult<String> {
+        if self.config.provider == LLMProvider::Demo {
+            let prompt = messages.last().map(|m| m.content.as_str()).unwrap_or("");
+            return Ok(DemoResponder::respond(prompt));
+        }
+        
+        // 本地服务没有实时API
+        if self.config.provider == LLMProvider::LocalOllama {
+            return match self.local_protocol().await? {
+                LocalProtocol::OllamaChat => self.ollama_completion(messages, params).await,
+                LocalProtocol::OpenAICompatible => self.traditional_completion(messages, params).await,
+            };
+        }
+        
         if self.config.use_realtime_api {
             // 使用实时API
-            self.realtime_completion(messages).await
+            self.realtime_completion(messages, params, &mut |_: &str| {}).await
         } else {
             // 使用传统API
-            self.traditional_completion(messages).await
+            self.traditional_completion(messages, params).await
         }
     }
     
-    /// 传统聊天补全请求
-    async fn traditional_completion(&self, messages: Vec<ChatMessage>) -> AppResult<String> {
-        let request_body = json!({
+    /// 流式聊天补全，每收到一段文本回调一次，返回完整文本
+    ///
+    /// 传统API走SSE，实时API按文本增量事件回调；演示模式整段回调一次
+    pub async fn chat_completion_stream(
+        &self,
+        messages: Vec<ChatMessage>,
+        params: &GenerationParams,
+        on_delta: &mut (dyn FnMut(&str) + Send)
+    ) -> AppResult<String> {
+        if self.config.provider == LLMProvider::Demo {
+            let prompt = messages.last().map(|m| m.content.as_str()).unwrap_or("");
+            let response = DemoResponder::respond(prompt);
+            on_delta(&response);
+            return Ok(response);
+        }
+        
+        if self.config.provider == LLMProvider::LocalOllama {
+            return match self.local_protocol().await? {
+                LocalProtocol::OllamaChat => self.ollama_stream(messages, params, on_delta).await,
+                LocalProtocol::OpenAICompatible => self.traditional_stream(messages, params, on_delta).await,
+            };
+        }
+        
+        if self.config.use_realtime_api {
+            self.realtime_completion(messages, params, on_delta).await
+        } else {
+            self.traditional_stream(messages, params, on_delta).await
+        }
+    }
+    
+    /// 本地服务的协议，第一次请求时探测并缓存；服务没启动时不缓存，下次再探测
+    async fn local_protocol(&self) -> AppResult<LocalProtocol> {
+        self.local_protocol
+            .get_or_try_init(|| local_llm::detect_protocol(&self.client, &self.config.base_url))
+            .await
+            .copied()
+    }
+    
+    /// Ollama原生/api/chat请求
+    async fn ollama_completion(&self, messages: Vec<ChatMessage>, params: &GenerationParams) -> AppResult<String> {
+        let request_body = local_llm::chat_body(
+            &self.config.model, &messages, params, self.config.temperature, self.config.max_tokens, false
+        );
+        
+        let response_json: Value = self.client
+            .post(format!("{}/api/chat", self.config.base_url))
+            .json(&request_body)
+            .send()
+            .await?
+            .json()
+            .await?;
+        
+        local_llm::check_error(&response_json)?;
+        let content = local_llm::message_content(&response_json)
+            .ok_or_else(|| AppError::LlmApi("响应中未找到内容".to_string()))?;
+        
+        self.record_usage(local_llm::usage(&response_json).as_ref(), &messages, content);
+        Ok(content.to_string())
+    }
+    
+    /// Ollama原生/api/chat流式请求，响应是逐行JSON
+    async fn ollama_stream(
+        &self,
+        messages: Vec<ChatMessage>,
+        params: &GenerationParams,
+        on_delta: &mut (dyn FnMut(&str) + Send)
+    ) -> AppResult<String> {
+        let request_body = local_llm::chat_body(
+            &self.config.model, &messages, params, self.config.temperature, self.config.max_tokens, true
+        );
+        
+        let mut response = self.client
+            .post(format!("{}/api/chat", self.config.base_url))
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            let response_json: Value = response.json().await?;
+            local_llm::check_error(&response_json)?;
+            return Err(AppError::LlmApi(format!("本地模型服务返回{}", status)));
+        }
+        
+        let mut decoder = NdjsonDecoder::new();
+        let mut content = String::new();
+        while let Some(chunk) = response.chunk().await? {
+            for delta in decoder.feed(&chunk)? {
+                on_delta(&delta);
+                content.push_str(&delta);
+            }
+            if decoder.is_done() {
+                break;
+            }
+        }
+        self.record_usage(decoder.usage(), &messages, &content);
+        
+        if content.is_empty() {
+            Err(AppError::LlmApi("未收到有效响应".to_string()))
+        } else {
+            Ok(content)
+        }
+    }
+    
+    /// chat/completions的请求体，流式与非流式共用
+    fn completion_body(&self, messages: &[ChatMessage], params: &GenerationParams) -> Value {
+        let mut request_body = json!({
             "model": self.config.model,
             "messages": messages,
             "max_tokens": self.config.max_tokens,
-            "temperature": self.config.temperature
+            "temperature": params.temperature.unwrap_or(self.config.temperature)
         });
+        if let Some(top_p) = params.top_p {
+            request_body["top_p"] = json!(top_p);
+        }
+        request_body
+    }
+    
+    /// 传统API的SSE流式请求
+    async fn traditional_stream(
+        &self,
+        messages: Vec<ChatMessage>,
+        params: &GenerationParams,
+        on_delta: &mut (dyn FnMut(&str) + Send)
+    ) -> AppResult<String> {
+        let mut request_body = self.completion_body(&messages, params);
+        request_body["stream"] = json!(true);
+        
+        let mut response = self.client
+            .post(format!("{}/v1/chat/completions", self.config.base_url))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("Content-Type", "application/json")
+            .header("Accept", "text/event-stream")
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        // 出错时接口返回普通JSON而不是事件流
+        if !response.status().is_success() {
+            let response_json: Value = response.json().await?;
+            return Err(AppError::LlmApi(
+                response_json.get("error")
+                    .and_then(|e| e.get("message"))
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown API error")
+                    .to_string()
+            ));
+        }
+        
+        let mut decoder = SseDecoder::new();
+        let mut content = String::new();
+        while let Some(chunk) = response.chunk().await? {
+            for delta in decoder.feed(&chunk)? {
+                on_delta(&delta);
+                content.push_str(&delta);
+            }
+            if decoder.is_done() {
+                break;
+            }
+        }
+        self.record_usage(decoder.usage(), &messages, &content);
+        
+        if content.is_empty() {
+            Err(AppError::LlmApi("未收到有效响应".to_string()))
+        } else {
+            Ok(content)
+        }
+    }
+    
+    /// 传统聊天补全请求
+    async fn traditional_completion(&self, messages: Vec<ChatMessage>, params: &GenerationParams) -> AppResult<String> {
+        let request_body = self.completion_body(&messages, params);
         
         let response = self.client
             .post(&format!("{}/v1/chat/completions", self.config.base_url))
@@ -77,11 +272,17 @@ impl LLMClient {
                 "响应中未找到内容".to_string()
             ))?;
         
+        self.record_usage(response_json.get("usage"), &messages, content);
         Ok(content.to_string())
     }
     
-    /// 实时API聊天补全请求
-    async fn realtime_completion(&self, messages: Vec<ChatMessage>) -> AppResult<String> {
+    /// 实时API聊天补全请求，文本增量事件逐段回调
+    async fn realtime_completion(
+        &self,
+        messages: Vec<ChatMessage>,
+        params: &GenerationParams,
+        on_delta: &mut (dyn FnMut(&str) + Send)
+    ) -> AppResult<String> {
         // 1. 创建会话获取临时令牌
         let session_response = self.create_realtime_session().await?;
         
@@ -112,7 +313,7 @@ impl LLMClient {
                 "input_audio_format": self.config.input_audio_format,
                 "output_audio_format": self.config.output_audio_format,
                 "turn_detection": self.config.turn_detection,
-                "temperature": self.config.temperature,
+                "temperature": params.temperature.unwrap_or(self.config.temperature),
                 "max_response_output_tokens": self.config.max_tokens
             }
         });
@@ -150,6 +351,7 @@ impl LLMClient {
         
         // 6. 接收响应
         let mut response_content = String::new();
+        let mut usage = None;
         
         while let Some(message) = ws_receiver.next().await {
             match message {
@@ -160,11 +362,21 @@ impl LLMClient {
                                 "response.content_part.added" => {
                                     if let Some(part) = event.get("part") {
                                         if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+                                            if !text.is_empty() {
+                                                on_delta(text);
+                                            }
                                             response_content.push_str(text);
                                         }
                                     }
                                 }
+                                "response.text.delta" => {
+                                    if let Some(delta) = event.get("delta").and_then(|d| d.as_str()) {
+                                        on_delta(delta);
+                                        response_content.push_str(delta);
+                                    }
+                                }
                                 "response.done" => {
+                                    usage = event.pointer("/response/usage").cloned();
                                     break;
                                 }
                                 "error" => {
@@ -194,6 +406,8 @@ impl LLMClient {
             }
         }
         
+        self.record_usage(usage.as_ref(), &messages, &response_content);
+        
         if response_content.is_empty() {
             Err(AppError::LlmApi("未收到有效响应".to_string()))
         } else {
@@ -201,6 +415,16 @@ impl LLMClient {
         }
     }
     
+    /// 记录一次调用的用量：优先用接口返回的数字（两种API的字段名不同），没有时按字数估算
+    fn record_usage(&self, usage: Option<&Value>, messages: &[ChatMessage], completion: &str) {
+        let count = |keys: [&str; 2]| usage.and_then(|usage| keys.iter().find_map(|key| usage.get(*key)?.as_u64()));
+        let prompt_tokens = count(["prompt_tokens", "input_tokens"])
+            .unwrap_or_else(|| messages.iter().map(|m| estimate_tokens(&m.content)).sum());
+        let completion_tokens = count(["completion_tokens", "output_tokens"])
+            .unwrap_or_else(|| estimate_tokens(completion));
+        self.meter.record(prompt_tokens, completion_tokens);
+    }
+    
     /// 创建实时会话
     async fn create_realtime_session(&self) -> AppResult<Value> {
         let session_body = json!({
@@ -230,6 +454,144 @@ impl LLMClient {
         Ok(response_json)
     }
     
+    /// 计算文本向量
+    pub async fn embedding(&self, input: &str, model: &str) -> AppResult<Vec<f32>> {
+        if self.config.provider == LLMProvider::Demo {
+            return Ok(embeddings::local_embedding(input));
+        }
+        if self.config.provider == LLMProvider::LocalOllama && self.local_protocol().await? == LocalProtocol::OllamaChat {
+            return self.ollama_embedding(input, model).await;
+        }
+        
+        let request_body = json!({
+            "model": model,
+            "input": input
+        });
+        
+        let response = self.client
+            .post(format!("{}/v1/embeddings", self.config.base_url))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("Content-Type", "application/json")
+            .json(&request_body)
+            .send()
+            .await?;
+        
+        let response_json: Value = response.json().await?;
+        
+        if let Some(error) = response_json.get("error") {
+            return Err(AppError::LlmApi(
+                error.get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown API error")
+                    .to_string()
+            ));
+        }
+        
+        let vector = response_json
+            .get("data")
+            .and_then(|data| data.get(0))
+            .and_then(|item| item.get("embedding"))
+            .and_then(|embedding| embedding.as_array())
+            .ok_or_else(|| AppError::LlmApi("响应中未找到向量".to_string()))?
+            .iter()
+            .filter_map(|v| v.as_f64().map(|v| v as f32))
+            .collect();
+        
+        let prompt_tokens = response_json.pointer("/usage/prompt_tokens")
+            .and_then(|t| t.as_u64())
+            .unwrap_or_else(|| estimate_tokens(input));
+        self.meter.record(prompt_tokens, 0);
+        Ok(vector)
+    }
+    
+    /// Ollama原生/api/embed请求
+    async fn ollama_embedding(&self, input: &str, model: &str) -> AppResult<Vec<f32>> {
+        let response_json: Value = self.client
+            .post(format!("{}/api/embed", self.config.base_url))
+            .json(&json!({ "model": model, "input": input }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        
+        local_llm::check_error(&response_json)?;
+        let vector = response_json
+            .pointer("/embeddings/0")
+            .and_then(|embedding| embedding.as_array())
+            .ok_or_else(|| AppError::LlmApi("响应中未找到向量".to_string()))?
+            .iter()
+            .filter_map(|v| v.as_f64().map(|v| v as f32))
+            .collect();
+        
+        let prompt_tokens = response_json.get("prompt_eval_count")
+            .and_then(|t| t.as_u64())
+            .unwrap_or_else(|| estimate_tokens(input));
+        self.meter.record(prompt_tokens, 0);
+        Ok(vector)
+    }
+    
+    /// 查询提供商的模型列表
+    pub async fn list_models(&self) -> AppResult<Vec<ModelInfo>> {
+        let provider = &self.config.provider;
+        if *provider == LLMProvider::Demo {
+            return Ok(vec![ModelInfo::tagged(provider, self.config.model.clone(), None)]);
+        }
+        if *provider == LLMProvider::LocalOllama && self.local_protocol().await? == LocalProtocol::OllamaChat {
+            let tags: Value = self.client
+                .get(format!("{}/api/tags", self.config.base_url))
+                .send()
+                .await?
+                .json()
+                .await?;
+            local_llm::check_error(&tags)?;
+            return Ok(local_llm::model_names(&tags).into_iter()
+                .map(|name| ModelInfo::tagged(provider, name, None))
+                .collect());
+        }
+        
+        let request = self.client.get(format!("{}/v1/models", self.config.base_url));
+        let request = match provider {
+            LLMProvider::Anthropic => request
+                .header("x-api-key", &self.config.api_key)
+                .header("anthropic-version", "2023-06-01"),
+            LLMProvider::Azure => request.header("api-key", &self.config.api_key),
+            _ => request.header("Authorization", format!("Bearer {}", self.config.api_key)),
+        };
+        
+        let response_json: Value = request.send().await?.json().await?;
+        
+        if let Some(error) = response_json.get("error") {
+            return Err(AppError::LlmApi(
+                error.get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown API error")
+                    .to_string()
+            ));
+        }
+        
+        let models = response_json
+            .get("data")
+            .and_then(|data| data.as_array())
+            .ok_or_else(|| AppError::LlmApi("响应中未找到模型列表".to_string()))?
+            .iter()
+            .filter_map(|item| {
+                let id = item.get("id").and_then(|id| id.as_str())?;
+                // 兼容接口对上下文长度的叫法不一
+                let context_length = ["context_length", "context_window", "max_context_length"].iter()
+                    .find_map(|key| item.get(*key).and_then(|v| v.as_u64()))
+                    .map(|length| length as u32);
+                Some(ModelInfo::tagged(provider, id.to_string(), context_length))
+            })
+            .collect();
+        
+        Ok(models)
+    }
+    
+    /// 获取模型名称
+    pub fn model_name(&self) -> &str {
+        &self.config.model
+    }
+    
     /// 测试连接
     pub async fn test_connection(&self) -> AppResult<bool> {
         let test_messages = vec![ChatMessage {
@@ -264,6 +626,68 @@ pub struct ChatMessage {
     pub content_type: Option<String>, // "text", "audio", "function_call"
 }
 
+/// 单次调用的生成参数，None表示沿用客户端配置
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GenerationParams {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+}
+
+/// 累计的模型用量，主备接口共用；计数只增不减，对局统计按开局时的读数做差
+#[derive(Debug, Clone, Default)]
+pub struct TokenMeter {
+    prompt_tokens: Arc<AtomicU64>,
+    completion_tokens: Arc<AtomicU64>,
+    requests: Arc<AtomicU64>,
+}
+
+impl TokenMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn record(&self, prompt_tokens: u64, completion_tokens: u64) {
+        self.prompt_tokens.fetch_add(prompt_tokens, Ordering::Relaxed);
+        self.completion_tokens.fetch_add(completion_tokens, Ordering::Relaxed);
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    pub fn usage(&self) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_tokens.load(Ordering::Relaxed),
+            completion_tokens: self.completion_tokens.load(Ordering::Relaxed),
+            requests: self.requests.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 某一时刻的用量读数
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub requests: u64,
+}
+
+impl TokenUsage {
+    /// 自baseline读数以来新增的用量
+    pub fn since(&self, baseline: &TokenUsage) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_tokens.saturating_sub(baseline.prompt_tokens),
+            completion_tokens: self.completion_tokens.saturating_sub(baseline.completion_tokens),
+            requests: self.requests.saturating_sub(baseline.requests),
+        }
+    }
+}
+
+/// 接口没有返回用量时的粗略估算：中日韩文字约一字一个token，其余约四个字符一个token
+pub fn estimate_tokens(text: &str) -> u64 {
+    let (cjk, other) = text.chars().fold((0u64, 0u64), |(cjk, other), c| {
+        if c.is_ascii() { (cjk, other + 1) } else { (cjk + 1, other) }
+    });
+    cjk + other.div_ceil(4)
+}
+
 /// 重试配置
 #[derive(Clone, Debug)]
 pub struct RetryConfig {
@@ -288,6 +712,7 @@ pub struct LLMManager {
     primary_client: LLMClient,
     fallback_clients: Vec<LLMClient>,
     retry_config: RetryConfig,
+    canceller: TaskCanceller,
 }
 
 impl LLMManager {
@@ -306,16 +731,48 @@ impl LLMManager {
             primary_client,
             fallback_clients,
             retry_config: RetryConfig::default(),
+            canceller: TaskCanceller::new(),
         }
     }
     
+    /// 与游戏共用取消控制，结束对局或切换阶段时进行中的请求被中止
+    pub fn with_canceller(mut self, canceller: TaskCanceller) -> Self {
+        self.canceller = canceller;
+        self
+    }
+    
+    /// 与游戏共用用量计数，更换模型配置后对局的用量统计不中断
+    pub fn with_token_meter(mut self, meter: TokenMeter) -> Self {
+        for client in std::iter::once(&mut self.primary_client).chain(self.fallback_clients.iter_mut()) {
+            client.meter = meter.clone();
+        }
+        self
+    }
+    
     /// 生成文本，支持重试和备用
     pub async fn generate_with_fallback(&self, prompt: String) -> AppResult<String> {
+        self.generate_with_source(prompt).await.map(|(result, _)| result)
+    }
+    
+    /// 生成文本，同时返回实际提供响应的模型标识（用于决策审计）
+    ///
+    /// 取消时中止正在进行的请求和重试等待，返回取消错误
+    pub async fn generate_with_source(&self, prompt: String) -> AppResult<(String, String)> {
+        self.generate_with_params(prompt, GenerationParams::default()).await
+    }
+    
+    /// 按本次调用的生成参数生成文本，主备接口使用同样的参数
+    pub async fn generate_with_params(&self, prompt: String, params: GenerationParams) -> AppResult<(String, String)> {
+        let token = self.canceller.token();
+        cancellation::run_cancellable(&token, self.generate_uncancelled(prompt, params)).await
+    }
+    
+    async fn generate_uncancelled(&self, prompt: String, params: GenerationParams) -> AppResult<(String, String)> {
         // 尝试主要API
-        match self.try_generate_with_retry(&self.primary_client, &prompt).await {
+        match self.try_generate_with_retry(&self.primary_client, &prompt, &params).await {
             Ok(result) => {
                 info!("主要LLM API调用成功");
-                return Ok(result);
+                return Ok((result, format!("primary:{}", self.primary_client.model_name())));
             }
             Err(e) => {
                 warn!("主要LLM API调用失败: {}", e);
@@ -324,10 +781,10 @@ impl LLMManager {
         
         // 尝试备用API
         for (index, fallback_client) in self.fallback_clients.iter().enumerate() {
-            match self.try_generate_with_retry(fallback_client, &prompt).await {
+            match self.try_generate_with_retry(fallback_client, &prompt, &params).await {
                 Ok(result) => {
                     info!("备用LLM API {} 调用成功", index);
-                    return Ok(result);
+                    return Ok((result, format!("fallback{}:{}", index, fallback_client.model_name())));
                 }
                 Err(e) => {
                     warn!("备用LLM API {} 调用失败: {}", index, e);
@@ -338,14 +795,59 @@ impl LLMManager {
         Err(AppError::LlmApi("所有LLM API都失败了".to_string()))
     }
     
+    /// 流式生成：主接口边生成边回调；主接口失败时改走非流式的重试与备用，此时以返回的完整文本为准
+    ///
+    /// 对局取消和调用方给的流令牌都会中止生成
+    pub async fn generate_streaming(
+        &self,
+        prompt: String,
+        params: GenerationParams,
+        stream: &CancellationToken,
+        on_delta: &mut (dyn FnMut(&str) + Send)
+    ) -> AppResult<(String, String)> {
+        let token = self.canceller.token();
+        let task = async {
+            let messages = Self::user_messages(&prompt);
+            match self.primary_client.chat_completion_stream(messages, &params, on_delta).await {
+                Ok(result) => {
+                    info!("主要LLM API流式调用成功");
+                    return Ok((result, format!("primary:{}", self.primary_client.model_name())));
+                }
+                Err(e) => {
+                    warn!("主要LLM API流式调用失败，改用非流式调用: {}", e);
+                }
+            }
+            self.generate_uncancelled(prompt.clone(), params).await
+        };
+        cancellation::run_cancellable(&token, cancellation::run_cancellable(stream, task)).await
+    }
+    
+    /// 计算文本向量，主接口失败时依次尝试备用接口
+    pub async fn embed_with_fallback(&self, input: &str, model: &str) -> AppResult<Vec<f32>> {
+        let token = self.canceller.token();
+        cancellation::run_cancellable(&token, self.embed_uncancelled(input, model)).await
+    }
+    
+    async fn embed_uncancelled(&self, input: &str, model: &str) -> AppResult<Vec<f32>> {
+        for client in std::iter::once(&self.primary_client).chain(self.fallback_clients.iter()) {
+            match client.embedding(input, model).await {
+                Ok(vector) => return Ok(vector),
+                Err(e) => warn!("向量接口调用失败({}): {}", client.model_name(), e),
+            }
+        }
+        
+        Err(AppError::LlmApi("所有向量接口都失败了".to_string()))
+    }
+    
     /// 带重试的生成
     async fn try_generate_with_retry(
         &self, 
         client: &LLMClient, 
-        prompt: &str
+        prompt: &str,
+        params: &GenerationParams
     ) -> AppResult<String> {
         for attempt in 1..=self.retry_config.max_attempts {
-            match self.generate_single(client, prompt).await {
+            match self.generate_single(client, prompt, params).await {
                 Ok(result) => return Ok(result),
                 Err(e) if attempt < self.retry_config.max_attempts => {
                     let delay = std::cmp::min(
@@ -373,9 +875,14 @@ impl LLMManager {
     async fn generate_single(
         &self,
         client: &LLMClient,
-        prompt: &str
+        prompt: &str,
+        params: &GenerationParams
     ) -> AppResult<String> {
-        let messages = vec![
+        client.chat_completion_with(Self::user_messages(prompt), params).await
+    }
+    
+    fn user_messages(prompt: &str) -> Vec<ChatMessage> {
+        vec![
             ChatMessage {
                 role: "user".to_string(),
                 content: prompt.to_string(),
@@ -383,9 +890,7 @@ impl LLMManager {
                 timestamp: Some(chrono::Utc::now()),
                 content_type: Some("text".to_string()),
             }
-        ];
-        
-        client.chat_completion(messages).await
+        ]
     }
     
     /// 测试所有LLM连接
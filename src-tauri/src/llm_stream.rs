@@ -0,0 +1,147 @@
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// 流式生成通过Tauri通道推给前端的事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// 新生成的一段文本
+    Delta { stream_id: String, text: String },
+    /// 生成结束；中途改用了备用接口时增量并不完整，以content为准
+    Done { stream_id: String, content: String, served_by: String },
+    Failed { stream_id: String, message: String },
+    Cancelled { stream_id: String },
+}
+
+/// 把chat/completions的SSE字节流拆成文本增量
+///
+/// 网络分块可能断在任意位置（包括多字节字符中间），不完整的行留到下一块再解析
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    done: bool,
+    usage: Option<Value>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 喂入一块数据，返回其中完整事件带来的文本增量；流里出现接口错误时返回错误
+    pub fn feed(&mut self, chunk: &[u8]) -> AppResult<Vec<String>> {
+        self.buffer.extend_from_slice(chunk);
+        let mut deltas = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let data = match line.trim().strip_prefix("data:") {
+                Some(data) => data.trim(),
+                None => continue,
+            };
+            if data == "[DONE]" {
+                self.done = true;
+                continue;
+            }
+            let event: Value = match serde_json::from_str(data) {
+                Ok(event) => event,
+                Err(_) => continue,
+            };
+            if let Some(error) = event.get("error") {
+                return Err(AppError::LlmApi(
+                    error.get("message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("Unknown API error")
+                        .to_string()
+                ));
+            }
+            if let Some(usage) = event.get("usage").filter(|usage| !usage.is_null()) {
+                self.usage = Some(usage.clone());
+            }
+            if let Some(text) = event.pointer("/choices/0/delta/content").and_then(|c| c.as_str()) {
+                if !text.is_empty() {
+                    deltas.push(text.to_string());
+                }
+            }
+        }
+        Ok(deltas)
+    }
+
+    /// 是否已收到结束标记
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// 接口在流末尾附带的用量，没有附带时为None
+    pub fn usage(&self) -> Option<&Value> {
+        self.usage.as_ref()
+    }
+}
+
+/// 进行中的流，前端凭流ID单独取消某一次生成
+///
+/// 每个流的令牌是AI任务令牌的子令牌，结束对局或切换阶段时一并取消
+#[derive(Debug, Clone, Default)]
+pub struct StreamRegistry {
+    active: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+impl StreamRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一个新的流，返回流ID和它的取消令牌
+    pub fn register(&self, parent: &CancellationToken) -> (String, CancellationToken) {
+        let stream_id = crate::utils::generate_id();
+        let token = parent.child_token();
+        self.active.lock().unwrap_or_else(|e| e.into_inner()).insert(stream_id.clone(), token.clone());
+        (stream_id, token)
+    }
+
+    /// 取消一个流，流已结束或不存在时返回false
+    pub fn cancel(&self, stream_id: &str) -> bool {
+        match self.active.lock().unwrap_or_else(|e| e.into_inner()).remove(stream_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 流结束后移除登记
+    pub fn finish(&self, stream_id: &str) {
+        self.active.lock().unwrap_or_else(|e| e.into_inner()).remove(stream_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_handles_split_chunks() {
+        let stream = "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+                      data: {\"choices\":[{\"delta\":{\"content\":\"我是\"}}]}\n\n\
+                      data: {\"choices\":[{\"delta\":{\"content\":\"预言家\"}}]}\n\n\
+                      data: [DONE]\n\n";
+        let bytes = stream.as_bytes();
+        // 在“预”字的字节中间断开
+        let split = stream.find("预言家").unwrap() + 1;
+
+        let mut decoder = SseDecoder::new();
+        let mut text = decoder.feed(&bytes[..split]).unwrap().concat();
+        assert!(!decoder.is_done());
+        text.push_str(&decoder.feed(&bytes[split..]).unwrap().concat());
+        assert_eq!(text, "我是预言家");
+        assert!(decoder.is_done());
+
+        let mut failing = SseDecoder::new();
+        assert!(failing.feed(b"data: {\"error\":{\"message\":\"rate limited\"}}\n").is_err());
+    }
+}
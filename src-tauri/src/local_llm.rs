@@ -0,0 +1,157 @@
+use crate::error::{AppError, AppResult};
+use crate::llm::{ChatMessage, GenerationParams};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+use log::info;
+
+/// Ollama的默认地址；llama.cpp server默认是http://localhost:8080
+pub const DEFAULT_LOCAL_URL: &str = "http://localhost:11434";
+
+/// 探测本地服务协议的超时，本地服务没响应时不值得等满请求超时
+const DETECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 本地模型服务使用的协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalProtocol {
+    /// Ollama原生的/api/chat
+    OllamaChat,
+    /// llama.cpp server等提供的OpenAI兼容接口（/v1/chat/completions）
+    OpenAICompatible,
+}
+
+/// 探测本地服务：有/api/tags的是Ollama，否则按OpenAI兼容接口处理；服务没启动时返回错误
+pub async fn detect_protocol(client: &Client, base_url: &str) -> AppResult<LocalProtocol> {
+    let tags = client.get(format!("{}/api/tags", base_url))
+        .timeout(DETECT_TIMEOUT)
+        .send()
+        .await;
+    let protocol = match tags {
+        Ok(response) if response.status().is_success() => LocalProtocol::OllamaChat,
+        Ok(_) => LocalProtocol::OpenAICompatible,
+        Err(e) => return Err(AppError::LlmApi(format!("无法连接本地模型服务{}: {}", base_url, e))),
+    };
+    info!("本地模型服务{}使用{:?}协议", base_url, protocol);
+    Ok(protocol)
+}
+
+/// /api/chat的请求体，生成参数放在options里
+pub fn chat_body(model: &str, messages: &[ChatMessage], params: &GenerationParams, temperature: f32, max_tokens: u32, stream: bool) -> Value {
+    let messages: Vec<Value> = messages.iter()
+        .map(|m| json!({ "role": m.role, "content": m.content }))
+        .collect();
+    let mut options = json!({
+        "temperature": params.temperature.unwrap_or(temperature),
+        "num_predict": max_tokens
+    });
+    if let Some(top_p) = params.top_p {
+        options["top_p"] = json!(top_p);
+    }
+    json!({
+        "model": model,
+        "messages": messages,
+        "stream": stream,
+        "options": options
+    })
+}
+
+/// Ollama把错误放在error字段里，是字符串而不是对象
+pub fn check_error(response: &Value) -> AppResult<()> {
+    match response.get("error").and_then(|e| e.as_str()) {
+        Some(message) => Err(AppError::LlmApi(message.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// 一条非流式响应或一行流式响应里的文本
+pub fn message_content(response: &Value) -> Option<&str> {
+    response.pointer("/message/content").and_then(|c| c.as_str())
+}
+
+/// 响应里的用量（提示词token数，生成token数），流式时只有最后一行带
+pub fn usage(response: &Value) -> Option<Value> {
+    let prompt = response.get("prompt_eval_count")?.as_u64()?;
+    let completion = response.get("eval_count")?.as_u64()?;
+    Some(json!({ "prompt_tokens": prompt, "completion_tokens": completion }))
+}
+
+/// 流式响应是逐行的JSON（不是SSE），不完整的行留到下一块再解析
+#[derive(Debug, Default)]
+pub struct NdjsonDecoder {
+    buffer: Vec<u8>,
+    done: bool,
+    usage: Option<Value>,
+}
+
+impl NdjsonDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> AppResult<Vec<String>> {
+        self.buffer.extend_from_slice(chunk);
+        let mut deltas = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line: Value = match serde_json::from_slice(&line) {
+                Ok(line) => line,
+                Err(_) => continue,
+            };
+            check_error(&line)?;
+            if let Some(text) = message_content(&line).filter(|text| !text.is_empty()) {
+                deltas.push(text.to_string());
+            }
+            if line.get("done").and_then(|d| d.as_bool()).unwrap_or(false) {
+                self.usage = usage(&line);
+                self.done = true;
+            }
+        }
+        Ok(deltas)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn usage(&self) -> Option<&Value> {
+        self.usage.as_ref()
+    }
+}
+
+/// /api/tags里的模型名
+pub fn model_names(tags: &Value) -> Vec<String> {
+    tags.get("models")
+        .and_then(|models| models.as_array())
+        .map(|models| models.iter()
+            .filter_map(|m| m.get("name").or_else(|| m.get("model")).and_then(|n| n.as_str()))
+            .map(|name| name.to_string())
+            .collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_ollama_stream() {
+        let stream = concat!(
+            "{\"model\":\"qwen2.5\",\"message\":{\"role\":\"assistant\",\"content\":\"我跳\"},\"done\":false}\n",
+            "{\"model\":\"qwen2.5\",\"message\":{\"role\":\"assistant\",\"content\":\"预言家\"},\"done\":false}\n",
+            "{\"model\":\"qwen2.5\",\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"prompt_eval_count\":120,\"eval_count\":8}\n",
+        );
+        let bytes = stream.as_bytes();
+        let split = stream.find("预言家").unwrap() + 2;
+
+        let mut decoder = NdjsonDecoder::new();
+        let mut text = decoder.feed(&bytes[..split]).unwrap().concat();
+        text.push_str(&decoder.feed(&bytes[split..]).unwrap().concat());
+        assert_eq!(text, "我跳预言家");
+        assert!(decoder.is_done());
+        assert_eq!(decoder.usage().unwrap()["completion_tokens"], 8);
+
+        assert!(NdjsonDecoder::new().feed(b"{\"error\":\"model not found\"}\n").is_err());
+        let tags = json!({ "models": [{ "name": "qwen2.5:7b" }, { "name": "llama3.1:8b" }] });
+        assert_eq!(model_names(&tags), vec!["qwen2.5:7b", "llama3.1:8b"]);
+    }
+}
@@ -5,6 +5,14 @@
 mod windows;
 
 fn main() {
+    // 便携模式要在初始化日志之前确定，日志也写进数据目录
+    #[cfg(windows)]
+    {
+        if let Err(e) = windows::configure_portable() {
+            eprintln!("Failed to configure portable mode: {}", e);
+        }
+    }
+    
     // 初始化日志到文件和控制台
     #[cfg(debug_assertions)]
     env_logger::Builder::from_default_env()
@@ -13,22 +21,19 @@ fn main() {
     
     #[cfg(not(debug_assertions))]
     {
-        // 在发布模式下，尝试写入到可执行文件目录的日志文件
-        let exe_path = std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("."));
-        let log_dir = exe_path.parent().unwrap_or_else(|| std::path::Path::new(".")).join("logs");
+        // 在发布模式下写入数据目录下的日志文件
+        let log_dir = mindwolf_lib::paths::data_paths().logs_dir();
         let _ = std::fs::create_dir_all(&log_dir);
         
-        env_logger::Builder::from_default_env()
-            .filter_level(log::LevelFilter::Info)
-            .init();
-    }
-    
-    // Windows便携式配置
-    #[cfg(windows)]
-    {
-        if let Err(e) = windows::configure_portable() {
-            eprintln!("Failed to configure portable mode: {}", e);
+        let mut builder = env_logger::Builder::from_default_env();
+        builder.filter_level(log::LevelFilter::Info);
+        match std::fs::OpenOptions::new().create(true).append(true).open(log_dir.join("mindwolf.log")) {
+            Ok(file) => {
+                builder.target(env_logger::Target::Pipe(Box::new(file)));
+            }
+            Err(e) => eprintln!("Failed to open log file: {}", e),
         }
+        builder.init();
     }
     
     mindwolf_lib::run()
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// 定长环形缓冲区，写满后淘汰最旧的元素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    /// 累计淘汰的元素数
+    evicted: u64,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: VecDeque::with_capacity(capacity.min(64)),
+            capacity: capacity.max(1),
+            evicted: 0,
+        }
+    }
+
+    /// 追加元素，返回被淘汰的最旧元素（调用方可以据此落库）
+    pub fn push(&mut self, item: T) -> Option<T> {
+        self.items.push_back(item);
+        if self.items.len() > self.capacity {
+            self.evicted += 1;
+            self.items.pop_front()
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl<T: ApproxSize> RingBuffer<T> {
+    /// 统计缓冲区的内存占用
+    pub fn stats(&self, component: &str) -> MemoryStats {
+        MemoryStats {
+            component: component.to_string(),
+            entries: self.items.len(),
+            capacity: Some(self.capacity),
+            evicted: self.evicted,
+            approx_bytes: self.items.iter().map(ApproxSize::approx_bytes).sum(),
+        }
+    }
+}
+
+/// 粗略估算对象占用的内存（字节），用于内存统计
+pub trait ApproxSize {
+    fn approx_bytes(&self) -> usize;
+}
+
+/// 单个组件的内存统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryStats {
+    pub component: String,
+    /// 当前驻留内存的条目数
+    pub entries: usize,
+    /// 容量上限，None表示不设上限
+    pub capacity: Option<usize>,
+    /// 已淘汰（或转存到数据库）的条目数
+    pub evicted: u64,
+    pub approx_bytes: usize,
+}
+
+impl MemoryStats {
+    /// 合并同类组件的统计（例如所有AI代理的发言记忆）
+    pub fn merge(component: &str, stats: impl IntoIterator<Item = MemoryStats>) -> MemoryStats {
+        stats.into_iter().fold(
+            MemoryStats {
+                component: component.to_string(),
+                entries: 0,
+                capacity: None,
+                evicted: 0,
+                approx_bytes: 0,
+            },
+            |mut total, s| {
+                total.entries += s.entries;
+                total.capacity = match (total.capacity, s.capacity) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (None, capacity) | (capacity, None) => capacity,
+                };
+                total.evicted += s.evicted;
+                total.approx_bytes += s.approx_bytes;
+                total
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl ApproxSize for String {
+        fn approx_bytes(&self) -> usize {
+            self.len()
+        }
+    }
+
+    #[test]
+    fn test_full_buffer_evicts_the_oldest() {
+        let mut buffer = RingBuffer::new(2);
+        assert_eq!(buffer.push("一".to_string()), None);
+        assert_eq!(buffer.push("二".to_string()), None);
+        assert_eq!(buffer.push("三".to_string()), Some("一".to_string()));
+
+        let items: Vec<&String> = buffer.iter().collect();
+        assert_eq!(items, ["二", "三"]);
+        let stats = buffer.stats("test");
+        assert_eq!((stats.entries, stats.capacity, stats.evicted), (2, Some(2), 1));
+        assert_eq!(stats.approx_bytes, "二".len() + "三".len());
+    }
+
+    #[test]
+    fn test_merge_sums_counts_and_bounded_capacities() {
+        let bounded = MemoryStats { component: "a".to_string(), entries: 3, capacity: Some(4), evicted: 1, approx_bytes: 10 };
+        let unbounded = MemoryStats { component: "b".to_string(), entries: 2, capacity: None, evicted: 0, approx_bytes: 5 };
+
+        let total = MemoryStats::merge("all", [bounded.clone(), bounded, unbounded]);
+        assert_eq!(total.component, "all");
+        assert_eq!((total.entries, total.capacity, total.evicted, total.approx_bytes), (8, Some(8), 2, 25));
+    }
+}
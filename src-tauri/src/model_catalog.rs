@@ -0,0 +1,135 @@
+use crate::error::{AppError, AppResult};
+use crate::llm::LLMClient;
+use crate::types::{LLMConfig, LLMProvider};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+
+/// 模型列表缓存有效期
+const CATALOG_TTL: Duration = Duration::from_secs(600);
+
+/// 已知模型系列的上下文长度，接口没有返回时使用（按前缀匹配，越具体越靠前）
+const KNOWN_CONTEXT_LENGTHS: [(&str, u32); 9] = [
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("claude", 200_000),
+    ("deepseek", 64_000),
+];
+
+/// 模型目录中的一个模型及其能力
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelInfo {
+    pub id: String,
+    /// 支持JSON输出模式
+    pub json_mode: bool,
+    /// 支持实时语音API
+    pub realtime: bool,
+    /// 向量模型，不能用于对话
+    pub embedding: bool,
+    pub context_length: Option<u32>,
+}
+
+impl ModelInfo {
+    /// 按模型ID推断能力，接口返回了上下文长度时以接口为准
+    pub fn tagged(provider: &LLMProvider, id: String, context_length: Option<u32>) -> Self {
+        let lower = id.to_lowercase();
+        let realtime = lower.contains("realtime");
+        let embedding = lower.contains("embedding") || lower.contains("embed-");
+        let json_mode = !embedding && match provider {
+            LLMProvider::Anthropic => false,
+            LLMProvider::Demo => true,
+            // Ollama和llama.cpp都能按JSON格式约束输出
+            LLMProvider::LocalOllama => true,
+            _ => lower.starts_with("gpt-4") || lower.starts_with("gpt-3.5-turbo") || lower.starts_with("o1")
+                || lower.starts_with("o3") || lower.contains("deepseek") || lower.contains("qwen"),
+        };
+        let context_length = context_length.or_else(|| {
+            KNOWN_CONTEXT_LENGTHS.iter()
+                .find(|(prefix, _)| lower.starts_with(prefix))
+                .map(|(_, length)| *length)
+        });
+
+        Self { id, json_mode, realtime, embedding, context_length }
+    }
+}
+
+struct CachedCatalog {
+    models: Vec<ModelInfo>,
+    fetched_at: Instant,
+}
+
+/// 按提供商和接口地址缓存的模型目录
+#[derive(Default)]
+pub struct ModelCatalog {
+    cache: HashMap<(LLMProvider, String), CachedCatalog>,
+}
+
+impl ModelCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取模型列表，缓存未过期时直接返回缓存
+    pub async fn models(&mut self, config: &LLMConfig, refresh: bool) -> AppResult<Vec<ModelInfo>> {
+        let key = (config.provider.clone(), config.base_url.clone());
+        if !refresh {
+            if let Some(cached) = self.cache.get(&key).filter(|c| c.fetched_at.elapsed() < CATALOG_TTL) {
+                return Ok(cached.models.clone());
+            }
+        }
+
+        let models = LLMClient::new(config.clone()).list_models().await?;
+        info!("获取到{:?}的{}个模型", config.provider, models.len());
+        self.cache.insert(key, CachedCatalog { models: models.clone(), fetched_at: Instant::now() });
+        Ok(models)
+    }
+
+    /// 校验配置的模型：必须在目录中，且不能是向量模型；开启实时API时必须支持实时
+    ///
+    /// 取不到模型列表（部分兼容接口没有该端点）时只记录警告，不阻止使用
+    pub async fn validate(&mut self, config: &LLMConfig) -> AppResult<()> {
+        let models = match self.models(config, false).await {
+            Ok(models) if !models.is_empty() => models,
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                warn!("无法获取模型列表，跳过模型校验: {}", e);
+                return Ok(());
+            }
+        };
+
+        let model = models.iter().find(|m| m.id == config.model)
+            .ok_or_else(|| AppError::Config(format!("模型{}不在{:?}的模型列表中，请检查模型名称", config.model, config.provider)))?;
+        if model.embedding {
+            return Err(AppError::Config(format!("{}是向量模型，不能用于对话", model.id)));
+        }
+        if config.use_realtime_api && !model.realtime {
+            return Err(AppError::Config(format!("{}不支持实时API", model.id)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capability_tags() {
+        let realtime = ModelInfo::tagged(&LLMProvider::OpenAI, "gpt-4o-realtime-preview-2024-12-17".to_string(), None);
+        assert!(realtime.realtime && realtime.json_mode);
+        assert_eq!(realtime.context_length, Some(128_000));
+
+        let embedding = ModelInfo::tagged(&LLMProvider::OpenAI, "text-embedding-3-small".to_string(), None);
+        assert!(embedding.embedding && !embedding.json_mode);
+
+        let claude = ModelInfo::tagged(&LLMProvider::Anthropic, "claude-3-5-sonnet-latest".to_string(), Some(100_000));
+        assert!(!claude.json_mode);
+        assert_eq!(claude.context_length, Some(100_000));
+    }
+}
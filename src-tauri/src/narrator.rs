@@ -0,0 +1,266 @@
+use crate::types::*;
+use crate::events::GameEvent;
+use crate::roles;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+
+/// 新手提示：主持人用规则语言解释刚刚发生的事
+///
+/// 只通过事件总线推送给前端，不写入发言记录，AI的提示词里看不到
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeginnerTip {
+    /// 规则标识，同一条规则每局只解释一次
+    pub rule: String,
+    pub content: String,
+    /// 提示涉及的角色
+    pub related_role: Option<RoleType>,
+}
+
+impl BeginnerTip {
+    fn new(rule: &str, content: &str, related_role: Option<RoleType>) -> Self {
+        Self {
+            rule: rule.to_string(),
+            content: content.to_string(),
+            related_role,
+        }
+    }
+}
+
+/// 主持人播报的详略
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum NarratorVerbosity {
+    /// 完整的仪式用语，如“天黑请闭眼”
+    #[default]
+    Full,
+    /// 只报关键信息
+    Terse,
+}
+
+/// 主持人语音播报配置，使用与玩家不同的独立音色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NarratorVoiceConfig {
+    pub enabled: bool,
+    pub voice_name: String,
+    pub speed: f32,
+    pub verbosity: NarratorVerbosity,
+}
+
+impl Default for NarratorVoiceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            voice_name: "zh-CN-YunyangNeural".to_string(),
+            speed: 0.9,
+            verbosity: NarratorVerbosity::Full,
+        }
+    }
+}
+
+/// 主持人 - 负责生成系统公告
+pub struct Narrator;
+
+impl Narrator {
+    /// 主持人公告在发言记录中的发言者ID
+    pub const SPEAKER: &'static str = "narrator";
+
+    /// 生成死亡公告，只公开身份公开规则允许的信息
+    pub fn death_announcement(player_name: &str, reveal: &DeathReveal) -> String {
+        match (&reveal.revealed_role, &reveal.revealed_faction) {
+            (Some(role), _) => format!("{}出局，身份是{}。", player_name, utils::get_role_name(role)),
+            (None, Some(faction)) => format!("{}出局，属于{}。", player_name, utils::get_faction_name(faction)),
+            (None, None) => format!("{}出局。", player_name),
+        }
+    }
+
+    /// 生成正式身份声明公告
+    pub fn role_claim(player_name: &str, role: &RoleType) -> String {
+        format!("{}正式声明自己是{}。", player_name, utils::get_role_name(role))
+    }
+
+    /// 生成查验报告公告
+    pub fn check_report(reporter_name: &str, target_name: &str, is_werewolf: bool) -> String {
+        let result = if is_werewolf { "狼人" } else { "好人" };
+        format!("{}报告查验结果：{}是{}。", reporter_name, target_name, result)
+    }
+
+    /// 根据事件生成主持人的语音播报，只播报公开信息
+    pub fn narration(event: &GameEvent, state: &GameState, verbosity: NarratorVerbosity) -> Option<String> {
+        let full = verbosity == NarratorVerbosity::Full;
+        let name_of = |id: &str| state.players.iter()
+            .chain(state.dead_players.iter())
+            .find(|p| p.id == id)
+            .map(|p| p.display_name())
+            .unwrap_or_else(|| id.to_string());
+
+        let text = match event {
+            GameEvent::PhaseChanged { phase: GamePhase::Night, day } if full => {
+                format!("天黑请闭眼。第{}夜，请有夜间行动的角色依次睁眼行动。", day)
+            }
+            GameEvent::PhaseChanged { phase: GamePhase::Night, day } => format!("第{}夜。", day),
+            GameEvent::PhaseChanged { phase: GamePhase::DayDiscussion, day } if full => {
+                format!("天亮了，请大家睁眼。第{}天，请按座位顺序依次发言。", day)
+            }
+            GameEvent::PhaseChanged { phase: GamePhase::DayDiscussion, day } => format!("第{}天，开始发言。", day),
+            GameEvent::PhaseChanged { phase: GamePhase::Voting, .. } if full => {
+                "发言结束，现在开始投票，请选出你要放逐的玩家。".to_string()
+            }
+            GameEvent::PhaseChanged { phase: GamePhase::Voting, .. } => "开始投票。".to_string(),
+            GameEvent::PhaseChanged { phase: GamePhase::Pk, .. } if full => "请平票的玩家依次发言，随后重新投票。".to_string(),
+            GameEvent::PhaseChanged { phase: GamePhase::Pk, .. } => "PK发言。".to_string(),
+            GameEvent::PhaseChanged { phase: GamePhase::LastWords, .. } if full => "请出局的玩家发表遗言。".to_string(),
+            GameEvent::PhaseChanged { phase: GamePhase::LastWords, .. } => "遗言。".to_string(),
+            GameEvent::DawnAnnounced { announcement } if announcement.peaceful => {
+                if full { "昨晚是平安夜。".to_string() } else { "平安夜。".to_string() }
+            }
+            GameEvent::PlayerDied { reveal } => Self::death_announcement(&name_of(&reveal.player_id), reveal),
+            GameEvent::VoteResolved { outcome } if outcome.eliminated.is_none() => {
+                match (outcome.tied.is_empty(), full) {
+                    (false, true) if outcome.pk => format!(
+                        "{}平票，进入PK。",
+                        outcome.tied.iter().map(|id| name_of(id)).collect::<Vec<_>>().join("、")
+                    ),
+                    (false, false) if outcome.pk => "平票，进入PK。".to_string(),
+                    (false, true) => format!(
+                        "{}平票，本轮无人出局。",
+                        outcome.tied.iter().map(|id| name_of(id)).collect::<Vec<_>>().join("、")
+                    ),
+                    (false, false) => "平票，无人出局。".to_string(),
+                    (true, _) => "无人出局。".to_string(),
+                }
+            }
+            GameEvent::GameEnded { result } if full => {
+                format!("游戏结束，{}获胜。", utils::get_faction_name(&result.winner))
+            }
+            GameEvent::GameEnded { result } => format!("{}胜。", utils::get_faction_name(&result.winner)),
+            _ => return None,
+        };
+        Some(text)
+    }
+
+    /// 根据事件生成新手提示，只使用公开信息和本局规则
+    pub fn beginner_tips(event: &GameEvent, state: &GameState) -> Vec<BeginnerTip> {
+        let config = &state.game_config;
+        let has_role = |role: RoleType| config.role_distribution.get(&role).copied().unwrap_or(0) > 0;
+        let mut tips = Vec::new();
+
+        match event {
+            GameEvent::PhaseChanged { phase: GamePhase::Night, day } => {
+                // 行动顺序取自角色知识库，同一顺位的角色只列一次
+                let mut order: Vec<&'static str> = Vec::new();
+                let mut last_order = None;
+                for info in roles::ALL_ROLES.iter().filter(|r| has_role((*r).clone())).map(roles::role_info) {
+                    if info.night_order.is_some() && info.night_order != last_order {
+                        order.push(info.name);
+                        last_order = info.night_order;
+                    }
+                }
+                tips.push(BeginnerTip {
+                    rule: "night_order".to_string(),
+                    content: format!("夜晚按{}的顺序行动，所有结果在天亮时统一公布。", order.join("、")),
+                    related_role: None,
+                });
+                if has_role(RoleType::Witch) {
+                    tips.push(BeginnerTip::new(
+                        "witch_potions",
+                        "女巫的解药只能使用一次，毒药也只能使用一次。",
+                        Some(RoleType::Witch),
+                    ));
+                }
+                if has_role(RoleType::Witch) && has_role(RoleType::Guard) && config.night_rules.guard_heal_conflict_kills {
+                    tips.push(BeginnerTip::new(
+                        "guard_heal_conflict",
+                        "本局采用“同守同救死”：同一晚既被守卫守护又被女巫救的玩家仍然会死亡。",
+                        Some(RoleType::Guard),
+                    ));
+                }
+                if *day == 1 && has_role(RoleType::Seer) {
+                    let content = match config.night_rules.first_night_seer {
+                        FirstNightSeerRule::Standard => "预言家每晚可以查验一名玩家是好人还是狼人。",
+                        FirstNightSeerRule::GoldWater => "本局预言家开局自带一个随机好人的查验结果（金水），每晚还能再查一人。",
+                        FirstNightSeerRule::DoubleCheck => "本局预言家首夜可以查验两名玩家，之后每晚查验一人。",
+                    };
+                    tips.push(BeginnerTip::new("seer_check", content, Some(RoleType::Seer)));
+                }
+                if *day == 1 {
+                    if let Some(role) = &config.passive_wolf {
+                        let info = roles::role_info(role);
+                        tips.push(BeginnerTip {
+                            rule: "passive_wolf".to_string(),
+                            content: format!("本局有{}：{}。", info.name, info.abilities.join("，")),
+                            related_role: Some(role.clone()),
+                        });
+                    }
+                }
+            }
+            GameEvent::PhaseChanged { phase: GamePhase::DayDiscussion, .. } => {
+                tips.push(BeginnerTip::new(
+                    "day_discussion",
+                    "白天按座位轮流发言，可以声明身份、公布查验结果，也可以表达投票意向。",
+                    None,
+                ));
+            }
+            GameEvent::PhaseChanged { phase: GamePhase::Voting, .. } => {
+                tips.push(BeginnerTip::new(
+                    "voting",
+                    "每名存活玩家投一票，得票最多的玩家出局。",
+                    None,
+                ));
+            }
+            GameEvent::PhaseChanged { phase: GamePhase::Pk, .. } => {
+                let rule = match config.vote_rules.revote_tie {
+                    RevoteTiePolicy::NoElimination => "重新投票仍然平票时无人出局。",
+                    RevoteTiePolicy::Random => "重新投票仍然平票时，在平票的玩家中随机放逐一人。",
+                };
+                tips.push(BeginnerTip::new(
+                    "pk",
+                    &format!("平票的玩家各有一次辩护发言，之后其他玩家只能在他们之间重新投票，PK的玩家不投票。{}", rule),
+                    None,
+                ));
+            }
+            GameEvent::PhaseChanged { phase: GamePhase::LastWords, .. } => {
+                tips.push(BeginnerTip::new(
+                    "last_words",
+                    "出局的玩家可以留下遗言，遗言比白天发言更短。",
+                    None,
+                ));
+            }
+            GameEvent::PlayerDied { reveal } => {
+                match (&reveal.revealed_role, &config.role_reveal) {
+                    (_, RoleRevealPolicy::Hidden) => tips.push(BeginnerTip::new(
+                        "reveal_hidden",
+                        "本局是暗牌局，出局玩家的身份不会公开，只能依靠发言推理。",
+                        None,
+                    )),
+                    (_, RoleRevealPolicy::FactionOnly) => tips.push(BeginnerTip::new(
+                        "reveal_faction",
+                        "本局出局时只公布阵营，不公布具体身份。",
+                        None,
+                    )),
+                    (Some(RoleType::Hunter), _) => tips.push(BeginnerTip::new(
+                        "hunter_shot",
+                        "猎人出局时可以开枪带走一名玩家。",
+                        Some(RoleType::Hunter),
+                    )),
+                    _ => {}
+                }
+            }
+            GameEvent::RoleClaimed { role: RoleType::Seer, contested: true, .. } => {
+                tips.push(BeginnerTip::new(
+                    "seer_contest",
+                    "多名玩家同时声明预言家叫做“对跳”，其中至少有一人在说谎。",
+                    Some(RoleType::Seer),
+                ));
+            }
+            GameEvent::CheckReported { .. } => {
+                tips.push(BeginnerTip::new(
+                    "gold_water_and_kill",
+                    "被预言家验为好人叫“金水”，验为狼人叫“查杀”；只有真预言家的查验才可信。",
+                    Some(RoleType::Seer),
+                ));
+            }
+            _ => {}
+        }
+
+        tips
+    }
+}
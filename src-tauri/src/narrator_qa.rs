@@ -0,0 +1,350 @@
+use crate::narrator::Narrator;
+use crate::night::DawnAnnouncement;
+use crate::roles;
+use crate::types::*;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+
+/// 给模型的最近公开发言条数
+const PUBLIC_SPEECH_LIMIT: usize = 20;
+
+/// 唤醒词配置：以唤醒词开头的发言不算正式发言，而是向主持人提问
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WakeWordConfig {
+    pub enabled: bool,
+    pub phrases: Vec<String>,
+}
+
+impl Default for WakeWordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phrases: vec!["法官".to_string(), "主持人".to_string()],
+        }
+    }
+}
+
+impl WakeWordConfig {
+    /// 发言以唤醒词开头时返回去掉唤醒词后的问题
+    pub fn strip<'a>(&self, content: &'a str) -> Option<&'a str> {
+        if !self.enabled {
+            return None;
+        }
+        let content = content.trim_start_matches(|c: char| c.is_whitespace() || is_separator(c));
+        self.phrases.iter()
+            .filter(|phrase| !phrase.is_empty())
+            .find_map(|phrase| content.strip_prefix(phrase.as_str()))
+            .map(|rest| rest.trim_matches(|c: char| c.is_whitespace() || is_separator(c)))
+    }
+}
+
+/// 主持人能回答的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NarratorQuery {
+    /// 重复昨晚的天亮公告
+    LastNight,
+    /// 上一轮投票结果
+    LastVote,
+    /// 某天（默认最近一轮）谁投了某个座位，座位为None时指提问者自己
+    VotesFor { day: Option<u32>, seat: Option<u8> },
+    AlivePlayers,
+    DeadPlayers,
+    /// 公开的身份声明和查验报告
+    Claims,
+    /// 某个角色的规则
+    RoleRules(RoleType),
+    /// 当前是第几天、什么阶段
+    CurrentPhase,
+    /// 无法直接查表，交给模型基于公开记录回答
+    Unknown,
+}
+
+impl NarratorQuery {
+    /// 按关键词判断问题类型，越具体的关键词越先匹配
+    pub fn parse(question: &str) -> Self {
+        let has = |keywords: &[&str]| keywords.iter().any(|k| question.contains(k));
+        if has(&["投了", "投我", "投的", "谁投"]) {
+            NarratorQuery::VotesFor {
+                day: parse_day(question),
+                seat: utils::parse_seat_mentions(question).first().copied(),
+            }
+        } else if has(&["昨晚", "昨夜", "晚上", "天亮", "夜里"]) {
+            NarratorQuery::LastNight
+        } else if has(&["投票", "票型", "得票", "放逐"]) {
+            NarratorQuery::LastVote
+        } else if has(&["查验", "声明", "跳", "金水", "查杀"]) {
+            NarratorQuery::Claims
+        } else if let Some(role) = mentioned_role(question).filter(|_| has(&["能", "技能", "规则", "怎么", "什么", "可以"])) {
+            NarratorQuery::RoleRules(role)
+        } else if has(&["身份"]) {
+            NarratorQuery::Claims
+        } else if has(&["出局", "死了", "死亡", "淘汰"]) {
+            NarratorQuery::DeadPlayers
+        } else if has(&["存活", "活着", "还剩", "还有谁"]) {
+            NarratorQuery::AlivePlayers
+        } else if has(&["第几天", "阶段", "现在", "轮到"]) {
+            NarratorQuery::CurrentPhase
+        } else {
+            NarratorQuery::Unknown
+        }
+    }
+}
+
+/// 查表回答玩家的提问，只使用公开记录，不涉及任何人的真实身份和夜间行动
+///
+/// 无法查表的问题返回None，由调用方交给模型
+pub fn answer(query: &NarratorQuery, state: &GameState, asker: &str, last_dawn: Option<&DawnAnnouncement>) -> Option<String> {
+    let name_of = |id: &str| state.players.iter()
+        .chain(state.dead_players.iter())
+        .find(|p| p.id == id)
+        .map(|p| p.display_name())
+        .unwrap_or_else(|| id.to_string());
+
+    let text = match query {
+        NarratorQuery::LastNight => match last_dawn {
+            None => "还没有天亮公告。".to_string(),
+            Some(dawn) if dawn.peaceful => format!("第{}天天亮时公布：昨晚是平安夜。", dawn.day),
+            Some(dawn) => {
+                let deaths: Vec<String> = dawn.deaths.iter()
+                    .map(|reveal| Narrator::death_announcement(&name_of(&reveal.player_id), reveal))
+                    .collect();
+                format!("第{}天天亮时公布：{}", dawn.day, deaths.join(""))
+            }
+        },
+        NarratorQuery::LastVote => match state.vote_outcomes.last() {
+            None => "还没有进行过放逐投票。".to_string(),
+            Some(outcome) => describe_outcome(outcome, &name_of),
+        },
+        NarratorQuery::VotesFor { day, seat } => {
+            let target = match seat {
+                Some(seat) => state.players.iter().find(|p| p.seat == *seat)?.id.clone(),
+                None => asker.to_string(),
+            };
+            let outcome = match day {
+                Some(day) => state.vote_outcomes.iter().find(|o| o.day == *day),
+                None => state.vote_outcomes.last(),
+            };
+            match outcome {
+                None => "那一天没有放逐投票的记录。".to_string(),
+                Some(outcome) => {
+                    let voters = outcome.tally.iter()
+                        .find(|t| t.target == target)
+                        .map(|t| t.voters.as_slice())
+                        .unwrap_or_default();
+                    if voters.is_empty() {
+                        format!("第{}天没有人投{}。", outcome.day, name_of(&target))
+                    } else {
+                        format!(
+                            "第{}天投{}的有：{}，共{}票。",
+                            outcome.day,
+                            name_of(&target),
+                            voters.iter().map(|id| name_of(id)).collect::<Vec<_>>().join("、"),
+                            voters.len()
+                        )
+                    }
+                }
+            }
+        }
+        NarratorQuery::AlivePlayers => {
+            let mut alive: Vec<&Player> = state.players.iter().filter(|p| p.is_alive).collect();
+            alive.sort_by_key(|p| p.seat);
+            format!(
+                "目前存活{}人：{}。",
+                alive.len(),
+                alive.iter().map(|p| p.display_name()).collect::<Vec<_>>().join("、")
+            )
+        }
+        NarratorQuery::DeadPlayers if state.death_reveals.is_empty() => "目前还没有玩家出局。".to_string(),
+        NarratorQuery::DeadPlayers => state.death_reveals.iter()
+            .map(|reveal| format!("第{}天，{}", reveal.day, Narrator::death_announcement(&name_of(&reveal.player_id), reveal)))
+            .collect::<Vec<_>>()
+            .join(""),
+        NarratorQuery::Claims => {
+            let lines = claim_lines(state, &name_of);
+            if lines.is_empty() {
+                "目前还没有人声明身份或公布查验。".to_string()
+            } else {
+                lines.join("")
+            }
+        }
+        NarratorQuery::RoleRules(role) => {
+            let info = roles::role_info(role);
+            let in_game = state.game_config.role_distribution.get(role).copied().unwrap_or(0) > 0;
+            format!(
+                "{}：{}。技能：{}。{}",
+                info.name,
+                info.summary,
+                info.abilities.join("；"),
+                if in_game { "本局有这个角色。" } else { "本局没有这个角色。" }
+            )
+        }
+        NarratorQuery::CurrentPhase => {
+            match state.current_speaker.as_deref() {
+                Some(speaker) => format!("现在是第{}天，{}，轮到{}发言。", state.day, phase_name(&state.phase), name_of(speaker)),
+                None => format!("现在是第{}天，{}。", state.day, phase_name(&state.phase)),
+            }
+        }
+        NarratorQuery::Unknown => return None,
+    };
+    Some(text)
+}
+
+/// 整理给模型看的公开记录：规则、存活、出局公示、声明、投票和最近的公开发言
+pub fn public_record(state: &GameState, last_dawn: Option<&DawnAnnouncement>, speeches: &[SpeechRecord]) -> String {
+    let name_of = |id: &str| state.players.iter()
+        .find(|p| p.id == id)
+        .map(|p| p.display_name())
+        .unwrap_or_else(|| id.to_string());
+    let config = &state.game_config;
+    let mut lines = Vec::new();
+
+    let mut setup: Vec<String> = config.role_distribution.iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(role, count)| format!("{}×{}", utils::get_role_name(role), count))
+        .collect();
+    setup.sort();
+    lines.push(format!("板子：{}人，{}", state.players.len(), setup.join("、")));
+    lines.push(format!("当前：第{}天，{}", state.day, phase_name(&state.phase)));
+    let alive: Vec<String> = state.players.iter().filter(|p| p.is_alive).map(|p| p.display_name()).collect();
+    lines.push(format!("存活：{}", alive.join("、")));
+    for reveal in &state.death_reveals {
+        lines.push(format!("第{}天：{}", reveal.day, Narrator::death_announcement(&name_of(&reveal.player_id), reveal)));
+    }
+    if let Some(dawn) = last_dawn.filter(|dawn| dawn.peaceful) {
+        lines.push(format!("第{}天天亮：平安夜", dawn.day));
+    }
+    lines.extend(claim_lines(state, &name_of));
+    for outcome in &state.vote_outcomes {
+        let votes: Vec<String> = outcome.tally.iter()
+            .map(|t| format!(
+                "{}得{}（{}）",
+                name_of(&t.target),
+                votes_label(t),
+                t.voters.iter().map(|id| name_of(id)).collect::<Vec<_>>().join("、")
+            ))
+            .collect();
+        lines.push(format!("第{}天投票：{}", outcome.day, votes.join("；")));
+    }
+
+    let start = speeches.len().saturating_sub(PUBLIC_SPEECH_LIMIT);
+    for speech in &speeches[start..] {
+        if speech.speaker == Narrator::SPEAKER {
+            continue;
+        }
+        lines.push(format!("第{}天 {}：{}", speech.day, name_of(&speech.speaker), speech.content));
+    }
+    lines.join("\n")
+}
+
+/// 查不到时交给模型的提示词，只给公开记录，并要求不猜测隐藏信息
+pub fn llm_prompt(question: &str, record: &str) -> String {
+    format!(
+        "你是狼人杀的法官。玩家向你提问，你只能根据下面的公开记录回答。\n\
+         不要推测或透露任何玩家的真实身份、夜间行动和狼人频道内容；\
+         公开记录里没有的信息，直接回答“公开记录里没有这条信息”。\n\
+         回答用一两句话，不要分析局势，不要给出站边建议。\n\n\
+         【公开记录】\n{}\n\n【问题】{}",
+        record, question
+    )
+}
+
+/// 票数说明，有警长等加权票时附上计票结果
+fn votes_label(tally: &VoteTally) -> String {
+    if tally.weight > 0.0 && (tally.weight - tally.count as f32).abs() > 1e-4 {
+        format!("{}票（计{}票）", tally.count, tally.weight)
+    } else {
+        format!("{}票", tally.count)
+    }
+}
+
+fn describe_outcome(outcome: &VoteOutcome, name_of: &dyn Fn(&str) -> String) -> String {
+    let mut tally: Vec<String> = outcome.tally.iter()
+        .map(|t| format!("{}得{}", name_of(&t.target), votes_label(t)))
+        .collect();
+    if !outcome.abstentions.is_empty() {
+        let names: Vec<String> = outcome.abstentions.iter().map(|id| name_of(id)).collect();
+        tally.push(format!("{}弃票", names.join("、")));
+    }
+    let result = match &outcome.eliminated {
+        Some(id) if !outcome.tied.is_empty() => format!("平票，随机放逐{}。", name_of(id)),
+        Some(id) => format!("{}被放逐。", name_of(id)),
+        None if outcome.pk => "平票，进入PK。".to_string(),
+        None if !outcome.tied.is_empty() => "平票，无人出局。".to_string(),
+        None => "无人出局。".to_string(),
+    };
+    let round = if outcome.revote { "重新投票" } else { "投票" };
+    format!("第{}天{}：{}，{}", outcome.day, round, tally.join("，"), result)
+}
+
+fn claim_lines(state: &GameState, name_of: &dyn Fn(&str) -> String) -> Vec<String> {
+    let board = &state.claims_board;
+    let mut claimants: Vec<&Player> = state.players.iter()
+        .filter(|p| board.current_claim(&p.id).is_some())
+        .collect();
+    claimants.sort_by_key(|p| p.seat);
+
+    let mut lines: Vec<String> = claimants.iter()
+        .filter_map(|p| board.current_claim(&p.id).map(|role| {
+            format!("{}声明是{}。", p.display_name(), utils::get_role_name(role))
+        }))
+        .collect();
+    for report in &board.check_reports {
+        lines.push(Narrator::check_report(&name_of(&report.reporter), &name_of(&report.target), report.is_werewolf));
+    }
+    lines
+}
+
+fn phase_name(phase: &GamePhase) -> &'static str {
+    match phase {
+        GamePhase::Preparation => "准备阶段",
+        GamePhase::Night => "夜晚",
+        GamePhase::DayDiscussion => "白天讨论",
+        GamePhase::Voting => "投票阶段",
+        GamePhase::Pk => "PK发言",
+        GamePhase::LastWords => "遗言阶段",
+        GamePhase::GameOver => "游戏已结束",
+    }
+}
+
+/// 问题里的“第N天”
+fn parse_day(question: &str) -> Option<u32> {
+    let (_, rest) = question.split_once('第')?;
+    let (token, _) = rest.split_once('天')?;
+    token.parse::<u32>().ok()
+        .or_else(|| utils::parse_chinese_number(token).map(u32::from))
+}
+
+/// 问题里提到的角色，按名字长度优先匹配，避免“狼人”抢先匹配“梦狼”之类的长名
+fn mentioned_role(question: &str) -> Option<RoleType> {
+    let mut roles: Vec<RoleType> = roles::ALL_ROLES.to_vec();
+    roles.sort_by_key(|role| std::cmp::Reverse(utils::get_role_name(role).chars().count()));
+    roles.into_iter().find(|role| question.contains(utils::get_role_name(role)))
+}
+
+fn is_separator(c: char) -> bool {
+    "，,。：:！!、 ".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wake_phrase_routes_question() {
+        let config = WakeWordConfig { enabled: true, ..WakeWordConfig::default() };
+        let question = config.strip("法官，重复一下昨晚的信息").unwrap();
+        assert_eq!(question, "重复一下昨晚的信息");
+        assert_eq!(NarratorQuery::parse(question), NarratorQuery::LastNight);
+        assert!(config.strip("我觉得法官说得对").is_none());
+        assert!(WakeWordConfig::default().strip("法官，现在第几天").is_none());
+    }
+
+    #[test]
+    fn test_parse_vote_and_rule_questions() {
+        assert_eq!(NarratorQuery::parse("谁在第二天投了我？"), NarratorQuery::VotesFor { day: Some(2), seat: None });
+        assert_eq!(NarratorQuery::parse("第3天谁投了5号"), NarratorQuery::VotesFor { day: Some(3), seat: Some(5) });
+        assert_eq!(NarratorQuery::parse("梦狼能做什么"), NarratorQuery::RoleRules(RoleType::DreamWolf));
+        assert_eq!(NarratorQuery::parse("谁跳了预言家"), NarratorQuery::Claims);
+        assert_eq!(NarratorQuery::parse("你觉得谁最像狼"), NarratorQuery::Unknown);
+    }
+}
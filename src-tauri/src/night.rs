@@ -0,0 +1,112 @@
+use crate::roles;
+use crate::types::*;
+use crate::validator::ActionValidator;
+use serde::{Deserialize, Serialize};
+
+/// 夜晚流程的推进结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum NightStatus {
+    /// 等待真人玩家提交夜晚行动，提交后自动继续
+    AwaitingHuman { player_id: String },
+    /// 夜晚已结算，附带天亮公告
+    Dawn { announcement: DawnAnnouncement },
+    /// 已经有一个夜晚流程在推进中
+    InProgress,
+    /// 对局结束或阶段被切换，夜晚流程被打断
+    Interrupted,
+}
+
+/// 天亮公告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DawnAnnouncement {
+    pub day: u32,
+    /// 按身份公开规则公示的死亡信息
+    pub deaths: Vec<DeathReveal>,
+    pub peaceful: bool,
+}
+
+/// 夜晚流程的下一步
+pub enum NightTurn {
+    /// 轮到该AI行动
+    Ai(Box<Player>),
+    /// 所有行动都已收集
+    Done,
+    /// 停止推进
+    Stop(NightStatus),
+}
+
+/// 一次夜晚行动机会
+#[derive(Debug, Clone)]
+pub struct NightStep {
+    pub player_id: String,
+    pub role_type: RoleType,
+}
+
+/// 一个夜晚的行动顺序与进度
+#[derive(Debug, Clone)]
+pub struct NightRound {
+    pub day: u32,
+    steps: Vec<NightStep>,
+    cursor: usize,
+    /// 是否有任务正在推进本夜
+    pub running: bool,
+    /// 正在等待行动的真人玩家
+    pub awaiting: Option<String>,
+}
+
+impl NightRound {
+    /// 按角色知识库的夜晚行动顺序排出行动步骤。狼队只由一名能刀人的狼代表行动，
+    /// 有真人狼时由真人决定；首夜双查时预言家多一次查验
+    pub fn new(state: &GameState) -> Self {
+        let mut steps: Vec<(u8, u8, NightStep)> = Vec::new();
+
+        let mut killers: Vec<&Player> = state.players.iter()
+            .filter(|p| p.is_alive && p.role.faction == Faction::Werewolf && ActionValidator::can_kill(state, p))
+            .collect();
+        killers.sort_by_key(|p| (p.is_ai, p.seat));
+        if let Some(wolf) = killers.first() {
+            let order = roles::role_info(&wolf.role.role_type).night_order.unwrap_or(1);
+            steps.push((order, wolf.seat, NightStep { player_id: wolf.id.clone(), role_type: wolf.role.role_type.clone() }));
+        }
+
+        let double_check = state.day == 1
+            && state.game_config.night_rules.first_night_seer == FirstNightSeerRule::DoubleCheck;
+        for player in state.players.iter().filter(|p| p.is_alive && p.role.faction != Faction::Werewolf) {
+            let order = match roles::role_info(&player.role.role_type).night_order {
+                Some(order) => order,
+                None => continue,
+            };
+            let step = NightStep { player_id: player.id.clone(), role_type: player.role.role_type.clone() };
+            if double_check && player.role.role_type == RoleType::Seer {
+                steps.push((order, player.seat, step.clone()));
+            }
+            steps.push((order, player.seat, step));
+        }
+        steps.sort_by_key(|(order, seat, _)| (*order, *seat));
+
+        Self {
+            day: state.day,
+            steps: steps.into_iter().map(|(_, _, step)| step).collect(),
+            cursor: 0,
+            running: false,
+            awaiting: None,
+        }
+    }
+
+    /// 下一位仍然存活的行动者
+    pub fn next_step<'a>(&mut self, state: &'a GameState) -> Option<(&NightStep, &'a Player)> {
+        while let Some(step) = self.steps.get(self.cursor) {
+            match state.players.iter().find(|p| p.id == step.player_id && p.is_alive) {
+                Some(player) => return Some((&self.steps[self.cursor], player)),
+                None => self.cursor += 1,
+            }
+        }
+        None
+    }
+
+    /// 当前行动者已行动，轮到下一位
+    pub fn advance(&mut self) {
+        self.cursor += 1;
+    }
+}
@@ -0,0 +1,154 @@
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+
+/// 夜晚行动的结算阶段，按声明顺序结算
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NightStage {
+    Guard,
+    Wolves,
+    Witch,
+    Seer,
+}
+
+impl NightStage {
+    pub fn of(action: &NightActionType) -> Self {
+        match action {
+            NightActionType::Protect => NightStage::Guard,
+            NightActionType::Kill => NightStage::Wolves,
+            NightActionType::Heal | NightActionType::Poison => NightStage::Witch,
+            NightActionType::Check => NightStage::Seer,
+        }
+    }
+}
+
+/// 一个夜晚的结算结果，包含全部夜间信息，只用于天亮结算和赛后复盘
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NightResult {
+    pub day: u32,
+    pub protected: Option<String>,
+    /// 狼刀目标
+    pub attacked: Vec<String>,
+    pub healed: Option<String>,
+    pub poisoned: Option<String>,
+    /// 被刀但被守护或解药救下的玩家
+    pub saved: Vec<String>,
+    pub checked: Vec<String>,
+    /// 天亮时出局的玩家，按ID排序，含房规连带的出局
+    pub deaths: Vec<String>,
+}
+
+/// 夜晚行动队列：夜间只收集行动，天亮时按守卫→狼人→女巫→预言家的顺序统一结算，
+/// 结果与行动提交的先后无关
+#[derive(Debug, Clone, Default)]
+pub struct NightResolution {
+    actions: Vec<NightAction>,
+    kill_targets: Vec<String>,
+}
+
+impl NightResolution {
+    /// 收集一次行动。狼队每晚只刀一人，再次提交的狼刀改写之前的刀口
+    pub fn collect(&mut self, action: NightAction) {
+        if matches!(action.action, NightActionType::Kill) {
+            self.kill_targets = action.target.iter().cloned().collect();
+        }
+        self.actions.push(action);
+    }
+
+    /// 今晚的狼刀目标，女巫用药前可见
+    pub fn kill_targets(&self) -> &[String] {
+        &self.kill_targets
+    }
+
+    /// 结算并清空本夜行动。同一阶段的多次行动以最后一次为准
+    pub fn resolve(&mut self, day: u32, rules: &NightRules) -> NightResult {
+        let mut actions = std::mem::take(&mut self.actions);
+        self.kill_targets.clear();
+        actions.sort_by_key(|action| NightStage::of(&action.action));
+
+        let mut result = NightResult { day, ..Default::default() };
+        for action in actions {
+            match action.action {
+                NightActionType::Protect => result.protected = action.target,
+                NightActionType::Kill => result.attacked = action.target.into_iter().collect(),
+                NightActionType::Heal => result.healed = action.target,
+                NightActionType::Poison => result.poisoned = action.target,
+                NightActionType::Check => result.checked.extend(action.target),
+            }
+        }
+
+        // 被守护或被救的玩家免于狼刀；开启同守同救死时，两者叠加反而死亡
+        let mut deaths = Vec::new();
+        for target in &result.attacked {
+            let is_protected = result.protected.as_ref() == Some(target);
+            let is_healed = result.healed.as_ref() == Some(target);
+            let dies = match (is_protected, is_healed) {
+                (true, true) => rules.guard_heal_conflict_kills,
+                (true, false) | (false, true) => false,
+                (false, false) => true,
+            };
+            if dies {
+                deaths.push(target.clone());
+            } else {
+                result.saved.push(target.clone());
+            }
+        }
+        // 毒药不受守护和解药影响
+        deaths.extend(result.poisoned.clone());
+
+        deaths.sort();
+        deaths.dedup();
+        result.saved.retain(|id| !deaths.contains(id));
+        result.saved.dedup();
+        result.deaths = deaths;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(player: &str, action: NightActionType, target: &str) -> NightAction {
+        NightAction { player: player.to_string(), action, target: Some(target.to_string()) }
+    }
+
+    #[test]
+    fn test_resolution_ignores_submission_order() {
+        let rules = NightRules { guard_heal_conflict_kills: true, first_night_seer: FirstNightSeerRule::Standard };
+        let actions = [
+            action("witch", NightActionType::Poison, "p3"),
+            action("witch", NightActionType::Heal, "p1"),
+            action("wolf", NightActionType::Kill, "p1"),
+            action("guard", NightActionType::Protect, "p2"),
+        ];
+
+        let mut forward = NightResolution::default();
+        let mut backward = NightResolution::default();
+        for a in &actions {
+            forward.collect(a.clone());
+        }
+        for a in actions.iter().rev() {
+            backward.collect(a.clone());
+        }
+
+        let result = forward.resolve(1, &rules);
+        assert_eq!(result.deaths, vec!["p3".to_string()]);
+        assert_eq!(result.saved, vec!["p1".to_string()]);
+        assert_eq!(backward.resolve(1, &rules).deaths, result.deaths);
+        assert!(forward.kill_targets().is_empty());
+    }
+
+    #[test]
+    fn test_second_wolf_kill_replaces_the_first() {
+        let rules = NightRules::default();
+        let mut night = NightResolution::default();
+        night.collect(action("wolf1", NightActionType::Kill, "p1"));
+        night.collect(action("wolf2", NightActionType::Kill, "p2"));
+        assert_eq!(night.kill_targets(), ["p2".to_string()]);
+
+        let result = night.resolve(1, &rules);
+        assert_eq!(result.attacked, vec!["p2".to_string()]);
+        assert_eq!(result.deaths, vec!["p2".to_string()]);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::ai::PersonalityManager;
+use crate::speech_style::SpeechLanguage;
+use crate::types::*;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// 性格模板缺失时（如真人被托管的座位）的打断倾向
+const DEFAULT_INTERRUPTION_TENDENCY: f32 = 0.3;
+
+/// 一次打断：对当前发言者的一句质疑，以及发言者的简短回应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Objection {
+    pub day: u32,
+    pub player_id: String,
+    pub speaker: String,
+    pub content: String,
+    /// AI发言者当场回应；真人发言者在自己的发言里回应，这里为None
+    pub reply: Option<String>,
+}
+
+/// 每名玩家每天只能打断一次
+#[derive(Debug, Clone, Default)]
+pub struct ObjectionTracker {
+    /// 玩家ID -> 最近一次打断的天数
+    used: HashMap<String, u32>,
+}
+
+impl ObjectionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.used.clear();
+    }
+
+    pub fn can_object(&self, player_id: &str, day: u32) -> bool {
+        self.used.get(player_id) != Some(&day)
+    }
+
+    pub fn mark(&mut self, player_id: &str, day: u32) {
+        self.used.insert(player_id.to_string(), day);
+    }
+}
+
+/// 异议只有一句话：去掉换行，超出字数时截到句末
+pub fn normalize(content: &str, max_chars: usize) -> Option<String> {
+    let line = content.lines().map(str::trim).find(|line| !line.is_empty())?;
+    if line.chars().count() <= max_chars {
+        Some(line.to_string())
+    } else {
+        Some(utils::trim_to_sentence(line, max_chars))
+    }
+}
+
+/// 按人设对应的性格模板取打断倾向
+pub fn interruption_tendency(player: &Player) -> f32 {
+    player.persona.as_ref()
+        .and_then(|persona| PersonalityManager::speech_patterns_of(&persona.template_id))
+        .map(|patterns| patterns.interruption_tendency)
+        .unwrap_or(DEFAULT_INTERRUPTION_TENDENCY)
+}
+
+/// AI是否打断：越爱插话、越怀疑发言者，越可能开口
+pub fn ai_should_object(config: &ObjectionConfig, tendency: f32, suspicion: f32, rng: &mut impl Rng) -> bool {
+    config.enabled && rng.gen::<f32>() < config.ai_frequency * tendency * suspicion.clamp(0.0, 1.0)
+}
+
+/// AI的质疑台词
+pub fn challenge_line(language: SpeechLanguage, rng: &mut impl Rng) -> String {
+    let lines: &[&str] = if language == SpeechLanguage::EnUs {
+        &["Hold on, that doesn't add up.", "Wait, that contradicts what you said earlier.", "Objection! Why dodge the question?"]
+    } else {
+        &["等一下，这里说不通。", "打断一下，你前面不是这么说的。", "我有异议，你在回避问题。"]
+    };
+    lines.choose(rng).copied().unwrap_or("……").to_string()
+}
+
+/// AI发言者被打断后的简短回应
+pub fn reply_line(language: SpeechLanguage, rng: &mut impl Rng) -> String {
+    let lines: &[&str] = if language == SpeechLanguage::EnUs {
+        &["Let me finish first.", "I'll get to that, hear me out.", "That's not what I meant."]
+    } else {
+        &["先让我说完。", "你听我解释完再说。", "我不是这个意思。"]
+    };
+    lines.choose(rng).copied().unwrap_or("……").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_objection_per_day() {
+        let mut tracker = ObjectionTracker::new();
+        assert!(tracker.can_object("p1", 1));
+        tracker.mark("p1", 1);
+        assert!(!tracker.can_object("p1", 1));
+        assert!(tracker.can_object("p2", 1));
+        assert!(tracker.can_object("p1", 2));
+
+        assert_eq!(normalize("  \n等一下\n第二行", 30).as_deref(), Some("等一下"));
+        assert!(normalize("   ", 30).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::llm::LLMManager;
+use crate::speech_style::{SpeechLanguage, SpeechStyle};
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+
+/// AI发言的生成后校验：语言不符时重新生成，超长时先请模型压缩改写，仍超长再按句子截断
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputControlConfig {
+    /// 发言语言与设置不符时重新生成
+    pub enforce_language: bool,
+    /// 语言不符时最多重新生成的次数
+    pub max_regenerations: u32,
+    /// 超出字数上限较多时请模型压缩改写
+    pub rewrite_over_limit: bool,
+    /// 软上限倍数：不超过上限的这个倍数时直接截断，不值得再调用一次模型
+    pub soft_limit_ratio: f32,
+}
+
+impl Default for OutputControlConfig {
+    fn default() -> Self {
+        Self {
+            enforce_language: true,
+            max_regenerations: 1,
+            rewrite_over_limit: true,
+            soft_limit_ratio: 1.2,
+        }
+    }
+}
+
+/// 校验并修正模型生成的发言，返回不超过字数上限的结果
+pub async fn enforce(
+    llm_manager: &LLMManager,
+    prompt: &str,
+    response: String,
+    style: &SpeechStyle,
+    max_chars: usize,
+    config: &OutputControlConfig,
+) -> String {
+    let mut response = response;
+
+    if config.enforce_language {
+        let mut attempts = 0;
+        while !language_matches(&response, style.language) && attempts < config.max_regenerations {
+            attempts += 1;
+            info!("发言语言与设置不符，第{}次重新生成", attempts);
+            let retry = format!("{}\n\n注意：{}", prompt, style.prompt_instruction());
+            match llm_manager.generate_with_fallback(retry).await {
+                Ok(text) => response = text,
+                Err(e) => {
+                    warn!("重新生成发言失败: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    let length = response.chars().count();
+    let soft_limit = (max_chars as f32 * config.soft_limit_ratio) as usize;
+    if config.rewrite_over_limit && length > soft_limit {
+        info!("发言{}字超出上限{}字，请模型压缩改写", length, max_chars);
+        match llm_manager.generate_with_fallback(rewrite_prompt(&response, max_chars, style)).await {
+            // 改写结果换了语言时不采用
+            Ok(text) if !config.enforce_language || language_matches(&text, style.language) => response = text,
+            Ok(_) => warn!("压缩改写的语言与设置不符，直接截断原发言"),
+            Err(e) => warn!("压缩改写失败，直接截断: {}", e),
+        }
+    }
+
+    // 模型不一定遵守字数要求，按句子边界硬截断
+    utils::trim_to_sentence(response.trim(), max_chars)
+}
+
+/// 发言的主要语言是否符合设置
+///
+/// 按汉字与拉丁字母的比例判断，数字和标点不计
+pub fn language_matches(text: &str, language: SpeechLanguage) -> bool {
+    let cjk = text.chars().filter(|c| ('\u{4e00}'..='\u{9fff}').contains(c)).count();
+    let latin = text.chars().filter(|c| c.is_ascii_alphabetic()).count();
+    if cjk + latin == 0 {
+        return true;
+    }
+    // 英文单词平均约5个字母，按词折算后与汉字比较
+    let latin_words = latin as f32 / 5.0;
+    let cjk_share = cjk as f32 / (cjk as f32 + latin_words);
+
+    match language {
+        SpeechLanguage::ZhCn => cjk_share >= 0.8,
+        SpeechLanguage::EnUs => cjk_share <= 0.1,
+        SpeechLanguage::Mixed => cjk_share >= 0.4,
+    }
+}
+
+fn rewrite_prompt(text: &str, max_chars: usize, style: &SpeechStyle) -> String {
+    format!(
+        "把下面的狼人杀发言压缩到{}字以内。必须保留所有身份声明、查验结果、座位号和投票意向，不要新增内容。{}只输出压缩后的发言。\n\n原文：{}",
+        max_chars,
+        style.prompt_instruction(),
+        text
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_detection() {
+        assert!(language_matches("我觉得3号是狼，大家跟我投。", SpeechLanguage::ZhCn));
+        assert!(!language_matches("I think seat 3 is a wolf, vote with me.", SpeechLanguage::ZhCn));
+        assert!(language_matches("I think seat 3 is a wolf, vote with me.", SpeechLanguage::EnUs));
+        assert!(language_matches("有人的发言有点sus，我先mark一下。", SpeechLanguage::Mixed));
+        assert!(!language_matches("有人的发言有点sus，我先mark一下。", SpeechLanguage::EnUs));
+    }
+}
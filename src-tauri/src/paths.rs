@@ -0,0 +1,122 @@
+use crate::error::{AppError, AppResult};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use log::info;
+
+/// 可执行文件旁存在此文件时进入便携模式
+pub const PORTABLE_MARKER: &str = "portable.flag";
+/// 便携模式下的数据目录，相对于可执行文件
+const PORTABLE_DATA_DIR: &str = "data";
+const APP_DIR: &str = "MindWolf";
+
+static DATA_PATHS: OnceLock<DataPaths> = OnceLock::new();
+
+/// 程序读写的所有位置：配置、数据库、日志、语音缓存和临时文件
+///
+/// 便携模式下全部放在可执行文件旁的data目录，不在系统目录留下任何文件；
+/// 否则遵循各平台惯例：Linux按XDG规范（配置、数据、状态分开），
+/// macOS放~/Library下对应目录，Windows放%APPDATA%
+#[derive(Debug, Clone)]
+pub struct DataPaths {
+    portable: bool,
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+    logs_dir: PathBuf,
+}
+
+/// 进程内统一的数据目录布局，首次调用时确定
+pub fn data_paths() -> &'static DataPaths {
+    DATA_PATHS.get_or_init(DataPaths::resolve)
+}
+
+impl DataPaths {
+    fn resolve() -> Self {
+        let exe_dir = std::env::current_exe().ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        if let Some(exe_dir) = exe_dir.filter(|dir| dir.join(PORTABLE_MARKER).exists()) {
+            let root = exe_dir.join(PORTABLE_DATA_DIR);
+            info!("便携模式，数据目录: {:?}", root);
+            return Self::portable(root);
+        }
+
+        let data_dir = dirs::data_dir().unwrap_or_else(std::env::temp_dir).join(APP_DIR);
+        let config_dir = dirs::config_dir().map(|dir| dir.join(APP_DIR)).unwrap_or_else(|| data_dir.clone());
+        let logs_dir = Self::system_logs_dir().unwrap_or_else(|| data_dir.join("logs"));
+        Self { portable: false, config_dir, data_dir, logs_dir }
+    }
+
+    /// Linux：$XDG_STATE_HOME/MindWolf/logs
+    #[cfg(target_os = "linux")]
+    fn system_logs_dir() -> Option<PathBuf> {
+        dirs::state_dir().map(|dir| dir.join(APP_DIR).join("logs"))
+    }
+
+    /// macOS：~/Library/Logs/MindWolf，控制台应用能直接看到
+    #[cfg(target_os = "macos")]
+    fn system_logs_dir() -> Option<PathBuf> {
+        dirs::home_dir().map(|dir| dir.join("Library").join("Logs").join(APP_DIR))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn system_logs_dir() -> Option<PathBuf> {
+        None
+    }
+
+    /// 以指定目录为根的便携布局
+    pub fn portable(root: PathBuf) -> Self {
+        Self { portable: true, config_dir: root.clone(), logs_dir: root.join("logs"), data_dir: root }
+    }
+
+    pub fn is_portable(&self) -> bool {
+        self.portable
+    }
+
+    /// 配置文件所在目录，词库、情感模型、自定义音效等用户文件也放在这里
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join("config.json")
+    }
+
+    pub fn database_file(&self) -> PathBuf {
+        self.data_dir.join("mindwolf.db")
+    }
+
+    pub fn logs_dir(&self) -> PathBuf {
+        self.logs_dir.clone()
+    }
+
+    /// 语音合成与识别的中间文件；便携模式下不使用系统临时目录
+    pub fn temp_dir(&self) -> PathBuf {
+        if self.portable {
+            self.data_dir.join("tmp")
+        } else {
+            std::env::temp_dir()
+        }
+    }
+
+    /// 创建所有目录
+    pub fn ensure_dirs(&self) -> AppResult<()> {
+        for dir in [self.config_dir.clone(), self.data_dir.clone(), self.logs_dir(), self.temp_dir()] {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| AppError::Io(format!("创建目录{:?}失败: {}", dir, e)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_layout_stays_under_root() {
+        let root = PathBuf::from("MindWolf").join("data");
+        let paths = DataPaths::portable(root.clone());
+        for path in [paths.config_file(), paths.database_file(), paths.logs_dir(), paths.temp_dir()] {
+            assert!(path.starts_with(&root), "{:?}", path);
+        }
+    }
+}
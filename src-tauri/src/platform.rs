@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+/// 启动失败时弹出系统原生的错误对话框（Windows、macOS和Linux都可用），同时输出到标准错误
+pub fn show_error_dialog(title: &str, message: &str) {
+    eprintln!("{}", message);
+    rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Error)
+        .set_title(title)
+        .set_description(message)
+        .set_buttons(rfd::MessageButtons::Ok)
+        .show();
+}
+
+/// 在PATH中查找外部程序，Windows下按PATHEXT补全扩展名
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    let candidates = executable_names(name);
+    std::env::split_paths(&paths)
+        .flat_map(|dir| candidates.iter().map(move |candidate| dir.join(candidate)))
+        .find(|path| path.is_file())
+}
+
+#[cfg(windows)]
+fn executable_names(name: &str) -> Vec<String> {
+    let extensions = std::env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string());
+    std::iter::once(name.to_string())
+        .chain(extensions.split(';').filter(|ext| !ext.is_empty()).map(|ext| format!("{}{}", name, ext.to_lowercase())))
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn executable_names(name: &str) -> Vec<String> {
+    vec![name.to_string()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_executable() {
+        assert!(find_executable("mindwolf-no-such-program").is_none());
+    }
+}
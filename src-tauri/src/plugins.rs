@@ -0,0 +1,388 @@
+use crate::error::{AppError, AppResult};
+use crate::events::GameEvent;
+use crate::game_engine::GameEngine;
+use crate::types::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use wasmtime::{Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc};
+use log::{info, warn};
+
+/// 插件目录，位于配置目录下
+pub const PLUGIN_DIR: &str = "plugins";
+/// 每次调用可消耗的燃料，耗尽时插件被中止
+const FUEL_PER_CALL: u64 = 50_000_000;
+/// 插件线性内存上限
+const MEMORY_LIMIT_BYTES: usize = 64 * 1024 * 1024;
+/// 插件返回的JSON长度上限
+const MAX_RESPONSE_BYTES: usize = 64 * 1024;
+
+/// 插件目录中的一个策略插件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    /// 文件名去掉.wasm，开局配置按此名称分配座位
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+/// 列出插件目录中的全部.wasm文件
+pub fn list_plugins(plugin_dir: &Path) -> Vec<PluginInfo> {
+    let mut plugins: Vec<PluginInfo> = std::fs::read_dir(plugin_dir)
+        .map(|entries| {
+            entries.filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+                .filter_map(|path| Some(PluginInfo {
+                    name: path.file_stem()?.to_string_lossy().to_string(),
+                    size_bytes: std::fs::metadata(&path).ok()?.len(),
+                }))
+                .collect()
+        })
+        .unwrap_or_default();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+/// 插件决策的类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionKind {
+    Vote,
+    NightAction,
+    Speech,
+}
+
+/// 交给插件的决策请求，只含该座位自己能看到的信息
+#[derive(Debug, Clone, Serialize)]
+pub struct DecisionRequest {
+    pub kind: DecisionKind,
+    pub view: SeatView,
+}
+
+/// 某个座位视角下的局面
+#[derive(Debug, Clone, Serialize)]
+pub struct SeatView {
+    pub player_id: String,
+    pub seat: u8,
+    pub role: RoleType,
+    pub day: u32,
+    pub phase: GamePhase,
+    pub players: Vec<PublicPlayer>,
+    /// 认识的狼队友
+    pub teammates: Vec<String>,
+    /// 预言家自己的查验结果
+    pub checks: Vec<SeerCheckResult>,
+    /// 女巫能看到的当晚刀口
+    pub pending_kills: Vec<String>,
+}
+
+/// 公开的玩家信息
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicPlayer {
+    pub id: String,
+    pub seat: u8,
+    pub name: String,
+    pub is_alive: bool,
+}
+
+impl SeatView {
+    pub fn new(engine: &GameEngine, player: &Player) -> Self {
+        let state = engine.get_state();
+        Self {
+            player_id: player.id.clone(),
+            seat: player.seat,
+            role: player.role.role_type.clone(),
+            day: state.day,
+            phase: state.phase.clone(),
+            players: state.players.iter()
+                .map(|p| PublicPlayer { id: p.id.clone(), seat: p.seat, name: p.name.clone(), is_alive: p.is_alive })
+                .collect(),
+            teammates: state.players.iter()
+                .filter(|p| player.knows_teammate(p))
+                .map(|p| p.id.clone())
+                .collect(),
+            checks: engine.seer_results(&player.id),
+            pending_kills: if player.role.role_type == RoleType::Witch {
+                engine.pending_kill_targets().to_vec()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    fn is_alive_other(&self, target: &str) -> bool {
+        target != self.player_id && self.players.iter().any(|p| p.id == target && p.is_alive)
+    }
+}
+
+/// 投票决策，target为空表示弃票
+#[derive(Debug, Clone, Deserialize)]
+pub struct VoteDecision {
+    pub target: Option<String>,
+}
+
+/// 发言意图；给出text时直接作为发言，否则把意图交给语言模型展开
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpeechIntent {
+    /// 如"accuse"、"defend"、"claim"、"pass"
+    pub intent: String,
+    #[serde(default)]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+struct SandboxState {
+    limits: StoreLimits,
+}
+
+/// 一个已实例化的策略插件
+///
+/// 插件不导入任何宿主函数，只能通过导出函数收发JSON：
+/// `mw_alloc(len) -> ptr`分配输入缓冲区，`mw_observe(ptr, len)`接收公开事件（可选），
+/// `mw_decide(ptr, len) -> (ptr << 32) | len`返回决策JSON
+pub struct StrategyPlugin {
+    name: String,
+    store: Store<SandboxState>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    observe: Option<TypedFunc<(i32, i32), ()>>,
+    decide: TypedFunc<(i32, i32), i64>,
+    /// 出错后本局不再调用，交回内置AI
+    failed: bool,
+}
+
+impl StrategyPlugin {
+    fn load(engine: &Engine, path: &Path) -> AppResult<Self> {
+        let name = path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let module = Module::from_file(engine, path).map_err(plugin_error)?;
+        let mut store = Store::new(engine, SandboxState {
+            limits: StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT_BYTES).instances(1).build(),
+        });
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(plugin_error)?;
+
+        // 空链接器：需要导入任何宿主函数（文件、网络、WASI）的插件都无法实例化
+        let instance: Instance = Linker::new(engine).instantiate(&mut store, &module).map_err(plugin_error)?;
+        let memory = instance.get_memory(&mut store, "memory")
+            .ok_or_else(|| AppError::Plugin(format!("插件{}未导出memory", name)))?;
+        let alloc = instance.get_typed_func(&mut store, "mw_alloc").map_err(plugin_error)?;
+        let observe = instance.get_typed_func(&mut store, "mw_observe").ok();
+        let decide = instance.get_typed_func(&mut store, "mw_decide").map_err(plugin_error)?;
+
+        Ok(Self { name, store, memory, alloc, observe, decide, failed: false })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write_input(&mut self, input: &[u8]) -> AppResult<(i32, i32)> {
+        let len = i32::try_from(input.len()).map_err(plugin_error)?;
+        let ptr = self.alloc.call(&mut self.store, len).map_err(plugin_error)?;
+        self.memory.write(&mut self.store, ptr as u32 as usize, input).map_err(plugin_error)?;
+        Ok((ptr, len))
+    }
+
+    fn observe(&mut self, event: &GameEvent) -> AppResult<()> {
+        let observe = match &self.observe {
+            Some(observe) => observe.clone(),
+            None => return Ok(()),
+        };
+        self.store.set_fuel(FUEL_PER_CALL).map_err(plugin_error)?;
+        let (ptr, len) = self.write_input(&serde_json::to_vec(event)?)?;
+        observe.call(&mut self.store, (ptr, len)).map_err(plugin_error)
+    }
+
+    fn decide<T: DeserializeOwned>(&mut self, request: &DecisionRequest) -> AppResult<T> {
+        self.store.set_fuel(FUEL_PER_CALL).map_err(plugin_error)?;
+        let (ptr, len) = self.write_input(&serde_json::to_vec(request)?)?;
+        let packed = self.decide.call(&mut self.store, (ptr, len)).map_err(plugin_error)?;
+
+        let (out_ptr, out_len) = ((packed >> 32) as u32 as usize, packed as u32 as usize);
+        if out_len > MAX_RESPONSE_BYTES {
+            return Err(AppError::Plugin(format!("返回内容过长: {}字节", out_len)));
+        }
+        let mut output = vec![0u8; out_len];
+        self.memory.read(&self.store, out_ptr, &mut output).map_err(plugin_error)?;
+        Ok(serde_json::from_slice(&output)?)
+    }
+}
+
+fn plugin_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Plugin(e.to_string())
+}
+
+/// 本局各座位加载的策略插件
+pub struct PluginHost {
+    engine: Option<Engine>,
+    plugin_dir: Option<PathBuf>,
+    /// 玩家ID -> 插件
+    seats: HashMap<String, StrategyPlugin>,
+}
+
+impl PluginHost {
+    pub fn new() -> Self {
+        Self { engine: None, plugin_dir: None, seats: HashMap::new() }
+    }
+
+    pub fn set_plugin_dir(&mut self, plugin_dir: PathBuf) {
+        self.plugin_dir = Some(plugin_dir);
+    }
+
+    fn wasm_engine(&mut self) -> AppResult<&Engine> {
+        if self.engine.is_none() {
+            let mut config = Config::new();
+            config.consume_fuel(true);
+            self.engine = Some(Engine::new(&config).map_err(plugin_error)?);
+        }
+        Ok(self.engine.as_ref().expect("已初始化"))
+    }
+
+    /// 按开局配置为AI座位加载插件；加载失败的座位由内置AI接管
+    pub fn load_seats(&mut self, assignments: &HashMap<u8, String>, players: &[Player]) {
+        self.seats.clear();
+        if assignments.is_empty() {
+            return;
+        }
+        let plugin_dir = match self.plugin_dir.clone() {
+            Some(plugin_dir) => plugin_dir,
+            None => {
+                warn!("未设置插件目录，忽略插件座位");
+                return;
+            }
+        };
+
+        for (seat, name) in assignments {
+            let player = match players.iter().find(|p| p.seat == *seat) {
+                Some(player) if player.is_ai => player,
+                Some(_) => {
+                    warn!("{}号是真人座位，不能分配插件{}", seat, name);
+                    continue;
+                }
+                None => {
+                    warn!("没有{}号座位，忽略插件{}", seat, name);
+                    continue;
+                }
+            };
+            let path = plugin_dir.join(format!("{}.wasm", name));
+            let loaded = self.wasm_engine().and_then(|engine| StrategyPlugin::load(engine, &path));
+            match loaded {
+                Ok(plugin) => {
+                    info!("{}号座位使用策略插件{}", seat, name);
+                    self.seats.insert(player.id.clone(), plugin);
+                }
+                Err(e) => warn!("加载插件{}失败，{}号由内置AI接管: {}", name, seat, e),
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.seats.clear();
+    }
+
+    /// 该玩家当前由可用的插件控制
+    pub fn controls(&self, player_id: &str) -> bool {
+        self.seats.get(player_id).is_some_and(|plugin| !plugin.failed)
+    }
+
+    pub fn plugin_name(&self, player_id: &str) -> Option<&str> {
+        self.seats.get(player_id).map(StrategyPlugin::name)
+    }
+
+    /// 把公开事件转发给所有插件；夜晚行动提示等只属于某个座位的事件不转发
+    pub fn observe(&mut self, event: &GameEvent) {
+        if self.seats.is_empty() || !Self::is_public(event) {
+            return;
+        }
+        for plugin in self.seats.values_mut().filter(|plugin| !plugin.failed) {
+            if let Err(e) = plugin.observe(event) {
+                warn!("插件{}处理事件失败，本局停用: {}", plugin.name, e);
+                plugin.failed = true;
+            }
+        }
+    }
+
+    fn is_public(event: &GameEvent) -> bool {
+        matches!(
+            event,
+            GameEvent::PhaseChanged { .. }
+                | GameEvent::SpeechDelivered { .. }
+                | GameEvent::ObjectionRaised { .. }
+                | GameEvent::VoteIntentChanged { .. }
+                | GameEvent::VoteCast { .. }
+                | GameEvent::PlayerDied { .. }
+                | GameEvent::RoleClaimed { .. }
+                | GameEvent::CheckReported { .. }
+                | GameEvent::AutoVoteApplied { .. }
+                | GameEvent::VoteResolved { .. }
+                | GameEvent::SheriffChanged { .. }
+                | GameEvent::VoteRevoked { .. }
+                | GameEvent::DiscussionTurn { .. }
+                | GameEvent::DawnAnnounced { .. }
+                | GameEvent::GameEnded { .. }
+        )
+    }
+
+    fn decide<T: DeserializeOwned>(&mut self, kind: DecisionKind, view: SeatView) -> Option<T> {
+        let plugin = self.seats.get_mut(&view.player_id).filter(|plugin| !plugin.failed)?;
+        match plugin.decide(&DecisionRequest { kind, view }) {
+            Ok(decision) => Some(decision),
+            Err(e) => {
+                warn!("插件{}决策失败，本局停用: {}", plugin.name, e);
+                plugin.failed = true;
+                None
+            }
+        }
+    }
+
+    /// 插件的投票决定；返回None表示没有插件或插件失效，由内置AI决定
+    pub fn decide_vote(&mut self, view: SeatView) -> Option<Option<String>> {
+        let player_id = view.player_id.clone();
+        let legal = view.clone();
+        let decision: VoteDecision = self.decide(DecisionKind::Vote, view)?;
+        match decision.target {
+            Some(target) if !legal.is_alive_other(&target) => {
+                warn!("插件为{}选择了无效的投票目标{}，改由内置AI决定", player_id, target);
+                None
+            }
+            target => Some(target),
+        }
+    }
+
+    /// 插件的夜晚行动，返回与模型相同格式的JSON，由调用方解析和校验
+    pub fn decide_night_action(&mut self, view: SeatView) -> Option<serde_json::Value> {
+        self.decide(DecisionKind::NightAction, view)
+    }
+
+    pub fn decide_speech(&mut self, view: SeatView) -> Option<SpeechIntent> {
+        self.decide(DecisionKind::Speech, view)
+    }
+}
+
+impl Default for PluginHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_without_exports_is_rejected() {
+        let mut host = PluginHost::new();
+        let engine = host.wasm_engine().unwrap().clone();
+        let dir = std::env::temp_dir().join(format!("mindwolf-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // 最小的合法模块：只有魔数和版本号，没有任何导出
+        let path = dir.join("empty.wasm");
+        std::fs::write(&path, [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00]).unwrap();
+
+        assert!(StrategyPlugin::load(&engine, &path).is_err());
+        assert_eq!(list_plugins(&dir).iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), vec!["empty"]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,110 @@
+use crate::types::*;
+use crate::speech_style::{SpeechLanguage, SpeechStyle};
+use serde::{Deserialize, Serialize};
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+/// 值得AI即时反应的戏剧性时刻
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DramaticMoment {
+    /// 预言家报出查杀
+    WolfExposed { reporter: String, target: String },
+    /// 起跳预言家的玩家出局后被翻出是狼
+    ClaimedSeerWasWolf { player_id: String },
+    /// 场上公认的好人或神职出局
+    TrustedPlayerDied { player_id: String },
+}
+
+impl DramaticMoment {
+    /// 时刻的焦点玩家，用于挑选座位相近的AI
+    pub fn subject(&self) -> &str {
+        match self {
+            DramaticMoment::WolfExposed { target, .. } => target,
+            DramaticMoment::ClaimedSeerWasWolf { player_id } => player_id,
+            DramaticMoment::TrustedPlayerDied { player_id } => player_id,
+        }
+    }
+}
+
+/// AI的即时反应（不占用发言轮次）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reaction {
+    pub player_id: String,
+    pub content: String,
+    pub moment: DramaticMoment,
+}
+
+/// 反应生成器
+pub struct ReactionGenerator;
+
+impl ReactionGenerator {
+    /// 按座位远近挑选存活AI，按配置的频率生成一句话反应
+    pub fn react(moment: &DramaticMoment, state: &GameState, config: &ReactionConfig) -> Vec<Reaction> {
+        if !config.enabled || config.frequency <= 0.0 {
+            return Vec::new();
+        }
+
+        let subject_seat = state.players.iter()
+            .chain(state.dead_players.iter())
+            .find(|p| p.id == moment.subject())
+            .map(|p| p.seat as i32);
+        let seat_count = state.players.len().max(1) as i32;
+
+        let mut candidates: Vec<&Player> = state.players.iter()
+            .filter(|p| p.is_alive && p.is_ai && p.id != moment.subject())
+            .collect();
+        if let Some(seat) = subject_seat {
+            // 环形座位距离
+            candidates.sort_by_key(|p| {
+                let d = (p.seat as i32 - seat).abs();
+                d.min(seat_count - d)
+            });
+        }
+
+        // 按闲聊强度缩放后的情绪表达调整频率：情绪表达为0.5时即配置的频率，闲聊强度为0时不反应
+        let style = state.game_config.speech_style;
+        let mut rng = thread_rng();
+        let reactors: Vec<&Player> = candidates.into_iter()
+            .take(config.max_per_event)
+            .filter(|player| rng.gen::<f32>() < config.frequency * style.banter_of(player).1 * 2.0)
+            .collect();
+        reactors.into_iter()
+            .map(|player| Reaction {
+                player_id: player.id.clone(),
+                content: Self::line_for(moment, player, style.language, &mut rng),
+                moment: moment.clone(),
+            })
+            .filter(|reaction| SpeechStyle::is_banter_safe(&reaction.content))
+            .collect()
+    }
+
+    /// 英文发言的AI不区分性格模板；中英混杂仍用中文反应
+    fn line_for(moment: &DramaticMoment, player: &Player, language: SpeechLanguage, rng: &mut impl Rng) -> String {
+        if language == SpeechLanguage::EnUs {
+            let lines: &[&str] = match moment {
+                DramaticMoment::WolfExposed { .. } => &["A wolf check? Interesting.", "Called it!", "Wait, I need to rethink this."],
+                DramaticMoment::ClaimedSeerWasWolf { .. } => &["So the seer was fake...", "None of those checks can be trusted now.", "No wonder the talk felt off."],
+                DramaticMoment::TrustedPlayerDied { .. } => &["What a loss...", "Another villager down.", "That was a brutal kill."],
+            };
+            return lines.choose(rng).copied().unwrap_or("...").to_string();
+        }
+
+        let template = player.persona.as_ref()
+            .map(|p| p.template_id.as_str())
+            .unwrap_or("");
+
+        let lines: &[&str] = match (moment, template) {
+            (DramaticMoment::WolfExposed { .. }, "impulsive") => &["我就说嘛！", "果然是他！", "这下实锤了！"],
+            (DramaticMoment::WolfExposed { .. }, "cautious") => &["先别急，再听听。", "嗯……记下了。"],
+            (DramaticMoment::WolfExposed { .. }, _) => &["查杀？有意思。", "这信息量很大。", "等等，我要重新想想。"],
+            (DramaticMoment::ClaimedSeerWasWolf { .. }, "impulsive") => &["什么？！居然是悍跳！", "被骗惨了！"],
+            (DramaticMoment::ClaimedSeerWasWolf { .. }, "leader") => &["悍跳狼出局，大家回头看看他的金水。", "好，跟他站边的都要重新审视。"],
+            (DramaticMoment::ClaimedSeerWasWolf { .. }, _) => &["原来是假预言家……", "那他的查验全都不能信了。", "难怪发言那么奇怪。"],
+            (DramaticMoment::TrustedPlayerDied { .. }, "chaotic") => &["啊这，好人倒了一个。", "天塌了！"],
+            (DramaticMoment::TrustedPlayerDied { .. }, "analytical") => &["狼人刀得很准，说明他们在盯着发言。", "这刀有讲究。"],
+            (DramaticMoment::TrustedPlayerDied { .. }, _) => &["可惜了……", "唉，好人又少了一个。", "这刀太狠了。"],
+        };
+
+        lines.choose(rng).copied().unwrap_or("……").to_string()
+    }
+}
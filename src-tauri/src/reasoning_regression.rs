@@ -0,0 +1,279 @@
+use crate::ai::difficulty::DifficultyProfile;
+use crate::ai::reasoning::ReasoningEngine;
+use crate::claims::ClaimsBoard;
+use crate::error::AppResult;
+use crate::replay::{GameEvent as ReplayEvent, GameEventType, GameReplay};
+use crate::types::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use log::warn;
+
+/// 录制的用例先写到配置目录下，检查并精简期望后再复制到CASES_DIR
+pub const RECORD_DIR: &str = "reasoning_cases";
+
+/// 要断言的推理指标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningMetric {
+    Suspicion,
+    WerewolfProbability,
+    Trust,
+}
+
+/// 某名玩家在某天结束时的推理指标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReasoningScores {
+    pub suspicion: f32,
+    pub werewolf_probability: f32,
+    pub trust: f32,
+}
+
+impl ReasoningScores {
+    fn get(&self, metric: ReasoningMetric) -> f32 {
+        match metric {
+            ReasoningMetric::Suspicion => self.suspicion,
+            ReasoningMetric::WerewolfProbability => self.werewolf_probability,
+            ReasoningMetric::Trust => self.trust,
+        }
+    }
+}
+
+/// 一条期望，如“第3天结束时对X的怀疑度≥0.7”
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReasoningExpectation {
+    pub day: u32,
+    pub player_id: String,
+    pub metric: ReasoningMetric,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f32>,
+}
+
+/// 回归用例：一局精选复盘和对它的期望
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReasoningCase {
+    pub name: String,
+    pub expectations: Vec<ReasoningExpectation>,
+    pub replay: GameReplay,
+}
+
+/// 把复盘里的公开事件按顺序喂给全新的推理引擎，返回每天结束时各玩家的指标。
+/// 使用完美记忆的难度配置，同一份复盘总是得到相同结果
+pub fn replay_reasoning(replay: &GameReplay) -> BTreeMap<u32, HashMap<String, ReasoningScores>> {
+    let mut engine = ReasoningEngine::new();
+    engine.set_profile(DifficultyProfile::expert());
+    engine.initialize(&initial_state(replay));
+
+    let snapshot = |engine: &ReasoningEngine| -> HashMap<String, ReasoningScores> {
+        replay.players.iter()
+            .map(|p| (p.id.clone(), ReasoningScores {
+                suspicion: engine.get_suspicion_score(&p.id),
+                werewolf_probability: engine.get_werewolf_probability(&p.id),
+                trust: engine.get_trust_score(&p.id),
+            }))
+            .collect()
+    };
+
+    let mut days = BTreeMap::new();
+    let mut current_day = 0;
+    for event in &replay.game_events {
+        if event.round > current_day {
+            days.insert(current_day, snapshot(&engine));
+            current_day = event.round;
+        }
+        if let Err(e) = apply_event(&mut engine, event) {
+            warn!("回放事件{}失败: {}", event.id, e);
+        }
+    }
+    days.insert(current_day, snapshot(&engine));
+    days
+}
+
+/// 只处理按规则公开的事件，与观察者在对局中看到的一致
+fn apply_event(engine: &mut ReasoningEngine, event: &ReplayEvent) -> AppResult<()> {
+    match (&event.event_type, &event.player_id, &event.target_id) {
+        (GameEventType::Speech, Some(speaker), _) => engine.analyze_speech(speaker.clone(), &event.content),
+        (GameEventType::Vote, Some(voter), Some(target)) => engine.analyze_vote(voter.clone(), target.clone()),
+        (GameEventType::PlayerDeath, Some(player_id), _) => {
+            engine.apply_death_reveal(&DeathReveal {
+                player_id: player_id.clone(),
+                day: event.round,
+                revealed_role: metadata(event, "revealed_role"),
+                revealed_faction: metadata(event, "revealed_faction"),
+            });
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 事件元数据里的字段，缺失或格式不符时为None
+fn metadata<T: DeserializeOwned>(event: &ReplayEvent, key: &str) -> Option<T> {
+    event.metadata.get(key).and_then(|value| serde_json::from_value(value.clone()).ok())
+}
+
+/// 开局时的公开局面：座位和角色配置，不含任何人的身份
+fn initial_state(replay: &GameReplay) -> GameState {
+    GameState {
+        phase: GamePhase::Night,
+        day: 0,
+        players: replay.players.clone(),
+        dead_players: Vec::new(),
+        votes: Vec::new(),
+        game_config: replay.game_config.clone(),
+        winner: None,
+        current_speaker: None,
+        time_remaining: None,
+        vote_changes: Vec::new(),
+        vote_intents: HashMap::new(),
+        death_reveals: Vec::new(),
+        claims_board: ClaimsBoard::new(),
+        vote_outcomes: Vec::new(),
+        result: None,
+        witch_potions: HashMap::new(),
+        guard_protections: HashMap::new(),
+        sheriff: None,
+        revoked_votes: Vec::new(),
+        pk_candidates: Vec::new(),
+    }
+}
+
+/// 按当前推理结果为复盘生成期望：每天每名玩家的怀疑度和狼人概率，上下各留tolerance余量。
+/// 生成后删掉不关心的条目，只保留能说明推理质量的几条
+pub fn record_case(name: &str, replay: GameReplay, tolerance: f32) -> ReasoningCase {
+    let mut expectations = Vec::new();
+    for (day, scores) in replay_reasoning(&replay) {
+        for player in &replay.players {
+            let Some(scores) = scores.get(&player.id) else { continue };
+            for metric in [ReasoningMetric::Suspicion, ReasoningMetric::WerewolfProbability] {
+                let value = scores.get(metric);
+                expectations.push(ReasoningExpectation {
+                    day,
+                    player_id: player.id.clone(),
+                    metric,
+                    min: Some((value - tolerance).max(0.0)),
+                    max: Some((value + tolerance).min(1.0)),
+                });
+            }
+        }
+    }
+    ReasoningCase { name: name.to_string(), expectations, replay }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::error::AppError;
+    use crate::game_engine::GameEngine;
+    use chrono::Utc;
+    use std::path::Path;
+
+    /// 推理回归用例目录，位于src-tauri下，cargo test时逐个校验
+    const CASES_DIR: &str = "tests/reasoning";
+
+    /// 校验用例，返回所有不满足的期望，全部满足时为空
+    fn check_case(case: &ReasoningCase) -> Vec<String> {
+        let days = replay_reasoning(&case.replay);
+        case.expectations.iter()
+            .filter_map(|expectation| {
+                let value = match days.get(&expectation.day).and_then(|scores| scores.get(&expectation.player_id)) {
+                    Some(scores) => scores.get(expectation.metric),
+                    None => return Some(format!("第{}天没有玩家{}的推理结果", expectation.day, expectation.player_id)),
+                };
+                let too_low = expectation.min.is_some_and(|min| value < min);
+                let too_high = expectation.max.is_some_and(|max| value > max);
+                (too_low || too_high).then(|| format!(
+                    "第{}天{}的{:?}为{:.3}，期望范围[{}, {}]",
+                    expectation.day,
+                    expectation.player_id,
+                    expectation.metric,
+                    value,
+                    expectation.min.map_or("-".to_string(), |min| format!("{:.3}", min)),
+                    expectation.max.map_or("-".to_string(), |max| format!("{:.3}", max)),
+                ))
+            })
+            .collect()
+    }
+
+    /// 读取目录下的全部用例，按文件名排序
+    fn load_cases(dir: &Path) -> AppResult<Vec<ReasoningCase>> {
+        let mut paths: Vec<_> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect(),
+            Err(_) => return Ok(Vec::new()),
+        };
+        paths.sort();
+
+        paths.iter()
+            .map(|path| {
+                let json = std::fs::read_to_string(path)
+                    .map_err(|e| AppError::Io(format!("读取推理用例{:?}失败: {}", path, e)))?;
+                serde_json::from_str(&json)
+                    .map_err(|e| AppError::Config(format!("推理用例{:?}格式错误: {}", path, e)))
+            })
+            .collect()
+    }
+
+    fn event(round: u32, event_type: GameEventType, player_id: &str, target_id: Option<&str>, content: &str) -> ReplayEvent {
+        ReplayEvent {
+            id: crate::utils::generate_id(),
+            event_type,
+            timestamp: Utc::now(),
+            round,
+            phase: GamePhase::DayDiscussion,
+            player_id: Some(player_id.to_string()),
+            target_id: target_id.map(str::to_string),
+            content: content.to_string(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_recorded_case_passes_and_detects_regression() {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        let state = engine.get_state();
+        let ids: Vec<String> = state.players.iter().map(|p| p.id.clone()).collect();
+        let replay = GameReplay {
+            game_id: "regression".to_string(),
+            start_time: Utc::now(),
+            end_time: None,
+            players: state.players.clone(),
+            game_events: vec![
+                event(1, GameEventType::Speech, &ids[1], None, "我不是狼人，相信我，我真的不是"),
+                event(1, GameEventType::Vote, &ids[1], Some(&ids[2]), ""),
+                event(2, GameEventType::Speech, &ids[1], None, "我不是狼，大家相信我"),
+            ],
+            ai_decisions: Vec::new(),
+            game_result: None,
+            game_config: state.game_config.clone(),
+            analysis: None,
+            notes: Vec::new(),
+            integrity_chain: Vec::new(),
+            spilled_events: 0,
+            chain_anchor: None,
+        };
+
+        let mut case = record_case("defensive", replay, 0.01);
+        assert!(check_case(&case).is_empty());
+
+        let expectation = case.expectations.iter_mut()
+            .find(|e| e.day == 2 && e.player_id == ids[1] && e.metric == ReasoningMetric::Suspicion)
+            .unwrap();
+        expectation.min = Some(expectation.max.unwrap() + 0.1);
+        assert_eq!(check_case(&case).len(), 1);
+    }
+
+    #[test]
+    fn test_checked_in_cases() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(CASES_DIR);
+        for case in load_cases(&dir).unwrap() {
+            let failures = check_case(&case);
+            assert!(failures.is_empty(), "推理用例{}回归:\n{}", case.name, failures.join("\n"));
+        }
+    }
+}
@@ -1,8 +1,19 @@
 use crate::error::AppResult;
 use crate::types::*;
+use crate::ai::VoteSurprise;
+use crate::database::{GameRepository, ReplayEventRecord};
+use crate::memory::{ApproxSize, MemoryStats};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// 哈希链起点
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+/// 每局在内存中保留的复盘事件上限
+const REPLAY_EVENT_CAPACITY: usize = 2000;
+/// 超出上限时一次转存到数据库的事件数
+const SPILL_BATCH: usize = 500;
 
 /// 游戏复盘数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +27,26 @@ pub struct GameReplay {
     pub game_result: Option<GameResult>,
     pub game_config: GameConfig,
     pub analysis: Option<GameAnalysis>,
+    #[serde(default)]
+    pub notes: Vec<PlayerNote>,
+    /// 按事件顺序计算的哈希链，与game_events一一对应
+    #[serde(default)]
+    pub integrity_chain: Vec<String>,
+    /// 已转存到数据库的早期事件数，game_events从这之后开始
+    #[serde(default)]
+    pub spilled_events: usize,
+    /// 最后一个转存事件的链哈希，内存中的链从这里接续
+    #[serde(default)]
+    pub chain_anchor: Option<String>,
+}
+
+/// 等待写入数据库的早期复盘事件
+#[derive(Debug, Clone)]
+struct SpilledEvent {
+    game_id: String,
+    index: usize,
+    event: GameEvent,
+    hash: String,
 }
 
 /// 游戏事件
@@ -32,6 +63,17 @@ pub struct GameEvent {
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl ApproxSize for GameEvent {
+    fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.id.capacity()
+            + self.content.capacity()
+            + self.player_id.as_ref().map_or(0, String::capacity)
+            + self.target_id.as_ref().map_or(0, String::capacity)
+            + self.metadata.iter().map(|(k, v)| k.capacity() + v.to_string().len()).sum::<usize>()
+    }
+}
+
 /// 游戏事件类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GameEventType {
@@ -57,6 +99,22 @@ pub enum GameEventType {
     LastWords,
     /// 系统公告
     SystemAnnouncement,
+    /// 完整性记录（特权命令调用）
+    IntegrityNote,
+    /// AI即时反应
+    Reaction,
+    /// 打断发言提出异议
+    Objection,
+    /// 教练复盘反馈
+    CoachFeedback,
+    /// AI局内性格漂移
+    PersonalityDrift,
+    /// 娱乐局撤销操作
+    Undo,
+    /// 真人挂机托管
+    Autopilot,
+    /// 狼人夜间刀人投票（提名与计票结果）
+    NightVote,
 }
 
 /// AI决策记录
@@ -71,6 +129,8 @@ pub struct AIDecision {
     pub confidence: f32,
     pub execution_time_ms: u64,
     pub alternatives: Vec<AlternativeDecision>,
+    /// 提供该决策的LLM（规则引擎决策为None）
+    pub served_by: Option<String>,
 }
 
 /// 决策类型
@@ -112,6 +172,20 @@ pub struct GameAnalysis {
     pub strategic_insights: Vec<StrategicInsight>,
     pub ai_performance_metrics: AIPerformanceMetrics,
     pub game_statistics: GameStatistics,
+    /// 每个AI的性格漂移轨迹：玩家ID -> 按时间排列的漂移点
+    #[serde(default)]
+    pub personality_drift: HashMap<String, Vec<DriftPoint>>,
+    /// 每天最出人意料的一票，按天排列
+    #[serde(default)]
+    pub surprising_votes: Vec<VoteSurprise>,
+}
+
+/// 性格漂移轨迹上的一个点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftPoint {
+    pub round: u32,
+    pub cause: String,
+    pub traits: PersonalityTraits,
 }
 
 /// 获胜分析
@@ -196,10 +270,82 @@ pub struct GameStatistics {
     pub faction_balance: HashMap<Faction, f32>,
 }
 
+/// 复盘完整性校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub game_id: String,
+    pub valid: bool,
+    pub event_count: usize,
+    /// 第一个哈希对不上的事件序号
+    pub first_invalid_index: Option<usize>,
+    /// 链尾哈希，可用于比对两份复盘是否一致
+    pub head_hash: Option<String>,
+}
+
+/// 计算链上下一个哈希：sha256(前一个哈希 + 事件的规范化JSON)
+fn chain_hash(previous: &str, event: &GameEvent) -> AppResult<String> {
+    // 先转成Value再序列化，保证metadata键序稳定
+    let payload = serde_json::to_string(&serde_json::to_value(event)?)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(previous.as_bytes());
+    hasher.update(payload.as_bytes());
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 逐个重算哈希链，校验复盘是否被篡改
+///
+/// 早期事件已转存时，从锚点哈希开始校验内存中的部分
+pub fn verify_integrity(replay: &GameReplay) -> AppResult<IntegrityReport> {
+    let mut previous = replay.chain_anchor.clone().unwrap_or_else(|| GENESIS_HASH.to_string());
+    let mut first_invalid_index = None;
+
+    for (index, event) in replay.game_events.iter().enumerate() {
+        let expected = chain_hash(&previous, event)?;
+        if replay.integrity_chain.get(index) != Some(&expected) {
+            first_invalid_index = Some(replay.spilled_events + index);
+            break;
+        }
+        previous = expected;
+    }
+
+    // 事件被删除时链会比事件多
+    if first_invalid_index.is_none() && replay.integrity_chain.len() != replay.game_events.len() {
+        first_invalid_index = Some(replay.spilled_events + replay.game_events.len());
+    }
+
+    Ok(IntegrityReport {
+        game_id: replay.game_id.clone(),
+        valid: first_invalid_index.is_none(),
+        event_count: replay.spilled_events + replay.game_events.len(),
+        first_invalid_index,
+        head_hash: replay.integrity_chain.last().cloned(),
+    })
+}
+
+/// 从头校验转存到数据库的复盘事件，返回可以保留的前缀长度
+///
+/// 遇到无法解析、序号不连续或哈希链断开的事件即停止，之后的事件无法证明未被篡改
+pub fn salvageable_prefix(records: &[ReplayEventRecord]) -> usize {
+    let mut previous = GENESIS_HASH.to_string();
+    for (index, record) in records.iter().enumerate() {
+        let intact = record.event_index == index as i64
+            && serde_json::from_str::<GameEvent>(&record.event_data).ok()
+                .and_then(|event| chain_hash(&previous, &event).ok())
+                .is_some_and(|hash| hash == record.chain_hash);
+        if !intact {
+            return index;
+        }
+        previous = record.chain_hash.clone();
+    }
+    records.len()
+}
+
 /// 复盘系统
 pub struct ReplaySystem {
     replays: HashMap<String, GameReplay>,
     analyzer: GameAnalyzer,
+    spill_queue: VecDeque<SpilledEvent>,
 }
 
 impl ReplaySystem {
@@ -207,6 +353,7 @@ impl ReplaySystem {
         Self {
             replays: HashMap::new(),
             analyzer: GameAnalyzer::new(),
+            spill_queue: VecDeque::new(),
         }
     }
 
@@ -222,6 +369,10 @@ impl ReplaySystem {
             game_result: None,
             game_config: config,
             analysis: None,
+            notes: Vec::new(),
+            integrity_chain: Vec::new(),
+            spilled_events: 0,
+            chain_anchor: None,
         };
 
         self.replays.insert(game_id, replay);
@@ -232,11 +383,106 @@ impl ReplaySystem {
     /// 记录游戏事件
     pub fn record_event(&mut self, game_id: &str, event: GameEvent) -> AppResult<()> {
         if let Some(replay) = self.replays.get_mut(game_id) {
+            let previous = replay.integrity_chain.last()
+                .map(String::as_str)
+                .unwrap_or(GENESIS_HASH);
+            let hash = chain_hash(previous, &event)?;
             replay.game_events.push(event);
+            replay.integrity_chain.push(hash);
+
+            // 超出上限时把最早的一批事件移出内存，等待写入数据库
+            if replay.game_events.len() > REPLAY_EVENT_CAPACITY {
+                let start = replay.spilled_events;
+                let events = replay.game_events.drain(..SPILL_BATCH);
+                let hashes = replay.integrity_chain.drain(..SPILL_BATCH);
+                for (offset, (event, hash)) in events.zip(hashes).enumerate() {
+                    replay.chain_anchor = Some(hash.clone());
+                    self.spill_queue.push_back(SpilledEvent {
+                        game_id: game_id.to_string(),
+                        index: start + offset,
+                        event,
+                        hash,
+                    });
+                }
+                replay.spilled_events += SPILL_BATCH;
+            }
         }
         Ok(())
     }
 
+    /// 按序把转存队列写入数据库；写入失败时保留剩余事件，下次继续
+    pub async fn flush_spilled(&mut self, repository: Option<&GameRepository>) {
+        let repository = match repository {
+            Some(repository) => repository,
+            None => {
+                if !self.spill_queue.is_empty() {
+                    log::warn!("数据库不可用，丢弃{}条早期复盘事件", self.spill_queue.len());
+                    self.spill_queue.clear();
+                }
+                return;
+            }
+        };
+
+        while let Some(spilled) = self.spill_queue.front() {
+            if let Err(e) = repository.record_replay_event(&spilled.game_id, spilled.index, &spilled.event, &spilled.hash).await {
+                log::warn!("转存复盘事件#{}失败，稍后重试: {}", spilled.index, e);
+                return;
+            }
+            self.spill_queue.pop_front();
+        }
+    }
+
+    /// 用数据库中的早期事件补全复盘，得到完整的事件与哈希链
+    pub fn full_replay(&self, game_id: &str, spilled: Vec<ReplayEventRecord>) -> AppResult<Option<GameReplay>> {
+        let mut replay = match self.replays.get(game_id) {
+            Some(replay) => replay.clone(),
+            None => return Ok(None),
+        };
+
+        let mut events = Vec::with_capacity(replay.spilled_events + replay.game_events.len());
+        let mut chain = Vec::with_capacity(events.capacity());
+        for record in spilled {
+            events.push(serde_json::from_str::<GameEvent>(&record.event_data)?);
+            chain.push(record.chain_hash);
+        }
+        // 还在转存队列里的事件
+        let loaded = events.len();
+        for queued in self.spill_queue.iter().filter(|q| q.game_id == game_id && q.index >= loaded) {
+            events.push(queued.event.clone());
+            chain.push(queued.hash.clone());
+        }
+
+        events.append(&mut replay.game_events);
+        chain.append(&mut replay.integrity_chain);
+        replay.game_events = events;
+        replay.integrity_chain = chain;
+        replay.spilled_events = 0;
+        replay.chain_anchor = None;
+        Ok(Some(replay))
+    }
+
+    /// 复盘事件的内存统计
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            component: "replay_events".to_string(),
+            entries: self.replays.values().map(|r| r.game_events.len()).sum(),
+            capacity: Some(REPLAY_EVENT_CAPACITY * self.replays.len()),
+            evicted: self.replays.values().map(|r| r.spilled_events as u64).sum(),
+            approx_bytes: self.replays.values()
+                .flat_map(|r| r.game_events.iter())
+                .chain(self.spill_queue.iter().map(|q| &q.event))
+                .map(ApproxSize::approx_bytes)
+                .sum(),
+        }
+    }
+
+    /// 校验内存中的复盘完整性
+    pub fn verify_replay(&self, game_id: &str) -> AppResult<IntegrityReport> {
+        let replay = self.replays.get(game_id)
+            .ok_or_else(|| crate::error::AppError::NotFound(format!("游戏复盘不存在: {}", game_id)))?;
+        verify_integrity(replay)
+    }
+
     /// 记录AI决策
     pub fn record_ai_decision(&mut self, game_id: &str, decision: AIDecision) -> AppResult<()> {
         if let Some(replay) = self.replays.get_mut(game_id) {
@@ -245,6 +491,15 @@ impl ReplaySystem {
         Ok(())
     }
 
+    /// 记录玩家笔记，同一玩家同一天的笔记会被覆盖
+    pub fn record_note(&mut self, game_id: &str, note: PlayerNote) -> AppResult<()> {
+        if let Some(replay) = self.replays.get_mut(game_id) {
+            replay.notes.retain(|n| !(n.player_id == note.player_id && n.day == note.day));
+            replay.notes.push(note);
+        }
+        Ok(())
+    }
+
     /// 结束游戏记录并分析
     pub async fn finish_recording(&mut self, game_id: &str, result: GameResult) -> AppResult<()> {
         if let Some(replay) = self.replays.get_mut(game_id) {
@@ -402,7 +657,13 @@ impl ReplaySystem {
 
         // 游戏结果
         if let Some(result) = &replay.game_result {
-            html.push_str(&format!("<h2>游戏结果</h2><p>获胜方: {:?}</p>", result.winner));
+            html.push_str(&format!(
+                "<h2>游戏结果</h2><p>获胜方: {:?}</p><p>时长: {}秒，总票数: {}</p><p>存活: {}</p>",
+                result.winner,
+                result.game_duration,
+                result.total_votes,
+                result.survivors.join("、")
+            ));
         }
 
         html.push_str("</body></html>");
@@ -467,6 +728,8 @@ impl GameAnalyzer {
         let strategic_insights = self.extract_strategic_insights(replay).await?;
         let ai_performance_metrics = self.calculate_ai_metrics(replay).await?;
         let game_statistics = self.calculate_game_statistics(replay).await?;
+        let personality_drift = self.trace_personality_drift(replay);
+        let surprising_votes = self.most_surprising_votes(replay);
 
         Ok(GameAnalysis {
             winner_analysis,
@@ -475,9 +738,52 @@ impl GameAnalyzer {
             strategic_insights,
             ai_performance_metrics,
             game_statistics,
+            personality_drift,
+            surprising_votes,
         })
     }
 
+    /// 从投票结算记录里挑出每天惊讶度最高的一票（同一天的PK重新投票一起比较）
+    fn most_surprising_votes(&self, replay: &GameReplay) -> Vec<VoteSurprise> {
+        let mut by_day: HashMap<u32, VoteSurprise> = HashMap::new();
+        let surprises = replay.game_events.iter()
+            .filter(|e| matches!(e.event_type, GameEventType::Vote))
+            .filter_map(|e| e.metadata.get("surprises"))
+            .filter_map(|surprises| serde_json::from_value::<Vec<VoteSurprise>>(surprises.clone()).ok())
+            .flatten();
+        for surprise in surprises {
+            match by_day.get(&surprise.day) {
+                Some(best) if best.surprise >= surprise.surprise => {}
+                _ => {
+                    by_day.insert(surprise.day, surprise);
+                }
+            }
+        }
+        
+        let mut highlights: Vec<VoteSurprise> = by_day.into_values().collect();
+        highlights.sort_by_key(|surprise| surprise.day);
+        highlights
+    }
+
+    /// 从复盘事件中整理每个AI的性格漂移轨迹
+    fn trace_personality_drift(&self, replay: &GameReplay) -> HashMap<String, Vec<DriftPoint>> {
+        let mut trajectories: HashMap<String, Vec<DriftPoint>> = HashMap::new();
+        for event in replay.game_events.iter().filter(|e| matches!(e.event_type, GameEventType::PersonalityDrift)) {
+            let (player_id, after) = match (&event.player_id, event.metadata.get("after")) {
+                (Some(player_id), Some(after)) => (player_id, after),
+                _ => continue,
+            };
+            if let Ok(traits) = serde_json::from_value::<PersonalityTraits>(after.clone()) {
+                trajectories.entry(player_id.clone()).or_default().push(DriftPoint {
+                    round: event.round,
+                    cause: event.content.clone(),
+                    traits,
+                });
+            }
+        }
+        trajectories
+    }
+
     /// 分析获胜原因
     async fn analyze_winner(&self, replay: &GameReplay) -> AppResult<WinnerAnalysis> {
         // 实现获胜分析逻辑
@@ -642,6 +948,7 @@ impl GameAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::AppConfig;
 
     #[test]
     fn test_replay_system_creation() {
@@ -661,16 +968,88 @@ mod tests {
             game_events: vec![],
             ai_decisions: vec![],
             game_result: Some(GameResult {
-                winner: Faction::Village,
-                reason: "测试".to_string(),
+                winner: Faction::Villager,
+                game_duration: 0,
+                total_votes: 0,
+                players_killed: vec![],
                 survivors: vec![],
             }),
-            game_config: GameConfig::default(),
+            game_config: AppConfig::default().game,
             analysis: None,
+            notes: vec![],
+            integrity_chain: vec![],
+            spilled_events: 0,
+            chain_anchor: None,
         };
 
         let analysis = analyzer.analyze_game(&replay).await.unwrap();
-        assert_eq!(analysis.winner_analysis.winning_faction, Faction::Village);
+        assert_eq!(analysis.winner_analysis.winning_faction, Faction::Villager);
     }
-}
 
+    #[test]
+    fn test_spilled_events_keep_chain_valid() {
+        let mut replay_system = ReplaySystem::new();
+        replay_system.start_recording("test".to_string(), AppConfig::default().game, vec![]).unwrap();
+
+        for round in 0..(REPLAY_EVENT_CAPACITY + 1) {
+            let event = GameEvent {
+                id: round.to_string(),
+                event_type: GameEventType::SystemAnnouncement,
+                timestamp: Utc::now(),
+                round: round as u32,
+                phase: GamePhase::Night,
+                player_id: None,
+                target_id: None,
+                content: "公告".to_string(),
+                metadata: HashMap::new(),
+            };
+            replay_system.record_event("test", event).unwrap();
+        }
+
+        let replay = replay_system.get_replay("test").unwrap();
+        assert_eq!(replay.spilled_events, SPILL_BATCH);
+        assert_eq!(replay.game_events.len(), REPLAY_EVENT_CAPACITY + 1 - SPILL_BATCH);
+        assert_eq!(replay_system.spill_queue.len(), SPILL_BATCH);
+
+        let report = replay_system.verify_replay("test").unwrap();
+        assert!(report.valid);
+        assert_eq!(report.event_count, REPLAY_EVENT_CAPACITY + 1);
+
+        let full = replay_system.full_replay("test", vec![]).unwrap().unwrap();
+        assert_eq!(full.game_events.len(), REPLAY_EVENT_CAPACITY + 1);
+        assert!(verify_integrity(&full).unwrap().valid);
+    }
+
+    #[test]
+    fn test_salvage_stops_at_broken_chain() {
+        let mut previous = GENESIS_HASH.to_string();
+        let mut records: Vec<ReplayEventRecord> = (0..3)
+            .map(|index| {
+                let event = GameEvent {
+                    id: index.to_string(),
+                    event_type: GameEventType::SystemAnnouncement,
+                    timestamp: Utc::now(),
+                    round: 1,
+                    phase: GamePhase::Night,
+                    player_id: None,
+                    target_id: None,
+                    content: "公告".to_string(),
+                    metadata: HashMap::new(),
+                };
+                let hash = chain_hash(&previous, &event).unwrap();
+                previous = hash.clone();
+                ReplayEventRecord {
+                    game_id: "test".to_string(),
+                    event_index: index,
+                    event_type: "SystemAnnouncement".to_string(),
+                    event_data: serde_json::to_string(&event).unwrap(),
+                    chain_hash: hash,
+                }
+            })
+            .collect();
+        assert_eq!(salvageable_prefix(&records), 3);
+
+        records[1].event_data = "{".to_string();
+        assert_eq!(salvageable_prefix(&records), 1);
+    }
+}
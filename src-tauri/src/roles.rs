@@ -0,0 +1,136 @@
+use crate::types::{Faction, RoleType};
+use serde::Serialize;
+
+/// 所有角色，按夜晚行动顺序排列
+pub const ALL_ROLES: [RoleType; 8] = [
+    RoleType::Werewolf,
+    RoleType::DreamWolf,
+    RoleType::Gargoyle,
+    RoleType::Guard,
+    RoleType::Witch,
+    RoleType::Seer,
+    RoleType::Hunter,
+    RoleType::Villager,
+];
+
+/// 角色知识库条目，供教程、提示框和主持人使用
+#[derive(Debug, Clone, Serialize)]
+pub struct RoleInfo {
+    pub role_type: RoleType,
+    pub name: &'static str,
+    pub faction: Faction,
+    /// 一句话介绍
+    pub summary: &'static str,
+    pub abilities: Vec<&'static str>,
+    /// 夜晚行动顺序（从1开始），没有夜晚行动时为None
+    pub night_order: Option<u8>,
+    pub can_vote: bool,
+    pub win_condition: &'static str,
+    pub strategies: Vec<&'static str>,
+    /// 克制或针对该角色的常见手段
+    pub counters: Vec<&'static str>,
+}
+
+impl RoleInfo {
+    /// 带角色名的完整描述，例如“预言家：每晚可以查验一名玩家的身份”
+    pub fn description(&self) -> String {
+        format!("{}：{}", self.name, self.summary)
+    }
+
+    /// 是否有夜晚行动
+    pub fn has_night_action(&self) -> bool {
+        self.night_order.is_some()
+    }
+}
+
+const WOLF_WIN: &str = "狼人数量不少于好人时，狼人阵营获胜";
+const GOOD_WIN: &str = "所有狼人出局时，好人阵营获胜";
+
+/// 查询角色的知识库条目
+pub fn role_info(role_type: &RoleType) -> RoleInfo {
+    let faction = role_type.faction();
+    let win_condition = match faction {
+        Faction::Werewolf => WOLF_WIN,
+        Faction::Villager => GOOD_WIN,
+    };
+
+    let (name, summary, abilities, night_order, strategies, counters) = match role_type {
+        RoleType::Werewolf => (
+            "狼人",
+            "夜晚可以杀死一名玩家，目标是消灭所有好人",
+            vec!["每晚与狼队友商量并刀杀一名玩家", "可以在狼人频道与队友私聊"],
+            Some(1),
+            vec!["白天伪装成好人，跟随多数意见投票", "必要时悍跳预言家，给真预言家发查杀", "优先刀掉发言好、被信任的神职"],
+            vec!["预言家的查杀", "守卫守护刀口", "女巫解药救人"],
+        ),
+        RoleType::DreamWolf => (
+            "梦狼",
+            "狼人阵营，狼队认识梦狼，但梦狼不认识队友，其他狼人全部出局后才能刀人",
+            vec!["狼队知道梦狼的身份，梦狼却不认识队友", "其他狼人全部出局后，夜晚可以刀人"],
+            Some(1),
+            vec!["通过发言和投票寻找可能的队友", "前期像好人一样发言，留到残局"],
+            vec!["预言家的查杀", "狼队出局后唯一的刀口来源，暴露后容易被集中投票"],
+        ),
+        RoleType::Gargoyle => (
+            "石像鬼",
+            "狼人阵营，与狼队互不相识，其他狼人全部出局后才能刀人",
+            vec!["与狼队互不相识，不参与狼人频道", "其他狼人全部出局后，夜晚可以刀人"],
+            Some(1),
+            vec!["靠观察投票和发言推测谁是狼队友", "不要过早暴露立场，争取活到残局"],
+            vec!["预言家的查杀", "好人对票型的复盘"],
+        ),
+        RoleType::Guard => (
+            "守卫",
+            "每晚可以保护一名玩家，使其免受狼人攻击",
+            vec!["每晚守护一名玩家，使其当晚不被狼人刀死"],
+            Some(2),
+            vec!["优先守护已经起跳的真预言家", "注意“同守同救”规则，避免和女巫撞药"],
+            vec!["狼人刀守卫本人", "狼人空刀或改刀避开守护目标"],
+        ),
+        RoleType::Witch => (
+            "女巫",
+            "拥有一瓶解药和一瓶毒药，可以救人或杀人",
+            vec!["解药：救活当晚被刀的玩家，只能使用一次", "毒药：毒死一名玩家，只能使用一次"],
+            Some(3),
+            vec!["首夜解药通常用来救人，保住好人数量", "毒药留给确定的狼人，避免毒错好人"],
+            vec!["狼人自刀骗药", "悍跳狼诱导女巫毒错人"],
+        ),
+        RoleType::Seer => (
+            "预言家",
+            "每晚可以查验一名玩家的身份",
+            vec!["每晚查验一名玩家是好人还是狼人"],
+            Some(4),
+            vec!["尽早起跳报出查验结果，留下清晰的警徽流", "优先查验发言可疑或位置关键的玩家"],
+            vec!["狼人悍跳对跳预言家", "狼人夜晚优先刀掉预言家"],
+        ),
+        RoleType::Hunter => (
+            "猎人",
+            "被投票出局或被狼人杀死时，可以带走一名玩家",
+            vec!["出局时可以开枪带走一名玩家"],
+            None,
+            vec!["身份暴露后可以威慑狼人不敢投票", "开枪前仔细复盘，带走最可疑的玩家"],
+            vec!["女巫毒药（被毒时无法开枪）", "狼人诱导猎人开枪带走好人"],
+        ),
+        RoleType::Villager => (
+            "村民",
+            "普通村民，没有特殊技能，依靠投票和推理找出狼人",
+            vec!["白天参与发言和投票"],
+            None,
+            vec!["认真听发言，跟随可信的预言家投票", "不要冒充神职，以免干扰真神职的信息"],
+            vec!["狼人带节奏引导好人投错票"],
+        ),
+    };
+
+    RoleInfo {
+        role_type: role_type.clone(),
+        name,
+        faction,
+        summary,
+        abilities,
+        night_order,
+        can_vote: true,
+        win_condition,
+        strategies,
+        counters,
+    }
+}
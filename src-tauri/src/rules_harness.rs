@@ -0,0 +1,365 @@
+use crate::config::AppConfig;
+use crate::game_engine::GameEngine;
+use crate::types::*;
+use crate::utils;
+use crate::validator::{ActionValidator, PlayerAction};
+use chrono::Utc;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+/// 随机对局使用的人数
+const PLAYER_COUNTS: [u8; 7] = [6, 8, 9, 10, 12, 15, 18];
+/// 随机发言模板，{seat}替换为随机座位号，用来触发声明板的解析
+const SPEECH_TEMPLATES: [&str; 6] = [
+    "我是预言家，{seat}号是狼",
+    "我是预言家，{seat}号是金水",
+    "我是女巫",
+    "{seat}号发言有问题",
+    "过",
+    "我是好人，大家相信我",
+];
+
+/// 违反的规则不变量，附带复现用的种子和事件日志（JSON）
+#[derive(Debug, Clone)]
+pub struct InvariantViolation {
+    pub seed: u64,
+    pub rule: String,
+    pub log: String,
+}
+
+/// 规则属性测试：用随机但合法的操作序列驱动引擎，每一步后检查规则不变量。
+/// 新增角色或规则后跑一遍，可以尽早发现边界情况
+pub struct RulesHarness {
+    rng: StdRng,
+    seed: u64,
+    engine: GameEngine,
+    total_players: usize,
+    initial_wolves: usize,
+}
+
+/// 从seed开始连续跑games局随机对局，返回第一处违反的不变量
+pub fn check_random_games(games: u64, seed: u64) -> Result<(), InvariantViolation> {
+    for offset in 0..games {
+        RulesHarness::new(seed.wrapping_add(offset))?.run()?;
+    }
+    Ok(())
+}
+
+impl RulesHarness {
+    /// 按种子随机生成配置并开局
+    pub fn new(seed: u64) -> Result<Self, InvariantViolation> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let players = *PLAYER_COUNTS.choose(&mut rng).unwrap_or(&8);
+        Self::with_rng(rng, seed, players)
+    }
+
+    /// 指定人数开局，其余配置按种子随机
+    pub fn with_players(seed: u64, players: u8) -> Result<Self, InvariantViolation> {
+        Self::with_rng(StdRng::seed_from_u64(seed), seed, players)
+    }
+
+    fn with_rng(mut rng: StdRng, seed: u64, players: u8) -> Result<Self, InvariantViolation> {
+        let mut config = AppConfig::default().game;
+        config.total_players = players;
+        // 夜晚不计时，阶段推进不依赖真实时间
+        config.night_timing = NightTimingConfig { min_duration: 0, max_padding: 0 };
+        config.passive_wolf = [None, Some(RoleType::DreamWolf), Some(RoleType::Gargoyle)]
+            .choose(&mut rng)
+            .cloned()
+            .flatten();
+        let preset = if rng.gen_bool(0.5) { RulePreset::Casual } else { RulePreset::Classic };
+        preset.apply(&mut config);
+        config.vote_rules.pk_on_tie = rng.gen_bool(0.8);
+        config.vote_rules.mandatory = rng.gen_bool(0.3);
+        config.vote_rules.revote_tie = if rng.gen_bool(0.5) { RevoteTiePolicy::NoElimination } else { RevoteTiePolicy::Random };
+
+        let violation = |rule: String| InvariantViolation { seed, rule, log: String::new() };
+        let mut engine = GameEngine::new(config).map_err(|e| violation(format!("创建游戏失败: {}", e)))?;
+        engine.initialize_game().map_err(|e| violation(format!("初始化失败: {}", e)))?;
+        engine.start_game().map_err(|e| violation(format!("开局失败: {}", e)))?;
+
+        let state = engine.get_state();
+        let total_players = state.players.len();
+        let initial_wolves = state.players.iter().filter(|p| p.faction == Faction::Werewolf).count();
+        if total_players != players as usize {
+            return Err(violation(format!("开局人数{}与配置的{}人不符", total_players, players)));
+        }
+        Ok(Self { rng, seed, engine, total_players, initial_wolves })
+    }
+
+    /// 一直推进到游戏结束，期间任一步违反不变量即返回
+    pub fn run(mut self) -> Result<(), InvariantViolation> {
+        // 每晚至少有一次击杀，对局必然在这个天数内结束
+        let max_days = self.total_players as u32 * 3;
+
+        while self.engine.get_state().phase != GamePhase::GameOver {
+            if self.engine.get_state().day > max_days {
+                return Err(self.violation(format!("对局在{}天内没有结束", max_days)));
+            }
+
+            match self.engine.get_state().phase {
+                GamePhase::Night => self.play_night()?,
+                GamePhase::DayDiscussion => self.play_discussion()?,
+                GamePhase::Voting => self.play_votes()?,
+                _ => {}
+            }
+            self.check_invariants()?;
+
+            if let Err(e) = self.engine.next_phase() {
+                return Err(self.violation(format!("推进阶段失败: {}", e)));
+            }
+            self.check_invariants()?;
+        }
+        Ok(())
+    }
+
+    /// 狼队必刀，其余神职随机行动；死亡玩家的夜晚行动、女巫用过的药和守卫连守必须被拒绝
+    fn play_night(&mut self) -> Result<(), InvariantViolation> {
+        let state = self.engine.get_state().clone();
+        let alive: Vec<&Player> = state.players.iter().filter(|p| p.is_alive).collect();
+
+        if let Some(wolf) = alive.iter().find(|p| ActionValidator::can_kill(&state, p)) {
+            let targets: Vec<&&Player> = alive.iter().filter(|p| p.id != wolf.id).collect();
+            if let Some(target) = targets.choose(&mut self.rng) {
+                self.try_night_action(&wolf.id, NightActionType::Kill, &target.id);
+            }
+        }
+
+        for player in &alive {
+            let action = match player.role.role_type {
+                RoleType::Seer => NightActionType::Check,
+                RoleType::Guard => NightActionType::Protect,
+                RoleType::Witch if self.rng.gen_bool(0.5) => NightActionType::Heal,
+                RoleType::Witch => NightActionType::Poison,
+                _ => continue,
+            };
+            if self.rng.gen_bool(0.2) {
+                continue;
+            }
+            let target = match action {
+                NightActionType::Heal => self.engine.pending_kill_targets().first().cloned(),
+                _ => alive.choose(&mut self.rng).map(|p| p.id.clone()),
+            };
+            let Some(target) = target else { continue };
+            let potions = self.engine.witch_potions(&player.id);
+            let spent = match action {
+                NightActionType::Heal => !potions.antidote,
+                NightActionType::Poison => !potions.poison,
+                _ => false,
+            };
+            if spent {
+                let action = NightAction { player: player.id.clone(), action, target: Some(target) };
+                if self.engine.execute_night_action(action).is_ok() {
+                    return Err(self.violation(format!("女巫{}重复使用了药剂", player.id)));
+                }
+            } else {
+                self.try_night_action(&player.id, action, &target);
+            }
+        }
+
+        for guard in alive.iter().filter(|p| p.role.role_type == RoleType::Guard) {
+            if let Some(target) = ActionValidator::blocked_protect_target(&state, &guard.id) {
+                let action = NightAction { player: guard.id.clone(), action: NightActionType::Protect, target: Some(target.to_string()) };
+                if self.engine.execute_night_action(action).is_ok() {
+                    return Err(self.violation(format!("守卫{}连续两晚守护{}", guard.id, target)));
+                }
+            }
+        }
+
+        if let (Some(dead), Some(target)) = (state.dead_players.first(), alive.first()) {
+            let action = NightAction { player: dead.id.clone(), action: NightActionType::Kill, target: Some(target.id.clone()) };
+            if self.engine.execute_night_action(action).is_ok() {
+                return Err(self.violation(format!("死亡玩家{}在夜晚行动", dead.id)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 只提交校验器认为合法的夜晚行动
+    fn try_night_action(&mut self, player_id: &str, action: NightActionType, target: &str) {
+        let action = NightAction { player: player_id.to_string(), action, target: Some(target.to_string()) };
+        if self.engine.validate_action(&PlayerAction::NightAction(action.clone())).is_ok() {
+            let _ = self.engine.execute_night_action(action);
+        }
+    }
+
+    /// 存活玩家按座位随机发言、表达投票意向
+    fn play_discussion(&mut self) -> Result<(), InvariantViolation> {
+        let state = self.engine.get_state().clone();
+        let seats: Vec<u8> = state.players.iter().chain(state.dead_players.iter()).map(|p| p.seat).collect();
+
+        for player in state.players.iter().filter(|p| p.is_alive) {
+            if self.rng.gen_bool(0.3) {
+                continue;
+            }
+            self.engine.set_current_speaker(Some(player.id.clone()));
+            let template = SPEECH_TEMPLATES.choose(&mut self.rng).copied().unwrap_or("过");
+            let seat = seats.choose(&mut self.rng).copied().unwrap_or(1);
+            let message = ChatMessage {
+                id: utils::generate_id(),
+                sender: player.id.clone(),
+                content: template.replace("{seat}", &seat.to_string()),
+                timestamp: Utc::now(),
+                message_type: MessageType::AI,
+            };
+            if self.engine.validate_action(&PlayerAction::Speech { speaker: player.id.clone() }).is_ok() {
+                let _ = self.engine.add_chat_message(message);
+            }
+
+            if let Some(target) = state.players.choose(&mut self.rng) {
+                let _ = self.engine.set_vote_intent(player.id.clone(), target.id.clone());
+            }
+        }
+        self.engine.set_current_speaker(None);
+        Ok(())
+    }
+
+    /// 存活玩家随机投票（偶尔弃票或改票）；死亡玩家的投票必须被拒绝
+    fn play_votes(&mut self) -> Result<(), InvariantViolation> {
+        let state = self.engine.get_state().clone();
+        let alive: Vec<&Player> = state.players.iter().filter(|p| p.is_alive).collect();
+
+        for voter in &alive {
+            let ballots = if self.rng.gen_bool(0.1) { 0 } else if self.rng.gen_bool(0.1) { 2 } else { 1 };
+            for _ in 0..ballots {
+                let target = if self.rng.gen_bool(0.1) {
+                    None
+                } else {
+                    alive.choose(&mut self.rng).map(|target| target.id.clone())
+                };
+                let _ = self.engine.vote(voter.id.clone(), target);
+            }
+        }
+
+        if let (Some(dead), Some(target)) = (state.dead_players.first(), alive.first()) {
+            if self.engine.vote(dead.id.clone(), Some(target.id.clone())).is_ok() {
+                return Err(self.violation(format!("死亡玩家{}投出了选票", dead.id)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 规则不变量：人数守恒、生死状态一致、狼人数量守恒、只有存活玩家的票、强制投票时没有弃票、
+    /// 重新投票只投PK玩家、弃票不计入得票、放逐者加权票数最高（重新投票平票随机放逐时为平票者之一）、胜负与存活人数一致
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        let state = self.engine.get_state();
+
+        if state.players.len() + state.dead_players.len() != self.total_players {
+            return Err(self.violation("玩家总数发生变化".to_string()));
+        }
+        if state.players.iter().any(|p| !p.is_alive) || state.dead_players.iter().any(|p| p.is_alive) {
+            return Err(self.violation("存活名单与玩家的存活状态不一致".to_string()));
+        }
+        let alive_ids: HashSet<&str> = state.players.iter().map(|p| p.id.as_str()).collect();
+        if state.dead_players.iter().any(|p| alive_ids.contains(p.id.as_str())) {
+            return Err(self.violation("玩家同时出现在存活和死亡名单中".to_string()));
+        }
+
+        let alive_wolves = state.players.iter().filter(|p| p.faction == Faction::Werewolf).count();
+        let dead_wolves = state.dead_players.iter().filter(|p| p.faction == Faction::Werewolf).count();
+        if alive_wolves + dead_wolves != self.initial_wolves {
+            return Err(self.violation(format!(
+                "狼人数量不守恒：存活{}，死亡{}，开局{}",
+                alive_wolves, dead_wolves, self.initial_wolves
+            )));
+        }
+
+        if let Some(vote) = state.votes.iter().find(|v| !alive_ids.contains(v.voter.as_str())) {
+            return Err(self.violation(format!("死亡玩家{}的选票被计入", vote.voter)));
+        }
+
+        if let Some(vote) = state.votes.iter().find(|v| v.is_abstention() && state.game_config.vote_rules.mandatory) {
+            return Err(self.violation(format!("强制投票规则下{}弃票被接受", vote.voter)));
+        }
+        if let Some(vote) = state.votes.iter()
+            .find(|v| !v.is_abstention() && !state.pk_candidates.is_empty() && !state.pk_candidates.contains(&v.target))
+        {
+            return Err(self.violation(format!("重新投票中{}投给了不在PK中的{}", vote.voter, vote.target)));
+        }
+        if let Some(vote) = state.votes.iter().find(|v| state.pk_candidates.contains(&v.voter)) {
+            return Err(self.violation(format!("PK中的{}参与了重新投票", vote.voter)));
+        }
+
+        for outcome in &state.vote_outcomes {
+            if outcome.pk && (outcome.revote || outcome.tied.len() < 2) {
+                return Err(self.violation(format!("第{}天的PK不是由首轮投票平票引起", outcome.day)));
+            }
+            if outcome.tally.iter().any(|t| t.target == VoteRecord::ABSTAIN) {
+                return Err(self.violation(format!("第{}天的弃票被计入得票", outcome.day)));
+            }
+            let Some(eliminated) = &outcome.eliminated else { continue };
+            if outcome.revote && outcome.tied.contains(eliminated) {
+                continue;
+            }
+            let top = outcome.tally.iter().find(|t| &t.target == eliminated).map_or(0.0, |t| t.weight);
+            if outcome.tally.iter().any(|t| &t.target != eliminated && t.weight >= top) {
+                return Err(self.violation(format!("第{}天放逐的{}不是加权票数唯一最高", outcome.day, eliminated)));
+            }
+        }
+
+        if state.phase == GamePhase::GameOver {
+            let alive_villagers = state.players.len() - alive_wolves;
+            let expected = utils::check_win_condition(alive_wolves, alive_villagers);
+            if state.winner.is_none() || state.winner != expected {
+                return Err(self.violation(format!(
+                    "胜负与存活人数不符：判定{:?}，存活狼人{}，存活好人{}",
+                    state.winner, alive_wolves, alive_villagers
+                )));
+            }
+            let survivors = state.result.as_ref().map(|r| r.survivors.len());
+            if survivors != Some(state.players.len()) {
+                return Err(self.violation("结算结果的幸存者与存活名单不一致".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    fn violation(&self, rule: String) -> InvariantViolation {
+        InvariantViolation {
+            seed: self.seed,
+            rule,
+            log: serde_json::to_string(self.engine.event_log()).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_games_hold_rule_invariants() {
+        if let Err(violation) = check_random_games(200, 20240601) {
+            panic!("种子{}违反规则: {}\n事件日志: {}", violation.seed, violation.rule, violation.log);
+        }
+    }
+
+    #[test]
+    fn test_large_boards_hold_rule_invariants() {
+        for players in 13..=utils::MAX_PLAYERS {
+            for seed in 0..20 {
+                if let Err(violation) = RulesHarness::with_players(seed, players).and_then(RulesHarness::run) {
+                    panic!("{}人局种子{}违反规则: {}\n事件日志: {}", players, violation.seed, violation.rule, violation.log);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_board_role_distribution() {
+        for players in utils::MIN_PLAYERS..=utils::MAX_PLAYERS {
+            let distribution = utils::generate_role_distribution(players);
+            let wolves = distribution[&RoleType::Werewolf];
+            assert_eq!(distribution.values().sum::<u8>(), players);
+            assert!(wolves * 2 < players, "{}人局狼人过多", players);
+            assert!(distribution[&RoleType::Villager] > 0);
+        }
+        assert_eq!(utils::generate_role_distribution(18)[&RoleType::Werewolf], 6);
+
+        let mut config = AppConfig::default().game;
+        config.total_players = utils::MAX_PLAYERS + 1;
+        assert!(GameEngine::new(config).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::llm::TokenUsage;
+use crate::types::{GamePhase, GameState, SpeechRecord};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// 对局进行中的统计，前端用它渲染不打扰的状态栏，玩家可以随时看到花了多少模型用量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStats {
+    /// 开局以来的秒数，对局结束后停止增长
+    pub elapsed_secs: u64,
+    pub day: u32,
+    pub phase: GamePhase,
+    pub speeches: usize,
+    pub human_speeches: usize,
+    pub ai_speeches: usize,
+    /// 经过模型的AI决策平均耗时，还没有调用时为None
+    pub average_ai_latency_ms: Option<u64>,
+    pub ai_calls: u32,
+    /// 本局的模型用量，接口没有返回用量时为估算值
+    pub tokens: TokenUsage,
+    pub phases_completed: u32,
+}
+
+/// 本局的计时和计数，开局时重置
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    started: Option<Instant>,
+    finished: Option<Instant>,
+    phases_completed: u32,
+    ai_latency_total_ms: u64,
+    ai_calls: u32,
+    /// 开局时的用量读数，用量计数跨对局累计
+    token_baseline: TokenUsage,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self, token_baseline: TokenUsage) {
+        *self = Self { token_baseline, ..Self::default() };
+    }
+
+    pub fn start(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    pub fn phase_completed(&mut self, game_over: bool) {
+        self.phases_completed += 1;
+        if game_over && self.finished.is_none() {
+            self.finished = Some(Instant::now());
+        }
+    }
+
+    pub fn record_ai_latency(&mut self, latency_ms: u64) {
+        self.ai_latency_total_ms += latency_ms;
+        self.ai_calls += 1;
+    }
+
+    pub fn stats(&self, state: &GameState, speeches: &[SpeechRecord], tokens: TokenUsage) -> SessionStats {
+        let elapsed_secs = match (self.started, self.finished) {
+            (Some(started), Some(finished)) => finished.duration_since(started).as_secs(),
+            (Some(started), None) => started.elapsed().as_secs(),
+            (None, _) => 0,
+        };
+        let ai_speeches = speeches.iter()
+            .filter(|speech| state.players.iter().any(|p| p.id == speech.speaker && p.is_ai))
+            .count();
+
+        SessionStats {
+            elapsed_secs,
+            day: state.day,
+            phase: state.phase.clone(),
+            speeches: speeches.len(),
+            human_speeches: speeches.len() - ai_speeches,
+            ai_speeches,
+            average_ai_latency_ms: (self.ai_calls > 0).then(|| self.ai_latency_total_ms / self.ai_calls as u64),
+            ai_calls: self.ai_calls,
+            tokens: tokens.since(&self.token_baseline),
+            phases_completed: self.phases_completed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::game_engine::GameEngine;
+
+    #[test]
+    fn test_stats_count_only_this_game() {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        let state = engine.get_state();
+        let baseline = TokenUsage { prompt_tokens: 1000, completion_tokens: 200, requests: 5 };
+
+        let mut tracker = SessionTracker::new();
+        tracker.reset(baseline);
+        tracker.start();
+        tracker.record_ai_latency(300);
+        tracker.record_ai_latency(500);
+        tracker.phase_completed(false);
+
+        let current = TokenUsage { prompt_tokens: 1600, completion_tokens: 250, requests: 7 };
+        let stats = tracker.stats(state, &[], current);
+        assert_eq!(stats.average_ai_latency_ms, Some(400));
+        assert_eq!(stats.tokens, TokenUsage { prompt_tokens: 600, completion_tokens: 50, requests: 2 });
+        assert_eq!(stats.phases_completed, 1);
+        assert_eq!(stats.speeches, 0);
+    }
+}
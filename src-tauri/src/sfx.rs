@@ -0,0 +1,212 @@
+use crate::events::GameEvent;
+use crate::types::GamePhase;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use log::{info, warn};
+
+/// 音效目录，放在数据目录下；同名文件会覆盖内置音效
+pub const SFX_DIR: &str = "sfx";
+/// 支持替换的音频格式，按顺序查找
+const SFX_EXTENSIONS: [&str; 3] = ["wav", "ogg", "mp3"];
+/// 内置音效的采样率
+const SAMPLE_RATE: u32 = 22050;
+
+/// 需要音效提示的游戏时刻
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SfxCue {
+    NightFall,
+    Dawn,
+    DeathReveal,
+    VoteResult,
+    TimerWarning,
+    GameOver,
+}
+
+impl SfxCue {
+    /// 替换文件的文件名（不含扩展名）
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            SfxCue::NightFall => "night_fall",
+            SfxCue::Dawn => "dawn",
+            SfxCue::DeathReveal => "death_reveal",
+            SfxCue::VoteResult => "vote_result",
+            SfxCue::TimerWarning => "timer_warning",
+            SfxCue::GameOver => "game_over",
+        }
+    }
+
+    /// 事件对应的音效
+    pub fn for_event(event: &GameEvent) -> Option<Self> {
+        match event {
+            GameEvent::PhaseChanged { phase: GamePhase::Night, .. } => Some(SfxCue::NightFall),
+            GameEvent::DawnAnnounced { .. } => Some(SfxCue::Dawn),
+            GameEvent::PlayerDied { .. } => Some(SfxCue::DeathReveal),
+            GameEvent::VoteResolved { .. } => Some(SfxCue::VoteResult),
+            GameEvent::VoteDeadlineWarning { .. } => Some(SfxCue::TimerWarning),
+            GameEvent::GameEnded { .. } => Some(SfxCue::GameOver),
+            _ => None,
+        }
+    }
+
+    /// 内置音效的音符（频率Hz，时长ms），频率为0表示休止
+    fn default_notes(&self) -> &'static [(f32, u32)] {
+        match self {
+            SfxCue::NightFall => &[(392.0, 220), (329.6, 220), (261.6, 420)],
+            SfxCue::Dawn => &[(261.6, 180), (329.6, 180), (392.0, 180), (523.3, 360)],
+            SfxCue::DeathReveal => &[(196.0, 450), (185.0, 550)],
+            SfxCue::VoteResult => &[(440.0, 120), (0.0, 60), (440.0, 120), (659.3, 260)],
+            SfxCue::TimerWarning => &[(880.0, 90), (0.0, 90), (880.0, 90)],
+            SfxCue::GameOver => &[(261.6, 200), (392.0, 200), (523.3, 200), (784.0, 500)],
+        }
+    }
+}
+
+/// 音效配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SfxConfig {
+    pub enabled: bool,
+    pub master_volume: f32,
+    /// 单项音量（0~1），未设置的音效按1处理
+    #[serde(default)]
+    pub volumes: HashMap<SfxCue, f32>,
+}
+
+impl Default for SfxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            master_volume: 0.7,
+            volumes: HashMap::new(),
+        }
+    }
+}
+
+impl SfxConfig {
+    pub fn volume_for(&self, cue: SfxCue) -> f32 {
+        (self.master_volume * self.volumes.get(&cue).copied().unwrap_or(1.0)).clamp(0.0, 1.0)
+    }
+}
+
+/// 推送给前端的音效播放请求
+///
+/// 音效走独立的播放通道，与TTS朗读同时混音，不进入朗读队列，也不打断朗读
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SfxPlayback {
+    pub cue: SfxCue,
+    pub volume: f32,
+}
+
+impl SfxPlayback {
+    pub const EVENT_NAME: &'static str = "game://sfx";
+}
+
+/// 音效管理器：把游戏事件映射为音效，并提供音效文件
+pub struct SfxManager {
+    dir: Option<PathBuf>,
+}
+
+impl SfxManager {
+    pub fn new(data_dir: Option<&Path>) -> Self {
+        Self {
+            dir: data_dir.map(|dir| dir.join(SFX_DIR)),
+        }
+    }
+
+    /// 事件需要播放的音效，音效关闭或音量为0时不播放
+    pub fn playback_for(event: &GameEvent, config: &SfxConfig) -> Option<SfxPlayback> {
+        if !config.enabled {
+            return None;
+        }
+        let cue = SfxCue::for_event(event)?;
+        let volume = config.volume_for(cue);
+        (volume > 0.0).then_some(SfxPlayback { cue, volume })
+    }
+
+    /// 读取音效：优先使用数据目录下用户替换的文件，否则使用内置音效
+    pub fn load(&self, cue: SfxCue) -> Vec<u8> {
+        if let Some(dir) = &self.dir {
+            for extension in SFX_EXTENSIONS {
+                let path = dir.join(format!("{}.{}", cue.file_stem(), extension));
+                if !path.exists() {
+                    continue;
+                }
+                match std::fs::read(&path) {
+                    Ok(data) => {
+                        info!("使用自定义音效: {:?}", path);
+                        return data;
+                    }
+                    Err(e) => warn!("读取音效{:?}失败，使用内置音效: {}", path, e),
+                }
+            }
+        }
+        synthesize(cue.default_notes())
+    }
+}
+
+/// 合成内置音效：正弦音符加短淡入淡出，输出16位单声道WAV
+fn synthesize(notes: &[(f32, u32)]) -> Vec<u8> {
+    let fade = (SAMPLE_RATE / 100) as usize;
+    let mut samples: Vec<i16> = Vec::new();
+    for &(frequency, millis) in notes {
+        let count = (SAMPLE_RATE as u64 * millis as u64 / 1000) as usize;
+        for i in 0..count {
+            if frequency <= 0.0 {
+                samples.push(0);
+                continue;
+            }
+            let envelope = (i.min(count - i) as f32 / fade as f32).min(1.0);
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let value = (2.0 * std::f32::consts::PI * frequency * t).sin() * envelope * 0.6;
+            samples.push((value * i16::MAX as f32) as i16);
+        }
+    }
+
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_pack_is_valid_wav() {
+        let manager = SfxManager::new(None);
+        let wav = manager.load(SfxCue::Dawn);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        let data_len = u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]) as usize;
+        assert_eq!(wav.len(), 44 + data_len);
+    }
+
+    #[test]
+    fn test_per_cue_volume_and_mute() {
+        let mut config = SfxConfig::default();
+        config.volumes.insert(SfxCue::TimerWarning, 0.0);
+        let night = GameEvent::PhaseChanged { day: 1, phase: GamePhase::Night };
+        let warning = GameEvent::VoteDeadlineWarning { player_ids: Vec::new(), seconds_left: 10 };
+
+        let playback = SfxManager::playback_for(&night, &config).unwrap();
+        assert_eq!(playback.cue, SfxCue::NightFall);
+        assert!((playback.volume - 0.7).abs() < 1e-6);
+        assert!(SfxManager::playback_for(&warning, &config).is_none());
+    }
+}
@@ -0,0 +1,63 @@
+use crate::chat::ChannelMessage;
+use crate::types::*;
+use crate::utils;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+/// 亡者频道的旁观者：死亡的AI已经知道全部身份，可以在亡者频道闲聊点评
+///
+/// 亡者频道的内容只对死亡玩家可见，不会进入存活AI的上下文
+pub struct Spectator;
+
+impl Spectator {
+    /// 按配置的频率挑选本次发言的死亡AI
+    pub fn pick_commentators(state: &GameState, config: &SpectatorConfig) -> Vec<Player> {
+        if !config.ai_comments || config.frequency <= 0.0 {
+            return Vec::new();
+        }
+
+        let mut rng = thread_rng();
+        let mut candidates: Vec<&Player> = state.dead_players.iter()
+            .filter(|p| p.is_ai)
+            .collect();
+        candidates.shuffle(&mut rng);
+
+        candidates.into_iter()
+            .filter(|_| rng.gen::<f32>() < config.frequency)
+            .take(config.max_per_event)
+            .cloned()
+            .collect()
+    }
+
+    /// 旁观点评的提示词，包含全部玩家的真实身份和亡者频道最近的聊天
+    pub fn comment_prompt(player: &Player, state: &GameState, recent: &[ChannelMessage]) -> String {
+        let mut everyone: Vec<&Player> = state.players.iter()
+            .chain(state.dead_players.iter())
+            .collect();
+        everyone.sort_by_key(|p| p.seat);
+
+        let roster = everyone.iter()
+            .map(|p| format!(
+                "{}：{}{}",
+                p.display_name(),
+                utils::get_role_name(&p.role.role_type),
+                if p.is_alive { "" } else { "（已出局）" }
+            ))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chat = recent.iter()
+            .map(|m| format!("{}: {}", m.sender, m.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!(
+            "你是已经出局的{}（{}），现在在亡者频道观战，已经知道所有人的真实身份：\n{}\n现在是第{}天。亡者频道最近的聊天：\n{}\n请用一两句话轻松地点评场上局势，可以回应其他亡者。你的话不会被存活玩家看到，只输出发言内容。{}",
+            player.display_name(),
+            utils::get_role_name(&player.role.role_type),
+            roster,
+            state.day,
+            if chat.is_empty() { "（暂无）".to_string() } else { chat },
+            state.game_config.speech_style.prompt_instruction()
+        )
+    }
+}
@@ -0,0 +1,114 @@
+use crate::llm::GenerationParams;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 一次推测性预生成任务（在锁外调用LLM）
+#[derive(Debug, Clone)]
+pub struct DraftJob {
+    pub player_id: String,
+    pub prompt: String,
+    /// 该座位的生成参数覆盖
+    pub params: GenerationParams,
+    /// 构建提示词时的信息序号
+    pub sequence: u64,
+}
+
+/// 预生成的发言草稿
+#[derive(Debug, Clone)]
+pub struct SpeculativeDraft {
+    pub content: String,
+    pub served_by: String,
+    /// 预生成时实际花费的模型耗时
+    pub latency: Duration,
+    sequence: u64,
+}
+
+/// 推测性草稿缓存，以事件总线的信息序号作为失效依据
+///
+/// 草稿生成后只要有新的公开信息到达（序号变化），就不再可用
+#[derive(Debug, Default)]
+pub struct DraftCache {
+    drafts: HashMap<String, SpeculativeDraft>,
+}
+
+impl DraftCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 清空所有草稿（新游戏开始时调用）
+    pub fn clear(&mut self) {
+        self.drafts.clear();
+    }
+
+    /// 保存草稿，生成期间信息已变化时直接丢弃，返回是否保存成功
+    pub fn store(&mut self, job: &DraftJob, content: String, served_by: String, latency: Duration, current_sequence: u64) -> bool {
+        if job.sequence != current_sequence {
+            return false;
+        }
+        self.drafts.insert(job.player_id.clone(), SpeculativeDraft {
+            content,
+            served_by,
+            latency,
+            sequence: job.sequence,
+        });
+        true
+    }
+
+    /// 该玩家是否已有与当前信息一致的草稿
+    pub fn is_fresh(&self, player_id: &str, current_sequence: u64) -> bool {
+        matches!(self.drafts.get(player_id), Some(draft) if draft.sequence == current_sequence)
+    }
+
+    /// 取出仍然有效的草稿，过期草稿一并清理
+    pub fn take(&mut self, player_id: &str, current_sequence: u64) -> Option<SpeculativeDraft> {
+        self.invalidate_before(current_sequence);
+        self.drafts.remove(player_id)
+    }
+
+    /// 丢弃基于旧信息生成的草稿
+    pub fn invalidate_before(&mut self, sequence: u64) {
+        self.drafts.retain(|_, draft| draft.sequence >= sequence);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(player_id: &str, sequence: u64) -> DraftJob {
+        DraftJob {
+            player_id: player_id.to_string(),
+            prompt: String::new(),
+            params: GenerationParams::default(),
+            sequence,
+        }
+    }
+
+    fn store(cache: &mut DraftCache, job: &DraftJob, current_sequence: u64) -> bool {
+        cache.store(job, "草稿".to_string(), "test".to_string(), Duration::from_millis(10), current_sequence)
+    }
+
+    #[test]
+    fn test_draft_built_on_stale_information_is_dropped() {
+        let mut cache = DraftCache::new();
+        assert!(!store(&mut cache, &job("a", 3), 4));
+        assert!(!cache.is_fresh("a", 4));
+
+        assert!(store(&mut cache, &job("a", 4), 4));
+        assert!(cache.is_fresh("a", 4));
+        assert!(!cache.is_fresh("a", 5));
+    }
+
+    #[test]
+    fn test_take_returns_fresh_drafts_and_clears_stale_ones() {
+        let mut cache = DraftCache::new();
+        store(&mut cache, &job("a", 1), 1);
+        store(&mut cache, &job("b", 2), 2);
+
+        assert_eq!(cache.take("a", 2).map(|d| d.content), None);
+        let draft = cache.take("b", 2).unwrap();
+        assert_eq!(draft.served_by, "test");
+        assert!(cache.take("b", 2).is_none());
+    }
+}
@@ -0,0 +1,178 @@
+use crate::claims;
+use crate::error::{AppError, AppResult};
+use crate::llm::LLMManager;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+
+/// 润色方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum PolishMode {
+    /// 精简：去掉赘述，让逻辑更紧凑
+    #[default]
+    Tighten,
+    /// 扩写：把省略的推理补充完整
+    Expand,
+}
+
+/// 差异片段类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// 差异片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// 润色结果，供前端预览后由玩家决定是否采用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolishedSpeech {
+    pub original: String,
+    pub polished: String,
+    pub diff: Vec<DiffSegment>,
+    /// 润色后的身份声明、查验与提及的座位是否与原稿一致
+    pub claims_preserved: bool,
+}
+
+/// 发言辅助
+pub struct SpeechAssistant;
+
+impl SpeechAssistant {
+    /// 润色玩家的发言草稿，不改变其中的声明
+    pub async fn polish(llm_manager: &LLMManager, draft: &str, mode: PolishMode) -> AppResult<PolishedSpeech> {
+        let draft = draft.trim();
+        if draft.is_empty() {
+            return Err(AppError::GameLogic("发言草稿为空".to_string()));
+        }
+
+        let response = llm_manager.generate_with_fallback(Self::build_prompt(draft, mode)).await?;
+        let polished = response.trim().trim_matches('"').trim_matches('“').trim_matches('”').to_string();
+
+        let claims_preserved = Self::claims_match(draft, &polished);
+        if !claims_preserved {
+            warn!("润色结果改变了发言中的声明");
+        }
+        info!("发言润色完成: {:?}", mode);
+
+        Ok(PolishedSpeech {
+            original: draft.to_string(),
+            diff: Self::diff(draft, &polished),
+            polished,
+            claims_preserved,
+        })
+    }
+
+    fn build_prompt(draft: &str, mode: PolishMode) -> String {
+        let instruction = match mode {
+            PolishMode::Tighten => "把下面的狼人杀发言改得更简洁有力，删去重复和口水话",
+            PolishMode::Expand => "把下面的狼人杀发言补充得更完整，把省略的推理过程说清楚",
+        };
+
+        format!(
+            "{}。必须保留原文的所有身份声明、查验结果、座位号和投票意向，不得新增或改变任何事实，不要替发言者做新的判断。只输出修改后的发言，不要任何解释。\n\n原文：{}",
+            instruction, draft
+        )
+    }
+
+    /// 比较原稿与润色稿中的声明、查验和提及的座位
+    fn claims_match(original: &str, polished: &str) -> bool {
+        let before = claims::parse_claims(original);
+        let after = claims::parse_claims(polished);
+
+        let mut seats_before = utils::parse_seat_mentions(original);
+        let mut seats_after = utils::parse_seat_mentions(polished);
+        seats_before.sort();
+        seats_before.dedup();
+        seats_after.sort();
+        seats_after.dedup();
+
+        before.role == after.role && before.checks == after.checks && seats_before == seats_after
+    }
+
+    /// 按字符计算差异（最长公共子序列）
+    pub fn diff(original: &str, polished: &str) -> Vec<DiffSegment> {
+        let a: Vec<char> = original.chars().collect();
+        let b: Vec<char> = polished.chars().collect();
+
+        // lcs[i][j]：a[i..]与b[j..]的最长公共子序列长度
+        let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i][j] = if a[i] == b[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let mut segments: Vec<DiffSegment> = Vec::new();
+        let mut push = |op: DiffOp, c: char| {
+            match segments.last_mut() {
+                Some(last) if last.op == op => last.text.push(c),
+                _ => segments.push(DiffSegment { op, text: c.to_string() }),
+            }
+        };
+
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                push(DiffOp::Equal, a[i]);
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                push(DiffOp::Delete, a[i]);
+                i += 1;
+            } else {
+                push(DiffOp::Insert, b[j]);
+                j += 1;
+            }
+        }
+        for &c in &a[i..] {
+            push(DiffOp::Delete, c);
+        }
+        for &c in &b[j..] {
+            push(DiffOp::Insert, c);
+        }
+
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(segments: &[DiffSegment]) -> (String, String) {
+        let pick = |skip: DiffOp| segments.iter()
+            .filter(|s| s.op != skip)
+            .map(|s| s.text.as_str())
+            .collect::<String>();
+        (pick(DiffOp::Insert), pick(DiffOp::Delete))
+    }
+
+    #[test]
+    fn test_diff_reconstructs_both_texts() {
+        let original = "我觉得3号嗯有点问题";
+        let polished = "我认为3号有问题";
+        let segments = SpeechAssistant::diff(original, polished);
+        assert_eq!(render(&segments), (original.to_string(), polished.to_string()));
+        // 相邻的同类片段合并在一起
+        assert!(segments.windows(2).all(|w| w[0].op != w[1].op));
+        assert_eq!(segments[0].op, DiffOp::Equal);
+        assert_eq!(segments[0].text, "我");
+    }
+
+    #[test]
+    fn test_claims_must_survive_polishing() {
+        assert!(SpeechAssistant::claims_match("我是预言家，昨晚验了5号是狼", "我是预言家，5号被我验出是狼"));
+        assert!(!SpeechAssistant::claims_match("我是预言家，昨晚验了5号是狼", "我是预言家，昨晚验了6号是狼"));
+        assert!(!SpeechAssistant::claims_match("我是预言家", "我是女巫"));
+    }
+}
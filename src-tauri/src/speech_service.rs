@@ -0,0 +1,213 @@
+use crate::database::GameRepository;
+use crate::objection::Objection;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use log::{debug, warn};
+
+/// 同一条发言连续写入失败这么多次后放弃，避免数据库长期不可用时卡住后续发言
+const MAX_PERSIST_ATTEMPTS: u32 = 5;
+/// 待落库发言的上限，超出时丢弃最早的一条
+const MAX_PENDING_PERSIST: usize = 500;
+
+/// 经过统一发言管道的一条发言
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechEntry {
+    /// 本局内单调递增的发言序号
+    pub sequence: u64,
+    pub message: ChatMessage,
+    pub day: u32,
+    pub phase: GamePhase,
+    /// 发言向量及其模型标识
+    #[serde(default)]
+    pub embedding: Option<(String, Vec<f32>)>,
+}
+
+/// 主持人的一条语音播报
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Narration {
+    pub content: String,
+    pub voice_name: String,
+    pub speed: f32,
+}
+
+/// 语音播报队列中的一项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "item")]
+pub enum PlaybackItem {
+    Narration(Narration),
+    Speech(SpeechEntry),
+    Objection(Objection),
+}
+
+/// 发言服务 - 为每条发言分配序号，并保证落库与语音播报按序进行、不丢失
+#[derive(Debug, Default)]
+pub struct SpeechService {
+    next_sequence: u64,
+    pending_persist: VecDeque<SpeechEntry>,
+    /// 队首发言已连续写入失败的次数
+    persist_failures: u32,
+    tts_queue: VecDeque<SpeechEntry>,
+    /// 主持人播报，优先于玩家发言朗读
+    narration_queue: VecDeque<Narration>,
+    /// 打断插在排队的发言之前，后面的发言等打断读完再继续
+    objection_queue: VecDeque<Objection>,
+}
+
+impl SpeechService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新的一局从头编号
+    pub fn reset(&mut self) {
+        self.next_sequence = 0;
+        self.pending_persist.clear();
+        self.persist_failures = 0;
+        self.tts_queue.clear();
+        self.narration_queue.clear();
+        self.objection_queue.clear();
+    }
+
+    /// 丢弃还没朗读的内容，已有发言照常落库
+    pub fn clear_playback(&mut self) {
+        self.tts_queue.clear();
+        self.narration_queue.clear();
+        self.objection_queue.clear();
+    }
+
+    /// 插入一次打断
+    pub fn interrupt(&mut self, objection: Objection) {
+        self.objection_queue.push_back(objection);
+    }
+
+    /// 加入一条主持人播报
+    pub fn narrate(&mut self, narration: Narration) {
+        self.narration_queue.push_back(narration);
+    }
+
+    /// 接收一条发言并分配序号
+    pub fn ingest(&mut self, message: ChatMessage, day: u32, phase: GamePhase) -> SpeechEntry {
+        self.next_sequence += 1;
+        let entry = SpeechEntry {
+            sequence: self.next_sequence,
+            message,
+            day,
+            phase,
+            embedding: None,
+        };
+
+        if self.pending_persist.len() >= MAX_PENDING_PERSIST {
+            if let Some(dropped) = self.pending_persist.pop_front() {
+                warn!("待保存发言超过{}条，丢弃发言#{}", MAX_PENDING_PERSIST, dropped.sequence);
+                self.persist_failures = 0;
+            }
+        }
+        self.pending_persist.push_back(entry.clone());
+        // 人类玩家的发言不需要再朗读一遍
+        if !matches!(entry.message.message_type, MessageType::Human) {
+            self.tts_queue.push_back(entry.clone());
+        }
+
+        debug!("发言#{} 来自 {}", entry.sequence, entry.message.sender);
+        entry
+    }
+
+    /// 为尚未落库的发言附上向量
+    pub fn attach_embedding(&mut self, sequence: u64, model: String, vector: Vec<f32>) {
+        if let Some(entry) = self.pending_persist.iter_mut().find(|e| e.sequence == sequence) {
+            entry.embedding = Some((model, vector));
+        }
+    }
+
+    /// 按序写入数据库；写入失败时保留剩余发言，下次继续，避免乱序或丢失。
+    /// 同一条发言连续失败MAX_PERSIST_ATTEMPTS次后丢弃，后面的发言继续写入
+    pub async fn flush(&mut self, repository: Option<&GameRepository>, game_id: Option<&str>) {
+        let (repository, game_id) = match (repository, game_id) {
+            (Some(repository), Some(game_id)) => (repository, game_id),
+            _ => return,
+        };
+
+        while let Some(entry) = self.pending_persist.front() {
+            if let Err(e) = repository.record_speech(game_id, &entry.message, entry.day, &entry.phase, entry.sequence).await {
+                self.persist_failures += 1;
+                if self.persist_failures < MAX_PERSIST_ATTEMPTS {
+                    warn!("保存发言#{}失败，稍后重试: {}", entry.sequence, e);
+                    return;
+                }
+                warn!("保存发言#{}连续失败{}次，放弃保存: {}", entry.sequence, self.persist_failures, e);
+                self.pending_persist.pop_front();
+                self.persist_failures = 0;
+                continue;
+            }
+            self.persist_failures = 0;
+            // 向量写入失败不影响发言本身
+            if let Some((model, vector)) = &entry.embedding {
+                if let Err(e) = repository.record_speech_embedding(game_id, entry.sequence, model, vector).await {
+                    warn!("保存发言#{}向量失败: {}", entry.sequence, e);
+                }
+            }
+            self.pending_persist.pop_front();
+        }
+    }
+
+    /// 取出待朗读的内容，依次为主持人播报、打断、玩家发言
+    pub fn take_tts_queue(&mut self) -> Vec<PlaybackItem> {
+        self.narration_queue.drain(..)
+            .map(PlaybackItem::Narration)
+            .chain(self.objection_queue.drain(..).map(PlaybackItem::Objection))
+            .chain(self.tts_queue.drain(..).map(PlaybackItem::Speech))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+    use chrono::Utc;
+
+    fn message(sender: &str, message_type: MessageType) -> ChatMessage {
+        ChatMessage {
+            id: utils::generate_id(),
+            sender: sender.to_string(),
+            content: "过".to_string(),
+            timestamp: Utc::now(),
+            message_type,
+        }
+    }
+
+    #[test]
+    fn test_ingest_numbers_speeches_and_skips_reading_human_ones() {
+        let mut service = SpeechService::new();
+        let ai = service.ingest(message("ai", MessageType::AI), 1, GamePhase::DayDiscussion);
+        let human = service.ingest(message("human", MessageType::Human), 1, GamePhase::DayDiscussion);
+        assert_eq!((ai.sequence, human.sequence), (1, 2));
+        assert_eq!(service.pending_persist.len(), 2);
+
+        service.attach_embedding(2, "local".to_string(), vec![1.0]);
+        assert!(service.pending_persist[1].embedding.is_some());
+
+        service.narrate(Narration { content: "天亮了".to_string(), voice_name: "narrator".to_string(), speed: 1.0 });
+        let queue = service.take_tts_queue();
+        assert_eq!(queue.len(), 2);
+        assert!(matches!(queue[0], PlaybackItem::Narration(_)));
+        assert!(matches!(&queue[1], PlaybackItem::Speech(entry) if entry.sequence == 1));
+        assert!(service.take_tts_queue().is_empty());
+    }
+
+    #[test]
+    fn test_pending_speeches_are_capped_and_reset_restarts_numbering() {
+        let mut service = SpeechService::new();
+        for _ in 0..=MAX_PENDING_PERSIST {
+            service.ingest(message("human", MessageType::Human), 1, GamePhase::DayDiscussion);
+        }
+        assert_eq!(service.pending_persist.len(), MAX_PENDING_PERSIST);
+        assert_eq!(service.pending_persist.front().unwrap().sequence, 2);
+
+        service.reset();
+        assert!(service.pending_persist.is_empty());
+        let entry = service.ingest(message("ai", MessageType::AI), 2, GamePhase::DayDiscussion);
+        assert_eq!(entry.sequence, 1);
+    }
+}
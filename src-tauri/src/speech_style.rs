@@ -0,0 +1,226 @@
+use crate::ai::PersonalityManager;
+use crate::claims;
+use crate::types::{Faction, Player, RoleType};
+use serde::{Deserialize, Serialize};
+
+/// AI发言使用的语言，与界面语言相互独立
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SpeechLanguage {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+    /// 以中文为主，夹杂英文词句
+    #[serde(rename = "mixed")]
+    Mixed,
+}
+
+/// AI发言的语体
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SpeechRegister {
+    /// 口语化，可以用网络用语和狼人杀黑话
+    #[default]
+    Casual,
+    /// 正式、有条理，不用俚语
+    Formal,
+}
+
+/// 性格模板缺失时的幽默和情绪表达
+const DEFAULT_BANTER_TRAIT: f32 = 0.5;
+
+/// AI发言的语言与语体，作用于发言提示词和备用发言库
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SpeechStyle {
+    pub language: SpeechLanguage,
+    pub register: SpeechRegister,
+    /// 闲聊强度（0~1）：开场白、即时反应、玩笑的多少。0.5时按性格模板原样，0为只谈局势
+    #[serde(default = "default_banter")]
+    pub banter: f32,
+}
+
+fn default_banter() -> f32 {
+    0.5
+}
+
+impl Default for SpeechStyle {
+    fn default() -> Self {
+        Self {
+            language: SpeechLanguage::default(),
+            register: SpeechRegister::default(),
+            banter: default_banter(),
+        }
+    }
+}
+
+impl SpeechStyle {
+    /// 按闲聊强度缩放性格模板的幽默或情绪表达
+    pub fn scale_banter(&self, trait_value: f32) -> f32 {
+        (trait_value * self.banter.clamp(0.0, 1.0) * 2.0).clamp(0.0, 1.0)
+    }
+
+    /// 该AI缩放后的(幽默, 情绪表达)
+    pub fn banter_of(&self, player: &Player) -> (f32, f32) {
+        let (humor, emotion) = player.persona.as_ref()
+            .and_then(|persona| PersonalityManager::speech_patterns_of(&persona.template_id))
+            .map(|patterns| (patterns.humor_usage, patterns.emotional_expression))
+            .unwrap_or((DEFAULT_BANTER_TRAIT, DEFAULT_BANTER_TRAIT));
+        (self.scale_banter(humor), self.scale_banter(emotion))
+    }
+
+    /// 附加在发言提示词末尾的闲聊要求，适中时不加要求
+    pub fn banter_instruction(&self, player: &Player) -> &'static str {
+        let (humor, emotion) = self.banter_of(player);
+        let liveliness = (humor + emotion) / 2.0;
+        let english = self.language == SpeechLanguage::EnUs;
+        if liveliness < 0.15 {
+            if english {
+                "Stay strictly on the game: no greetings, openers or jokes, get straight to your read."
+            } else {
+                "只谈局势：不要寒暄、开场白和玩笑，直接给出判断。"
+            }
+        } else if liveliness > 0.6 {
+            if english {
+                "Feel free to open with banter, tease and joke with some emotion, but state any role claim or check result plainly in your analysis, never inside a joke."
+            } else {
+                "可以有开场白、调侃和玩笑，语气有情绪起伏；但身份声明和查验结果只能在正经分析里说清楚，不要夹在玩笑里。"
+            }
+        } else {
+            ""
+        }
+    }
+
+    /// 闲聊内容（反应、打断台词等）不能带身份声明或查验结果，否则会被当成正式信息
+    pub fn is_banter_safe(text: &str) -> bool {
+        let parsed = claims::parse_claims(text);
+        parsed.role.is_none() && parsed.checks.is_empty()
+    }
+
+    /// 附加在发言提示词末尾的语言与语体要求
+    pub fn prompt_instruction(&self) -> &'static str {
+        match (self.language, self.register) {
+            (SpeechLanguage::ZhCn, SpeechRegister::Casual) => "请用中文发言，语气口语化，可以用网络用语和狼人杀黑话（如“悍跳”“金水”“倒钩”）。",
+            (SpeechLanguage::ZhCn, SpeechRegister::Formal) => "请用中文发言，语气正式、条理清晰，不要使用网络用语和俚语。",
+            (SpeechLanguage::EnUs, SpeechRegister::Casual) => "Speak in English only, in a casual chat style; internet slang and werewolf jargon are fine. Refer to players by seat number, e.g. \"Seat 3\".",
+            (SpeechLanguage::EnUs, SpeechRegister::Formal) => "Speak in English only, in a formal and well-structured tone without slang. Refer to players by seat number, e.g. \"Seat 3\".",
+            (SpeechLanguage::Mixed, SpeechRegister::Casual) => "请以中文为主、自然地夹杂英文单词和短语发言，语气口语化，可以用网络用语。",
+            (SpeechLanguage::Mixed, SpeechRegister::Formal) => "请以中文为主、适当夹杂英文术语发言，语气正式、条理清晰，不要使用网络用语。",
+        }
+    }
+
+    /// 无法调用模型时使用的备用发言，按天数轮换
+    pub fn fallback_speech(&self, role_type: &RoleType, day: u32) -> String {
+        let lines = self.fallback_bank(role_type);
+        lines[day as usize % lines.len()].to_string()
+    }
+
+    /// 模型生成失败时的占位发言
+    pub fn thinking_line(&self) -> &'static str {
+        match self.language {
+            SpeechLanguage::ZhCn => "我需要思考一下...",
+            SpeechLanguage::EnUs => "Let me think about this...",
+            SpeechLanguage::Mixed => "让我think一下...",
+        }
+    }
+
+    fn fallback_bank(&self, role_type: &RoleType) -> &'static [&'static str] {
+        let is_wolf = role_type.faction() == Faction::Werewolf;
+        let is_seer = *role_type == RoleType::Seer;
+
+        match (self.language, self.register) {
+            (SpeechLanguage::ZhCn, SpeechRegister::Formal) if is_wolf => &[
+                "我觉得某位玩家的发言有些可疑。",
+                "我们需要仔细分析投票情况。",
+                "我倾向于相信好人的判断。",
+            ],
+            (SpeechLanguage::ZhCn, SpeechRegister::Formal) if is_seer => &[
+                "我有一些信息要分享。",
+                "根据我的观察，有人可能有问题。",
+                "大家要相信我的判断。",
+            ],
+            (SpeechLanguage::ZhCn, SpeechRegister::Formal) => &[
+                "我需要再观察一下。",
+                "大家的分析都很有道理。",
+                "我暂时保留意见。",
+            ],
+            (SpeechLanguage::ZhCn, SpeechRegister::Casual) if is_wolf => &[
+                "这轮有人发言怪怪的，我先打个问号。",
+                "票型挺有意思的，大家盘一盘。",
+                "我是好人，跟着好人走就完事了。",
+            ],
+            (SpeechLanguage::ZhCn, SpeechRegister::Casual) if is_seer => &[
+                "我手里有信息，听我说两句。",
+                "有人状态不对，我心里有数。",
+                "信我，这把稳。",
+            ],
+            (SpeechLanguage::ZhCn, SpeechRegister::Casual) => &[
+                "我再听听，先不站边。",
+                "大家盘得都挺有道理的。",
+                "过，这轮先划个水。",
+            ],
+            (SpeechLanguage::EnUs, SpeechRegister::Formal) if is_wolf => &[
+                "One player's statement seems questionable to me.",
+                "We should analyze the voting pattern carefully.",
+                "I am inclined to trust the village's judgment.",
+            ],
+            (SpeechLanguage::EnUs, SpeechRegister::Formal) if is_seer => &[
+                "I have some information to share.",
+                "Based on my observations, someone may be suspicious.",
+                "I ask everyone to trust my judgment.",
+            ],
+            (SpeechLanguage::EnUs, SpeechRegister::Formal) => &[
+                "I would like to observe a little longer.",
+                "Everyone's analysis makes sense so far.",
+                "I will reserve my opinion for now.",
+            ],
+            (SpeechLanguage::EnUs, SpeechRegister::Casual) if is_wolf => &[
+                "Someone's talk feels kinda off this round, not gonna lie.",
+                "The votes are spicy, let's break them down.",
+                "I'm village, just follow the good guys.",
+            ],
+            (SpeechLanguage::EnUs, SpeechRegister::Casual) if is_seer => &[
+                "I've got info, hear me out.",
+                "Somebody's acting sus, I know what I know.",
+                "Trust me on this one.",
+            ],
+            (SpeechLanguage::EnUs, SpeechRegister::Casual) => &[
+                "Gonna listen a bit more before I pick a side.",
+                "Honestly everyone's reads sound reasonable.",
+                "Pass for now.",
+            ],
+            (SpeechLanguage::Mixed, _) if is_wolf => &[
+                "有人的发言有点sus，我先mark一下。",
+                "票型很interesting，大家再盘一下。",
+                "我是好人，follow好人就对了。",
+            ],
+            (SpeechLanguage::Mixed, _) if is_seer => &[
+                "我有info要分享，听我说。",
+                "有人的状态不太对，我心里有数。",
+                "Trust me，这把稳。",
+            ],
+            (SpeechLanguage::Mixed, _) => &[
+                "我再listen一下，先不站边。",
+                "大家的分析都make sense。",
+                "Pass，这轮先听。",
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banter_scaling_and_claim_guard() {
+        let mut style = SpeechStyle::default();
+        assert!((style.scale_banter(0.4) - 0.4).abs() < 1e-6);
+        style.banter = 0.0;
+        assert_eq!(style.scale_banter(0.9), 0.0);
+        style.banter = 1.0;
+        assert_eq!(style.scale_banter(0.8), 1.0);
+
+        assert!(SpeechStyle::is_banter_safe("哈哈，这局有意思。"));
+        assert!(!SpeechStyle::is_banter_safe("我是预言家，3号查杀。"));
+    }
+}
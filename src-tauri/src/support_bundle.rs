@@ -0,0 +1,191 @@
+use crate::commands::AppState;
+use crate::database::migrations;
+use crate::diagnostics;
+use crate::error::{AppError, AppResult};
+use crate::paths;
+use chrono::Utc;
+use regex::Regex;
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use log::info;
+
+/// 最多打包的日志文件数（按修改时间取最新的）
+const MAX_LOG_FILES: usize = 3;
+/// 每个日志文件只保留末尾这么多字节
+const MAX_LOG_BYTES: u64 = 512 * 1024;
+const BUNDLE_DIR: &str = "support";
+
+/// 支持包的基本信息
+#[derive(Debug, Serialize)]
+struct BundleManifest {
+    app_version: String,
+    os: String,
+    arch: String,
+    portable: bool,
+    /// 数据库结构版本，数据库无法打开时为空
+    schema_version: Option<i32>,
+    expected_schema_version: i32,
+    created_at: String,
+    files: Vec<String>,
+}
+
+/// 生成支持包：最近的日志、脱敏后的生效配置、数据库结构版本、自检结果，
+/// 可选附带最近一局复盘。所有文本写入前都会去除个人信息，返回zip文件路径
+pub async fn create(state: &AppState, include_replay: bool) -> AppResult<PathBuf> {
+    let (config, data_dir) = {
+        let config_manager = state.config_manager.read().await;
+        (config_manager.get_config().clone(), config_manager.config_dir().map(Path::to_path_buf))
+    };
+    let data_paths = paths::data_paths();
+    let output_dir = data_dir.unwrap_or_else(|| data_paths.config_dir().to_path_buf()).join(BUNDLE_DIR);
+    std::fs::create_dir_all(&output_dir)
+        .map_err(|e| AppError::Io(format!("创建目录{:?}失败: {}", output_dir, e)))?;
+
+    let mut entries: Vec<(String, String)> = Vec::new();
+
+    let mut config = config;
+    if !config.llm.api_key.is_empty() {
+        config.llm.api_key = "***".to_string();
+    }
+    entries.push(("config.json".to_string(), serde_json::to_string_pretty(&config)?));
+
+    let report = diagnostics::run(state).await;
+    entries.push(("diagnostics.json".to_string(), serde_json::to_string_pretty(&report)?));
+
+    for log_file in recent_logs(&data_paths.logs_dir()) {
+        let name = log_file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        match read_tail(&log_file) {
+            Ok(content) => entries.push((format!("logs/{}", name), content)),
+            Err(e) => entries.push((format!("logs/{}.error", name), format!("读取日志失败: {}", e))),
+        }
+    }
+
+    if include_replay {
+        let game_manager = state.game_manager.read().await;
+        if let Some(replay) = game_manager.latest_replay().await? {
+            let mut json = serde_json::to_string_pretty(&replay)?;
+            // 人类玩家的名字是用户自己填的，换成座位号
+            for player in replay.players.iter().filter(|p| !p.is_ai && !p.name.is_empty()) {
+                json = json.replace(&player.name, &format!("玩家{}", player.seat));
+            }
+            entries.push(("replay.json".to_string(), json));
+        }
+    }
+
+    let schema_version = match &state.database {
+        Some(database) => migrations::get_current_version(database.get_pool()).await.ok(),
+        None => None,
+    };
+    let created_at = Utc::now();
+    let manifest = BundleManifest {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        portable: data_paths.is_portable(),
+        schema_version,
+        expected_schema_version: migrations::CURRENT_VERSION,
+        created_at: created_at.to_rfc3339(),
+        files: entries.iter().map(|(name, _)| name.clone()).collect(),
+    };
+    entries.insert(0, ("manifest.json".to_string(), serde_json::to_string_pretty(&manifest)?));
+
+    let path = output_dir.join(format!("mindwolf-support-{}.zip", created_at.format("%Y%m%d-%H%M%S")));
+    write_zip(&path, &entries)?;
+    info!("已生成支持包: {:?}", path);
+    Ok(path)
+}
+
+fn write_zip(path: &Path, entries: &[(String, String)]) -> AppResult<()> {
+    let file = std::fs::File::create(path)?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, content) in entries {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| AppError::Io(format!("写入支持包失败: {}", e)))?;
+        zip.write_all(redact(content).as_bytes())?;
+    }
+    zip.finish().map_err(|e| AppError::Io(format!("写入支持包失败: {}", e)))?;
+    Ok(())
+}
+
+/// 日志目录中最新的几个日志文件
+fn recent_logs(logs_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<_> = std::fs::read_dir(logs_dir)
+        .map(|entries| {
+            entries.filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    files.into_iter().take(MAX_LOG_FILES).map(|(_, path)| path).collect()
+}
+
+/// 读取文件末尾部分，超长的日志只保留最近的内容
+fn read_tail(path: &Path) -> AppResult<String> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    if len > MAX_LOG_BYTES {
+        file.seek(SeekFrom::Start(len - MAX_LOG_BYTES))?;
+    }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// 去除个人信息：用户目录、系统用户名、邮箱、密钥和长串数字（手机号等）
+fn redact(text: &str) -> String {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "<email>"),
+            (r"(?i)bearer\s+[A-Za-z0-9._~+/=-]+", "Bearer ***"),
+            (r"\bsk-[A-Za-z0-9_-]{8,}", "sk-***"),
+            (r"\d{11,}", "<number>"),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).expect("脱敏规则无效"), replacement))
+        .collect()
+    });
+
+    let mut text = text.to_string();
+    if let Some(home) = dirs::home_dir().map(|home| home.to_string_lossy().to_string()).filter(|home| home.len() > 1) {
+        text = text.replace(&home, "~");
+        // JSON里的Windows路径是转义过的反斜杠
+        text = text.replace(&home.replace('\\', "\\\\"), "~");
+    }
+    for var in ["USER", "USERNAME"] {
+        if let Ok(user) = std::env::var(var) {
+            // 太短的用户名容易误伤正常文本
+            if user.len() >= 4 {
+                text = text.replace(&user, "<user>");
+            }
+        }
+    }
+    for (pattern, replacement) in patterns {
+        text = pattern.replace_all(&text, *replacement).into_owned();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_strips_pii() {
+        let text = "联系 alice@example.com，手机13800138000，Authorization: Bearer abc.def-123，key=sk-proj1234567890";
+        let redacted = redact(text);
+        assert!(!redacted.contains("alice@example.com"));
+        assert!(!redacted.contains("13800138000"));
+        assert!(!redacted.contains("abc.def-123"));
+        assert!(!redacted.contains("sk-proj1234567890"));
+        assert!(redacted.contains("<email>"));
+        assert_eq!(redact("第3天投票"), "第3天投票");
+    }
+}
@@ -0,0 +1,195 @@
+use crate::error::{AppError, AppResult};
+use crate::types::LLMProvider;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use log::{info, warn};
+
+const STATE_FILE: &str = "telemetry.json";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 匿名使用统计配置，默认关闭，只有用户主动开启并填写上报地址后才会联网
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    /// 上报地址，为空时只在本地统计
+    pub endpoint: String,
+}
+
+/// 汇总计数，不含任何可识别身份的信息：没有设备ID、密钥、玩家名或发言
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TelemetryCounters {
+    pub games_played: u64,
+    /// 按玩家人数统计的对局数
+    pub board_sizes: BTreeMap<u8, u64>,
+    pub sessions: u64,
+    /// 正常退出的会话数
+    pub crash_free_sessions: u64,
+    /// 开局时使用的模型提供商类型
+    pub providers: BTreeMap<String, u64>,
+}
+
+impl TelemetryCounters {
+    fn subtract(&mut self, reported: &TelemetryCounters) {
+        fn subtract_map<K: Ord + Clone>(counts: &mut BTreeMap<K, u64>, reported: &BTreeMap<K, u64>) {
+            for (key, count) in reported {
+                if let Some(current) = counts.get_mut(key) {
+                    *current = current.saturating_sub(*count);
+                }
+            }
+            counts.retain(|_, count| *count > 0);
+        }
+        self.games_played = self.games_played.saturating_sub(reported.games_played);
+        self.sessions = self.sessions.saturating_sub(reported.sessions);
+        self.crash_free_sessions = self.crash_free_sessions.saturating_sub(reported.crash_free_sessions);
+        subtract_map(&mut self.board_sizes, &reported.board_sizes);
+        subtract_map(&mut self.providers, &reported.providers);
+    }
+}
+
+/// 上报内容，设置页预览的就是这份数据
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TelemetryReport {
+    pub app_version: String,
+    pub os: String,
+    #[serde(flatten)]
+    pub counters: TelemetryCounters,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedState {
+    counters: TelemetryCounters,
+    /// 上次会话没有正常结束，说明程序崩溃或被强制结束
+    session_open: bool,
+}
+
+/// 本地累计计数，上报成功后清零
+pub struct Telemetry {
+    path: Option<PathBuf>,
+    state: PersistedState,
+}
+
+impl Telemetry {
+    pub fn new(data_dir: Option<&Path>) -> Self {
+        let path = data_dir.map(|dir| dir.join(STATE_FILE));
+        let state = path.as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, state }
+    }
+
+    /// 程序启动时调用；上次会话未正常结束的计为非正常会话
+    pub fn start_session(&mut self, config: &TelemetryConfig) {
+        if !config.enabled {
+            return;
+        }
+        if self.state.session_open {
+            self.state.counters.sessions += 1;
+        }
+        self.state.session_open = true;
+        self.save();
+    }
+
+    /// 程序正常退出时调用
+    pub fn end_session(&mut self, config: &TelemetryConfig) {
+        if !config.enabled || !self.state.session_open {
+            return;
+        }
+        self.state.session_open = false;
+        self.state.counters.sessions += 1;
+        self.state.counters.crash_free_sessions += 1;
+        self.save();
+    }
+
+    /// 记录一局新游戏
+    pub fn record_game(&mut self, config: &TelemetryConfig, total_players: u8, provider: &LLMProvider) {
+        if !config.enabled {
+            return;
+        }
+        let counters = &mut self.state.counters;
+        counters.games_played += 1;
+        *counters.board_sizes.entry(total_players).or_default() += 1;
+        *counters.providers.entry(format!("{:?}", provider).to_lowercase()).or_default() += 1;
+        self.save();
+    }
+
+    /// 将要上报的内容
+    pub fn preview(&self) -> TelemetryReport {
+        TelemetryReport {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            counters: self.state.counters.clone(),
+        }
+    }
+
+    /// 待上报的内容；未开启、没有上报地址或没有新计数时为None
+    pub fn pending_report(&self, config: &TelemetryConfig) -> Option<TelemetryReport> {
+        if !config.enabled || config.endpoint.trim().is_empty() || self.state.counters == TelemetryCounters::default() {
+            return None;
+        }
+        Some(self.preview())
+    }
+
+    /// 上报成功后扣掉已上报的计数，上报期间新增的计数留到下次
+    pub fn mark_reported(&mut self, report: &TelemetryReport) {
+        self.state.counters.subtract(&report.counters);
+        self.save();
+    }
+
+    fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+        let result = serde_json::to_string_pretty(&self.state)
+            .map_err(AppError::from)
+            .and_then(|content| std::fs::write(path, content).map_err(AppError::from));
+        if let Err(e) = result {
+            warn!("保存使用统计失败: {}", e);
+        }
+    }
+}
+
+/// 把计数发到上报地址；调用方不应持有Telemetry的锁
+pub async fn send_report(endpoint: &str, report: &TelemetryReport) -> AppResult<()> {
+    let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+    let response = client.post(endpoint.trim()).json(report).send().await?;
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!("上报使用统计失败: {}", response.status())));
+    }
+    info!("已上报使用统计: {}局", report.counters.games_played);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_only_when_opted_in() {
+        let mut telemetry = Telemetry::new(None);
+        telemetry.record_game(&TelemetryConfig::default(), 12, &LLMProvider::OpenAI);
+        assert_eq!(telemetry.preview().counters, TelemetryCounters::default());
+
+        let config = TelemetryConfig { enabled: true, endpoint: String::new() };
+        telemetry.start_session(&config);
+        telemetry.record_game(&config, 12, &LLMProvider::OpenAI);
+        telemetry.end_session(&config);
+
+        let report = serde_json::to_value(telemetry.preview()).unwrap();
+        assert_eq!(report["games_played"], 1);
+        assert_eq!(report["board_sizes"]["12"], 1);
+        assert_eq!(report["providers"]["openai"], 1);
+        assert_eq!(report["crash_free_sessions"], 1);
+
+        let sent = telemetry.preview();
+        telemetry.record_game(&config, 9, &LLMProvider::OpenAI);
+        telemetry.mark_reported(&sent);
+        let remaining = telemetry.preview().counters;
+        assert_eq!(remaining.games_played, 1);
+        assert_eq!(remaining.board_sizes, BTreeMap::from([(9, 1)]));
+        assert_eq!(remaining.crash_free_sessions, 0);
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
+use crate::claims::ClaimsBoard;
 
 
 /// 角色信息
@@ -22,6 +23,35 @@ pub enum RoleType {
     Witch,
     Hunter,
     Guard,
+    /// 梦狼：狼队知道他，但他不认识队友，也不参与刀人
+    DreamWolf,
+    /// 石像鬼：与狼队互不相识，不参与刀人
+    Gargoyle,
+}
+
+impl RoleType {
+    /// 角色所属阵营
+    pub fn faction(&self) -> Faction {
+        match self {
+            RoleType::Werewolf | RoleType::DreamWolf | RoleType::Gargoyle => Faction::Werewolf,
+            _ => Faction::Villager,
+        }
+    }
+    
+    /// 是否参与狼队夜间击杀
+    pub fn joins_wolf_kill(&self) -> bool {
+        *self == RoleType::Werewolf
+    }
+    
+    /// 能否看到狼队成员和狼人频道
+    pub fn sees_wolf_team(&self) -> bool {
+        *self == RoleType::Werewolf
+    }
+    
+    /// 是否为狼队所知
+    pub fn known_to_wolf_team(&self) -> bool {
+        matches!(self, RoleType::Werewolf | RoleType::DreamWolf)
+    }
 }
 
 /// 阵营枚举
@@ -38,6 +68,8 @@ pub enum GamePhase {
     Night,
     DayDiscussion,
     Voting,
+    /// 平票PK：平票的玩家依次辩护，随后只在他们之间重新投票
+    Pk,
     LastWords,
     GameOver,
 }
@@ -54,16 +86,130 @@ pub struct GameState {
     pub winner: Option<Faction>,
     pub current_speaker: Option<String>,
     pub time_remaining: Option<u32>,
+    #[serde(default)]
+    pub vote_changes: Vec<VoteChange>,
+    /// 讨论阶段的非正式投票意向：玩家ID -> 倾向的目标
+    #[serde(default)]
+    pub vote_intents: HashMap<String, String>,
+    /// 按身份公开规则公示的死亡信息
+    #[serde(default)]
+    pub death_reveals: Vec<DeathReveal>,
+    /// 公开声明板（身份声明与查验报告）
+    #[serde(default)]
+    pub claims_board: ClaimsBoard,
+    /// 每轮放逐投票的计票结果
+    #[serde(default)]
+    pub vote_outcomes: Vec<VoteOutcome>,
+    /// 游戏结束时的结算结果
+    #[serde(default)]
+    pub result: Option<GameResult>,
+    /// 女巫剩余的药剂：玩家ID -> 药剂
+    #[serde(default)]
+    pub witch_potions: HashMap<String, WitchPotions>,
+    /// 守卫最近一次守护：玩家ID -> 守护记录，空守时清除
+    #[serde(default)]
+    pub guard_protections: HashMap<String, GuardProtection>,
+    /// 警长，投票按VoteRules中的权重计
+    #[serde(default)]
+    pub sheriff: Option<String>,
+    /// 失去投票权的玩家，如翻牌后的白痴
+    #[serde(default)]
+    pub revoked_votes: Vec<String>,
+    /// PK发言和重新投票期间的平票玩家，其余时候为空
+    #[serde(default)]
+    pub pk_candidates: Vec<String>,
 }
 
 /// 投票记录
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct VoteRecord {
     pub voter: String,
+    /// 投票目标，弃票时为ABSTAIN
+    pub target: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl VoteRecord {
+    /// 弃票记录的目标标记，不会与玩家ID冲突
+    pub const ABSTAIN: &'static str = "__abstain__";
+    
+    pub fn is_abstention(&self) -> bool {
+        self.target == Self::ABSTAIN
+    }
+}
+
+/// 单个候选人的得票
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteTally {
     pub target: String,
+    pub count: u32,
+    /// 按票权加权后的票数，决定放逐结果；早期记录没有此字段
+    #[serde(default)]
+    pub weight: f32,
+    /// 投票者，按座位号排序。投票后才失去票权的玩家仍列在这里，但不计入weight
+    pub voters: Vec<String>,
+}
+
+/// 一轮放逐投票的计票结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteOutcome {
+    pub day: u32,
+    /// 按票数从高到低排列，票数相同时按座位号排列
+    pub tally: Vec<VoteTally>,
+    /// 被放逐的玩家，平票或无人投票时为None
+    pub eliminated: Option<String>,
+    /// 平票时并列最高票的玩家，按座位号排序
+    pub tied: Vec<String>,
+    /// 明确弃票的玩家，按座位号排序
+    #[serde(default)]
+    pub abstentions: Vec<String>,
+    /// 平票后进入PK，本轮的放逐留到重新投票
+    #[serde(default)]
+    pub pk: bool,
+    /// PK后的重新投票
+    #[serde(default)]
+    pub revote: bool,
+}
+
+/// 改票记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteChange {
+    pub voter: String,
+    pub previous_target: String,
+    pub new_target: String,
+    pub day: u32,
     pub timestamp: DateTime<Utc>,
 }
 
+/// 被撤销的操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum UndoneAction {
+    Vote { voter: String, target: String },
+}
+
+/// 玩家私人笔记（每人每天一条）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerNote {
+    pub player_id: String,
+    pub day: u32,
+    pub content: String,
+    /// 怀疑的身份：玩家ID -> 角色
+    #[serde(default)]
+    pub suspected_roles: HashMap<String, RoleType>,
+    /// 听到的身份声明
+    #[serde(default)]
+    pub claims_heard: Vec<ClaimNote>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 笔记中记录的身份声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimNote {
+    pub player_id: String,
+    pub claimed_role: RoleType,
+}
+
 /// 游戏配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameConfig {
@@ -72,6 +218,569 @@ pub struct GameConfig {
     pub discussion_time: u32,
     pub voting_time: u32,
     pub enable_voice: bool,
+    #[serde(default)]
+    pub speech_timing: SpeechTimingConfig,
+    #[serde(default)]
+    pub role_reveal: RoleRevealPolicy,
+    #[serde(default)]
+    pub night_timing: NightTimingConfig,
+    /// 人类玩家选择的座位号，None表示随机
+    #[serde(default)]
+    pub human_seat: Option<u8>,
+    #[serde(default)]
+    pub reactions: ReactionConfig,
+    #[serde(default)]
+    pub objections: ObjectionConfig,
+    /// 真人挂机时的保守托管
+    #[serde(default)]
+    pub autopilot: AutopilotConfig,
+    #[serde(default)]
+    pub kill_heat_veto: KillHeatVetoConfig,
+    /// AI座位的难度：记忆容量、遗忘概率和投票失误率
+    #[serde(default)]
+    pub ai_difficulty: crate::ai::AIDifficulty,
+    /// 狼人夜间刀人投票的计票方式
+    #[serde(default)]
+    pub night_vote: crate::wolf_council::NightVoteMode,
+    #[serde(default)]
+    pub night_rules: NightRules,
+    /// 用梦狼或石像鬼替换一名普通狼人
+    #[serde(default)]
+    pub passive_wolf: Option<RoleType>,
+    #[serde(default)]
+    pub speech_limits: SpeechLimitConfig,
+    #[serde(default)]
+    pub embeddings: crate::embeddings::EmbeddingConfig,
+    /// 从往局获胜一方挑选范例发言加入AI发言提示词
+    #[serde(default)]
+    pub exemplars: crate::exemplars::ExemplarConfig,
+    /// 真人发言和聊天的不雅用语、个人信息过滤
+    #[serde(default)]
+    pub content_filter: crate::content_filter::ContentFilterConfig,
+    /// 提示词相关发言的打分与字数预算
+    #[serde(default)]
+    pub context: crate::context::ContextConfig,
+    /// AI发言的语言与语体，与界面语言分开设置
+    #[serde(default)]
+    pub speech_style: crate::speech_style::SpeechStyle,
+    /// AI发言的语言校验与超长改写
+    #[serde(default)]
+    pub output_control: crate::output_control::OutputControlConfig,
+    /// 主持人语音播报的音色与详略
+    #[serde(default)]
+    pub narrator_voice: crate::narrator::NarratorVoiceConfig,
+    /// 唤醒词：以“法官”等开头的发言交给主持人回答，不计入正式发言
+    #[serde(default)]
+    pub wake_word: crate::narrator_qa::WakeWordConfig,
+    /// 教练模式：每个白天结束和游戏结束时给真人玩家复盘反馈
+    #[serde(default)]
+    pub coach_mode: bool,
+    /// 新手提示：主持人用规则语言解释刚发生的事，只推送给前端
+    #[serde(default)]
+    pub beginner_tips: bool,
+    /// 真人玩家投票超时后的处理方式
+    #[serde(default)]
+    pub auto_vote: AutoVotePolicy,
+    /// 票权与弃票规则
+    #[serde(default)]
+    pub vote_rules: VoteRules,
+    #[serde(default)]
+    pub spectator: SpectatorConfig,
+    /// 最近一次套用的规则预设，娱乐局允许主持人撤销操作
+    #[serde(default)]
+    pub rule_preset: Option<RulePreset>,
+    /// 讨论计时结束后自动进入投票，不等待主持人推进
+    #[serde(default)]
+    pub auto_advance: bool,
+    /// 由后台循环驱动整局：夜晚行动、按顺序发言、AI投票和阶段切换都自动进行
+    #[serde(default)]
+    pub auto_drive: bool,
+    /// 最近一次套用的节奏档位
+    #[serde(default)]
+    pub pacing: Option<PacingProfile>,
+    /// 座位号 -> 策略插件名，这些AI座位由插件目录中的WASM插件决策
+    #[serde(default)]
+    pub plugin_seats: HashMap<u8, String>,
+    /// 座位号 -> 该座位AI的生成参数覆盖，可以让一桌里既有天马行空的AI也有严谨的分析型AI
+    #[serde(default)]
+    pub seat_generation: HashMap<u8, SeatGeneration>,
+    /// 随规则预设加载的房规脚本
+    #[serde(default)]
+    pub house_rules: Vec<crate::house_rules::HouseRuleScript>,
+}
+
+/// 单个AI座位的生成参数覆盖，不填的项沿用全局模型配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeatGeneration {
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    /// 人设强度（0~1）：越高越放大性格和说话习惯，越低越偏冷静分析，0.5为默认
+    #[serde(default)]
+    pub persona_strength: Option<f32>,
+}
+
+impl SeatGeneration {
+    /// 本座位每次调用模型时使用的参数，超出接口范围的取值会被截断
+    pub fn params(&self) -> crate::llm::GenerationParams {
+        crate::llm::GenerationParams {
+            temperature: self.temperature.map(|t| t.clamp(0.0, 2.0)),
+            top_p: self.top_p.map(|p| p.clamp(0.0, 1.0)),
+        }
+    }
+    
+    /// 人设强度对应的发言要求，默认强度不加要求
+    pub fn persona_instruction(&self) -> &'static str {
+        match self.persona_strength {
+            Some(strength) if strength >= 0.7 => "充分放大你的性格特点和说话习惯，可以出人意料。",
+            Some(strength) if strength <= 0.3 => "少带个人色彩，以冷静、有条理的逻辑分析为主。",
+            _ => "",
+        }
+    }
+}
+
+/// 超长发言的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum OverLimitPolicy {
+    /// 按句子边界截断
+    #[default]
+    Truncate,
+    /// 拒绝并提示玩家修改
+    Reject,
+}
+
+/// 分阶段的发言长度限制（字数按字符计）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechLimitConfig {
+    pub discussion_max_chars: usize,
+    pub last_words_max_chars: usize,
+    /// 单次发言（含AI思考与打字）的最长秒数
+    pub discussion_max_seconds: u32,
+    pub last_words_max_seconds: u32,
+    #[serde(default)]
+    pub over_limit: OverLimitPolicy,
+}
+
+impl Default for SpeechLimitConfig {
+    fn default() -> Self {
+        Self {
+            discussion_max_chars: 200,
+            last_words_max_chars: 120,
+            discussion_max_seconds: 90,
+            last_words_max_seconds: 45,
+            over_limit: OverLimitPolicy::Truncate,
+        }
+    }
+}
+
+impl SpeechLimitConfig {
+    /// 当前阶段的字数上限
+    pub fn max_chars_for(&self, phase: &GamePhase) -> usize {
+        match phase {
+            GamePhase::LastWords => self.last_words_max_chars,
+            _ => self.discussion_max_chars,
+        }
+    }
+    
+    /// 当前阶段的单次发言时长上限
+    pub fn max_duration_for(&self, phase: &GamePhase) -> std::time::Duration {
+        let seconds = match phase {
+            GamePhase::LastWords => self.last_words_max_seconds,
+            _ => self.discussion_max_seconds,
+        };
+        std::time::Duration::from_secs(seconds as u64)
+    }
+}
+
+/// 夜晚结算规则
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NightRules {
+    /// 同守同救死：同一晚既被守护又被女巫救的玩家仍然死亡
+    pub guard_heal_conflict_kills: bool,
+    /// 首夜预言家的特殊规则
+    #[serde(default)]
+    pub first_night_seer: FirstNightSeerRule,
+}
+
+/// 首夜预言家规则
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum FirstNightSeerRule {
+    /// 标准：首夜查验一次
+    #[default]
+    Standard,
+    /// 开局告知预言家一名好人作为金水
+    GoldWater,
+    /// 首夜可以查验两次
+    DoubleCheck,
+}
+
+/// 守卫的一次守护
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GuardProtection {
+    pub night: u32,
+    pub target: String,
+}
+
+/// 女巫的药剂，解药和毒药各一瓶，用过即无
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WitchPotions {
+    pub antidote: bool,
+    pub poison: bool,
+}
+
+impl Default for WitchPotions {
+    fn default() -> Self {
+        Self { antidote: true, poison: true }
+    }
+}
+
+/// 预言家的查验结果（仅预言家本人可见）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeerCheckResult {
+    pub night: u32,
+    pub seer: String,
+    pub target: String,
+    pub is_werewolf: bool,
+    /// 开局直接告知的金水，而不是查验所得
+    pub reference: bool,
+}
+
+/// 规则预设
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RulePreset {
+    /// 娱乐局：宽松规则，预言家开局有金水
+    Casual,
+    /// 经典局：同守同救死
+    Classic,
+}
+
+impl RulePreset {
+    /// 房规脚本所在的子目录名
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            RulePreset::Casual => "casual",
+            RulePreset::Classic => "classic",
+        }
+    }
+    
+    /// 把预设规则写入游戏配置，其余配置保持不变
+    pub fn apply(&self, config: &mut GameConfig) {
+        config.rule_preset = Some(*self);
+        match self {
+            RulePreset::Casual => {
+                config.night_rules = NightRules {
+                    guard_heal_conflict_kills: false,
+                    first_night_seer: FirstNightSeerRule::GoldWater,
+                };
+            }
+            RulePreset::Classic => {
+                config.night_rules = NightRules {
+                    guard_heal_conflict_kills: true,
+                    first_night_seer: FirstNightSeerRule::Standard,
+                };
+            }
+        }
+    }
+}
+
+/// 节奏档位：同时决定阶段时长、AI思考延迟、语音播报和自动推进
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PacingProfile {
+    /// 快节奏：缩短各阶段，AI不模拟思考，不朗读也不播报，计时结束自动推进
+    Blitz,
+    /// 标准节奏，与默认配置一致
+    Standard,
+    /// 慢节奏：阶段更长，AI思考更久，朗读AI发言
+    Relaxed,
+}
+
+impl PacingProfile {
+    /// 把节奏相关的配置一次写入，其余配置保持不变
+    pub fn apply(&self, config: &mut GameConfig) {
+        config.pacing = Some(*self);
+        let (discussion_time, voting_time, voice, narration, auto_advance) = match self {
+            PacingProfile::Blitz => (120, 30, false, false, true),
+            PacingProfile::Standard => (300, 60, false, true, false),
+            PacingProfile::Relaxed => (480, 90, true, true, false),
+        };
+        config.discussion_time = discussion_time;
+        config.voting_time = voting_time;
+        config.enable_voice = voice;
+        config.narrator_voice.enabled = narration;
+        config.auto_advance = auto_advance;
+        config.speech_timing = match self {
+            PacingProfile::Blitz => SpeechTimingConfig { enabled: false, ..SpeechTimingConfig::default() },
+            PacingProfile::Standard => SpeechTimingConfig::default(),
+            PacingProfile::Relaxed => SpeechTimingConfig {
+                enabled: true,
+                base_delay_ms: 1500,
+                per_char_delay_ms: 60,
+                max_delay_ms: 12000,
+            },
+        };
+        config.night_timing = match self {
+            PacingProfile::Blitz => NightTimingConfig { min_duration: 5, max_padding: 2 },
+            PacingProfile::Standard => NightTimingConfig::default(),
+            PacingProfile::Relaxed => NightTimingConfig { min_duration: 15, max_padding: 8 },
+        };
+    }
+}
+
+/// 狼人避刀配置：不刀已被村民重点怀疑的玩家，让好人白天浪费放逐票
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillHeatVetoConfig {
+    pub enabled: bool,
+    /// 公开怀疑热度达到该值的玩家不作为击杀目标
+    pub threshold: f32,
+}
+
+impl Default for KillHeatVetoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.65,
+        }
+    }
+}
+
+/// AI即时反应配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReactionConfig {
+    pub enabled: bool,
+    /// 每个候选AI做出反应的概率
+    pub frequency: f32,
+    /// 每个事件最多几个AI反应
+    pub max_per_event: usize,
+}
+
+impl Default for ReactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            frequency: 0.5,
+            max_per_event: 2,
+        }
+    }
+}
+
+/// 发言打断（异议）配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectionConfig {
+    pub enabled: bool,
+    /// 异议最多几个字
+    pub max_chars: usize,
+    /// AI提出异议的基础概率，再乘以性格的打断倾向和对发言者的怀疑度
+    pub ai_frequency: f32,
+}
+
+impl Default for ObjectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_chars: 30,
+            ai_frequency: 0.3,
+        }
+    }
+}
+
+/// 挂机托管配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutopilotConfig {
+    pub enabled: bool,
+    /// 连续两个阶段切换时都超过这么多秒没有操作，视为挂机
+    pub idle_seconds: u32,
+}
+
+impl Default for AutopilotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_seconds: 90,
+        }
+    }
+}
+
+/// 亡者频道配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpectatorConfig {
+    /// 死亡的AI是否在亡者频道点评
+    pub ai_comments: bool,
+    /// 每个死亡AI在一次触发中发言的概率
+    pub frequency: f32,
+    /// 每次触发最多几个AI发言
+    pub max_per_event: usize,
+}
+
+impl Default for SpectatorConfig {
+    fn default() -> Self {
+        Self {
+            ai_comments: true,
+            frequency: 0.3,
+            max_per_event: 1,
+        }
+    }
+}
+
+/// 夜晚时长配置：固定时长加随机补时，避免通过夜晚长短推断身份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NightTimingConfig {
+    pub min_duration: u32,
+    pub max_padding: u32,
+}
+
+impl Default for NightTimingConfig {
+    fn default() -> Self {
+        Self {
+            min_duration: 10,
+            max_padding: 5,
+        }
+    }
+}
+
+/// 投票超时的自动投票策略
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum AutoVotePolicy {
+    /// 弃票
+    #[default]
+    Abstain,
+    /// 随机投给一名存活玩家
+    Random,
+    /// 跟随当前得票最多的玩家
+    FollowMajority,
+}
+
+/// 放逐投票规则：每张票的权重由这里统一决定，新角色只需改变局面中的警长或票权
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteRules {
+    /// 警长的票数
+    #[serde(default = "default_sheriff_weight")]
+    pub sheriff_weight: f32,
+    /// 强制投票：不允许弃票，真人超时未投时随机代投
+    #[serde(default)]
+    pub mandatory: bool,
+    /// 平票时进入PK发言并重新投票，关闭时平票直接无人出局
+    #[serde(default = "default_pk_on_tie")]
+    pub pk_on_tie: bool,
+    /// 重新投票仍然平票时的处理
+    #[serde(default)]
+    pub revote_tie: RevoteTiePolicy,
+}
+
+impl Default for VoteRules {
+    fn default() -> Self {
+        Self {
+            sheriff_weight: default_sheriff_weight(),
+            mandatory: false,
+            pk_on_tie: true,
+            revote_tie: RevoteTiePolicy::default(),
+        }
+    }
+}
+
+fn default_sheriff_weight() -> f32 {
+    1.5
+}
+
+fn default_pk_on_tie() -> bool {
+    true
+}
+
+/// PK后重新投票仍然平票时的处理
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RevoteTiePolicy {
+    /// 无人出局
+    #[default]
+    NoElimination,
+    /// 在平票的玩家中随机放逐一人
+    Random,
+}
+
+impl VoteRules {
+    /// 玩家一票的权重，失去票权的玩家为0
+    pub fn weight(&self, state: &GameState, voter: &str) -> f32 {
+        if state.revoked_votes.iter().any(|id| id == voter) {
+            0.0
+        } else if state.sheriff.as_deref() == Some(voter) {
+            self.sheriff_weight
+        } else {
+            1.0
+        }
+    }
+}
+
+/// 死亡时的身份公开规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RoleRevealPolicy {
+    /// 公开具体身份
+    #[default]
+    RevealAll,
+    /// 只公开阵营
+    FactionOnly,
+    /// 什么都不公开（暗牌局）
+    Hidden,
+}
+
+impl RoleRevealPolicy {
+    /// 按规则生成玩家死亡时的公示信息
+    pub fn reveal(&self, player: &Player, day: u32) -> DeathReveal {
+        let (revealed_role, revealed_faction) = match self {
+            RoleRevealPolicy::RevealAll => (Some(player.role.role_type.clone()), Some(player.role.faction.clone())),
+            RoleRevealPolicy::FactionOnly => (None, Some(player.role.faction.clone())),
+            RoleRevealPolicy::Hidden => (None, None),
+        };
+        
+        DeathReveal {
+            player_id: player.id.clone(),
+            day,
+            revealed_role,
+            revealed_faction,
+        }
+    }
+}
+
+/// 死亡公示信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeathReveal {
+    pub player_id: String,
+    pub day: u32,
+    pub revealed_role: Option<RoleType>,
+    pub revealed_faction: Option<Faction>,
+}
+
+/// AI发言节奏配置（模拟思考与打字延迟）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechTimingConfig {
+    pub enabled: bool,
+    pub base_delay_ms: u64,
+    pub per_char_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for SpeechTimingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base_delay_ms: 800,
+            per_char_delay_ms: 40,
+            max_delay_ms: 8000,
+        }
+    }
+}
+
+impl SpeechTimingConfig {
+    /// 根据发言长度和耐心计算总延迟，耐心越高的AI思考越久
+    pub fn delay_for(&self, content: &str, patience: f32) -> std::time::Duration {
+        if !self.enabled {
+            return std::time::Duration::ZERO;
+        }
+        
+        let chars = content.chars().count() as u64;
+        let raw = self.base_delay_ms + chars * self.per_char_delay_ms;
+        let scaled = (raw as f32 * (0.5 + patience.clamp(0.0, 1.0))) as u64;
+        std::time::Duration::from_millis(scaled.min(self.max_delay_ms))
+    }
 }
 
 /// AI性格
@@ -90,6 +799,21 @@ pub struct PersonalityTraits {
     pub logic: f32,         // 0.0-1.0
     pub deception: f32,     // 0.0-1.0
     pub trustfulness: f32,  // 0.0-1.0
+    #[serde(default = "default_patience")]
+    pub patience: f32,      // 0.0-1.0
+}
+
+fn default_patience() -> f32 {
+    0.5
+}
+
+/// AI人设（名字、头像、简介），与性格模板保持一致，整局不变
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AIPersona {
+    pub name: String,
+    pub avatar: String,
+    pub bio: String,
+    pub template_id: String,
 }
 
 /// 发言意图
@@ -141,12 +865,16 @@ pub struct TurnDetectionConfig {
 }
 
 /// LLM提供商
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum LLMProvider {
     OpenAI,
     Anthropic,
     Azure,
     Custom,
+    /// 演示/离线模式：不联网，使用预置或程序生成的回复
+    Demo,
+    /// 本地模型服务（Ollama或llama.cpp server），不需要API密钥，可完全离线运行
+    LocalOllama,
 }
 
 /// 游戏动作
@@ -235,6 +963,112 @@ pub struct Player {
     pub is_alive: bool,
     pub is_ai: bool,
     pub personality: Option<AIPersonality>,
+    #[serde(default)]
+    pub persona: Option<AIPersona>,
+    /// 座位号（1..N）
+    #[serde(default)]
+    pub seat: u8,
+}
+
+impl Player {
+    /// 带座位号的显示名，例如“3号机智的狼”
+    pub fn display_name(&self) -> String {
+        format!("{}号{}", self.seat, self.name)
+    }
+    
+    /// 该玩家是否知道对方是狼队队友
+    pub fn knows_teammate(&self, other: &Player) -> bool {
+        self.id != other.id
+            && self.role.role_type.sees_wolf_team()
+            && other.role.faction == Faction::Werewolf
+            && other.role.role_type.known_to_wolf_team()
+    }
+}
+
+/// 某位玩家视角下的座位信息，身份只在本人、狼队友或已公示时给出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerView {
+    pub id: String,
+    pub name: String,
+    pub seat: u8,
+    pub is_alive: bool,
+    pub is_ai: bool,
+    pub persona: Option<AIPersona>,
+    pub role: Option<RoleType>,
+    pub faction: Option<Faction>,
+}
+
+/// 某位玩家视角下的局面，不含他人身份、女巫的药和守卫的守护
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameView {
+    pub viewer_id: String,
+    pub phase: GamePhase,
+    pub day: u32,
+    pub players: Vec<PlayerView>,
+    pub dead_players: Vec<PlayerView>,
+    pub votes: Vec<VoteRecord>,
+    pub game_config: GameConfig,
+    pub winner: Option<Faction>,
+    pub current_speaker: Option<String>,
+    pub time_remaining: Option<u32>,
+    pub vote_changes: Vec<VoteChange>,
+    pub vote_intents: HashMap<String, String>,
+    pub death_reveals: Vec<DeathReveal>,
+    pub claims_board: ClaimsBoard,
+    pub vote_outcomes: Vec<VoteOutcome>,
+    pub result: Option<GameResult>,
+    pub sheriff: Option<String>,
+    pub revoked_votes: Vec<String>,
+    pub pk_candidates: Vec<String>,
+}
+
+impl GameState {
+    /// 按viewer能掌握的信息裁剪局面，游戏结束后公开全部身份
+    pub fn view_for(&self, viewer: &Player) -> GameView {
+        let reveal_all = self.phase == GamePhase::GameOver;
+        let view_player = |player: &Player| {
+            let (role, faction) = if reveal_all || player.id == viewer.id || viewer.knows_teammate(player) {
+                (Some(player.role.role_type.clone()), Some(player.role.faction.clone()))
+            } else {
+                self.death_reveals.iter()
+                    .find(|reveal| reveal.player_id == player.id)
+                    .map(|reveal| (reveal.revealed_role.clone(), reveal.revealed_faction.clone()))
+                    .unwrap_or((None, None))
+            };
+            PlayerView {
+                id: player.id.clone(),
+                name: player.name.clone(),
+                seat: player.seat,
+                is_alive: player.is_alive,
+                is_ai: player.is_ai,
+                persona: player.persona.clone(),
+                role,
+                faction,
+            }
+        };
+        
+        GameView {
+            viewer_id: viewer.id.clone(),
+            phase: self.phase.clone(),
+            day: self.day,
+            players: self.players.iter().map(&view_player).collect(),
+            dead_players: self.dead_players.iter().map(view_player).collect(),
+            votes: self.votes.clone(),
+            game_config: self.game_config.clone(),
+            winner: self.winner.clone(),
+            current_speaker: self.current_speaker.clone(),
+            time_remaining: self.time_remaining,
+            vote_changes: self.vote_changes.clone(),
+            vote_intents: self.vote_intents.clone(),
+            death_reveals: self.death_reveals.clone(),
+            claims_board: self.claims_board.clone(),
+            vote_outcomes: self.vote_outcomes.clone(),
+            result: self.result.clone(),
+            sheriff: self.sheriff.clone(),
+            revoked_votes: self.revoked_votes.clone(),
+            pk_candidates: self.pk_candidates.clone(),
+        }
+    }
 }
 
 /// 夜晚结果
@@ -270,9 +1104,15 @@ pub struct AIAnalysisReport {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameResult {
     pub winner: Faction,
+    /// 游戏时长（秒）
     pub game_duration: u32,
+    /// 所有放逐投票的总票数
     pub total_votes: u32,
+    /// 出局玩家，按出局顺序排列
     pub players_killed: Vec<String>,
+    /// 存活到最后的玩家，按座位号排列
+    #[serde(default)]
+    pub survivors: Vec<String>,
 }
 
 /// 语音记录
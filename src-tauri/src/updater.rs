@@ -0,0 +1,168 @@
+use crate::error::{AppError, AppResult};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use log::info;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 检查更新配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// 关闭后不发出任何联网请求
+    pub enabled: bool,
+    /// GitHub Releases接口地址
+    pub releases_url: String,
+    /// 是否提示预发布版本
+    pub include_prereleases: bool,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            releases_url: "https://api.github.com/repos/NTLx/MindWolf/releases".to_string(),
+            include_prereleases: false,
+        }
+    }
+}
+
+/// 检查更新的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum UpdateStatus {
+    /// 已在设置中关闭检查更新
+    Disabled,
+    /// 已是最新版本
+    UpToDate { current_version: String },
+    /// 有新版本
+    Available(ReleaseInfo),
+}
+
+/// 新版本信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub release_notes: String,
+    /// 当前平台的安装包，没有匹配的安装包时为发布页
+    pub download_url: String,
+    pub published_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// 查询发布列表，与当前版本比较
+pub async fn check(config: &UpdateConfig, current_version: &str) -> AppResult<UpdateStatus> {
+    if !config.enabled {
+        return Ok(UpdateStatus::Disabled);
+    }
+
+    let current = parse_version(current_version)
+        .ok_or_else(|| AppError::Config(format!("无法解析当前版本号: {}", current_version)))?;
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    let response = client.get(&config.releases_url)
+        // GitHub接口要求带User-Agent
+        .header("User-Agent", format!("MindWolf/{}", current_version))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!("查询发布列表失败: {}", response.status())));
+    }
+    let releases: Vec<GitHubRelease> = response.json().await?;
+
+    let status = match latest_release(releases, config.include_prereleases) {
+        Some((version, release)) if version > current => {
+            info!("发现新版本: {} (当前 {})", version, current);
+            UpdateStatus::Available(ReleaseInfo {
+                current_version: current.to_string(),
+                latest_version: version.to_string(),
+                release_notes: release.body.clone().unwrap_or_default(),
+                download_url: platform_asset(&release.assets)
+                    .map(|asset| asset.browser_download_url.clone())
+                    .unwrap_or_else(|| release.html_url.clone()),
+                published_at: release.published_at,
+            })
+        }
+        _ => UpdateStatus::UpToDate { current_version: current.to_string() },
+    };
+    Ok(status)
+}
+
+/// 版本号可带v前缀，如v0.2.0
+fn parse_version(tag: &str) -> Option<Version> {
+    Version::parse(tag.trim().trim_start_matches(['v', 'V'])).ok()
+}
+
+/// 版本号最高的正式发布（按配置包含预发布），跳过草稿和无法解析的标签
+fn latest_release(releases: Vec<GitHubRelease>, include_prereleases: bool) -> Option<(Version, GitHubRelease)> {
+    releases.into_iter()
+        .filter(|release| !release.draft && (include_prereleases || !release.prerelease))
+        .filter_map(|release| parse_version(&release.tag_name).map(|version| (version, release)))
+        .filter(|(version, _)| include_prereleases || version.pre.is_empty())
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+}
+
+/// 按扩展名挑选当前平台的安装包
+fn platform_asset(assets: &[GitHubAsset]) -> Option<&GitHubAsset> {
+    let extensions: &[&str] = if cfg!(windows) {
+        &[".msi", ".exe", ".zip"]
+    } else if cfg!(target_os = "macos") {
+        &[".dmg", ".app.tar.gz"]
+    } else {
+        &[".AppImage", ".deb", ".rpm"]
+    };
+    extensions.iter()
+        .find_map(|ext| assets.iter().find(|asset| asset.name.ends_with(ext)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> GitHubRelease {
+        GitHubRelease {
+            tag_name: tag.to_string(),
+            html_url: format!("https://github.com/NTLx/MindWolf/releases/tag/{}", tag),
+            body: None,
+            draft: false,
+            prerelease,
+            published_at: None,
+            assets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_latest_release_skips_prereleases() {
+        let releases = || vec![release("v0.2.0", false), release("v0.3.0-beta.1", true), release("nightly", false)];
+
+        let (version, _) = latest_release(releases(), false).unwrap();
+        assert_eq!(version, Version::new(0, 2, 0));
+        let (version, _) = latest_release(releases(), true).unwrap();
+        assert_eq!(version.to_string(), "0.3.0-beta.1");
+        assert!(parse_version("v0.2.0").unwrap() > parse_version("0.1.0").unwrap());
+    }
+}
@@ -7,33 +7,108 @@ pub fn generate_id() -> String {
     Uuid::new_v4().to_string()
 }
 
-/// 生成随机昵称
-pub fn generate_ai_name() -> String {
-    let adjectives = [
-        "聪明的", "机智的", "冷静的", "狡猾的", "勇敢的",
-        "沉稳的", "敏锐的", "谨慎的", "果断的", "睿智的"
-    ];
+/// 从发言中解析提到的座位号，支持“3号”和“三号”两种写法
+pub fn parse_seat_mentions(content: &str) -> Vec<u8> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut seats = Vec::new();
     
-    let nouns = [
-        "狼", "鹰", "狐", "豹", "虎", "狮", "熊", "鹿", "鸟", "蛇"
-    ];
+    for (index, &c) in chars.iter().enumerate() {
+        if c != '号' {
+            continue;
+        }
+        
+        // 向前收集紧挨着“号”的数字
+        let mut start = index;
+        while start > 0 && (chars[start - 1].is_ascii_digit() || chinese_digit(chars[start - 1]).is_some()) {
+            start -= 1;
+        }
+        if start == index {
+            continue;
+        }
+        
+        let token: String = chars[start..index].iter().collect();
+        let seat = if token.chars().all(|c| c.is_ascii_digit()) {
+            token.parse::<u8>().ok()
+        } else {
+            parse_chinese_number(&token)
+        };
+        
+        if let Some(seat) = seat {
+            if seat > 0 && !seats.contains(&seat) {
+                seats.push(seat);
+            }
+        }
+    }
     
-    let mut rng = thread_rng();
-    let adj = adjectives[rng.gen_range(0..adjectives.len())];
-    let noun = nouns[rng.gen_range(0..nouns.len())];
+    seats
+}
+
+fn chinese_digit(c: char) -> Option<u8> {
+    match c {
+        '一' => Some(1),
+        '二' | '两' => Some(2),
+        '三' => Some(3),
+        '四' => Some(4),
+        '五' => Some(5),
+        '六' => Some(6),
+        '七' => Some(7),
+        '八' => Some(8),
+        '九' => Some(9),
+        '十' => Some(10),
+        _ => None,
+    }
+}
+
+/// 解析二十以内的中文数字（如“十二”）
+pub fn parse_chinese_number(token: &str) -> Option<u8> {
+    let digits: Vec<u8> = token.chars().map(chinese_digit).collect::<Option<Vec<_>>>()?;
+    match digits.as_slice() {
+        [d] => Some(*d),
+        [10, d] if *d < 10 => Some(10 + d),
+        [d, 10] if *d < 10 => Some(d * 10),
+        [d, 10, e] if *d < 10 && *e < 10 => Some(d * 10 + e),
+        _ => None,
+    }
+}
+
+/// 按字符数截断文本，尽量停在句子边界上
+pub fn trim_to_sentence(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    
+    let head: Vec<char> = text.chars().take(max_chars).collect();
+    // 在后半段找最后一个句末标点，找不到就硬截断
+    let boundary = head.iter()
+        .rposition(|c| "。！？!?；;…\n".contains(*c))
+        .filter(|&index| index + 1 >= max_chars / 2);
     
-    format!("{}{}", adj, noun)
+    match boundary {
+        Some(index) => head[..=index].iter().collect::<String>().trim_end().to_string(),
+        None => head.into_iter().collect(),
+    }
 }
 
-/// 获取角色描述
-pub fn get_role_description(role_type: &RoleType) -> String {
+/// 获取角色名称
+pub fn get_role_name(role_type: &RoleType) -> &'static str {
     match role_type {
-        RoleType::Werewolf => "狼人：夜晚可以杀死一名玩家，目标是消灭所有好人".to_string(),
-        RoleType::Villager => "村民：普通村民，没有特殊技能，依靠投票和推理找出狼人".to_string(),
-        RoleType::Seer => "预言家：每晚可以查验一名玩家的身份".to_string(),
-        RoleType::Witch => "女巫：拥有一瓶解药和一瓶毒药，可以救人或杀人".to_string(),
-        RoleType::Hunter => "猎人：被投票出局或被狼人杀死时，可以带走一名玩家".to_string(),
-        RoleType::Guard => "守卫：每晚可以保护一名玩家，使其免受狼人攻击".to_string(),
+        RoleType::Werewolf => "狼人",
+        RoleType::Villager => "村民",
+        RoleType::Seer => "预言家",
+        RoleType::Witch => "女巫",
+        RoleType::Hunter => "猎人",
+        RoleType::Guard => "守卫",
+        RoleType::DreamWolf => "梦狼",
+        RoleType::Gargoyle => "石像鬼",
+    }
+}
+
+/// 获取阵营名称
+pub fn get_faction_name(faction: &Faction) -> &'static str {
+    match faction {
+        Faction::Werewolf => "狼人阵营",
+        Faction::Villager => "好人阵营",
     }
 }
 
@@ -77,44 +152,47 @@ pub fn shuffle<T>(vec: &mut Vec<T>) {
     }
 }
 
+/// 支持的最少玩家数
+pub const MIN_PLAYERS: u8 = 6;
+/// 支持的最多玩家数
+pub const MAX_PLAYERS: u8 = 18;
+
 /// 生成角色分配
+///
+/// 狼人约占三分之一；预言家、女巫固定，10人起加猎人，12人起加守卫，其余为村民。
+/// 6/8/10/12人与经典板子一致，其他人数按同样的比例扩展到18人
 pub fn generate_role_distribution(total_players: u8) -> std::collections::HashMap<RoleType, u8> {
     let mut distribution = std::collections::HashMap::new();
+    let total_players = total_players.clamp(MIN_PLAYERS, MAX_PLAYERS);
     
-    match total_players {
-        6 => {
-            distribution.insert(RoleType::Werewolf, 2);
-            distribution.insert(RoleType::Villager, 2);
-            distribution.insert(RoleType::Seer, 1);
-            distribution.insert(RoleType::Witch, 1);
-        }
-        8 => {
-            distribution.insert(RoleType::Werewolf, 3);
-            distribution.insert(RoleType::Villager, 3);
-            distribution.insert(RoleType::Seer, 1);
-            distribution.insert(RoleType::Witch, 1);
-        }
-        10 => {
-            distribution.insert(RoleType::Werewolf, 3);
-            distribution.insert(RoleType::Villager, 4);
-            distribution.insert(RoleType::Seer, 1);
-            distribution.insert(RoleType::Witch, 1);
-            distribution.insert(RoleType::Hunter, 1);
-        }
-        12 => {
-            distribution.insert(RoleType::Werewolf, 4);
-            distribution.insert(RoleType::Villager, 4);
-            distribution.insert(RoleType::Seer, 1);
-            distribution.insert(RoleType::Witch, 1);
-            distribution.insert(RoleType::Hunter, 1);
-            distribution.insert(RoleType::Guard, 1);
-        }
-        _ => {
-            // 默认配置
-            distribution.insert(RoleType::Werewolf, 2);
-            distribution.insert(RoleType::Villager, total_players - 2);
-        }
+    let wolves = (total_players + 1) / 3;
+    let mut gods = vec![RoleType::Seer, RoleType::Witch];
+    if total_players >= 10 {
+        gods.push(RoleType::Hunter);
+    }
+    if total_players >= 12 {
+        gods.push(RoleType::Guard);
+    }
+    
+    distribution.insert(RoleType::Werewolf, wolves);
+    distribution.insert(RoleType::Villager, total_players - wolves - gods.len() as u8);
+    for god in gods {
+        distribution.insert(god, 1);
     }
     
     distribution
 }
+
+/// 指定的板子能否开局：总数与人数一致，至少有一只能刀人的狼，且狼队人数少于好人
+pub fn is_playable_distribution(total_players: u8, distribution: &std::collections::HashMap<RoleType, u8>) -> bool {
+    let count = |faction: Faction| -> u32 {
+        distribution.iter()
+            .filter(|(role, _)| role.faction() == faction)
+            .map(|(_, count)| *count as u32)
+            .sum()
+    };
+    let killers = distribution.get(&RoleType::Werewolf).copied().unwrap_or(0);
+    count(Faction::Werewolf) + count(Faction::Villager) == total_players as u32
+        && killers > 0
+        && count(Faction::Werewolf) < count(Faction::Villager)
+}
@@ -0,0 +1,381 @@
+use crate::error::{AppError, AppResult};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 操作校验错误码
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ActionErrorCode {
+    GameNotStarted,
+    GameAlreadyStarted,
+    GameOver,
+    WrongPhase,
+    PlayerNotFound,
+    PlayerDead,
+    TargetNotFound,
+    TargetDead,
+    RoleNotAllowed,
+    NotYourTurn,
+    DuplicateAction,
+    SpeechTooLong,
+    UndoDisabled,
+    NothingToUndo,
+    PotionUsed,
+    ConsecutiveProtect,
+    NotPkCandidate,
+    AbstainNotAllowed,
+    ContentBlocked,
+}
+
+impl ActionErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionErrorCode::GameNotStarted => "GAME_NOT_STARTED",
+            ActionErrorCode::GameAlreadyStarted => "GAME_ALREADY_STARTED",
+            ActionErrorCode::GameOver => "GAME_OVER",
+            ActionErrorCode::WrongPhase => "WRONG_PHASE",
+            ActionErrorCode::PlayerNotFound => "PLAYER_NOT_FOUND",
+            ActionErrorCode::PlayerDead => "PLAYER_DEAD",
+            ActionErrorCode::TargetNotFound => "TARGET_NOT_FOUND",
+            ActionErrorCode::TargetDead => "TARGET_DEAD",
+            ActionErrorCode::RoleNotAllowed => "ROLE_NOT_ALLOWED",
+            ActionErrorCode::NotYourTurn => "NOT_YOUR_TURN",
+            ActionErrorCode::DuplicateAction => "DUPLICATE_ACTION",
+            ActionErrorCode::SpeechTooLong => "SPEECH_TOO_LONG",
+            ActionErrorCode::UndoDisabled => "UNDO_DISABLED",
+            ActionErrorCode::NothingToUndo => "NOTHING_TO_UNDO",
+            ActionErrorCode::PotionUsed => "POTION_USED",
+            ActionErrorCode::ConsecutiveProtect => "CONSECUTIVE_PROTECT",
+            ActionErrorCode::NotPkCandidate => "NOT_PK_CANDIDATE",
+            ActionErrorCode::AbstainNotAllowed => "ABSTAIN_NOT_ALLOWED",
+            ActionErrorCode::ContentBlocked => "CONTENT_BLOCKED",
+        }
+    }
+}
+
+/// 待校验的玩家操作
+#[derive(Debug, Clone)]
+pub enum PlayerAction {
+    StartGame,
+    /// target为None表示弃票
+    Vote { voter: String, target: Option<String> },
+    VoteIntent { voter: String, target: String },
+    Speech { speaker: String },
+    /// 打断当前发言者提出异议
+    Objection { player: String },
+    NightAction(NightAction),
+    Undo,
+    /// 主持人设置警长，None表示撕毁警徽
+    SetSheriff { player: Option<String> },
+    /// 主持人剥夺玩家的投票权
+    RevokeVote { player: String },
+}
+
+/// 操作校验器，所有入站操作在改变游戏状态前都需经过校验
+pub struct ActionValidator;
+
+impl ActionValidator {
+    /// 校验操作是否合法
+    pub fn validate(state: &GameState, night_actors: &HashSet<String>, action: &PlayerAction) -> AppResult<()> {
+        match action {
+            PlayerAction::StartGame => {
+                if state.phase != GamePhase::Preparation {
+                    return Err(Self::error(ActionErrorCode::GameAlreadyStarted, "游戏已经开始"));
+                }
+                Ok(())
+            }
+            PlayerAction::Vote { voter, target } => {
+                Self::check_in_progress(state)?;
+                Self::check_phase(state, &[GamePhase::Voting], "当前不是投票阶段")?;
+                let voter = Self::check_actor(state, voter)?;
+                if !Self::can_vote(state, voter) {
+                    return Err(Self::error(ActionErrorCode::RoleNotAllowed, "该玩家没有投票权"));
+                }
+                let target = match target {
+                    Some(target) => target,
+                    None if state.game_config.vote_rules.mandatory => {
+                        return Err(Self::error(ActionErrorCode::AbstainNotAllowed, "本局规则不允许弃票"));
+                    }
+                    None => return Ok(()),
+                };
+                Self::check_target(state, target)?;
+                // PK后的重新投票只能投给平票的玩家
+                if !state.pk_candidates.is_empty() && !state.pk_candidates.contains(target) {
+                    return Err(Self::error(ActionErrorCode::NotPkCandidate, "重新投票只能投给PK的玩家"));
+                }
+                Ok(())
+            }
+            PlayerAction::VoteIntent { voter, target } => {
+                Self::check_in_progress(state)?;
+                Self::check_phase(state, &[GamePhase::DayDiscussion], "只能在讨论阶段表达投票意向")?;
+                let voter = Self::check_actor(state, voter)?;
+                if !Self::can_vote(state, voter) {
+                    return Err(Self::error(ActionErrorCode::RoleNotAllowed, "该玩家没有投票权"));
+                }
+                Self::check_target(state, target)?;
+                Ok(())
+            }
+            PlayerAction::Speech { speaker } => {
+                Self::check_in_progress(state)?;
+                Self::check_phase(state, &[GamePhase::DayDiscussion, GamePhase::Pk, GamePhase::LastWords], "当前阶段不能发言")?;
+                if state.phase == GamePhase::Pk && !state.pk_candidates.contains(speaker) {
+                    return Err(Self::error(ActionErrorCode::NotPkCandidate, "PK阶段只有平票的玩家发言"));
+                }
+
+                // 遗言阶段允许刚出局的玩家发言
+                if state.phase == GamePhase::LastWords {
+                    Self::find_player(state, speaker)
+                        .ok_or_else(|| Self::error(ActionErrorCode::PlayerNotFound, "发言者不存在"))?;
+                } else {
+                    Self::check_actor(state, speaker)?;
+                }
+
+                if let Some(current) = &state.current_speaker {
+                    if current != speaker {
+                        return Err(Self::error(ActionErrorCode::NotYourTurn, "还没有轮到该玩家发言"));
+                    }
+                }
+                Ok(())
+            }
+            PlayerAction::Objection { player } => {
+                Self::check_in_progress(state)?;
+                Self::check_phase(state, &[GamePhase::DayDiscussion], "只能在讨论阶段提出异议")?;
+                Self::check_actor(state, player)?;
+                match &state.current_speaker {
+                    Some(current) if current != player => Ok(()),
+                    Some(_) => Err(Self::error(ActionErrorCode::NotYourTurn, "不能打断自己的发言")),
+                    None => Err(Self::error(ActionErrorCode::NotYourTurn, "当前没有人在发言")),
+                }
+            }
+            PlayerAction::NightAction(night_action) => {
+                Self::check_in_progress(state)?;
+                Self::check_phase(state, &[GamePhase::Night], "当前不是夜晚")?;
+                let actor = Self::check_actor(state, &night_action.player)?;
+
+                let allowed = match night_action.action {
+                    NightActionType::Kill => Self::can_kill(state, actor),
+                    NightActionType::Check => actor.role.role_type == RoleType::Seer,
+                    NightActionType::Heal | NightActionType::Poison => actor.role.role_type == RoleType::Witch,
+                    NightActionType::Protect => actor.role.role_type == RoleType::Guard,
+                };
+                if !allowed {
+                    return Err(Self::error(ActionErrorCode::RoleNotAllowed, "该角色不能执行此夜晚行动"));
+                }
+
+                if night_actors.contains(&night_action.player) {
+                    return Err(Self::error(ActionErrorCode::DuplicateAction, "本夜已经行动过"));
+                }
+
+                // 不选目标视为不用药
+                if night_action.target.is_some() {
+                    let potions = state.witch_potions.get(&night_action.player).copied().unwrap_or_default();
+                    match night_action.action {
+                        NightActionType::Heal if !potions.antidote => {
+                            return Err(Self::error(ActionErrorCode::PotionUsed, "解药已经用过"));
+                        }
+                        NightActionType::Poison if !potions.poison => {
+                            return Err(Self::error(ActionErrorCode::PotionUsed, "毒药已经用过"));
+                        }
+                        _ => {}
+                    }
+                }
+
+                if let Some(target) = &night_action.target {
+                    Self::check_target(state, target)?;
+                    if matches!(night_action.action, NightActionType::Protect)
+                        && Self::blocked_protect_target(state, &night_action.player) == Some(target.as_str())
+                    {
+                        return Err(Self::error(ActionErrorCode::ConsecutiveProtect, "不能连续两晚守护同一名玩家"));
+                    }
+                }
+                Ok(())
+            }
+            PlayerAction::Undo => {
+                Self::check_in_progress(state)?;
+                if state.game_config.rule_preset != Some(RulePreset::Casual) {
+                    return Err(Self::error(ActionErrorCode::UndoDisabled, "只有娱乐局可以撤销操作"));
+                }
+                Ok(())
+            }
+            PlayerAction::SetSheriff { player } => {
+                Self::check_in_progress(state)?;
+                match player {
+                    Some(player) => Self::check_target(state, player),
+                    None => Ok(()),
+                }
+            }
+            PlayerAction::RevokeVote { player } => {
+                Self::check_in_progress(state)?;
+                Self::check_target(state, player)
+            }
+        }
+    }
+
+    /// 是否有投票权：身份允许投票、没有被剥夺票权，且不是正在PK的玩家
+    pub fn can_vote(state: &GameState, voter: &Player) -> bool {
+        voter.role.can_vote
+            && state.game_config.vote_rules.weight(state, &voter.id) > 0.0
+            && !state.pk_candidates.contains(&voter.id)
+    }
+
+    /// 守卫今晚不能守护的玩家：昨晚守过的人
+    pub fn blocked_protect_target<'a>(state: &'a GameState, guard_id: &str) -> Option<&'a str> {
+        state.guard_protections.get(guard_id)
+            .filter(|protection| protection.night + 1 == state.day)
+            .map(|protection| protection.target.as_str())
+    }
+
+    /// 能否参与夜间击杀：不参与刀人的狼只有在没有其他能刀人的狼存活时才能动手
+    pub fn can_kill(state: &GameState, actor: &Player) -> bool {
+        if actor.role.role_type.joins_wolf_kill() {
+            return true;
+        }
+        actor.role.faction == Faction::Werewolf
+            && !state.players.iter().any(|p| p.is_alive && p.role.role_type.joins_wolf_kill())
+    }
+
+    fn check_in_progress(state: &GameState) -> AppResult<()> {
+        match state.phase {
+            GamePhase::Preparation => Err(Self::error(ActionErrorCode::GameNotStarted, "游戏尚未开始")),
+            GamePhase::GameOver => Err(Self::error(ActionErrorCode::GameOver, "游戏已经结束")),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_phase(state: &GameState, allowed: &[GamePhase], message: &str) -> AppResult<()> {
+        if allowed.contains(&state.phase) {
+            Ok(())
+        } else {
+            Err(Self::error(ActionErrorCode::WrongPhase, message))
+        }
+    }
+
+    fn check_actor<'a>(state: &'a GameState, player_id: &str) -> AppResult<&'a Player> {
+        let player = Self::find_player(state, player_id)
+            .ok_or_else(|| Self::error(ActionErrorCode::PlayerNotFound, "玩家不存在"))?;
+
+        if !player.is_alive {
+            return Err(Self::error(ActionErrorCode::PlayerDead, "玩家已死亡"));
+        }
+        Ok(player)
+    }
+
+    fn check_target(state: &GameState, target_id: &str) -> AppResult<()> {
+        let target = Self::find_player(state, target_id)
+            .ok_or_else(|| Self::error(ActionErrorCode::TargetNotFound, "目标不存在"))?;
+
+        if !target.is_alive {
+            return Err(Self::error(ActionErrorCode::TargetDead, "目标已死亡"));
+        }
+        Ok(())
+    }
+
+    fn find_player<'a>(state: &'a GameState, player_id: &str) -> Option<&'a Player> {
+        state.players.iter()
+            .chain(state.dead_players.iter())
+            .find(|p| p.id == player_id)
+    }
+
+    fn error(code: ActionErrorCode, message: &str) -> AppError {
+        AppError::InvalidAction(code, message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::game_engine::GameEngine;
+
+    /// 开局后停在第一夜的局面
+    fn night_state() -> GameState {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        engine.start_game().unwrap();
+        engine.get_state().clone()
+    }
+
+    fn code(state: &GameState, night_actors: &HashSet<String>, action: PlayerAction) -> Option<ActionErrorCode> {
+        match ActionValidator::validate(state, night_actors, &action) {
+            Ok(()) => None,
+            Err(AppError::InvalidAction(code, _)) => Some(code),
+            Err(e) => panic!("非预期的错误: {}", e),
+        }
+    }
+
+    fn player_with(state: &GameState, role: RoleType) -> String {
+        state.players.iter().find(|p| p.role.role_type == role).unwrap().id.clone()
+    }
+
+    #[test]
+    fn test_phase_and_lifecycle_checks() {
+        let mut state = night_state();
+        let none = HashSet::new();
+        let ids: Vec<String> = state.players.iter().map(|p| p.id.clone()).collect();
+
+        assert_eq!(code(&state, &none, PlayerAction::StartGame), Some(ActionErrorCode::GameAlreadyStarted));
+        assert_eq!(code(&state, &none, PlayerAction::Speech { speaker: ids[0].clone() }), Some(ActionErrorCode::WrongPhase));
+        assert_eq!(
+            code(&state, &none, PlayerAction::Vote { voter: ids[0].clone(), target: Some(ids[1].clone()) }),
+            Some(ActionErrorCode::WrongPhase)
+        );
+
+        state.phase = GamePhase::Preparation;
+        assert_eq!(code(&state, &none, PlayerAction::StartGame), None);
+        assert_eq!(code(&state, &none, PlayerAction::Speech { speaker: ids[0].clone() }), Some(ActionErrorCode::GameNotStarted));
+
+        state.phase = GamePhase::GameOver;
+        assert_eq!(code(&state, &none, PlayerAction::Vote { voter: ids[0].clone(), target: None }), Some(ActionErrorCode::GameOver));
+    }
+
+    #[test]
+    fn test_liveness_and_turn_checks() {
+        let mut state = night_state();
+        let none = HashSet::new();
+        let ids: Vec<String> = state.players.iter().map(|p| p.id.clone()).collect();
+        state.phase = GamePhase::Voting;
+        state.players[0].is_alive = false;
+
+        assert_eq!(
+            code(&state, &none, PlayerAction::Vote { voter: ids[0].clone(), target: Some(ids[1].clone()) }),
+            Some(ActionErrorCode::PlayerDead)
+        );
+        assert_eq!(
+            code(&state, &none, PlayerAction::Vote { voter: ids[1].clone(), target: Some(ids[0].clone()) }),
+            Some(ActionErrorCode::TargetDead)
+        );
+        assert_eq!(
+            code(&state, &none, PlayerAction::Vote { voter: "nobody".to_string(), target: None }),
+            Some(ActionErrorCode::PlayerNotFound)
+        );
+        assert_eq!(code(&state, &none, PlayerAction::Vote { voter: ids[1].clone(), target: None }), None);
+
+        state.phase = GamePhase::DayDiscussion;
+        state.current_speaker = Some(ids[2].clone());
+        assert_eq!(code(&state, &none, PlayerAction::Speech { speaker: ids[1].clone() }), Some(ActionErrorCode::NotYourTurn));
+        assert_eq!(code(&state, &none, PlayerAction::Speech { speaker: ids[2].clone() }), None);
+        assert_eq!(code(&state, &none, PlayerAction::Undo), Some(ActionErrorCode::UndoDisabled));
+    }
+
+    #[test]
+    fn test_night_action_role_and_duplicate_checks() {
+        let state = night_state();
+        let seer = player_with(&state, RoleType::Seer);
+        let wolf = player_with(&state, RoleType::Werewolf);
+        let villager = player_with(&state, RoleType::Villager);
+        let action = |player: &str, action: NightActionType| PlayerAction::NightAction(NightAction {
+            player: player.to_string(),
+            action,
+            target: Some(villager.clone()),
+        });
+        let none = HashSet::new();
+
+        assert_eq!(code(&state, &none, action(&seer, NightActionType::Kill)), Some(ActionErrorCode::RoleNotAllowed));
+        assert_eq!(code(&state, &none, action(&villager, NightActionType::Check)), Some(ActionErrorCode::RoleNotAllowed));
+        assert_eq!(code(&state, &none, action(&seer, NightActionType::Check)), None);
+        assert_eq!(code(&state, &none, action(&wolf, NightActionType::Kill)), None);
+
+        let acted: HashSet<String> = [seer.clone()].into_iter().collect();
+        assert_eq!(code(&state, &acted, action(&seer, NightActionType::Check)), Some(ActionErrorCode::DuplicateAction));
+    }
+}
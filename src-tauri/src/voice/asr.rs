@@ -1,7 +1,8 @@
 use crate::error::{AppError, AppResult};
+use crate::platform;
 use crate::voice::VoiceConfig;
-use std::process::Command;
 use tokio::fs;
+use tokio::process::Command;
 use log::{info, warn, debug};
 use std::path::PathBuf;
 use chrono::Utc;
@@ -9,20 +10,74 @@ use chrono::Utc;
 /// 语音识别引擎
 pub struct ASREngine {
     config: VoiceConfig,
-    model_path: Option<PathBuf>,
+    backend: ASRBackend,
+}
+
+/// 本地语音识别后端
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ASRBackend {
+    /// openai-whisper命令行（pip安装，各平台通用）
+    Whisper,
+    /// whisper.cpp命令行，需要数据目录models下的ggml模型；macOS可用Homebrew安装
+    WhisperCpp { program: String, model: PathBuf },
+    /// 模拟识别
+    Mock,
+}
+
+impl ASRBackend {
+    /// whisper.cpp不同安装方式下的程序名
+    const WHISPER_CPP_PROGRAMS: [&'static str; 2] = ["whisper-cli", "whisper-cpp"];
+    
+    pub fn detect() -> Self {
+        if platform::find_executable("whisper").is_some() {
+            return Self::Whisper;
+        }
+        let program = Self::WHISPER_CPP_PROGRAMS.iter()
+            .find(|program| platform::find_executable(program).is_some());
+        match (program, Self::find_ggml_model()) {
+            (Some(program), Some(model)) => Self::WhisperCpp { program: program.to_string(), model },
+            _ => Self::Mock,
+        }
+    }
+    
+    /// 数据目录models下的第一个ggml模型
+    fn find_ggml_model() -> Option<PathBuf> {
+        let dir = crate::paths::data_paths().config_dir().join("models");
+        let mut models: Vec<PathBuf> = std::fs::read_dir(dir).ok()?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.file_name().and_then(|n| n.to_str())
+                .is_some_and(|name| name.starts_with("ggml-") && name.ends_with(".bin")))
+            .collect();
+        models.sort();
+        models.into_iter().next()
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Whisper => "whisper",
+            Self::WhisperCpp { .. } => "whisper.cpp",
+            Self::Mock => "模拟识别",
+        }
+    }
 }
 
 impl ASREngine {
     /// 创建ASR引擎
     pub fn new(config: &VoiceConfig) -> AppResult<Self> {
-        let model_path = Self::find_whisper_model()?;
+        let backend = ASRBackend::detect();
+        info!("语音识别后端: {}", backend.name());
         
         Ok(Self {
             config: config.clone(),
-            model_path,
+            backend,
         })
     }
     
+    /// 当前使用的识别后端
+    pub fn backend(&self) -> &ASRBackend {
+        &self.backend
+    }
+    
     /// 初始化ASR引擎
     pub async fn initialize(&mut self) -> AppResult<()> {
         // 检查模型可用性
@@ -48,16 +103,9 @@ impl ASREngine {
         Ok(text)
     }
     
-    /// 查找Whisper模型
-    fn find_whisper_model() -> AppResult<Option<PathBuf>> {
-        // 简化实现：返回None表示使用在线服务
-        // 实际实现中可以检查本地Whisper模型文件
-        Ok(None)
-    }
-    
     /// 保存临时音频文件
     async fn save_temp_audio(&self, audio_data: &[u8]) -> AppResult<PathBuf> {
-        let temp_dir = std::env::temp_dir();
+        let temp_dir = crate::paths::data_paths().temp_dir();
         let temp_path = temp_dir.join(format!("mindwolf_audio_{}.wav", Utc::now().timestamp()));
         
         fs::write(&temp_path, audio_data).await
@@ -68,24 +116,38 @@ impl ASREngine {
     
     /// 使用Whisper进行语音识别
     async fn whisper_recognize(&self, audio_path: &PathBuf) -> AppResult<String> {
-        if self.model_path.is_some() {
-            // 使用本地Whisper模型
-            self.local_whisper_recognize(audio_path).await
-        } else {
-            // 使用简化的识别逻辑（演示用）
-            self.mock_recognize(audio_path).await
+        match &self.backend {
+            ASRBackend::Mock => self.mock_recognize(audio_path).await,
+            backend => self.local_whisper_recognize(backend, audio_path).await,
         }
     }
     
     /// 本地Whisper识别
-    async fn local_whisper_recognize(&self, audio_path: &PathBuf) -> AppResult<String> {
-        let output = Command::new("whisper")
-            .arg(audio_path)
-            .arg("--language")
-            .arg(&self.config.language)
-            .arg("--output_format")
-            .arg("txt")
+    async fn local_whisper_recognize(&self, backend: &ASRBackend, audio_path: &PathBuf) -> AppResult<String> {
+        // whisper只认语言代码的前半部分，如zh-CN取zh
+        let language = self.config.language.split('-').next().unwrap_or("zh");
+        let mut command = match backend {
+            ASRBackend::WhisperCpp { program, model } => {
+                let mut command = Command::new(program);
+                command.arg("-m").arg(model)
+                    .arg("-l").arg(language)
+                    .arg("--no-timestamps")
+                    .arg("--no-prints")
+                    .arg("-f").arg(audio_path);
+                command
+            }
+            _ => {
+                let mut command = Command::new("whisper");
+                command.arg(audio_path)
+                    .arg("--language").arg(language)
+                    .arg("--output_format").arg("txt");
+                command
+            }
+        };
+        let output = command
+            .kill_on_drop(true)
             .output()
+            .await
             .map_err(|e| AppError::Io(format!("执行Whisper失败: {}", e)))?;
         
         if output.status.success() {
@@ -123,7 +185,7 @@ impl ASREngine {
     /// 检查ASR可用性
     pub fn is_available(&self) -> bool {
         // 检查是否有可用的识别方法
-        self.model_path.is_some() || self.has_online_service()
+        self.backend != ASRBackend::Mock || self.has_online_service()
     }
     
     /// 检查是否有在线服务
@@ -1,6 +1,7 @@
 use crate::error::AppResult;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +16,127 @@ pub struct AudioSettings {
     pub volume: f32,
     pub noise_reduction: bool,
     pub auto_gain_control: bool,
+    #[serde(default)]
+    pub ducking: DuckingSettings,
+}
+
+/// 真人说话时压低TTS与音效的设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckingSettings {
+    pub enabled: bool,
+    /// 压低后的音量倍数（0~1）
+    pub duck_level: f32,
+    /// 压低音量的渐变时长
+    pub attack_ms: u32,
+    /// 恢复音量的渐变时长
+    pub release_ms: u32,
+    /// 麦克风RMS超过该值视为在说话
+    pub vad_threshold: f32,
+    /// 说话停止后保持压低的时长，避免句间停顿时音量来回跳
+    pub hold_ms: u32,
+}
+
+impl Default for DuckingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duck_level: 0.25,
+            attack_ms: 80,
+            release_ms: 400,
+            vad_threshold: 0.02,
+            hold_ms: 300,
+        }
+    }
+}
+
+/// 闪避状态：跟踪真人是否在说话，并计算当前的播放增益
+#[derive(Debug, Clone)]
+pub struct Ducker {
+    settings: DuckingSettings,
+    voice_active: bool,
+    push_to_talk: bool,
+    last_voice_at: Option<Instant>,
+    /// 当前渐变的起点增益、起始时刻与目标增益
+    ramp_from: f32,
+    ramp_start: Instant,
+    target: f32,
+}
+
+impl Ducker {
+    pub fn new(settings: DuckingSettings, now: Instant) -> Self {
+        Self {
+            settings,
+            voice_active: false,
+            push_to_talk: false,
+            last_voice_at: None,
+            ramp_from: 1.0,
+            ramp_start: now,
+            target: 1.0,
+        }
+    }
+
+    pub fn set_settings(&mut self, settings: DuckingSettings, now: Instant) {
+        self.settings = settings;
+        self.retarget(now);
+    }
+
+    /// 麦克风一帧的语音活动检测
+    pub fn on_input_frame(&mut self, frame: &[f32], now: Instant) {
+        if frame.is_empty() {
+            return;
+        }
+        let rms = (frame.iter().map(|&x| x * x).sum::<f32>() / frame.len() as f32).sqrt();
+        self.voice_active = rms >= self.settings.vad_threshold;
+        if self.voice_active {
+            self.last_voice_at = Some(now);
+        }
+        self.retarget(now);
+    }
+
+    /// 按住说话键
+    pub fn set_push_to_talk(&mut self, held: bool, now: Instant) {
+        self.push_to_talk = held;
+        if held {
+            self.last_voice_at = Some(now);
+        }
+        self.retarget(now);
+    }
+
+    /// 真人是否在说话（含说话停止后的保持期）
+    pub fn is_ducking(&self, now: Instant) -> bool {
+        if !self.settings.enabled {
+            return false;
+        }
+        let held = self.last_voice_at
+            .is_some_and(|at| now.duration_since(at) < Duration::from_millis(self.settings.hold_ms as u64));
+        self.voice_active || self.push_to_talk || held
+    }
+
+    /// 当前的播放增益，在渐变时长内线性过渡到目标值
+    pub fn gain(&mut self, now: Instant) -> f32 {
+        self.retarget(now);
+        self.gain_at(now)
+    }
+
+    /// 说话状态变化时从当前增益开始新的渐变
+    fn retarget(&mut self, now: Instant) {
+        let target = if self.is_ducking(now) { self.settings.duck_level.clamp(0.0, 1.0) } else { 1.0 };
+        if (target - self.target).abs() > f32::EPSILON {
+            let current = self.gain_at(now);
+            self.ramp_from = current;
+            self.ramp_start = now;
+            self.target = target;
+        }
+    }
+
+    fn gain_at(&self, now: Instant) -> f32 {
+        let ramp_ms = if self.target < self.ramp_from { self.settings.attack_ms } else { self.settings.release_ms };
+        if ramp_ms == 0 {
+            return self.target;
+        }
+        let progress = (now.duration_since(self.ramp_start).as_secs_f32() * 1000.0 / ramp_ms as f32).min(1.0);
+        self.ramp_from + (self.target - self.ramp_from) * progress
+    }
 }
 
 impl Default for AudioSettings {
@@ -29,6 +151,7 @@ impl Default for AudioSettings {
             volume: 1.0,
             noise_reduction: true,
             auto_gain_control: true,
+            ducking: DuckingSettings::default(),
         }
     }
 }
@@ -49,6 +172,7 @@ pub struct AudioManager {
     is_playing: Arc<Mutex<bool>>,
     devices: Arc<Mutex<Vec<AudioDevice>>>,
     callbacks: Arc<Mutex<HashMap<String, Box<dyn Fn(Vec<f32>) + Send + Sync>>>>,
+    ducker: Arc<Mutex<Ducker>>,
 }
 
 impl AudioManager {
@@ -59,6 +183,7 @@ impl AudioManager {
             is_playing: Arc::new(Mutex::new(false)),
             devices: Arc::new(Mutex::new(Vec::new())),
             callbacks: Arc::new(Mutex::new(HashMap::new())),
+            ducker: Arc::new(Mutex::new(Ducker::new(DuckingSettings::default(), Instant::now()))),
         }
     }
 
@@ -169,7 +294,7 @@ impl AudioManager {
         }
         
         let settings = self.settings.lock().await;
-        log::info!("开始播放音频，设备: {:?}", settings.output_device);
+        log::info!("开始播放音频，设备: {:?}，音量: {:.2}", settings.output_device, settings.volume * self.ducker.lock().await.gain(Instant::now()));
         
         *is_playing = true;
         
@@ -187,6 +312,7 @@ impl AudioManager {
     /// 设置音频参数
     pub async fn set_settings(&self, new_settings: AudioSettings) -> AppResult<()> {
         let mut settings = self.settings.lock().await;
+        self.ducker.lock().await.set_settings(new_settings.ducking.clone(), Instant::now());
         *settings = new_settings;
         log::info!("音频设置已更新");
         Ok(())
@@ -246,6 +372,22 @@ impl AudioManager {
         Ok(settings.volume)
     }
 
+    /// 麦克风输入的一帧，做语音活动检测，真人说话时压低播放音量
+    pub async fn process_input_frame(&self, frame: &[f32]) {
+        self.ducker.lock().await.on_input_frame(frame, Instant::now());
+    }
+
+    /// 按住/松开说话键
+    pub async fn set_push_to_talk(&self, held: bool) {
+        self.ducker.lock().await.set_push_to_talk(held, Instant::now());
+    }
+
+    /// TTS与音效实际使用的播放音量（输出音量乘以闪避增益）
+    pub async fn playback_volume(&self) -> f32 {
+        let volume = self.settings.lock().await.volume;
+        volume * self.ducker.lock().await.gain(Instant::now())
+    }
+
     /// 启用/禁用噪音抑制
     pub async fn set_noise_reduction(&self, enabled: bool) -> AppResult<()> {
         let mut settings = self.settings.lock().await;
@@ -444,6 +586,32 @@ mod tests {
         assert_eq!(retrieved_settings.volume, 0.8);
     }
 
+    #[test]
+    fn test_ducking_ramps_down_and_restores() {
+        let start = Instant::now();
+        let ms = |n: u64| start + Duration::from_millis(n);
+        let mut ducker = Ducker::new(DuckingSettings::default(), start);
+        assert_eq!(ducker.gain(start), 1.0);
+
+        ducker.on_input_frame(&[0.3, -0.3, 0.3, -0.3], start);
+        assert!(ducker.gain(ms(40)) < 1.0 && ducker.gain(ms(40)) > 0.25);
+        assert!((ducker.gain(ms(80)) - 0.25).abs() < 1e-3);
+
+        // 说话停止后先保持，再渐变恢复
+        ducker.on_input_frame(&[0.0; 4], ms(100));
+        assert!((ducker.gain(ms(300)) - 0.25).abs() < 1e-3);
+        assert!(ducker.gain(ms(600)) > 0.25);
+        assert!((ducker.gain(ms(900)) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_push_to_talk_ducks_without_voice() {
+        let start = Instant::now();
+        let mut ducker = Ducker::new(DuckingSettings { attack_ms: 0, ..DuckingSettings::default() }, start);
+        ducker.set_push_to_talk(true, start);
+        assert_eq!(ducker.gain(start), 0.25);
+    }
+
     #[test]
     fn test_noise_reduction() {
         let manager = AudioManager::new();
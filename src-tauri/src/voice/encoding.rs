@@ -0,0 +1,294 @@
+use crate::error::{AppError, AppResult};
+use crate::voice::AudioFormat;
+use mp3lame_encoder::{Bitrate, Builder, FlushNoGap, InterleavedPcm, MonoPcm, Quality};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use log::{info, warn};
+
+/// 对局录音目录（数据目录下）
+pub const RECORDINGS_DIR: &str = "recordings";
+/// TTS缓存目录（数据目录下）
+pub const TTS_CACHE_DIR: &str = "tts_cache";
+
+/// LAME支持的码率（kbps）
+const MP3_BITRATES: [(u32, Bitrate); 16] = [
+    (8, Bitrate::Kbps8),
+    (16, Bitrate::Kbps16),
+    (24, Bitrate::Kbps24),
+    (32, Bitrate::Kbps32),
+    (40, Bitrate::Kbps40),
+    (48, Bitrate::Kbps48),
+    (64, Bitrate::Kbps64),
+    (80, Bitrate::Kbps80),
+    (96, Bitrate::Kbps96),
+    (112, Bitrate::Kbps112),
+    (128, Bitrate::Kbps128),
+    (160, Bitrate::Kbps160),
+    (192, Bitrate::Kbps192),
+    (224, Bitrate::Kbps224),
+    (256, Bitrate::Kbps256),
+    (320, Bitrate::Kbps320),
+];
+
+/// 保存和导出音频使用的编码
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioCodec {
+    /// 不压缩，保留原始WAV
+    Wav,
+    #[default]
+    Mp3,
+}
+
+/// 音频编码配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodingConfig {
+    pub codec: AudioCodec,
+    /// 码率（kbps），取不到时使用最接近的LAME码率
+    pub bitrate_kbps: u32,
+}
+
+impl Default for EncodingConfig {
+    fn default() -> Self {
+        Self {
+            codec: AudioCodec::Mp3,
+            bitrate_kbps: 64,
+        }
+    }
+}
+
+/// 编码后的音频
+pub struct EncodedAudio {
+    pub data: Vec<u8>,
+    pub format: AudioFormat,
+}
+
+/// 转码结果统计
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TranscodeReport {
+    pub converted: usize,
+    pub failed: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// 16位PCM的WAV音频
+struct WavPcm {
+    channels: u16,
+    sample_rate: u32,
+    samples: Vec<i16>,
+}
+
+impl WavPcm {
+    /// 解析WAV，只支持16位PCM
+    fn parse(data: &[u8]) -> AppResult<Self> {
+        if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+            return Err(AppError::Io("不是WAV音频".to_string()));
+        }
+
+        let mut format: Option<(u16, u32)> = None;
+        let mut offset = 12;
+        while offset + 8 <= data.len() {
+            let id = &data[offset..offset + 4];
+            let size = u32::from_le_bytes([data[offset + 4], data[offset + 5], data[offset + 6], data[offset + 7]]) as usize;
+            let body = &data[offset + 8..(offset + 8 + size).min(data.len())];
+
+            if id == b"fmt " && body.len() >= 16 {
+                let audio_format = u16::from_le_bytes([body[0], body[1]]);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                if audio_format != 1 || bits != 16 {
+                    return Err(AppError::Io(format!("不支持的WAV编码（格式{}，{}位）", audio_format, bits)));
+                }
+                let channels = u16::from_le_bytes([body[2], body[3]]);
+                let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                format = Some((channels, sample_rate));
+            } else if id == b"data" {
+                let (channels, sample_rate) = format
+                    .ok_or_else(|| AppError::Io("WAV缺少格式信息".to_string()))?;
+                let samples = body.chunks_exact(2)
+                    .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                    .collect();
+                return Ok(Self { channels, sample_rate, samples });
+            }
+            // 块长度为奇数时有一个填充字节
+            offset += 8 + size + size % 2;
+        }
+        Err(AppError::Io("WAV缺少音频数据".to_string()))
+    }
+}
+
+/// 是否为WAV音频
+pub fn is_wav(data: &[u8]) -> bool {
+    data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WAVE"
+}
+
+/// 按配置编码WAV音频；非WAV输入（如已是MP3）原样返回
+pub fn encode(data: Vec<u8>, config: &EncodingConfig) -> AppResult<EncodedAudio> {
+    if config.codec == AudioCodec::Wav || !is_wav(&data) {
+        let format = if is_wav(&data) { AudioFormat::Wav } else { AudioFormat::Mp3 };
+        return Ok(EncodedAudio { data, format });
+    }
+    Ok(EncodedAudio {
+        data: encode_mp3(&WavPcm::parse(&data)?, config.bitrate_kbps)?,
+        format: AudioFormat::Mp3,
+    })
+}
+
+fn encode_mp3(pcm: &WavPcm, bitrate_kbps: u32) -> AppResult<Vec<u8>> {
+    let encoder_error = |e: &dyn std::fmt::Debug| AppError::Io(format!("MP3编码失败: {:?}", e));
+
+    let mut builder = Builder::new()
+        .ok_or_else(|| AppError::Io("无法创建MP3编码器".to_string()))?;
+    builder.set_num_channels(pcm.channels as u8).map_err(|e| encoder_error(&e))?;
+    builder.set_sample_rate(pcm.sample_rate).map_err(|e| encoder_error(&e))?;
+    builder.set_brate(nearest_bitrate(bitrate_kbps)).map_err(|e| encoder_error(&e))?;
+    builder.set_quality(Quality::Good).map_err(|e| encoder_error(&e))?;
+    let mut encoder = builder.build().map_err(|e| encoder_error(&e))?;
+
+    let frames = pcm.samples.len() / pcm.channels.max(1) as usize;
+    let mut output = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(frames));
+    if pcm.channels == 1 {
+        encoder.encode_to_vec(MonoPcm(&pcm.samples), &mut output)
+    } else {
+        encoder.encode_to_vec(InterleavedPcm(&pcm.samples), &mut output)
+    }.map_err(|e| encoder_error(&e))?;
+    encoder.flush_to_vec::<FlushNoGap>(&mut output).map_err(|e| encoder_error(&e))?;
+    Ok(output)
+}
+
+/// 最接近配置值的LAME码率
+fn nearest_bitrate(kbps: u32) -> Bitrate {
+    MP3_BITRATES.iter()
+        .min_by_key(|(rate, _)| rate.abs_diff(kbps))
+        .map(|(_, bitrate)| *bitrate)
+        .unwrap_or(Bitrate::Kbps64)
+}
+
+/// 把数据目录下录音和TTS缓存中的WAV转为配置的编码，成功后删除原文件
+pub async fn transcode_data_dir(data_dir: &Path, config: &EncodingConfig) -> AppResult<TranscodeReport> {
+    let mut report = TranscodeReport::default();
+    if config.codec == AudioCodec::Wav {
+        return Ok(report);
+    }
+
+    for dir in [RECORDINGS_DIR, TTS_CACHE_DIR] {
+        let dir = data_dir.join(dir);
+        if !dir.exists() {
+            continue;
+        }
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("wav") {
+                continue;
+            }
+            match transcode_file(&path, config).await {
+                Ok((before, after)) => {
+                    report.converted += 1;
+                    report.bytes_before += before;
+                    report.bytes_after += after;
+                }
+                Err(e) => {
+                    warn!("转码{:?}失败: {}", path, e);
+                    report.failed += 1;
+                }
+            }
+        }
+    }
+
+    info!("音频转码完成: {}个成功，{}个失败，{} -> {}字节",
+        report.converted, report.failed, report.bytes_before, report.bytes_after);
+    Ok(report)
+}
+
+async fn transcode_file(path: &Path, config: &EncodingConfig) -> AppResult<(u64, u64)> {
+    let data = tokio::fs::read(path).await?;
+    let before = data.len() as u64;
+    let encoded = tokio::task::spawn_blocking({
+        let config = config.clone();
+        move || encode(data, &config)
+    }).await.map_err(|e| AppError::Unknown(e.to_string()))??;
+
+    let extension = match encoded.format {
+        AudioFormat::Mp3 => "mp3",
+        AudioFormat::Ogg => "ogg",
+        // 没有转码，原文件保留
+        AudioFormat::Wav => return Ok((before, before)),
+    };
+    let output = path.with_extension(extension);
+    tokio::fs::write(&output, &encoded.data).await?;
+    tokio::fs::remove_file(path).await?;
+    Ok((before, encoded.data.len() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(channels: u16, samples: &[i16]) -> Vec<u8> {
+        let data_len = (samples.len() * 2) as u32;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(&(16000 * 2 * channels as u32).to_le_bytes());
+        wav.extend_from_slice(&(2 * channels).to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav
+    }
+
+    #[test]
+    fn test_parse_wav_reads_format_and_samples() {
+        let pcm = WavPcm::parse(&wav(2, &[1, -1, 300, -300])).unwrap();
+        assert_eq!(pcm.channels, 2);
+        assert_eq!(pcm.sample_rate, 16000);
+        assert_eq!(pcm.samples, vec![1, -1, 300, -300]);
+    }
+
+    #[test]
+    fn test_mp3_is_smaller_than_wav() {
+        let samples: Vec<i16> = (0..16000)
+            .map(|i| ((i as f32 * 0.05).sin() * 8000.0) as i16)
+            .collect();
+        let source = wav(1, &samples);
+        let encoded = encode(source.clone(), &EncodingConfig::default()).unwrap();
+        assert!(matches!(encoded.format, AudioFormat::Mp3));
+        assert!(!encoded.data.is_empty());
+        // 16kHz单声道PCM是256kbps，64kbps的MP3约为四分之一，再算上帧头和编码器补齐
+        assert!(encoded.data.len() < source.len() / 3);
+    }
+
+    #[tokio::test]
+    async fn test_transcode_replaces_wav_files_with_mp3() {
+        let data_dir = std::env::temp_dir().join(format!("mindwolf_transcode_{}", crate::utils::generate_id()));
+        let recordings = data_dir.join(RECORDINGS_DIR);
+        tokio::fs::create_dir_all(&recordings).await.unwrap();
+        let samples: Vec<i16> = (0..1600).map(|i| (i % 200) as i16 * 40).collect();
+        tokio::fs::write(recordings.join("speech.wav"), wav(1, &samples)).await.unwrap();
+        tokio::fs::write(recordings.join("notes.txt"), "不是音频").await.unwrap();
+
+        let report = transcode_data_dir(&data_dir, &EncodingConfig::default()).await.unwrap();
+        assert_eq!((report.converted, report.failed), (1, 0));
+        assert!(report.bytes_after < report.bytes_before);
+        assert!(recordings.join("speech.mp3").exists());
+        assert!(!recordings.join("speech.wav").exists());
+        assert!(recordings.join("notes.txt").exists());
+
+        tokio::fs::remove_dir_all(&data_dir).await.unwrap();
+    }
+
+    #[test]
+    fn test_nearest_bitrate() {
+        assert!(matches!(nearest_bitrate(70), Bitrate::Kbps64));
+        assert!(matches!(nearest_bitrate(1000), Bitrate::Kbps320));
+    }
+}
@@ -1,14 +1,20 @@
 ﻿pub mod asr;
 pub mod tts;
 pub mod audio;
+pub mod subtitles;
+pub mod encoding;
 
 pub use asr::*;
 pub use tts::*;
 pub use audio::*;
+pub use subtitles::*;
+pub use encoding::*;
 
+use crate::cancellation;
 use crate::error::AppResult;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use serde::{Serialize, Deserialize};
 
 /// 语音配置
@@ -20,6 +26,8 @@ pub struct VoiceConfig {
     pub sample_rate: u32,
     pub channels: u16,
     pub chunk_duration_ms: u32,
+    #[serde(default)]
+    pub encoding: EncodingConfig,
 }
 
 impl Default for VoiceConfig {
@@ -31,6 +39,7 @@ impl Default for VoiceConfig {
             sample_rate: 16000,
             channels: 1,
             chunk_duration_ms: 1000,
+            encoding: EncodingConfig::default(),
         }
     }
 }
@@ -108,6 +117,27 @@ impl VoiceManager {
         self.tts_engine.lock().await.synthesize(text).await
     }
     
+    /// 文本转语音，附带逐词时间轴
+    pub async fn text_to_speech_with_timing(&self, text: &str) -> AppResult<TTSResult> {
+        if !self.config.enable_tts {
+            return Err(crate::error::AppError::Config("语音合成未启用".to_string()));
+        }
+        
+        self.tts_engine.lock().await.synthesize_with_timing(text).await
+    }
+    
+    /// 依次合成一批朗读内容；取消后正在合成的一条立即中止，剩下的不再合成
+    pub async fn text_to_speech_queue(&self, texts: &[String], token: &CancellationToken) -> AppResult<Vec<TTSResult>> {
+        let mut results = Vec::with_capacity(texts.len());
+        for text in texts {
+            if token.is_cancelled() {
+                break;
+            }
+            results.push(cancellation::run_cancellable(token, self.text_to_speech_with_timing(text)).await?);
+        }
+        Ok(results)
+    }
+    
     /// 播放语音
     pub async fn play_audio(&self, audio_data: &[u8]) -> AppResult<()> {
         self.audio_manager.play_audio(audio_data.to_vec()).await
@@ -0,0 +1,134 @@
+use crate::ai::lexicon;
+use serde::{Deserialize, Serialize};
+
+/// 每个汉字的朗读时长（毫秒，1倍语速）
+const CJK_CHAR_MS: f32 = 230.0;
+/// 英文单词的基础时长与每个字母的附加时长
+const LATIN_WORD_MS: f32 = 120.0;
+const LATIN_LETTER_MS: f32 = 45.0;
+/// 逗号类与句末标点的停顿
+const CLAUSE_PAUSE_MS: f32 = 250.0;
+const SENTENCE_PAUSE_MS: f32 = 450.0;
+
+/// 朗读中一个词的起止时间，前端据此逐词高亮
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WordTiming {
+    pub word: String,
+    pub start_ms: u32,
+    pub end_ms: u32,
+}
+
+/// 解析edge-tts输出的字幕（WebVTT或SRT）
+///
+/// 新版edge-tts会把多个词合成一条字幕，这种情况下按字数把这条字幕的时长分给其中的词
+pub fn parse_subtitles(content: &str) -> Vec<WordTiming> {
+    let mut words = Vec::new();
+    let mut lines = content.lines().map(str::trim);
+
+    while let Some(line) = lines.next() {
+        let (start, end) = match parse_cue_range(line) {
+            Some(range) => range,
+            None => continue,
+        };
+        let text: Vec<&str> = lines.by_ref().take_while(|l| !l.is_empty()).collect();
+        let tokens: Vec<String> = lexicon::segment(&text.join(" "))
+            .into_iter()
+            .filter(|token| !is_punctuation(token))
+            .collect();
+        let total: usize = tokens.iter().map(|t| lexicon::char_len(t)).sum();
+        if total == 0 {
+            continue;
+        }
+
+        let span = end.saturating_sub(start) as f32;
+        let mut consumed = 0;
+        for token in tokens {
+            let token_start = start + (span * consumed as f32 / total as f32) as u32;
+            consumed += lexicon::char_len(&token);
+            let token_end = start + (span * consumed as f32 / total as f32) as u32;
+            words.push(WordTiming { word: token, start_ms: token_start, end_ms: token_end });
+        }
+    }
+    words
+}
+
+/// 没有字幕时按字数和标点估算每个词的朗读时间
+pub fn estimate_word_timings(text: &str, speech_rate: f32) -> Vec<WordTiming> {
+    let rate = if speech_rate > 0.0 { speech_rate } else { 1.0 };
+    let mut words = Vec::new();
+    let mut cursor = 0.0f32;
+
+    for token in lexicon::segment(text) {
+        if is_punctuation(&token) {
+            let pause = if token.chars().any(|c| "。！？!?.…".contains(c)) { SENTENCE_PAUSE_MS } else { CLAUSE_PAUSE_MS };
+            cursor += pause / rate;
+            continue;
+        }
+
+        let duration = if token.is_ascii() {
+            LATIN_WORD_MS + LATIN_LETTER_MS * token.len() as f32
+        } else {
+            CJK_CHAR_MS * lexicon::char_len(&token) as f32
+        } / rate;
+        words.push(WordTiming {
+            word: token,
+            start_ms: cursor as u32,
+            end_ms: (cursor + duration) as u32,
+        });
+        cursor += duration;
+    }
+    words
+}
+
+/// 解析“00:00:01.250 --> 00:00:01.900”形式的时间轴（SRT用逗号分隔毫秒）
+fn parse_cue_range(line: &str) -> Option<(u32, u32)> {
+    let (start, end) = line.split_once("-->")?;
+    Some((parse_timestamp(start.trim())?, parse_timestamp(end.split_whitespace().next()?)?))
+}
+
+fn parse_timestamp(value: &str) -> Option<u32> {
+    let value = value.replace(',', ".");
+    let (clock, millis) = value.split_once('.').unwrap_or((value.as_str(), "0"));
+    let seconds = clock.split(':')
+        .try_fold(0u32, |acc, part| part.parse::<u32>().ok().map(|n| acc * 60 + n))?;
+    let millis: u32 = format!("{:0<3}", millis).get(..3)?.parse().ok()?;
+    Some(seconds * 1000 + millis)
+}
+
+fn is_punctuation(token: &str) -> bool {
+    token.chars().all(|c| c.is_ascii_punctuation() || "，。！？、；：“”‘’（）…—".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_word_level_vtt() {
+        let vtt = "WEBVTT\n\n00:00:00.100 --> 00:00:00.500\nTrust\n\n00:00:00.500 --> 00:00:01.200\nme\n";
+        let words = parse_subtitles(vtt);
+        assert_eq!(words, vec![
+            WordTiming { word: "Trust".to_string(), start_ms: 100, end_ms: 500 },
+            WordTiming { word: "me".to_string(), start_ms: 500, end_ms: 1200 },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_grouped_srt_splits_by_length() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nhello world\n";
+        let words = parse_subtitles(srt);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].start_ms, 1000);
+        assert_eq!(words[0].end_ms, 1500);
+        assert_eq!(words[1].end_ms, 2000);
+    }
+
+    #[test]
+    fn test_estimate_is_monotonic_and_scales_with_rate() {
+        let normal = estimate_word_timings("我觉得3号是狼，大家跟我投。", 1.0);
+        let fast = estimate_word_timings("我觉得3号是狼，大家跟我投。", 2.0);
+        assert!(!normal.is_empty());
+        assert!(normal.windows(2).all(|w| w[0].end_ms <= w[1].start_ms));
+        assert!(fast.last().unwrap().end_ms < normal.last().unwrap().end_ms);
+    }
+}
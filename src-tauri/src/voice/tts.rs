@@ -1,6 +1,7 @@
 use crate::error::{AppError, AppResult};
-use crate::voice::VoiceConfig;
-use std::process::Command;
+use crate::platform;
+use crate::voice::{encoding, estimate_word_timings, parse_subtitles, VoiceConfig, WordTiming};
+use tokio::process::Command;
 use tokio::fs;
 use log::{info, debug};
 use std::path::PathBuf;
@@ -11,6 +12,63 @@ use chrono::Utc;
 pub struct TTSEngine {
     config: VoiceConfig,
     voice_config: TTSVoiceConfig,
+    backend: TTSBackend,
+}
+
+/// 语音合成后端，按平台检测
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TTSBackend {
+    /// edge-tts命令行，各平台通用，需要联网
+    EdgeTts,
+    /// macOS自带的say
+    MacSay,
+    /// Linux常见的espeak-ng
+    EspeakNg,
+    /// Windows自带的SAPI，通过PowerShell调用
+    WindowsSapi,
+    /// 模拟合成，只返回空音频
+    Mock,
+}
+
+impl TTSBackend {
+    /// 优先edge-tts，找不到时用系统自带的合成程序
+    pub fn detect(prefer_edge_tts: bool) -> Self {
+        if prefer_edge_tts && platform::find_executable("edge-tts").is_some() {
+            return Self::EdgeTts;
+        }
+        Self::native().unwrap_or(Self::Mock)
+    }
+    
+    #[cfg(target_os = "macos")]
+    fn native() -> Option<Self> {
+        platform::find_executable("say").map(|_| Self::MacSay)
+    }
+    
+    #[cfg(target_os = "linux")]
+    fn native() -> Option<Self> {
+        platform::find_executable("espeak-ng").map(|_| Self::EspeakNg)
+    }
+    
+    #[cfg(windows)]
+    fn native() -> Option<Self> {
+        platform::find_executable("powershell").map(|_| Self::WindowsSapi)
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+    fn native() -> Option<Self> {
+        None
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::EdgeTts => "edge-tts",
+            Self::MacSay => "say",
+            Self::EspeakNg => "espeak-ng",
+            Self::WindowsSapi => "Windows SAPI",
+            Self::Mock => "模拟合成",
+        }
+    }
 }
 
 /// TTS语音配置
@@ -38,12 +96,22 @@ impl Default for TTSVoiceConfig {
 impl TTSEngine {
     /// 创建TTS引擎
     pub fn new(config: &VoiceConfig) -> AppResult<Self> {
+        let voice_config = TTSVoiceConfig::default();
+        let backend = TTSBackend::detect(voice_config.use_edge_tts);
+        info!("语音合成后端: {}", backend.name());
+        
         Ok(Self {
             config: config.clone(),
-            voice_config: TTSVoiceConfig::default(),
+            voice_config,
+            backend,
         })
     }
     
+    /// 当前使用的合成后端
+    pub fn backend(&self) -> TTSBackend {
+        self.backend
+    }
+    
     /// 初始化TTS引擎
     pub async fn initialize(&mut self) -> AppResult<()> {
         // 检查TTS可用性
@@ -57,19 +125,43 @@ impl TTSEngine {
     
     /// 语音合成
     pub async fn synthesize(&self, text: &str) -> AppResult<Vec<u8>> {
-        if self.voice_config.use_edge_tts {
-            self.edge_tts_synthesize(text).await
+        Ok(self.synthesize_with_timing(text).await?.audio_data)
+    }
+    
+    /// 语音合成，同时返回逐词时间轴
+    ///
+    /// Edge TTS优先用它输出的字幕；字幕缺失或模拟合成时按字数估算
+    pub async fn synthesize_with_timing(&self, text: &str) -> AppResult<TTSResult> {
+        let (audio_data, words) = match self.backend {
+            TTSBackend::EdgeTts => self.edge_tts_synthesize(text).await?,
+            TTSBackend::Mock => (self.mock_synthesize(text).await?, Vec::new()),
+            _ => (self.native_synthesize(text).await?, Vec::new()),
+        };
+        let words = if words.is_empty() {
+            estimate_word_timings(text, self.voice_config.speed)
         } else {
-            self.mock_synthesize(text).await
-        }
+            words
+        };
+        
+        // 合成结果会被缓存和导出，按配置压缩
+        let encoded = encoding::encode(audio_data, &self.config.encoding)?;
+        
+        Ok(TTSResult {
+            duration_ms: words.last().map(|w| w.end_ms).unwrap_or(0),
+            audio_data: encoded.data,
+            format: encoded.format,
+            words,
+        })
     }
     
-    /// 使用Edge TTS进行语音合成
-    async fn edge_tts_synthesize(&self, text: &str) -> AppResult<Vec<u8>> {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join(format!("mindwolf_tts_{}.wav", Utc::now().timestamp()));
+    /// 使用Edge TTS进行语音合成，返回音频和字幕中的逐词时间
+    async fn edge_tts_synthesize(&self, text: &str) -> AppResult<(Vec<u8>, Vec<WordTiming>)> {
+        let temp_dir = crate::paths::data_paths().temp_dir();
+        let stamp = Utc::now().timestamp_millis();
+        let output_path = temp_dir.join(format!("mindwolf_tts_{}.wav", stamp));
+        let subtitles_path = temp_dir.join(format!("mindwolf_tts_{}.vtt", stamp));
         
-        // 构建edge-tts命令
+        // 构建edge-tts命令；合成任务被取消（future被丢弃）时结束子进程
         let output = Command::new("edge-tts")
             .arg("--voice")
             .arg(&self.voice_config.voice_name)
@@ -78,8 +170,10 @@ impl TTSEngine {
             .arg("--write-media")
             .arg(&output_path)
             .arg("--write-subtitles")
-            .arg("/dev/null") // 忽略字幕文件
+            .arg(&subtitles_path)
+            .kill_on_drop(true)
             .output()
+            .await
             .map_err(|e| AppError::Io(format!("执行edge-tts失败: {}", e)))?;
         
         if output.status.success() {
@@ -87,17 +181,84 @@ impl TTSEngine {
             let audio_data = fs::read(&output_path).await
                 .map_err(|e| AppError::Io(format!("读取TTS音频文件失败: {}", e)))?;
             
+            // 字幕读取失败时由调用方估算时间轴
+            let words = fs::read_to_string(&subtitles_path).await
+                .map(|content| parse_subtitles(&content))
+                .unwrap_or_default();
+            
             // 清理临时文件
             let _ = fs::remove_file(&output_path).await;
+            let _ = fs::remove_file(&subtitles_path).await;
             
-            debug!("TTS合成成功，音频大小: {} 字节", audio_data.len());
-            Ok(audio_data)
+            debug!("TTS合成成功，音频大小: {} 字节，字幕{}个词", audio_data.len(), words.len());
+            Ok((audio_data, words))
         } else {
+            let _ = fs::remove_file(&subtitles_path).await;
             let error = String::from_utf8_lossy(&output.stderr);
             Err(AppError::Io(format!("TTS合成失败: {}", error)))
         }
     }
     
+    /// 使用系统自带的合成程序，输出WAV；没有字幕，时间轴由调用方估算
+    async fn native_synthesize(&self, text: &str) -> AppResult<Vec<u8>> {
+        let output_path = temp_wav_path();
+        let english = self.config.language.starts_with("en");
+        let speed = self.voice_config.speed.clamp(0.5, 2.0);
+        
+        let mut command = match self.backend {
+            TTSBackend::MacSay => {
+                let mut command = Command::new("say");
+                command.arg("-v").arg(if english { "Samantha" } else { "Tingting" })
+                    .arg("-r").arg(((175.0 * speed) as u32).to_string())
+                    .arg("--file-format=WAVE")
+                    .arg("--data-format=LEI16@22050")
+                    .arg("-o").arg(&output_path)
+                    .arg(text);
+                command
+            }
+            TTSBackend::EspeakNg => {
+                let mut command = Command::new("espeak-ng");
+                command.arg("-v").arg(if english { "en-us" } else { "cmn" })
+                    .arg("-s").arg(((175.0 * speed) as u32).to_string())
+                    .arg("-w").arg(&output_path)
+                    .arg(text);
+                command
+            }
+            TTSBackend::WindowsSapi => {
+                // 文本和路径通过环境变量传入，避免拼进脚本时被转义
+                let rate = ((speed - 1.0) * 10.0).round().clamp(-10.0, 10.0) as i32;
+                let script = format!(
+                    "Add-Type -AssemblyName System.Speech; \
+                     $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; $s.Rate = {}; \
+                     $s.SetOutputToWaveFile($env:MINDWOLF_TTS_OUTPUT); $s.Speak($env:MINDWOLF_TTS_TEXT); $s.Dispose()",
+                    rate
+                );
+                let mut command = Command::new("powershell");
+                command.arg("-NoProfile").arg("-NonInteractive").arg("-Command").arg(script)
+                    .env("MINDWOLF_TTS_OUTPUT", &output_path)
+                    .env("MINDWOLF_TTS_TEXT", text);
+                command
+            }
+            TTSBackend::EdgeTts | TTSBackend::Mock => {
+                return Err(AppError::Config(format!("{}不是系统合成后端", self.backend.name())));
+            }
+        };
+        
+        let output = command.kill_on_drop(true).output().await
+            .map_err(|e| AppError::Io(format!("执行{}失败: {}", self.backend.name(), e)))?;
+        if !output.status.success() {
+            let _ = fs::remove_file(&output_path).await;
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::Io(format!("{}合成失败: {}", self.backend.name(), error)));
+        }
+        
+        let audio_data = fs::read(&output_path).await
+            .map_err(|e| AppError::Io(format!("读取TTS音频文件失败: {}", e)))?;
+        let _ = fs::remove_file(&output_path).await;
+        debug!("{}合成成功，音频大小: {} 字节", self.backend.name(), audio_data.len());
+        Ok(audio_data)
+    }
+    
     /// 模拟语音合成（用于演示）
     async fn mock_synthesize(&self, text: &str) -> AppResult<Vec<u8>> {
         info!("模拟语音合成: {}", text);
@@ -128,12 +289,15 @@ impl TTSEngine {
     
     /// 设置语音参数
     pub fn set_voice_config(&mut self, config: TTSVoiceConfig) {
+        if config.use_edge_tts != self.voice_config.use_edge_tts {
+            self.backend = TTSBackend::detect(config.use_edge_tts);
+        }
         self.voice_config = config;
     }
     
     /// 获取可用的语音列表
     pub async fn get_available_voices(&self) -> AppResult<Vec<VoiceInfo>> {
-        if self.voice_config.use_edge_tts {
+        if self.backend == TTSBackend::EdgeTts {
             self.get_edge_tts_voices().await
         } else {
             Ok(self.get_mock_voices())
@@ -145,6 +309,7 @@ impl TTSEngine {
         let output = Command::new("edge-tts")
             .arg("--list-voices")
             .output()
+            .await
             .map_err(|e| AppError::Io(format!("获取Edge TTS语音列表失败: {}", e)))?;
         
         if output.status.success() {
@@ -201,25 +366,16 @@ impl TTSEngine {
         ]
     }
     
-    /// 检查TTS可用性
+    /// 检查TTS可用性：找到了真实的合成程序，或者本来就没打算用edge-tts（演示模式）
     pub fn is_available(&self) -> bool {
-        if self.voice_config.use_edge_tts {
-            self.check_edge_tts_available()
-        } else {
-            true // 模拟模式总是可用
-        }
-    }
-    
-    /// 检查Edge TTS是否可用
-    fn check_edge_tts_available(&self) -> bool {
-        Command::new("edge-tts")
-            .arg("--version")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+        self.backend != TTSBackend::Mock || !self.voice_config.use_edge_tts
     }
 }
 
+fn temp_wav_path() -> PathBuf {
+    crate::paths::data_paths().temp_dir().join(format!("mindwolf_tts_{}.wav", Utc::now().timestamp_millis()))
+}
+
 /// 语音信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceInfo {
@@ -230,11 +386,13 @@ pub struct VoiceInfo {
 }
 
 /// TTS合成结果
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TTSResult {
     pub audio_data: Vec<u8>,
     pub duration_ms: u32,
     pub format: AudioFormat,
+    /// 逐词时间轴，前端播放时据此高亮
+    pub words: Vec<WordTiming>,
 }
 
 /// 音频格式
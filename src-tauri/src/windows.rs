@@ -1,17 +1,18 @@
 #![cfg(windows)]
 
+use mindwolf_lib::paths;
 use std::io;
 
-/// Windows特定配置，确保程序完全便携
+/// Windows特定配置：便携模式下连WebView2的缓存也放进数据目录，确保程序完全便携
 pub fn configure_portable() -> Result<(), io::Error> {
-    // 设置应用程序为便携模式
-    // 确保所有数据都存储在可执行文件目录下
+    let paths = paths::data_paths();
+    if !paths.is_portable() {
+        return Ok(());
+    }
+    
+    paths.ensure_dirs()
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    // WebView2默认把用户数据写到%LOCALAPPDATA%
+    std::env::set_var("WEBVIEW2_USER_DATA_FOLDER", paths.config_dir().join("webview"));
     Ok(())
 }
-
-/// 获取便携式应用数据目录
-pub fn get_portable_data_dir() -> Result<std::path::PathBuf, io::Error> {
-    let exe_path = std::env::current_exe()?;
-    let exe_dir = exe_path.parent().unwrap_or_else(|| std::path::Path::new("."));
-    Ok(exe_dir.join("data"))
-}
\ No newline at end of file
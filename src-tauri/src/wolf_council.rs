@@ -0,0 +1,256 @@
+use crate::chat::ChannelMessage;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 狼人频道里单条商量消息的字数上限
+pub const COUNCIL_MESSAGE_MAX_CHARS: usize = 60;
+/// 模型没给出信心时的默认值
+pub const DEFAULT_NOMINATION_CONFIDENCE: f32 = 0.5;
+/// 比较加权得分时视为相等的误差
+const SCORE_EPSILON: f32 = 1e-4;
+
+/// 狼人夜间投票的计票方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NightVoteMode {
+    /// 每名狼人一票，得票最多者出局
+    #[default]
+    Majority,
+    /// 每票按提名时的信心计分，总分最高者出局
+    Weighted,
+}
+
+fn default_confidence() -> f32 {
+    DEFAULT_NOMINATION_CONFIDENCE
+}
+
+/// 狼人对本夜刀口的提议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WolfProposal {
+    pub target: String,
+    pub message: String,
+    /// 对这个刀口的信心 0.0-1.0
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+}
+
+/// 一名狼人的提名
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WolfNomination {
+    pub wolf_id: String,
+    pub target: String,
+    pub confidence: f32,
+}
+
+/// 一夜投票的结果，完整记入复盘
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NightVoteResult {
+    pub day: u32,
+    pub mode: NightVoteMode,
+    pub target: String,
+    /// 目标 -> 得分（多数模式为票数），只含存活目标
+    pub tally: BTreeMap<String, f32>,
+    pub nominations: Vec<WolfNomination>,
+    /// 平票时拍板的狼王，没有平票时为None
+    pub leader: Option<String>,
+}
+
+/// 狼队一夜的投票：每名狼人提名一个刀口，按计票方式决定本夜刀谁
+#[derive(Debug, Clone)]
+pub struct WolfCouncil {
+    pub day: u32,
+    /// AI狼是否已经在频道里表过态
+    pub held: bool,
+    /// 投票结果是否已记入复盘
+    pub recorded: bool,
+    /// 狼人ID -> 提名，同一名狼人以最后一次提名为准
+    votes: BTreeMap<String, WolfNomination>,
+}
+
+impl WolfCouncil {
+    pub fn new(day: u32) -> Self {
+        Self { day, held: false, recorded: false, votes: BTreeMap::new() }
+    }
+
+    pub fn propose(&mut self, wolf_id: &str, target: &str, confidence: f32) {
+        self.votes.insert(wolf_id.to_string(), WolfNomination {
+            wolf_id: wolf_id.to_string(),
+            target: target.to_string(),
+            confidence: confidence.clamp(0.0, 1.0),
+        });
+    }
+
+    /// 计票；平票时由狼王在平票目标里拍板
+    pub fn resolve(&self, state: &GameState, mode: NightVoteMode) -> Option<NightVoteResult> {
+        let mut tally: BTreeMap<String, f32> = BTreeMap::new();
+        for nomination in self.votes.values() {
+            if state.players.iter().any(|p| p.id == nomination.target && p.is_alive) {
+                *tally.entry(nomination.target.clone()).or_default() += match mode {
+                    NightVoteMode::Majority => 1.0,
+                    NightVoteMode::Weighted => nomination.confidence,
+                };
+            }
+        }
+        let top = tally.values().copied().fold(f32::MIN, f32::max);
+        let tied: Vec<&String> = tally.iter()
+            .filter(|(_, score)| top - **score < SCORE_EPSILON)
+            .map(|(target, _)| target)
+            .collect();
+
+        let (target, leader) = match tied.as_slice() {
+            [] => return None,
+            [only] => ((*only).clone(), None),
+            _ => {
+                let mut wolves: Vec<&Player> = state.players.iter()
+                    .filter(|p| self.votes.get(&p.id).is_some_and(|n| tied.contains(&&n.target)))
+                    .collect();
+                wolves.sort_by(|a, b| leader_rank(a).total_cmp(&leader_rank(b)).reverse().then(a.seat.cmp(&b.seat)));
+                let leader = wolves.first()?;
+                (self.votes[&leader.id].target.clone(), Some(leader.id.clone()))
+            }
+        };
+        Some(NightVoteResult {
+            day: self.day,
+            mode,
+            target,
+            tally,
+            nominations: self.votes.values().cloned().collect(),
+            leader,
+        })
+    }
+}
+
+/// 狼王顺位：真人狼优先拍板，AI狼按性格里的攻击性和逻辑性排序
+pub fn leader_rank(wolf: &Player) -> f32 {
+    match (&wolf.personality, wolf.is_ai) {
+        (_, false) => f32::MAX,
+        (Some(personality), true) => personality.traits.aggressiveness + personality.traits.logic,
+        (None, true) => 0.0,
+    }
+}
+
+/// 让AI狼在狼人频道用一两句话提出刀口
+pub fn council_prompt(wolf: &Player, state: &GameState, chat: &[ChannelMessage]) -> String {
+    let name_of = |id: &str| state.players.iter()
+        .find(|p| p.id == id)
+        .map(|p| p.display_name())
+        .unwrap_or_else(|| id.to_string());
+    let candidates: Vec<String> = state.players.iter()
+        .filter(|p| p.is_alive && !wolf.knows_teammate(p) && p.id != wolf.id)
+        .map(|p| format!("{}（{}）", p.display_name(), p.id))
+        .collect();
+    let chat: Vec<String> = chat.iter()
+        .map(|m| format!("{}：{}", name_of(&m.sender), m.content))
+        .collect();
+
+    format!(
+        "你是狼人{}，现在是第{}夜，狼队正在狼人频道商量今晚刀谁。可以刀的玩家：{}。频道里已有的讨论：\n{}\n\
+         请用不超过{}字和队友商量，说明想刀谁和理由，尽量和队友统一意见，并给出你对这个刀口的信心（0到1）。\
+         返回JSON格式：{{\"target\":\"player_id\",\"message\":\"你在频道里说的话\",\"confidence\":0.7}}",
+        wolf.display_name(),
+        state.day,
+        candidates.join("、"),
+        if chat.is_empty() { "（暂无）".to_string() } else { chat.join("\n") },
+        COUNCIL_MESSAGE_MAX_CHARS
+    )
+}
+
+/// 解析模型给出的提议，解析失败返回None
+pub fn parse_proposal(response: &str) -> Option<WolfProposal> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    let proposal: WolfProposal = serde_json::from_str(response.get(start..=end)?).ok()?;
+    let message = proposal.message.trim();
+    if proposal.target.is_empty() || message.is_empty() {
+        return None;
+    }
+    Some(WolfProposal {
+        target: proposal.target,
+        message: crate::utils::trim_to_sentence(message, COUNCIL_MESSAGE_MAX_CHARS),
+        confidence: if proposal.confidence.is_finite() { proposal.confidence.clamp(0.0, 1.0) } else { DEFAULT_NOMINATION_CONFIDENCE },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::game_engine::GameEngine;
+
+    #[test]
+    fn test_night_vote_modes_and_leader_tiebreak() {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        let state = engine.get_state();
+        let mut players: Vec<&Player> = state.players.iter().collect();
+        players.sort_by_key(|p| p.seat);
+        let (first, second, third) = (players[0], players[1], players[2]);
+        let (target_a, target_b) = (&players[3].id, &players[4].id);
+
+        let mut council = WolfCouncil::new(state.day);
+        assert!(council.resolve(state, NightVoteMode::Majority).is_none());
+
+        council.propose(&first.id, target_a, 0.9);
+        council.propose(&second.id, target_b, 0.2);
+        let tie = council.resolve(state, NightVoteMode::Majority).unwrap();
+        let leader = [first, second].into_iter()
+            .max_by(|a, b| leader_rank(a).total_cmp(&leader_rank(b)).then(b.seat.cmp(&a.seat)))
+            .unwrap();
+        assert_eq!(tie.leader.as_ref(), Some(&leader.id));
+        assert_eq!(&tie.target, if leader.id == first.id { target_a } else { target_b });
+
+        council.propose(&third.id, target_b, 0.3);
+        let majority = council.resolve(state, NightVoteMode::Majority).unwrap();
+        assert_eq!(&majority.target, target_b);
+        assert_eq!(majority.leader, None);
+        assert_eq!(majority.nominations.len(), 3);
+
+        let weighted = council.resolve(state, NightVoteMode::Weighted).unwrap();
+        assert_eq!(&weighted.target, target_a);
+        assert!((weighted.tally[target_b] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tie_is_settled_by_the_wolf_leader() {
+        let mut engine = GameEngine::new(AppConfig::default().game).unwrap();
+        engine.initialize_game().unwrap();
+        let mut state = engine.get_state().clone();
+        state.players.sort_by_key(|p| p.seat);
+        let template = state.players.iter().find_map(|p| p.personality.clone()).unwrap();
+        for (index, player) in state.players.iter_mut().enumerate() {
+            // 全部按AI狼处理，第二个座位最有话语权
+            let rank = if index == 1 { 0.9 } else { 0.1 };
+            let mut personality = template.clone();
+            personality.traits.aggressiveness = rank;
+            personality.traits.logic = rank;
+            player.personality = Some(personality);
+            player.is_ai = true;
+        }
+        let ids: Vec<String> = state.players.iter().map(|p| p.id.clone()).collect();
+
+        let mut council = WolfCouncil::new(1);
+        council.propose(&ids[0], &ids[4], 1.0);
+        council.propose(&ids[1], &ids[5], 1.0);
+        // 同一名狼人以最后一次提名为准
+        council.propose(&ids[2], &ids[5], 1.0);
+        council.propose(&ids[2], &ids[4], 1.0);
+        council.propose(&ids[3], &ids[5], 1.0);
+        let tie = council.resolve(&state, NightVoteMode::Majority).unwrap();
+        assert_eq!(tie.leader.as_ref(), Some(&ids[1]));
+        assert_eq!(tie.target, ids[5]);
+        assert_eq!(tie.nominations.len(), 4);
+
+        // 真人狼在场时由真人拍板
+        state.players[0].is_ai = false;
+        let tie = council.resolve(&state, NightVoteMode::Majority).unwrap();
+        assert_eq!(tie.leader.as_ref(), Some(&ids[0]));
+        assert_eq!(tie.target, ids[4]);
+
+        // 出局的目标不计票，不再平票
+        state.players[4].is_alive = false;
+        let result = council.resolve(&state, NightVoteMode::Majority).unwrap();
+        assert_eq!(result.target, ids[5]);
+        assert_eq!(result.leader, None);
+        assert!(!result.tally.contains_key(&ids[4]));
+    }
+}
@@ -1,5 +1,5 @@
-import { invoke } from '@tauri-apps/api/core'
-import type { LLMConfig, GameConfig, GameState } from '../types'
+import { invoke, Channel } from '@tauri-apps/api/core'
+import type { LLMConfig, GameConfig, GameState, SessionStats, StreamEvent } from '../types'
 
 // 应用配置API
 export const configAPI = {
@@ -15,10 +15,20 @@ export const configAPI = {
     return await invoke('test_llm_connection')
   },
 
+  // 本地模型服务（Ollama或llama.cpp server）已安装的模型
+  async listLocalModels(baseUrl?: string, refresh = false): Promise<{ id: string; json_mode: boolean; realtime: boolean; embedding: boolean; context_length: number | null }[]> {
+    return await invoke('list_local_models', { baseUrl, refresh })
+  },
+
   async updateGameConfig(config: GameConfig) {
     return await invoke('update_game_config', { config })
   },
 
+  // 按人数和难度生成平衡板子，结果的role_distribution可直接写回游戏配置
+  async generateBoard(totalPlayers: number, difficulty?: 'Beginner' | 'Standard' | 'Advanced') {
+    return await invoke('generate_board', { totalPlayers, difficulty })
+  },
+
   async exportConfig(): Promise<string> {
     return await invoke('export_config')
   },
@@ -38,11 +48,12 @@ export const gameAPI = {
     return await invoke('launch_game')
   },
 
+  // 只返回本机玩家能看到的信息，他人身份未公开时为空
   async getGameState(): Promise<GameState | null> {
     return await invoke('get_game_state')
   },
 
-  async playerVote(voterId: string, targetId: string) {
+  async playerVote(voterId: string, targetId: string | null) {
     return await invoke('player_vote', { voterId, targetId })
   },
 
@@ -50,10 +61,23 @@ export const gameAPI = {
     return await invoke('player_speech', { playerId, content })
   },
 
+  async getCurrentSpeaker(): Promise<{ player_id: string; index: number; total: number; awaiting_human: boolean; seconds_remaining: number | null } | null> {
+    return await invoke('get_current_speaker')
+  },
+
+  async passTurn(playerId: string) {
+    return await invoke('pass_turn', { playerId })
+  },
+
   async generateAISpeech(playerId: string): Promise<string> {
     return await invoke('generate_ai_speech', { playerId })
   },
 
+  // 状态栏用的本局实时统计
+  async getSessionStats(): Promise<SessionStats> {
+    return await invoke('get_session_stats')
+  },
+
   async endGame() {
     return await invoke('end_game')
   }
@@ -63,6 +87,23 @@ export const gameAPI = {
 export const aiAPI = {
   async generateAIResponse(prompt: string): Promise<string> {
     return await invoke('generate_ai_response', { prompt })
+  },
+
+  // 流式生成，onEvent逐段收到文本，返回流ID供cancelStream使用
+  async streamAIResponse(prompt: string, onEvent: (event: StreamEvent) => void): Promise<string> {
+    const channel = new Channel<StreamEvent>()
+    channel.onmessage = onEvent
+    return await invoke('stream_ai_response', { prompt, onEvent: channel })
+  },
+
+  // 取消流式生成，也可取消game://speech_stream事件里正在生成的AI发言
+  async cancelStream(streamId: string): Promise<boolean> {
+    return await invoke('cancel_stream', { streamId })
+  },
+
+  // 悬停玩家时展示的怀疑解释，polish为true时由模型润色
+  async explainSuspicion(observerId: string, targetId: string, polish = false): Promise<{ text: string; suspicion: number; polished: boolean }> {
+    return await invoke('explain_suspicion', { observerId, targetId, polish })
   }
 }
 
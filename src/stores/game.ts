@@ -2,6 +2,7 @@ import { defineStore } from 'pinia'
 import { ref, computed } from 'vue'
 import { GameState, GamePhase, LLMConfig, GameConfig, RoleType } from '../types'
 import { gameAPI, configAPI } from '../api'
+import { listen, UnlistenFn } from '@tauri-apps/api/event'
 import { ElMessage, ElNotification } from 'element-plus'
 
 export const useGameStore = defineStore('game', () => {
@@ -130,6 +131,7 @@ export const useGameStore = defineStore('game', () => {
       
       const newGameState = await gameAPI.startNewGame(config)
       gameState.value = newGameState
+      await subscribeGameEvents()
       gameConfig.value = config
       
       ElNotification({
@@ -188,6 +190,24 @@ export const useGameStore = defineStore('game', () => {
     }
   }
 
+  // 后端推送的游戏事件：局面变化时刷新状态，倒计时直接更新，不再轮询
+  const unlisteners: UnlistenFn[] = []
+  const subscribeGameEvents = async () => {
+    if (unlisteners.length > 0) return
+    for (const name of ['game://phase_changed', 'game://player_died', 'game://speech', 'game://vote']) {
+      unlisteners.push(await listen(name, () => refreshGameState()))
+    }
+    unlisteners.push(await listen<{ payload: { time_remaining: number } }>('game://timer_tick', (event) => {
+      if (gameState.value) {
+        gameState.value.timeRemaining = event.payload.payload.time_remaining
+      }
+    }))
+  }
+
+  const unsubscribeGameEvents = () => {
+    unlisteners.splice(0).forEach(unlisten => unlisten())
+  }
+
   // 玩家行动
   const playerVote = async (targetId: string) => {
     try {
@@ -307,6 +327,8 @@ export const useGameStore = defineStore('game', () => {
     startGame,
     endGame,
     refreshGameState,
+    subscribeGameEvents,
+    unsubscribeGameEvents,
     playerVote,
     playerSpeech,
     generateAISpeech,
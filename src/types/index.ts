@@ -2,8 +2,9 @@
 export interface Player {
   id: string;
   name: string;
-  role: Role;
-  faction: Faction;
+  // 他人身份未公开时为空
+  role?: Role;
+  faction?: Faction;
   isAlive: boolean;
   isAI: boolean;
   personality?: AIPersonality;
@@ -189,4 +190,28 @@ export interface GameResult {
   winner: Faction;
   reason: string;
   survivors: string[];
-}
\ No newline at end of file
+}
+// 流式生成的通道事件
+export type StreamEvent =
+  | { type: 'delta'; stream_id: string; text: string }
+  | { type: 'done'; stream_id: string; content: string; served_by: string }
+  | { type: 'failed'; stream_id: string; message: string }
+  | { type: 'cancelled'; stream_id: string };
+
+// 本局实时统计
+export interface SessionStats {
+  elapsed_secs: number;
+  day: number;
+  phase: GamePhase;
+  speeches: number;
+  human_speeches: number;
+  ai_speeches: number;
+  average_ai_latency_ms: number | null;
+  ai_calls: number;
+  tokens: {
+    prompt_tokens: number;
+    completion_tokens: number;
+    requests: number;
+  };
+  phases_completed: number;
+}